@@ -0,0 +1,61 @@
+// Runs the bot with a custom intent router instead of the default OpenAI one.
+//
+//   DISCORD_CLIENT_SECRET=... OPENAI_API_KEY=... cargo run --example custom_router
+use std::sync::Arc;
+
+use reminderBot::bot::ReminderBot;
+use reminderBot::service::routing::{Intent, IntentResult, IntentRouter};
+use serenity::async_trait;
+
+// Stand-in for a local classifier such as a BERT model: anything that
+// mentions a reminder keyword becomes a notification, everything else a todo.
+pub struct KeywordRouter {
+    keywords: Vec<String>,
+}
+
+impl Default for KeywordRouter {
+    fn default() -> Self {
+        Self {
+            keywords: ["remind", "alert", "ping me"]
+                .iter()
+                .map(|k| k.to_string())
+                .collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl IntentRouter for KeywordRouter {
+    async fn route(&self, text: &str) -> IntentResult {
+        let normalized_text = text.trim().to_string();
+        if normalized_text.is_empty() {
+            return IntentResult {
+                intent: Intent::Unknown,
+                normalized_text,
+                confidence: 0.0,
+            };
+        }
+        let lower = normalized_text.to_lowercase();
+        let intent = if self.keywords.iter().any(|k| lower.contains(k.as_str())) {
+            Intent::Notification
+        } else {
+            Intent::Todolist
+        };
+        IntentResult {
+            intent,
+            normalized_text,
+            confidence: 1.0,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    reminderBot::logging::init(None, None);
+    let secret = std::env::var("DISCORD_CLIENT_SECRET").expect("DISCORD_CLIENT_SECRET must be set");
+    let openai_api_key = std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY must be set");
+    ReminderBot::new(secret, openai_api_key)
+        .with_intent_router(Arc::new(KeywordRouter::default()))
+        .run()
+        .await;
+}
@@ -0,0 +1,22 @@
+// Delivers stored reminders without connecting to the Discord gateway. Only the
+// notification, todo and calendar loops run, so reminders can be created by
+// another process or through the HTTP API.
+//
+//   DISCORD_CLIENT_SECRET=... OPENAI_API_KEY=... cargo run --example headless_scheduler
+use reminderBot::bot::ReminderBot;
+use reminderBot::runtime::RunModes;
+
+#[tokio::main]
+async fn main() {
+    reminderBot::logging::init(None, None);
+    let secret = std::env::var("DISCORD_CLIENT_SECRET").expect("DISCORD_CLIENT_SECRET must be set");
+    let openai_api_key = std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY must be set");
+    ReminderBot::new(secret, openai_api_key)
+        .with_modes(RunModes {
+            gateway: false,
+            http: false,
+            worker: true,
+        })
+        .run()
+        .await;
+}
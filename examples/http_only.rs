@@ -0,0 +1,31 @@
+// Serves the REST API over the stores in DB_LOCATION without Discord or any
+// background loops.
+//
+//   cargo run --example http_only
+//   curl http://127.0.0.1:8080/notifications
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use memory_db::{DB, load_db};
+use reminderBot::models::notification::{self, Notification};
+use reminderBot::models::todo::{self, TodoItem};
+use reminderBot::runtime;
+use tokio::sync::Mutex;
+
+#[tokio::main]
+async fn main() {
+    reminderBot::logging::init(None, None);
+    let notifications: DB<Notification> =
+        load_db(&notification::get_db_location()).unwrap_or_else(|_| HashMap::new());
+    let todos: DB<TodoItem> = load_db(&todo::get_db_location()).unwrap_or_else(|_| HashMap::new());
+    let addr = std::env::var("HTTP_BIND")
+        .unwrap_or_else(|_| "127.0.0.1:8080".to_string())
+        .parse()
+        .expect("valid HTTP_BIND address");
+    runtime::run_http(
+        Arc::new(Mutex::new(notifications)),
+        Arc::new(Mutex::new(todos)),
+        addr,
+    )
+    .await;
+}
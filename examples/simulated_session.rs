@@ -0,0 +1,104 @@
+// Plays a /notify conversation through the real handler, event worker and
+// action engine, with OpenAI answered from the recorded cassette and the
+// confirm button pressed automatically. Writes the notification store under
+// DB_LOCATION (default ./data).
+//
+//   cargo run --example simulated_session
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use reminderBot::events::queue::EventBus;
+use reminderBot::events::worker::run_event_worker;
+use reminderBot::handlers::action::{Action, ActionEngine, ActionEvent, ActionStore};
+use reminderBot::handlers::discord::BotHandler;
+use reminderBot::models::notification::Notification;
+use reminderBot::models::todo::TodoItem;
+use reminderBot::service::approval_prompt::ApprovalPromptService;
+use reminderBot::service::openai_service::{
+    CassetteMode, DEFAULT_CASSETTE_PATH, OpenAIClient, OpenAIService,
+};
+use reminderBot::service::routing::OpenAIRouter;
+use serenity::async_trait;
+use tokio::sync::{Mutex, mpsc};
+
+// Prints what Discord would show and hands the action id back to the driver.
+struct PrintingApprovalPrompt {
+    prompted: mpsc::UnboundedSender<String>,
+}
+
+#[async_trait]
+impl ApprovalPromptService for PrintingApprovalPrompt {
+    async fn prompt(&self, action: &mut Action) -> Result<(), String> {
+        if let Some(draft) = action.notification_draft() {
+            println!("bot: remind you to \"{}\" at {}? [Confirm] [Cancel]", draft.content, draft.time);
+        }
+        self.prompted
+            .send(action.id.clone())
+            .map_err(|e| e.to_string())
+    }
+
+    async fn update_status(&self, _action: &Action, message: &str) -> Result<(), String> {
+        println!("bot: {}", message);
+        Ok(())
+    }
+
+    async fn update_status_message(
+        &self,
+        _channel_id: &str,
+        _user_id: &str,
+        message: &str,
+    ) -> Result<(), String> {
+        println!("bot: {}", message);
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let openai: Arc<dyn OpenAIClient> = Arc::new(
+        OpenAIService::with_cassette("unused".to_string(), CassetteMode::Replay, DEFAULT_CASSETTE_PATH)
+            .expect("cassette loads"),
+    );
+    let (prompted_tx, mut prompted_rx) = mpsc::unbounded_channel();
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(
+        Arc::new(Mutex::new(ActionStore::new())),
+        openai.clone(),
+        Arc::new(PrintingApprovalPrompt {
+            prompted: prompted_tx,
+        }),
+        notification_db.clone(),
+    );
+    let (bus, rx) = EventBus::new(8);
+    let worker = tokio::spawn(run_event_worker(rx, engine));
+    let handler = BotHandler::new(
+        Arc::new(Mutex::new(HashMap::<String, TodoItem>::new())),
+        bus.clone(),
+        Arc::new(Mutex::new(HashMap::new())),
+        Arc::new(OpenAIRouter::new(openai)),
+    );
+
+    let text = "call mom tomorrow at 5";
+    println!("user: /notify {}", text);
+    handler.handle_notify_internal(text, "@user", "123", None).await;
+
+    let action_id = prompted_rx.recv().await.expect("approval prompt shown");
+    println!("user: *clicks Confirm*");
+    bus.emit(ActionEvent::ApprovalConfirmed {
+        action_id,
+        user_id: "@user".to_string(),
+    })
+    .await
+    .expect("event should queue");
+
+    drop(handler);
+    drop(bus);
+    worker.await.expect("worker finished");
+
+    for stored in notification_db.lock().await.values() {
+        println!(
+            "stored: \"{}\" for {} in channel {}, reminders at {:?}",
+            stored.content, stored.notify.join(", "), stored.channel, stored.notification_times
+        );
+    }
+}
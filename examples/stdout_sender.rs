@@ -0,0 +1,70 @@
+// Runs the notification tick against a MessageSender that prints to stdout, with
+// an offline LLM client so the built-in fallback messages are used. Writes the
+// notification store under DB_LOCATION (default ./data).
+//
+//   cargo run --example stdout_sender
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
+use memory_db::DB;
+use reminderBot::models::notification::{self, Notification};
+use reminderBot::models::settings::UserSettings;
+use reminderBot::service::openai_service::OpenAIClient;
+use reminderBot::tasks::notification_loop::{MessageSender, notification_tick};
+use serenity::async_trait;
+
+struct StdoutSender;
+
+#[async_trait]
+impl MessageSender for StdoutSender {
+    async fn send_message(&self, channel_id: &str, content: &str) -> Result<(), String> {
+        println!("[#{}] {}", channel_id, content);
+        Ok(())
+    }
+}
+
+struct OfflineOpenAI;
+
+#[async_trait]
+impl OpenAIClient for OfflineOpenAI {
+    async fn generate_prompt(
+        &self,
+        _prompt: &str,
+        _prompt_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Err("offline".into())
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut db: DB<Notification> = HashMap::new();
+    let mut settings: DB<UserSettings> = HashMap::new();
+
+    // Both the day-before and hour-before reminders are already due.
+    let event_time = Utc::now() + Duration::minutes(30);
+    notification::create_notification(
+        &mut db,
+        &"stand up and stretch".to_string(),
+        &"@you".to_string(),
+        &event_time,
+        &"general".to_string(),
+        None,
+    )
+    .await
+    .expect("notification stored");
+
+    for _ in 0..2 {
+        notification_tick(
+            &mut db,
+            &mut settings,
+            &HashMap::new(),
+            &StdoutSender,
+            &OfflineOpenAI,
+            Utc::now(),
+        )
+        .await
+        .expect("tick succeeds");
+    }
+    println!("{} notification(s) left", db.len());
+}
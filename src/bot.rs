@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
+
+use memory_db::{DB, load_db};
+use tokio::sync::Mutex;
+
+use crate::config::{AppConfig, SharedConfig};
+use crate::handlers::http::WebhookConfig;
+use crate::models::notification::{self, Notification};
+use crate::models::settings::{self, GuildSettings, UserSettings};
+use crate::models::todo::{self, TodoItem};
+use crate::runtime::{self, RunModes};
+use crate::service::openai_service::{OpenAIClient, OpenAIService};
+use crate::service::routing::{CascadeRouter, IntentRouter, OpenAIRouter};
+use crate::service::rules_router::RulesRouter;
+
+// Builder for the full bot. Embedders can swap in their own intent router or
+// LLM client without forking; see examples/custom_router.rs.
+pub struct ReminderBot {
+    pub(crate) modes: RunModes,
+    pub(crate) discord_client_secret: String,
+    pub(crate) http_addr: Option<SocketAddr>,
+    pub(crate) webhook: Option<WebhookConfig>,
+    pub(crate) feed_addr: Option<SocketAddr>,
+    pub(crate) config: SharedConfig,
+    pub(crate) admin_role: Option<String>,
+    openai_api_key: String,
+    openai: Option<Arc<dyn OpenAIClient>>,
+    // The client built from `openai_api_key` when none was supplied, made on
+    // first use and shared from then on.
+    default_openai: OnceLock<Arc<dyn OpenAIClient>>,
+    router: Option<Arc<dyn IntentRouter>>,
+}
+
+impl ReminderBot {
+    pub fn new(discord_client_secret: impl Into<String>, openai_api_key: impl Into<String>) -> Self {
+        Self {
+            modes: RunModes {
+                gateway: true,
+                http: false,
+                worker: true,
+                tui: false,
+            },
+            discord_client_secret: discord_client_secret.into(),
+            http_addr: None,
+            webhook: None,
+            feed_addr: None,
+            config: AppConfig::default().shared(),
+            admin_role: None,
+            openai_api_key: openai_api_key.into(),
+            openai: None,
+            default_openai: OnceLock::new(),
+            router: None,
+        }
+    }
+
+    pub fn with_modes(mut self, modes: RunModes) -> Self {
+        self.modes = modes;
+        self
+    }
+
+    pub fn with_http(mut self, addr: SocketAddr) -> Self {
+        self.modes.http = true;
+        self.http_addr = Some(addr);
+        self
+    }
+
+    pub fn with_webhook(mut self, webhook: WebhookConfig) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
+
+    // Where the /settings feed calendar URLs are served.
+    pub fn with_feeds(mut self, addr: SocketAddr) -> Self {
+        self.feed_addr = Some(addr);
+        self
+    }
+
+    // Discord role id allowed to run /admin.
+    pub fn with_admin_role(mut self, role_id: impl Into<String>) -> Self {
+        self.admin_role = Some(role_id.into());
+        self
+    }
+
+    // Runtime-tunable settings; pair with `config::watch` for hot reload.
+    pub fn with_config(mut self, config: SharedConfig) -> Self {
+        self.config = config;
+        self.default_openai = OnceLock::new();
+        self
+    }
+
+    // Replaces the OpenAI client used for message generation, parsing and the
+    // default router.
+    pub fn with_openai_client(mut self, openai: Arc<dyn OpenAIClient>) -> Self {
+        self.openai = Some(openai);
+        self
+    }
+
+    pub fn with_intent_router(mut self, router: Arc<dyn IntentRouter>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    pub fn openai_client(&self) -> Arc<dyn OpenAIClient> {
+        match &self.openai {
+            Some(openai) => openai.clone(),
+            None => self
+                .default_openai
+                .get_or_init(|| {
+                    let service = OpenAIService::new(self.openai_api_key.clone());
+                    Arc::new(service.with_config(self.config.clone()))
+                })
+                .clone(),
+        }
+    }
+
+    // Falls back to the keyword rules, asking the configured OpenAI client
+    // about anything they aren't sure of, when no router was set.
+    pub fn intent_router(&self) -> Arc<dyn IntentRouter> {
+        self.intent_router_with(self.openai_client())
+    }
+
+    // ROUTER_RULES_FILE rules are checked before either. A file that doesn't
+    // load is logged and skipped rather than stopping the bot.
+    pub(crate) fn intent_router_with(&self, openai: Arc<dyn OpenAIClient>) -> Arc<dyn IntentRouter> {
+        let router: Arc<dyn IntentRouter> = match &self.router {
+            Some(router) => router.clone(),
+            None if self.config.load().router_cascade() => {
+                Arc::new(CascadeRouter::new(Arc::new(OpenAIRouter::new(openai))))
+            }
+            None => Arc::new(OpenAIRouter::new(openai)),
+        };
+        let Some(path) = self.config.load().router_rules_file() else {
+            return router;
+        };
+        match RulesRouter::from_file(&path, router.clone()) {
+            Ok(rules) => {
+                tracing::info!(%path, rules = rules.len(), "loaded routing rules");
+                Arc::new(rules)
+            }
+            Err(err) => {
+                tracing::warn!(%path, error = %err, "ignoring routing rules");
+                router
+            }
+        }
+    }
+
+    // Loads the stores from DB_LOCATION and runs until SIGINT/SIGTERM.
+    pub async fn run(self) {
+        let db: DB<Notification> =
+            load_db(&notification::get_db_location()).expect("Unable to load database.");
+        let todo_db: DB<TodoItem> =
+            load_db(&todo::get_db_location()).unwrap_or_else(|_| HashMap::new());
+        let user_settings_db: DB<UserSettings> =
+            load_db(&settings::get_user_db_location()).unwrap_or_else(|_| HashMap::new());
+        let guild_settings_db: DB<GuildSettings> =
+            load_db(&settings::get_guild_db_location()).unwrap_or_else(|_| HashMap::new());
+        runtime::run(
+            self,
+            Arc::new(Mutex::new(db)),
+            Arc::new(Mutex::new(todo_db)),
+            Arc::new(Mutex::new(user_settings_db)),
+            Arc::new(Mutex::new(guild_settings_db)),
+        )
+        .await;
+    }
+}
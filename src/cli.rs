@@ -0,0 +1,1206 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono_tz::America::New_York;
+use memory_db::{DB, load_db, save_db};
+use serde::Deserialize;
+
+use crate::config::AppConfig;
+use crate::handlers::action::NotificationDraft;
+use crate::i18n::Locale;
+use crate::models::notification::{self, AINotification, Notification, get_db_location};
+use crate::models::recurrence;
+use crate::models::todo::{self, AITodo, TodoItem};
+use crate::runtime::RunModes;
+use crate::service::notification_normalizer::{self, NormalizedNotification};
+use crate::service::notification_service::{
+    render_notification_list, render_notifications_between, render_pending_message,
+};
+use crate::service::notify_flow::{
+    NotifyDecision, PendingSession, SessionKey, clarify_question, is_list_command, parse_query_window,
+    route_notify,
+};
+use crate::service::openai_service::{OpenAIClient, parse_model_json};
+use crate::service::routing::IntentRouter;
+
+// Headless management of the saved reminders and todos, for running the bot
+// without Discord. Works on the same files as the bot; a running bot keeps its
+// own copy and only sees changes after a restart:
+//
+//   list [--user <id>]
+//   show <id>
+//   create --user <id> --channel <id> --at <time> <text>
+//   edit <id> [--text <text>] [--at <time>]
+//   delete <id>
+//   todo [list] --user <id> | todo add --user <id> <text> | todo done <id> | todo delete <id>
+//   init [--config <path>] [--force]
+//   completions <bash|zsh|fish> | man
+//   repl --user <id> --channel <id>  (see Repl; dispatched from main)
+//   prompt --user <id> --channel <id> [--dry-run] [--force] <text>  (also from main)
+//   import --file <reminders.jsonl> [--user <id>] [--channel <id>]  (also from main)
+//   watch [--user <id>] [--from-start]  (also from main)
+//   route-eval --corpus <file.jsonl> [--router configured|heuristic]  (also from main)
+//
+// Ids may be shortened to any unique prefix, as printed by list. Times are
+// RFC 3339 or "YYYY-MM-DD HH:MM" in New York time. `--output json|table|plain`
+// may go anywhere on the line; json prints the stored records with the
+// models' field names.
+//
+// Takes the whole command line and returns None for commands that aren't
+// handled here.
+pub async fn command(args: &[String]) -> Option<Result<String, String>> {
+    let (format, args) = match take_output_format(args) {
+        Ok(parsed) => parsed,
+        Err(err) => return Some(Err(err)),
+    };
+    let (name, args) = args.split_first()?;
+    let output = match name.as_str() {
+        "list" => list_command(args, format),
+        "show" => show_command(args, format),
+        "create" => create_command(args, format).await,
+        "edit" => edit_command(args, format, Utc::now()),
+        "delete" => delete_command(args),
+        "todo" => todo_command(args, format, Utc::now()),
+        "init" => init_command(args),
+        "completions" => completions_command(args),
+        "man" => Ok(render_man_page()),
+        _ => return None,
+    };
+    Some(output)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Plain,
+    Table,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "plain" => Ok(Self::Plain),
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            other => Err(format!("Unknown output format '{}'. Use json, table or plain.", other)),
+        }
+    }
+}
+
+// Pulls `--output <format>` out of the arguments, wherever it is.
+fn take_output_format(args: &[String]) -> Result<(OutputFormat, Vec<String>), String> {
+    let mut format = OutputFormat::default();
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--output" {
+            format = OutputFormat::parse(args.next().ok_or("--output needs a format")?)?;
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    Ok((format, rest))
+}
+
+fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| e.to_string())
+}
+
+// Left-aligned columns, two spaces apart, under an upper-case header row.
+fn render_table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let header: Vec<String> = headers.iter().map(|header| header.to_string()).collect();
+    std::iter::once(header)
+        .chain(rows)
+        .map(|row: Vec<String>| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = *width))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn status(notification: &Notification) -> &'static str {
+    if notification.completed_at.is_some() {
+        "done"
+    } else if notification.paused {
+        "paused"
+    } else {
+        "scheduled"
+    }
+}
+
+fn render_reminders(reminders: &[&Notification], format: OutputFormat) -> Result<String, String> {
+    match format {
+        OutputFormat::Json => to_json(reminders),
+        OutputFormat::Plain => Ok(reminders.iter().map(|n| render_line(n)).collect::<Vec<_>>().join("\n")),
+        OutputFormat::Table => Ok(render_table(
+            &["ID", "TIME", "TEXT", "NOTIFY", "STATUS"],
+            reminders
+                .iter()
+                .map(|n| {
+                    vec![
+                        short_id(&n.id).to_string(),
+                        reminder_time(n).map(format_time).unwrap_or_default(),
+                        n.content.clone(),
+                        n.notify.join(","),
+                        status(n).to_string(),
+                    ]
+                })
+                .collect(),
+        )),
+    }
+}
+
+fn load_notifications() -> DB<Notification> {
+    load_db(&get_db_location()).unwrap_or_else(|_| HashMap::new())
+}
+
+fn load_todos() -> DB<TodoItem> {
+    load_db(&todo::get_db_location()).unwrap_or_else(|_| HashMap::new())
+}
+
+fn short_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+// The full key `prefix` names, if it names exactly one.
+fn resolve_id<'a>(keys: impl Iterator<Item = &'a String>, prefix: &str) -> Result<String, String> {
+    let matches: Vec<&String> = keys.filter(|key| key.starts_with(prefix)).collect();
+    match matches.as_slice() {
+        [id] => Ok(id.to_string()),
+        [] => Err(format!("No entry with id '{}'", prefix)),
+        _ => Err(format!("'{}' matches {} entries; give more of the id", prefix, matches.len())),
+    }
+}
+
+pub fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .and_then(|local| local.and_local_timezone(New_York).earliest())
+        .map(|at| at.with_timezone(&Utc))
+        .ok_or_else(|| format!("Expected a time like 2026-03-01 09:30, got '{}'", value))
+}
+
+fn format_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&New_York).format("%a %b %-d %Y, %-I:%M %p").to_string()
+}
+
+fn reminder_time(notification: &Notification) -> Option<DateTime<Utc>> {
+    notification
+        .event_time
+        .or_else(|| notification.notification_times.first().copied())
+}
+
+fn render_line(notification: &Notification) -> String {
+    let mut line = format!(
+        "{}  {}  {} → {}",
+        short_id(&notification.id),
+        reminder_time(notification).map(format_time).unwrap_or_default(),
+        notification.content,
+        notification.notify.join(", ")
+    );
+    if notification.paused {
+        line.push_str(" [paused]");
+    }
+    if notification.completed_at.is_some() {
+        line.push_str(" [done]");
+    }
+    line
+}
+
+fn render_details(notification: &Notification) -> String {
+    let mut lines = vec![
+        format!("id: {}", notification.id),
+        format!("text: {}", notification.content),
+        format!("notify: {}", notification.notify.join(", ")),
+        format!("channel: {}", notification.channel),
+    ];
+    if let Some(at) = notification.event_time {
+        lines.push(format!("time: {}", format_time(at)));
+    }
+    for at in &notification.notification_times {
+        lines.push(format!("ping: {}", format_time(*at)));
+    }
+    if let Some(recurrence) = &notification.recurrence {
+        lines.push(format!("repeats: {}", recurrence.describe()));
+    }
+    for item in &notification.checklist {
+        lines.push(format!("checklist: [{}] {}", if item.done { "x" } else { " " }, item.text));
+    }
+    if notification.paused {
+        lines.push("paused: yes".to_string());
+    }
+    if let Some(at) = notification.completed_at {
+        lines.push(format!("completed: {}", format_time(at)));
+    }
+    lines.join("\n")
+}
+
+// Splits `--flag value` pairs from the remaining words, which are joined
+// back into the free text.
+fn parse_options<'a>(args: &'a [String], flags: &[&str]) -> Result<(HashMap<&'a str, &'a str>, String), String> {
+    let mut options = HashMap::new();
+    let mut words = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg.starts_with("--") {
+            if !flags.contains(&arg.as_str()) {
+                return Err(format!("Unknown option '{}'", arg));
+            }
+            let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+            options.insert(arg.as_str(), value.as_str());
+        } else {
+            words.push(arg.as_str());
+        }
+    }
+    Ok((options, words.join(" ")))
+}
+
+fn list_command(args: &[String], format: OutputFormat) -> Result<String, String> {
+    let (options, _) = parse_options(args, &["--user"])?;
+    let db = load_notifications();
+    let mut reminders: Vec<&Notification> = db
+        .values()
+        .filter(|n| options.get("--user").is_none_or(|user| n.notify.iter().any(|u| u == *user)))
+        .collect();
+    if reminders.is_empty() && format != OutputFormat::Json {
+        return Ok("No reminders.".to_string());
+    }
+    reminders.sort_by_key(|n| (reminder_time(n), n.id.clone()));
+    render_reminders(&reminders, format)
+}
+
+fn show_command(args: &[String], format: OutputFormat) -> Result<String, String> {
+    let [prefix] = args else {
+        return Err("Usage: show <id>".to_string());
+    };
+    let db = load_notifications();
+    let id = resolve_id(db.keys(), prefix)?;
+    match format {
+        OutputFormat::Json => to_json(&db[&id]),
+        OutputFormat::Plain | OutputFormat::Table => Ok(render_details(&db[&id])),
+    }
+}
+
+async fn create_command(args: &[String], format: OutputFormat) -> Result<String, String> {
+    let usage = "Usage: create --user <id> --channel <id> --at <time> <text>";
+    let (options, text) = parse_options(args, &["--user", "--channel", "--at"])?;
+    let (Some(user_id), Some(channel_id), Some(at)) = (
+        options.get("--user"),
+        options.get("--channel"),
+        options.get("--at"),
+    ) else {
+        return Err(usage.to_string());
+    };
+    if text.is_empty() {
+        return Err(usage.to_string());
+    }
+    let at = parse_time(at)?;
+    let mut db = load_notifications();
+    let id = notification::create_notification(
+        &mut db,
+        &text,
+        &user_id.to_string(),
+        &at,
+        &channel_id.to_string(),
+        None,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    if format == OutputFormat::Json {
+        return to_json(&db[&id]);
+    }
+    Ok(format!("Created {}: {}", short_id(&id), render_line(&db[&id])))
+}
+
+fn edit_command(args: &[String], format: OutputFormat, now: DateTime<Utc>) -> Result<String, String> {
+    let usage = "Usage: edit <id> [--text <text>] [--at <time>]";
+    let Some((prefix, rest)) = args.split_first() else {
+        return Err(usage.to_string());
+    };
+    let (options, extra) = parse_options(rest, &["--text", "--at"])?;
+    if !extra.is_empty() || options.is_empty() {
+        return Err(usage.to_string());
+    }
+    let at = options.get("--at").map(|value| parse_time(value)).transpose()?;
+    let mut db = load_notifications();
+    let id = resolve_id(db.keys(), prefix)?;
+    let Some(notification) = db.get_mut(&id) else {
+        return Err(format!("No entry with id '{}'", prefix));
+    };
+    if let Some(text) = options.get("--text") {
+        notification.content = text.to_string();
+    }
+    if let Some(at) = at {
+        notification::reschedule(notification, at, now);
+    }
+    let output = match format {
+        OutputFormat::Json => to_json(&*notification)?,
+        _ => format!("Updated {}", render_line(notification)),
+    };
+    save_db(&get_db_location(), &db).map_err(|e| e.to_string())?;
+    Ok(output)
+}
+
+fn delete_command(args: &[String]) -> Result<String, String> {
+    let [prefix] = args else {
+        return Err("Usage: delete <id>".to_string());
+    };
+    let mut db = load_notifications();
+    let id = resolve_id(db.keys(), prefix)?;
+    notification::delete_notification(&mut db, &id).map_err(|e| e.to_string())?;
+    Ok(format!("Deleted {}.", short_id(&id)))
+}
+
+fn todo_command(args: &[String], format: OutputFormat, now: DateTime<Utc>) -> Result<String, String> {
+    let usage = "Usage: todo [list] --user <id> | todo add --user <id> <text> | todo done <id> | todo delete <id>";
+    let (action, rest) = match args.split_first() {
+        Some((action, rest)) if !action.starts_with("--") => (action.as_str(), rest),
+        _ => ("list", args),
+    };
+    let mut db = load_todos();
+    match action {
+        "list" => {
+            let (options, _) = parse_options(rest, &["--user"])?;
+            let user_id = options.get("--user").ok_or(usage)?;
+            let mut items: Vec<&TodoItem> = db
+                .values()
+                .filter(|item| item.user_id == *user_id && item.completed_at.is_none())
+                .collect();
+            if items.is_empty() && format != OutputFormat::Json {
+                return Ok("No open todos.".to_string());
+            }
+            items.sort_by_key(|item| (item.created_at, item.id.clone()));
+            match format {
+                OutputFormat::Json => to_json(&items),
+                OutputFormat::Plain => Ok(items
+                    .into_iter()
+                    .map(|item| format!("{}  {}", short_id(&item.id), item.content))
+                    .collect::<Vec<_>>()
+                    .join("\n")),
+                OutputFormat::Table => Ok(render_table(
+                    &["ID", "CREATED", "TEXT"],
+                    items
+                        .into_iter()
+                        .map(|item| vec![short_id(&item.id).to_string(), format_time(item.created_at), item.content.clone()])
+                        .collect(),
+                )),
+            }
+        }
+        "add" => {
+            let (options, text) = parse_options(rest, &["--user"])?;
+            let user_id = options.get("--user").ok_or(usage)?;
+            if text.is_empty() {
+                return Err(usage.to_string());
+            }
+            let id = todo::create_todo(&mut db, user_id, &text).map_err(|e| e.to_string())?;
+            Ok(format!("Added {}: {}", short_id(&id), text))
+        }
+        "done" | "delete" => {
+            let [prefix] = rest else {
+                return Err(usage.to_string());
+            };
+            let id = resolve_id(db.keys(), prefix)?;
+            if action == "delete" {
+                todo::delete_todo(&mut db, &id).map_err(|e| e.to_string())?;
+                return Ok(format!("Deleted {}.", short_id(&id)));
+            }
+            if let Some(item) = db.get_mut(&id) {
+                item.completed_at = Some(now);
+            }
+            save_db(&todo::get_db_location(), &db).map_err(|e| e.to_string())?;
+            Ok(format!("Marked {} done.", short_id(&id)))
+        }
+        _ => Err(usage.to_string()),
+    }
+}
+
+// What the REPL is waiting on a yes/no (or, for reminders, a correction) for.
+enum ReplDraft {
+    Reminder {
+        original_text: String,
+        draft: NormalizedNotification,
+    },
+    Todos(Vec<String>),
+}
+
+// `repl --user <id> --channel <id>`: /notify as a conversation in the
+// terminal. Lines go through the same routing and session handling as
+// Discord, so an unclear request can be finished on the next line, and each
+// draft is shown for a y/n before it's saved.
+pub struct Repl {
+    openai: Arc<dyn OpenAIClient>,
+    router: Arc<dyn IntentRouter>,
+    sessions: HashMap<SessionKey, PendingSession>,
+    session_key: SessionKey,
+    pending: Option<ReplDraft>,
+    notifications: DB<Notification>,
+    todos: DB<TodoItem>,
+}
+
+impl Repl {
+    pub fn new(
+        openai: Arc<dyn OpenAIClient>,
+        router: Arc<dyn IntentRouter>,
+        user_id: impl Into<String>,
+        channel_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            openai,
+            router,
+            sessions: HashMap::new(),
+            session_key: (user_id.into(), channel_id.into()),
+            pending: None,
+            notifications: load_notifications(),
+            todos: load_todos(),
+        }
+    }
+
+    pub async fn handle_line(&mut self, line: &str, now: DateTime<Utc>) -> String {
+        let line = line.trim();
+        if line.is_empty() {
+            return String::new();
+        }
+        if let Some(pending) = self.pending.take() {
+            return self.answer(pending, line, now).await;
+        }
+        if is_list_command(line) {
+            return render_notification_list(&self.notifications, &self.session_key.0);
+        }
+        let decision = route_notify(
+            self.router.as_ref(),
+            &mut self.sessions,
+            self.session_key.clone(),
+            line.to_string(),
+            Locale::En,
+            now,
+        )
+        .await;
+        match decision {
+            NotifyDecision::EmitNotify { normalized_text } => {
+                self.draft_reminder(normalized_text.clone(), &normalized_text, "notification", now)
+                    .await
+            }
+            NotifyDecision::EmitTodo { normalized_text } => self.draft_todos(&normalized_text).await,
+            NotifyDecision::EmitCalendarEvent { .. } => {
+                "Calendar events can only be added from Discord for now.".to_string()
+            }
+            NotifyDecision::EmitQuery { normalized_text } => match parse_query_window(&normalized_text, now) {
+                Some((from, until)) => {
+                    render_notifications_between(&self.notifications, &self.session_key.0, from, until)
+                }
+                None => render_notification_list(&self.notifications, &self.session_key.0),
+            },
+            NotifyDecision::EmitCancel { .. } | NotifyDecision::EmitSnooze { .. } => {
+                "Reminders can only be canceled or snoozed from Discord for now.".to_string()
+            }
+            NotifyDecision::EmitMany { parts } => format!(
+                "That's {} requests in one; send them one at a time here.",
+                parts.len()
+            ),
+            NotifyDecision::ClarifyIntent { intent, normalized_text } => {
+                clarify_question(Locale::En, &intent, &normalized_text).replace("Re-run /notify", "Reply")
+            }
+            NotifyDecision::ConversationCanceled => "Okay, never mind.".to_string(),
+            NotifyDecision::GaveUp => {
+                "I still couldn't tell what you meant, so I've let it go.".to_string()
+            }
+            _ => "What should I remind you about, and when?".to_string(),
+        }
+    }
+
+    async fn answer(&mut self, pending: ReplDraft, line: &str, now: DateTime<Utc>) -> String {
+        let reply = match line.to_lowercase().as_str() {
+            "y" | "yes" => self.save(pending).await,
+            "n" | "no" => "Discarded.".to_string(),
+            _ => match pending {
+                // Anything else corrects the draft, as the Discord edit
+                // button does.
+                ReplDraft::Reminder { original_text, .. } => {
+                    let prompt = format!("Original request: {}\nCorrection note: {}", original_text, line);
+                    return self
+                        .draft_reminder(original_text, &prompt, "notification_correction", now)
+                        .await;
+                }
+                pending => {
+                    self.pending = Some(pending);
+                    return "Answer y or n.".to_string();
+                }
+            },
+        };
+        self.sessions.remove(&self.session_key);
+        reply
+    }
+
+    async fn draft_reminder(
+        &mut self,
+        original_text: String,
+        prompt: &str,
+        prompt_type: &str,
+        now: DateTime<Utc>,
+    ) -> String {
+        let ai = match extract_reminder(self.openai.as_ref(), prompt, prompt_type).await {
+            Ok(ai) => ai,
+            Err(err) => return err,
+        };
+        let draft = notification_normalizer::normalize(ai, &original_text, now);
+        let mut reply = format!("Remind you \"{}\" at {}", draft.content, format_time(draft.time));
+        if let Some(recurrence) = &draft.recurrence {
+            reply.push_str(&format!(" ({})", recurrence.describe()));
+        }
+        reply.push('?');
+        if let Some(issue) = draft.time_issue(now) {
+            reply.push_str(&format!(" {}.", issue.describe(Locale::En)));
+        }
+        reply.push_str(" y to save, n to discard, or say what to change.");
+        self.pending = Some(ReplDraft::Reminder { original_text, draft });
+        reply
+    }
+
+    async fn draft_todos(&mut self, text: &str) -> String {
+        let extracted = match self.openai.generate_prompt(text, "todo_extraction").await {
+            Ok(payload) => parse_model_json::<AITodo>(&payload)
+                .map_err(|err| format!("Couldn't read todos from that: {}", err)),
+            Err(err) => Err(format!("Failed to call OpenAI: {}", err)),
+        };
+        let items = match extracted {
+            Ok(extracted) => todo::draft_items(extracted, text),
+            Err(err) => return err,
+        };
+        let mut reply = String::from("Add these todos?");
+        for item in &items {
+            reply.push_str(&format!("\n- {}", item));
+        }
+        reply.push_str("\ny to save, n to discard.");
+        self.pending = Some(ReplDraft::Todos(items));
+        reply
+    }
+
+    async fn save(&mut self, pending: ReplDraft) -> String {
+        let (user_id, channel_id) = self.session_key.clone();
+        match pending {
+            ReplDraft::Reminder { draft, .. } => {
+                let created = notification::create_notification(
+                    &mut self.notifications,
+                    &draft.content,
+                    &user_id,
+                    &draft.time,
+                    &channel_id,
+                    draft.recurrence,
+                )
+                .await;
+                let created = match created {
+                    Ok(id) if !draft.offsets.is_empty() => {
+                        notification::set_offsets(&mut self.notifications, &id, &draft.offsets).map(|_| id)
+                    }
+                    other => other,
+                };
+                match created {
+                    Ok(id) => format!("Saved {}.", short_id(&id)),
+                    Err(err) => format!("Failed to save the reminder: {}", err),
+                }
+            }
+            ReplDraft::Todos(items) => {
+                let saved = items
+                    .iter()
+                    .try_for_each(|item| todo::create_todo(&mut self.todos, &user_id, item).map(|_| ()));
+                match saved {
+                    Ok(()) => format!("Added {} todos.", items.len()),
+                    Err(err) => format!("Failed to save the todos: {}", err),
+                }
+            }
+        }
+    }
+}
+
+async fn extract_reminder(
+    openai: &dyn OpenAIClient,
+    prompt: &str,
+    prompt_type: &str,
+) -> Result<AINotification, String> {
+    let payload = openai
+        .generate_prompt(prompt, prompt_type)
+        .await
+        .map_err(|err| format!("Failed to call OpenAI: {}", err))?;
+    parse_model_json(&payload).map_err(|err| format!("Couldn't read a reminder from that: {}", err))
+}
+
+// `prompt --user <id> --channel <id> [--dry-run] [--force] <text>`: one /notify
+// request, read by OpenAI and saved without a confirmation step. With
+// --dry-run nothing is saved (and --user/--channel may be left out);
+// instead it prints what the model returned, the pings that would be
+// scheduled and the confirmation Discord would show, for checking prompt
+// and time zone behaviour. A time that looks wrong (see
+// service::time_sanity) is refused unless --force is given.
+pub async fn prompt_command(args: &[String], openai: &dyn OpenAIClient, now: DateTime<Utc>) -> Result<String, String> {
+    let usage = "Usage: prompt --user <id> --channel <id> [--dry-run] [--force] <text>";
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    let force = args.iter().any(|arg| arg == "--force");
+    let args: Vec<String> = args
+        .iter()
+        .filter(|arg| *arg != "--dry-run" && *arg != "--force")
+        .cloned()
+        .collect();
+    let (options, text) = parse_options(&args, &["--user", "--channel"])?;
+    if text.is_empty() {
+        return Err(usage.to_string());
+    }
+    let user_id = options.get("--user").copied();
+    let channel_id = options.get("--channel").copied();
+    let ai = extract_reminder(openai, &text, "notification").await?;
+
+    if dry_run {
+        let raw = to_json(&ai)?;
+        let checklist = ai.checklist.clone();
+        let normalized = notification_normalizer::normalize(ai, &text, now);
+        let time_issue = normalized.time_issue(now);
+        let draft = NotificationDraft {
+            user_id: user_id.unwrap_or_default().to_string(),
+            channel_id: channel_id.unwrap_or_default().to_string(),
+            content: normalized.content,
+            time: normalized.time,
+            original_text: text.clone(),
+            extra_context: None,
+            expires_at: now,
+            message_id: None,
+            recurrence: normalized.recurrence,
+            day_load: 0,
+            alternatives: Vec::new(),
+            checklist,
+            conflicts: Vec::new(),
+            duplicate: None,
+            time_issue,
+            offsets: normalized.offsets,
+            original_phrase: normalized.original_phrase,
+        };
+        let pings: Vec<String> = notification::ping_times(&draft.time, &draft.offsets)
+            .into_iter()
+            .map(|at| format!("  {} ({})", at.to_rfc3339(), format_time(at)))
+            .collect();
+        return Ok(format!(
+            "AINotification:\n{}\n\nnotification_times:\n{}\n\nConfirmation:\n{}",
+            raw,
+            pings.join("\n"),
+            render_pending_message(&draft, Locale::En, New_York)
+        ));
+    }
+
+    let (Some(user_id), Some(channel_id)) = (user_id, channel_id) else {
+        return Err(usage.to_string());
+    };
+    let checklist = ai.checklist.clone();
+    let normalized = notification_normalizer::normalize(ai, &text, now);
+    if let Some(issue) = normalized.time_issue(now).filter(|_| !force) {
+        return Err(format!(
+            "Not saved: read the time as {}. {}; say when more precisely, or pass --force to save it anyway.",
+            format_time(normalized.time),
+            issue.describe(Locale::En)
+        ));
+    }
+    let mut db = load_notifications();
+    let id = notification::create_notification(
+        &mut db,
+        &normalized.content,
+        &user_id.to_string(),
+        &normalized.time,
+        &channel_id.to_string(),
+        normalized.recurrence,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    if !normalized.offsets.is_empty() {
+        notification::set_offsets(&mut db, &id, &normalized.offsets).map_err(|e| e.to_string())?;
+    }
+    if !checklist.is_empty() {
+        notification::set_checklist(&mut db, &id, &checklist).map_err(|e| e.to_string())?;
+    }
+    Ok(format!("Created {}: {}", short_id(&id), render_line(&db[&id])))
+}
+
+// One line of a bulk import file. Structured lines give `content` and
+// `time`; anything else is a request for the extraction prompt, either as
+// `text` or as the whole line.
+#[derive(Debug, Default, Deserialize)]
+struct ImportLine {
+    content: Option<String>,
+    time: Option<String>,
+    text: Option<String>,
+    // Comma-separated, as for /notify targets.
+    notify: Option<String>,
+    channel: Option<String>,
+    // A phrase such as "every Friday".
+    recurrence: Option<String>,
+}
+
+fn parse_import_line(line: &str) -> ImportLine {
+    match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(serde_json::Value::String(text)) => ImportLine {
+            text: Some(text),
+            ..Default::default()
+        },
+        Ok(value @ serde_json::Value::Object(_)) => serde_json::from_value(value).unwrap_or_default(),
+        _ => ImportLine {
+            text: Some(line.to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+async fn import_line(
+    db: &mut DB<Notification>,
+    line: ImportLine,
+    defaults: (Option<&str>, Option<&str>),
+    openai: Option<&dyn OpenAIClient>,
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    let user_id = line.notify.as_deref().or(defaults.0).ok_or("no user; pass --user or give \"notify\"")?;
+    let channel_id = line
+        .channel
+        .as_deref()
+        .or(defaults.1)
+        .ok_or("no channel; pass --channel or give \"channel\"")?;
+    let (content, time, recurrence) = match (line.content, line.time, line.text) {
+        (Some(content), Some(time), _) => {
+            let recurrence = match line.recurrence {
+                Some(phrase) => Some(
+                    recurrence::parse_recurrence(&phrase, now)
+                        .ok_or_else(|| format!("couldn't read the recurrence '{}'", phrase))?,
+                ),
+                None => None,
+            };
+            (content, parse_time(&time)?, recurrence)
+        }
+        (_, _, Some(text)) => {
+            let openai = openai.ok_or("free text needs OPENAI_API_KEY")?;
+            let normalized = notification_normalizer::normalize(
+                extract_reminder(openai, &text, "notification").await?,
+                &text,
+                now,
+            );
+            if let Some(issue) = normalized.time_issue(now) {
+                return Err(format!(
+                    "{} (read as {}); give \"time\" instead",
+                    issue.describe(Locale::En),
+                    format_time(normalized.time)
+                ));
+            }
+            (normalized.content, normalized.time, normalized.recurrence)
+        }
+        _ => return Err("expected \"content\" and \"time\", or \"text\"".to_string()),
+    };
+    let id = notification::create_notification(
+        db,
+        &content,
+        &user_id.to_string(),
+        &time,
+        &channel_id.to_string(),
+        recurrence,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(render_line(&db[&id]))
+}
+
+// `import --file <path> [--user <id>] [--channel <id>]`: creates a reminder
+// per line of a JSON lines file, reporting each line's result. Blank lines
+// and lines starting with # are skipped; --user and --channel fill in lines
+// that don't name their own.
+pub async fn import_file_command(
+    args: &[String],
+    openai: Option<&dyn OpenAIClient>,
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    let (options, extra) = parse_options(args, &["--file", "--user", "--channel"])?;
+    let (Some(path), true) = (options.get("--file"), extra.is_empty()) else {
+        return Err("Usage: import --file <reminders.jsonl> [--user <id>] [--channel <id>]".to_string());
+    };
+    let raw = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let defaults = (options.get("--user").copied(), options.get("--channel").copied());
+    let mut db = load_notifications();
+    let mut report = Vec::new();
+    let (mut created, mut total) = (0, 0);
+    for (number, line) in raw.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        total += 1;
+        match import_line(&mut db, parse_import_line(line), defaults, openai, now).await {
+            Ok(summary) => {
+                created += 1;
+                report.push(format!("line {}: created {}", number + 1, summary));
+            }
+            Err(err) => report.push(format!("line {}: failed: {}", number + 1, err)),
+        }
+    }
+    report.push(format!("Imported {} of {} reminders.", created, total));
+    Ok(report.join("\n"))
+}
+
+pub async fn repl_command(
+    args: &[String],
+    openai: Arc<dyn OpenAIClient>,
+    router: Arc<dyn IntentRouter>,
+) -> Result<String, String> {
+    let usage = "Usage: repl --user <id> --channel <id>";
+    let (options, extra) = parse_options(args, &["--user", "--channel"])?;
+    let (Some(user_id), Some(channel_id)) = (options.get("--user"), options.get("--channel")) else {
+        return Err(usage.to_string());
+    };
+    if !extra.is_empty() {
+        return Err(usage.to_string());
+    }
+    let mut repl = Repl::new(openai, router, *user_id, *channel_id);
+    println!("What should I remind you about? (\"list\" shows your reminders, Ctrl-D quits)");
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().map_err(|e| e.to_string())?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+            break;
+        }
+        let reply = repl.handle_line(&line, Utc::now()).await;
+        if !reply.is_empty() {
+            println!("{}", reply);
+        }
+    }
+    Ok("Bye.".to_string())
+}
+
+// Asks until `check` accepts the answer; an empty answer takes `default`
+// when there is one.
+fn ask(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    default: Option<&str>,
+    check: impl Fn(&str) -> Result<(), String>,
+) -> Result<String, String> {
+    loop {
+        let prompt = match default {
+            Some(default) if !default.is_empty() => format!("{} [{}]: ", question, default),
+            _ => format!("{}: ", question),
+        };
+        write!(output, "{}", prompt)
+            .and_then(|_| output.flush())
+            .map_err(|e| e.to_string())?;
+        let mut answer = String::new();
+        if input.read_line(&mut answer).map_err(|e| e.to_string())? == 0 {
+            return Err("Setup canceled.".to_string());
+        }
+        let mut answer = answer.trim().to_string();
+        if answer.is_empty() {
+            answer = default.unwrap_or_default().to_string();
+        }
+        match check(&answer) {
+            Ok(()) => return Ok(answer),
+            Err(err) => writeln!(output, "{}", err).map_err(|e| e.to_string())?,
+        }
+    }
+}
+
+fn required(answer: &str) -> Result<(), String> {
+    if answer.is_empty() || answer.contains(char::is_whitespace) {
+        return Err("This one is required, without spaces.".to_string());
+    }
+    Ok(())
+}
+
+// The first-run questions, answered from `input`. Writes `path` and loads
+// it back with AppConfig::from_file before reporting success.
+pub fn run_init(input: &mut impl BufRead, output: &mut impl Write, path: &str) -> Result<String, String> {
+    let token = ask(input, output, "Discord bot token", None, required)?;
+    let openai_key = ask(input, output, "OpenAI API key", None, required)?;
+    let operator_channel = ask(input, output, "Channel id for operator alerts (optional)", Some(""), |answer| {
+        if answer.chars().all(|c| c.is_ascii_digit()) {
+            Ok(())
+        } else {
+            Err("Channel ids are numbers; copy one with Discord's developer mode on.".to_string())
+        }
+    })?;
+    let db_location = ask(input, output, "Where to keep data", Some("./data"), |answer| {
+        std::fs::create_dir_all(answer).map_err(|e| format!("Can't use {}: {}", answer, e))
+    })?;
+    let run_mode = ask(input, output, "Run mode", Some("api"), |answer| RunModes::parse(answer).map(|_| ()))?;
+
+    let mut lines = vec![
+        "# Written by `reminderBot init`; see the README for every setting.".to_string(),
+        format!("DISCORD_CLIENT_SECRET={}", token),
+        format!("OPENAI_API_KEY={}", openai_key),
+        format!("DB_LOCATION={}", db_location),
+        format!("RUN_MODE={}", run_mode),
+    ];
+    if !operator_channel.is_empty() {
+        lines.push(format!("OPERATOR_CHANNEL_ID={}", operator_channel));
+    }
+    std::fs::write(path, lines.join("\n") + "\n").map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    let config = AppConfig::from_file(path)?;
+    if config.get("DISCORD_CLIENT_SECRET").as_deref() != Some(token.as_str()) {
+        return Err(format!("{} didn't read back as written", path));
+    }
+    Ok(format!(
+        "Saved {}. Reminder times default to New York; everyone can pick their own with /settings timezone.",
+        path
+    ))
+}
+
+fn init_command(args: &[String]) -> Result<String, String> {
+    let force = args.iter().any(|arg| arg == "--force");
+    let args: Vec<String> = args.iter().filter(|arg| *arg != "--force").cloned().collect();
+    let (options, extra) = parse_options(&args, &["--config"])?;
+    if !extra.is_empty() {
+        return Err("Usage: init [--config <path>] [--force]".to_string());
+    }
+    let path = options
+        .get("--config")
+        .map(|path| path.to_string())
+        .or_else(|| std::env::var("CONFIG_FILE").ok())
+        .unwrap_or_else(|| "./config.properties".to_string());
+    if !force && std::path::Path::new(&path).exists() {
+        return Err(format!("{} already exists; pass --force to replace it", path));
+    }
+    run_init(&mut io::stdin().lock(), &mut io::stdout(), &path)
+}
+
+struct CommandSpec {
+    name: &'static str,
+    usage: &'static str,
+    about: &'static str,
+    // Offered after the command name; bare words are subcommands.
+    words: &'static [&'static str],
+}
+
+// Every command the binary takes, for completions and the man page. Keep in
+// step with the dispatch here and in main.
+const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "list",
+        usage: "list [--user <id>] [--output json|table|plain]",
+        about: "List every reminder, soonest first.",
+        words: &["--user", "--output"],
+    },
+    CommandSpec {
+        name: "show",
+        usage: "show <id> [--output json|table|plain]",
+        about: "Show one reminder in full.",
+        words: &["--output"],
+    },
+    CommandSpec {
+        name: "create",
+        usage: "create --user <id> --channel <id> --at <time> <text>",
+        about: "Create a reminder at a given time.",
+        words: &["--user", "--channel", "--at", "--output"],
+    },
+    CommandSpec {
+        name: "edit",
+        usage: "edit <id> [--text <text>] [--at <time>]",
+        about: "Change a reminder's text or time.",
+        words: &["--text", "--at", "--output"],
+    },
+    CommandSpec {
+        name: "delete",
+        usage: "delete <id>",
+        about: "Delete a reminder.",
+        words: &[],
+    },
+    CommandSpec {
+        name: "todo",
+        usage: "todo [list|add|done|delete] ...",
+        about: "List, add, complete or delete todos.",
+        words: &["list", "add", "done", "delete", "--user", "--output"],
+    },
+    CommandSpec {
+        name: "prompt",
+        usage: "prompt --user <id> --channel <id> [--dry-run] [--force] <text>",
+        about: "Create a reminder from a request read by OpenAI.",
+        words: &["--user", "--channel", "--dry-run", "--force"],
+    },
+    CommandSpec {
+        name: "repl",
+        usage: "repl --user <id> --channel <id>",
+        about: "Create reminders and todos conversationally.",
+        words: &["--user", "--channel"],
+    },
+    CommandSpec {
+        name: "import",
+        usage: "import --ics <file> --user <id> --channel <id> [--yes] | import --file <jsonl> [--user <id>] [--channel <id>]",
+        about: "Import reminders from an iCalendar file or a JSON lines file.",
+        words: &["--ics", "--file", "--user", "--channel", "--yes"],
+    },
+    CommandSpec {
+        name: "export",
+        usage: "export --ics [--user <id>]",
+        about: "Print the saved reminders as an iCalendar feed.",
+        words: &["--ics", "--user"],
+    },
+    CommandSpec {
+        name: "history",
+        usage: "history <user_id> [--status <status>] [--days <n>]",
+        about: "List what the bot drafted or did for a user.",
+        words: &["--status", "--days"],
+    },
+    CommandSpec {
+        name: "events",
+        usage: "events tail [n] [--user <id>]",
+        about: "Print the latest entries of the event log.",
+        words: &["tail", "--user"],
+    },
+    CommandSpec {
+        name: "watch",
+        usage: "watch [--user <id>] [--from-start]",
+        about: "Follow a running worker's deliveries, failures and expirations.",
+        words: &["--user", "--from-start"],
+    },
+    CommandSpec {
+        name: "route-eval",
+        usage: "route-eval --corpus <file.jsonl> [--router configured|heuristic]",
+        about: "Score an intent router against labeled requests.",
+        words: &["--corpus", "--router"],
+    },
+    CommandSpec {
+        name: "calendar",
+        usage: "calendar auth",
+        about: "Authorize access to the configured calendar.",
+        words: &["auth"],
+    },
+    CommandSpec {
+        name: "simulate",
+        usage: "simulate <script.jsonl>",
+        about: "Replay scripted interactions and print the replies.",
+        words: &[],
+    },
+    CommandSpec {
+        name: "init",
+        usage: "init [--config <path>] [--force]",
+        about: "Write a first config file interactively.",
+        words: &["--config", "--force"],
+    },
+    CommandSpec {
+        name: "completions",
+        usage: "completions <bash|zsh|fish>",
+        about: "Print a shell completion script.",
+        words: &["bash", "zsh", "fish"],
+    },
+    CommandSpec {
+        name: "man",
+        usage: "man",
+        about: "Print this manual page.",
+        words: &[],
+    },
+];
+
+const BIN: &str = "reminderBot";
+
+fn completions_command(args: &[String]) -> Result<String, String> {
+    let names: Vec<&str> = COMMANDS.iter().map(|command| command.name).collect();
+    let script = match args {
+        [shell] if shell == "bash" => {
+            let mut cases = String::new();
+            for command in COMMANDS.iter().filter(|command| !command.words.is_empty()) {
+                cases.push_str(&format!(
+                    "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n",
+                    command.name,
+                    command.words.join(" ")
+                ));
+            }
+            format!(
+                "_{bin}() {{\n    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    if [ \"$COMP_CWORD\" -eq 1 ]; then\n        COMPREPLY=($(compgen -W \"{names}\" -- \"$cur\"))\n        return\n    fi\n    case \"${{COMP_WORDS[1]}}\" in\n{cases}    esac\n}}\ncomplete -o default -F _{bin} {bin}",
+                bin = BIN,
+                names = names.join(" "),
+                cases = cases
+            )
+        }
+        [shell] if shell == "zsh" => {
+            let described: Vec<String> = COMMANDS
+                .iter()
+                .map(|command| format!("'{}:{}'", command.name, command.about.replace('\'', "")))
+                .collect();
+            let mut cases = String::new();
+            for command in COMMANDS.iter().filter(|command| !command.words.is_empty()) {
+                cases.push_str(&format!("    {}) compadd -- {} ;;\n", command.name, command.words.join(" ")));
+            }
+            format!(
+                "#compdef {bin}\n_{bin}() {{\n  local -a commands\n  commands=({described})\n  if (( CURRENT == 2 )); then\n    _describe 'command' commands\n    return\n  fi\n  case $words[2] in\n{cases}    *) _files ;;\n  esac\n}}\ncompdef _{bin} {bin}",
+                bin = BIN,
+                described = described.join(" "),
+                cases = cases
+            )
+        }
+        [shell] if shell == "fish" => {
+            let mut lines = Vec::new();
+            for command in COMMANDS {
+                lines.push(format!(
+                    "complete -c {} -n __fish_use_subcommand -a {} -d '{}'",
+                    BIN,
+                    command.name,
+                    command.about.replace('\'', "")
+                ));
+                for word in command.words {
+                    let offer = match word.strip_prefix("--") {
+                        Some(long) => format!("-l {}", long),
+                        None => format!("-a {}", word),
+                    };
+                    lines.push(format!(
+                        "complete -c {} -n '__fish_seen_subcommand_from {}' {}",
+                        BIN, command.name, offer
+                    ));
+                }
+            }
+            lines.join("\n")
+        }
+        _ => return Err("Usage: completions <bash|zsh|fish>".to_string()),
+    };
+    Ok(script)
+}
+
+fn roff(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+pub fn render_man_page() -> String {
+    let mut page = vec![
+        format!(".TH {} 1", BIN.to_uppercase()),
+        ".SH NAME".to_string(),
+        format!("{} \\- Discord reminder bot and its command line tools", BIN),
+        ".SH SYNOPSIS".to_string(),
+        format!(".B {}", BIN),
+        "[\\fIcommand\\fR] [\\fIoptions\\fR]".to_string(),
+        ".SH DESCRIPTION".to_string(),
+        roff("Without a command the bot runs the components chosen by RUN_MODE. The commands below work on the same data directory without connecting to Discord."),
+        ".SH COMMANDS".to_string(),
+    ];
+    for command in COMMANDS {
+        page.push(".TP".to_string());
+        page.push(format!(".B {}", roff(command.usage)));
+        page.push(roff(command.about));
+    }
+    page.extend([
+        ".SH ENVIRONMENT".to_string(),
+        ".TP".to_string(),
+        ".B CONFIG_FILE".to_string(),
+        roff("The config file to read (default ./config.properties)."),
+        ".TP".to_string(),
+        ".B DB_LOCATION".to_string(),
+        roff("Where reminders, todos and settings are kept (default ./data)."),
+    ]);
+    page.join("\n")
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use reqwest::Method;
+use reqwest::header::CONTENT_TYPE;
+
+use crate::service::ics;
+use crate::tasks::calendar_loop::{CalendarClient, CalendarEvent, SyncTarget};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalDavConfig {
+    // The calendar collection itself, e.g.
+    // https://cloud.example.com/remote.php/dav/calendars/alice/personal/
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub target: SyncTarget,
+}
+
+// A calendar on a CalDAV server (Nextcloud, Radicale, Baïkal, ...), reached
+// with HTTP basic auth. Servers expand repeating events into occurrences for
+// the requested day.
+pub struct CalDavClient {
+    http: reqwest::Client,
+    config: CalDavConfig,
+}
+
+impl CalDavClient {
+    pub fn new(config: CalDavConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    fn collection_url(&self) -> String {
+        format!("{}/", self.config.url.trim_end_matches('/'))
+    }
+}
+
+fn calendar_query(start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    let (start, end) = (start.format("%Y%m%dT%H%M%SZ"), end.format("%Y%m%dT%H%M%SZ"));
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <c:calendar-data><c:expand start="{start}" end="{end}"/></c:calendar-data>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT"><c:time-range start="{start}" end="{end}"/></c:comp-filter>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#
+    )
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&#13;", "\r")
+        .replace("&#xD;", "\r")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// Timed events from a calendar-query multistatus response. Each
+// calendar-data element holds one calendar object; the XML around them is
+// skipped rather than parsed.
+pub fn parse_multistatus(body: &str) -> Vec<CalendarEvent> {
+    const END: &str = "END:VCALENDAR";
+    let mut events = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("BEGIN:VCALENDAR") {
+        let Some(len) = rest[start..].find(END) else {
+            break;
+        };
+        let end = start + len + END.len();
+        events.extend(ics::calendar_events(&xml_unescape(&rest[start..end])));
+        rest = &rest[end..];
+    }
+    events
+}
+
+#[serenity::async_trait]
+impl CalendarClient for CalDavClient {
+    async fn get_events_for_day(&self, day: NaiveDate) -> Result<Vec<CalendarEvent>, String> {
+        let start = day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        // The time-range filter also matches events that started the day
+        // before and run into this one.
+        Ok(self
+            .get_events_between(start, start + Duration::days(1))
+            .await?
+            .into_iter()
+            .filter(|event| event.start_time.date_naive() == day)
+            .collect())
+    }
+
+    // A CalDAV time-range filter matches every event overlapping the range.
+    async fn get_events_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let report = Method::from_bytes(b"REPORT").map_err(|e| e.to_string())?;
+        let body = self
+            .http
+            .request(report, self.collection_url())
+            .basic_auth(&self.config.username, Some(&self.config.password))
+            .header("Depth", "1")
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .body(calendar_query(start, end))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to query the CalDAV calendar: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Failed to query the CalDAV calendar: {}", e))?
+            .text()
+            .await
+            .map_err(|e| format!("Unexpected CalDAV response: {}", e))?;
+        Ok(parse_multistatus(&body))
+    }
+
+    async fn create_event(&self, event: CalendarEvent) -> Result<(), String> {
+        let name = uuid::Uuid::new_v4();
+        let uid = format!("{}@reminderbot", name);
+        self.http
+            .put(format!("{}{}.ics", self.collection_url(), name))
+            .basic_auth(&self.config.username, Some(&self.config.password))
+            .header(CONTENT_TYPE, "text/calendar; charset=utf-8")
+            .header("If-None-Match", "*")
+            .body(ics::render_event(&event, &uid, Utc::now()))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to create calendar event: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Failed to create calendar event: {}", e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// A recorded OpenAI response, keyed by prompt type and the caller's prompt
+// (not the full templated prompt, which embeds the current time).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedInteraction {
+    pub prompt_type: String,
+    pub prompt: String,
+    pub response: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Cassette {
+    path: PathBuf,
+    interactions: HashMap<String, RecordedInteraction>,
+}
+
+fn key(prompt_type: &str, prompt: &str) -> String {
+    format!("{}\n{}", prompt_type, prompt)
+}
+
+impl Cassette {
+    // Missing files load as an empty cassette so recording can start fresh.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let interactions = if path.exists() {
+            let raw = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read cassette {}: {}", path.display(), e))?;
+            let recorded: Vec<RecordedInteraction> = serde_json::from_str(&raw)
+                .map_err(|e| format!("Failed to parse cassette {}: {}", path.display(), e))?;
+            recorded
+                .into_iter()
+                .map(|interaction| (key(&interaction.prompt_type, &interaction.prompt), interaction))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        Ok(Self { path, interactions })
+    }
+
+    pub fn lookup(&self, prompt_type: &str, prompt: &str) -> Option<&str> {
+        self.interactions
+            .get(&key(prompt_type, prompt))
+            .map(|interaction| interaction.response.as_str())
+    }
+
+    pub fn record(&mut self, prompt_type: &str, prompt: &str, response: &str) -> Result<(), String> {
+        self.interactions.insert(
+            key(prompt_type, prompt),
+            RecordedInteraction {
+                prompt_type: prompt_type.to_string(),
+                prompt: prompt.to_string(),
+                response: response.to_string(),
+            },
+        );
+        self.save()
+    }
+
+    // Sorted so re-recording produces stable fixture diffs.
+    fn save(&self) -> Result<(), String> {
+        let mut recorded: Vec<&RecordedInteraction> = self.interactions.values().collect();
+        recorded.sort_by(|a, b| (&a.prompt_type, &a.prompt).cmp(&(&b.prompt_type, &b.prompt)));
+        let body = serde_json::to_string_pretty(&recorded)
+            .map_err(|e| format!("Failed to serialize cassette: {}", e))?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&self.path, body)
+            .map_err(|e| format!("Failed to write cassette {}: {}", self.path.display(), e))
+    }
+}
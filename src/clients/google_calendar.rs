@@ -0,0 +1,301 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::sync::Mutex;
+
+use crate::config::AppConfig;
+use crate::tasks::calendar_loop::{CalendarClient, CalendarEvent, SyncTarget};
+
+const DEVICE_CODE_URL: &str = "https://oauth2.googleapis.com/device/code";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const EVENTS_URL: &str = "https://www.googleapis.com/calendar/v3/calendars";
+const CALENDAR_SCOPE: &str = "https://www.googleapis.com/auth/calendar.events";
+const DEVICE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+// Returns the file the Google OAuth tokens are kept in between runs.
+// Defaults to a relative "./data/google_calendar_token.json" file.
+pub fn get_token_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/google_calendar_token.json", base)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoogleCalendarConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub calendar_id: String,
+    pub target: SyncTarget,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredToken {
+    access_token: String,
+    refresh_token: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeviceCode {
+    device_code: String,
+    pub user_code: String,
+    pub verification_url: String,
+    expires_in: i64,
+    #[serde(default = "default_poll_interval")]
+    interval: u64,
+}
+
+fn default_poll_interval() -> u64 {
+    5
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+    error: Option<String>,
+}
+
+// Google Calendar over its REST API. Authorization uses the OAuth device
+// flow (see `calendar auth`), and the refresh token is saved so the loop can
+// keep going across restarts without asking again.
+pub struct GoogleCalendarClient {
+    http: reqwest::Client,
+    config: GoogleCalendarConfig,
+    token_path: PathBuf,
+    token: Mutex<Option<StoredToken>>,
+}
+
+impl GoogleCalendarClient {
+    pub fn new(config: GoogleCalendarConfig) -> Self {
+        Self::with_token_path(config, get_token_location())
+    }
+
+    pub fn with_token_path(config: GoogleCalendarConfig, token_path: impl AsRef<Path>) -> Self {
+        let token_path = token_path.as_ref().to_path_buf();
+        let token = fs::read_to_string(&token_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok());
+        Self {
+            http: reqwest::Client::new(),
+            config,
+            token_path,
+            token: Mutex::new(token),
+        }
+    }
+
+    pub async fn is_authorized(&self) -> bool {
+        self.token.lock().await.is_some()
+    }
+
+    fn save_token(&self, token: &StoredToken) -> Result<(), String> {
+        if let Some(parent) = self.token_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let raw = serde_json::to_string(token).map_err(|e| e.to_string())?;
+        fs::write(&self.token_path, raw)
+            .map_err(|e| format!("Failed to save {}: {}", self.token_path.display(), e))
+    }
+
+    // Runs the device flow: `announce` is given the URL and code the user
+    // enters, then Google is polled until they approve, refuse or the code
+    // lapses.
+    pub async fn authorize_device(&self, announce: impl Fn(&DeviceCode)) -> Result<(), String> {
+        let device: DeviceCode = self
+            .http
+            .post(DEVICE_CODE_URL)
+            .form(&[("client_id", self.config.client_id.as_str()), ("scope", CALENDAR_SCOPE)])
+            .send()
+            .await
+            .map_err(|e| format!("Failed to request a device code: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected device code response: {}", e))?;
+        announce(&device);
+
+        let deadline = Utc::now() + Duration::seconds(device.expires_in);
+        let mut interval = device.interval;
+        while Utc::now() < deadline {
+            tokio::time::sleep(StdDuration::from_secs(interval)).await;
+            let response = self
+                .request_token(&[
+                    ("client_id", self.config.client_id.as_str()),
+                    ("client_secret", self.config.client_secret.as_str()),
+                    ("device_code", device.device_code.as_str()),
+                    ("grant_type", DEVICE_GRANT),
+                ])
+                .await?;
+            match response.error.as_deref() {
+                Some("authorization_pending") => continue,
+                Some("slow_down") => interval += 5,
+                Some(other) => return Err(format!("Google refused authorization: {}", other)),
+                None => {
+                    let token = StoredToken {
+                        access_token: response.access_token.ok_or("Token response had no access token")?,
+                        refresh_token: response.refresh_token.ok_or("Token response had no refresh token")?,
+                        expires_at: Utc::now() + Duration::seconds(response.expires_in.unwrap_or(3600)),
+                    };
+                    self.save_token(&token)?;
+                    *self.token.lock().await = Some(token);
+                    return Ok(());
+                }
+            }
+        }
+        Err("The device code expired before it was approved".to_string())
+    }
+
+    async fn request_token(&self, form: &[(&str, &str)]) -> Result<TokenResponse, String> {
+        self.http
+            .post(TOKEN_URL)
+            .form(form)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Google: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected token response: {}", e))
+    }
+
+    // Refreshed a minute early so a request never goes out with a token
+    // that lapses mid-flight.
+    async fn access_token(&self) -> Result<String, String> {
+        let mut guard = self.token.lock().await;
+        let token = guard
+            .as_mut()
+            .ok_or("Google Calendar isn't authorized yet; run `reminderBot calendar auth`")?;
+        if token.expires_at > Utc::now() + Duration::seconds(60) {
+            return Ok(token.access_token.clone());
+        }
+        let response = self
+            .request_token(&[
+                ("client_id", self.config.client_id.as_str()),
+                ("client_secret", self.config.client_secret.as_str()),
+                ("refresh_token", token.refresh_token.as_str()),
+                ("grant_type", "refresh_token"),
+            ])
+            .await?;
+        if let Some(error) = response.error {
+            return Err(format!("Failed to refresh the Google token: {}", error));
+        }
+        token.access_token = response.access_token.ok_or("Refresh response had no access token")?;
+        token.expires_at = Utc::now() + Duration::seconds(response.expires_in.unwrap_or(3600));
+        if let Some(refresh_token) = response.refresh_token {
+            token.refresh_token = refresh_token;
+        }
+        self.save_token(token)?;
+        Ok(token.access_token.clone())
+    }
+
+    fn events_url(&self) -> String {
+        format!("{}/{}/events", EVENTS_URL, self.config.calendar_id)
+    }
+}
+
+// `calendar auth`: walks the operator through the device flow and saves the
+// token the calendar loop uses.
+pub async fn auth_command(config: &AppConfig) -> Result<String, String> {
+    let settings = config
+        .google_calendar()
+        .ok_or("Set GOOGLE_CLIENT_ID, GOOGLE_CLIENT_SECRET, GOOGLE_CALENDAR_USER_ID and GOOGLE_CALENDAR_CHANNEL_ID first")?;
+    let client = GoogleCalendarClient::new(settings);
+    client
+        .authorize_device(|device| {
+            tracing::info!(
+                url = %device.verification_url,
+                code = %device.user_code,
+                "visit the url and enter the code to let the bot read and add calendar events"
+            );
+        })
+        .await?;
+    Ok(format!("Authorized. Token saved to {}.", client.token_path.display()))
+}
+
+// Timed events from an events.list response. All-day events have no time to
+// remind at and are left out.
+pub fn parse_events(body: &Value) -> Vec<CalendarEvent> {
+    let time = |event: &Value, key: &str| {
+        event[key]["dateTime"]
+            .as_str()
+            .and_then(|raw| DateTime::parse_from_rfc3339(raw).ok())
+            .map(|at| at.with_timezone(&Utc))
+    };
+    body["items"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(|event| event["status"].as_str() != Some("cancelled"))
+        .filter_map(|event| {
+            let start_time = time(event, "start")?;
+            Some(CalendarEvent {
+                id: event["id"].as_str().map(str::to_string),
+                title: event["summary"].as_str().unwrap_or("(untitled event)").to_string(),
+                start_time,
+                end_time: time(event, "end").unwrap_or(start_time),
+                description: event["description"].as_str().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+#[serenity::async_trait]
+impl CalendarClient for GoogleCalendarClient {
+    async fn get_events_for_day(&self, day: NaiveDate) -> Result<Vec<CalendarEvent>, String> {
+        let start = day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        self.get_events_between(start, start + Duration::days(1)).await
+    }
+
+    // The API itself returns every event overlapping the range.
+    async fn get_events_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let token = self.access_token().await?;
+        let body: Value = self
+            .http
+            .get(self.events_url())
+            .bearer_auth(token)
+            .query(&[
+                ("timeMin", start.to_rfc3339()),
+                ("timeMax", end.to_rfc3339()),
+                ("singleEvents", "true".to_string()),
+                ("orderBy", "startTime".to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Failed to list calendar events: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Failed to list calendar events: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected calendar events response: {}", e))?;
+        Ok(parse_events(&body))
+    }
+
+    async fn create_event(&self, event: CalendarEvent) -> Result<(), String> {
+        let token = self.access_token().await?;
+        let body = json!({
+            "summary": event.title,
+            "description": event.description,
+            "start": { "dateTime": event.start_time.to_rfc3339() },
+            "end": { "dateTime": event.end_time.to_rfc3339() },
+        });
+        self.http
+            .post(self.events_url())
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to create calendar event: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Failed to create calendar event: {}", e))?;
+        Ok(())
+    }
+}
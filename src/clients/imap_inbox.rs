@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use async_imap::Session;
+use futures::TryStreamExt;
+use rustls::pki_types::ServerName;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+
+use crate::tasks::email_loop::Mailbox;
+
+pub const DEFAULT_IMAP_PORT: u16 = 993;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImapConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    // The folder reminder requests arrive in, usually INBOX.
+    pub folder: String,
+}
+
+// A mailbox read over IMAP with TLS (rustls, like the rest of the bot's
+// connections). Each call opens its own short-lived session.
+pub struct ImapInbox {
+    config: ImapConfig,
+    tls: TlsConnector,
+}
+
+impl ImapInbox {
+    pub fn new(config: ImapConfig) -> Self {
+        let roots = rustls::RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        let tls = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .expect("ring supports the default TLS versions")
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Self {
+            config,
+            tls: TlsConnector::from(Arc::new(tls)),
+        }
+    }
+
+    async fn session(&self) -> Result<Session<TlsStream<TcpStream>>, String> {
+        let config = &self.config;
+        let tcp = TcpStream::connect((config.host.as_str(), config.port))
+            .await
+            .map_err(|err| format!("connecting to {}: {}", config.host, err))?;
+        let server_name = ServerName::try_from(config.host.clone()).map_err(|err| err.to_string())?;
+        let tls = self
+            .tls
+            .connect(server_name, tcp)
+            .await
+            .map_err(|err| format!("TLS with {}: {}", config.host, err))?;
+        let mut client = async_imap::Client::new(tls);
+        client
+            .read_response()
+            .await
+            .map_err(|err| err.to_string())?
+            .ok_or("server closed the connection before greeting")?;
+        let mut session = client
+            .login(&config.username, &config.password)
+            .await
+            .map_err(|(err, _)| format!("logging in: {}", err))?;
+        session.select(&config.folder).await.map_err(|err| err.to_string())?;
+        Ok(session)
+    }
+}
+
+async fn logout(mut session: Session<TlsStream<TcpStream>>) {
+    if let Err(err) = session.logout().await {
+        tracing::debug!(error = %err, "IMAP logout failed");
+    }
+}
+
+fn uid_set(uids: &[u32]) -> String {
+    uids.iter().map(u32::to_string).collect::<Vec<_>>().join(",")
+}
+
+#[serenity::async_trait]
+impl Mailbox for ImapInbox {
+    // BODY.PEEK[] leaves the messages unseen; mark_seen settles them once
+    // they've been handled.
+    async fn fetch_unseen(&self) -> Result<Vec<(u32, Vec<u8>)>, String> {
+        let mut session = self.session().await?;
+        let mut uids: Vec<u32> = session
+            .uid_search("UNSEEN")
+            .await
+            .map_err(|err| err.to_string())?
+            .into_iter()
+            .collect();
+        uids.sort_unstable();
+        let mut messages = Vec::new();
+        if !uids.is_empty() {
+            let fetched: Vec<_> = session
+                .uid_fetch(uid_set(&uids), "(UID BODY.PEEK[])")
+                .await
+                .map_err(|err| err.to_string())?
+                .try_collect()
+                .await
+                .map_err(|err| err.to_string())?;
+            messages = fetched
+                .iter()
+                .filter_map(|fetch| Some((fetch.uid?, fetch.body()?.to_vec())))
+                .collect();
+        }
+        logout(session).await;
+        Ok(messages)
+    }
+
+    async fn mark_seen(&self, uids: &[u32]) -> Result<(), String> {
+        if uids.is_empty() {
+            return Ok(());
+        }
+        let mut session = self.session().await?;
+        let _: Vec<_> = session
+            .uid_store(uid_set(uids), "+FLAGS (\\Seen)")
+            .await
+            .map_err(|err| err.to_string())?
+            .try_collect()
+            .await
+            .map_err(|err| err.to_string())?;
+        logout(session).await;
+        Ok(())
+    }
+}
@@ -1 +1,6 @@
+pub mod caldav;
+pub mod cassette;
+pub mod google_calendar;
+pub mod imap_inbox;
 pub mod openai_client;
+pub mod outlook_calendar;
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use chrono::Utc;
 use chrono::DateTime;
+use chrono_tz::Tz;
 use reqwest;
+use crate::config::AppConfig;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct OpenAIMessage {
@@ -36,7 +38,9 @@ struct Message {
 pub async fn generate_openai_prompt(
     prompt: &str,
     prompt_type: &str,
+    zone: Tz,
     api_key: &str,
+    config: &AppConfig,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let now: DateTime<Utc> = Utc::now();
 
@@ -44,12 +48,20 @@ pub async fn generate_openai_prompt(
         "notification" => format!(
             "You are a notification extraction engine.\n\
              Current date and time (UTC): {now}\n\
-             User timezone: America/New_York\n\
+             User timezone: {zone}\n\
              Task: From the user message below, extract:\n\
              - \"content\": the core notification text with extraneous scheduling words removed. For example:\n\
                - \"buy eggs tomorrow\" -> \"buy eggs\"\n\
                - \"notify me to call mom at 5\" -> \"call mom\"\n\
              - \"time\": an RFC3339 datetime string in the user's timezone.\n\
+             Optionally also extract, omitting any field you cannot determine:\n\
+             - \"recurrence\": the repeat phrase as written (e.g. \"every Friday until March\"), or null for one-off reminders.\n\
+             - \"category\": one of \"bill\", \"birthday\", \"meeting\", \"health\", \"chore\", or null.\n\
+             - \"target\": who should be reminded (\"me\" unless the user names someone else).\n\
+             - \"offsets\": minutes before the event to send reminders, only if the user asks (e.g. \"remind me 30 minutes before\" -> [30]).\n\
+             - \"original_phrase\": the exact time expression from the message.\n\
+             - \"confidence\": a number from 0 to 1 for how sure you are about the time.\n\
+             - \"checklist\": items the user lists to bring or do for the event (e.g. \"pack passport, charger and snacks\" -> [\"passport\", \"charger\", \"snacks\"]); leave them out of \"content\".\n\
              Rules:\n\
              - If the user gives an explicit date like \"December 6th\", use that exact month and day at noon in the local timezone; do NOT change them.\n\
              - If the year is omitted, assume the next occurrence of that date on or after the current date.\n\
@@ -62,26 +74,29 @@ pub async fn generate_openai_prompt(
              - If the message contains a \"Context notes\" or \"Additional context\" section, never copy that text into \"content\".\n\
              - Never invent or adjust the date away from what the user wrote; only add a year or time if needed.\n\
              - Output ONLY raw JSON, no prose, markdown, or code fences.\n\
-             - The JSON shape must be exactly:\n\
-             {{\"content\":\"<string>\",\"time\":\"<RFC3339 datetime>\"}}\n\
+             - The JSON shape must be:\n\
+             {{\"content\":\"<string>\",\"time\":\"<RFC3339 datetime>\",\"recurrence\":<string|null>,\"category\":<string|null>,\"target\":<string|null>,\"offsets\":[<minutes>],\"original_phrase\":<string|null>,\"confidence\":<number>,\"checklist\":[<string>]}}\n\
              User message: \"{user_prompt}\"",
             now = now.to_rfc3339(),
+            zone = zone.name(),
             user_prompt = prompt
         ),
         "notification_correction" => format!(
             "You are a notification correction engine.\n\
              Current date and time (UTC): {now}\n\
-             User timezone: America/New_York\n\
+             User timezone: {zone}\n\
              Task: Given the original notification request and a user-provided correction note, output a corrected notification.\n\
              Rules:\n\
              - The correction note is NOT notification content. It is only for fixing the date/time or clarifying intent.\n\
              - Preserve the original notification content unless the correction explicitly changes it.\n\
              - If the correction only adjusts time (e.g. \"actually I meant this Saturday\"), update only the time.\n\
              - Output ONLY raw JSON, no prose, markdown, or code fences.\n\
-             - The JSON shape must be exactly:\n\
-             {{\"content\":\"<string>\",\"time\":\"<RFC3339 datetime>\"}}\n\
+             - Keep any recurrence, category, target or offsets from the original request unless the correction changes them.\n\
+             - The JSON shape must be:\n\
+             {{\"content\":\"<string>\",\"time\":\"<RFC3339 datetime>\",\"recurrence\":<string|null>,\"category\":<string|null>,\"target\":<string|null>,\"offsets\":[<minutes>],\"original_phrase\":<string|null>,\"confidence\":<number>}}\n\
              Original request: \"{user_prompt}\"",
             now = now.to_rfc3339(),
+            zone = zone.name(),
             user_prompt = prompt
         ),
         "notification_message" => format!(
@@ -94,45 +109,119 @@ pub async fn generate_openai_prompt(
              - Include the notification content naturally.\n\
              - If hours remaining is provided, include it in a friendly way.\n\
              - Keep it to 1–2 sentences, no markdown, no lists, no JSON.\n\
+             - Write in the given voice: normal is friendly, formal is polite and professional, playful is light-hearted (one emoji is fine), terse is as few words as possible in a single sentence.\n\
              - Do NOT wrap the output in quotes.\n\
              Structured input:\n\
              {structured}",
             now = now.to_rfc3339(),
             structured = prompt
         ),
+        "todo_extraction" => format!(
+            "You are a todo extraction engine.\n\
+             Task: From the user message below, extract the todo items the user wants to keep track of.\n\
+             Rules:\n\
+             - Each item is a short imperative phrase with filler removed (e.g. \"I really need to finish the report\" -> \"finish the report\").\n\
+             - Split lists into separate items (e.g. \"buy milk and call the plumber\" -> [\"buy milk\", \"call the plumber\"]).\n\
+             - Never invent items the user did not mention.\n\
+             - Output ONLY raw JSON, no prose, markdown, or code fences.\n\
+             - The JSON shape must be:\n\
+             {{\"items\":[<string>]}}\n\
+             User message: \"{user_prompt}\"",
+            user_prompt = prompt
+        ),
+        "tool_selection" => format!(
+            "You are a tool selection engine for a personal assistant bot.\n\
+             Current date and time (UTC): {now}\n\
+             User timezone: {zone}\n\
+             Task: Below is a list of tools, each with a JSON schema for its parameters, followed by a user message. Pick the one tool that does what the user asks and fill in its arguments.\n\
+             Rules:\n\
+             - Only use a tool from the list, by its exact name.\n\
+             - Arguments must match the tool's schema; give datetimes as RFC3339 strings in UTC.\n\
+             - If no tool fits, or a required argument can't be worked out from the message, set \"tool\" to null.\n\
+             - Output ONLY raw JSON, no prose, markdown, or code fences.\n\
+             - The JSON shape must be:\n\
+             {{\"tool\":<string|null>,\"arguments\":{{...}}}}\n\
+             {structured}",
+            now = now.to_rfc3339(),
+            zone = zone.name(),
+            structured = prompt
+        ),
+        "calendar_event_extraction" => format!(
+            "You are a calendar event extraction engine.\n\
+             Current date and time (UTC): {now}\n\
+             User timezone: {zone}\n\
+             Task: From the user message below, extract the calendar event the user wants created.\n\
+             Rules:\n\
+             - \"title\" is a short name for the event with the scheduling words removed (e.g. \"Schedule a 30-minute sync with Alex on Thursday at 2\" -> \"Sync with Alex\").\n\
+             - \"start\" is when it begins; a bare hour like \"at 2\" means the next daytime occurrence (2pm). Weekday rules match reminders: \"Thursday\" is the next one on or after today, \"next Thursday\" the one in the following week.\n\
+             - \"end\" is start plus the length the user gives (\"30-minute\", \"for an hour\"), or null if they give none.\n\
+             - \"description\" holds any extra details (agenda, location, attendees), or null.\n\
+             - Datetimes are RFC3339 strings in UTC.\n\
+             - Output ONLY raw JSON, no prose, markdown, or code fences.\n\
+             - The JSON shape must be:\n\
+             {{\"title\":\"<string>\",\"start\":\"<RFC3339 datetime>\",\"end\":<RFC3339 datetime|null>,\"description\":<string|null>}}\n\
+             User message: \"{user_prompt}\"",
+            now = now.to_rfc3339(),
+            zone = zone.name(),
+            user_prompt = prompt
+        ),
         "intent_router" => format!(
             "You are an intent router for a notification bot.\n\
              Current date and time (UTC): {now}\n\
-             User timezone: America/New_York\n\
              Task: Classify the user's message into one of these intents:\n\
              - notification: requests that include a time/date for a notification\n\
              - todolist: requests to create or update a todo list without a time\n\
+             - tooluse: requests to put an event on the user's calendar, e.g. scheduling a meeting or a call with someone\n\
+             - query: questions about the user's existing reminders, e.g. \"what do I have this week\"\n\
+             - cancel: requests to cancel or delete an existing reminder, e.g. \"cancel the dentist one\"\n\
+             - snooze: requests to push an existing reminder back, e.g. \"snooze my rent reminder a day\"\n\
              - unknown: unclear or missing time/action\n\
              Rules:\n\
+             - If the message is about a reminder the user already has, choose query, cancel or snooze even if it has a time.\n\
              - If the message contains any explicit or implicit time/date (e.g., \"tomorrow\", \"next week\", weekdays, months, \"at 5pm\"), choose notification.\n\
+             - If the message asks to schedule a meeting or add something to a calendar, choose tooluse even if it has a time.\n\
              - If the message contains do, or finish, or check or similar words, its a todolist. \n\
+             - \"confidence\" is how sure you are of the intent, from 0.0 to 1.0. Go below 0.5 when another intent would be a reasonable reading too.\n\
+             - If the message asks for several separate things (e.g. \"add buy milk to my list and remind me to call mom at 6\"), return one entry per request, in order, each with only its own part of the text. Otherwise return a single entry.\n\
              Output ONLY raw JSON, no prose, markdown, or code fences.\n\
              The JSON shape must be exactly:\n\
-             {{\"intent\":\"notification|todolist|unknown\",\"normalized_text\":\"<cleaned user text>\"}}\n\
+             {{\"intents\":[{{\"intent\":\"notification|todolist|tooluse|query|cancel|snooze|unknown\",\"normalized_text\":\"<cleaned text of this request>\",\"confidence\":<number>}}]}}\n\
              User message: \"{user_prompt}\"",
             now = now.to_rfc3339(),
             user_prompt = prompt
         ),
         _ => return Err("Not a valid base prompt".to_string().into()),
     };
+    let full_prompt = match config.prompt_template(prompt_type) {
+        Some(template) => template
+            .replace("{now}", &now.to_rfc3339())
+            .replace("{zone}", zone.name())
+            .replace("{prompt}", prompt),
+        None => full_prompt,
+    };
 
-    query_openai(full_prompt, prompt_type, api_key).await
+    query_openai(full_prompt, prompt_type, api_key, &config.openai_model()).await
 }
 
 async fn query_openai(
     prompt: String,
     prompt_type: &str,
     api_key: &str,
+    model: &str,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let system_message = match prompt_type {
         "notification" | "notification_correction" => {
             "You are a strict JSON notification extraction engine. You read instructions and a user message and reply ONLY with a single JSON object, with no markdown, no backticks, and no extra text. If the user gives an explicit date (e.g. \"December 6th\"), you preserve that exact month and day and only fill in missing year/time according to the instructions."
         }
+        "tool_selection" => {
+            "You are a strict JSON tool selector. Reply ONLY with a single JSON object, with no markdown, no backticks, and no extra text."
+        }
+        "calendar_event_extraction" => {
+            "You are a strict JSON calendar event extraction engine. Reply ONLY with a single JSON object, with no markdown, no backticks, and no extra text."
+        }
+        "todo_extraction" => {
+            "You are a strict JSON todo extraction engine. Reply ONLY with a single JSON object, with no markdown, no backticks, and no extra text."
+        }
         "intent_router" => {
             "You are a strict JSON intent router. Reply ONLY with a single JSON object, with no markdown, no backticks, and no extra text."
         }
@@ -143,7 +232,7 @@ async fn query_openai(
     };
 
     let request: OpenAIRequest = OpenAIRequest {
-        model: "gpt-4o-mini".to_string(),
+        model: model.to_string(),
         messages: vec![
             OpenAIMessage {
                 role: "system".to_string(),
@@ -172,7 +261,7 @@ async fn query_openai(
         
         if !status.is_success() {
             // Non-2xx response — show raw body for debugging
-            println!("Error {}: {}", status, text);
+            tracing::warn!(%status, body = %text, "OpenAI request failed");
             return Err(format!("Request failed with status {}", status).into());
         }
         
@@ -188,7 +277,7 @@ async fn query_openai(
         if let Some(choice) = parsed.choices.first() {
             Ok(choice.message.content.clone())
         } else {
-            println!("No choices found in response.\nRaw body:\n{}", text);
+            tracing::warn!(body = %text, "OpenAI response had no choices");
             Err("No response from OpenAI".to_string().into())
         }
 }
@@ -0,0 +1,332 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::sync::Mutex;
+
+use crate::config::AppConfig;
+use crate::tasks::calendar_loop::{CalendarClient, CalendarEvent, SyncTarget};
+
+const LOGIN_URL: &str = "https://login.microsoftonline.com";
+const GRAPH_URL: &str = "https://graph.microsoft.com/v1.0/me";
+const CALENDAR_SCOPE: &str = "offline_access Calendars.ReadWrite";
+const DEVICE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+// Returns the file the Microsoft OAuth tokens are kept in between runs.
+// Defaults to a relative "./data/outlook_calendar_token.json" file.
+pub fn get_token_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/outlook_calendar_token.json", base)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlookCalendarConfig {
+    // An app registration allowing public client flows; no secret needed.
+    pub client_id: String,
+    // "common" for any work, school or personal account, or the directory id
+    // to keep sign-ins to one organisation.
+    pub tenant: String,
+    pub target: SyncTarget,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredToken {
+    access_token: String,
+    refresh_token: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeviceCode {
+    device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    expires_in: i64,
+    #[serde(default = "default_poll_interval")]
+    interval: u64,
+}
+
+fn default_poll_interval() -> u64 {
+    5
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+    error: Option<String>,
+}
+
+// Outlook / Microsoft 365 calendars through Microsoft Graph. Like the Google
+// client, it signs in with the OAuth device flow (`calendar auth`) and keeps
+// the refresh token so later runs don't ask again.
+pub struct OutlookCalendarClient {
+    http: reqwest::Client,
+    config: OutlookCalendarConfig,
+    token_path: PathBuf,
+    token: Mutex<Option<StoredToken>>,
+}
+
+impl OutlookCalendarClient {
+    pub fn new(config: OutlookCalendarConfig) -> Self {
+        Self::with_token_path(config, get_token_location())
+    }
+
+    pub fn with_token_path(config: OutlookCalendarConfig, token_path: impl AsRef<Path>) -> Self {
+        let token_path = token_path.as_ref().to_path_buf();
+        let token = fs::read_to_string(&token_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok());
+        Self {
+            http: reqwest::Client::new(),
+            config,
+            token_path,
+            token: Mutex::new(token),
+        }
+    }
+
+    pub async fn is_authorized(&self) -> bool {
+        self.token.lock().await.is_some()
+    }
+
+    fn save_token(&self, token: &StoredToken) -> Result<(), String> {
+        if let Some(parent) = self.token_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let raw = serde_json::to_string(token).map_err(|e| e.to_string())?;
+        fs::write(&self.token_path, raw)
+            .map_err(|e| format!("Failed to save {}: {}", self.token_path.display(), e))
+    }
+
+    fn login_url(&self, endpoint: &str) -> String {
+        format!("{}/{}/oauth2/v2.0/{}", LOGIN_URL, self.config.tenant, endpoint)
+    }
+
+    // Runs the device flow: `announce` is given the URL and code the user
+    // enters, then Microsoft is polled until they approve, refuse or the
+    // code lapses.
+    pub async fn authorize_device(&self, announce: impl Fn(&DeviceCode)) -> Result<(), String> {
+        let device: DeviceCode = self
+            .http
+            .post(self.login_url("devicecode"))
+            .form(&[("client_id", self.config.client_id.as_str()), ("scope", CALENDAR_SCOPE)])
+            .send()
+            .await
+            .map_err(|e| format!("Failed to request a device code: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected device code response: {}", e))?;
+        announce(&device);
+
+        let deadline = Utc::now() + Duration::seconds(device.expires_in);
+        let mut interval = device.interval;
+        while Utc::now() < deadline {
+            tokio::time::sleep(StdDuration::from_secs(interval)).await;
+            let response = self
+                .request_token(&[
+                    ("client_id", self.config.client_id.as_str()),
+                    ("device_code", device.device_code.as_str()),
+                    ("grant_type", DEVICE_GRANT),
+                ])
+                .await?;
+            match response.error.as_deref() {
+                Some("authorization_pending") => continue,
+                Some("slow_down") => interval += 5,
+                Some(other) => return Err(format!("Microsoft refused authorization: {}", other)),
+                None => {
+                    let token = StoredToken {
+                        access_token: response.access_token.ok_or("Token response had no access token")?,
+                        refresh_token: response.refresh_token.ok_or("Token response had no refresh token")?,
+                        expires_at: Utc::now() + Duration::seconds(response.expires_in.unwrap_or(3600)),
+                    };
+                    self.save_token(&token)?;
+                    *self.token.lock().await = Some(token);
+                    return Ok(());
+                }
+            }
+        }
+        Err("The device code expired before it was approved".to_string())
+    }
+
+    async fn request_token(&self, form: &[(&str, &str)]) -> Result<TokenResponse, String> {
+        self.http
+            .post(self.login_url("token"))
+            .form(form)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Microsoft: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected token response: {}", e))
+    }
+
+    // Refreshed a minute early, as for Google. Microsoft rotates refresh
+    // tokens, so the new one is saved each time.
+    async fn access_token(&self) -> Result<String, String> {
+        let mut guard = self.token.lock().await;
+        let token = guard
+            .as_mut()
+            .ok_or("Outlook isn't authorized yet; run `reminderBot calendar auth`")?;
+        if token.expires_at > Utc::now() + Duration::seconds(60) {
+            return Ok(token.access_token.clone());
+        }
+        let response = self
+            .request_token(&[
+                ("client_id", self.config.client_id.as_str()),
+                ("refresh_token", token.refresh_token.as_str()),
+                ("grant_type", "refresh_token"),
+                ("scope", CALENDAR_SCOPE),
+            ])
+            .await?;
+        if let Some(error) = response.error {
+            return Err(format!("Failed to refresh the Microsoft token: {}", error));
+        }
+        token.access_token = response.access_token.ok_or("Refresh response had no access token")?;
+        token.expires_at = Utc::now() + Duration::seconds(response.expires_in.unwrap_or(3600));
+        if let Some(refresh_token) = response.refresh_token {
+            token.refresh_token = refresh_token;
+        }
+        self.save_token(token)?;
+        Ok(token.access_token.clone())
+    }
+}
+
+// `calendar auth` with CALENDAR_PROVIDER=outlook.
+pub async fn auth_command(config: &AppConfig) -> Result<String, String> {
+    let settings = config
+        .outlook_calendar()
+        .ok_or("Set OUTLOOK_CLIENT_ID, OUTLOOK_CALENDAR_USER_ID and OUTLOOK_CALENDAR_CHANNEL_ID first")?;
+    let client = OutlookCalendarClient::new(settings);
+    client
+        .authorize_device(|device| {
+            tracing::info!(
+                url = %device.verification_uri,
+                code = %device.user_code,
+                "visit the url and enter the code to let the bot read and add calendar events"
+            );
+        })
+        .await?;
+    Ok(format!("Authorized. Token saved to {}.", client.token_path.display()))
+}
+
+// Graph gives times as a local wall-clock time plus a zone name. Requests
+// ask for UTC; other IANA zones are converted, anything else is skipped.
+fn graph_time(value: &Value) -> Option<DateTime<Utc>> {
+    let local = NaiveDateTime::parse_from_str(value["dateTime"].as_str()?, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+    match value["timeZone"].as_str().unwrap_or("UTC") {
+        "UTC" => Some(local.and_utc()),
+        zone => zone
+            .parse::<Tz>()
+            .ok()?
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|at| at.with_timezone(&Utc)),
+    }
+}
+
+// Timed events from a calendarView response. All-day and cancelled events
+// are left out, as with Google.
+pub fn parse_graph_events(body: &Value) -> Vec<CalendarEvent> {
+    body["value"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(|event| !event["isAllDay"].as_bool().unwrap_or(false))
+        .filter(|event| !event["isCancelled"].as_bool().unwrap_or(false))
+        .filter_map(|event| {
+            let start_time = graph_time(&event["start"])?;
+            Some(CalendarEvent {
+                id: event["id"].as_str().map(str::to_string),
+                title: event["subject"]
+                    .as_str()
+                    .filter(|subject| !subject.is_empty())
+                    .unwrap_or("(untitled event)")
+                    .to_string(),
+                start_time,
+                end_time: graph_time(&event["end"]).unwrap_or(start_time),
+                description: event["bodyPreview"]
+                    .as_str()
+                    .filter(|preview| !preview.is_empty())
+                    .map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+#[serenity::async_trait]
+impl CalendarClient for OutlookCalendarClient {
+    async fn get_events_for_day(&self, day: NaiveDate) -> Result<Vec<CalendarEvent>, String> {
+        let start = day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        // The view also holds events that started the day before and run
+        // into this one.
+        Ok(self
+            .get_events_between(start, start + Duration::days(1))
+            .await?
+            .into_iter()
+            .filter(|event| event.start_time.date_naive() == day)
+            .collect())
+    }
+
+    // calendarView returns every event overlapping the range.
+    async fn get_events_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let token = self.access_token().await?;
+        let mut events = Vec::new();
+        let mut request = self.http.get(format!("{}/calendarView", GRAPH_URL)).query(&[
+            ("startDateTime", start.to_rfc3339()),
+            ("endDateTime", end.to_rfc3339()),
+            ("$top", "100".to_string()),
+        ]);
+        // calendarView pages its results; each page links to the next.
+        loop {
+            let body: Value = request
+                .bearer_auth(&token)
+                .header("Prefer", "outlook.timezone=\"UTC\"")
+                .send()
+                .await
+                .map_err(|e| format!("Failed to list calendar events: {}", e))?
+                .error_for_status()
+                .map_err(|e| format!("Failed to list calendar events: {}", e))?
+                .json()
+                .await
+                .map_err(|e| format!("Unexpected calendar events response: {}", e))?;
+            events.extend(parse_graph_events(&body));
+            let Some(next) = body["@odata.nextLink"].as_str() else {
+                break;
+            };
+            request = self.http.get(next);
+        }
+        Ok(events)
+    }
+
+    async fn create_event(&self, event: CalendarEvent) -> Result<(), String> {
+        let token = self.access_token().await?;
+        let body = json!({
+            "subject": event.title,
+            "body": { "contentType": "text", "content": event.description.unwrap_or_default() },
+            "start": { "dateTime": event.start_time.format("%Y-%m-%dT%H:%M:%S").to_string(), "timeZone": "UTC" },
+            "end": { "dateTime": event.end_time.format("%Y-%m-%dT%H:%M:%S").to_string(), "timeZone": "UTC" },
+        });
+        self.http
+            .post(format!("{}/events", GRAPH_URL))
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to create calendar event: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Failed to create calendar event: {}", e))?;
+        Ok(())
+    }
+}
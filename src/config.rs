@@ -1,5 +1,94 @@
 use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use chrono::{NaiveTime, Timelike};
+use chrono_tz::Tz;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::clients::caldav::CalDavConfig;
+use crate::clients::google_calendar::GoogleCalendarConfig;
+use crate::clients::imap_inbox::{DEFAULT_IMAP_PORT, ImapConfig};
+use crate::clients::outlook_calendar::OutlookCalendarConfig;
+use crate::events::queue::{DEFAULT_OVERFLOW_TIMEOUT, OverflowPolicy};
+use crate::service::delivery_channel::{DEFAULT_NTFY_SERVER, EmailConfig};
+use crate::service::latency::LatencySlo;
+use crate::service::routing::DEFAULT_CONFIDENCE_THRESHOLD;
+use crate::service::notify_flow::DEFAULT_MAX_TURNS;
+use crate::tasks::calendar_loop::{DEFAULT_LEAD_MINUTES, SyncMode, SyncOptions, SyncTarget};
+use crate::tasks::notification_loop::{CatchUpPolicy, DEFAULT_DELIVERIES_PER_SECOND, DEFAULT_LATE_AFTER_MINUTES};
+use crate::tasks::task_runner::CronSchedule;
+
+// Config shared with the loops and services; `watch` swaps in a fresh copy
+// whenever the file changes.
+pub type SharedConfig = Arc<ArcSwap<AppConfig>>;
+
+pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+const DEFAULT_NOTIFICATION_INTERVAL_SECS: u64 = 60;
+const DEFAULT_APPROVAL_SWEEP_INTERVAL_SECS: u64 = 30;
+const DEFAULT_FOCUS_DIGEST_INTERVAL_SECS: u64 = 30;
+const DEFAULT_SESSION_SWEEP_INTERVAL_SECS: u64 = 60;
+const DEFAULT_LATENCY_CHECK_INTERVAL_SECS: u64 = 60;
+const DEFAULT_BRIEFING_CHECK_INTERVAL_SECS: u64 = 60;
+const DEFAULT_CALENDAR_SYNC_INTERVAL_SECS: u64 = 15 * 60;
+const DEFAULT_EMAIL_POLL_INTERVAL_SECS: u64 = 60;
+const DEFAULT_TODO_SUMMARY_TIME: &str = "07:00";
+const DEFAULT_TODO_SUMMARY_TIMEZONE: Tz = chrono_tz::America::New_York;
+const DEFAULT_CLEANUP_CRON: &str = "0 30 3 * * *";
+const DEFAULT_WEEKLY_DIGEST_CRON: &str = "0 0 18 * * SUN";
+// Jobs that can be switched off with "off", with their default schedule.
+const OPTIONAL_CRON_KEYS: [(&str, &str); 2] = [
+    ("CLEANUP_CRON", DEFAULT_CLEANUP_CRON),
+    ("WEEKLY_DIGEST_CRON", DEFAULT_WEEKLY_DIGEST_CRON),
+];
+const DEFAULT_TODO_RETENTION_DAYS: i64 = 30;
+
+// Every background loop's sleep, keyed by its setting. A day is the longest
+// any of them may wait.
+const INTERVAL_KEYS: [(&str, u64); 8] = [
+    ("NOTIFICATION_INTERVAL_SECS", DEFAULT_NOTIFICATION_INTERVAL_SECS),
+    ("APPROVAL_SWEEP_INTERVAL_SECS", DEFAULT_APPROVAL_SWEEP_INTERVAL_SECS),
+    ("FOCUS_DIGEST_INTERVAL_SECS", DEFAULT_FOCUS_DIGEST_INTERVAL_SECS),
+    ("SESSION_SWEEP_INTERVAL_SECS", DEFAULT_SESSION_SWEEP_INTERVAL_SECS),
+    ("LATENCY_CHECK_INTERVAL_SECS", DEFAULT_LATENCY_CHECK_INTERVAL_SECS),
+    ("BRIEFING_CHECK_INTERVAL_SECS", DEFAULT_BRIEFING_CHECK_INTERVAL_SECS),
+    ("CALENDAR_SYNC_INTERVAL_SECS", DEFAULT_CALENDAR_SYNC_INTERVAL_SECS),
+    ("EMAIL_POLL_INTERVAL_SECS", DEFAULT_EMAIL_POLL_INTERVAL_SECS),
+];
+const MAX_INTERVAL_SECS: u64 = 24 * 60 * 60;
+const DEFAULT_EVENT_WORKERS: usize = 4;
+const DEFAULT_EVENT_QUEUE_CAPACITY: usize = 256;
+const DEFAULT_SLO_PROMPT_P95_MS: i64 = 15_000;
+const DEFAULT_SLO_DELIVERY_P95_MS: i64 = 60_000;
+
+// The calendar that events sync from and approved calendar events go to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalendarProvider {
+    Google(GoogleCalendarConfig),
+    CalDav(CalDavConfig),
+    Outlook(OutlookCalendarConfig),
+}
+
+impl CalendarProvider {
+    pub fn target(&self) -> &SyncTarget {
+        match self {
+            CalendarProvider::Google(settings) => &settings.target,
+            CalendarProvider::CalDav(settings) => &settings.target,
+            CalendarProvider::Outlook(settings) => &settings.target,
+        }
+    }
+
+    pub fn user_id(&self) -> &str {
+        &self.target().user_id
+    }
+
+    pub fn channel_id(&self) -> &str {
+        &self.target().channel_id
+    }
+}
 
 #[derive(Debug, Default, Clone)]
 pub struct AppConfig {
@@ -34,4 +123,587 @@ impl AppConfig {
     pub fn get(&self, key: &str) -> Option<String> {
         self.values.get(key).cloned()
     }
+
+    pub fn shared(self) -> SharedConfig {
+        Arc::new(ArcSwap::from_pointee(self))
+    }
+
+    // The longest the notification loop sleeps between looks at the store;
+    // it wakes at each reminder's deadline and on engine changes regardless,
+    // so this only bounds how late other writes are noticed.
+    pub fn notification_interval(&self) -> Duration {
+        self.interval("NOTIFICATION_INTERVAL_SECS")
+    }
+
+    // The loops below re-read theirs before every sleep, so a change applies
+    // from the next one.
+    pub fn approval_sweep_interval(&self) -> Duration {
+        self.interval("APPROVAL_SWEEP_INTERVAL_SECS")
+    }
+
+    pub fn focus_digest_interval(&self) -> Duration {
+        self.interval("FOCUS_DIGEST_INTERVAL_SECS")
+    }
+
+    pub fn session_sweep_interval(&self) -> Duration {
+        self.interval("SESSION_SWEEP_INTERVAL_SECS")
+    }
+
+    pub fn latency_check_interval(&self) -> Duration {
+        self.interval("LATENCY_CHECK_INTERVAL_SECS")
+    }
+
+    // How often the todo loop checks whether a briefing is due, so also how
+    // late one can go out.
+    pub fn briefing_check_interval(&self) -> Duration {
+        self.interval("BRIEFING_CHECK_INTERVAL_SECS")
+    }
+
+    pub fn calendar_sync_interval(&self) -> Duration {
+        self.interval("CALENDAR_SYNC_INTERVAL_SECS")
+    }
+
+    pub fn email_poll_interval(&self) -> Duration {
+        self.interval("EMAIL_POLL_INTERVAL_SECS")
+    }
+
+    fn interval(&self, key: &str) -> Duration {
+        let default = INTERVAL_KEYS
+            .iter()
+            .find(|(known, _)| *known == key)
+            .map(|(_, secs)| *secs)
+            .unwrap_or(DEFAULT_NOTIFICATION_INTERVAL_SECS);
+        let secs = self
+            .get(key)
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|secs| (1..=MAX_INTERVAL_SECS).contains(secs))
+            .unwrap_or(default);
+        Duration::from_secs(secs)
+    }
+
+    // TODO_SUMMARY_TIME (HH:MM, default 07:00) in TODO_SUMMARY_TIMEZONE
+    // (default America/New_York): when the daily todo summary goes out
+    // unless TODO_SUMMARY_CRON is set.
+    pub fn todo_summary_schedule(&self) -> (NaiveTime, Tz) {
+        let time = self
+            .get("TODO_SUMMARY_TIME")
+            .and_then(|v| NaiveTime::parse_from_str(v.trim(), "%H:%M").ok())
+            .or_else(|| NaiveTime::parse_from_str(DEFAULT_TODO_SUMMARY_TIME, "%H:%M").ok())
+            .unwrap_or_default();
+        let zone = self
+            .get("TODO_SUMMARY_TIMEZONE")
+            .and_then(|v| v.trim().parse::<Tz>().ok())
+            .unwrap_or(DEFAULT_TODO_SUMMARY_TIMEZONE);
+        (time, zone)
+    }
+
+    // CRON_TIMEZONE, the zone every *_CRON expression is read in; defaults
+    // to America/New_York.
+    fn cron_timezone(&self) -> Tz {
+        self.get("CRON_TIMEZONE")
+            .and_then(|v| v.trim().parse::<Tz>().ok())
+            .unwrap_or(DEFAULT_TODO_SUMMARY_TIMEZONE)
+    }
+
+    fn cron(&self, key: &str) -> Option<Result<CronSchedule, String>> {
+        self.get(key)
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| CronSchedule::parse(&v, self.cron_timezone()))
+    }
+
+    // TODO_SUMMARY_CRON when set, otherwise every day at
+    // todo_summary_schedule().
+    pub fn todo_summary_cron(&self) -> CronSchedule {
+        if let Some(Ok(schedule)) = self.cron("TODO_SUMMARY_CRON") {
+            return schedule;
+        }
+        let (time, zone) = self.todo_summary_schedule();
+        let daily = format!("0 {} {} * * *", time.minute(), time.hour());
+        CronSchedule::parse(&daily, zone).expect("a daily schedule always parses")
+    }
+
+    // DELIVERIES_PER_SECOND (default 5): how fast the notification loop
+    // sends to Discord. Read once at startup.
+    pub fn deliveries_per_second(&self) -> u32 {
+        self.get("DELIVERIES_PER_SECOND")
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .filter(|rate| *rate > 0)
+            .unwrap_or(DEFAULT_DELIVERIES_PER_SECOND)
+    }
+
+    // LATE_DELIVERY_MINUTES (default 10): how overdue a ping has to be to be
+    // marked late. SKIP_PASSED_EVENTS=true drops overdue pings for events
+    // that are already over instead.
+    pub fn catch_up_policy(&self) -> CatchUpPolicy {
+        let minutes = self
+            .get("LATE_DELIVERY_MINUTES")
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(DEFAULT_LATE_AFTER_MINUTES);
+        CatchUpPolicy {
+            late_after: chrono::Duration::minutes(minutes),
+            skip_passed_events: self
+                .get("SKIP_PASSED_EVENTS")
+                .is_some_and(|v| v.trim() == "true"),
+        }
+    }
+
+    // CLEANUP_CRON (default 03:30 every day) runs the cleanup job; "off"
+    // turns it off.
+    pub fn cleanup_cron(&self) -> Option<CronSchedule> {
+        self.optional_cron("CLEANUP_CRON")
+    }
+
+    // WEEKLY_DIGEST_CRON (default Sunday 18:00) sends the weekly digest;
+    // "off" turns it off.
+    pub fn weekly_digest_cron(&self) -> Option<CronSchedule> {
+        self.optional_cron("WEEKLY_DIGEST_CRON")
+    }
+
+    fn optional_cron(&self, key: &str) -> Option<CronSchedule> {
+        if self.get(key).is_some_and(|v| v.trim() == "off") {
+            return None;
+        }
+        let (_, default) = OPTIONAL_CRON_KEYS.iter().find(|(known, _)| *known == key)?;
+        match self.cron(key) {
+            Some(Ok(schedule)) => Some(schedule),
+            _ => CronSchedule::parse(default, self.cron_timezone()).ok(),
+        }
+    }
+
+    // TODO_RETENTION_DAYS (default 30): how long the cleanup job keeps
+    // finished todos.
+    pub fn todo_retention(&self) -> chrono::Duration {
+        let days = self
+            .get("TODO_RETENTION_DAYS")
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|days| *days > 0)
+            .unwrap_or(DEFAULT_TODO_RETENTION_DAYS);
+        chrono::Duration::days(days)
+    }
+
+    // Schedule settings that are set but unusable, each saying which default
+    // is used instead. Logged at startup and on every reload.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (key, default) in INTERVAL_KEYS {
+            let Some(raw) = self.get(key) else {
+                continue;
+            };
+            let valid = raw
+                .trim()
+                .parse::<u64>()
+                .is_ok_and(|secs| (1..=MAX_INTERVAL_SECS).contains(&secs));
+            if !valid {
+                problems.push(format!(
+                    "{}={} is not a number of seconds between 1 and {}; using {}",
+                    key, raw, MAX_INTERVAL_SECS, default
+                ));
+            }
+        }
+        if let Some(raw) = self.get("TODO_SUMMARY_TIME") {
+            if NaiveTime::parse_from_str(raw.trim(), "%H:%M").is_err() {
+                problems.push(format!(
+                    "TODO_SUMMARY_TIME={} is not an HH:MM time; using {}",
+                    raw, DEFAULT_TODO_SUMMARY_TIME
+                ));
+            }
+        }
+        if let Some(raw) = self.get("TODO_SUMMARY_TIMEZONE") {
+            if raw.trim().parse::<Tz>().is_err() {
+                problems.push(format!(
+                    "TODO_SUMMARY_TIMEZONE={} is not an IANA time zone; using {}",
+                    raw,
+                    DEFAULT_TODO_SUMMARY_TIMEZONE.name()
+                ));
+            }
+        }
+        if let Some(raw) = self.get("CRON_TIMEZONE") {
+            if raw.trim().parse::<Tz>().is_err() {
+                problems.push(format!(
+                    "CRON_TIMEZONE={} is not an IANA time zone; using {}",
+                    raw,
+                    DEFAULT_TODO_SUMMARY_TIMEZONE.name()
+                ));
+            }
+        }
+        if let Some(Err(err)) = self.cron("TODO_SUMMARY_CRON") {
+            problems.push(format!("TODO_SUMMARY_CRON: {}; using TODO_SUMMARY_TIME", err));
+        }
+        for (key, default) in OPTIONAL_CRON_KEYS {
+            if self.get(key).is_some_and(|v| v.trim() == "off") {
+                continue;
+            }
+            if let Some(Err(err)) = self.cron(key) {
+                problems.push(format!("{}: {}; using {}", key, err, default));
+            }
+        }
+        let email_keys = ["EMAIL_API_URL", "EMAIL_API_TOKEN", "EMAIL_FROM"];
+        if email_keys.iter().any(|key| self.get(key).is_some()) && self.email_delivery().is_none() {
+            problems.push("EMAIL_API_URL, EMAIL_API_TOKEN and EMAIL_FROM must all be set; email delivery is off".to_string());
+        }
+        if self.get("IMAP_HOST").is_some() {
+            if self.imap_inbox().is_none() || self.inbound_email_channel().is_none() {
+                problems.push(
+                    "IMAP_HOST needs IMAP_USERNAME, IMAP_PASSWORD and INBOUND_EMAIL_CHANNEL_ID; inbound email is off"
+                        .to_string(),
+                );
+            } else if self.inbound_email_senders().is_empty() {
+                problems.push("INBOUND_EMAIL_SENDERS is empty; every inbound email will be ignored".to_string());
+            }
+        }
+        if let Some(raw) = self.get("IMAP_PORT") {
+            if raw.trim().parse::<u16>().is_err() {
+                problems.push(format!("IMAP_PORT={} is not a port; using {}", raw, DEFAULT_IMAP_PORT));
+            }
+        }
+        problems
+    }
+
+    pub fn log_problems(&self) {
+        for problem in self.problems() {
+            tracing::warn!(%problem, "invalid config value");
+        }
+    }
+
+    // ROUTER_MODE=llm sends every request to the LLM router; the default
+    // "cascade" only sends what the keyword rules aren't sure of. Read once
+    // at startup.
+    pub fn router_cascade(&self) -> bool {
+        self.get("ROUTER_MODE").is_none_or(|mode| mode.trim() != "llm")
+    }
+
+    // Read once at startup, like the threshold below.
+    pub fn router_rules_file(&self) -> Option<String> {
+        self.get("ROUTER_RULES_FILE").map(|path| path.trim().to_string()).filter(|path| !path.is_empty())
+    }
+
+    // Read once at startup. 0 acts on every guess; 1 asks about all but
+    // certain ones.
+    pub fn router_confidence_threshold(&self) -> f32 {
+        self.get("ROUTER_CONFIDENCE_THRESHOLD")
+            .and_then(|v| v.trim().parse::<f32>().ok())
+            .filter(|threshold| (0.0..=1.0).contains(threshold))
+            .unwrap_or(DEFAULT_CONFIDENCE_THRESHOLD)
+    }
+
+    // Read once at startup, like the threshold above.
+    pub fn conversation_max_turns(&self) -> u32 {
+        self.get("CONVERSATION_MAX_TURNS")
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .filter(|turns| *turns > 0)
+            .unwrap_or(DEFAULT_MAX_TURNS)
+    }
+
+    // Read once at startup; lanes are not resized on reload.
+    pub fn event_workers(&self) -> usize {
+        self.get("EVENT_WORKERS")
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|workers| *workers > 0)
+            .unwrap_or(DEFAULT_EVENT_WORKERS)
+    }
+
+    // Read once at startup, like EVENT_WORKERS.
+    pub fn event_queue_capacity(&self) -> usize {
+        self.get("EVENT_QUEUE_CAPACITY")
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|capacity| *capacity > 0)
+            .unwrap_or(DEFAULT_EVENT_QUEUE_CAPACITY)
+    }
+
+    // EVENT_QUEUE_OVERFLOW=block|drop-oldest|reject, with block waiting up to
+    // EVENT_QUEUE_TIMEOUT_MS. Unknown values fall back to block. Read once at
+    // startup.
+    pub fn event_overflow(&self) -> OverflowPolicy {
+        match self.get("EVENT_QUEUE_OVERFLOW").as_deref().map(str::trim) {
+            Some("drop-oldest") => OverflowPolicy::DropOldest,
+            Some("reject") => OverflowPolicy::Reject,
+            _ => OverflowPolicy::Block {
+                timeout: self
+                    .get("EVENT_QUEUE_TIMEOUT_MS")
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .map(Duration::from_millis)
+                    .unwrap_or(DEFAULT_OVERFLOW_TIMEOUT),
+            },
+        }
+    }
+
+    // Where every emitted event is also posted. Read once at startup.
+    pub fn event_forward_url(&self) -> Option<String> {
+        self.get("EVENT_FORWARD_URL")
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+    }
+
+    // SLO_PROMPT_P95_MS and SLO_DELIVERY_P95_MS: the p95 latencies above
+    // which the operator channel is alerted.
+    pub fn latency_slo(&self) -> LatencySlo {
+        let millis = |key: &str, default: i64| {
+            let ms = self
+                .get(key)
+                .and_then(|v| v.parse::<i64>().ok())
+                .filter(|ms| *ms > 0)
+                .unwrap_or(default);
+            chrono::Duration::milliseconds(ms)
+        };
+        LatencySlo {
+            prompt_p95: millis("SLO_PROMPT_P95_MS", DEFAULT_SLO_PROMPT_P95_MS),
+            delivery_p95: millis("SLO_DELIVERY_P95_MS", DEFAULT_SLO_DELIVERY_P95_MS),
+        }
+    }
+
+    // Channel that latency alerts are posted to. Unset means log only.
+    pub fn operator_channel(&self) -> Option<String> {
+        self.get("OPERATOR_CHANNEL_ID")
+            .map(|channel| channel.trim().to_string())
+            .filter(|channel| !channel.is_empty())
+    }
+
+    // GOOGLE_CLIENT_ID, GOOGLE_CLIENT_SECRET, GOOGLE_CALENDAR_USER_ID and
+    // GOOGLE_CALENDAR_CHANNEL_ID together turn on calendar sync for one
+    // user; GOOGLE_CALENDAR_ID defaults to their primary calendar.
+    pub fn google_calendar(&self) -> Option<GoogleCalendarConfig> {
+        let value = |key: &str| self.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Some(GoogleCalendarConfig {
+            client_id: value("GOOGLE_CLIENT_ID")?,
+            client_secret: value("GOOGLE_CLIENT_SECRET")?,
+            calendar_id: value("GOOGLE_CALENDAR_ID").unwrap_or_else(|| "primary".to_string()),
+            target: SyncTarget {
+                user_id: value("GOOGLE_CALENDAR_USER_ID")?,
+                channel_id: value("GOOGLE_CALENDAR_CHANNEL_ID")?,
+            },
+        })
+    }
+
+    // CALDAV_URL (the calendar collection), CALDAV_USERNAME, CALDAV_PASSWORD,
+    // CALDAV_USER_ID and CALDAV_CHANNEL_ID, the CalDAV counterpart of the
+    // Google settings.
+    pub fn caldav(&self) -> Option<CalDavConfig> {
+        let value = |key: &str| self.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Some(CalDavConfig {
+            url: value("CALDAV_URL")?,
+            username: value("CALDAV_USERNAME")?,
+            password: value("CALDAV_PASSWORD")?,
+            target: SyncTarget {
+                user_id: value("CALDAV_USER_ID")?,
+                channel_id: value("CALDAV_CHANNEL_ID")?,
+            },
+        })
+    }
+
+    // CALENDAR_SYNC_MODE=mirror|lead-time, with CALENDAR_LEAD_MINUTES for
+    // the latter, and comma-separated CALENDAR_SYNC_INCLUDE and
+    // CALENDAR_SYNC_EXCLUDE title words. Re-read on every sync.
+    pub fn calendar_sync_options(&self) -> SyncOptions {
+        let words = |key: &str| -> Vec<String> {
+            self.get(key)
+                .unwrap_or_default()
+                .split(',')
+                .map(|word| word.trim().to_lowercase())
+                .filter(|word| !word.is_empty())
+                .collect()
+        };
+        let mode = match self.get("CALENDAR_SYNC_MODE").as_deref().map(str::trim) {
+            Some("lead-time") => SyncMode::LeadTime {
+                minutes: self
+                    .get("CALENDAR_LEAD_MINUTES")
+                    .and_then(|v| v.trim().parse::<i64>().ok())
+                    .filter(|minutes| *minutes > 0)
+                    .unwrap_or(DEFAULT_LEAD_MINUTES),
+            },
+            _ => SyncMode::Mirror,
+        };
+        SyncOptions {
+            mode,
+            include: words("CALENDAR_SYNC_INCLUDE"),
+            exclude: words("CALENDAR_SYNC_EXCLUDE"),
+        }
+    }
+
+    // OUTLOOK_CLIENT_ID, OUTLOOK_CALENDAR_USER_ID and
+    // OUTLOOK_CALENDAR_CHANNEL_ID; OUTLOOK_TENANT defaults to "common".
+    pub fn outlook_calendar(&self) -> Option<OutlookCalendarConfig> {
+        let value = |key: &str| self.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Some(OutlookCalendarConfig {
+            client_id: value("OUTLOOK_CLIENT_ID")?,
+            tenant: value("OUTLOOK_TENANT").unwrap_or_else(|| "common".to_string()),
+            target: SyncTarget {
+                user_id: value("OUTLOOK_CALENDAR_USER_ID")?,
+                channel_id: value("OUTLOOK_CALENDAR_CHANNEL_ID")?,
+            },
+        })
+    }
+
+    // EMAIL_API_URL, EMAIL_API_TOKEN and EMAIL_FROM, for reminders with
+    // email targets. Read at startup.
+    pub fn email_delivery(&self) -> Option<EmailConfig> {
+        let value = |key: &str| self.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Some(EmailConfig {
+            api_url: value("EMAIL_API_URL")?,
+            api_token: value("EMAIL_API_TOKEN")?,
+            from: value("EMAIL_FROM")?,
+        })
+    }
+
+    // IMAP_HOST, IMAP_PORT (993), IMAP_USERNAME, IMAP_PASSWORD and
+    // IMAP_FOLDER (INBOX): the mailbox reminder requests are emailed to.
+    // Read at startup.
+    pub fn imap_inbox(&self) -> Option<ImapConfig> {
+        let value = |key: &str| self.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Some(ImapConfig {
+            host: value("IMAP_HOST")?,
+            port: value("IMAP_PORT")
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(DEFAULT_IMAP_PORT),
+            username: value("IMAP_USERNAME")?,
+            password: value("IMAP_PASSWORD")?,
+            folder: value("IMAP_FOLDER").unwrap_or_else(|| "INBOX".to_string()),
+        })
+    }
+
+    // INBOUND_EMAIL_CHANNEL_ID: where emailed requests are answered and
+    // their reminders fire. Read at startup.
+    pub fn inbound_email_channel(&self) -> Option<String> {
+        self.get("INBOUND_EMAIL_CHANNEL_ID")
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+    }
+
+    // INBOUND_EMAIL_SENDERS=alice@example.com=@123,... — whose emails are
+    // read, and which user each one is. Re-read on every check.
+    pub fn inbound_email_senders(&self) -> HashMap<String, String> {
+        self.get("INBOUND_EMAIL_SENDERS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(address, user)| (address.trim().to_lowercase(), user.trim().to_string()))
+            .filter(|(address, user)| !address.is_empty() && !user.is_empty())
+            .collect()
+    }
+
+    // NTFY_SERVER (default ntfy.sh) and NTFY_TOKEN, for users who picked an
+    // ntfy topic in /settings push. Read at startup.
+    pub fn ntfy_server(&self) -> String {
+        self.get("NTFY_SERVER")
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| DEFAULT_NTFY_SERVER.to_string())
+    }
+
+    pub fn ntfy_token(&self) -> Option<String> {
+        self.get("NTFY_TOKEN")
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+    }
+
+    // The bot's Pushover application token; without it Pushover keys in
+    // /settings push are refused. Read at startup.
+    pub fn pushover_app_token(&self) -> Option<String> {
+        self.get("PUSHOVER_APP_TOKEN")
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+    }
+
+    // FEED_BASE_URL=https://reminders.example.com — the public address of the
+    // FEED_BIND listener, used to hand out /settings feed links. Read at
+    // startup.
+    pub fn feed_base_url(&self) -> Option<String> {
+        self.get("FEED_BASE_URL")
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+    }
+
+    // WEBHOOK_SINK_URLS=url,... — every ping that goes out is also POSTed to
+    // each of these. Read at startup.
+    pub fn webhook_sink_urls(&self) -> Vec<String> {
+        self.get("WEBHOOK_SINK_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+            .collect()
+    }
+
+    // CALENDAR_PROVIDER=google|caldav|outlook picks which calendar is used;
+    // google is the default. None until the chosen provider's settings are complete.
+    pub fn calendar_provider(&self) -> Option<CalendarProvider> {
+        match self.get("CALENDAR_PROVIDER").as_deref().map(str::trim) {
+            Some("caldav") => self.caldav().map(CalendarProvider::CalDav),
+            Some("outlook") => self.outlook_calendar().map(CalendarProvider::Outlook),
+            Some("google") | None => self.google_calendar().map(CalendarProvider::Google),
+            Some(other) => {
+                tracing::warn!(provider = other, "unknown CALENDAR_PROVIDER; calendar disabled");
+                None
+            }
+        }
+    }
+
+    // INTEGRATION_APPS=app_id:token,... — other bots allowed to create
+    // reminders through the integrations endpoint, each with its own token.
+    pub fn integration_apps(&self) -> Vec<(String, String)> {
+        self.get("INTEGRATION_APPS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|pair| pair.split_once(':'))
+            .map(|(app, token)| (app.trim().to_string(), token.trim().to_string()))
+            .filter(|(app, token)| !app.is_empty() && !token.is_empty())
+            .collect()
+    }
+
+    pub fn openai_model(&self) -> String {
+        self.get("OPENAI_MODEL")
+            .filter(|model| !model.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string())
+    }
+
+    // PROMPT_<TYPE> (e.g. PROMPT_NOTIFICATION_MESSAGE) overrides a built-in
+    // prompt. `{now}`, `{zone}` (the user's timezone) and `{prompt}` are
+    // substituted and `\n` becomes a newline.
+    pub fn prompt_template(&self, prompt_type: &str) -> Option<String> {
+        self.get(&format!("PROMPT_{}", prompt_type.to_uppercase()))
+            .filter(|template| !template.trim().is_empty())
+            .map(|template| template.replace("\\n", "\n"))
+    }
+}
+
+// Reloads `path` into `shared` on every change. The directory is watched rather
+// than the file so editors that save by renaming are picked up too. Secrets are
+// only read at startup, so changing them still needs a restart.
+pub fn watch(path: impl AsRef<Path>, shared: SharedConfig) -> Result<RecommendedWatcher, String> {
+    let path = path.as_ref().to_path_buf();
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let file_name = path.file_name().map(|name| name.to_os_string());
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if !event.kind.is_modify() && !event.kind.is_create() {
+            return;
+        }
+        if !event
+            .paths
+            .iter()
+            .any(|changed| changed.file_name().map(|n| n.to_os_string()) == file_name)
+        {
+            return;
+        }
+        match AppConfig::from_file(&path.to_string_lossy()) {
+            Ok(config) => {
+                config.log_problems();
+                shared.store(Arc::new(config));
+                tracing::info!(path = %path.display(), "configuration reloaded");
+            }
+            Err(err) => {
+                tracing::warn!(path = %path.display(), error = %err, "keeping previous configuration");
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create config watcher: {}", e))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+    Ok(watcher)
 }
@@ -0,0 +1,90 @@
+use std::env;
+
+use chrono::{DateTime, Utc};
+use memory_db::{DB, DBError, save_db};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::handlers::action::ActionEvent;
+
+// Returns the directory where events that kept failing are parked.
+// Defaults to a relative "./data/dead_letters" directory.
+pub fn get_dead_letter_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/dead_letters", base)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: String,
+    pub event: ActionEvent,
+    pub error: String,
+    // Total handling attempts across the original delivery and any retries.
+    pub attempts: u32,
+    pub failed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DeadLetterStore {
+    letters: DB<DeadLetter>,
+}
+
+impl DeadLetterStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_db(letters: DB<DeadLetter>) -> Self {
+        Self { letters }
+    }
+
+    pub fn save(&self) -> Result<(), DBError> {
+        save_db(&get_dead_letter_location(), &self.letters)
+    }
+
+    pub fn insert(
+        &mut self,
+        event: ActionEvent,
+        error: &str,
+        attempts: u32,
+        now: DateTime<Utc>,
+    ) -> String {
+        let id = Uuid::new_v4().to_string();
+        self.letters.insert(
+            id.clone(),
+            DeadLetter {
+                id: id.clone(),
+                event,
+                error: error.to_string(),
+                attempts,
+                failed_at: now,
+            },
+        );
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    // Oldest first, so the list reads in the order things broke.
+    pub fn list(&self) -> Vec<&DeadLetter> {
+        let mut letters: Vec<&DeadLetter> = self.letters.values().collect();
+        letters.sort_by_key(|letter| letter.failed_at);
+        letters
+    }
+
+    pub fn take(&mut self, id: &str) -> Option<DeadLetter> {
+        self.letters.remove(id)
+    }
+
+    pub fn take_all(&mut self) -> Vec<DeadLetter> {
+        let mut letters: Vec<DeadLetter> = self.letters.drain().map(|(_, letter)| letter).collect();
+        letters.sort_by_key(|letter| letter.failed_at);
+        letters
+    }
+}
@@ -0,0 +1,56 @@
+use serde_json::json;
+
+use crate::events::queue::{EventSubscription, QueuedEvent};
+
+// The JSON posted for each event: its name and ids up front so receivers
+// can route without understanding every event, then the event itself.
+pub fn forward_body(queued: &QueuedEvent) -> serde_json::Value {
+    let event = &queued.event;
+    let ids = event.ids();
+    json!({
+        "correlation_id": queued.correlation_id,
+        "event": ids.name,
+        "user_id": ids.user_id,
+        "channel_id": ids.channel_id,
+        "action_id": ids.action_id,
+        "payload": event,
+    })
+}
+
+// Posts every emitted event to `url`. Best effort: a failed post is logged
+// and the event is not retried, so the endpoint can't hold up the bot.
+pub async fn run_event_forwarder(mut subscription: EventSubscription, url: String) {
+    let client = reqwest::Client::new();
+    while let Some(queued) = subscription.recv_queued().await {
+        let result = client.post(&url).json(&forward_body(&queued)).send().await;
+        match result {
+            Ok(response) if !response.status().is_success() => {
+                tracing::warn!(status = %response.status(), "event forward rejected");
+            }
+            Ok(_) => {}
+            Err(err) => tracing::warn!(error = %err, "failed to forward event"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::action::ActionEvent;
+
+    #[test]
+    fn body_leads_with_the_event_ids() {
+        let event = ActionEvent::ApprovalConfirmed {
+            action_id: "a1".to_string(),
+            user_id: "@u".to_string(),
+        };
+        let queued = QueuedEvent::new(event);
+        let body = forward_body(&queued);
+        assert_eq!(body["correlation_id"], queued.correlation_id.as_str());
+        assert_eq!(body["event"], "approval_confirmed");
+        assert_eq!(body["user_id"], "@u");
+        assert_eq!(body["action_id"], "a1");
+        assert!(body["channel_id"].is_null());
+        assert_eq!(body["payload"]["ApprovalConfirmed"]["action_id"], "a1");
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::queue::QueuedEvent;
+use crate::events::trail::new_correlation_id;
+use crate::handlers::action::ActionEvent;
+
+// Returns the file unprocessed events are journaled to.
+// Defaults to a relative "./data/events.journal" file.
+pub fn get_journal_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/events.journal", base)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalEntry {
+    Emit {
+        seq: u64,
+        // Journals written before ids were kept get a fresh one.
+        #[serde(default = "new_correlation_id")]
+        correlation_id: String,
+        event: ActionEvent,
+    },
+    Done { seq: u64 },
+}
+
+// Done entries tolerated behind an event that is still outstanding, such as
+// one scheduled days ahead, before the file is rewritten without them.
+const COMPACT_AFTER: usize = 256;
+
+struct JournalState {
+    path: PathBuf,
+    file: File,
+    next_seq: u64,
+    // Sequence numbers in the order their events were queued. Whoever reads
+    // the channel sees events in the same order, so it claims from the front.
+    undispatched: VecDeque<u64>,
+    // Claimed or not, everything not yet acked. Lanes finish out of order.
+    outstanding: BTreeSet<u64>,
+    replay: Vec<QueuedEvent>,
+    done_since_compact: usize,
+}
+
+// Append-only log of events between emit and the worker finishing them, so a
+// crash in between replays them on the next start instead of losing them.
+pub struct EventJournal {
+    state: Mutex<JournalState>,
+}
+
+impl EventJournal {
+    // Reads whatever a previous run left unfinished and rewrites the file with
+    // just those events.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let mut emitted: Vec<(u64, QueuedEvent)> = Vec::new();
+        let mut done: Vec<u64> = Vec::new();
+        if path.exists() {
+            let raw = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read journal {}: {}", path.display(), e))?;
+            for line in raw.lines().filter(|line| !line.trim().is_empty()) {
+                // A crash mid-write leaves a partial last line; skip it.
+                match serde_json::from_str::<JournalEntry>(line) {
+                    Ok(JournalEntry::Emit {
+                        seq,
+                        correlation_id,
+                        event,
+                    }) => emitted.push((seq, QueuedEvent { correlation_id, event })),
+                    Ok(JournalEntry::Done { seq }) => done.push(seq),
+                    Err(err) => tracing::warn!(error = %err, "skipping unreadable journal entry"),
+                }
+            }
+        }
+        emitted.retain(|(seq, _)| !done.contains(seq));
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut file = File::create(&path)
+            .map_err(|e| format!("Failed to write journal {}: {}", path.display(), e))?;
+        let mut undispatched = VecDeque::new();
+        let mut replay = Vec::new();
+        for (seq, (_, queued)) in (1..).zip(emitted) {
+            write_emit(&mut file, seq, &queued)?;
+            undispatched.push_back(seq);
+            replay.push(queued);
+        }
+        let file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open journal {}: {}", path.display(), e))?;
+        let next_seq = undispatched.len() as u64 + 1;
+        let outstanding = undispatched.iter().copied().collect();
+        Ok(Self {
+            state: Mutex::new(JournalState {
+                path,
+                file,
+                next_seq,
+                undispatched,
+                outstanding,
+                replay,
+                done_since_compact: 0,
+            }),
+        })
+    }
+
+    // Events left over from the previous run, oldest first. Only the first
+    // call returns anything.
+    pub fn take_replay(&self) -> Vec<QueuedEvent> {
+        std::mem::take(&mut self.state.lock().unwrap().replay)
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.state.lock().unwrap().outstanding.len()
+    }
+
+    // The sequence number is reserved even if the write fails, so acks stay
+    // lined up with the channel.
+    pub fn record(&self, queued: &QueuedEvent) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.undispatched.push_back(seq);
+        state.outstanding.insert(seq);
+        write_emit(&mut state.file, seq, queued)
+    }
+
+    // Sequence number of the next event read off the channel (replayed events
+    // come first), for acking it once handled.
+    pub fn claim_next(&self) -> Option<u64> {
+        self.state.lock().unwrap().undispatched.pop_front()
+    }
+
+    // Marks an event as handled. Once nothing is outstanding the file is
+    // truncated so it doesn't grow without bound; while something is, it is
+    // compacted every so often instead.
+    pub fn ack(&self, seq: u64) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if !state.outstanding.remove(&seq) {
+            return Ok(());
+        }
+        if state.outstanding.is_empty() {
+            state.done_since_compact = 0;
+            return state
+                .file
+                .set_len(0)
+                .map_err(|e| format!("Failed to truncate journal: {}", e));
+        }
+        write_entry(&mut state.file, &JournalEntry::Done { seq })?;
+        state.done_since_compact += 1;
+        if state.done_since_compact >= COMPACT_AFTER {
+            compact(&mut state)?;
+        }
+        Ok(())
+    }
+}
+
+// Just enough of an entry to tell which event it is.
+#[derive(Deserialize)]
+struct EntrySeq {
+    op: String,
+    seq: u64,
+}
+
+// Rewrites the file with only the outstanding events, keeping their sequence
+// numbers since some may already be claimed. The new file replaces the old
+// one in a single rename.
+fn compact(state: &mut JournalState) -> Result<(), String> {
+    let raw = fs::read_to_string(&state.path)
+        .map_err(|e| format!("Failed to read journal {}: {}", state.path.display(), e))?;
+    let tmp = state.path.with_extension("journal.tmp");
+    let mut file = File::create(&tmp)
+        .map_err(|e| format!("Failed to write journal {}: {}", tmp.display(), e))?;
+    for line in raw.lines() {
+        let keep = serde_json::from_str::<EntrySeq>(line)
+            .is_ok_and(|entry| entry.op == "emit" && state.outstanding.contains(&entry.seq));
+        if keep {
+            writeln!(file, "{}", line).map_err(|e| format!("Failed to append to journal: {}", e))?;
+        }
+    }
+    fs::rename(&tmp, &state.path)
+        .map_err(|e| format!("Failed to replace journal {}: {}", state.path.display(), e))?;
+    state.file = OpenOptions::new()
+        .append(true)
+        .open(&state.path)
+        .map_err(|e| format!("Failed to open journal {}: {}", state.path.display(), e))?;
+    state.done_since_compact = 0;
+    Ok(())
+}
+
+// Borrowing twin of JournalEntry::Emit so recording doesn't clone the event.
+#[derive(Serialize)]
+struct EmitRef<'a> {
+    op: &'static str,
+    seq: u64,
+    correlation_id: &'a str,
+    event: &'a ActionEvent,
+}
+
+fn write_emit(file: &mut File, seq: u64, queued: &QueuedEvent) -> Result<(), String> {
+    let line = serde_json::to_string(&EmitRef {
+        op: "emit",
+        seq,
+        correlation_id: &queued.correlation_id,
+        event: &queued.event,
+    })
+        .map_err(|e| format!("Failed to serialize event: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to append to journal: {}", e))
+}
+
+fn write_entry(file: &mut File, entry: &JournalEntry) -> Result<(), String> {
+    let line =
+        serde_json::to_string(entry).map_err(|e| format!("Failed to serialize event: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to append to journal: {}", e))
+}
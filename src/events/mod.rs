@@ -1,4 +1,11 @@
 #![allow(dead_code)]
 
+pub mod dead_letter;
+pub mod forwarder;
+pub mod journal;
+pub mod prompt_watch;
 pub mod queue;
+pub mod retry;
+pub mod timer;
+pub mod trail;
 pub mod worker;
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+// How long a /notify interaction waits for its confirm prompt before it
+// stops watching. Well inside the 15 minutes Discord allows editing it.
+pub const READY_TIMEOUT: Duration = Duration::from_secs(120);
+
+// Lets the interaction that asked for a draft learn when the engine has
+// posted its confirm prompt, so its status can say it is ready.
+#[derive(Clone)]
+pub struct PromptWatch {
+    tx: broadcast::Sender<(String, String)>,
+}
+
+impl Default for PromptWatch {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(64);
+        Self { tx }
+    }
+}
+
+impl PromptWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // A prompt for the user went up in the channel. Nobody waiting is fine.
+    pub fn posted(&self, user_id: &str, channel_id: &str) {
+        let _ = self.tx.send((user_id.to_string(), channel_id.to_string()));
+    }
+
+    // Subscribe before emitting the request, so a prompt that goes up
+    // quickly isn't missed.
+    pub fn subscribe(&self) -> PromptWaiter {
+        PromptWaiter {
+            rx: self.tx.subscribe(),
+        }
+    }
+}
+
+pub struct PromptWaiter {
+    rx: broadcast::Receiver<(String, String)>,
+}
+
+impl PromptWaiter {
+    // Whether a prompt for the user in the channel went up within `timeout`.
+    pub async fn wait_for(mut self, user_id: &str, channel_id: &str, timeout: Duration) -> bool {
+        let posted = async {
+            loop {
+                match self.rx.recv().await {
+                    Ok((user, channel)) if user == user_id && channel == channel_id => return true,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return false,
+                }
+            }
+        };
+        tokio::time::timeout(timeout, posted).await.unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn waits_for_the_requesters_prompt_only() {
+        let watch = PromptWatch::new();
+        let waiter = watch.subscribe();
+        watch.posted("@other", "1");
+        watch.posted("@u", "2");
+        watch.posted("@u", "1");
+        assert!(waiter.wait_for("@u", "1", Duration::from_millis(50)).await);
+
+        let waiter = watch.subscribe();
+        watch.posted("@other", "1");
+        assert!(!waiter.wait_for("@u", "1", Duration::from_millis(50)).await);
+    }
+}
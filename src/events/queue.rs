@@ -1,19 +1,474 @@
-use tokio::sync::mpsc;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
+use tokio::sync::{broadcast, mpsc, Mutex};
+
+use crate::events::journal::EventJournal;
+use crate::events::trail::{EventOutcome, EventRecord, EventTrail, new_correlation_id};
 use crate::handlers::action::ActionEvent;
 
+// An event on its way to the worker, tagged with the id its trail records
+// and forwarded copy share. Scheduled events keep it once unwrapped.
+#[derive(Debug, Clone)]
+pub struct QueuedEvent {
+    pub correlation_id: String,
+    pub event: ActionEvent,
+}
+
+impl QueuedEvent {
+    pub fn new(event: ActionEvent) -> Self {
+        Self {
+            correlation_id: new_correlation_id(),
+            event,
+        }
+    }
+}
+
+// What emit does when the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    // Wait for room, giving up after `timeout`.
+    Block { timeout: Duration },
+    // Make room by discarding the oldest queued event.
+    DropOldest,
+    // Fail straight away so the caller can tell the user to retry.
+    Reject,
+}
+
+pub const DEFAULT_OVERFLOW_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl Default for OverflowPolicy {
+    fn default() -> Self {
+        OverflowPolicy::Block {
+            timeout: DEFAULT_OVERFLOW_TIMEOUT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmitError {
+    QueueFull,
+    // The worker has shut down.
+    Closed,
+}
+
+impl fmt::Display for EmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmitError::QueueFull => f.write_str("event queue is full"),
+            EmitError::Closed => f.write_str("event queue is closed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueueMetrics {
+    pub depth: usize,
+    pub capacity: usize,
+    // Emits that failed because the queue stayed full.
+    pub rejected: u64,
+    // Events discarded under DropOldest.
+    pub evicted: u64,
+}
+
+#[derive(Default)]
+struct Overflow {
+    // Queued events the worker should discard instead of handling.
+    pending_evictions: AtomicUsize,
+    rejected: AtomicU64,
+    evicted: AtomicU64,
+}
+
+// The worker's end of the bus. Under DropOldest, check take_eviction after
+// each recv to find out whether that event has been pushed out.
+pub struct EventReceiver {
+    rx: mpsc::Receiver<QueuedEvent>,
+    overflow: Arc<Overflow>,
+}
+
+impl EventReceiver {
+    pub async fn recv(&mut self) -> Option<ActionEvent> {
+        self.rx.recv().await.map(|queued| queued.event)
+    }
+
+    // Like recv, but keeps the correlation id.
+    pub async fn recv_queued(&mut self) -> Option<QueuedEvent> {
+        self.rx.recv().await
+    }
+
+    pub fn try_recv(&mut self) -> Result<ActionEvent, mpsc::error::TryRecvError> {
+        self.rx.try_recv().map(|queued| queued.event)
+    }
+
+    pub fn close(&mut self) {
+        self.rx.close();
+    }
+
+    // Whether the event just received was evicted by a newer one.
+    pub fn take_eviction(&self) -> bool {
+        self.overflow
+            .pending_evictions
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
+
+// A read-only copy of everything emitted, for consumers besides the worker
+// (audit, analytics, forwarding). Subscribers can't delay or reject events:
+// one that falls more than a queue's worth behind skips what it missed.
+pub struct EventSubscription {
+    name: &'static str,
+    rx: broadcast::Receiver<QueuedEvent>,
+}
+
+impl EventSubscription {
+    // None once every EventBus handle is gone.
+    pub async fn recv(&mut self) -> Option<ActionEvent> {
+        self.recv_queued().await.map(|queued| queued.event)
+    }
+
+    // Like recv, but keeps the correlation id.
+    pub async fn recv_queued(&mut self) -> Option<QueuedEvent> {
+        loop {
+            match self.rx.recv().await {
+                Ok(queued) => return Some(queued),
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(subscriber = self.name, missed, "event subscriber fell behind");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<ActionEvent> {
+        loop {
+            match self.rx.try_recv() {
+                Ok(queued) => return Some(queued.event),
+                Err(broadcast::error::TryRecvError::Lagged(missed)) => {
+                    tracing::warn!(subscriber = self.name, missed, "event subscriber fell behind");
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+// The only queue between the handlers and the worker. ActionEvent is the one
+// event type on it; there is no separate bus-level enum to translate from.
+// Other consumers subscribe for a copy of each event.
 #[derive(Clone)]
 pub struct EventBus {
-    tx: mpsc::Sender<ActionEvent>,
+    tx: mpsc::Sender<QueuedEvent>,
+    // How many events may wait at once. Under DropOldest the channel itself
+    // is larger, leaving room for evicted events the worker hasn't skipped yet.
+    capacity: usize,
+    policy: OverflowPolicy,
+    overflow: Arc<Overflow>,
+    journal: Option<Arc<EventJournal>>,
+    trail: Option<Arc<EventTrail>>,
+    subscribers: broadcast::Sender<QueuedEvent>,
+    // Keeps journal order and channel order identical across emitters.
+    emit_lock: Arc<Mutex<()>>,
 }
 
 impl EventBus {
-    pub fn new(buffer: usize) -> (Self, mpsc::Receiver<ActionEvent>) {
-        let (tx, rx) = mpsc::channel(buffer);
-        (Self { tx }, rx)
+    pub fn new(buffer: usize) -> (Self, EventReceiver) {
+        Self::with_overflow(buffer, OverflowPolicy::default())
+    }
+
+    pub fn with_overflow(buffer: usize, policy: OverflowPolicy) -> (Self, EventReceiver) {
+        let capacity = buffer.max(1);
+        let channel_size = match policy {
+            OverflowPolicy::DropOldest => capacity * 2,
+            _ => capacity,
+        };
+        let (tx, rx) = mpsc::channel(channel_size);
+        let overflow = Arc::new(Overflow::default());
+        let (subscribers, _) = broadcast::channel(capacity);
+        (
+            Self {
+                tx,
+                capacity,
+                policy,
+                overflow: overflow.clone(),
+                journal: None,
+                trail: None,
+                subscribers,
+                emit_lock: Arc::new(Mutex::new(())),
+            },
+            EventReceiver { rx, overflow },
+        )
     }
 
-    pub async fn emit(&self, event: ActionEvent) {
-        let _ = self.tx.send(event).await;
+    // Every emitted event is journaled before it is queued; pass the same
+    // journal to the worker so it can replay and ack them.
+    pub fn with_journal(mut self, journal: Arc<EventJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    // Logs every emitted event, and any dropped before reaching the worker.
+    // Pass the same trail to the engine so outcomes land alongside.
+    pub fn with_trail(mut self, trail: Arc<EventTrail>) -> Self {
+        self.trail = Some(trail);
+        self
+    }
+
+    // Room is reserved before the event is journaled, so a rejected event
+    // never leaves a journal entry the worker would wait for.
+    pub async fn emit(&self, event: ActionEvent) -> Result<(), EmitError> {
+        self.emit_all(vec![event]).await
+    }
+
+    // Queues every event or none of them: room for all is reserved before
+    // any is journaled or sent, so a full queue can't leave half of a
+    // multi-part request behind.
+    pub async fn emit_all(&self, events: Vec<ActionEvent>) -> Result<(), EmitError> {
+        self.queue(events, true).await
+    }
+
+    // Without `wait`, a Block policy rejects instead, and another emitter
+    // holding the journal lock (likely waiting for room itself) counts as
+    // a full queue.
+    async fn queue(&self, events: Vec<ActionEvent>, wait: bool) -> Result<(), EmitError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let policy = match self.policy {
+            OverflowPolicy::Block { .. } if !wait => OverflowPolicy::Reject,
+            policy => policy,
+        };
+        let _guard = match (&self.journal, wait) {
+            (None, _) => None,
+            (Some(_), true) => Some(self.emit_lock.lock().await),
+            (Some(_), false) => match self.emit_lock.try_lock() {
+                Ok(guard) => Some(guard),
+                Err(_) => return self.refuse(events, EmitError::QueueFull),
+            },
+        };
+        let permits = match self.reserve(events.len(), policy).await {
+            Ok(permits) => permits,
+            Err(err) => return self.refuse(events, err),
+        };
+        for (permit, event) in permits.zip(events) {
+            let queued = QueuedEvent::new(event);
+            if let Some(journal) = &self.journal {
+                if let Err(err) = journal.record(&queued) {
+                    tracing::warn!(error = %err, "failed to journal event");
+                }
+            }
+            self.log(&queued, EventOutcome::Emitted);
+            if self.subscribers.receiver_count() > 0 {
+                let _ = self.subscribers.send(queued.clone());
+            }
+            permit.send(queued);
+        }
+        Ok(())
+    }
+
+    fn refuse(&self, events: Vec<ActionEvent>, err: EmitError) -> Result<(), EmitError> {
+        if err == EmitError::QueueFull {
+            self.overflow.rejected.fetch_add(events.len() as u64, Ordering::Relaxed);
+            tracing::warn!(depth = self.queue_depth(), "event queue full, rejecting event");
+        }
+        for event in events {
+            self.log_with_detail(&QueuedEvent::new(event), EventOutcome::Dropped, err.to_string());
+        }
+        Err(err)
+    }
+
+    // Sees events emitted from now on, in emit order. Scheduled events are
+    // delivered when emitted, still wrapped, not when they fall due.
+    pub fn subscribe(&self, name: &'static str) -> EventSubscription {
+        EventSubscription {
+            name,
+            rx: self.subscribers.subscribe(),
+        }
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.receiver_count()
+    }
+
+    async fn reserve(
+        &self,
+        count: usize,
+        policy: OverflowPolicy,
+    ) -> Result<mpsc::PermitIterator<'_, QueuedEvent>, EmitError> {
+        if self.tx.is_closed() {
+            return Err(EmitError::Closed);
+        }
+        // More than the channel can ever hold would wait forever.
+        if count > self.tx.max_capacity() {
+            return Err(EmitError::QueueFull);
+        }
+        match policy {
+            OverflowPolicy::Block { timeout } => {
+                match tokio::time::timeout(timeout, self.tx.reserve_many(count)).await {
+                    Ok(Ok(permits)) => Ok(permits),
+                    Ok(Err(_)) => Err(EmitError::Closed),
+                    Err(_) => Err(EmitError::QueueFull),
+                }
+            }
+            OverflowPolicy::Reject => self.tx.try_reserve_many(count).map_err(try_send_error),
+            OverflowPolicy::DropOldest => {
+                let permits = self.tx.try_reserve_many(count).map_err(try_send_error)?;
+                // The permits already count toward the depth.
+                let over = self.queue_depth().saturating_sub(self.capacity).min(count);
+                if over > 0 {
+                    self.overflow.pending_evictions.fetch_add(over, Ordering::SeqCst);
+                    self.overflow.evicted.fetch_add(over as u64, Ordering::Relaxed);
+                    tracing::warn!(evicted = over, "event queue full, evicting the oldest events");
+                }
+                Ok(permits)
+            }
+        }
+    }
+
+    fn log(&self, queued: &QueuedEvent, outcome: EventOutcome) {
+        if let Some(trail) = &self.trail {
+            trail.log(EventRecord::new(queued, outcome, Utc::now()));
+        }
+    }
+
+    fn log_with_detail(&self, queued: &QueuedEvent, outcome: EventOutcome, detail: String) {
+        if let Some(trail) = &self.trail {
+            trail.log(EventRecord::new(queued, outcome, Utc::now()).with_detail(detail));
+        }
+    }
+
+    // Queues `event` to be handled once `when` has passed. The worker keeps it
+    // on a timer wheel until then; with a journal it survives restarts too.
+    pub async fn emit_at(&self, event: ActionEvent, when: DateTime<Utc>) -> Result<(), EmitError> {
+        self.emit(ActionEvent::Scheduled {
+            at: when,
+            event: Box::new(event),
+        })
+        .await
+    }
+
+    // Like emit_at, but fails straight away on a full queue instead of
+    // waiting for room, whatever the overflow policy. For the engine's own
+    // follow-ups: it runs inside the worker, which can't drain the queue
+    // while it waits.
+    pub async fn try_emit_at(&self, event: ActionEvent, when: DateTime<Utc>) -> Result<(), EmitError> {
+        let scheduled = ActionEvent::Scheduled {
+            at: when,
+            event: Box::new(event),
+        };
+        self.queue(vec![scheduled], false).await
+    }
+
+    // Events emitted but not yet picked up by the worker, not counting ones
+    // already evicted.
+    pub fn queue_depth(&self) -> usize {
+        let queued = self.tx.max_capacity() - self.tx.capacity();
+        queued.saturating_sub(self.overflow.pending_evictions.load(Ordering::SeqCst))
+    }
+
+    pub fn metrics(&self) -> QueueMetrics {
+        QueueMetrics {
+            depth: self.queue_depth(),
+            capacity: self.capacity,
+            rejected: self.overflow.rejected.load(Ordering::Relaxed),
+            evicted: self.overflow.evicted.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn try_send_error<T>(err: mpsc::error::TrySendError<T>) -> EmitError {
+    match err {
+        mpsc::error::TrySendError::Full(_) => EmitError::QueueFull,
+        mpsc::error::TrySendError::Closed(_) => EmitError::Closed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(text: &str) -> ActionEvent {
+        ActionEvent::NotifyRequested {
+            text: text.to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        }
+    }
+
+    fn text(event: ActionEvent) -> String {
+        match event {
+            ActionEvent::NotifyRequested { text, .. } => text,
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn full_queue_rejects_or_times_out() {
+        let (bus, _rx) = EventBus::with_overflow(1, OverflowPolicy::Reject);
+        assert_eq!(bus.emit(event("a")).await, Ok(()));
+        assert_eq!(bus.emit(event("b")).await, Err(EmitError::QueueFull));
+
+        let policy = OverflowPolicy::Block {
+            timeout: Duration::from_millis(10),
+        };
+        let (blocking, _blocking_rx) = EventBus::with_overflow(1, policy);
+        assert_eq!(blocking.emit(event("a")).await, Ok(()));
+        assert_eq!(blocking.emit(event("b")).await, Err(EmitError::QueueFull));
+        assert_eq!(bus.metrics().rejected, 1);
+        assert_eq!(blocking.metrics().depth, 1);
+    }
+
+    #[tokio::test]
+    async fn emit_all_queues_every_event_or_none() {
+        let (bus, mut rx) = EventBus::with_overflow(2, OverflowPolicy::Reject);
+        assert_eq!(bus.emit(event("a")).await, Ok(()));
+        assert_eq!(
+            bus.emit_all(vec![event("b"), event("c")]).await,
+            Err(EmitError::QueueFull)
+        );
+        assert_eq!(bus.metrics().depth, 1);
+
+        assert_eq!(text(rx.try_recv().unwrap()), "a");
+        assert_eq!(bus.emit_all(vec![event("b"), event("c")]).await, Ok(()));
+        assert_eq!(text(rx.try_recv().unwrap()), "b");
+        assert_eq!(text(rx.try_recv().unwrap()), "c");
+    }
+
+    #[tokio::test]
+    async fn every_subscriber_sees_emitted_events() {
+        let (bus, mut rx) = EventBus::with_overflow(1, OverflowPolicy::Reject);
+        let mut audit = bus.subscribe("audit");
+        let mut forwarder = bus.subscribe("forwarder");
+        assert_eq!(bus.subscriber_count(), 2);
+
+        assert_eq!(bus.emit(event("a")).await, Ok(()));
+        assert_eq!(bus.emit(event("b")).await, Err(EmitError::QueueFull));
+
+        assert_eq!(text(rx.try_recv().unwrap()), "a");
+        assert_eq!(audit.try_recv().map(text), Some("a".to_string()));
+        assert_eq!(forwarder.try_recv().map(text), Some("a".to_string()));
+        assert!(audit.try_recv().is_none(), "rejected events aren't broadcast");
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_evicts_from_the_front() {
+        let (bus, mut rx) = EventBus::with_overflow(2, OverflowPolicy::DropOldest);
+        for name in ["a", "b", "c"] {
+            assert_eq!(bus.emit(event(name)).await, Ok(()));
+        }
+        let metrics = bus.metrics();
+        assert_eq!((metrics.depth, metrics.evicted), (2, 1));
+
+        let mut kept = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if !rx.take_eviction() {
+                kept.push(text(event));
+            }
+        }
+        assert_eq!(kept, vec!["b", "c"]);
     }
 }
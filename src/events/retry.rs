@@ -0,0 +1,124 @@
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::time::sleep;
+
+// Why handling an event failed. Retryable covers things that may work a
+// moment later (LLM timeouts and rate limits, Discord 5xx); Permanent covers
+// things another attempt won't fix, like a reply that doesn't parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventError {
+    Retryable(String),
+    Permanent(String),
+}
+
+impl EventError {
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, EventError::Retryable(_))
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            EventError::Retryable(message) | EventError::Permanent(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    // Doubles after every failed attempt (1-based), capped at max_delay.
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+// Runs `attempt` until it succeeds, fails permanently, or runs out of
+// attempts. On failure returns the last error and how many attempts were made.
+pub async fn run_with_retries<F, Fut>(policy: &RetryPolicy, mut attempt: F) -> Result<(), (EventError, u32)>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), EventError>>,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut tries = 0;
+    loop {
+        tries += 1;
+        match attempt().await {
+            Ok(()) => return Ok(()),
+            Err(err) if err.is_retryable() && tries < max_attempts => {
+                let delay = policy.delay_for(tries);
+                tracing::warn!(attempt = tries, ?delay, error = %err, "event failed, retrying");
+                sleep(delay).await;
+            }
+            Err(err) => return Err((err, tries)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn delay_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 6,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+        };
+        assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(400));
+        assert_eq!(policy.delay_for(4), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn permanent_errors_are_not_retried() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+        };
+        let calls = AtomicU32::new(0);
+        let result = run_with_retries(&policy, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(EventError::Permanent("bad json".to_string()))
+        })
+        .await;
+        assert_eq!(result, Err((EventError::Permanent("bad json".to_string()), 1)));
+
+        calls.store(0, Ordering::SeqCst);
+        let result = run_with_retries(&policy, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(EventError::Retryable("timeout".to_string()))
+        })
+        .await;
+        assert_eq!(result, Err((EventError::Retryable("timeout".to_string()), 5)));
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+}
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::events::queue::QueuedEvent;
+
+struct Timer {
+    // Full turns of the wheel left before this timer is due.
+    rounds: u64,
+    seq: Option<u64>,
+    event: QueuedEvent,
+}
+
+// Hashed timing wheel holding events emitted with EventBus::emit_at. Each slot
+// covers one tick; timers further out than a full turn wait out extra rounds.
+// Deadlines are rounded up to the next tick.
+pub struct TimerWheel {
+    slots: Vec<Vec<Timer>>,
+    tick: Duration,
+    cursor: usize,
+    // Time the cursor's slot was last reached.
+    now: DateTime<Utc>,
+    len: usize,
+}
+
+impl TimerWheel {
+    pub fn new(tick: Duration, slots: usize, now: DateTime<Utc>) -> Self {
+        Self {
+            slots: (0..slots.max(1)).map(|_| Vec::new()).collect(),
+            tick,
+            cursor: 0,
+            now,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Queues `event` for `when`. Anything already due comes straight back so
+    // the caller can dispatch it without waiting for a tick.
+    pub fn insert(
+        &mut self,
+        when: DateTime<Utc>,
+        seq: Option<u64>,
+        event: QueuedEvent,
+    ) -> Option<(Option<u64>, QueuedEvent)> {
+        let wait_ms = (when - self.now).num_milliseconds();
+        if wait_ms <= 0 {
+            return Some((seq, event));
+        }
+        let tick_ms = self.tick.num_milliseconds().max(1);
+        let ticks = (wait_ms as u64).div_ceil(tick_ms as u64);
+        let slot_count = self.slots.len() as u64;
+        let slot = (self.cursor as u64 + ticks) % slot_count;
+        self.slots[slot as usize].push(Timer {
+            rounds: (ticks - 1) / slot_count,
+            seq,
+            event,
+        });
+        self.len += 1;
+        None
+    }
+
+    // Moves the wheel forward to `now` and returns everything that fell due,
+    // in deadline order.
+    pub fn advance(&mut self, now: DateTime<Utc>) -> Vec<(Option<u64>, QueuedEvent)> {
+        let mut due = Vec::new();
+        // Nothing to fire, so skip the empty slots in one go.
+        if self.len == 0 {
+            let tick_ms = self.tick.num_milliseconds().max(1);
+            let ticks = (now - self.now).num_milliseconds() / tick_ms;
+            if ticks > 0 {
+                self.cursor = ((self.cursor as i64 + ticks) % self.slots.len() as i64) as usize;
+                self.now += Duration::milliseconds(ticks * tick_ms);
+            }
+            return due;
+        }
+        while self.now + self.tick <= now {
+            self.cursor = (self.cursor + 1) % self.slots.len();
+            self.now += self.tick;
+            let slot = std::mem::take(&mut self.slots[self.cursor]);
+            for mut timer in slot {
+                if timer.rounds == 0 {
+                    due.push((timer.seq, timer.event));
+                } else {
+                    timer.rounds -= 1;
+                    self.slots[self.cursor].push(timer);
+                }
+            }
+        }
+        self.len -= due.len();
+        due
+    }
+
+    // Empties the wheel, whether due or not, in no particular order.
+    pub fn drain(&mut self) -> Vec<(Option<u64>, QueuedEvent)> {
+        self.len = 0;
+        self.slots
+            .iter_mut()
+            .flat_map(std::mem::take)
+            .map(|timer| (timer.seq, timer.event))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::action::ActionEvent;
+    use chrono::TimeZone;
+
+    fn event(text: &str) -> QueuedEvent {
+        QueuedEvent::new(ActionEvent::NotifyRequested {
+            text: text.to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+    }
+
+    fn texts(due: Vec<(Option<u64>, QueuedEvent)>) -> Vec<String> {
+        due.into_iter()
+            .map(|(_, queued)| match queued.event {
+                ActionEvent::NotifyRequested { text, .. } => text,
+                other => panic!("unexpected event: {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn timers_fire_on_their_tick_across_rounds() {
+        let start = Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap();
+        let mut wheel = TimerWheel::new(Duration::seconds(1), 4, start);
+
+        assert!(wheel.insert(start - Duration::seconds(1), None, event("late")).is_some());
+        assert!(wheel.insert(start + Duration::seconds(10), Some(2), event("ten")).is_none());
+        assert!(wheel.insert(start + Duration::milliseconds(1500), Some(1), event("two")).is_none());
+        assert_eq!(wheel.len(), 2);
+
+        assert!(wheel.advance(start + Duration::seconds(1)).is_empty());
+        assert_eq!(texts(wheel.advance(start + Duration::seconds(2))), vec!["two"]);
+        // Same slot as "two", but a couple of rounds later.
+        assert!(wheel.advance(start + Duration::seconds(9)).is_empty());
+        let due = wheel.advance(start + Duration::seconds(10));
+        assert_eq!(due[0].0, Some(2));
+        assert_eq!(texts(due), vec!["ten"]);
+        assert!(wheel.is_empty());
+    }
+}
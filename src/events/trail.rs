@@ -0,0 +1,217 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::events::queue::QueuedEvent;
+
+// Returns the file every event and its outcome is logged to.
+// Defaults to a relative "./data/events.log" file.
+pub fn get_event_log_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/events.log", base)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventOutcome {
+    Emitted,
+    Handled,
+    // Still failing after retries, so dead-lettered.
+    Failed,
+    // Never reached the engine, e.g. emitted during shutdown.
+    Dropped,
+}
+
+impl EventOutcome {
+    pub fn code(&self) -> &'static str {
+        match self {
+            EventOutcome::Emitted => "emitted",
+            EventOutcome::Handled => "handled",
+            EventOutcome::Failed => "failed",
+            EventOutcome::Dropped => "dropped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub at: DateTime<Utc>,
+    // Same on an event's emitted line and its outcome line.
+    pub correlation_id: String,
+    pub outcome: EventOutcome,
+    pub event: String,
+    pub user_id: String,
+    #[serde(default)]
+    pub channel_id: Option<String>,
+    #[serde(default)]
+    pub action_id: Option<String>,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+impl EventRecord {
+    pub fn new(queued: &QueuedEvent, outcome: EventOutcome, at: DateTime<Utc>) -> Self {
+        let ids = queued.event.ids();
+        Self {
+            at,
+            correlation_id: queued.correlation_id.clone(),
+            outcome,
+            event: ids.name.to_string(),
+            user_id: ids.user_id.to_string(),
+            channel_id: ids.channel_id.map(str::to_string),
+            action_id: ids.action_id.map(str::to_string),
+            detail: None,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+// Assigned once per emit. The journal keeps it with the event, so a replay
+// after a restart logs its outcome under the same id.
+pub fn new_correlation_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+// Append-only JSON-lines log of every event the bus saw. Unlike the journal
+// it is never truncated, so it can answer "what happened to my reminder".
+pub struct EventTrail {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl EventTrail {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn record(&self, record: &EventRecord) -> Result<(), String> {
+        let line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to serialize event record: {}", e))?;
+        let _guard = self.write_lock.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open event log {}: {}", self.path.display(), e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to append to event log: {}", e))
+    }
+
+    // Logging is best effort; a full disk shouldn't stop events being handled.
+    pub fn log(&self, record: EventRecord) {
+        if let Err(err) = self.record(&record) {
+            tracing::warn!(error = %err, "failed to write event log");
+        }
+    }
+
+    // The last `limit` records, oldest first, optionally just one user's.
+    // Unreadable lines (a crash mid-write) are skipped.
+    pub fn tail(&self, limit: usize, user_id: Option<&str>) -> Result<Vec<EventRecord>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let raw = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read event log {}: {}", self.path.display(), e))?;
+        let records: Vec<EventRecord> = raw
+            .lines()
+            .filter_map(|line| serde_json::from_str::<EventRecord>(line).ok())
+            .filter(|record| user_id.is_none_or(|user| record.user_id == user))
+            .collect();
+        let skip = records.len().saturating_sub(limit);
+        Ok(records.into_iter().skip(skip).collect())
+    }
+}
+
+// One line per record for `events tail`.
+pub fn render_record(record: &EventRecord) -> String {
+    let mut line = format!(
+        "{} {} {:<8} {} user={}",
+        record.at.to_rfc3339(),
+        record.correlation_id,
+        record.outcome.code(),
+        record.event,
+        record.user_id
+    );
+    if let Some(channel_id) = &record.channel_id {
+        line.push_str(&format!(" channel={}", channel_id));
+    }
+    if let Some(action_id) = &record.action_id {
+        line.push_str(&format!(" id={}", action_id));
+    }
+    if let Some(detail) = &record.detail {
+        line.push_str(&format!(" — {}", detail));
+    }
+    line
+}
+
+const DEFAULT_TAIL: usize = 20;
+
+// `events tail [n] [--user <id>]`: the last n records (20 by default) from
+// the event log, one per line.
+pub fn tail_command(args: &[String]) -> Result<String, String> {
+    let mut limit = DEFAULT_TAIL;
+    let mut user_id: Option<&str> = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--user" {
+            user_id = Some(args.next().ok_or("--user needs a user id")?.as_str());
+        } else {
+            limit = arg
+                .parse()
+                .map_err(|_| format!("Expected a number of records, got '{}'", arg))?;
+        }
+    }
+    let records = EventTrail::new(get_event_log_location()).tail(limit, user_id)?;
+    Ok(records.iter().map(render_record).collect::<Vec<_>>().join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::action::ActionEvent;
+    use chrono::TimeZone;
+
+    #[test]
+    fn tail_keeps_the_latest_records_for_a_user() {
+        let path = env::temp_dir().join(format!("events_{}.log", uuid::Uuid::new_v4()));
+        let trail = EventTrail::new(&path);
+        let at = Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap();
+        let event = |user: &str| {
+            QueuedEvent::new(ActionEvent::ListRequested {
+                user_id: user.to_string(),
+                channel_id: "123".to_string(),
+            })
+        };
+        let first = event("@a");
+        let again = event("@a");
+
+        trail.log(EventRecord::new(&first, EventOutcome::Emitted, at));
+        trail.log(EventRecord::new(&event("@b"), EventOutcome::Emitted, at));
+        trail.log(EventRecord::new(&again, EventOutcome::Emitted, at));
+        trail.log(EventRecord::new(&first, EventOutcome::Failed, at).with_detail("timeout"));
+
+        let records = trail.tail(10, Some("@a")).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].correlation_id, records[2].correlation_id);
+        assert_ne!(records[0].correlation_id, records[1].correlation_id);
+        assert_eq!(records[2].detail.as_deref(), Some("timeout"));
+        assert_eq!(trail.tail(1, None).unwrap()[0].outcome, EventOutcome::Failed);
+        let _ = fs::remove_file(path);
+    }
+}
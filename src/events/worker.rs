@@ -1,9 +1,241 @@
-use tokio::sync::mpsc;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
+use chrono::Utc;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{MissedTickBehavior, interval};
+use tracing::Instrument;
+
+use crate::events::journal::EventJournal;
+use crate::events::queue::{EventReceiver, QueuedEvent};
+use crate::events::retry::run_with_retries;
+use crate::events::timer::TimerWheel;
+use crate::events::trail::{EventOutcome, EventRecord};
 use crate::handlers::action::{ActionEngine, ActionEvent};
 
-pub async fn run_event_worker(mut rx: mpsc::Receiver<ActionEvent>, engine: ActionEngine) {
-    while let Some(event) = rx.recv().await {
-        engine.handle_event(event).await;
+// Runs until every EventBus is dropped.
+pub async fn run_event_worker(rx: EventReceiver, engine: ActionEngine) {
+    // Never signalled; holding the sender keeps `shutdown` from closing.
+    let (_shutdown_tx, shutdown) = watch::channel(false);
+    run_event_worker_pool_until(rx, engine, 1, shutdown, None).await;
+}
+
+// Runs one event under the engine's retry policy, backing off between
+// retryable failures. Whatever still fails is dead-lettered; `prior_attempts`
+// carries the count over when a dead letter is retried. Returns whether the
+// event was handled.
+pub async fn process_event(engine: &ActionEngine, queued: QueuedEvent, prior_attempts: u32) -> bool {
+    let event = &queued.event;
+    let span = event.span();
+    let result = run_with_retries(engine.retry_policy(), || engine.try_handle_event(event.clone()))
+        .instrument(span.clone())
+        .await;
+    match result {
+        Ok(()) => {
+            if let Some(trail) = engine.trail() {
+                trail.log(EventRecord::new(&queued, EventOutcome::Handled, Utc::now()));
+            }
+            true
+        }
+        Err((err, attempts)) => {
+            if let Some(trail) = engine.trail() {
+                let detail = format!("{} (after {} attempts)", err, prior_attempts + attempts);
+                trail.log(EventRecord::new(&queued, EventOutcome::Failed, Utc::now()).with_detail(detail));
+            }
+            engine
+                .dead_letter(queued.event, &err, prior_attempts + attempts)
+                .instrument(span)
+                .await;
+            false
+        }
+    }
+}
+
+// Like run_event_worker, but once `shutdown` flips to true the queue stops
+// accepting events and everything already queued is handled before returning.
+// With a journal, events a previous run never finished are handled first.
+pub async fn run_event_worker_until(
+    rx: EventReceiver,
+    engine: ActionEngine,
+    shutdown: watch::Receiver<bool>,
+    journal: Option<Arc<EventJournal>>,
+) {
+    run_event_worker_pool_until(rx, engine, 1, shutdown, journal).await;
+}
+
+// Lanes are small; the shared queue in front of them does the buffering.
+const LANE_BUFFER: usize = 16;
+
+type LaneItem = (Option<u64>, QueuedEvent);
+
+// One slot a second on the emit_at timer wheel, so a full turn is an hour.
+const TIMER_TICK_MS: i64 = 1000;
+const TIMER_SLOTS: usize = 3600;
+
+// Which of `lanes` lanes a user's events go to.
+pub fn lane_for(user_id: &str, lanes: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    user_id.hash(&mut hasher);
+    (hasher.finish() % lanes.max(1) as u64) as usize
+}
+
+// Like run_event_worker_until, but spreads events over `lanes` tasks keyed by
+// user, so one user's slow OpenAI call doesn't hold up everyone else while
+// each user's own events still run in the order they were sent.
+pub async fn run_event_worker_pool_until(
+    mut rx: EventReceiver,
+    engine: ActionEngine,
+    lanes: usize,
+    mut shutdown: watch::Receiver<bool>,
+    journal: Option<Arc<EventJournal>>,
+) {
+    let engine = Arc::new(engine);
+    let mut senders: Vec<mpsc::Sender<LaneItem>> = Vec::new();
+    let mut workers = Vec::new();
+    for _ in 0..lanes.max(1) {
+        let (tx, lane_rx) = mpsc::channel(LANE_BUFFER);
+        senders.push(tx);
+        workers.push(tokio::spawn(run_lane(lane_rx, engine.clone(), journal.clone())));
+    }
+
+    let mut timers = TimerWheel::new(
+        chrono::Duration::milliseconds(TIMER_TICK_MS),
+        TIMER_SLOTS,
+        Utc::now(),
+    );
+    let mut ticker = interval(std::time::Duration::from_millis(TIMER_TICK_MS as u64));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let journal = journal.as_deref();
+    if let Some(journal) = journal {
+        let replay = journal.take_replay();
+        if !replay.is_empty() {
+            tracing::info!(count = replay.len(), "replaying journaled events");
+        }
+        for queued in replay {
+            dispatch(&senders, &mut timers, Some(journal), queued).await;
+        }
+    }
+    loop {
+        tokio::select! {
+            queued = rx.recv_queued() => match queued {
+                Some(queued) if rx.take_eviction() => evict(&engine, journal, queued),
+                Some(queued) => dispatch(&senders, &mut timers, journal, queued).await,
+                None => break,
+            },
+            _ = ticker.tick(), if !timers.is_empty() => {
+                for (seq, queued) in timers.advance(Utc::now()) {
+                    send_to_lane(&senders, seq, queued).await;
+                }
+            },
+            changed = shutdown.changed() => {
+                if changed.is_err() || *shutdown.borrow() {
+                    break;
+                }
+            }
+        }
+    }
+    rx.close();
+    tracing::info!("draining event queue");
+    while let Some(queued) = rx.recv_queued().await {
+        if rx.take_eviction() {
+            evict(&engine, journal, queued);
+        } else {
+            dispatch(&senders, &mut timers, journal, queued).await;
+        }
+    }
+    if !timers.is_empty() {
+        if journal.is_some() {
+            tracing::info!(count = timers.len(), "scheduled events left in the journal for the next start");
+        } else {
+            tracing::warn!(count = timers.len(), "dropping scheduled events");
+            if let Some(trail) = engine.trail() {
+                for (_, queued) in timers.drain() {
+                    trail.log(EventRecord::new(&queued, EventOutcome::Dropped, Utc::now()));
+                }
+            }
+        }
+    }
+    // Closing the lanes lets each finish what it has and exit.
+    drop(senders);
+    for worker in workers {
+        if let Err(err) = worker.await {
+            tracing::error!(error = ?err, "event lane failed");
+        }
+    }
+}
+
+// Pushed out by a newer event under OverflowPolicy::DropOldest. Its journal
+// entry is settled so it isn't replayed.
+fn evict(engine: &ActionEngine, journal: Option<&EventJournal>, queued: QueuedEvent) {
+    tracing::warn!(user_id = %queued.event.user_id(), "dropping evicted event");
+    if let Some(journal) = journal {
+        if let Some(seq) = journal.claim_next() {
+            if let Err(err) = journal.ack(seq) {
+                tracing::warn!(error = %err, "failed to ack evicted event");
+            }
+        }
+    }
+    if let Some(trail) = engine.trail() {
+        trail.log(
+            EventRecord::new(&queued, EventOutcome::Dropped, Utc::now())
+                .with_detail("evicted from a full queue"),
+        );
+    }
+}
+
+// Scheduled events wait on the timer wheel, keeping their journal sequence
+// number and correlation id so they are only acked once actually handled
+// and their outcome lines up with the emit.
+async fn dispatch(
+    senders: &[mpsc::Sender<LaneItem>],
+    timers: &mut TimerWheel,
+    journal: Option<&EventJournal>,
+    queued: QueuedEvent,
+) {
+    let seq = journal.and_then(EventJournal::claim_next);
+    let (at, inner) = match queued {
+        QueuedEvent {
+            correlation_id,
+            event: ActionEvent::Scheduled { at, event },
+        } => (
+            at,
+            QueuedEvent {
+                correlation_id,
+                event: *event,
+            },
+        ),
+        queued => {
+            send_to_lane(senders, seq, queued).await;
+            return;
+        }
+    };
+    // Catch the wheel up first so the deadline is measured from now.
+    for (due_seq, due) in timers.advance(Utc::now()) {
+        send_to_lane(senders, due_seq, due).await;
+    }
+    if let Some((seq, queued)) = timers.insert(at, seq, inner) {
+        send_to_lane(senders, seq, queued).await;
+    }
+}
+
+async fn send_to_lane(senders: &[mpsc::Sender<LaneItem>], seq: Option<u64>, queued: QueuedEvent) {
+    let lane = lane_for(queued.event.user_id(), senders.len());
+    let _ = senders[lane].send((seq, queued)).await;
+}
+
+async fn run_lane(
+    mut rx: mpsc::Receiver<LaneItem>,
+    engine: Arc<ActionEngine>,
+    journal: Option<Arc<EventJournal>>,
+) {
+    while let Some((seq, queued)) = rx.recv().await {
+        process_event(&engine, queued, 0).await;
+        if let (Some(journal), Some(seq)) = (&journal, seq) {
+            if let Err(err) = journal.ack(seq) {
+                tracing::warn!(error = %err, "failed to ack journaled event");
+            }
+        }
     }
 }
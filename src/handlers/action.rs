@@ -1,16 +1,44 @@
 use std::collections::HashMap;
+use std::env;
 use std::sync::Arc;
 
 use chrono::{DateTime, Duration, Utc};
-use memory_db::DB;
+use chrono_tz::Tz;
+use memory_db::{DB, DBError, load_db, save_db};
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use crate::events::dead_letter::DeadLetterStore;
+use crate::events::prompt_watch::PromptWatch;
+use crate::events::queue::{EventBus, QueuedEvent};
+use crate::events::retry::{EventError, RetryPolicy};
+use crate::events::trail::EventTrail;
+use crate::events::worker;
+use crate::handlers::middleware::{ActionMiddleware, Flow};
+use crate::i18n::{self, Locale, MessageKey};
 use crate::models::notification::{self, Notification};
+use crate::models::recurrence::Recurrence;
+use crate::models::settings::{UserSettings, get_user_db_location};
+use crate::models::todo::{self, TodoItem};
 use crate::service::approval_prompt::ApprovalPromptService;
-use crate::service::notification_service::NotificationService;
-use crate::service::openai_service::OpenAIClient;
+use crate::service::capacity;
+use crate::service::duplicates;
+use crate::service::find_time;
+use crate::service::ics::{self, ImportedEvent};
+use crate::service::idempotency::{RecentRequests, idempotency_key};
+use crate::service::notification_normalizer;
+use crate::service::notification_service::{self, NotificationService};
+use crate::service::openai_service::{OpenAIClient, parse_model_json};
+use crate::service::latency::{LatencyKind, LatencyTracker, format_latency};
+use crate::service::scheduler::SchedulerWake;
+use crate::service::time_sanity::TimeIssue;
+use crate::service::upcoming::{self, SharedUpcoming};
+use crate::tasks::calendar_loop::{CalendarClient, CalendarEvent};
+use crate::tasks::task_runner::{TaskHealth, format_task_status};
+use crate::tasks::todo_loop::DmSender;
+use crate::tools::calendar::CREATE_CALENDAR_EVENT;
+use crate::tools::{Tool, ToolChoice, ToolRegistry};
 
 pub type ActionId = String;
 
@@ -20,6 +48,8 @@ pub enum ActionType {
     CreateNotification,
     CreateTodo,
     ToolUse,
+    ImportCalendar,
+    FindTime,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -30,6 +60,48 @@ pub enum ActionStatus {
     Rejected,
     Completed,
     Failed,
+    // Nobody answered the approval prompt before the draft lapsed.
+    Expired,
+}
+
+impl ActionStatus {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ActionStatus::Pending => "pending",
+            ActionStatus::AwaitingApproval => "awaiting_approval",
+            ActionStatus::Approved => "approved",
+            ActionStatus::Rejected => "rejected",
+            ActionStatus::Completed => "completed",
+            ActionStatus::Failed => "failed",
+            ActionStatus::Expired => "expired",
+        }
+    }
+
+    // Finished actions never move again; a failed draft can be requeued.
+    pub fn can_transition_to(&self, to: ActionStatus) -> bool {
+        use ActionStatus::*;
+        matches!(
+            (self, to),
+            (Pending, AwaitingApproval | Failed)
+                | (AwaitingApproval, Approved | Rejected | Expired | Failed)
+                | (Approved, Completed | Failed)
+                | (Failed, AwaitingApproval)
+        )
+    }
+
+    pub fn parse(code: &str) -> Option<Self> {
+        [
+            ActionStatus::Pending,
+            ActionStatus::AwaitingApproval,
+            ActionStatus::Approved,
+            ActionStatus::Rejected,
+            ActionStatus::Completed,
+            ActionStatus::Failed,
+            ActionStatus::Expired,
+        ]
+        .into_iter()
+        .find(|status| status.code() == code.trim().to_lowercase())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,11 +114,113 @@ pub struct NotificationDraft {
     pub extra_context: Option<String>,
     pub expires_at: DateTime<Utc>,
     pub message_id: Option<u64>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    // Reminders the user already has that day, and lighter days offered
+    // instead when it is busy (see service::capacity).
+    #[serde(default)]
+    pub day_load: usize,
+    #[serde(default)]
+    pub alternatives: Vec<DateTime<Utc>>,
+    #[serde(default)]
+    pub checklist: Vec<String>,
+    // Events on the user's connected calendar that the reminder time falls
+    // inside.
+    #[serde(default)]
+    pub conflicts: Vec<CalendarConflict>,
+    // A reminder the user already has that looks like this one (see
+    // service::duplicates), offered to merge into instead.
+    #[serde(default)]
+    pub duplicate: Option<SimilarReminder>,
+    // Why the time looks wrong (see service::time_sanity), so the prompt asks
+    // the user to confirm or correct it.
+    #[serde(default)]
+    pub time_issue: Option<TimeIssue>,
+    // Minutes before the event to ping instead of the default schedule.
+    #[serde(default)]
+    pub offsets: Vec<i64>,
+    // The time expression the model read, e.g. "next Friday at 5".
+    #[serde(default)]
+    pub original_phrase: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarReminder {
+    pub notification_id: String,
+    pub content: String,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarConflict {
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoDraft {
+    pub user_id: String,
+    pub channel_id: String,
+    pub items: Vec<String>,
+    pub original_text: String,
+    pub expires_at: DateTime<Utc>,
+    pub message_id: Option<u64>,
+}
+
+// A tool call the LLM proposed, waiting for the user to approve it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDraft {
+    pub user_id: String,
+    pub channel_id: String,
+    pub tool: String,
+    pub arguments: serde_json::Value,
+    // From Tool::describe, shown on the approval prompt.
+    pub summary: String,
+    pub original_text: String,
+    pub expires_at: DateTime<Utc>,
+    pub message_id: Option<u64>,
+}
+
+// Reminders read from an uploaded calendar file, created together once
+// approved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportDraft {
+    pub user_id: String,
+    pub channel_id: String,
+    pub events: Vec<ImportedEvent>,
+    pub expires_at: DateTime<Utc>,
+    pub message_id: Option<u64>,
+}
+
+// Free slots found for a "find me a time" request. Picking one creates the
+// reminder, and the calendar event when one was asked for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlotDraft {
+    pub user_id: String,
+    pub channel_id: String,
+    pub title: String,
+    pub minutes: i64,
+    pub slots: Vec<DateTime<Utc>>,
+    pub add_to_calendar: bool,
+    pub expires_at: DateTime<Utc>,
+    pub message_id: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ActionPayload {
     NotificationDraft(NotificationDraft),
+    TodoDraft(TodoDraft),
+    ToolDraft(ToolDraft),
+    ImportDraft(ImportDraft),
+    SlotDraft(SlotDraft),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub from: ActionStatus,
+    pub to: ActionStatus,
+    pub at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,9 +233,32 @@ pub struct Action {
     pub payload: Option<ActionPayload>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    // Every status change since the action was created, oldest first.
+    #[serde(default)]
+    pub history: Vec<StatusChange>,
 }
 
 impl Action {
+    // The only way the engine changes an action's status. Moves the state
+    // machine doesn't allow (see ActionStatus::can_transition_to) leave the
+    // action untouched.
+    pub fn transition(&mut self, to: ActionStatus, at: DateTime<Utc>) -> Result<(), String> {
+        let from = self.status;
+        if !from.can_transition_to(to) {
+            return Err(format!(
+                "Action {} can't move from {} to {}",
+                self.id,
+                from.code(),
+                to.code()
+            ));
+        }
+        self.status = to;
+        self.updated_at = at;
+        self.history.push(StatusChange { from, to, at });
+        tracing::info!(action_id = %self.id, from = from.code(), to = to.code(), "action status changed");
+        Ok(())
+    }
+
     pub fn notification_draft(&self) -> Option<&NotificationDraft> {
         match &self.payload {
             Some(ActionPayload::NotificationDraft(draft)) => Some(draft),
@@ -75,6 +272,92 @@ impl Action {
             _ => None,
         }
     }
+
+    pub fn todo_draft(&self) -> Option<&TodoDraft> {
+        match &self.payload {
+            Some(ActionPayload::TodoDraft(draft)) => Some(draft),
+            _ => None,
+        }
+    }
+
+    pub fn tool_draft(&self) -> Option<&ToolDraft> {
+        match &self.payload {
+            Some(ActionPayload::ToolDraft(draft)) => Some(draft),
+            _ => None,
+        }
+    }
+
+    pub fn import_draft(&self) -> Option<&ImportDraft> {
+        match &self.payload {
+            Some(ActionPayload::ImportDraft(draft)) => Some(draft),
+            _ => None,
+        }
+    }
+
+    pub fn slot_draft(&self) -> Option<&SlotDraft> {
+        match &self.payload {
+            Some(ActionPayload::SlotDraft(draft)) => Some(draft),
+            _ => None,
+        }
+    }
+
+    // When an unconfirmed draft of any kind lapses.
+    pub fn draft_expires_at(&self) -> Option<DateTime<Utc>> {
+        match &self.payload {
+            Some(ActionPayload::NotificationDraft(draft)) => Some(draft.expires_at),
+            Some(ActionPayload::TodoDraft(draft)) => Some(draft.expires_at),
+            Some(ActionPayload::ToolDraft(draft)) => Some(draft.expires_at),
+            Some(ActionPayload::ImportDraft(draft)) => Some(draft.expires_at),
+            Some(ActionPayload::SlotDraft(draft)) => Some(draft.expires_at),
+            None => None,
+        }
+    }
+
+    // The approval prompt posted for the draft, once there is one.
+    pub fn draft_message_id(&self) -> Option<u64> {
+        match &self.payload {
+            Some(ActionPayload::NotificationDraft(draft)) => draft.message_id,
+            Some(ActionPayload::TodoDraft(draft)) => draft.message_id,
+            Some(ActionPayload::ToolDraft(draft)) => draft.message_id,
+            Some(ActionPayload::ImportDraft(draft)) => draft.message_id,
+            Some(ActionPayload::SlotDraft(draft)) => draft.message_id,
+            None => None,
+        }
+    }
+
+    // One line describing what the action was for, as shown by /history.
+    pub fn summary(&self) -> String {
+        match &self.payload {
+            Some(ActionPayload::NotificationDraft(draft)) => format!("Reminder: {}", draft.content),
+            Some(ActionPayload::TodoDraft(draft)) => format!("Todo: {}", draft.items.join(", ")),
+            Some(ActionPayload::ToolDraft(draft)) => format!("Tool: {}", draft.summary),
+            Some(ActionPayload::ImportDraft(draft)) => {
+                format!("Calendar import: {} reminders", draft.events.len())
+            }
+            Some(ActionPayload::SlotDraft(draft)) => {
+                format!("Find time: {} minutes for {}", draft.minutes, draft.title)
+            }
+            None => format!("{:?}", self.action_type),
+        }
+    }
+
+    pub fn set_draft_expires_at(&mut self, expires_at: DateTime<Utc>) {
+        match &mut self.payload {
+            Some(ActionPayload::NotificationDraft(draft)) => draft.expires_at = expires_at,
+            Some(ActionPayload::TodoDraft(draft)) => draft.expires_at = expires_at,
+            Some(ActionPayload::ToolDraft(draft)) => draft.expires_at = expires_at,
+            Some(ActionPayload::ImportDraft(draft)) => draft.expires_at = expires_at,
+            Some(ActionPayload::SlotDraft(draft)) => draft.expires_at = expires_at,
+            None => {}
+        }
+    }
+}
+
+// Returns the directory where in-flight actions are kept across restarts.
+// Defaults to a relative "./data/actions" directory.
+pub fn get_store_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/actions", base)
 }
 
 #[derive(Debug, Clone)]
@@ -89,6 +372,14 @@ impl ActionStore {
         }
     }
 
+    pub fn from_db(actions: DB<Action>) -> Self {
+        Self { actions }
+    }
+
+    pub fn save(&self) -> Result<(), DBError> {
+        save_db(&get_store_location(), &self.actions)
+    }
+
     pub fn insert(&mut self, action: Action) {
         self.actions.insert(action.id.clone(), action);
     }
@@ -101,14 +392,183 @@ impl ActionStore {
     pub fn ids(&self) -> Vec<ActionId> {
         self.actions.keys().cloned().collect()
     }
+
+    pub fn values(&self) -> impl Iterator<Item = &Action> {
+        self.actions.values()
+    }
+
+    // A user's actions, newest first, optionally only those in `status` or
+    // created at or after `since`.
+    pub fn query(
+        &self,
+        user_id: &str,
+        status: Option<ActionStatus>,
+        since: Option<DateTime<Utc>>,
+    ) -> Vec<&Action> {
+        let mut actions: Vec<&Action> = self
+            .actions
+            .values()
+            .filter(|action| action.user_id == user_id)
+            .filter(|action| status.is_none_or(|status| action.status == status))
+            .filter(|action| since.is_none_or(|since| action.created_at >= since))
+            .collect();
+        actions.sort_by(|a, b| (b.created_at, &b.id).cmp(&(a.created_at, &a.id)));
+        actions
+    }
+
+    // Marks drafts still awaiting approval past their expiry as Expired and
+    // returns them.
+    pub fn expire_lapsed(&mut self, now: DateTime<Utc>) -> Vec<Action> {
+        let mut expired = Vec::new();
+        for action in self.actions.values_mut() {
+            if lapse(action, now) {
+                expired.push(action.clone());
+            }
+        }
+        expired
+    }
+
+    // Like expire_lapsed, for the one draft whose expiry timer just fired.
+    // A draft re-prompted since with a later expiry is left alone.
+    pub fn expire_if_lapsed(&mut self, id: &str, now: DateTime<Utc>) -> Option<Action> {
+        let action = self.actions.get_mut(id)?;
+        lapse(action, now).then(|| action.clone())
+    }
+
+    // Returns how many actions were dropped.
+    pub fn retain(&mut self, keep: impl FnMut(&ActionId, &mut Action) -> bool) -> usize {
+        let before = self.actions.len();
+        self.actions.retain(keep);
+        before - self.actions.len()
+    }
+}
+
+fn lapse(action: &mut Action, now: DateTime<Utc>) -> bool {
+    action.draft_expires_at().is_some_and(|expires_at| expires_at <= now)
+        && action.transition(ActionStatus::Expired, now).is_ok()
+}
+
+// For moves the surrounding checks already guarantee; a refusal is logged
+// rather than bubbled up.
+fn settle(action: &mut Action, to: ActionStatus) {
+    if let Err(err) = action.transition(to, Utc::now()) {
+        tracing::warn!(action_id = %action.id, error = %err, "illegal action transition");
+    }
+}
+
+// Most actions /history and `history` show at once.
+pub const HISTORY_LIMIT: usize = 20;
+// How far back /history looks when no window is given.
+pub const HISTORY_DEFAULT_DAYS: i64 = 7;
+
+pub fn render_action_history(actions: &[&Action], zone: Tz, locale: Locale) -> String {
+    if actions.is_empty() {
+        return i18n::text(locale, MessageKey::HistoryEmpty).to_string();
+    }
+    let mut body = i18n::text(locale, MessageKey::HistoryHeader).to_string();
+    for action in actions.iter().take(HISTORY_LIMIT) {
+        let when = action.created_at.with_timezone(&zone).format("%Y-%m-%d %H:%M");
+        body.push_str(&format!(
+            "\n{} — {} [{}]",
+            when,
+            action.summary(),
+            action.status.code()
+        ));
+    }
+    if actions.len() > HISTORY_LIMIT {
+        let count = (actions.len() - HISTORY_LIMIT).to_string();
+        body.push('\n');
+        body.push_str(&i18n::render(locale, MessageKey::HistoryMore, &[("count", &count)]));
+    }
+    body
+}
+
+// `history <user_id> [--status <status>] [--days <n>]`: the same listing as
+// /history, read from the saved action store.
+pub fn history_command(args: &[String]) -> Result<String, String> {
+    let mut args = args.iter();
+    let user_id = args.next().ok_or("Usage: history <user_id> [--status <status>] [--days <n>]")?;
+    let mut status = None;
+    let mut since = None;
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+        match arg.as_str() {
+            "--status" => {
+                status = Some(
+                    ActionStatus::parse(value).ok_or_else(|| format!("Unknown status '{}'", value))?,
+                );
+            }
+            "--days" => {
+                let days: i64 = value
+                    .parse()
+                    .map_err(|_| format!("Expected a number of days, got '{}'", value))?;
+                since = Some(Utc::now() - Duration::days(days));
+            }
+            other => return Err(format!("Unknown option '{}'", other)),
+        }
+    }
+    let store = ActionStore::from_db(load_db(&get_store_location()).unwrap_or_else(|_| HashMap::new()));
+    let users: DB<UserSettings> = load_db(&get_user_db_location()).unwrap_or_default();
+    let user = users.get(user_id.as_str());
+    Ok(render_action_history(
+        &store.query(user_id, status, since),
+        user.map_or_else(|| UserSettings::default().zone(), UserSettings::zone),
+        Locale::resolve(user, None),
+    ))
+}
+
+// Operator commands from /admin. Stats carries the numbers only the handler
+// can see.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AdminCommand {
+    Stats { queue_depth: usize, todo_count: usize },
+    PurgeExpired,
+    RequeueFailed,
+    DeadLetterList,
+    // None retries every dead letter.
+    DeadLetterRetry { id: Option<String> },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ActionEvent {
     NotifyRequested {
         text: String,
         user_id: String,
         channel_id: String,
+        // When the interaction arrived, for prompt latency. None from
+        // callers that don't track it.
+        #[serde(default)]
+        received_at: Option<DateTime<Utc>>,
+    },
+    // A "todolist" intent; drafted and confirmed like a notification.
+    TodoRequested {
+        text: String,
+        user_id: String,
+        channel_id: String,
+    },
+    // From /tool; the LLM picks a tool and the user approves the call.
+    ToolRequested {
+        text: String,
+        user_id: String,
+        channel_id: String,
+    },
+    // A "tooluse" intent; drafted as a create_calendar_event call.
+    CalendarEventRequested {
+        text: String,
+        user_id: String,
+        channel_id: String,
+    },
+    // From /import, carrying the uploaded .ics file's contents.
+    ImportRequested {
+        ics: String,
+        user_id: String,
+        channel_id: String,
+    },
+    // "find 30 minutes tomorrow for ..."; see service::find_time.
+    FindTimeRequested {
+        text: String,
+        user_id: String,
+        channel_id: String,
     },
     ApprovalConfirmed {
         action_id: String,
@@ -118,11 +578,267 @@ pub enum ActionEvent {
         action_id: String,
         user_id: String,
     },
+    // Scheduled for a draft's expiry when its prompt is posted.
+    ApprovalExpired {
+        action_id: String,
+        user_id: String,
+    },
     ContextSubmitted {
         action_id: String,
         user_id: String,
         context: String,
     },
+    // One of the lighter days offered on a busy day's approval prompt.
+    DraftRescheduled {
+        action_id: String,
+        user_id: String,
+        time: DateTime<Utc>,
+    },
+    // "Merge" on a prompt flagged as a likely repeat: the existing reminder
+    // takes the draft's time and checklist instead of a new one being made.
+    DuplicateMerged {
+        action_id: String,
+        user_id: String,
+    },
+    // One of the free slots offered for a find-time request.
+    SlotChosen {
+        action_id: String,
+        user_id: String,
+        time: DateTime<Utc>,
+    },
+    // The same, typed as a 1-based number in reply to the latest offer.
+    SlotPicked {
+        user_id: String,
+        channel_id: String,
+        index: usize,
+    },
+    NotificationCompleted {
+        notification_id: String,
+        user_id: String,
+    },
+    // The user's Dismiss button: stop it for them, not for everyone.
+    NotificationDismissed {
+        notification_id: String,
+        user_id: String,
+    },
+    SkipNextOccurrence {
+        user_id: String,
+        channel_id: String,
+        query: String,
+    },
+    ListRequested {
+        user_id: String,
+        channel_id: String,
+    },
+    // "what do I have this week": the list, limited to [from, until).
+    QueryRequested {
+        user_id: String,
+        channel_id: String,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    },
+    // "cancel the dentist one"; `query` picks the reminder out by content
+    // unless the user already chose one by id.
+    CancelReminder {
+        user_id: String,
+        channel_id: String,
+        query: String,
+        #[serde(default)]
+        reminder_id: Option<String>,
+    },
+    SnoozeReminder {
+        user_id: String,
+        channel_id: String,
+        query: String,
+        minutes: i64,
+        #[serde(default)]
+        reminder_id: Option<String>,
+    },
+    // From /history.
+    HistoryRequested {
+        user_id: String,
+        channel_id: String,
+        status: Option<ActionStatus>,
+        since: Option<DateTime<Utc>>,
+    },
+    SetPaused {
+        user_id: String,
+        channel_id: String,
+        index: usize,
+        paused: bool,
+    },
+    RequireAck {
+        user_id: String,
+        channel_id: String,
+        index: usize,
+        backup: Option<String>,
+        window_minutes: Option<i64>,
+    },
+    AdminRequested {
+        user_id: String,
+        channel_id: String,
+        command: AdminCommand,
+    },
+    // Emitted by EventBus::emit_at. The worker holds `event` on its timer
+    // wheel and dispatches it once `at` has passed.
+    Scheduled {
+        at: DateTime<Utc>,
+        event: Box<ActionEvent>,
+    },
+}
+
+impl ActionEvent {
+    pub fn user_id(&self) -> &str {
+        match self {
+            ActionEvent::NotifyRequested { user_id, .. }
+            | ActionEvent::TodoRequested { user_id, .. }
+            | ActionEvent::ToolRequested { user_id, .. }
+            | ActionEvent::CalendarEventRequested { user_id, .. }
+            | ActionEvent::ImportRequested { user_id, .. }
+            | ActionEvent::FindTimeRequested { user_id, .. }
+            | ActionEvent::ApprovalConfirmed { user_id, .. }
+            | ActionEvent::ApprovalCanceled { user_id, .. }
+            | ActionEvent::ApprovalExpired { user_id, .. }
+            | ActionEvent::ContextSubmitted { user_id, .. }
+            | ActionEvent::DraftRescheduled { user_id, .. }
+            | ActionEvent::DuplicateMerged { user_id, .. }
+            | ActionEvent::SlotChosen { user_id, .. }
+            | ActionEvent::SlotPicked { user_id, .. }
+            | ActionEvent::NotificationCompleted { user_id, .. }
+            | ActionEvent::NotificationDismissed { user_id, .. }
+            | ActionEvent::SkipNextOccurrence { user_id, .. }
+            | ActionEvent::ListRequested { user_id, .. }
+            | ActionEvent::QueryRequested { user_id, .. }
+            | ActionEvent::CancelReminder { user_id, .. }
+            | ActionEvent::SnoozeReminder { user_id, .. }
+            | ActionEvent::HistoryRequested { user_id, .. }
+            | ActionEvent::SetPaused { user_id, .. }
+            | ActionEvent::RequireAck { user_id, .. }
+            | ActionEvent::AdminRequested { user_id, .. } => user_id,
+            ActionEvent::Scheduled { event, .. } => event.user_id(),
+        }
+    }
+
+    // The ids this event concerns, for tying logs back to the interaction
+    // that produced it.
+    pub fn ids(&self) -> EventIds<'_> {
+        let (name, user_id, channel_id, action_id) = match self {
+            ActionEvent::NotifyRequested { user_id, channel_id, .. } => {
+                ("notify_requested", user_id, Some(channel_id), None)
+            }
+            ActionEvent::TodoRequested { user_id, channel_id, .. } => {
+                ("todo_requested", user_id, Some(channel_id), None)
+            }
+            ActionEvent::ToolRequested { user_id, channel_id, .. } => {
+                ("tool_requested", user_id, Some(channel_id), None)
+            }
+            ActionEvent::CalendarEventRequested { user_id, channel_id, .. } => {
+                ("calendar_event_requested", user_id, Some(channel_id), None)
+            }
+            ActionEvent::ImportRequested { user_id, channel_id, .. } => {
+                ("import_requested", user_id, Some(channel_id), None)
+            }
+            ActionEvent::FindTimeRequested { user_id, channel_id, .. } => {
+                ("find_time_requested", user_id, Some(channel_id), None)
+            }
+            ActionEvent::ApprovalConfirmed { action_id, user_id } => {
+                ("approval_confirmed", user_id, None, Some(action_id))
+            }
+            ActionEvent::ApprovalCanceled { action_id, user_id } => {
+                ("approval_canceled", user_id, None, Some(action_id))
+            }
+            ActionEvent::ApprovalExpired { action_id, user_id } => {
+                ("approval_expired", user_id, None, Some(action_id))
+            }
+            ActionEvent::ContextSubmitted { action_id, user_id, .. } => {
+                ("context_submitted", user_id, None, Some(action_id))
+            }
+            ActionEvent::DraftRescheduled { action_id, user_id, .. } => {
+                ("draft_rescheduled", user_id, None, Some(action_id))
+            }
+            ActionEvent::DuplicateMerged { action_id, user_id } => {
+                ("duplicate_merged", user_id, None, Some(action_id))
+            }
+            ActionEvent::SlotChosen { action_id, user_id, .. } => {
+                ("slot_chosen", user_id, None, Some(action_id))
+            }
+            ActionEvent::SlotPicked { user_id, channel_id, .. } => {
+                ("slot_picked", user_id, Some(channel_id), None)
+            }
+            ActionEvent::NotificationCompleted { notification_id, user_id } => {
+                ("notification_completed", user_id, None, Some(notification_id))
+            }
+            ActionEvent::NotificationDismissed { notification_id, user_id } => {
+                ("notification_dismissed", user_id, None, Some(notification_id))
+            }
+            ActionEvent::SkipNextOccurrence { user_id, channel_id, .. } => {
+                ("skip_next_occurrence", user_id, Some(channel_id), None)
+            }
+            ActionEvent::ListRequested { user_id, channel_id } => {
+                ("list_requested", user_id, Some(channel_id), None)
+            }
+            ActionEvent::QueryRequested { user_id, channel_id, .. } => {
+                ("query_requested", user_id, Some(channel_id), None)
+            }
+            ActionEvent::CancelReminder { user_id, channel_id, .. } => {
+                ("cancel_reminder", user_id, Some(channel_id), None)
+            }
+            ActionEvent::SnoozeReminder { user_id, channel_id, .. } => {
+                ("snooze_reminder", user_id, Some(channel_id), None)
+            }
+            ActionEvent::HistoryRequested { user_id, channel_id, .. } => {
+                ("history_requested", user_id, Some(channel_id), None)
+            }
+            ActionEvent::SetPaused { user_id, channel_id, .. } => {
+                ("set_paused", user_id, Some(channel_id), None)
+            }
+            ActionEvent::RequireAck { user_id, channel_id, .. } => {
+                ("require_ack", user_id, Some(channel_id), None)
+            }
+            ActionEvent::AdminRequested { user_id, channel_id, .. } => {
+                ("admin_requested", user_id, Some(channel_id), None)
+            }
+            ActionEvent::Scheduled { event, .. } => return event.ids(),
+        };
+        EventIds {
+            name,
+            user_id,
+            channel_id: channel_id.map(String::as_str),
+            action_id: action_id.map(String::as_str),
+        }
+    }
+
+    pub fn span(&self) -> tracing::Span {
+        let EventIds {
+            name,
+            user_id,
+            channel_id,
+            action_id,
+        } = self.ids();
+        let span = tracing::info_span!(
+            "action_event",
+            event = name,
+            user_id = %user_id,
+            channel_id = tracing::field::Empty,
+            action_id = tracing::field::Empty,
+        );
+        if let Some(channel_id) = channel_id {
+            span.record("channel_id", channel_id);
+        }
+        if let Some(action_id) = action_id {
+            span.record("action_id", action_id);
+        }
+        span
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventIds<'a> {
+    pub name: &'static str,
+    pub user_id: &'a str,
+    pub channel_id: Option<&'a str>,
+    // The action or notification the event is about.
+    pub action_id: Option<&'a str>,
 }
 
 pub struct ActionEngine {
@@ -130,6 +846,22 @@ pub struct ActionEngine {
     openai: Arc<dyn OpenAIClient>,
     approval: Arc<dyn ApprovalPromptService>,
     notification_db: Arc<Mutex<DB<Notification>>>,
+    dead_letters: Arc<Mutex<DeadLetterStore>>,
+    retry_policy: RetryPolicy,
+    trail: Option<Arc<EventTrail>>,
+    upcoming: Option<SharedUpcoming>,
+    scheduler: Option<SchedulerWake>,
+    bus: Option<EventBus>,
+    latency: Option<Arc<LatencyTracker>>,
+    task_health: Option<TaskHealth>,
+    todo_db: Option<Arc<Mutex<DB<TodoItem>>>>,
+    tools: ToolRegistry,
+    middleware: Vec<Arc<dyn ActionMiddleware>>,
+    calendar: Option<(Arc<dyn CalendarClient>, String)>,
+    recent_requests: Arc<Mutex<RecentRequests>>,
+    user_settings: Option<Arc<Mutex<DB<UserSettings>>>>,
+    dm: Option<Arc<dyn DmSender>>,
+    prompt_watch: Option<PromptWatch>,
 }
 
 impl ActionEngine {
@@ -144,89 +876,350 @@ impl ActionEngine {
             openai,
             approval,
             notification_db,
+            dead_letters: Arc::new(Mutex::new(DeadLetterStore::new())),
+            retry_policy: RetryPolicy::default(),
+            trail: None,
+            upcoming: None,
+            scheduler: None,
+            bus: None,
+            latency: None,
+            task_health: None,
+            todo_db: None,
+            tools: ToolRegistry::new(),
+            middleware: Vec::new(),
+            calendar: None,
+            recent_requests: Arc::new(Mutex::new(RecentRequests::default())),
+            user_settings: None,
+            dm: None,
+            prompt_watch: None,
         }
     }
 
-    pub async fn handle_event(&self, event: ActionEvent) {
-        match event {
-            ActionEvent::NotifyRequested {
-                text,
-                user_id,
-                channel_id,
-            } => {
-                let payload = match self.openai.generate_prompt(&text, "notification").await {
-                    Ok(p) => p,
-                    Err(err) => {
-                        let _ = self.approval.update_status_message(
-                            &channel_id,
-                            &user_id,
-                            &format!("Failed to call OpenAI for notification: {}", err),
-                        ).await;
-                        return;
-                    }
-                };
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
 
-                let ai_notification: notification::AINotification = match serde_json::from_str(&payload) {
-                    Ok(r) => r,
-                    Err(err) => {
-                        let _ = self.approval.update_status_message(
-                            &channel_id,
-                            &user_id,
-                            &format!("Failed to parse notification JSON: {}", err),
-                        ).await;
-                        return;
-                    }
-                };
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
 
-                let now = Utc::now();
-                let pending_id = Uuid::new_v4().to_string();
-                let mut action = Action {
-                    id: pending_id,
-                    action_type: ActionType::CreateNotification,
-                    status: ActionStatus::AwaitingApproval,
-                    user_id: user_id.clone(),
-                    channel_id: channel_id.clone(),
-                    payload: Some(ActionPayload::NotificationDraft(NotificationDraft {
-                        user_id: user_id.clone(),
-                        channel_id: channel_id.clone(),
-                        content: ai_notification.content,
-                        time: ai_notification.time,
-                        original_text: text.clone(),
-                        extra_context: None,
-                        expires_at: now + Duration::minutes(5),
-                        message_id: None,
-                    })),
-                    created_at: now,
-                    updated_at: now,
-                };
+    pub fn with_dead_letters(mut self, dead_letters: Arc<Mutex<DeadLetterStore>>) -> Self {
+        self.dead_letters = dead_letters;
+        self
+    }
 
-                if self.approval.prompt(&mut action).await.is_err() {
-                    action.status = ActionStatus::Failed;
-                }
+    // Where handled and failed outcomes are logged.
+    pub fn with_trail(mut self, trail: Arc<EventTrail>) -> Self {
+        self.trail = Some(trail);
+        self
+    }
 
-                let mut store = self.store.lock().await;
-                store.insert(action);
-            }
-            ActionEvent::ApprovalConfirmed { action_id, user_id } => {
-                let action_snapshot = {
-                    let store = self.store.lock().await;
-                    store.get(&action_id).cloned()
-                };
+    pub fn trail(&self) -> Option<&EventTrail> {
+        self.trail.as_deref()
+    }
+
+    // Rebuilt after every event that changes a reminder.
+    pub fn with_upcoming(mut self, upcoming: SharedUpcoming) -> Self {
+        self.upcoming = Some(upcoming);
+        self
+    }
+
+    // Woken after every event that changes a reminder, so the notification
+    // loop picks up a new deadline without waiting out its sleep.
+    pub fn with_scheduler(mut self, scheduler: SchedulerWake) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    // Where confirmed todo drafts are saved. Without it they fail on confirm.
+    pub fn with_todos(mut self, todo_db: Arc<Mutex<DB<TodoItem>>>) -> Self {
+        self.todo_db = Some(todo_db);
+        self
+    }
+
+    // Tools /tool may call. With none registered every request is declined.
+    pub fn with_tools(mut self, tools: ToolRegistry) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    // `user_id`'s calendar, checked for clashes before their drafts are
+    // prompted.
+    pub fn with_calendar(mut self, client: Arc<dyn CalendarClient>, user_id: impl Into<String>) -> Self {
+        self.calendar = Some((client, user_id.into()));
+        self
+    }
+
+    // How long a repeated /notify request is treated as a retry of the first
+    // and dropped.
+    pub fn with_duplicate_window(mut self, window: Duration) -> Self {
+        self.recent_requests = Arc::new(Mutex::new(RecentRequests::new(window)));
+        self
+    }
+
+    // Records how long each approval prompt took from interaction to send.
+    pub fn with_latency(mut self, latency: Arc<LatencyTracker>) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    // Lists the supervised background tasks in /admin stats.
+    pub fn with_task_health(mut self, health: TaskHealth) -> Self {
+        self.task_health = Some(health);
+        self
+    }
+
+    // Each user's /settings, for replying in their language. Without it every
+    // reply is English.
+    pub fn with_user_settings(mut self, user_settings: Arc<Mutex<DB<UserSettings>>>) -> Self {
+        self.user_settings = Some(user_settings);
+        self
+    }
+
+    async fn locale_for(&self, user_id: &str) -> Locale {
+        match &self.user_settings {
+            Some(settings) => Locale::resolve(settings.lock().await.get(user_id), None),
+            None => Locale::default(),
+        }
+    }
+
+    // Lets the engine schedule its own follow-ups, such as expiring a draft
+    // the moment its prompt lapses rather than on the next sweep. The worker
+    // still stops on its shutdown signal while the engine holds this.
+    pub fn with_event_bus(mut self, bus: EventBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    // Tells the /notify interaction waiting on a draft when its prompt is up.
+    pub fn with_prompt_watch(mut self, watch: PromptWatch) -> Self {
+        self.prompt_watch = Some(watch);
+        self
+    }
+
+    // Posts the approval prompt and schedules the draft's expiry. This runs
+    // on a worker lane, so a full queue isn't waited on; the approval sweep
+    // still catches an expiry that couldn't be queued.
+    async fn prompt(&self, action: &mut Action) -> Result<(), String> {
+        self.approval.prompt(action).await?;
+        if let Some(watch) = &self.prompt_watch {
+            watch.posted(&action.user_id, &action.channel_id);
+        }
+        if let (Some(bus), Some(expires_at)) = (&self.bus, action.draft_expires_at()) {
+            let expiry = ActionEvent::ApprovalExpired {
+                action_id: action.id.clone(),
+                user_id: action.user_id.clone(),
+            };
+            if let Err(err) = bus.try_emit_at(expiry, expires_at).await {
+                tracing::warn!(action_id = %action.id, error = %err, "failed to schedule approval expiry");
+            }
+        }
+        Ok(())
+    }
+
+    // Posts a new draft's confirm prompt and stores the draft. When Discord
+    // rejects the prompt the draft is kept as Failed so /admin requeue can
+    // post it again.
+    async fn post_draft(&self, mut action: Action) {
+        if let Err(err) = self.prompt(&mut action).await {
+            tracing::warn!(action_id = %action.id, error = %err, "failed to post approval prompt");
+            settle(&mut action, ActionStatus::Failed);
+        }
+        self.store.lock().await.insert(action);
+    }
+
+    // How replies only the requester should see (their history) reach them.
+    // Without it those replies are dropped rather than posted in the channel.
+    pub fn with_dm_sender(mut self, dm: Arc<dyn DmSender>) -> Self {
+        self.dm = Some(dm);
+        self
+    }
+
+    async fn reply_privately(&self, user_id: &str, message: &str) {
+        let Some(dm) = &self.dm else {
+            tracing::warn!(%user_id, "no DM sender, dropping private reply");
+            return;
+        };
+        if let Err(err) = dm.send_dm(user_id.trim_start_matches('@'), message).await {
+            tracing::warn!(%user_id, error = %err, "failed to send private reply");
+        }
+    }
+
+    // The timezone the model reads `user_id`'s times in.
+    async fn zone_for(&self, user_id: &str) -> Tz {
+        if let Some(settings) = &self.user_settings {
+            if let Some(user) = settings.lock().await.get(user_id) {
+                return user.zone();
+            }
+        }
+        UserSettings::default().zone()
+    }
+
+    // Runs around every event; see handlers::middleware for the ordering.
+    pub fn with_middleware(mut self, middleware: Arc<dyn ActionMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    // Handles the event under the retry policy, dead-lettering it if it
+    // still fails.
+    pub async fn handle_event(&self, event: ActionEvent) {
+        worker::process_event(self, QueuedEvent::new(event), 0).await;
+    }
+
+    // A single attempt; callers decide whether to retry.
+    pub async fn try_handle_event(&self, event: ActionEvent) -> Result<(), EventError> {
+        // Only reaches the engine when handled outside the worker (e.g. a
+        // dead-letter retry), by which point the wait is moot. Unwrapped
+        // here so middleware sees the real event.
+        let mut event = match event {
+            ActionEvent::Scheduled { event, .. } => *event,
+            other => other,
+        };
+        for middleware in &self.middleware {
+            if let Flow::Stop(reason) = middleware.before_handle(&mut event).await {
+                tracing::info!(middleware = middleware.name(), %reason, "event stopped by middleware");
+                return Ok(());
+            }
+        }
+        let handled = (!self.middleware.is_empty()).then(|| event.clone());
+        let changes_reminders = matches!(
+            event,
+            ActionEvent::ApprovalConfirmed { .. }
+                | ActionEvent::SlotChosen { .. }
+                | ActionEvent::SlotPicked { .. }
+                | ActionEvent::DuplicateMerged { .. }
+                | ActionEvent::NotificationCompleted { .. }
+                | ActionEvent::NotificationDismissed { .. }
+                | ActionEvent::SkipNextOccurrence { .. }
+                | ActionEvent::CancelReminder { .. }
+                | ActionEvent::SnoozeReminder { .. }
+                | ActionEvent::SetPaused { .. }
+                | ActionEvent::RequireAck { .. }
+                | ActionEvent::AdminRequested {
+                    command: AdminCommand::PurgeExpired,
+                    ..
+                }
+        );
+        let result = self.apply_event(event).await;
+        if changes_reminders {
+            if let Some(upcoming) = &self.upcoming {
+                let db = self.notification_db.lock().await;
+                upcoming::refresh(upcoming, &db, Utc::now()).await;
+            }
+            if let Some(scheduler) = &self.scheduler {
+                scheduler.wake();
+            }
+        }
+        if let Some(event) = handled {
+            for middleware in self.middleware.iter().rev() {
+                middleware.after_handle(&event, &result).await;
+            }
+        }
+        result
+    }
+
+    async fn apply_event(&self, event: ActionEvent) -> Result<(), EventError> {
+        match event {
+            // try_handle_event unwraps these; nested ones are unwrapped again.
+            ActionEvent::Scheduled { event, .. } => {
+                return Box::pin(self.try_handle_event(*event)).await;
+            }
+            ActionEvent::NotifyRequested {
+                text,
+                user_id,
+                channel_id,
+                received_at,
+            } => {
+                let key = idempotency_key(&user_id, &channel_id, &text);
+                let at = received_at.unwrap_or_else(Utc::now);
+                if !self.recent_requests.lock().await.claim(key, at) {
+                    tracing::info!(%user_id, %channel_id, "dropping a repeated reminder request");
+                    return Ok(());
+                }
+                let result = self
+                    .request_notification(text, user_id, channel_id, received_at)
+                    .await;
+                if result.is_err() {
+                    self.recent_requests.lock().await.release(key);
+                }
+                return result;
+            }
+            ActionEvent::TodoRequested {
+                text,
+                user_id,
+                channel_id,
+            } => {
+                return self.request_todo(text, user_id, channel_id).await;
+            }
+            ActionEvent::ToolRequested {
+                text,
+                user_id,
+                channel_id,
+            } => {
+                return self.request_tool(text, user_id, channel_id).await;
+            }
+            ActionEvent::CalendarEventRequested {
+                text,
+                user_id,
+                channel_id,
+            } => {
+                return self.request_calendar_event(text, user_id, channel_id).await;
+            }
+            ActionEvent::ImportRequested {
+                ics,
+                user_id,
+                channel_id,
+            } => {
+                return self.request_import(ics, user_id, channel_id).await;
+            }
+            ActionEvent::FindTimeRequested {
+                text,
+                user_id,
+                channel_id,
+            } => {
+                return self.request_find_time(text, user_id, channel_id).await;
+            }
+            ActionEvent::ApprovalConfirmed { action_id, user_id } => {
+                let action_snapshot = {
+                    let store = self.store.lock().await;
+                    store.get(&action_id).cloned()
+                };
 
                 let Some(mut action) = action_snapshot else {
-                    return;
+                    return Ok(());
                 };
 
-                if action.user_id != user_id || action.status != ActionStatus::AwaitingApproval {
-                    return;
+                if action.user_id != user_id {
+                    return Ok(());
+                }
+                // A second click on an already-answered prompt lands here.
+                if let Err(err) = action.transition(ActionStatus::Approved, Utc::now()) {
+                    tracing::debug!(%action_id, error = %err, "ignoring confirmation");
+                    return Ok(());
                 }
 
-                action.status = ActionStatus::Approved;
-                action.updated_at = Utc::now();
+                if action.todo_draft().is_some() {
+                    self.confirm_todo(action).await;
+                    return Ok(());
+                }
+                if action.tool_draft().is_some() {
+                    self.confirm_tool(action).await;
+                    return Ok(());
+                }
+                if action.import_draft().is_some() {
+                    self.confirm_import(action).await;
+                    return Ok(());
+                }
+                // A ✅ reaction can't pick a slot, so it takes the first.
+                if let Some(time) = action.slot_draft().and_then(|draft| draft.slots.first().copied()) {
+                    self.confirm_slot(action, time).await;
+                    return Ok(());
+                }
 
                 let Some(draft) = action.notification_draft() else {
-                    action.status = ActionStatus::Failed;
-                    action.updated_at = Utc::now();
+                    settle(&mut action, ActionStatus::Failed);
                     let _ = self
                         .approval
                         .update_status_message(
@@ -237,7 +1230,7 @@ impl ActionEngine {
                         .await;
                     let mut store = self.store.lock().await;
                     store.insert(action);
-                    return;
+                    return Ok(());
                 };
 
                 let mut db = self.notification_db.lock().await;
@@ -247,12 +1240,24 @@ impl ActionEngine {
                     &action.user_id,
                     &draft.time,
                     &action.channel_id,
+                    draft.recurrence.clone(),
                 )
                 .await;
+                let result = match result {
+                    Ok(id) if !draft.offsets.is_empty() => {
+                        notification::set_offsets(&mut db, &id, &draft.offsets).map(|_| id)
+                    }
+                    other => other,
+                };
+                let result = match result {
+                    Ok(id) if !draft.checklist.is_empty() => {
+                        notification::set_checklist(&mut db, &id, &draft.checklist).map(|_| id)
+                    }
+                    other => other,
+                };
 
                 if result.is_ok() {
-                    action.status = ActionStatus::Completed;
-                    action.updated_at = Utc::now();
+                    settle(&mut action, ActionStatus::Completed);
                     let message = if let Some(draft) = action.notification_draft() {
                         format!(
                             "Confirmed! I'll notify you: \"{}\" at {}",
@@ -263,8 +1268,7 @@ impl ActionEngine {
                     };
                     let _ = self.approval.update_status(&action, &message).await;
                 } else {
-                    action.status = ActionStatus::Failed;
-                    action.updated_at = Utc::now();
+                    settle(&mut action, ActionStatus::Failed);
                     let _ = self.approval.update_status_message(
                         &action.channel_id,
                         &action.user_id,
@@ -282,20 +1286,38 @@ impl ActionEngine {
                 };
 
                 let Some(mut action) = action_snapshot else {
-                    return;
+                    return Ok(());
                 };
 
-                if action.user_id != user_id || action.status != ActionStatus::AwaitingApproval {
-                    return;
+                if action.user_id != user_id {
+                    return Ok(());
                 }
-
-                action.status = ActionStatus::Rejected;
-                action.updated_at = Utc::now();
-                let _ = self.approval.update_status(&action, "Canceled notification request.").await;
+                if let Err(err) = action.transition(ActionStatus::Rejected, Utc::now()) {
+                    tracing::debug!(%action_id, error = %err, "ignoring cancellation");
+                    return Ok(());
+                }
+                let key = match &action.payload {
+                    Some(ActionPayload::TodoDraft(_)) => MessageKey::TodoCanceled,
+                    Some(ActionPayload::ToolDraft(_)) => MessageKey::ToolCanceled,
+                    Some(ActionPayload::ImportDraft(_)) => MessageKey::ImportCanceled,
+                    Some(ActionPayload::SlotDraft(_)) => MessageKey::SlotSearchCanceled,
+                    _ => MessageKey::NotificationCanceled,
+                };
+                let locale = self.locale_for(&action.user_id).await;
+                let _ = self.approval.update_status(&action, i18n::text(locale, key)).await;
 
                 let mut store = self.store.lock().await;
                 store.insert(action);
             }
+            ActionEvent::ApprovalExpired { action_id, user_id } => {
+                let expired = self.store.lock().await.expire_if_lapsed(&action_id, Utc::now());
+                if let Some(action) = expired {
+                    tracing::info!(%action_id, %user_id, "approval prompt expired");
+                    if let Err(err) = self.approval.expire(&action).await {
+                        tracing::warn!(%action_id, error = %err, "failed to expire approval prompt");
+                    }
+                }
+            }
             ActionEvent::ContextSubmitted {
                 action_id,
                 user_id,
@@ -307,17 +1329,17 @@ impl ActionEngine {
                 };
 
                 let Some(mut action) = action_snapshot else {
-                    return;
+                    return Ok(());
                 };
 
                 if action.user_id != user_id || action.status != ActionStatus::AwaitingApproval {
-                    return;
+                    return Ok(());
                 }
 
                 let mut combined_prompt = if let Some(draft) = action.notification_draft() {
                     draft.original_text.clone()
                 } else {
-                    return;
+                    return Ok(());
                 };
 
                 if !context.trim().is_empty() {
@@ -328,12 +1350,13 @@ impl ActionEngine {
                     );
                 }
 
+                let zone = self.zone_for(&user_id).await;
                 let refreshed = match self
                     .openai
-                    .generate_prompt(&combined_prompt, "notification_correction")
+                    .generate_prompt_in(&combined_prompt, "notification_correction", zone)
                     .await
                 {
-                    Ok(payload) => serde_json::from_str::<notification::AINotification>(&payload).ok(),
+                    Ok(payload) => parse_model_json::<notification::AINotification>(&payload).ok(),
                     Err(_) => None,
                 };
 
@@ -342,17 +1365,1428 @@ impl ActionEngine {
                         if !context.trim().is_empty() {
                             draft.extra_context = Some(context.trim().to_string());
                         }
+                        if !updated.checklist.is_empty() {
+                            draft.checklist = updated.checklist.clone();
+                        }
+                        let updated =
+                            notification_normalizer::normalize(updated, &draft.original_text, Utc::now());
+                        draft.time_issue = updated.time_issue(Utc::now());
                         draft.content = updated.content;
                         draft.time = updated.time;
+                        draft.offsets = updated.offsets;
+                        draft.original_phrase = updated.original_phrase;
+                        if updated.recurrence.is_some() {
+                            draft.recurrence = updated.recurrence;
+                        }
                     }
 
-                    let _ = self.approval.prompt(&mut action).await;
+                    self.annotate_day_load(&mut action).await;
+                    self.annotate_conflicts(&mut action).await;
+                    self.annotate_duplicate(&mut action).await;
+                    let _ = self.prompt(&mut action).await;
                     action.updated_at = Utc::now();
 
                     let mut store = self.store.lock().await;
                     store.insert(action);
                 }
             }
+            ActionEvent::DraftRescheduled {
+                action_id,
+                user_id,
+                time,
+            } => {
+                let action_snapshot = {
+                    let store = self.store.lock().await;
+                    store.get(&action_id).cloned()
+                };
+
+                let Some(mut action) = action_snapshot else {
+                    return Ok(());
+                };
+
+                if action.user_id != user_id || action.status != ActionStatus::AwaitingApproval {
+                    return Ok(());
+                }
+
+                // Only the days we offered, so a crafted button id can't move it elsewhere.
+                let Some(draft) = action
+                    .notification_draft_mut()
+                    .filter(|draft| draft.alternatives.contains(&time))
+                else {
+                    return Ok(());
+                };
+                draft.time = time;
+                draft.time_issue = None;
+                self.annotate_day_load(&mut action).await;
+                self.annotate_conflicts(&mut action).await;
+                self.annotate_duplicate(&mut action).await;
+                let _ = self.prompt(&mut action).await;
+                action.updated_at = Utc::now();
+
+                let mut store = self.store.lock().await;
+                store.insert(action);
+            }
+            ActionEvent::DuplicateMerged { action_id, user_id } => {
+                self.merge_duplicate(&action_id, &user_id).await;
+            }
+            ActionEvent::SlotChosen {
+                action_id,
+                user_id,
+                time,
+            } => {
+                self.choose_slot(&action_id, &user_id, time).await;
+            }
+            ActionEvent::SlotPicked {
+                user_id,
+                channel_id,
+                index,
+            } => {
+                let picked = {
+                    let store = self.store.lock().await;
+                    store
+                        .values()
+                        .filter(|action| action.user_id == user_id && action.status == ActionStatus::AwaitingApproval)
+                        .filter_map(|action| {
+                            let draft = action.slot_draft()?;
+                            Some((action.created_at, action.id.clone(), draft.slots.get(index.checked_sub(1)?).copied()))
+                        })
+                        .max_by_key(|(created_at, _, _)| *created_at)
+                };
+                match picked {
+                    Some((_, action_id, Some(time))) => self.choose_slot(&action_id, &user_id, time).await,
+                    Some(_) => {
+                        let message = format!("There is no slot #{} in that offer.", index);
+                        let _ = self
+                            .approval
+                            .update_status_message(&channel_id, &user_id, &message)
+                            .await;
+                    }
+                    None => {
+                        let message = "There are no free slots waiting on a pick.";
+                        let _ = self
+                            .approval
+                            .update_status_message(&channel_id, &user_id, message)
+                            .await;
+                    }
+                }
+            }
+            ActionEvent::NotificationCompleted {
+                notification_id,
+                user_id,
+            } => {
+                let mut db = self.notification_db.lock().await;
+                if let Err(err) =
+                    notification::complete_notification(&mut db, &notification_id, &user_id, Utc::now())
+                {
+                    tracing::error!(%notification_id, error = %err, "failed to complete notification");
+                }
+            }
+            ActionEvent::NotificationDismissed {
+                notification_id,
+                user_id,
+            } => {
+                let mut db = self.notification_db.lock().await;
+                if let Err(err) =
+                    notification::dismiss_notification(&mut db, &notification_id, &user_id, Utc::now())
+                {
+                    tracing::error!(%notification_id, error = %err, "failed to dismiss notification");
+                }
+            }
+            ActionEvent::SkipNextOccurrence {
+                user_id,
+                channel_id,
+                query,
+            } => {
+                let message = self.skip_next_occurrence(&user_id, &query).await;
+                let _ = self
+                    .approval
+                    .update_status_message(&channel_id, &user_id, &message)
+                    .await;
+            }
+            ActionEvent::SetPaused {
+                user_id,
+                channel_id,
+                index,
+                paused,
+            } => {
+                let message = self.set_paused(&user_id, index, paused).await;
+                let _ = self
+                    .approval
+                    .update_status_message(&channel_id, &user_id, &message)
+                    .await;
+            }
+            ActionEvent::RequireAck {
+                user_id,
+                channel_id,
+                index,
+                backup,
+                window_minutes,
+            } => {
+                let message = self
+                    .require_ack(&user_id, index, backup, window_minutes)
+                    .await;
+                let _ = self
+                    .approval
+                    .update_status_message(&channel_id, &user_id, &message)
+                    .await;
+            }
+            ActionEvent::ListRequested {
+                user_id,
+                channel_id,
+            } => {
+                let message = {
+                    let db = self.notification_db.lock().await;
+                    notification_service::render_notification_list(&db, &user_id)
+                };
+                let _ = self
+                    .approval
+                    .update_status_message(&channel_id, &user_id, &message)
+                    .await;
+            }
+            ActionEvent::QueryRequested {
+                user_id,
+                channel_id,
+                from,
+                until,
+            } => {
+                let message = {
+                    let db = self.notification_db.lock().await;
+                    notification_service::render_notifications_between(&db, &user_id, from, until)
+                };
+                let _ = self
+                    .approval
+                    .update_status_message(&channel_id, &user_id, &message)
+                    .await;
+            }
+            ActionEvent::CancelReminder {
+                user_id,
+                channel_id,
+                query,
+                reminder_id,
+            } => {
+                let message = self.cancel_reminder(&user_id, &query, reminder_id.as_deref()).await;
+                let _ = self
+                    .approval
+                    .update_status_message(&channel_id, &user_id, &message)
+                    .await;
+            }
+            ActionEvent::SnoozeReminder {
+                user_id,
+                channel_id,
+                query,
+                minutes,
+                reminder_id,
+            } => {
+                let message = self
+                    .snooze_reminder(&user_id, &query, minutes, reminder_id.as_deref())
+                    .await;
+                let _ = self
+                    .approval
+                    .update_status_message(&channel_id, &user_id, &message)
+                    .await;
+            }
+            // Posted by DM: the listing is nobody else's business.
+            ActionEvent::HistoryRequested {
+                user_id,
+                channel_id: _,
+                status,
+                since,
+            } => {
+                let zone = self.zone_for(&user_id).await;
+                let locale = self.locale_for(&user_id).await;
+                let message = {
+                    let store = self.store.lock().await;
+                    render_action_history(&store.query(&user_id, status, since), zone, locale)
+                };
+                self.reply_privately(&user_id, &message).await;
+            }
+            // Operator output stays out of the channel.
+            ActionEvent::AdminRequested { user_id, command, .. } => {
+                let locale = self.locale_for(&user_id).await;
+                let message = match command {
+                    AdminCommand::Stats {
+                        queue_depth,
+                        todo_count,
+                    } => self.admin_stats(queue_depth, todo_count, locale).await,
+                    AdminCommand::PurgeExpired => self.purge_expired(Utc::now(), locale).await,
+                    AdminCommand::RequeueFailed => self.requeue_failed(Utc::now(), locale).await,
+                    AdminCommand::DeadLetterList => self.dead_letter_list(locale).await,
+                    AdminCommand::DeadLetterRetry { id } => {
+                        self.dead_letter_retry(id.as_deref(), locale).await
+                    }
+                };
+                self.reply_privately(&user_id, &message).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn request_notification(
+        &self,
+        text: String,
+        user_id: String,
+        channel_id: String,
+        received_at: Option<DateTime<Utc>>,
+    ) -> Result<(), EventError> {
+        let ai_notification = self.draft_notification(&text, &user_id).await?;
+        self.prompt_notification_draft(ai_notification, text, user_id, channel_id)
+            .await?;
+        if let (Some(latency), Some(received_at)) = (&self.latency, received_at) {
+            latency.record(LatencyKind::Prompt, Utc::now() - received_at);
+        }
+        Ok(())
+    }
+
+    // Same retry split as notifications: a failed call is retried, a reply
+    // that doesn't parse is not.
+    async fn request_todo(
+        &self,
+        text: String,
+        user_id: String,
+        channel_id: String,
+    ) -> Result<(), EventError> {
+        let payload = self
+            .openai
+            .generate_prompt(&text, "todo_extraction")
+            .await
+            .map_err(|err| EventError::Retryable(format!("Failed to call OpenAI for todo: {}", err)))?;
+        let extracted: todo::AITodo = parse_model_json(&payload).map_err(|err| {
+            EventError::Permanent(format!("Failed to parse todo JSON: {}", err))
+        })?;
+        let now = Utc::now();
+        let action = Action {
+            id: Uuid::new_v4().to_string(),
+            action_type: ActionType::CreateTodo,
+            status: ActionStatus::AwaitingApproval,
+            user_id: user_id.clone(),
+            channel_id: channel_id.clone(),
+            payload: Some(ActionPayload::TodoDraft(TodoDraft {
+                user_id,
+                channel_id,
+                items: todo::draft_items(extracted, &text),
+                original_text: text,
+                expires_at: now + Duration::minutes(5),
+                message_id: None,
+            })),
+            created_at: now,
+            updated_at: now,
+            history: Vec::new(),
+        };
+        self.post_draft(action).await;
+        Ok(())
+    }
+
+    async fn confirm_todo(&self, mut action: Action) {
+        let items = action.todo_draft().map(|draft| draft.items.clone()).unwrap_or_default();
+        let saved = match &self.todo_db {
+            Some(todo_db) => {
+                let mut db = todo_db.lock().await;
+                todo::create_todos(&mut db, &action.user_id, &items)
+                    .map(|_| ())
+                    .map_err(|err| err.to_string())
+            }
+            None => Err("no todo store configured".to_string()),
+        };
+        let locale = self.locale_for(&action.user_id).await;
+        match saved {
+            Ok(()) => {
+                settle(&mut action, ActionStatus::Completed);
+                let message = i18n::render(locale, MessageKey::TodosAdded, &[("items", &items.join(", "))]);
+                let _ = self.approval.update_status(&action, &message).await;
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to save todo draft");
+                settle(&mut action, ActionStatus::Failed);
+                let _ = self
+                    .approval
+                    .update_status_message(
+                        &action.channel_id,
+                        &action.user_id,
+                        i18n::text(locale, MessageKey::TodoSaveFailed),
+                    )
+                    .await;
+            }
+        }
+        let mut store = self.store.lock().await;
+        store.insert(action);
+    }
+
+    // Asks the LLM which registered tool fits the request. When none does, or
+    // its arguments don't hold up, the user is told and nothing is drafted.
+    async fn request_tool(
+        &self,
+        text: String,
+        user_id: String,
+        channel_id: String,
+    ) -> Result<(), EventError> {
+        if self.tools.is_empty() {
+            let locale = self.locale_for(&user_id).await;
+            let _ = self
+                .approval
+                .update_status_message(&channel_id, &user_id, i18n::text(locale, MessageKey::NoToolsAvailable))
+                .await;
+            return Ok(());
+        }
+        let prompt = format!(
+            "Tools: {}\nUser message: {}",
+            self.tools.catalog(),
+            text
+        );
+        let zone = self.zone_for(&user_id).await;
+        let payload = self
+            .openai
+            .generate_prompt_in(&prompt, "tool_selection", zone)
+            .await
+            .map_err(|err| EventError::Retryable(format!("Failed to call OpenAI for tool: {}", err)))?;
+        let choice: ToolChoice = parse_model_json(&payload).map_err(|err| {
+            EventError::Permanent(format!("Failed to parse tool JSON: {}", err))
+        })?;
+        let Some(tool) = choice.tool.as_deref().and_then(|name| self.tools.get(name)) else {
+            let locale = self.locale_for(&user_id).await;
+            let _ = self
+                .approval
+                .update_status_message(&channel_id, &user_id, i18n::text(locale, MessageKey::NoToolForThat))
+                .await;
+            return Ok(());
+        };
+        self.draft_tool_call(tool, choice.arguments, text, user_id, channel_id)
+            .await
+    }
+
+    // Skips tool selection: the calendar_event_extraction prompt fills in the
+    // create_calendar_event arguments directly.
+    async fn request_calendar_event(
+        &self,
+        text: String,
+        user_id: String,
+        channel_id: String,
+    ) -> Result<(), EventError> {
+        let Some(tool) = self.tools.get(CREATE_CALENDAR_EVENT) else {
+            let locale = self.locale_for(&user_id).await;
+            let _ = self
+                .approval
+                .update_status_message(&channel_id, &user_id, i18n::text(locale, MessageKey::CalendarEventsUnavailable))
+                .await;
+            return Ok(());
+        };
+        let zone = self.zone_for(&user_id).await;
+        let payload = self
+            .openai
+            .generate_prompt_in(&text, "calendar_event_extraction", zone)
+            .await
+            .map_err(|err| {
+                EventError::Retryable(format!("Failed to call OpenAI for calendar event: {}", err))
+            })?;
+        let arguments: serde_json::Value = parse_model_json(&payload).map_err(|err| {
+            EventError::Permanent(format!("Failed to parse calendar event JSON: {}", err))
+        })?;
+        self.draft_tool_call(tool, arguments, text, user_id, channel_id)
+            .await
+    }
+
+    // Prompts for approval of a call; arguments the tool can't use are
+    // reported back instead.
+    async fn draft_tool_call(
+        &self,
+        tool: Arc<dyn Tool>,
+        arguments: serde_json::Value,
+        text: String,
+        user_id: String,
+        channel_id: String,
+    ) -> Result<(), EventError> {
+        let summary = match tool.describe(&arguments) {
+            Ok(summary) => summary,
+            Err(err) => {
+                let _ = self
+                    .approval
+                    .update_status_message(&channel_id, &user_id, &err)
+                    .await;
+                return Ok(());
+            }
+        };
+        let now = Utc::now();
+        let action = Action {
+            id: Uuid::new_v4().to_string(),
+            action_type: ActionType::ToolUse,
+            status: ActionStatus::AwaitingApproval,
+            user_id: user_id.clone(),
+            channel_id: channel_id.clone(),
+            payload: Some(ActionPayload::ToolDraft(ToolDraft {
+                user_id,
+                channel_id,
+                tool: tool.name().to_string(),
+                arguments,
+                summary,
+                original_text: text,
+                expires_at: now + Duration::minutes(5),
+                message_id: None,
+            })),
+            created_at: now,
+            updated_at: now,
+            history: Vec::new(),
+        };
+        self.post_draft(action).await;
+        Ok(())
+    }
+
+    async fn confirm_tool(&self, mut action: Action) {
+        let Some(draft) = action.tool_draft().cloned() else {
+            return;
+        };
+        let result = match self.tools.get(&draft.tool) {
+            Some(tool) => tool.execute(&action.user_id, &draft.arguments).await,
+            None => Err(format!("The {} tool is no longer available.", draft.tool)),
+        };
+        match result {
+            Ok(message) => {
+                settle(&mut action, ActionStatus::Completed);
+                let _ = self.approval.update_status(&action, &message).await;
+            }
+            Err(err) => {
+                tracing::warn!(tool = %draft.tool, error = %err, "tool call failed");
+                settle(&mut action, ActionStatus::Failed);
+                let _ = self
+                    .approval
+                    .update_status_message(&action.channel_id, &action.user_id, &err)
+                    .await;
+            }
+        }
+        let mut store = self.store.lock().await;
+        store.insert(action);
+    }
+
+    // No LLM involved: the file is parsed here and the upcoming events are
+    // offered as one batch. Files that don't parse, or hold nothing ahead,
+    // are reported back without a prompt.
+    async fn request_import(
+        &self,
+        ics: String,
+        user_id: String,
+        channel_id: String,
+    ) -> Result<(), EventError> {
+        let now = Utc::now();
+        let events = match ics::parse_calendar(&ics) {
+            Ok(events) => ics::importable(events, now),
+            Err(err) => {
+                let _ = self
+                    .approval
+                    .update_status_message(&channel_id, &user_id, &err)
+                    .await;
+                return Ok(());
+            }
+        };
+        if events.is_empty() {
+            let locale = self.locale_for(&user_id).await;
+            let message = i18n::text(locale, MessageKey::ImportNothingUpcoming);
+            let _ = self
+                .approval
+                .update_status_message(&channel_id, &user_id, message)
+                .await;
+            return Ok(());
+        }
+        let action = Action {
+            id: Uuid::new_v4().to_string(),
+            action_type: ActionType::ImportCalendar,
+            status: ActionStatus::AwaitingApproval,
+            user_id: user_id.clone(),
+            channel_id: channel_id.clone(),
+            payload: Some(ActionPayload::ImportDraft(ImportDraft {
+                user_id,
+                channel_id,
+                events,
+                expires_at: now + Duration::minutes(5),
+                message_id: None,
+            })),
+            created_at: now,
+            updated_at: now,
+            history: Vec::new(),
+        };
+        self.post_draft(action).await;
+        Ok(())
+    }
+
+    // Every event becomes a reminder or none does, so a failed save can be
+    // confirmed again without duplicates.
+    async fn confirm_import(&self, mut action: Action) {
+        let items: Vec<(String, DateTime<Utc>)> = action
+            .import_draft()
+            .map(|draft| draft.events.iter().map(|event| (event.summary.clone(), event.start)).collect())
+            .unwrap_or_default();
+        let locale = self.locale_for(&action.user_id).await;
+        let created = {
+            let mut db = self.notification_db.lock().await;
+            notification::create_notifications(&mut db, &action.user_id, &action.channel_id, &items)
+        };
+        match created {
+            Ok(ids) => {
+                settle(&mut action, ActionStatus::Completed);
+                let message = i18n::render(locale, MessageKey::ImportDone, &[("count", &ids.len().to_string())]);
+                let _ = self.approval.update_status(&action, &message).await;
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "calendar import failed");
+                settle(&mut action, ActionStatus::Failed);
+                let message = i18n::render(locale, MessageKey::ImportFailed, &[("error", &err.to_string())]);
+                let _ = self
+                    .approval
+                    .update_status_message(&action.channel_id, &action.user_id, &message)
+                    .await;
+            }
+        }
+        let mut store = self.store.lock().await;
+        store.insert(action);
+    }
+
+    // Offers the first free slots of the requested length that day. Only the
+    // calendar's owner has their events taken into account; anyone else
+    // gets slots inside working hours.
+    async fn request_find_time(
+        &self,
+        text: String,
+        user_id: String,
+        channel_id: String,
+    ) -> Result<(), EventError> {
+        let locale = self.locale_for(&user_id).await;
+        let Some(request) = find_time::parse_find_time(&text) else {
+            let message = i18n::text(locale, MessageKey::FindTimeUnclear);
+            let _ = self
+                .approval
+                .update_status_message(&channel_id, &user_id, message)
+                .await;
+            return Ok(());
+        };
+        let now = Utc::now();
+        let zone = self.zone_for(&user_id).await;
+        let day = find_time::resolve_day(request.day, now, zone);
+        let busy = self
+            .busy_times(&user_id, day, zone)
+            .await
+            .map_err(|err| EventError::Retryable(format!("Failed to read the calendar: {}", err)))?;
+        let slots = find_time::free_slots(day, request.minutes, &busy, now, zone);
+        if slots.is_empty() {
+            let day = find_time::search_window(day, zone)
+                .map(|(open, _)| format!("<t:{}:D>", open.timestamp()))
+                .unwrap_or_else(|| day.to_string());
+            let message = i18n::render(
+                locale,
+                MessageKey::NoFreeSlot,
+                &[("minutes", &request.minutes.to_string()), ("day", &day)],
+            );
+            let _ = self
+                .approval
+                .update_status_message(&channel_id, &user_id, &message)
+                .await;
+            return Ok(());
+        }
+        let action = Action {
+            id: Uuid::new_v4().to_string(),
+            action_type: ActionType::FindTime,
+            status: ActionStatus::AwaitingApproval,
+            user_id: user_id.clone(),
+            channel_id: channel_id.clone(),
+            payload: Some(ActionPayload::SlotDraft(SlotDraft {
+                user_id,
+                channel_id,
+                title: request.title,
+                minutes: request.minutes,
+                slots,
+                add_to_calendar: request.add_to_calendar,
+                expires_at: now + Duration::minutes(5),
+                message_id: None,
+            })),
+            created_at: now,
+            updated_at: now,
+            history: Vec::new(),
+        };
+        self.post_draft(action).await;
+        Ok(())
+    }
+
+    // The user's calendar events overlapping `day`'s search window. The
+    // window can cross midnight UTC, so both UTC days are read.
+    async fn busy_times(
+        &self,
+        user_id: &str,
+        day: chrono::NaiveDate,
+        zone: Tz,
+    ) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>, String> {
+        let Some((client, owner)) = &self.calendar else {
+            return Ok(Vec::new());
+        };
+        let Some((open, close)) = find_time::search_window(day, zone) else {
+            return Ok(Vec::new());
+        };
+        if user_id != owner {
+            return Ok(Vec::new());
+        }
+        let mut events = client.get_events_for_day(open.date_naive()).await?;
+        if close.date_naive() != open.date_naive() {
+            events.extend(client.get_events_for_day(close.date_naive()).await?);
+        }
+        Ok(events
+            .into_iter()
+            .map(|event| (event.start_time, event.end_time.max(event.start_time)))
+            .filter(|(start, end)| *start < close && *end > open)
+            .collect())
+    }
+
+    async fn confirm_slot(&self, mut action: Action, time: DateTime<Utc>) {
+        let Some(draft) = action.slot_draft().cloned() else {
+            return;
+        };
+        let created = {
+            let mut db = self.notification_db.lock().await;
+            NotificationService::create(
+                &mut db,
+                &draft.title,
+                &action.user_id,
+                &time,
+                &action.channel_id,
+                None,
+            )
+            .await
+        };
+        let locale = self.locale_for(&action.user_id).await;
+        if let Err(err) = created {
+            tracing::warn!(error = %err, "failed to save chosen slot");
+            settle(&mut action, ActionStatus::Failed);
+            let message = i18n::render(locale, MessageKey::ReminderSaveFailed, &[("error", &err.to_string())]);
+            let _ = self
+                .approval
+                .update_status_message(&action.channel_id, &action.user_id, &message)
+                .await;
+            let mut store = self.store.lock().await;
+            store.insert(action);
+            return;
+        }
+        let mut key = MessageKey::SlotBooked;
+        if draft.add_to_calendar {
+            let result = match &self.calendar {
+                Some((client, owner)) if *owner == action.user_id => {
+                    client
+                        .create_event(CalendarEvent {
+                            id: None,
+                            title: draft.title.clone(),
+                            start_time: time,
+                            end_time: time + Duration::minutes(draft.minutes),
+                            description: None,
+                        })
+                        .await
+                }
+                _ => Err("no calendar is connected for you".to_string()),
+            };
+            key = match result {
+                Ok(()) => MessageKey::SlotBookedOnCalendar,
+                Err(err) => {
+                    tracing::warn!(error = %err, "failed to add chosen slot to the calendar");
+                    MessageKey::SlotBookedNotOnCalendar
+                }
+            };
+        }
+        let message = i18n::render(
+            locale,
+            key,
+            &[("title", &draft.title), ("time", &format!("<t:{}:f>", time.timestamp()))],
+        );
+        settle(&mut action, ActionStatus::Completed);
+        let _ = self.approval.update_status(&action, &message).await;
+        let mut store = self.store.lock().await;
+        store.insert(action);
+    }
+
+    // Call failures (timeouts, rate limits, 5xx) are worth retrying; a reply
+    // that doesn't parse is not.
+    async fn draft_notification(
+        &self,
+        text: &str,
+        user_id: &str,
+    ) -> Result<notification::AINotification, EventError> {
+        let zone = self.zone_for(user_id).await;
+        let payload = self
+            .openai
+            .generate_prompt_in(text, "notification", zone)
+            .await
+            .map_err(|err| {
+                EventError::Retryable(format!("Failed to call OpenAI for notification: {}", err))
+            })?;
+        parse_model_json(&payload).map_err(|err| {
+            EventError::Permanent(format!("Failed to parse notification JSON: {}", err))
+        })
+    }
+
+    // Tells the user what went wrong and parks the event for /admin dlq retry.
+    pub async fn dead_letter(&self, event: ActionEvent, error: &EventError, attempts: u32) {
+        tracing::warn!(attempts, error = %error, "event dead-lettered");
+        if let ActionEvent::NotifyRequested {
+            user_id,
+            channel_id,
+            ..
+        }
+        | ActionEvent::TodoRequested {
+            user_id,
+            channel_id,
+            ..
+        }
+        | ActionEvent::ToolRequested {
+            user_id,
+            channel_id,
+            ..
+        }
+        | ActionEvent::CalendarEventRequested {
+            user_id,
+            channel_id,
+            ..
+        }
+        | ActionEvent::ImportRequested {
+            user_id,
+            channel_id,
+            ..
+        }
+        | ActionEvent::FindTimeRequested {
+            user_id,
+            channel_id,
+            ..
+        } = &event
+        {
+            let _ = self
+                .approval
+                .update_status_message(channel_id, user_id, error.message())
+                .await;
+        }
+        let mut dead_letters = self.dead_letters.lock().await;
+        dead_letters.insert(event, error.message(), attempts, Utc::now());
+        if let Err(err) = dead_letters.save() {
+            tracing::error!(error = %err, "failed to save dead letters");
+        }
+    }
+
+    async fn prompt_notification_draft(
+        &self,
+        ai_notification: notification::AINotification,
+        text: String,
+        user_id: String,
+        channel_id: String,
+    ) -> Result<(), EventError> {
+        let now = Utc::now();
+        let checklist = ai_notification.checklist.clone();
+        let normalized = notification_normalizer::normalize(ai_notification, &text, now);
+        let time_issue = normalized.time_issue(now);
+        let pending_id = Uuid::new_v4().to_string();
+        let mut action = Action {
+            id: pending_id,
+            action_type: ActionType::CreateNotification,
+            status: ActionStatus::AwaitingApproval,
+            user_id: user_id.clone(),
+            channel_id: channel_id.clone(),
+            payload: Some(ActionPayload::NotificationDraft(NotificationDraft {
+                user_id: user_id.clone(),
+                channel_id: channel_id.clone(),
+                content: normalized.content,
+                time: normalized.time,
+                original_text: text.clone(),
+                extra_context: None,
+                expires_at: now + Duration::minutes(5),
+                message_id: None,
+                recurrence: normalized.recurrence,
+                day_load: 0,
+                alternatives: Vec::new(),
+                checklist,
+                conflicts: Vec::new(),
+                duplicate: None,
+                time_issue,
+                offsets: normalized.offsets,
+                original_phrase: normalized.original_phrase,
+            })),
+            created_at: now,
+            updated_at: now,
+            history: Vec::new(),
+        };
+
+        self.annotate_day_load(&mut action).await;
+        self.annotate_conflicts(&mut action).await;
+        self.annotate_duplicate(&mut action).await;
+        self.post_draft(action).await;
+        Ok(())
+    }
+
+    // Records how busy the draft's day already is and, when it is crowded,
+    // which nearby days are lighter.
+    async fn annotate_day_load(&self, action: &mut Action) {
+        let zone = self.zone_for(&action.user_id).await;
+        let open_todos = match &self.todo_db {
+            Some(todo_db) => todo_db
+                .lock()
+                .await
+                .values()
+                .filter(|item| item.user_id == action.user_id && item.completed_at.is_none())
+                .count(),
+            None => 0,
+        };
+        let Some(draft) = action.notification_draft_mut() else {
+            return;
+        };
+        let db = self.notification_db.lock().await;
+        let load = capacity::DayLoad {
+            db: &db,
+            user_id: &draft.user_id,
+            zone,
+            open_todos,
+            now: Utc::now(),
+        };
+        draft.day_load = load.on(load.day_of(draft.time));
+        draft.alternatives = if draft.day_load >= capacity::BUSY_DAY_THRESHOLD {
+            load.lighter_days(draft.time)
+        } else {
+            Vec::new()
+        };
+    }
+
+    // Records the user's existing reminder this draft most looks like.
+    async fn annotate_duplicate(&self, action: &mut Action) {
+        let Some(draft) = action.notification_draft_mut() else {
+            return;
+        };
+        let db = self.notification_db.lock().await;
+        draft.duplicate = duplicates::find_similar(&db, &draft.user_id, &draft.content, draft.time).map(|existing| {
+            SimilarReminder {
+                notification_id: existing.id.clone(),
+                content: existing.content.clone(),
+                time: duplicates::scheduled_for(existing).unwrap_or(draft.time),
+            }
+        });
+    }
+
+    // Folds the draft into the existing reminder it was flagged against.
+    // When that reminder has gone since, the draft is prompted again without
+    // the warning.
+    async fn merge_duplicate(&self, action_id: &str, user_id: &str) {
+        let action_snapshot = {
+            let store = self.store.lock().await;
+            store.get(action_id).cloned()
+        };
+        let Some(mut action) = action_snapshot else {
+            return;
+        };
+        if action.user_id != user_id || action.status != ActionStatus::AwaitingApproval {
+            return;
+        }
+        let Some(draft) = action.notification_draft().cloned() else {
+            return;
+        };
+        let Some(duplicate) = draft.duplicate else {
+            return;
+        };
+        // The action is moved on before the stored reminder is touched, so a
+        // refused transition leaves both as they were.
+        let now = Utc::now();
+        let result = {
+            let mut db = self.notification_db.lock().await;
+            let Some(existing) = db.get_mut(&duplicate.notification_id) else {
+                drop(db);
+                self.annotate_duplicate(&mut action).await;
+                let _ = self.prompt(&mut action).await;
+                action.updated_at = Utc::now();
+                self.store.lock().await.insert(action);
+                return;
+            };
+            if let Err(err) = action.transition(ActionStatus::Approved, now) {
+                tracing::debug!(%action_id, error = %err, "ignoring merge");
+                return;
+            }
+            if duplicates::scheduled_for(existing) != Some(draft.time) {
+                notification::reschedule(existing, draft.time, now);
+            }
+            let content = existing.content.clone();
+            let mut checklist: Vec<String> = existing.checklist.iter().map(|item| item.text.clone()).collect();
+            let before = checklist.len();
+            for item in &draft.checklist {
+                if !checklist.contains(item) {
+                    checklist.push(item.clone());
+                }
+            }
+            let result = if checklist.len() > before {
+                notification::set_checklist(&mut db, &duplicate.notification_id, &checklist).map(|_| ())
+            } else {
+                save_db(&notification::get_db_location(), &*db)
+            };
+            result.map(|()| content)
+        };
+        let locale = self.locale_for(&action.user_id).await;
+        let message = match result {
+            Ok(content) => {
+                settle(&mut action, ActionStatus::Completed);
+                let time = format!("<t:{}:f>", draft.time.timestamp());
+                i18n::render(
+                    locale,
+                    MessageKey::DuplicateMerged,
+                    &[("content", &content), ("time", &time)],
+                )
+            }
+            Err(err) => {
+                tracing::error!(%action_id, error = %err, "failed to merge reminder");
+                settle(&mut action, ActionStatus::Failed);
+                i18n::text(locale, MessageKey::DuplicateMergeFailed).to_string()
+            }
+        };
+        let _ = self.approval.update_status(&action, &message).await;
+        self.store.lock().await.insert(action);
+    }
+
+    // Records the calendar events the draft's time lands in. A calendar that
+    // can't be read just means no warning.
+    async fn annotate_conflicts(&self, action: &mut Action) {
+        let Some((client, owner)) = &self.calendar else {
+            return;
+        };
+        let Some(draft) = action.notification_draft_mut() else {
+            return;
+        };
+        if draft.user_id != *owner {
+            return;
+        }
+        let time = draft.time;
+        // By interval rather than by day, so an event that started the
+        // evening before and runs past midnight still counts.
+        let window = client.get_events_between(time, time + Duration::minutes(1)).await;
+        draft.conflicts = match window {
+            Ok(events) => events
+                .into_iter()
+                .filter(|event| {
+                    event.start_time == time || (event.start_time <= time && time < event.end_time)
+                })
+                .map(|event| CalendarConflict {
+                    title: event.title,
+                    start: event.start_time,
+                    end: event.end_time,
+                })
+                .collect(),
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to check the calendar for conflicts");
+                Vec::new()
+            }
+        };
+    }
+
+    async fn dead_letter_list(&self, locale: Locale) -> String {
+        let dead_letters = self.dead_letters.lock().await;
+        if dead_letters.is_empty() {
+            return i18n::text(locale, MessageKey::AdminNoDeadLetters).to_string();
+        }
+        let count = dead_letters.len().to_string();
+        let mut body = i18n::render(locale, MessageKey::AdminDeadLetters, &[("count", &count)]);
+        for letter in dead_letters.list() {
+            let summary = match &letter.event {
+                ActionEvent::NotifyRequested { text, user_id, .. } => {
+                    format!("notify from {}: \"{}\"", user_id, text)
+                }
+                ActionEvent::TodoRequested { text, user_id, .. } => {
+                    format!("todo from {}: \"{}\"", user_id, text)
+                }
+                ActionEvent::ToolRequested { text, user_id, .. } => {
+                    format!("tool request from {}: \"{}\"", user_id, text)
+                }
+                ActionEvent::CalendarEventRequested { text, user_id, .. } => {
+                    format!("calendar event from {}: \"{}\"", user_id, text)
+                }
+                ActionEvent::ImportRequested { ics, user_id, .. } => {
+                    format!("calendar import from {} ({} bytes)", user_id, ics.len())
+                }
+                ActionEvent::FindTimeRequested { text, user_id, .. } => {
+                    format!("find time from {}: \"{}\"", user_id, text)
+                }
+                other => format!("{:?}", other),
+            };
+            body.push_str(&format!(
+                "\n- `{}` {} after {} attempts ({}) at {}",
+                letter.id, summary, letter.attempts, letter.error, letter.failed_at
+            ));
+        }
+        body
+    }
+
+    async fn dead_letter_retry(&self, id: Option<&str>, locale: Locale) -> String {
+        let letters = {
+            let mut dead_letters = self.dead_letters.lock().await;
+            let letters = match id {
+                Some(id) => dead_letters.take(id).into_iter().collect(),
+                None => dead_letters.take_all(),
+            };
+            if let Err(err) = dead_letters.save() {
+                tracing::error!(error = %err, "failed to save dead letters");
+            }
+            letters
+        };
+        if letters.is_empty() {
+            return match id {
+                Some(id) => i18n::render(locale, MessageKey::AdminNoSuchDeadLetter, &[("id", id)]),
+                None => i18n::text(locale, MessageKey::AdminNoDeadLetters).to_string(),
+            };
+        }
+        let total = letters.len();
+        let mut succeeded = 0;
+        for letter in letters {
+            // Boxed because processing an event can itself land back here.
+            let queued = QueuedEvent::new(letter.event);
+            if Box::pin(worker::process_event(self, queued, letter.attempts)).await {
+                succeeded += 1;
+            }
+        }
+        i18n::render(
+            locale,
+            MessageKey::AdminRetried,
+            &[
+                ("total", &total.to_string()),
+                ("succeeded", &succeeded.to_string()),
+                ("failed", &(total - succeeded).to_string()),
+            ],
+        )
+    }
+
+    async fn admin_stats(&self, queue_depth: usize, todo_count: usize, locale: Locale) -> String {
+        let (awaiting, failed, total) = {
+            let store = self.store.lock().await;
+            let awaiting = store
+                .values()
+                .filter(|a| a.status == ActionStatus::AwaitingApproval)
+                .count();
+            let failed = store
+                .values()
+                .filter(|a| a.status == ActionStatus::Failed)
+                .count();
+            (awaiting, failed, store.values().count())
+        };
+        let (notifications, paused) = {
+            let db = self.notification_db.lock().await;
+            (db.len(), db.values().filter(|n| n.paused).count())
+        };
+        let dead_letters = self.dead_letters.lock().await.len();
+        let mut stats = i18n::render(
+            locale,
+            MessageKey::AdminStats,
+            &[
+                ("depth", &queue_depth.to_string()),
+                ("awaiting", &awaiting.to_string()),
+                ("failed", &failed.to_string()),
+                ("total", &total.to_string()),
+                ("notifications", &notifications.to_string()),
+                ("paused", &paused.to_string()),
+                ("todos", &todo_count.to_string()),
+                ("dead_letters", &dead_letters.to_string()),
+            ],
+        );
+        if let Some(upcoming) = &self.upcoming {
+            let firing = upcoming.lock().await.reminder_count().to_string();
+            stats.push('\n');
+            let line = i18n::render(locale, MessageKey::AdminFiringSoon, &[("count", &firing)]);
+            stats.push_str(&line);
+        }
+        if let Some(latency) = &self.latency {
+            for kind in [LatencyKind::Prompt, LatencyKind::Delivery] {
+                if let Some(p95) = latency.p95(kind) {
+                    stats.push_str(&format!("\n{} p95: {}", kind.label(), format_latency(p95)));
+                }
+            }
+        }
+        if let Some(health) = &self.task_health {
+            let tasks = health.snapshot();
+            if !tasks.is_empty() {
+                stats.push('\n');
+                stats.push_str(i18n::text(locale, MessageKey::AdminTasks));
+                for task in tasks {
+                    stats.push_str(&format!("\n{}", format_task_status(&task)));
+                }
+            }
+        }
+        stats
+    }
+
+    // Drops finished actions and drafts nobody confirmed in time, plus
+    // notifications the loop has not cleaned up yet.
+    async fn purge_expired(&self, now: DateTime<Utc>, locale: Locale) -> String {
+        let actions = self.store.lock().await.retain(|_, action| match action.status {
+            ActionStatus::Completed | ActionStatus::Rejected | ActionStatus::Expired => false,
+            ActionStatus::AwaitingApproval => action
+                .draft_expires_at()
+                .is_none_or(|expires_at| expires_at >= now),
+            _ => true,
+        });
+        let mut db = self.notification_db.lock().await;
+        let before = db.len();
+        db.retain(|_, n| !n.notification_times.is_empty());
+        let notifications = before - db.len();
+        if notifications > 0 {
+            if let Err(err) = save_db(&notification::get_db_location(), &*db) {
+                let error = err.to_string();
+                return i18n::render(locale, MessageKey::AdminSaveFailed, &[("error", &error)]);
+            }
+        }
+        i18n::render(
+            locale,
+            MessageKey::AdminPurged,
+            &[
+                ("actions", &actions.to_string()),
+                ("notifications", &notifications.to_string()),
+            ],
+        )
+    }
+
+    // Failed drafts get a fresh confirm prompt so the user can try again.
+    async fn requeue_failed(&self, now: DateTime<Utc>, locale: Locale) -> String {
+        let failed: Vec<Action> = {
+            let store = self.store.lock().await;
+            store
+                .values()
+                .filter(|a| a.status == ActionStatus::Failed && a.draft_expires_at().is_some())
+                .cloned()
+                .collect()
+        };
+        let mut requeued = 0;
+        for mut action in failed {
+            if action.transition(ActionStatus::AwaitingApproval, now).is_err() {
+                continue;
+            }
+            action.set_draft_expires_at(now + Duration::minutes(5));
+            if self.prompt(&mut action).await.is_ok() {
+                requeued += 1;
+                self.store.lock().await.insert(action);
+            }
+        }
+        i18n::render(locale, MessageKey::AdminRequeued, &[("count", &requeued.to_string())])
+    }
+
+    async fn set_paused(&self, user_id: &str, index: usize, paused: bool) -> String {
+        let locale = self.locale_for(user_id).await;
+        let missing = i18n::render(locale, MessageKey::NoSuchReminder, &[("index", &index.to_string())]);
+        let mut db = self.notification_db.lock().await;
+        let Some(id) = index
+            .checked_sub(1)
+            .and_then(|idx| notification_service::user_notifications(&db, user_id).get(idx).map(|n| n.id.clone()))
+        else {
+            return missing;
+        };
+        let Some(notification) = db.get_mut(&id) else {
+            return missing;
+        };
+        if notification.recurrence.is_none() {
+            return i18n::render(locale, MessageKey::NotRecurring, &[("content", &notification.content)]);
+        }
+        if notification.paused == paused {
+            let key = if paused { MessageKey::AlreadyPaused } else { MessageKey::NotPaused };
+            return i18n::render(locale, key, &[("content", &notification.content)]);
+        }
+        notification.paused = paused;
+        let content = notification.content.clone();
+        if let Err(err) = save_db(&notification::get_db_location(), &*db) {
+            return i18n::render(locale, MessageKey::ReminderSaveFailed, &[("error", &err.to_string())]);
+        }
+        if paused {
+            i18n::render(
+                locale,
+                MessageKey::ReminderPaused,
+                &[("content", &content), ("index", &index.to_string())],
+            )
+        } else {
+            i18n::render(locale, MessageKey::ReminderResumed, &[("content", &content)])
+        }
+    }
+
+    async fn require_ack(
+        &self,
+        user_id: &str,
+        index: usize,
+        backup: Option<String>,
+        window_minutes: Option<i64>,
+    ) -> String {
+        let locale = self.locale_for(user_id).await;
+        let missing = i18n::render(locale, MessageKey::NoSuchReminder, &[("index", &index.to_string())]);
+        let mut db = self.notification_db.lock().await;
+        let Some(id) = index
+            .checked_sub(1)
+            .and_then(|idx| notification_service::user_notifications(&db, user_id).get(idx).map(|n| n.id.clone()))
+        else {
+            return missing;
+        };
+        let window = window_minutes
+            .unwrap_or(notification::DEFAULT_ACK_WINDOW_MINUTES)
+            .to_string();
+        match notification::require_ack(&mut db, &id, backup.clone(), window_minutes) {
+            Ok(Some(content)) => match backup {
+                Some(backup) => i18n::render(
+                    locale,
+                    MessageKey::MustAckSet,
+                    &[("content", &content), ("backup", &backup), ("minutes", &window)],
+                ),
+                None => i18n::render(
+                    locale,
+                    MessageKey::MustAckSetNoBackup,
+                    &[("content", &content), ("minutes", &window)],
+                ),
+            },
+            Ok(None) => missing,
+            Err(err) => i18n::render(locale, MessageKey::ReminderSaveFailed, &[("error", &err.to_string())]),
+        }
+    }
+
+    // The one reminder `query` names (or `chosen`, when the user already
+    // picked one), or the reply explaining why there isn't exactly one.
+    // `which` asks for more words when the query is empty.
+    fn pick_reminder(
+        db: &DB<Notification>,
+        user_id: &str,
+        query: &str,
+        chosen: Option<&str>,
+        which: MessageKey,
+        locale: Locale,
+    ) -> Result<String, String> {
+        if let Some(id) = chosen {
+            return match db.get(id) {
+                Some(notification) if notification.notify.iter().any(|u| u == user_id) => Ok(id.to_string()),
+                _ => Err(i18n::text(locale, MessageKey::ReminderGone).to_string()),
+            };
+        }
+        let matches = notification_service::find_user_notifications(db, user_id, query);
+        match matches.as_slice() {
+            [notification] => Ok(notification.id.clone()),
+            [] if query.is_empty() => Err(i18n::text(locale, which).to_string()),
+            [] => Err(i18n::render(locale, MessageKey::ReminderNoMatch, &[("query", query)])),
+            several => {
+                let names = several
+                    .iter()
+                    .map(|n| format!("\"{}\"", n.content))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Err(i18n::render(locale, MessageKey::ReminderSeveralMatch, &[("names", &names)]))
+            }
+        }
+    }
+
+    // A reminder shared with others is only stopped for the caller, as with
+    // the Dismiss button.
+    async fn cancel_reminder(&self, user_id: &str, query: &str, chosen: Option<&str>) -> String {
+        let locale = self.locale_for(user_id).await;
+        let mut db = self.notification_db.lock().await;
+        let id = match Self::pick_reminder(&db, user_id, query, chosen, MessageKey::CancelWhich, locale) {
+            Ok(id) => id,
+            Err(reply) => return reply,
+        };
+        let Some(reminder) = db.get(&id) else {
+            return i18n::text(locale, MessageKey::ReminderGone).to_string();
+        };
+        let content = reminder.content.clone();
+        let shared = reminder.notify.iter().any(|u| u != user_id);
+        let result = if shared {
+            notification::dismiss_notification(&mut db, &id, user_id, Utc::now())
+        } else {
+            notification::delete_notification(&mut db, &id)
+        };
+        match result {
+            Ok(_) if shared => {
+                i18n::render(locale, MessageKey::ReminderCanceledForYou, &[("content", &content)])
+            }
+            Ok(_) => i18n::render(locale, MessageKey::ReminderCanceled, &[("content", &content)]),
+            Err(err) => i18n::render(locale, MessageKey::ReminderCancelFailed, &[("error", &err.to_string())]),
+        }
+    }
+
+    async fn snooze_reminder(&self, user_id: &str, query: &str, minutes: i64, chosen: Option<&str>) -> String {
+        let locale = self.locale_for(user_id).await;
+        let zone = self.zone_for(user_id).await;
+        let mut db = self.notification_db.lock().await;
+        let id = match Self::pick_reminder(&db, user_id, query, chosen, MessageKey::SnoozeWhich, locale) {
+            Ok(id) => id,
+            Err(reply) => return reply,
+        };
+        let Some(reminder) = db.get_mut(&id) else {
+            return i18n::text(locale, MessageKey::ReminderGone).to_string();
+        };
+        let Some(next) = notification::snooze_notification(reminder, Duration::minutes(minutes)) else {
+            return i18n::render(locale, MessageKey::NothingToSnooze, &[("content", &reminder.content)]);
+        };
+        let content = reminder.content.clone();
+        if let Err(err) = save_db(&notification::get_db_location(), &*db) {
+            return i18n::render(locale, MessageKey::ReminderSaveFailed, &[("error", &err.to_string())]);
+        }
+        let next = next.with_timezone(&zone).format("%a %b %-d, %-I:%M %p").to_string();
+        i18n::render(
+            locale,
+            MessageKey::ReminderSnoozed,
+            &[("content", &content), ("next", &next)],
+        )
+    }
+
+    async fn choose_slot(&self, action_id: &str, user_id: &str, time: DateTime<Utc>) {
+        let action_snapshot = {
+            let store = self.store.lock().await;
+            store.get(action_id).cloned()
+        };
+        let Some(mut action) = action_snapshot else {
+            return;
+        };
+        // Only the slots we offered, as with DraftRescheduled.
+        let offered = action.slot_draft().is_some_and(|draft| draft.slots.contains(&time));
+        if action.user_id != user_id || !offered {
+            return;
+        }
+        if let Err(err) = action.transition(ActionStatus::Approved, Utc::now()) {
+            tracing::debug!(%action_id, error = %err, "ignoring slot choice");
+            return;
+        }
+        self.confirm_slot(action, time).await;
+    }
+
+    async fn skip_next_occurrence(&self, user_id: &str, query: &str) -> String {
+        let locale = self.locale_for(user_id).await;
+        let not_found = || i18n::text(locale, MessageKey::SkipNotFound).to_string();
+        let query = query.trim().to_lowercase();
+        let mut db = self.notification_db.lock().await;
+        let mut matches: Vec<String> = db
+            .values()
+            .filter(|n| n.recurrence.is_some() && n.notify.iter().any(|u| u == user_id))
+            .filter(|n| query.is_empty() || n.content.to_lowercase().contains(&query))
+            .map(|n| n.id.clone())
+            .collect();
+
+        if matches.len() > 1 {
+            let names = matches
+                .iter()
+                .filter_map(|id| db.get(id))
+                .map(|n| format!("\"{}\"", n.content))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return i18n::render(locale, MessageKey::SkipAmbiguous, &[("names", &names)]);
+        }
+        let Some(id) = matches.pop() else {
+            return not_found();
+        };
+
+        let now = Utc::now();
+        let Some(notification) = db.get_mut(&id) else {
+            return not_found();
+        };
+        let Some(skipped) = notification::skip_next_occurrence(notification, now) else {
+            return not_found();
+        };
+        let content = notification.content.clone();
+        let next = notification.event_time;
+        if let Err(err) = save_db(&notification::get_db_location(), &*db) {
+            return i18n::render(locale, MessageKey::ReminderSaveFailed, &[("error", &err.to_string())]);
+        }
+        let skipped_at = format!("<t:{}:f>", skipped.timestamp());
+        match next {
+            Some(next) if next != skipped => i18n::render(
+                locale,
+                MessageKey::SkippedWithNext,
+                &[
+                    ("content", &content),
+                    ("skipped", &skipped_at),
+                    ("next", &format!("<t:{}:f>", next.timestamp())),
+                ],
+            ),
+            _ => i18n::render(
+                locale,
+                MessageKey::Skipped,
+                &[("content", &content), ("skipped", &skipped_at)],
+            ),
         }
     }
 }
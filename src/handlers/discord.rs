@@ -1,26 +1,39 @@
-use crate::handlers::action::ActionEvent;
-use crate::events::queue::EventBus;
-use crate::handlers::discord_responder::{InteractionResponder, SerenityResponder};
-use crate::service::notify_flow::{route_notify, NotifyDecision, PendingSession, SessionKey};
-use crate::service::routing::IntentRouter;
+use crate::handlers::action::{ActionEvent, ActionStatus, AdminCommand, HISTORY_DEFAULT_DAYS};
+use crate::events::prompt_watch::{self, PromptWatch};
+use crate::events::queue::{EmitError, EventBus};
+use crate::handlers::discord_responder::{InteractionResponder, MessageResponder, SerenityResponder};
+use crate::i18n::{self, Locale, MessageKey};
+use crate::service::aliases::{expand_aliases, is_valid_alias};
+use crate::service::approval_prompt::{APPROVE_REACTION, CANCEL_REACTION};
+use crate::service::categories;
+use crate::service::notification_service::{self, checklist_buttons, render_checklist};
+use crate::service::find_time::parse_find_time;
+use crate::service::focus::parse_focus_duration;
+use crate::service::notify_flow::{
+    is_list_command, parse_must_ack_command, parse_pause_command, parse_query_window, parse_skip_command, parse_snooze, reminder_query, clarify_question, ConversationManager, NotifyDecision, PendingSession, ReminderAction, SessionKey,
+};
+use crate::service::rate_limit::RateLimiter;
+use crate::service::routing::{Intent, IntentRouter};
+use crate::models::settings::{self, CategoryStyle, GuildSettings, UserSettings, Voice};
+use crate::models::notification::{self, DeliveryTarget, Notification, checklist_complete};
 use crate::models::todo;
 use memory_db::DB;
 use serde::Serialize;
 use serenity::prelude::*;
 use serenity::async_trait;
+use tracing::Instrument;
+use serenity::model::channel::{Message, Reaction, ReactionType};
 use serenity::model::gateway::Ready;
 use serenity::all::{Command, CommandOptionType, Interaction as DiscordInteraction};
 use serenity::builder::{
     CreateCommand,
     CreateCommandOption,
-    CreateInteractionResponse,
-    CreateInteractionResponseMessage,
     CreateActionRow,
     CreateModal,
     CreateInputText,
 };
 use serenity::all::InputTextStyle;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -30,11 +43,31 @@ pub struct ErrorMessage {
     pub error: String,
 }
 
+// Calendar exports are text; anything bigger than this isn't one worth
+// reading.
+const IMPORT_MAX_BYTES: u32 = 1024 * 1024;
+
 pub struct BotHandler {
     todo_db: Arc<Mutex<DB<todo::TodoItem>>>,
     sessions: Arc<Mutex<HashMap<SessionKey, PendingSession>>>,
     router: Arc<dyn IntentRouter>,
+    conversation: ConversationManager,
     event_bus: EventBus,
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+    guild_settings: Arc<Mutex<DB<GuildSettings>>>,
+    rate_limiter: Arc<Mutex<RateLimiter>>,
+    admin_role: Option<String>,
+    // Only checklist toggles touch notifications directly; everything else
+    // goes through the event bus.
+    notification_db: Arc<Mutex<DB<Notification>>>,
+    // Public address of the HTTP API for /settings feed links.
+    feed_base_url: Option<String>,
+    // Whether PUSHOVER_APP_TOKEN is set, without which Pushover keys can't
+    // be used.
+    pushover_enabled: bool,
+    // Says when a /notify's confirm prompt is up. Without it the status
+    // stops at the acknowledgement.
+    prompt_watch: Option<PromptWatch>,
 }
 
 impl BotHandler {
@@ -48,147 +81,2090 @@ impl BotHandler {
             todo_db,
             sessions,
             router,
+            conversation: ConversationManager::default(),
             event_bus,
+            user_settings: Arc::new(Mutex::new(HashMap::new())),
+            guild_settings: Arc::new(Mutex::new(HashMap::new())),
+            rate_limiter: Arc::new(Mutex::new(RateLimiter::default())),
+            admin_role: None,
+            notification_db: Arc::new(Mutex::new(HashMap::new())),
+            feed_base_url: None,
+            pushover_enabled: false,
+            prompt_watch: None,
         }
     }
+
+    pub fn with_settings(
+        mut self,
+        user_settings: Arc<Mutex<DB<UserSettings>>>,
+        guild_settings: Arc<Mutex<DB<GuildSettings>>>,
+    ) -> Self {
+        self.user_settings = user_settings;
+        self.guild_settings = guild_settings;
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Arc::new(Mutex::new(rate_limiter));
+        self
+    }
+
+    // Role id allowed to run /admin. Without one, /admin is refused for everyone.
+    pub fn with_admin_role(mut self, admin_role: Option<String>) -> Self {
+        self.admin_role = admin_role;
+        self
+    }
+
+    // Routing guesses less certain than this are asked about first.
+    pub fn with_confidence_threshold(mut self, threshold: f32) -> Self {
+        self.conversation = self.conversation.with_threshold(threshold);
+        self
+    }
+
+    // Follow-ups an unclear /notify gets before the bot stops asking.
+    pub fn with_max_turns(mut self, max_turns: u32) -> Self {
+        self.conversation = self.conversation.with_max_turns(max_turns);
+        self
+    }
+
+    pub fn with_notifications(mut self, notification_db: Arc<Mutex<DB<Notification>>>) -> Self {
+        self.notification_db = notification_db;
+        self
+    }
+
+    pub fn with_feed_base_url(mut self, feed_base_url: Option<String>) -> Self {
+        self.feed_base_url = feed_base_url;
+        self
+    }
+
+    pub fn with_pushover(mut self, enabled: bool) -> Self {
+        self.pushover_enabled = enabled;
+        self
+    }
+
+    pub fn with_prompt_watch(mut self, watch: PromptWatch) -> Self {
+        self.prompt_watch = Some(watch);
+        self
+    }
+
+    // Guards /notify, mentions and context submissions, which all end in an
+    // LLM call.
+    async fn check_rate_limit(&self, user_id: &str) -> Result<(), DateTime<Utc>> {
+        self.rate_limiter.lock().await.check(user_id, Utc::now())
+    }
+}
+
+fn string_option(options: &[serenity::all::CommandDataOption], name: &str) -> Option<String> {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| match &opt.value {
+            serenity::all::CommandDataOptionValue::String(s) => Some(s.clone()),
+            _ => None,
+        })
+}
+
+fn bool_option(options: &[serenity::all::CommandDataOption], name: &str) -> Option<bool> {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| match &opt.value {
+            serenity::all::CommandDataOptionValue::Boolean(b) => Some(*b),
+            _ => None,
+        })
+}
+
+fn integer_option(options: &[serenity::all::CommandDataOption], name: &str) -> Option<i64> {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| match &opt.value {
+            serenity::all::CommandDataOptionValue::Integer(n) => Some(*n),
+            _ => None,
+        })
+}
+
+fn channel_option(options: &[serenity::all::CommandDataOption], name: &str) -> Option<String> {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| match &opt.value {
+            serenity::all::CommandDataOptionValue::Channel(id) => Some(id.to_string()),
+            _ => None,
+        })
+}
+
+// Returns the message text with the bot's mention removed, or None when the
+// message does not mention the bot at all.
+pub fn strip_bot_mention(content: &str, bot_id: u64) -> Option<String> {
+    let plain = format!("<@{}>", bot_id);
+    let nick = format!("<@!{}>", bot_id);
+    if !content.contains(&plain) && !content.contains(&nick) {
+        return None;
+    }
+    let stripped = content.replace(&plain, " ").replace(&nick, " ");
+    Some(stripped.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+#[derive(Debug, Clone)]
+pub enum AliasCommand {
+    Set { name: String, expansion: String },
+    Remove { name: String },
+    List,
+}
+
+#[derive(Debug, Clone)]
+pub enum FocusCommand {
+    On { duration: String },
+    Off,
 }
 
 impl BotHandler {
+    pub async fn locale_for(&self, user_id: &str, guild_id: Option<&str>) -> Locale {
+        let user = {
+            let users = self.user_settings.lock().await;
+            users.get(user_id).cloned()
+        };
+        let guild = match guild_id {
+            Some(id) => {
+                let guilds = self.guild_settings.lock().await;
+                guilds.get(id).cloned()
+            }
+            None => None,
+        };
+        Locale::resolve(user.as_ref(), guild.as_ref())
+    }
+
     async fn handle_notify(&self, ctx: &Context, command: serenity::all::CommandInteraction) {
-        let text = command
+        let text = string_option(&command.data.options, "text").unwrap_or_default();
+
+        let user_id = format!("@{}", command.user.id.to_string());
+        let channel_id = command.channel_id.to_string();
+        let guild_id = command.guild_id.map(|id| id.to_string());
+        let responder = SerenityResponder::for_command(ctx, &command);
+        if text.trim().is_empty() {
+            let locale = self.locale_for(&user_id, guild_id.as_deref()).await;
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::NotifyMissingText))
+                .await;
+            return;
+        }
+
+        self.handle_notify_with(&responder, &text, &user_id, &channel_id, guild_id.as_deref())
+            .await;
+
+    }
+
+    async fn handle_tool(&self, ctx: &Context, command: serenity::all::CommandInteraction) {
+        let text = string_option(&command.data.options, "request").unwrap_or_default();
+        let user_id = format!("@{}", command.user.id);
+        let channel_id = command.channel_id.to_string();
+        let guild_id = command.guild_id.map(|id| id.to_string());
+        let responder = SerenityResponder::for_command(ctx, &command);
+        self.handle_tool_with(&responder, &text, &user_id, &channel_id, guild_id.as_deref())
+            .await;
+    }
+
+    // The engine picks the tool and asks for approval in the channel; this
+    // only acknowledges the request.
+    pub async fn handle_tool_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        text: &str,
+        user_id: &str,
+        channel_id: &str,
+        guild_id: Option<&str>,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let queued = self
+            .event_bus
+            .emit(ActionEvent::ToolRequested {
+                text: text.trim().to_string(),
+                user_id: user_id.to_string(),
+                channel_id: channel_id.to_string(),
+            })
+            .await;
+        let key = match queued {
+            Ok(()) => MessageKey::ToolProcessing,
+            Err(_) => MessageKey::QueueFull,
+        };
+        responder.reply_ephemeral(i18n::text(locale, key)).await;
+    }
+
+    async fn handle_import(&self, ctx: &Context, command: serenity::all::CommandInteraction) {
+        let user_id = format!("@{}", command.user.id);
+        let channel_id = command.channel_id.to_string();
+        let guild_id = command.guild_id.map(|id| id.to_string());
+        let responder = SerenityResponder::for_command(ctx, &command);
+        let attachment = command
             .data
             .options
             .iter()
-            .find(|opt| opt.name == "text")
+            .find(|opt| opt.name == "file")
             .and_then(|opt| match &opt.value {
-                serenity::all::CommandDataOptionValue::String(s) => Some(s.as_str()),
+                serenity::all::CommandDataOptionValue::Attachment(id) => command.data.resolved.attachments.get(id),
                 _ => None,
             })
-            .unwrap_or("")
-            .to_string();
+            .filter(|attachment| attachment.size <= IMPORT_MAX_BYTES);
+        let ics = match attachment {
+            Some(attachment) => attachment
+                .download()
+                .await
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok()),
+            None => None,
+        };
+        let Some(ics) = ics else {
+            let locale = self.locale_for(&user_id, guild_id.as_deref()).await;
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::ImportInvalidFile))
+                .await;
+            return;
+        };
+        self.handle_import_with(&responder, &ics, &user_id, &channel_id, guild_id.as_deref())
+            .await;
+    }
+
+    // Parsing and the preview happen in the engine, which asks for approval
+    // before any reminder is created.
+    pub async fn handle_import_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        ics: &str,
+        user_id: &str,
+        channel_id: &str,
+        guild_id: Option<&str>,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let queued = self
+            .event_bus
+            .emit(ActionEvent::ImportRequested {
+                ics: ics.to_string(),
+                user_id: user_id.to_string(),
+                channel_id: channel_id.to_string(),
+            })
+            .await;
+        let key = match queued {
+            Ok(()) => MessageKey::ImportProcessing,
+            Err(_) => MessageKey::QueueFull,
+        };
+        responder.reply_ephemeral(i18n::text(locale, key)).await;
+    }
+
+    async fn handle_history(&self, ctx: &Context, command: serenity::all::CommandInteraction) {
+        let status = string_option(&command.data.options, "status");
+        let days = integer_option(&command.data.options, "days");
+        let user_id = format!("@{}", command.user.id);
+        let channel_id = command.channel_id.to_string();
+        let guild_id = command.guild_id.map(|id| id.to_string());
+        let responder = SerenityResponder::for_command(ctx, &command);
+        self.handle_history_with(
+            &responder,
+            &user_id,
+            &channel_id,
+            guild_id.as_deref(),
+            status.as_deref(),
+            days,
+        )
+        .await;
+    }
+
+    // Looks back HISTORY_DEFAULT_DAYS unless told otherwise; the engine DMs
+    // the listing to the user.
+    pub async fn handle_history_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        channel_id: &str,
+        guild_id: Option<&str>,
+        status: Option<&str>,
+        days: Option<i64>,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let days = days.unwrap_or(HISTORY_DEFAULT_DAYS).max(1);
+        let queued = self
+            .event_bus
+            .emit(ActionEvent::HistoryRequested {
+                user_id: user_id.to_string(),
+                channel_id: channel_id.to_string(),
+                status: status.and_then(ActionStatus::parse),
+                since: Some(Utc::now() - chrono::Duration::days(days)),
+            })
+            .await;
+        let key = match queued {
+            Ok(()) => MessageKey::HistoryRequested,
+            Err(_) => MessageKey::QueueFull,
+        };
+        responder.reply_ephemeral(i18n::text(locale, key)).await;
+    }
+
+    pub async fn handle_notify_internal(
+        &self,
+        text: &str,
+        user_id: &str,
+        channel_id: &str,
+        guild_id: Option<&str>,
+    ) -> NotifyDecision {
+        if is_list_command(text) {
+            if self
+                .event_bus
+                .emit(ActionEvent::ListRequested {
+                    user_id: user_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                })
+                .await
+                .is_err()
+            {
+                return NotifyDecision::QueueFull;
+            }
+            return NotifyDecision::ListRequested;
+        }
+
+        if let Some((index, paused)) = parse_pause_command(text) {
+            if self
+                .event_bus
+                .emit(ActionEvent::SetPaused {
+                    user_id: user_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    index,
+                    paused,
+                })
+                .await
+                .is_err()
+            {
+                return NotifyDecision::QueueFull;
+            }
+            return NotifyDecision::PauseRequested { paused };
+        }
+
+        if let Some(command) = parse_must_ack_command(text) {
+            if self
+                .event_bus
+                .emit(ActionEvent::RequireAck {
+                    user_id: user_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    index: command.index,
+                    backup: command.backup,
+                    window_minutes: command.window_minutes,
+                })
+                .await
+                .is_err()
+            {
+                return NotifyDecision::QueueFull;
+            }
+            return NotifyDecision::MustAckRequested;
+        }
+
+        if let Some(query) = parse_skip_command(text) {
+            if self
+                .event_bus
+                .emit(ActionEvent::SkipNextOccurrence {
+                    user_id: user_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    query,
+                })
+                .await
+                .is_err()
+            {
+                return NotifyDecision::QueueFull;
+            }
+            return NotifyDecision::SkipRequested;
+        }
+
+        let session_key = (user_id.to_string(), channel_id.to_string());
+        if parse_find_time(text).is_some() {
+            if self
+                .event_bus
+                .emit(ActionEvent::FindTimeRequested {
+                    text: text.trim().to_string(),
+                    user_id: user_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                })
+                .await
+                .is_err()
+            {
+                return NotifyDecision::QueueFull;
+            }
+            let mut sessions = self.sessions.lock().await;
+            self.conversation.offer_slots(&mut sessions, session_key, text.trim(), Utc::now());
+            return NotifyDecision::FindTimeRequested;
+        }
+
+        let now = Utc::now();
+        let text = self.expand_guild_aliases(text, guild_id).await;
+        let locale = self.locale_for(user_id, guild_id).await;
+        let decision = {
+            let mut sessions = self.sessions.lock().await;
+            let decision = self
+                .conversation
+                .handle(self.router.as_ref(), &mut sessions, session_key.clone(), text, locale, now)
+                .await;
+            self.narrow_reminder(&mut sessions, session_key, user_id, decision, now).await
+        };
+
+        if let NotifyDecision::EmitNotify { normalized_text } = &decision {
+            let queued = self
+                .event_bus
+                .emit(ActionEvent::NotifyRequested {
+                    text: normalized_text.clone(),
+                    user_id: user_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    received_at: Some(now),
+                })
+                .await;
+            if queued.is_err() {
+                return NotifyDecision::QueueFull;
+            }
+        }
+        if let NotifyDecision::EmitTodo { normalized_text } = &decision {
+            let queued = self
+                .event_bus
+                .emit(ActionEvent::TodoRequested {
+                    text: normalized_text.clone(),
+                    user_id: user_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                })
+                .await;
+            if queued.is_err() {
+                return NotifyDecision::QueueFull;
+            }
+        }
+        if let NotifyDecision::EmitCalendarEvent { normalized_text } = &decision {
+            let queued = self
+                .event_bus
+                .emit(ActionEvent::CalendarEventRequested {
+                    text: normalized_text.clone(),
+                    user_id: user_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                })
+                .await;
+            if queued.is_err() {
+                return NotifyDecision::QueueFull;
+            }
+        }
+        let manage = match &decision {
+            NotifyDecision::EmitQuery { normalized_text } => Some(
+                match parse_query_window(normalized_text, now) {
+                    Some((from, until)) => ActionEvent::QueryRequested {
+                        user_id: user_id.to_string(),
+                        channel_id: channel_id.to_string(),
+                        from,
+                        until,
+                    },
+                    None => ActionEvent::ListRequested {
+                        user_id: user_id.to_string(),
+                        channel_id: channel_id.to_string(),
+                    },
+                },
+            ),
+            NotifyDecision::EmitCancel { normalized_text } => Some(ActionEvent::CancelReminder {
+                user_id: user_id.to_string(),
+                channel_id: channel_id.to_string(),
+                query: reminder_query(normalized_text),
+                reminder_id: None,
+            }),
+            NotifyDecision::EmitSnooze { normalized_text } => {
+                let (query, minutes) = parse_snooze(normalized_text);
+                Some(ActionEvent::SnoozeReminder {
+                    user_id: user_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    query,
+                    minutes,
+                    reminder_id: None,
+                })
+            }
+            NotifyDecision::ActOnReminder {
+                action: ReminderAction::Cancel,
+                reminder_id,
+            } => Some(ActionEvent::CancelReminder {
+                user_id: user_id.to_string(),
+                channel_id: channel_id.to_string(),
+                query: String::new(),
+                reminder_id: Some(reminder_id.clone()),
+            }),
+            NotifyDecision::ActOnReminder {
+                action: ReminderAction::Snooze { minutes },
+                reminder_id,
+            } => Some(ActionEvent::SnoozeReminder {
+                user_id: user_id.to_string(),
+                channel_id: channel_id.to_string(),
+                query: String::new(),
+                minutes: *minutes,
+                reminder_id: Some(reminder_id.clone()),
+            }),
+            NotifyDecision::SlotPicked { index } => Some(ActionEvent::SlotPicked {
+                user_id: user_id.to_string(),
+                channel_id: channel_id.to_string(),
+                index: *index,
+            }),
+            _ => None,
+        };
+        if let Some(event) = manage {
+            if self.event_bus.emit(event).await.is_err() {
+                return NotifyDecision::QueueFull;
+            }
+        }
+        if let NotifyDecision::EmitMany { parts } = &decision {
+            let mut events = Vec::with_capacity(parts.len());
+            for part in parts {
+                let text = part.normalized_text.clone();
+                let (user_id, channel_id) = (user_id.to_string(), channel_id.to_string());
+                let event = match part.intent {
+                    Intent::Notification => ActionEvent::NotifyRequested {
+                        text,
+                        user_id,
+                        channel_id,
+                        received_at: Some(now),
+                    },
+                    Intent::Todolist => ActionEvent::TodoRequested { text, user_id, channel_id },
+                    Intent::ToolUse => ActionEvent::CalendarEventRequested { text, user_id, channel_id },
+                    Intent::Cancel => ActionEvent::CancelReminder {
+                        query: reminder_query(&text),
+                        user_id,
+                        channel_id,
+                        reminder_id: None,
+                    },
+                    Intent::Snooze => {
+                        let (query, minutes) = parse_snooze(&text);
+                        ActionEvent::SnoozeReminder {
+                            user_id,
+                            channel_id,
+                            query,
+                            minutes,
+                            reminder_id: None,
+                        }
+                    }
+                    Intent::Query => match parse_query_window(&text, now) {
+                        Some((from, until)) => ActionEvent::QueryRequested { user_id, channel_id, from, until },
+                        None => ActionEvent::ListRequested { user_id, channel_id },
+                    },
+                    Intent::Unknown => continue,
+                };
+                events.push(event);
+            }
+            // All parts or none, so a retry after QueueFull doesn't repeat
+            // the ones that got in.
+            if self.event_bus.emit_all(events).await.is_err() {
+                return NotifyDecision::QueueFull;
+            }
+        }
+
+        decision
+    }
+
+    // A cancel or snooze that fits several reminders asks which one; one that
+    // fits exactly one is settled here. Anything else is left for the action
+    // handler to explain.
+    async fn narrow_reminder(
+        &self,
+        sessions: &mut HashMap<SessionKey, PendingSession>,
+        session_key: SessionKey,
+        user_id: &str,
+        decision: NotifyDecision,
+        now: DateTime<Utc>,
+    ) -> NotifyDecision {
+        let (action, query, text) = match &decision {
+            NotifyDecision::EmitCancel { normalized_text } => {
+                (ReminderAction::Cancel, reminder_query(normalized_text), normalized_text.clone())
+            }
+            NotifyDecision::EmitSnooze { normalized_text } => {
+                let (query, minutes) = parse_snooze(normalized_text);
+                (ReminderAction::Snooze { minutes }, query, normalized_text.clone())
+            }
+            _ => return decision,
+        };
+        let candidates: Vec<(String, String)> = {
+            let db = self.notification_db.lock().await;
+            notification_service::find_user_notifications(&db, user_id, &query)
+                .into_iter()
+                .map(|n| (n.id.clone(), n.content.clone()))
+                .collect()
+        };
+        match candidates.len() {
+            0 => decision,
+            1 => NotifyDecision::ActOnReminder {
+                action,
+                reminder_id: candidates[0].0.clone(),
+            },
+            _ => self
+                .conversation
+                .ask_which_reminder(sessions, session_key, action, candidates, &text, now),
+        }
+    }
+
+    async fn expand_guild_aliases(&self, text: &str, guild_id: Option<&str>) -> String {
+        let Some(guild_id) = guild_id else {
+            return text.to_string();
+        };
+        let guilds = self.guild_settings.lock().await;
+        match guilds.get(guild_id) {
+            Some(settings) => expand_aliases(text, &settings.aliases),
+            None => text.to_string(),
+        }
+    }
+
+    pub fn notify_response(locale: Locale, decision: &NotifyDecision) -> String {
+        match decision {
+            NotifyDecision::EmitNotify { .. } => {
+                i18n::text(locale, MessageKey::NotifyProcessing).to_string()
+            }
+            NotifyDecision::EmitTodo { .. } => {
+                i18n::text(locale, MessageKey::TodoProcessing).to_string()
+            }
+            NotifyDecision::EmitCalendarEvent { .. } => {
+                i18n::text(locale, MessageKey::CalendarEventProcessing).to_string()
+            }
+            NotifyDecision::EmitQuery { .. } => {
+                i18n::text(locale, MessageKey::ListRequested).to_string()
+            }
+            NotifyDecision::EmitCancel { .. } => {
+                i18n::text(locale, MessageKey::CancelRequested).to_string()
+            }
+            NotifyDecision::EmitSnooze { .. } => {
+                i18n::text(locale, MessageKey::SnoozeRequested).to_string()
+            }
+            NotifyDecision::NeedClarification => {
+                i18n::text(locale, MessageKey::NeedClarification).to_string()
+            }
+            NotifyDecision::ClarifyIntent { intent, normalized_text } => {
+                clarify_question(locale, intent, normalized_text)
+            }
+            NotifyDecision::EmitMany { parts } => {
+                let count = parts.len().to_string();
+                i18n::render(locale, MessageKey::ManyProcessing, &[("count", count.as_str())])
+            }
+            NotifyDecision::ChooseReminder { options } => {
+                let options: Vec<String> = options
+                    .iter()
+                    .enumerate()
+                    .map(|(index, content)| format!("{}. {}", index + 1, content))
+                    .collect();
+                i18n::render(locale, MessageKey::WhichReminder, &[("options", options.join("\n").as_str())])
+            }
+            NotifyDecision::ActOnReminder {
+                action: ReminderAction::Cancel,
+                ..
+            } => i18n::text(locale, MessageKey::CancelRequested).to_string(),
+            NotifyDecision::ActOnReminder {
+                action: ReminderAction::Snooze { .. },
+                ..
+            } => i18n::text(locale, MessageKey::SnoozeRequested).to_string(),
+            NotifyDecision::SlotPicked { .. } => {
+                i18n::text(locale, MessageKey::SlotPickRequested).to_string()
+            }
+            NotifyDecision::ConversationCanceled => {
+                i18n::text(locale, MessageKey::ConversationCanceled).to_string()
+            }
+            NotifyDecision::GaveUp => {
+                i18n::text(locale, MessageKey::ConversationGaveUp).to_string()
+            }
+            NotifyDecision::SkipRequested => {
+                i18n::text(locale, MessageKey::SkipRequested).to_string()
+            }
+            NotifyDecision::ListRequested => {
+                i18n::text(locale, MessageKey::ListRequested).to_string()
+            }
+            NotifyDecision::PauseRequested { paused: true } => {
+                i18n::text(locale, MessageKey::PauseRequested).to_string()
+            }
+            NotifyDecision::PauseRequested { paused: false } => {
+                i18n::text(locale, MessageKey::ResumeRequested).to_string()
+            }
+            NotifyDecision::MustAckRequested => {
+                i18n::text(locale, MessageKey::MustAckRequested).to_string()
+            }
+            NotifyDecision::FindTimeRequested => {
+                i18n::text(locale, MessageKey::FindTimeRequested).to_string()
+            }
+            NotifyDecision::QueueFull => i18n::text(locale, MessageKey::QueueFull).to_string(),
+            NotifyDecision::RateLimited { retry_at } => {
+                // Discord renders this as a live relative time ("in 12 seconds").
+                let retry = format!("<t:{}:R>", retry_at.timestamp());
+                i18n::render(locale, MessageKey::RateLimited, &[("retry", retry.as_str())])
+            }
+        }
+    }
+
+    pub async fn handle_notify_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        text: &str,
+        user_id: &str,
+        channel_id: &str,
+        guild_id: Option<&str>,
+    ) -> NotifyDecision {
+        let locale = self.locale_for(user_id, guild_id).await;
+        if let Err(retry_at) = self.check_rate_limit(user_id).await {
+            let decision = NotifyDecision::RateLimited { retry_at };
+            responder
+                .reply_ephemeral(&Self::notify_response(locale, &decision))
+                .await;
+            return decision;
+        }
+        // Routing can involve an LLM round-trip, so acknowledge right away and
+        // keep the deferred reply updated as the request moves along.
+        responder.defer_ephemeral().await;
+        responder
+            .edit_original(i18n::text(locale, MessageKey::ProgressInterpreting))
+            .await;
+
+        let waiter = self.prompt_watch.as_ref().map(PromptWatch::subscribe);
+        let decision = self
+            .handle_notify_internal(text, user_id, channel_id, guild_id)
+            .await;
+        responder
+            .edit_original(&Self::notify_response(locale, &decision))
+            .await;
+        // The worker drafts and posts the confirm prompt; once it is up the
+        // status moves on to ready.
+        let drafting = matches!(
+            decision,
+            NotifyDecision::EmitNotify { .. }
+                | NotifyDecision::EmitTodo { .. }
+                | NotifyDecision::EmitCalendarEvent { .. }
+                | NotifyDecision::EmitMany { .. }
+        );
+        if let (true, Some(waiter)) = (drafting, waiter) {
+            if waiter.wait_for(user_id, channel_id, prompt_watch::READY_TIMEOUT).await {
+                responder
+                    .edit_original(i18n::text(locale, MessageKey::ProgressReady))
+                    .await;
+            }
+        }
+        decision
+    }
+
+    pub async fn handle_mention_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        content: &str,
+        bot_id: u64,
+        user_id: &str,
+        channel_id: &str,
+        guild_id: Option<&str>,
+    ) -> Option<NotifyDecision> {
+        let text = strip_bot_mention(content, bot_id)?;
+        if text.is_empty() {
+            let locale = self.locale_for(user_id, guild_id).await;
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::NeedClarification))
+                .await;
+            return None;
+        }
+        Some(
+            self.handle_notify_with(responder, &text, user_id, channel_id, guild_id)
+                .await,
+        )
+    }
+
+    async fn handle_settings(&self, ctx: &Context, command: serenity::all::CommandInteraction) {
+        let Some(subcommand) = command.data.options.first() else {
+            return;
+        };
+        let serenity::all::CommandDataOptionValue::SubCommand(options) = &subcommand.value else {
+            return;
+        };
+        let user_id = format!("@{}", command.user.id);
+        let guild_id = command.guild_id.map(|id| id.to_string());
+        if subcommand.name == "timezone" {
+            let zone = string_option(options, "zone").unwrap_or_default();
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_timezone_with(&responder, &user_id, guild_id.as_deref(), &zone)
+                .await;
+            return;
+        }
+        if subcommand.name == "style" {
+            let category = string_option(options, "category").unwrap_or_default();
+            let emoji = string_option(options, "emoji");
+            let color = string_option(options, "color");
+            let gif_url = string_option(options, "gif");
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_style_with(
+                &responder,
+                &user_id,
+                guild_id.as_deref(),
+                &category,
+                emoji,
+                color.as_deref(),
+                gif_url,
+            )
+            .await;
+            return;
+        }
+        if subcommand.name == "clarification-todos" {
+            let enabled = bool_option(options, "enabled").unwrap_or(false);
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_stale_to_todo_with(&responder, &user_id, guild_id.as_deref(), enabled)
+                .await;
+            return;
+        }
+        if subcommand.name == "briefing" {
+            let enabled = bool_option(options, "enabled").unwrap_or(false);
+            let hour = integer_option(options, "hour");
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_agenda_with(&responder, &user_id, guild_id.as_deref(), enabled, hour)
+                .await;
+            return;
+        }
+        if subcommand.name == "push" {
+            let service = string_option(options, "service").unwrap_or_default();
+            let key = string_option(options, "key");
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_push_with(&responder, &user_id, guild_id.as_deref(), &service, key.as_deref())
+                .await;
+            return;
+        }
+        if subcommand.name == "feed" {
+            let action = string_option(options, "action").unwrap_or_else(|| "show".to_string());
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_feed_with(&responder, &user_id, guild_id.as_deref(), &action)
+                .await;
+            return;
+        }
+        if subcommand.name == "summary" {
+            let enabled = bool_option(options, "enabled").unwrap_or(false);
+            let time = string_option(options, "time");
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_summary_with(&responder, &user_id, guild_id.as_deref(), enabled, time.as_deref())
+                .await;
+            return;
+        }
+        if subcommand.name == "quiet-hours" {
+            let start = string_option(options, "start");
+            let end = string_option(options, "end");
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_quiet_hours_with(&responder, &user_id, guild_id.as_deref(), start.as_deref(), end.as_deref())
+                .await;
+            return;
+        }
+        if subcommand.name == "integrations" {
+            let app_id = string_option(options, "app").unwrap_or_default();
+            let allowed = bool_option(options, "allow").unwrap_or(false);
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_trusted_app_with(&responder, &user_id, guild_id.as_deref(), &app_id, allowed)
+                .await;
+            return;
+        }
+        if subcommand.name == "voice" {
+            let code = string_option(options, "style").unwrap_or_default();
+            let can_manage_guild = command
+                .member
+                .as_ref()
+                .and_then(|member| member.permissions)
+                .is_some_and(|perms| perms.manage_guild());
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_voice_with(&responder, &user_id, guild_id.as_deref(), &code, can_manage_guild)
+                .await;
+            return;
+        }
+        if subcommand.name == "reactions" {
+            let enabled = bool_option(options, "enabled").unwrap_or(false);
+            let can_manage_guild = command
+                .member
+                .as_ref()
+                .and_then(|member| member.permissions)
+                .is_some_and(|perms| perms.manage_guild());
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_approval_reactions_with(
+                &responder,
+                &user_id,
+                guild_id.as_deref(),
+                enabled,
+                can_manage_guild,
+            )
+            .await;
+            return;
+        }
+        if subcommand.name == "escalation" {
+            let channel = channel_option(options, "channel").unwrap_or_default();
+            let can_manage_guild = command
+                .member
+                .as_ref()
+                .and_then(|member| member.permissions)
+                .is_some_and(|perms| perms.manage_guild());
+            let responder = SerenityResponder::for_command(ctx, &command);
+            self.handle_escalation_channel_with(
+                &responder,
+                &user_id,
+                guild_id.as_deref(),
+                &channel,
+                can_manage_guild,
+            )
+            .await;
+            return;
+        }
+        if subcommand.name != "locale" {
+            return;
+        }
+
+        let code = string_option(options, "language").unwrap_or_default();
+        let server_wide = bool_option(options, "server").unwrap_or(false);
+        let can_manage_guild = command
+            .member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .is_some_and(|perms| perms.manage_guild());
+
+        let responder = SerenityResponder::for_command(ctx, &command);
+        self.handle_locale_with(
+            &responder,
+            &user_id,
+            guild_id.as_deref(),
+            &code,
+            server_wide,
+            can_manage_guild,
+        )
+        .await;
+    }
+
+    pub async fn handle_timezone_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        zone: &str,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let zone = zone.trim();
+        let Ok(tz) = zone.parse::<chrono_tz::Tz>() else {
+            responder
+                .reply_ephemeral(&i18n::render(
+                    locale,
+                    MessageKey::TimezoneUnsupported,
+                    &[("timezone", zone)],
+                ))
+                .await;
+            return;
+        };
+
+        let result = {
+            let mut users = self.user_settings.lock().await;
+            settings::set_user_timezone(&mut users, user_id, tz.name())
+        };
+        let reply = match result {
+            Ok(()) => i18n::render(locale, MessageKey::TimezoneUpdated, &[("timezone", tz.name())]),
+            Err(err) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    // `hour` is local to the user's /settings timezone and defaults to 7.
+    pub async fn handle_agenda_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        enabled: bool,
+        hour: Option<i64>,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let hour = hour.unwrap_or(7).clamp(0, 23) as u32;
+        let result = {
+            let mut users = self.user_settings.lock().await;
+            settings::set_user_agenda(&mut users, user_id, enabled.then_some(hour))
+        };
+        let reply = match result {
+            Ok(()) if enabled => {
+                i18n::render(locale, MessageKey::AgendaOn, &[("hour", hour.to_string().as_str())])
+            }
+            Ok(()) => i18n::text(locale, MessageKey::AgendaOff).to_string(),
+            Err(err) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    // `service` is ntfy (key = topic), pushover (key = user key) or off.
+    pub async fn handle_push_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        service: &str,
+        key: Option<&str>,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let key = key.map(str::trim).unwrap_or_default().to_string();
+        if service == "pushover" && !self.pushover_enabled {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::PushoverUnavailable))
+                .await;
+            return;
+        }
+        let push = match service {
+            "off" => None,
+            "ntfy"
+                if !key.is_empty()
+                    && key.len() <= 64
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                Some(DeliveryTarget::Ntfy(key))
+            }
+            // Pushover user keys are always exactly 30 characters.
+            "pushover" if key.len() == 30 && key.chars().all(|c| c.is_ascii_alphanumeric()) => {
+                Some(DeliveryTarget::Pushover(key))
+            }
+            _ => {
+                responder
+                    .reply_ephemeral(i18n::text(locale, MessageKey::PushInvalid))
+                    .await;
+                return;
+            }
+        };
+        let result = {
+            let mut users = self.user_settings.lock().await;
+            settings::set_user_push(&mut users, user_id, push.clone())
+        };
+        let reply = match (result, push) {
+            (Ok(()), Some(target)) => i18n::render(locale, MessageKey::PushOn, &[("service", target.kind())]),
+            (Ok(()), None) => i18n::text(locale, MessageKey::PushOff).to_string(),
+            (Err(err), _) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    // `action` is show (making the link on first use), reset or off.
+    pub async fn handle_feed_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        action: &str,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let result = {
+            let mut users = self.user_settings.lock().await;
+            if action == "off" {
+                settings::disable_user_feed(&mut users, user_id).map(|()| None)
+            } else {
+                settings::user_feed_token(&mut users, user_id, action == "reset").map(Some)
+            }
+        };
+        let reply = match result {
+            Ok(Some(token)) => {
+                let url = format!("{}/feeds/{}.ics", self.feed_base_url.as_deref().unwrap_or_default(), token);
+                i18n::render(locale, MessageKey::FeedUrl, &[("url", url.as_str())])
+            }
+            Ok(None) => i18n::text(locale, MessageKey::FeedOff).to_string(),
+            Err(err) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    // `time` is HH:MM in the user's /settings timezone; without it the
+    // summary follows the bot-wide schedule.
+    pub async fn handle_summary_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        enabled: bool,
+        time: Option<&str>,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let time = match time.filter(|_| enabled) {
+            Some(raw) => match chrono::NaiveTime::parse_from_str(raw.trim(), "%H:%M") {
+                Ok(time) => Some(time),
+                Err(_) => {
+                    responder
+                        .reply_ephemeral(i18n::text(locale, MessageKey::SummaryTimeInvalid))
+                        .await;
+                    return;
+                }
+            },
+            None => None,
+        };
+        let result = {
+            let mut users = self.user_settings.lock().await;
+            settings::set_user_summary(&mut users, user_id, enabled, time)
+        };
+        let reply = match (result, time) {
+            (Ok(()), Some(time)) => {
+                let time = time.format("%H:%M").to_string();
+                i18n::render(locale, MessageKey::SummaryAt, &[("time", time.as_str())])
+            }
+            (Ok(()), None) if enabled => i18n::text(locale, MessageKey::SummaryDefault).to_string(),
+            (Ok(()), None) => i18n::text(locale, MessageKey::SummaryOff).to_string(),
+            (Err(err), _) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    // HH:MM in the user's /settings timezone; neither turns quiet hours off.
+    pub async fn handle_quiet_hours_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        start: Option<&str>,
+        end: Option<&str>,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let parse = |value: &str| chrono::NaiveTime::parse_from_str(value.trim(), "%H:%M").ok();
+        let quiet_hours = match (start, end) {
+            (None, None) => None,
+            (Some(start), Some(end)) => match (parse(start), parse(end)) {
+                (Some(start), Some(end)) if start != end => Some(settings::QuietHours { start, end }),
+                _ => {
+                    responder
+                        .reply_ephemeral(i18n::text(locale, MessageKey::QuietHoursInvalid))
+                        .await;
+                    return;
+                }
+            },
+            _ => {
+                responder
+                    .reply_ephemeral(i18n::text(locale, MessageKey::QuietHoursInvalid))
+                    .await;
+                return;
+            }
+        };
+        let result = {
+            let mut users = self.user_settings.lock().await;
+            settings::set_user_quiet_hours(&mut users, user_id, quiet_hours)
+        };
+        let reply = match (result, quiet_hours) {
+            (Ok(()), Some(window)) => {
+                let start = window.start.format("%H:%M").to_string();
+                let end = window.end.format("%H:%M").to_string();
+                i18n::render(
+                    locale,
+                    MessageKey::QuietHoursOn,
+                    &[("start", start.as_str()), ("end", end.as_str())],
+                )
+            }
+            (Ok(()), None) => i18n::text(locale, MessageKey::QuietHoursOff).to_string(),
+            (Err(err), _) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    pub async fn handle_stale_to_todo_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        enabled: bool,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let result = {
+            let mut users = self.user_settings.lock().await;
+            settings::set_user_stale_to_todo(&mut users, user_id, enabled)
+        };
+        let reply = match result {
+            Ok(()) if enabled => i18n::text(locale, MessageKey::StaleToTodoEnabled).to_string(),
+            Ok(()) => i18n::text(locale, MessageKey::StaleToTodoDisabled).to_string(),
+            Err(err) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    pub async fn handle_voice_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        code: &str,
+        can_manage_guild: bool,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let Some(voice) = Voice::from_code(code) else {
+            let supported = Voice::ALL
+                .iter()
+                .map(|v| v.code())
+                .collect::<Vec<_>>()
+                .join(", ");
+            responder
+                .reply_ephemeral(&i18n::render(
+                    locale,
+                    MessageKey::VoiceUnsupported,
+                    &[("voice", code), ("supported", supported.as_str())],
+                ))
+                .await;
+            return;
+        };
+        let Some(guild_id) = guild_id else {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::GuildOnly))
+                .await;
+            return;
+        };
+        if !can_manage_guild {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::ManageGuildRequired))
+                .await;
+            return;
+        }
+        let result = {
+            let mut guilds = self.guild_settings.lock().await;
+            settings::set_guild_voice(&mut guilds, guild_id, voice)
+        };
+        let reply = match result {
+            Ok(()) => i18n::render(locale, MessageKey::VoiceUpdated, &[("voice", voice.code())]),
+            Err(err) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    pub async fn handle_escalation_channel_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        channel_id: &str,
+        can_manage_guild: bool,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let Some(guild_id) = guild_id else {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::GuildOnly))
+                .await;
+            return;
+        };
+        if !can_manage_guild {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::ManageGuildRequired))
+                .await;
+            return;
+        }
+        let result = {
+            let mut guilds = self.guild_settings.lock().await;
+            settings::set_guild_escalation_channel(&mut guilds, guild_id, channel_id)
+        };
+        let reply = match result {
+            Ok(()) => {
+                let mention = format!("<#{}>", channel_id);
+                i18n::render(
+                    locale,
+                    MessageKey::EscalationChannelUpdated,
+                    &[("channel", mention.as_str())],
+                )
+            }
+            Err(err) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    pub async fn handle_approval_reactions_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        enabled: bool,
+        can_manage_guild: bool,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let Some(guild_id) = guild_id else {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::GuildOnly))
+                .await;
+            return;
+        };
+        if !can_manage_guild {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::ManageGuildRequired))
+                .await;
+            return;
+        }
+        let result = {
+            let mut guilds = self.guild_settings.lock().await;
+            settings::set_guild_approval_reactions(&mut guilds, guild_id, enabled)
+        };
+        let reply = match result {
+            Ok(()) if enabled => i18n::text(locale, MessageKey::ApprovalReactionsOn).to_string(),
+            Ok(()) => i18n::text(locale, MessageKey::ApprovalReactionsOff).to_string(),
+            Err(err) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    // A ✅ or ❌ on an approval prompt, in a guild that allows it. The action
+    // id comes from the prompt's own confirm button, so a prompt that has
+    // expired (buttons removed) can't be answered this way. Returns whether
+    // an event was queued.
+    pub async fn handle_reaction_with(
+        &self,
+        user_id: &str,
+        guild_id: Option<&str>,
+        emoji: &str,
+        custom_ids: &[String],
+    ) -> bool {
+        let Some(guild_id) = guild_id else {
+            return false;
+        };
+        let enabled = {
+            let guilds = self.guild_settings.lock().await;
+            guilds.get(guild_id).is_some_and(|g| g.approval_reactions)
+        };
+        if !enabled {
+            return false;
+        }
+        let Some(action_id) = custom_ids
+            .iter()
+            .find_map(|id| id.strip_prefix("action_confirm:"))
+        else {
+            return false;
+        };
+        let event = match emoji {
+            APPROVE_REACTION => ActionEvent::ApprovalConfirmed {
+                action_id: action_id.to_string(),
+                user_id: user_id.to_string(),
+            },
+            CANCEL_REACTION => ActionEvent::ApprovalCanceled {
+                action_id: action_id.to_string(),
+                user_id: user_id.to_string(),
+            },
+            _ => return false,
+        };
+        self.event_bus.emit(event).await.is_ok()
+    }
+
+    pub async fn handle_trusted_app_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        app_id: &str,
+        allowed: bool,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let app_id = app_id.trim();
+        let result = {
+            let mut users = self.user_settings.lock().await;
+            settings::set_user_trusted_app(&mut users, user_id, app_id, guild_id, allowed)
+        };
+        let reply = match result {
+            Ok(()) if allowed => i18n::render(locale, MessageKey::IntegrationAllowed, &[("app", app_id)]),
+            Ok(()) => i18n::render(locale, MessageKey::IntegrationRevoked, &[("app", app_id)]),
+            Err(err) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn handle_style_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        category: &str,
+        emoji: Option<String>,
+        color: Option<&str>,
+        gif_url: Option<String>,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        if !categories::CATEGORIES.contains(&category) {
+            let supported = categories::CATEGORIES.join(", ");
+            responder
+                .reply_ephemeral(&i18n::render(
+                    locale,
+                    MessageKey::StyleUnknownCategory,
+                    &[("category", category), ("supported", supported.as_str())],
+                ))
+                .await;
+            return;
+        }
+        let color = match color {
+            Some(value) => match categories::parse_color(value) {
+                Some(color) => Some(color),
+                None => {
+                    responder
+                        .reply_ephemeral(&i18n::render(
+                            locale,
+                            MessageKey::StyleInvalidColor,
+                            &[("color", value)],
+                        ))
+                        .await;
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        let style = CategoryStyle {
+            emoji: emoji.filter(|e| !e.trim().is_empty()),
+            color,
+            gif_url: gif_url.filter(|url| !url.trim().is_empty()),
+        };
+        let result = {
+            let mut users = self.user_settings.lock().await;
+            settings::set_user_category_style(&mut users, user_id, category, style)
+        };
+        let reply = match result {
+            Ok(()) => i18n::render(locale, MessageKey::StyleUpdated, &[("category", category)]),
+            Err(err) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    pub async fn handle_locale_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        code: &str,
+        server_wide: bool,
+        can_manage_guild: bool,
+    ) {
+        let current = self.locale_for(user_id, guild_id).await;
+        let Some(locale) = Locale::from_code(code) else {
+            let supported = Locale::ALL
+                .iter()
+                .map(|l| l.code())
+                .collect::<Vec<_>>()
+                .join(", ");
+            responder
+                .reply_ephemeral(&i18n::render(
+                    current,
+                    MessageKey::LocaleUnsupported,
+                    &[("locale", code), ("supported", supported.as_str())],
+                ))
+                .await;
+            return;
+        };
+
+        let result = if server_wide {
+            let Some(guild_id) = guild_id else {
+                responder
+                    .reply_ephemeral(i18n::text(current, MessageKey::GuildOnly))
+                    .await;
+                return;
+            };
+            if !can_manage_guild {
+                responder
+                    .reply_ephemeral(i18n::text(current, MessageKey::ManageGuildRequired))
+                    .await;
+                return;
+            }
+            let mut guilds = self.guild_settings.lock().await;
+            settings::set_guild_locale(&mut guilds, guild_id, locale.code())
+        } else {
+            let mut users = self.user_settings.lock().await;
+            settings::set_user_locale(&mut users, user_id, locale.code())
+        };
+
+        let reply = match result {
+            Ok(()) => {
+                let reply_locale = if server_wide {
+                    self.locale_for(user_id, guild_id).await
+                } else {
+                    locale
+                };
+                i18n::render(reply_locale, MessageKey::LocaleUpdated, &[("locale", locale.code())])
+            }
+            Err(err) => i18n::render(
+                current,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    async fn handle_admin(&self, ctx: &Context, command: serenity::all::CommandInteraction) {
+        let Some(subcommand) = command.data.options.first() else {
+            return;
+        };
+        let user_id = format!("@{}", command.user.id);
+        let channel_id = command.channel_id.to_string();
+        let guild_id = command.guild_id.map(|id| id.to_string());
+        let roles: Vec<String> = command
+            .member
+            .as_ref()
+            .map(|member| member.roles.iter().map(|role| role.to_string()).collect())
+            .unwrap_or_default();
+        // Groups flatten to "dlq retry <id>" so handle_admin_with stays string-driven.
+        let subcommand = match &subcommand.value {
+            serenity::all::CommandDataOptionValue::SubCommandGroup(options) => {
+                let Some(inner) = options.first() else {
+                    return;
+                };
+                let id = match &inner.value {
+                    serenity::all::CommandDataOptionValue::SubCommand(options) => {
+                        string_option(options, "id").unwrap_or_default()
+                    }
+                    _ => String::new(),
+                };
+                format!("{} {} {}", subcommand.name, inner.name, id)
+            }
+            _ => subcommand.name.clone(),
+        };
+        let responder = SerenityResponder::for_command(ctx, &command);
+        self.handle_admin_with(
+            &responder,
+            &user_id,
+            &channel_id,
+            guild_id.as_deref(),
+            &roles,
+            &subcommand,
+        )
+        .await;
+    }
+
+    pub async fn handle_admin_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        channel_id: &str,
+        guild_id: Option<&str>,
+        member_roles: &[String],
+        subcommand: &str,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let is_admin = self
+            .admin_role
+            .as_ref()
+            .is_some_and(|role| member_roles.iter().any(|r| r == role));
+        if !is_admin {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::AdminRoleRequired))
+                .await;
+            return;
+        }
+        let mut words = subcommand.split_whitespace();
+        let command = match (words.next(), words.next()) {
+            (Some("stats"), _) => AdminCommand::Stats {
+                queue_depth: self.event_bus.queue_depth(),
+                todo_count: self.todo_db.lock().await.len(),
+            },
+            (Some("purge-expired"), _) => AdminCommand::PurgeExpired,
+            (Some("requeue-failed"), _) => AdminCommand::RequeueFailed,
+            (Some("dlq"), Some("list")) => AdminCommand::DeadLetterList,
+            (Some("dlq"), Some("retry")) => AdminCommand::DeadLetterRetry {
+                id: words.next().map(str::to_string),
+            },
+            _ => return,
+        };
+        let queued = self
+            .event_bus
+            .emit(ActionEvent::AdminRequested {
+                user_id: user_id.to_string(),
+                channel_id: channel_id.to_string(),
+                command,
+            })
+            .await;
+        let key = match queued {
+            Ok(()) => MessageKey::ProcessingRequest,
+            Err(_) => MessageKey::QueueFull,
+        };
+        responder.reply_ephemeral(i18n::text(locale, key)).await;
+    }
+
+    async fn handle_alias(&self, ctx: &Context, command: serenity::all::CommandInteraction) {
+        let Some(subcommand) = command.data.options.first() else {
+            return;
+        };
+        let serenity::all::CommandDataOptionValue::SubCommand(options) = &subcommand.value else {
+            return;
+        };
+        let alias_command = match subcommand.name.as_str() {
+            "set" => AliasCommand::Set {
+                name: string_option(options, "name").unwrap_or_default(),
+                expansion: string_option(options, "expansion").unwrap_or_default(),
+            },
+            "remove" => AliasCommand::Remove {
+                name: string_option(options, "name").unwrap_or_default(),
+            },
+            "list" => AliasCommand::List,
+            _ => return,
+        };
+
+        let user_id = format!("@{}", command.user.id);
+        let guild_id = command.guild_id.map(|id| id.to_string());
+        let can_manage_guild = command
+            .member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .is_some_and(|perms| perms.manage_guild());
+        let responder = SerenityResponder::for_command(ctx, &command);
+        self.handle_alias_with(
+            &responder,
+            &user_id,
+            guild_id.as_deref(),
+            can_manage_guild,
+            alias_command,
+        )
+        .await;
+    }
+
+    pub async fn handle_alias_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        can_manage_guild: bool,
+        command: AliasCommand,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let Some(guild_id) = guild_id else {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::GuildOnly))
+                .await;
+            return;
+        };
+        let mutates = !matches!(command, AliasCommand::List);
+        if mutates && !can_manage_guild {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::ManageGuildRequired))
+                .await;
+            return;
+        }
+
+        let mut guilds = self.guild_settings.lock().await;
+        let reply = match command {
+            AliasCommand::Set { name, expansion } => {
+                if !is_valid_alias(&name) || expansion.trim().is_empty() {
+                    i18n::text(locale, MessageKey::AliasInvalid).to_string()
+                } else {
+                    match settings::set_guild_alias(&mut guilds, guild_id, &name, &expansion) {
+                        Ok(()) => i18n::render(
+                            locale,
+                            MessageKey::AliasSaved,
+                            &[
+                                ("name", name.trim().to_lowercase().as_str()),
+                                ("expansion", expansion.trim()),
+                            ],
+                        ),
+                        Err(err) => i18n::render(
+                            locale,
+                            MessageKey::SettingsFailed,
+                            &[("error", err.to_string().as_str())],
+                        ),
+                    }
+                }
+            }
+            AliasCommand::Remove { name } => {
+                match settings::remove_guild_alias(&mut guilds, guild_id, &name) {
+                    Ok(true) => i18n::render(
+                        locale,
+                        MessageKey::AliasRemoved,
+                        &[("name", name.trim())],
+                    ),
+                    Ok(false) => i18n::render(
+                        locale,
+                        MessageKey::AliasNotFound,
+                        &[("name", name.trim())],
+                    ),
+                    Err(err) => i18n::render(
+                        locale,
+                        MessageKey::SettingsFailed,
+                        &[("error", err.to_string().as_str())],
+                    ),
+                }
+            }
+            AliasCommand::List => {
+                let mut aliases: Vec<(String, String)> = guilds
+                    .get(guild_id)
+                    .map(|settings| {
+                        settings
+                            .aliases
+                            .iter()
+                            .map(|(k, v)| (k.clone(), v.clone()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if aliases.is_empty() {
+                    i18n::text(locale, MessageKey::AliasListEmpty).to_string()
+                } else {
+                    aliases.sort();
+                    let mut body = i18n::text(locale, MessageKey::AliasListHeader).to_string();
+                    for (name, expansion) in aliases {
+                        body.push_str(&format!("\n- {} → {}", name, expansion));
+                    }
+                    body
+                }
+            }
+        };
+        drop(guilds);
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    async fn handle_focus(&self, ctx: &Context, command: serenity::all::CommandInteraction) {
+        let Some(subcommand) = command.data.options.first() else {
+            return;
+        };
+        let focus_command = match (subcommand.name.as_str(), &subcommand.value) {
+            ("on", serenity::all::CommandDataOptionValue::SubCommand(options)) => FocusCommand::On {
+                duration: string_option(options, "duration").unwrap_or_default(),
+            },
+            ("off", _) => FocusCommand::Off,
+            _ => return,
+        };
+        let user_id = format!("@{}", command.user.id);
+        let guild_id = command.guild_id.map(|id| id.to_string());
+        let responder = SerenityResponder::for_command(ctx, &command);
+        self.handle_focus_with(&responder, &user_id, guild_id.as_deref(), focus_command, Utc::now())
+            .await;
+    }
+
+    // Turning focus off just ends it now; the focus loop sends the digest on
+    // its next tick.
+    pub async fn handle_focus_with(
+        &self,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        command: FocusCommand,
+        now: DateTime<Utc>,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let (until, key) = match command {
+            FocusCommand::On { duration } => match parse_focus_duration(&duration) {
+                Some(length) => (now + length, MessageKey::FocusOn),
+                None => {
+                    responder
+                        .reply_ephemeral(&i18n::render(
+                            locale,
+                            MessageKey::FocusInvalidDuration,
+                            &[("duration", duration.trim())],
+                        ))
+                        .await;
+                    return;
+                }
+            },
+            FocusCommand::Off => (now, MessageKey::FocusOff),
+        };
+
+        let result = {
+            let mut users = self.user_settings.lock().await;
+            settings::set_user_focus(&mut users, user_id, until)
+        };
+        let reply = match result {
+            Ok(()) => {
+                let until = format!("<t:{}:t>", until.timestamp());
+                i18n::render(locale, key, &[("until", until.as_str())])
+            }
+            Err(err) => i18n::render(
+                locale,
+                MessageKey::SettingsFailed,
+                &[("error", err.to_string().as_str())],
+            ),
+        };
+        responder.reply_ephemeral(&reply).await;
+    }
+
+    async fn handle_pending_confirm(
+        &self,
+        ctx: &Context,
+        interaction: serenity::all::ComponentInteraction,
+        action_id: &str,
+    ) {
+        let user_id = format!("@{}", interaction.user.id);
+        let queued = self
+            .event_bus
+            .emit(ActionEvent::ApprovalConfirmed {
+                action_id: action_id.to_string(),
+                user_id: user_id.clone(),
+            })
+            .await;
+        self.acknowledge_prompt_button(ctx, &interaction, &user_id, queued)
+            .await;
+    }
+
+    // Button ids carry "<action id>:<unix seconds>" for the day picked.
+    async fn handle_pending_move(
+        &self,
+        ctx: &Context,
+        interaction: serenity::all::ComponentInteraction,
+        target: &str,
+    ) {
+        let Some((action_id, time)) = target
+            .split_once(':')
+            .and_then(|(id, secs)| Some((id, DateTime::from_timestamp(secs.parse().ok()?, 0)?)))
+        else {
+            return;
+        };
+        let user_id = format!("@{}", interaction.user.id);
+        let queued = self
+            .event_bus
+            .emit(ActionEvent::DraftRescheduled {
+                action_id: action_id.to_string(),
+                user_id: user_id.clone(),
+                time,
+            })
+            .await;
+        self.acknowledge_prompt_button(ctx, &interaction, &user_id, queued)
+            .await;
+    }
+
+    async fn handle_pending_merge(
+        &self,
+        ctx: &Context,
+        interaction: serenity::all::ComponentInteraction,
+        action_id: &str,
+    ) {
+        let user_id = format!("@{}", interaction.user.id);
+        let queued = self
+            .event_bus
+            .emit(ActionEvent::DuplicateMerged {
+                action_id: action_id.to_string(),
+                user_id: user_id.clone(),
+            })
+            .await;
+        self.acknowledge_prompt_button(ctx, &interaction, &user_id, queued)
+            .await;
+    }
+
+    async fn handle_slot_choice(
+        &self,
+        ctx: &Context,
+        interaction: serenity::all::ComponentInteraction,
+        target: &str,
+    ) {
+        let Some((action_id, time)) = target
+            .split_once(':')
+            .and_then(|(id, secs)| Some((id, DateTime::from_timestamp(secs.parse().ok()?, 0)?)))
+        else {
+            return;
+        };
+        let user_id = format!("@{}", interaction.user.id);
+        let queued = self
+            .event_bus
+            .emit(ActionEvent::SlotChosen {
+                action_id: action_id.to_string(),
+                user_id: user_id.clone(),
+                time,
+            })
+            .await;
+        self.acknowledge_prompt_button(ctx, &interaction, &user_id, queued)
+            .await;
+    }
+
+    async fn handle_pending_cancel(
+        &self,
+        ctx: &Context,
+        interaction: serenity::all::ComponentInteraction,
+        action_id: &str,
+    ) {
+        let user_id = format!("@{}", interaction.user.id);
+        let queued = self
+            .event_bus
+            .emit(ActionEvent::ApprovalCanceled {
+                action_id: action_id.to_string(),
+                user_id: user_id.clone(),
+            })
+            .await;
+        self.acknowledge_prompt_button(ctx, &interaction, &user_id, queued)
+            .await;
+    }
 
-        if text.trim().is_empty() {
-            let _ = command
-                .create_response(
-                    &ctx.http,
-                    CreateInteractionResponse::Message(
-                        CreateInteractionResponseMessage::new()
-                            .content("Missing `text` argument for /notify")
-                            .ephemeral(true),
-                    ),
-                )
+    // Answers a prompt button once its event is queued. On a full queue the
+    // prompt and its buttons are left in place so the user can retry.
+    async fn acknowledge_prompt_button(
+        &self,
+        ctx: &Context,
+        interaction: &serenity::all::ComponentInteraction,
+        user_id: &str,
+        queued: Result<(), EmitError>,
+    ) {
+        let guild_id = interaction.guild_id.map(|id| id.to_string());
+        let locale = self.locale_for(user_id, guild_id.as_deref()).await;
+        let responder = SerenityResponder::for_component(ctx, interaction);
+        if queued.is_err() {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::QueueFull))
                 .await;
             return;
         }
-
-        let user_id = format!("@{}", command.user.id.to_string());
-        let channel_id = command.channel_id.to_string();
-        let responder = SerenityResponder::for_command(ctx, &command);
-        self.handle_notify_with(&responder, &text, &user_id, &channel_id)
+        responder
+            .reply_update(i18n::text(locale, MessageKey::ProcessingRequest))
             .await;
+    }
 
+    async fn handle_reminder_resolved(
+        &self,
+        ctx: &Context,
+        interaction: serenity::all::ComponentInteraction,
+        notification_id: &str,
+        dismissed: bool,
+    ) {
+        let user_id = format!("@{}", interaction.user.id);
+        let guild_id = interaction.guild_id.map(|id| id.to_string());
+        let responder = SerenityResponder::for_component(ctx, &interaction);
+        self.handle_reminder_resolved_with(
+            &responder,
+            &user_id,
+            guild_id.as_deref(),
+            notification_id,
+            dismissed,
+        )
+        .await;
     }
 
-    pub async fn handle_notify_internal(
+    pub async fn handle_reminder_resolved_with(
         &self,
-        text: &str,
+        responder: &dyn InteractionResponder,
         user_id: &str,
-        channel_id: &str,
-    ) -> NotifyDecision {
-        let session_key = (user_id.to_string(), channel_id.to_string());
-        let now = Utc::now();
-        let decision = {
-            let mut sessions = self.sessions.lock().await;
-            route_notify(
-                self.router.as_ref(),
-                &mut sessions,
-                session_key,
-                text.to_string(),
-                now,
-            )
+        guild_id: Option<&str>,
+        notification_id: &str,
+        dismissed: bool,
+    ) {
+        let locale = self.locale_for(user_id, guild_id).await;
+        let is_target = self
+            .notification_db
+            .lock()
             .await
-        };
-
-        if let NotifyDecision::EmitNotify { normalized_text } = &decision {
-            self.event_bus
-                .emit(ActionEvent::NotifyRequested {
-                    text: normalized_text.clone(),
-                    user_id: user_id.to_string(),
-                    channel_id: channel_id.to_string(),
-                })
+            .get(notification_id)
+            .is_some_and(|notification| notification.notify.iter().any(|user| user == user_id));
+        if !is_target {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::ReminderNotYours))
                 .await;
+            return;
         }
-        if let NotifyDecision::EmitTodo { normalized_text } = &decision {
-            let mut db = self.todo_db.lock().await;
-            if let Err(err) = todo::create_todo(&mut db, user_id, normalized_text) {
-                return NotifyDecision::TodoFailed {
-                    error: err.to_string(),
-                };
-            }
-        }
-
-        decision
-    }
 
-    pub fn notify_response(decision: &NotifyDecision) -> String {
-        match decision {
-            NotifyDecision::EmitNotify { .. } => {
-                "Got it — processing your notification.".to_string()
-            }
-            NotifyDecision::EmitTodo { .. } => "Added to your todo list.".to_string(),
-            NotifyDecision::NeedClarification => {
-                "I can set notifications. What should I notify you about, and when? Re-run /notify with a time.".to_string()
-            }
-            NotifyDecision::TodoFailed { error } => {
-                format!("Failed to create todo: {}", error)
-            }
+        let notification_id = notification_id.to_string();
+        let user_id = user_id.to_string();
+        let event = if dismissed {
+            ActionEvent::NotificationDismissed { notification_id, user_id }
+        } else {
+            ActionEvent::NotificationCompleted { notification_id, user_id }
+        };
+        if self.event_bus.emit(event).await.is_err() {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::QueueFull))
+                .await;
+            return;
+        }
+        // Done finishes the reminder for everyone, so the shared message says
+        // so; a dismissal only concerns the clicker and leaves it alone.
+        if dismissed {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::ReminderDismissed))
+                .await;
+        } else {
+            responder
+                .reply_update(i18n::text(locale, MessageKey::ReminderDone))
+                .await;
         }
     }
 
-    pub async fn handle_notify_with(
-        &self,
-        responder: &dyn InteractionResponder,
-        text: &str,
-        user_id: &str,
-        channel_id: &str,
-    ) -> NotifyDecision {
-        let decision = self.handle_notify_internal(text, user_id, channel_id).await;
-        responder.reply_ephemeral(&Self::notify_response(&decision)).await;
-        decision
-    }
-
-    async fn handle_pending_confirm(
+    async fn handle_checklist_toggle(
         &self,
         ctx: &Context,
         interaction: serenity::all::ComponentInteraction,
-        action_id: &str,
+        target: &str,
     ) {
-        self.event_bus
-            .emit(ActionEvent::ApprovalConfirmed {
-                action_id: action_id.to_string(),
-                user_id: format!("@{}", interaction.user.id),
-            })
-            .await;
-
+        // custom_id is checklist:{notification_id}:{index}
+        let Some((notification_id, index)) = target.rsplit_once(':') else {
+            return;
+        };
+        let Ok(index) = index.parse::<usize>() else {
+            return;
+        };
+        let user_id = format!("@{}", interaction.user.id);
+        let guild_id = interaction.guild_id.map(|id| id.to_string());
+        let message = interaction.message.content.clone();
         let responder = SerenityResponder::for_component(ctx, &interaction);
-        responder.reply_update("Processing your request.").await;
+        self.handle_checklist_toggle_with(
+            &responder,
+            &user_id,
+            guild_id.as_deref(),
+            &message,
+            notification_id,
+            index,
+        )
+        .await;
     }
 
-    async fn handle_pending_cancel(
+    // Re-renders the checklist lines of the delivered message in place and
+    // posts a wrap-up once the last item is ticked.
+    pub async fn handle_checklist_toggle_with(
         &self,
-        ctx: &Context,
-        interaction: serenity::all::ComponentInteraction,
-        action_id: &str,
+        responder: &dyn InteractionResponder,
+        user_id: &str,
+        guild_id: Option<&str>,
+        message: &str,
+        notification_id: &str,
+        index: usize,
     ) {
-        self.event_bus
-            .emit(ActionEvent::ApprovalCanceled {
-                action_id: action_id.to_string(),
-                user_id: format!("@{}", interaction.user.id),
-            })
+        let locale = self.locale_for(user_id, guild_id).await;
+        let toggled = {
+            let mut db = self.notification_db.lock().await;
+            match notification::toggle_checklist_item(&mut db, notification_id, user_id, index) {
+                Ok(Some(_)) => db.get(notification_id).cloned(),
+                Ok(None) => None,
+                Err(err) => {
+                    tracing::warn!(%notification_id, error = %err, "failed to save checklist");
+                    None
+                }
+            }
+        };
+        let Some(toggled) = toggled else {
+            responder
+                .reply_ephemeral(i18n::text(locale, MessageKey::ChecklistItemMissing))
+                .await;
+            return;
+        };
+
+        let mut lines: Vec<&str> = message
+            .lines()
+            .filter(|line| !line.starts_with('☐') && !line.starts_with('☑'))
+            .collect();
+        let checklist = render_checklist(&toggled);
+        lines.push(&checklist);
+        responder
+            .reply_update_with_buttons(&lines.join("\n"), checklist_buttons(&toggled))
             .await;
 
-        let responder = SerenityResponder::for_component(ctx, &interaction);
-        responder.reply_update("Processing your request.").await;
+        if checklist_complete(&toggled) {
+            let summary = i18n::render(
+                locale,
+                MessageKey::ChecklistComplete,
+                &[
+                    ("content", toggled.content.as_str()),
+                    ("total", toggled.checklist.len().to_string().as_str()),
+                ],
+            );
+            responder.follow_up(&summary, false).await;
+        }
     }
 
     async fn handle_pending_context(
@@ -197,26 +2173,31 @@ impl BotHandler {
         interaction: serenity::all::ComponentInteraction,
         action_id: &str,
     ) {
+        let user_id = format!("@{}", interaction.user.id);
+        let guild_id = interaction.guild_id.map(|id| id.to_string());
+        let locale = self.locale_for(&user_id, guild_id.as_deref()).await;
         let responder = SerenityResponder::for_component(ctx, &interaction);
-        self.handle_pending_context_with(&responder, action_id).await;
+        self.handle_pending_context_with(&responder, locale, action_id)
+            .await;
     }
 
     pub async fn handle_pending_context_with(
         &self,
         responder: &dyn InteractionResponder,
+        locale: Locale,
         action_id: &str,
     ) {
         let modal = CreateModal::new(
             format!("action_context_modal:{}", action_id),
-            "Add context",
+            i18n::text(locale, MessageKey::ContextModalTitle),
         )
         .components(vec![CreateActionRow::InputText(
             CreateInputText::new(
                 InputTextStyle::Paragraph,
-                "Context",
+                i18n::text(locale, MessageKey::ContextModalLabel),
                 "context",
             )
-            .placeholder("Add any details or corrections (optional)")
+            .placeholder(i18n::text(locale, MessageKey::ContextModalPlaceholder))
             .required(false),
         )]);
 
@@ -227,28 +2208,536 @@ impl BotHandler {
 #[async_trait]
 impl EventHandler for BotHandler {
     async fn ready(&self, ctx: Context, ready: Ready) {
-        println!("{} is connected!", ready.user.name);
+        tracing::info!(user = %ready.user.name, "connected to Discord");
 
         let builder = CreateCommand::new("notify")
             .description("Create a notification")
+            .description_localized("es-ES", "Crear una notificación")
+            .description_localized("de", "Eine Benachrichtigung erstellen")
+            .description_localized("fr", "Créer une notification")
             .add_option(
                 CreateCommandOption::new(
                     CommandOptionType::String,
                     "text",
                     "What should I notify you about?",
                 )
+                .description_localized("es-ES", "¿Sobre qué debo avisarte?")
+                .description_localized("de", "Woran soll ich dich erinnern?")
+                .description_localized("fr", "De quoi dois-je te prévenir ?")
                 .required(true),
             );
 
         let _ = Command::create_global_command(&ctx.http, builder).await;
 
+        let mut language = CreateCommandOption::new(
+            CommandOptionType::String,
+            "language",
+            "Language code",
+        )
+        .required(true);
+        for locale in Locale::ALL {
+            language = language.add_string_choice(locale.code(), locale.code());
+        }
+        let settings_builder = CreateCommand::new("settings")
+            .description("Change your bot preferences")
+            .description_localized("es-ES", "Cambiar tus preferencias del bot")
+            .description_localized("de", "Bot-Einstellungen ändern")
+            .description_localized("fr", "Modifier tes préférences du bot")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "locale",
+                    "Set the language the bot replies in",
+                )
+                .add_sub_option(language)
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "server",
+                    "Apply as the server default (requires Manage Server)",
+                )),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "timezone",
+                    "Set the timezone reminders show your local time in",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "zone",
+                        "IANA timezone, e.g. America/Los_Angeles",
+                    )
+                    .required(true),
+                ),
+            )
+            .add_option({
+                let mut category = CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "category",
+                    "Which kind of reminder to style",
+                )
+                .required(true);
+                for name in categories::CATEGORIES {
+                    category = category.add_string_choice(name, name);
+                }
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "style",
+                    "Set how a category of reminders looks when delivered",
+                )
+                .add_sub_option(category)
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "emoji",
+                    "Emoji shown before the reminder",
+                ))
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "color",
+                    "Embed color as hex, e.g. #ff8800",
+                ))
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "gif",
+                    "GIF or image URL attached to the reminder",
+                ))
+            })
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "clarification-todos",
+                    "Add requests you never give a time for to your todo list",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Boolean, "enabled", "Turn this on or off")
+                        .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "briefing",
+                    "Get a morning DM with today's calendar, reminders and todos",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Boolean, "enabled", "Turn this on or off")
+                        .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "hour",
+                        "Hour to send it in your timezone, 0-23 (default 7)",
+                    )
+                    .min_int_value(0)
+                    .max_int_value(23),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "push",
+                    "Also get every reminder as a phone push through ntfy or Pushover",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "service", "Where to push, or off")
+                        .required(true)
+                        .add_string_choice("ntfy", "ntfy")
+                        .add_string_choice("pushover", "pushover")
+                        .add_string_choice("off", "off"),
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "key",
+                    "Your ntfy topic or Pushover user key",
+                )),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "feed",
+                    "Get a private calendar link that shows your reminders in your calendar app",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "action", "Show the link (default), replace it, or turn it off")
+                        .add_string_choice("show", "show")
+                        .add_string_choice("reset", "reset")
+                        .add_string_choice("off", "off"),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "summary",
+                    "Choose when your daily todo summary DM arrives, or turn it off",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Boolean, "enabled", "Turn this on or off")
+                        .required(true),
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "time",
+                    "Time as HH:MM in your timezone, e.g. 08:30 (default: the usual 7am)",
+                )),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "quiet-hours",
+                    "Hold reminders that fall in these hours until they end",
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "start",
+                    "Start as HH:MM in your timezone, e.g. 22:00 (leave both empty to turn off)",
+                ))
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "end",
+                    "End as HH:MM in your timezone, e.g. 07:00",
+                )),
+            )
+            .add_option({
+                let mut style = CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "style",
+                    "How generated reminders should sound",
+                )
+                .required(true);
+                for voice in Voice::ALL {
+                    style = style.add_string_choice(voice.code(), voice.code());
+                }
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "voice",
+                    "Set the server's reminder voice (requires Manage Server)",
+                )
+                .add_sub_option(style)
+            })
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "reactions",
+                    "Let approval prompts take ✅/❌ reactions as well as buttons (requires Manage Server)",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Boolean, "enabled", "Allow reactions")
+                        .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "escalation",
+                    "Set where unacknowledged must-ack reminders go (requires Manage Server)",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Channel,
+                        "channel",
+                        "e.g. #missed-reminders",
+                    )
+                    .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "integrations",
+                    "Allow or revoke another bot creating reminders for you",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "app", "The bot's application id")
+                        .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Boolean, "allow", "Allow or revoke")
+                        .required(true),
+                ),
+            );
+
+        let _ = Command::create_global_command(&ctx.http, settings_builder).await;
+
+        let alias_builder = CreateCommand::new("alias")
+            .description("Manage server shorthand expanded before /notify is interpreted")
+            .dm_permission(false)
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Define an alias")
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "name", "Single word, e.g. standup")
+                            .required(true),
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "expansion",
+                            "What it means, e.g. team standup in #eng at 9:30am",
+                        )
+                        .required(true),
+                    ),
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove an alias")
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "name", "Alias to remove")
+                            .required(true),
+                    ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "List this server's aliases",
+            ));
+
+        let _ = Command::create_global_command(&ctx.http, alias_builder).await;
+
+        let admin_builder = CreateCommand::new("admin")
+            .description("Operator tools, limited to the configured admin role")
+            .dm_permission(false)
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "stats",
+                "Queue depth, pending actions and stored counts",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "purge-expired",
+                "Drop finished actions, expired drafts and spent notifications",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "requeue-failed",
+                "Send failed drafts back to their owners for confirmation",
+            ))
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommandGroup,
+                    "dlq",
+                    "Requests that kept failing after retries",
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "list",
+                    "Show dead-lettered requests and why they failed",
+                ))
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "retry",
+                        "Run dead-lettered requests again",
+                    )
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "id",
+                        "Dead letter id from /admin dlq list; omit to retry all",
+                    )),
+                ),
+            );
+
+        let _ = Command::create_global_command(&ctx.http, admin_builder).await;
+
+        let focus_builder = CreateCommand::new("focus")
+            .description("Hold non-urgent reminders and deliver them as one digest later")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "on", "Start focus mode")
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "duration", "How long, e.g. 45m or 2h")
+                            .required(true),
+                    ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "off",
+                "End focus mode and get the digest now",
+            ));
+
+        let _ = Command::create_global_command(&ctx.http, focus_builder).await;
+
+        let tool_builder = CreateCommand::new("tool")
+            .description("Ask the bot to do something for you, such as add a calendar event")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "request", "What should I do?")
+                    .required(true),
+            );
+
+        let _ = Command::create_global_command(&ctx.http, tool_builder).await;
+
+        let mut status = CreateCommandOption::new(
+            CommandOptionType::String,
+            "status",
+            "Only actions in this state",
+        );
+        for code in ["awaiting_approval", "completed", "rejected", "failed", "expired"] {
+            status = status.add_string_choice(code, code);
+        }
+        let history_builder = CreateCommand::new("history")
+            .description("What the bot has recently done, or been asked to do, for you")
+            .add_option(status)
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "days", "How far back to look (default 7)")
+                    .min_int_value(1)
+                    .max_int_value(90),
+            );
+
+        let _ = Command::create_global_command(&ctx.http, history_builder).await;
+
+        let import_builder = CreateCommand::new("import")
+            .description("Create reminders for the events in a calendar (.ics) file")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Attachment, "file", "The .ics file to import")
+                    .required(true),
+            );
+
+        let _ = Command::create_global_command(&ctx.http, import_builder).await;
+
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        let Some(reactor) = reaction.user_id else {
+            return;
+        };
+        if reactor == ctx.cache.current_user().id {
+            return;
+        }
+        let ReactionType::Unicode(emoji) = &reaction.emoji else {
+            return;
+        };
+        if emoji != APPROVE_REACTION && emoji != CANCEL_REACTION {
+            return;
+        }
+        let Ok(mut message) = reaction.message(&ctx.http).await else {
+            return;
+        };
+        let custom_ids: Vec<String> = message
+            .components
+            .iter()
+            .flat_map(|row| row.components.iter())
+            .filter_map(|component| match component {
+                serenity::all::ActionRowComponent::Button(button) => match &button.data {
+                    serenity::all::ButtonKind::NonLink { custom_id, .. } => Some(custom_id.clone()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        let user_id = format!("@{}", reactor);
+        let guild_id = reaction.guild_id.map(|id| id.to_string());
+        if !self
+            .handle_reaction_with(&user_id, guild_id.as_deref(), emoji, &custom_ids)
+            .await
+        {
+            return;
+        }
+        // Same as pressing the button: the prompt stops taking answers.
+        let locale = self.locale_for(&user_id, guild_id.as_deref()).await;
+        let _ = message
+            .edit(
+                &ctx.http,
+                serenity::builder::EditMessage::new()
+                    .content(i18n::text(locale, MessageKey::ProcessingRequest))
+                    .components(Vec::new()),
+            )
+            .await;
+    }
+
+    async fn message(&self, ctx: Context, message: Message) {
+        if message.author.bot {
+            return;
+        }
+        let bot_id = ctx.cache.current_user().id;
+        if !message.mentions_user_id(bot_id) {
+            return;
+        }
+
+        let user_id = format!("@{}", message.author.id);
+        let channel_id = message.channel_id.to_string();
+        let guild_id = message.guild_id.map(|id| id.to_string());
+        let span = tracing::info_span!(
+            "interaction",
+            kind = "mention",
+            user_id = %user_id,
+            channel_id = %channel_id,
+        );
+        let responder = MessageResponder::new(&ctx, &message);
+        self.handle_mention_with(
+            &responder,
+            &message.content,
+            bot_id.get(),
+            &user_id,
+            &channel_id,
+            guild_id.as_deref(),
+        )
+        .instrument(span)
+        .await;
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: DiscordInteraction) {
+        let span = interaction_span(&interaction);
+        self.dispatch_interaction(ctx, interaction)
+            .instrument(span)
+            .await;
+    }
+}
+
+// One span per Discord interaction; component and modal custom ids carry the
+// action id after the colon.
+fn interaction_span(interaction: &DiscordInteraction) -> tracing::Span {
+    let (kind, name, user_id, channel_id) = match interaction {
+        DiscordInteraction::Command(command) => (
+            "command",
+            command.data.name.as_str(),
+            command.user.id,
+            command.channel_id,
+        ),
+        DiscordInteraction::Component(component) => (
+            "component",
+            component.data.custom_id.as_str(),
+            component.user.id,
+            component.channel_id,
+        ),
+        DiscordInteraction::Modal(modal) => (
+            "modal",
+            modal.data.custom_id.as_str(),
+            modal.user.id,
+            modal.channel_id,
+        ),
+        _ => return tracing::info_span!("interaction", kind = "other"),
+    };
+    let span = tracing::info_span!(
+        "interaction",
+        kind,
+        name = tracing::field::Empty,
+        user_id = %format!("@{}", user_id),
+        channel_id = %channel_id,
+        action_id = tracing::field::Empty,
+    );
+    match name.split_once(':') {
+        Some((name, action_id)) => {
+            span.record("name", name);
+            span.record("action_id", action_id);
+        }
+        None => {
+            span.record("name", name);
+        }
+    }
+    span
+}
+
+impl BotHandler {
+    async fn dispatch_interaction(&self, ctx: Context, interaction: DiscordInteraction) {
         match interaction {
             DiscordInteraction::Command(command) => {
                 match command.data.name.as_str() {
                     "notify" => self.handle_notify(&ctx, command).await,
+                    "settings" => self.handle_settings(&ctx, command).await,
+                    "alias" => self.handle_alias(&ctx, command).await,
+                    "admin" => self.handle_admin(&ctx, command).await,
+                    "focus" => self.handle_focus(&ctx, command).await,
+                    "tool" => self.handle_tool(&ctx, command).await,
+                    "history" => self.handle_history(&ctx, command).await,
+                    "import" => self.handle_import(&ctx, command).await,
                     _ => {
                         // Unknown or unhandled command; ignore for now.
                     }
@@ -261,12 +2750,32 @@ impl EventHandler for BotHandler {
                         "action_confirm" => {
                             self.handle_pending_confirm(&ctx, component, pending_id).await;
                         }
-                        "action_cancel" => {
+                        "action_cancel" | "action_skip" => {
                             self.handle_pending_cancel(&ctx, component, pending_id).await;
                         }
+                        "action_merge" => {
+                            self.handle_pending_merge(&ctx, component, pending_id).await;
+                        }
                         "action_context" => {
                             self.handle_pending_context(&ctx, component, pending_id).await;
                         }
+                        "action_move" => {
+                            self.handle_pending_move(&ctx, component, pending_id).await;
+                        }
+                        "action_slot" => {
+                            self.handle_slot_choice(&ctx, component, pending_id).await;
+                        }
+                        "reminder_done" => {
+                            self.handle_reminder_resolved(&ctx, component, pending_id, false)
+                                .await;
+                        }
+                        "reminder_dismiss" => {
+                            self.handle_reminder_resolved(&ctx, component, pending_id, true)
+                                .await;
+                        }
+                        "checklist" => {
+                            self.handle_checklist_toggle(&ctx, component, pending_id).await;
+                        }
                         _ => {}
                     }
                 }
@@ -291,24 +2800,30 @@ impl EventHandler for BotHandler {
                         }
                     }
 
-                    self.event_bus
+                    let user_id = format!("@{}", modal.user.id);
+                    let guild_id = modal.guild_id.map(|id| id.to_string());
+                    let locale = self.locale_for(&user_id, guild_id.as_deref()).await;
+                    let responder = SerenityResponder::for_modal(&ctx, &modal);
+                    if let Err(retry_at) = self.check_rate_limit(&user_id).await {
+                        let decision = NotifyDecision::RateLimited { retry_at };
+                        responder
+                            .reply_ephemeral(&Self::notify_response(locale, &decision))
+                            .await;
+                        return;
+                    }
+                    let queued = self
+                        .event_bus
                         .emit(ActionEvent::ContextSubmitted {
                             action_id: action_id.to_string(),
-                            user_id: format!("@{}", modal.user.id),
+                            user_id: user_id.clone(),
                             context: context_value.unwrap_or_default(),
                         })
                         .await;
-
-                    let _ = modal
-                        .create_response(
-                            &ctx.http,
-                            CreateInteractionResponse::Message(
-                                CreateInteractionResponseMessage::new()
-                                    .content("Thanks! Updating your notification preview.")
-                                    .ephemeral(true),
-                            ),
-                        )
-                        .await;
+                    let key = match queued {
+                        Ok(()) => MessageKey::ContextSubmitted,
+                        Err(_) => MessageKey::QueueFull,
+                    };
+                    responder.reply_ephemeral(i18n::text(locale, key)).await;
                 }
             }
         }
@@ -1,19 +1,99 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use serenity::async_trait;
-use serenity::builder::{CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal};
-use serenity::all::{CommandInteraction, ComponentInteraction};
+use serenity::builder::{
+    CreateActionRow,
+    CreateInteractionResponse,
+    CreateInteractionResponseFollowup,
+    CreateInteractionResponseMessage,
+    CreateModal,
+    EditInteractionResponse,
+};
+use serenity::all::{CommandInteraction, ComponentInteraction, Message, ModalInteraction};
+use serenity::builder::EditMessage;
 use serenity::prelude::Context;
+use tokio::sync::Mutex;
 
 #[async_trait]
 pub trait InteractionResponder: Send + Sync {
     async fn reply_ephemeral(&self, content: &str);
     async fn reply_update(&self, content: &str);
+    // Like reply_update, but keeps the message's buttons as given instead of
+    // clearing them.
+    async fn reply_update_with_buttons(&self, content: &str, _buttons: Vec<CreateActionRow>) {
+        self.reply_update(content).await;
+    }
     async fn show_modal(&self, modal: CreateModal);
+    async fn defer_ephemeral(&self);
+    async fn follow_up(&self, content: &str, ephemeral: bool);
+    async fn edit_original(&self, content: &str);
+}
+
+// The kinds of reply a handler asks SerenityResponder for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reply {
+    Update,
+    UpdateWithButtons,
+    Modal,
+    Defer,
+    Message,
+}
+
+// How a reply actually goes out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    // Initial responses: edit the clicked message, post a new one, open a
+    // modal or acknowledge for later.
+    UpdateMessage,
+    Message,
+    Modal,
+    Defer,
+    FollowUp,
+    // Nothing Discord would accept, e.g. a modal after the initial response.
+    Skip,
+}
+
+// Discord only accepts one initial response per interaction; anything after
+// that has to go out as a follow-up message.
+#[derive(Debug, Default)]
+pub struct InitialResponse {
+    claimed: AtomicBool,
+}
+
+impl InitialResponse {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns true exactly once: for the caller that gets to use the
+    // interaction's initial response.
+    fn claim(&self) -> bool {
+        !self.claimed.swap(true, Ordering::SeqCst)
+    }
+
+    // Picks the delivery for `reply`, using up the initial response if it
+    // gets it. Only component interactions have a message to update or can
+    // open a modal.
+    pub fn route(&self, reply: Reply, on_component: bool) -> Delivery {
+        let (first, later) = match reply {
+            Reply::Update | Reply::UpdateWithButtons if on_component => {
+                (Delivery::UpdateMessage, Delivery::FollowUp)
+            }
+            Reply::Update | Reply::UpdateWithButtons | Reply::Message => (Delivery::Message, Delivery::FollowUp),
+            Reply::Modal if on_component => (Delivery::Modal, Delivery::Skip),
+            Reply::Modal => return Delivery::Skip,
+            Reply::Defer => (Delivery::Defer, Delivery::Skip),
+        };
+        if self.claim() { first } else { later }
+    }
 }
 
 pub struct SerenityResponder<'a> {
     ctx: &'a Context,
     command: Option<&'a CommandInteraction>,
     component: Option<&'a ComponentInteraction>,
+    modal: Option<&'a ModalInteraction>,
+    initial: InitialResponse,
 }
 
 impl<'a> SerenityResponder<'a> {
@@ -22,6 +102,8 @@ impl<'a> SerenityResponder<'a> {
             ctx,
             command: Some(command),
             component: None,
+            modal: None,
+            initial: InitialResponse::new(),
         }
     }
 
@@ -30,6 +112,113 @@ impl<'a> SerenityResponder<'a> {
             ctx,
             command: None,
             component: Some(component),
+            modal: None,
+            initial: InitialResponse::new(),
+        }
+    }
+
+    pub fn for_modal(ctx: &'a Context, modal: &'a ModalInteraction) -> Self {
+        Self {
+            ctx,
+            command: None,
+            component: None,
+            modal: Some(modal),
+            initial: InitialResponse::new(),
+        }
+    }
+
+    fn route(&self, reply: Reply) -> Delivery {
+        self.initial.route(reply, self.component.is_some())
+    }
+
+    async fn respond(&self, response: CreateInteractionResponse) {
+        let http = &self.ctx.http;
+        let result = if let Some(command) = self.command {
+            command.create_response(http, response).await
+        } else if let Some(component) = self.component {
+            component.create_response(http, response).await
+        } else if let Some(modal) = self.modal {
+            modal.create_response(http, response).await
+        } else {
+            Ok(())
+        };
+        if let Err(err) = result {
+            tracing::warn!(error = %err, "failed to send interaction response");
+        }
+    }
+
+    async fn follow_up_message(&self, followup: CreateInteractionResponseFollowup) {
+        let http = &self.ctx.http;
+        let result = if let Some(command) = self.command {
+            command.create_followup(http, followup).await.map(drop)
+        } else if let Some(component) = self.component {
+            component.create_followup(http, followup).await.map(drop)
+        } else if let Some(modal) = self.modal {
+            modal.create_followup(http, followup).await.map(drop)
+        } else {
+            Ok(())
+        };
+        if let Err(err) = result {
+            tracing::warn!(error = %err, "failed to send interaction follow-up");
+        }
+    }
+
+    async fn edit_response(&self, edit: EditInteractionResponse) {
+        let http = &self.ctx.http;
+        let result = if let Some(command) = self.command {
+            command.edit_response(http, edit).await.map(drop)
+        } else if let Some(component) = self.component {
+            component.edit_response(http, edit).await.map(drop)
+        } else if let Some(modal) = self.modal {
+            modal.edit_response(http, edit).await.map(drop)
+        } else {
+            Ok(())
+        };
+        if let Err(err) = result {
+            tracing::warn!(error = %err, "failed to edit interaction response");
+        }
+    }
+
+    // `buttons` replaces the message's components; None clears them.
+    async fn send(
+        &self,
+        delivery: Delivery,
+        content: &str,
+        ephemeral: bool,
+        buttons: Option<Vec<CreateActionRow>>,
+    ) {
+        match delivery {
+            Delivery::UpdateMessage => {
+                self.respond(CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .content(content)
+                        .components(buttons.unwrap_or_default()),
+                ))
+                .await
+            }
+            Delivery::Message => {
+                self.respond(CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(content)
+                        .ephemeral(ephemeral),
+                ))
+                .await
+            }
+            Delivery::FollowUp => {
+                self.follow_up_message(
+                    CreateInteractionResponseFollowup::new()
+                        .content(content)
+                        .ephemeral(ephemeral),
+                )
+                .await
+            }
+            Delivery::Defer => {
+                self.respond(CreateInteractionResponse::Defer(
+                    CreateInteractionResponseMessage::new().ephemeral(true),
+                ))
+                .await
+            }
+            Delivery::Modal | Delivery::Skip => {}
         }
     }
 }
@@ -37,51 +226,99 @@ impl<'a> SerenityResponder<'a> {
 #[async_trait]
 impl InteractionResponder for SerenityResponder<'_> {
     async fn reply_ephemeral(&self, content: &str) {
-        let response = CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content(content)
-                .ephemeral(true),
-        );
-        if let Some(command) = self.command {
-            let _ = command.create_response(&self.ctx.http, response).await;
-            return;
-        }
-        if let Some(component) = self.component {
-            let _ = component.create_response(&self.ctx.http, response).await;
-        }
+        self.follow_up(content, true).await;
     }
 
     async fn reply_update(&self, content: &str) {
-        if let Some(component) = self.component {
-            let _ = component
-                .create_response(
-                    &self.ctx.http,
-                    CreateInteractionResponse::UpdateMessage(
-                        CreateInteractionResponseMessage::new()
-                            .content(content)
-                            .components(vec![]),
-                    ),
-                )
-                .await;
-            return;
+        self.send(self.route(Reply::Update), content, false, None).await;
+    }
+
+    async fn reply_update_with_buttons(&self, content: &str, buttons: Vec<CreateActionRow>) {
+        self.send(self.route(Reply::UpdateWithButtons), content, false, Some(buttons))
+            .await;
+    }
+
+    async fn show_modal(&self, modal: CreateModal) {
+        if self.route(Reply::Modal) == Delivery::Modal {
+            self.respond(CreateInteractionResponse::Modal(modal)).await;
         }
-        if let Some(command) = self.command {
-            let _ = command
-                .create_response(
-                    &self.ctx.http,
-                    CreateInteractionResponse::Message(
-                        CreateInteractionResponseMessage::new().content(content),
-                    ),
-                )
-                .await;
+    }
+
+    async fn defer_ephemeral(&self) {
+        self.send(self.route(Reply::Defer), "", true, None).await;
+    }
+
+    async fn follow_up(&self, content: &str, ephemeral: bool) {
+        self.send(self.route(Reply::Message), content, ephemeral, None).await;
+    }
+
+    async fn edit_original(&self, content: &str) {
+        self.edit_response(EditInteractionResponse::new().content(content))
+            .await;
+    }
+}
+
+// Adapts a plain channel message (e.g. one that mentions the bot) to the
+// responder interface. Channel replies cannot be ephemeral or carry modals,
+// so those degrade to ordinary replies / no-ops.
+pub struct MessageResponder<'a> {
+    ctx: &'a Context,
+    message: &'a Message,
+    last_reply: Mutex<Option<Message>>,
+}
+
+impl<'a> MessageResponder<'a> {
+    pub fn new(ctx: &'a Context, message: &'a Message) -> Self {
+        Self {
+            ctx,
+            message,
+            last_reply: Mutex::new(None),
         }
     }
 
-    async fn show_modal(&self, modal: CreateModal) {
-        if let Some(component) = self.component {
-            let _ = component
-                .create_response(&self.ctx.http, CreateInteractionResponse::Modal(modal))
-                .await;
+    async fn reply(&self, content: &str) {
+        match self.message.reply(&self.ctx.http, content).await {
+            Ok(sent) => {
+                let mut last_reply = self.last_reply.lock().await;
+                *last_reply = Some(sent);
+            }
+            Err(err) => tracing::warn!(error = %err, "failed to reply to message"),
+        }
+    }
+}
+
+#[async_trait]
+impl InteractionResponder for MessageResponder<'_> {
+    async fn reply_ephemeral(&self, content: &str) {
+        self.reply(content).await;
+    }
+
+    async fn reply_update(&self, content: &str) {
+        self.reply(content).await;
+    }
+
+    async fn show_modal(&self, _modal: CreateModal) {}
+
+    async fn defer_ephemeral(&self) {
+        let _ = self.message.channel_id.broadcast_typing(&self.ctx.http).await;
+    }
+
+    async fn follow_up(&self, content: &str, _ephemeral: bool) {
+        self.reply(content).await;
+    }
+
+    async fn edit_original(&self, content: &str) {
+        let mut last_reply = self.last_reply.lock().await;
+        if let Some(sent) = last_reply.as_mut() {
+            if let Err(err) = sent
+                .edit(&self.ctx.http, EditMessage::new().content(content))
+                .await
+            {
+                tracing::warn!(error = %err, "failed to edit reply");
+            }
+            return;
         }
+        drop(last_reply);
+        self.reply(content).await;
     }
 }
@@ -0,0 +1,520 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use memory_db::DB;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use warp::http::StatusCode;
+use warp::reply::{Reply, Response};
+use warp::Filter;
+
+use crate::config::SharedConfig;
+use crate::events::queue::{EventBus, QueueMetrics};
+use crate::handlers::action::ActionEvent;
+use crate::models::notification::{self, DeliveryTarget, Notification};
+use crate::models::recurrence::Recurrence;
+use crate::models::settings::{self, UserSettings};
+use crate::models::todo::{self, TodoItem};
+use crate::service::audit::{AuditEntry, AuditLog};
+use crate::service::ics;
+use crate::tasks::notification_loop::MessageSender;
+use crate::tasks::task_runner::{TaskHealth, TaskStatus};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateNotificationRequest {
+    pub content: String,
+    pub notify: Vec<String>,
+    pub time: DateTime<Utc>,
+    pub channel: String,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default)]
+    pub checklist: Vec<String>,
+    // Where else each ping goes, e.g. [{"kind": "email", "to": "me@example.com"}].
+    #[serde(default)]
+    pub targets: Vec<DeliveryTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTodoRequest {
+    pub user_id: String,
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookNotifyRequest {
+    pub text: String,
+    pub user_id: String,
+    pub channel_id: String,
+}
+
+// A reminder another bot creates for a user who has trusted it. Unlike
+// /webhooks/notify nothing is interpreted or confirmed: the time is exact and
+// the reminder is stored straight away.
+#[derive(Debug, Deserialize)]
+pub struct IntegrationReminderRequest {
+    pub user_id: String,
+    pub content: String,
+    pub time: DateTime<Utc>,
+    pub channel: String,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+}
+
+// Where the webhook listener binds and the bearer token callers must present.
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub addr: SocketAddr,
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+fn error_response(status: StatusCode, error: impl ToString) -> Response {
+    warp::reply::with_status(
+        warp::reply::json(&ErrorBody {
+            error: error.to_string(),
+        }),
+        status,
+    )
+    .into_response()
+}
+
+fn with_db<T: Send + 'static>(
+    db: Arc<Mutex<DB<T>>>,
+) -> impl Filter<Extract = (Arc<Mutex<DB<T>>>,), Error = Infallible> + Clone {
+    warp::any().map(move || db.clone())
+}
+
+// REST routes over the notification and todo stores:
+//   GET/POST /notifications, DELETE /notifications/{id}
+//   GET /calendar.ics[?user=<id>] (iCalendar feed of scheduled reminders)
+//   GET/POST /todos, DELETE /todos/{id}
+pub fn routes(
+    notification_db: Arc<Mutex<DB<Notification>>>,
+    todo_db: Arc<Mutex<DB<TodoItem>>>,
+) -> impl Filter<Extract = (Response,), Error = warp::Rejection> + Clone {
+    let list_notifications = warp::path!("notifications")
+        .and(warp::get())
+        .and(with_db(notification_db.clone()))
+        .and_then(list_notifications);
+    let calendar_feed = warp::path!("calendar.ics")
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_db(notification_db.clone()))
+        .and_then(calendar_feed);
+    let create_notification = warp::path!("notifications")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_db(notification_db.clone()))
+        .and_then(create_notification);
+    let delete_notification = warp::path!("notifications" / String)
+        .and(warp::delete())
+        .and(with_db(notification_db))
+        .and_then(delete_notification);
+    let list_todos = warp::path!("todos")
+        .and(warp::get())
+        .and(with_db(todo_db.clone()))
+        .and_then(list_todos);
+    let create_todo = warp::path!("todos")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_db(todo_db.clone()))
+        .and_then(create_todo);
+    let delete_todo = warp::path!("todos" / String)
+        .and(warp::delete())
+        .and(with_db(todo_db))
+        .and_then(delete_todo);
+
+    list_notifications
+        .or(calendar_feed)
+        .unify()
+        .or(create_notification)
+        .unify()
+        .or(delete_notification)
+        .unify()
+        .or(list_todos)
+        .unify()
+        .or(create_todo)
+        .unify()
+        .or(delete_todo)
+        .unify()
+}
+
+async fn list_notifications(db: Arc<Mutex<DB<Notification>>>) -> Result<Response, Infallible> {
+    let db = db.lock().await;
+    let mut notifications: Vec<&Notification> = db.values().collect();
+    notifications.sort_by_key(|n| n.notification_times.first().copied());
+    Ok(warp::reply::json(&notifications).into_response())
+}
+
+async fn calendar_feed(
+    query: HashMap<String, String>,
+    db: Arc<Mutex<DB<Notification>>>,
+) -> Result<Response, Infallible> {
+    let db = db.lock().await;
+    let body = ics::render_calendar(
+        &ics::scheduled(&db, query.get("user").map(String::as_str)),
+        Utc::now(),
+    );
+    Ok(warp::reply::with_header(body, "content-type", "text/calendar; charset=utf-8").into_response())
+}
+
+// GET /feeds/<token>.ics is one user's calendar feed, for the URL
+// /settings feed hands out. Served on its own listener: calendar apps need
+// it reachable from outside, the REST API above must not be.
+pub fn feed_route(
+    notification_db: Arc<Mutex<DB<Notification>>>,
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+) -> impl Filter<Extract = (Response,), Error = warp::Rejection> + Clone {
+    warp::path!("feeds" / String)
+        .and(warp::get())
+        .and(with_db(user_settings))
+        .and(with_db(notification_db))
+        .and_then(calendar_feed_for_token)
+}
+
+async fn calendar_feed_for_token(
+    file: String,
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+    db: Arc<Mutex<DB<Notification>>>,
+) -> Result<Response, Infallible> {
+    let user = {
+        let user_settings = user_settings.lock().await;
+        file.strip_suffix(".ics")
+            .filter(|token| !token.is_empty())
+            .and_then(|token| settings::user_for_feed_token(&user_settings, token))
+            .map(str::to_string)
+    };
+    let Some(user) = user else {
+        return Ok(error_response(StatusCode::NOT_FOUND, "unknown feed"));
+    };
+    let db = db.lock().await;
+    let body = ics::render_calendar(&ics::scheduled(&db, Some(&user)), Utc::now());
+    Ok(warp::reply::with_header(body, "content-type", "text/calendar; charset=utf-8").into_response())
+}
+
+async fn create_notification(
+    request: CreateNotificationRequest,
+    db: Arc<Mutex<DB<Notification>>>,
+) -> Result<Response, Infallible> {
+    if request.content.trim().is_empty() || request.notify.is_empty() {
+        return Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "content and notify are required",
+        ));
+    }
+    if request.targets.iter().any(|target| target.address().trim().is_empty()) {
+        return Ok(error_response(StatusCode::BAD_REQUEST, "every target needs an address"));
+    }
+    let mut db = db.lock().await;
+    let result = notification::create_notification(
+        &mut db,
+        &request.content,
+        &request.notify.join(","),
+        &request.time,
+        &request.channel,
+        request.recurrence,
+    )
+    .await;
+    let result = match result {
+        Ok(id) if !request.checklist.is_empty() => {
+            notification::set_checklist(&mut db, &id, &request.checklist).map(|_| id)
+        }
+        other => other,
+    };
+    let result = match result {
+        Ok(id) if !request.targets.is_empty() => notification::set_targets(&mut db, &id, request.targets).map(|_| id),
+        other => other,
+    };
+    match result {
+        Ok(id) => match db.get(&id) {
+            Some(created) => Ok(warp::reply::with_status(
+                warp::reply::json(created),
+                StatusCode::CREATED,
+            )
+            .into_response()),
+            None => Ok(error_response(StatusCode::INTERNAL_SERVER_ERROR, "notification not stored")),
+        },
+        Err(err) => Ok(error_response(StatusCode::INTERNAL_SERVER_ERROR, err)),
+    }
+}
+
+async fn delete_notification(
+    id: String,
+    db: Arc<Mutex<DB<Notification>>>,
+) -> Result<Response, Infallible> {
+    let mut db = db.lock().await;
+    match notification::delete_notification(&mut db, &id) {
+        Ok(true) => Ok(StatusCode::NO_CONTENT.into_response()),
+        Ok(false) => Ok(error_response(StatusCode::NOT_FOUND, "notification not found")),
+        Err(err) => Ok(error_response(StatusCode::INTERNAL_SERVER_ERROR, err)),
+    }
+}
+
+async fn list_todos(db: Arc<Mutex<DB<TodoItem>>>) -> Result<Response, Infallible> {
+    let db = db.lock().await;
+    let mut todos: Vec<&TodoItem> = db.values().collect();
+    todos.sort_by_key(|t| t.created_at);
+    Ok(warp::reply::json(&todos).into_response())
+}
+
+async fn create_todo(
+    request: CreateTodoRequest,
+    db: Arc<Mutex<DB<TodoItem>>>,
+) -> Result<Response, Infallible> {
+    if request.content.trim().is_empty() || request.user_id.trim().is_empty() {
+        return Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "user_id and content are required",
+        ));
+    }
+    let mut db = db.lock().await;
+    match todo::create_todo(&mut db, &request.user_id, &request.content) {
+        Ok(id) => match db.get(&id) {
+            Some(created) => Ok(warp::reply::with_status(
+                warp::reply::json(created),
+                StatusCode::CREATED,
+            )
+            .into_response()),
+            None => Ok(error_response(StatusCode::INTERNAL_SERVER_ERROR, "todo not stored")),
+        },
+        Err(err) => Ok(error_response(StatusCode::INTERNAL_SERVER_ERROR, err)),
+    }
+}
+
+async fn delete_todo(id: String, db: Arc<Mutex<DB<TodoItem>>>) -> Result<Response, Infallible> {
+    let mut db = db.lock().await;
+    match todo::delete_todo(&mut db, &id) {
+        Ok(true) => Ok(StatusCode::NO_CONTENT.into_response()),
+        Ok(false) => Ok(error_response(StatusCode::NOT_FOUND, "todo not found")),
+        Err(err) => Ok(error_response(StatusCode::INTERNAL_SERVER_ERROR, err)),
+    }
+}
+
+// POST /webhooks/notify lets external systems (CI, cron, home automation) push
+// a reminder request onto the event bus as if it came from /notify.
+pub fn webhook_routes(
+    event_bus: EventBus,
+    token: String,
+) -> impl Filter<Extract = (Response,), Error = warp::Rejection> + Clone {
+    let token = Arc::new(token);
+    warp::path!("webhooks" / "notify")
+        .and(warp::post())
+        .and(warp::header::optional::<String>("authorization"))
+        .and(warp::body::json())
+        .and(warp::any().map(move || token.clone()))
+        .and(warp::any().map(move || event_bus.clone()))
+        .and_then(webhook_notify)
+}
+
+// GET /metrics reports the event queue in Prometheus text format. Served on
+// the webhook listener, which lives in the same process as the bus.
+pub fn metrics_route(
+    event_bus: EventBus,
+) -> impl Filter<Extract = (Response,), Error = warp::Rejection> + Clone {
+    warp::path!("metrics")
+        .and(warp::get())
+        .map(move || render_metrics(&event_bus.metrics()).into_response())
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthBody {
+    pub status: &'static str,
+    pub tasks: Vec<TaskStatus>,
+}
+
+// 503 while any background loop is waiting to be restarted after a crash.
+pub fn health_route(
+    health: TaskHealth,
+) -> impl Filter<Extract = (Response,), Error = warp::Rejection> + Clone {
+    warp::path!("healthz").and(warp::get()).map(move || {
+        let (status, code) = if health.is_healthy() {
+            ("ok", StatusCode::OK)
+        } else {
+            ("degraded", StatusCode::SERVICE_UNAVAILABLE)
+        };
+        let body = HealthBody {
+            status,
+            tasks: health.snapshot(),
+        };
+        warp::reply::with_status(warp::reply::json(&body), code).into_response()
+    })
+}
+
+pub fn render_metrics(metrics: &QueueMetrics) -> String {
+    format!(
+        "# TYPE reminderbot_event_queue_depth gauge\n\
+         reminderbot_event_queue_depth {}\n\
+         # TYPE reminderbot_event_queue_capacity gauge\n\
+         reminderbot_event_queue_capacity {}\n\
+         # TYPE reminderbot_events_rejected_total counter\n\
+         reminderbot_events_rejected_total {}\n\
+         # TYPE reminderbot_events_evicted_total counter\n\
+         reminderbot_events_evicted_total {}\n",
+        metrics.depth, metrics.capacity, metrics.rejected, metrics.evicted
+    )
+}
+
+fn bearer_matches(header: Option<&str>, token: &str) -> bool {
+    let Some(presented) = header.and_then(|h| h.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    // Compare every byte so the response time does not leak the match length.
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+async fn webhook_notify(
+    authorization: Option<String>,
+    request: WebhookNotifyRequest,
+    token: Arc<String>,
+    event_bus: EventBus,
+) -> Result<Response, Infallible> {
+    if token.is_empty() || !bearer_matches(authorization.as_deref(), &token) {
+        return Ok(error_response(StatusCode::UNAUTHORIZED, "invalid token"));
+    }
+    if request.text.trim().is_empty()
+        || request.user_id.trim().is_empty()
+        || request.channel_id.trim().is_empty()
+    {
+        return Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "text, user_id and channel_id are required",
+        ));
+    }
+    let queued = event_bus
+        .emit(ActionEvent::NotifyRequested {
+            text: request.text.trim().to_string(),
+            // Stored the way Discord interactions name users, so the owner can
+            // confirm the draft from the prompt.
+            user_id: format!("@{}", request.user_id.trim().trim_start_matches('@')),
+            channel_id: request.channel_id,
+            received_at: Some(Utc::now()),
+        })
+        .await;
+    if let Err(err) = queued {
+        return Ok(error_response(StatusCode::SERVICE_UNAVAILABLE, err));
+    }
+    Ok(StatusCode::ACCEPTED.into_response())
+}
+
+// POST /integrations/reminders lets bots listed in INTEGRATION_APPS create
+// reminders for users who allowed them with /settings integrations, in
+// channels of the guild they allowed them from. The bearer token identifies
+// the app; every creation is written to the audit log.
+pub fn integration_routes(
+    notification_db: Arc<Mutex<DB<Notification>>>,
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+    audit: Arc<AuditLog>,
+    config: SharedConfig,
+    sender: Arc<dyn MessageSender>,
+) -> impl Filter<Extract = (Response,), Error = warp::Rejection> + Clone {
+    warp::path!("integrations" / "reminders")
+        .and(warp::post())
+        .and(warp::header::optional::<String>("authorization"))
+        .and(warp::body::json())
+        .and(warp::any().map(move || config.clone()))
+        .and(with_db(user_settings))
+        .and(with_db(notification_db))
+        .and(warp::any().map(move || audit.clone()))
+        .and(warp::any().map(move || sender.clone()))
+        .and_then(integration_reminder)
+}
+
+async fn integration_reminder(
+    authorization: Option<String>,
+    request: IntegrationReminderRequest,
+    config: SharedConfig,
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+    notification_db: Arc<Mutex<DB<Notification>>>,
+    audit: Arc<AuditLog>,
+    sender: Arc<dyn MessageSender>,
+) -> Result<Response, Infallible> {
+    // Read per request so apps can be added or revoked without a restart.
+    let Some(app_id) = config
+        .load()
+        .integration_apps()
+        .into_iter()
+        .find(|(_, token)| bearer_matches(authorization.as_deref(), token))
+        .map(|(app_id, _)| app_id)
+    else {
+        return Ok(error_response(StatusCode::UNAUTHORIZED, "invalid token"));
+    };
+    let user_id = request.user_id.trim().to_string();
+    if request.content.trim().is_empty() || user_id.is_empty() || request.channel.trim().is_empty() {
+        return Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "user_id, content and channel are required",
+        ));
+    }
+    let trusted_guild = {
+        let users = user_settings.lock().await;
+        if !settings::user_trusts_app(&users, &user_id, &app_id) {
+            tracing::warn!(%app_id, %user_id, "integration reminder rejected without consent");
+            return Ok(error_response(
+                StatusCode::FORBIDDEN,
+                "user has not allowed this app to create reminders",
+            ));
+        }
+        settings::trusted_app_guild(&users, &user_id, &app_id).map(str::to_string)
+    };
+    // Consent covers the guild it was given in; an app must not reach into
+    // channels elsewhere, and a channel whose guild can't be told is refused.
+    let channel_guild = sender.guild_for_channel(request.channel.trim()).await;
+    if trusted_guild.is_none() || channel_guild != trusted_guild {
+        tracing::warn!(
+            %app_id,
+            %user_id,
+            channel = %request.channel,
+            "integration reminder rejected outside the trusted guild"
+        );
+        return Ok(error_response(
+            StatusCode::FORBIDDEN,
+            "channel is not in the guild the user allowed this app from",
+        ));
+    }
+
+    let mut db = notification_db.lock().await;
+    let id = match notification::create_notification(
+        &mut db,
+        &request.content.trim().to_string(),
+        &user_id,
+        &request.time,
+        &request.channel,
+        request.recurrence,
+    )
+    .await
+    {
+        Ok(id) => id,
+        Err(err) => return Ok(error_response(StatusCode::INTERNAL_SERVER_ERROR, err)),
+    };
+    let entry = AuditEntry {
+        at: Utc::now(),
+        actor: format!("app:{}", app_id),
+        user_id: user_id.clone(),
+        action: "reminder.create".to_string(),
+        detail: format!("{} at {}", id, request.time.to_rfc3339()),
+    };
+    if let Err(err) = audit.record(&entry) {
+        tracing::error!(%app_id, %user_id, error = %err, "failed to audit integration reminder");
+    }
+    match db.get(&id) {
+        Some(created) => Ok(warp::reply::with_status(
+            warp::reply::json(created),
+            StatusCode::CREATED,
+        )
+        .into_response()),
+        None => Ok(error_response(StatusCode::INTERNAL_SERVER_ERROR, "notification not stored")),
+    }
+}
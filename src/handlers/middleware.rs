@@ -0,0 +1,26 @@
+use crate::events::retry::EventError;
+use crate::handlers::action::ActionEvent;
+
+// What a before_handle hook decided.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    // The event is dropped as handled; the reason is logged.
+    Stop(String),
+}
+
+// Cross-cutting behaviour around every event the engine handles (auditing,
+// metrics, rate limits, redaction) without touching each match arm.
+// Registered with ActionEngine::with_middleware. before_handle hooks run in
+// registration order and may rewrite the event; after_handle hooks run in
+// reverse order, only for events every before_handle let through.
+#[serenity::async_trait]
+pub trait ActionMiddleware: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn before_handle(&self, _event: &mut ActionEvent) -> Flow {
+        Flow::Continue
+    }
+
+    async fn after_handle(&self, _event: &ActionEvent, _result: &Result<(), EventError>) {}
+}
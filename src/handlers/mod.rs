@@ -1,3 +1,7 @@
 pub mod action;
 pub mod discord;
 pub mod discord_responder;
+pub mod http;
+pub mod middleware;
+pub mod simulate;
+pub mod tui;
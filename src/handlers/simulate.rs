@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::events::queue::{EventBus, EventReceiver};
+use crate::handlers::discord::BotHandler;
+use crate::handlers::discord_responder::InteractionResponder;
+use crate::service::routing::HeuristicRouter;
+
+// The mention id scripts address the bot with, e.g. "<@1> call mom at 5".
+pub const SIMULATED_BOT_ID: u64 = 1;
+
+fn default_user() -> String {
+    "@sim".to_string()
+}
+
+fn default_channel() -> String {
+    "0".to_string()
+}
+
+// One line of a simulation script, e.g.
+// {"kind": "notify", "user": "@1", "text": "call mom tomorrow at 5"}.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScriptStep {
+    Notify {
+        text: String,
+        #[serde(default = "default_user")]
+        user: String,
+        #[serde(default = "default_channel")]
+        channel: String,
+        #[serde(default)]
+        guild: Option<String>,
+    },
+    Mention {
+        text: String,
+        #[serde(default = "default_user")]
+        user: String,
+        #[serde(default = "default_channel")]
+        channel: String,
+        #[serde(default)]
+        guild: Option<String>,
+    },
+    Tool {
+        text: String,
+        #[serde(default = "default_user")]
+        user: String,
+        #[serde(default = "default_channel")]
+        channel: String,
+    },
+    Timezone {
+        zone: String,
+        #[serde(default = "default_user")]
+        user: String,
+    },
+    Locale {
+        code: String,
+        #[serde(default = "default_user")]
+        user: String,
+    },
+}
+
+// Writes down everything a handler sends instead of talking to Discord.
+#[derive(Default)]
+pub struct RecordingResponder {
+    lines: Mutex<Vec<String>>,
+}
+
+impl RecordingResponder {
+    pub async fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.lines.lock().await)
+    }
+
+    async fn push(&self, line: String) {
+        self.lines.lock().await.push(line);
+    }
+}
+
+#[serenity::async_trait]
+impl InteractionResponder for RecordingResponder {
+    async fn reply_ephemeral(&self, content: &str) {
+        self.push(format!("reply: {}", content)).await;
+    }
+
+    async fn reply_update(&self, content: &str) {
+        self.push(format!("update: {}", content)).await;
+    }
+
+    async fn show_modal(&self, _modal: serenity::builder::CreateModal) {
+        self.push("modal".to_string()).await;
+    }
+
+    async fn defer_ephemeral(&self) {
+        self.push("defer".to_string()).await;
+    }
+
+    async fn follow_up(&self, content: &str, ephemeral: bool) {
+        let kind = if ephemeral { "follow-up (ephemeral)" } else { "follow-up" };
+        self.push(format!("{}: {}", kind, content)).await;
+    }
+
+    async fn edit_original(&self, content: &str) {
+        self.push(format!("edit: {}", content)).await;
+    }
+}
+
+// Replays a JSON lines script through the Discord handler with the keyword
+// router and a recording responder, and returns what each step replied and
+// which events it queued. Nothing reaches Discord or OpenAI, and the events
+// are listed rather than processed, so the output only depends on the
+// script.
+pub async fn run_script(script: &str) -> Result<String, String> {
+    let steps: Vec<(usize, ScriptStep)> = script
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(number, line)| {
+            serde_json::from_str(line)
+                .map(|step| (number + 1, step))
+                .map_err(|err| format!("line {}: {}", number + 1, err))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let (bus, mut events) = EventBus::new(steps.len().max(1) * 4);
+    let handler = BotHandler::new(
+        Arc::new(Mutex::new(HashMap::new())),
+        bus,
+        Arc::new(Mutex::new(HashMap::new())),
+        Arc::new(HeuristicRouter),
+    );
+    let responder = RecordingResponder::default();
+    let mut output = Vec::new();
+    for (number, step) in steps {
+        output.push(format!("line {}: {}", number, describe(&step)));
+        if let Some(decision) = run_step(&handler, &responder, step).await {
+            output.push(format!("  decision: {:?}", decision));
+        }
+        output.extend(responder.take().await.into_iter().map(|line| format!("  {}", line)));
+        output.extend(drain(&mut events).into_iter().map(|line| format!("  {}", line)));
+    }
+    Ok(output.join("\n"))
+}
+
+fn describe(step: &ScriptStep) -> String {
+    match step {
+        ScriptStep::Notify { text, user, .. } => format!("{} /notify {}", user, text),
+        ScriptStep::Mention { text, user, .. } => format!("{} mentions: {}", user, text),
+        ScriptStep::Tool { text, user, .. } => format!("{} /tool {}", user, text),
+        ScriptStep::Timezone { zone, user } => format!("{} /settings timezone {}", user, zone),
+        ScriptStep::Locale { code, user } => format!("{} /settings locale {}", user, code),
+    }
+}
+
+async fn run_step(
+    handler: &BotHandler,
+    responder: &RecordingResponder,
+    step: ScriptStep,
+) -> Option<crate::service::notify_flow::NotifyDecision> {
+    match step {
+        ScriptStep::Notify { text, user, channel, guild } => Some(
+            handler
+                .handle_notify_with(responder, &text, &user, &channel, guild.as_deref())
+                .await,
+        ),
+        ScriptStep::Mention { text, user, channel, guild } => {
+            handler
+                .handle_mention_with(responder, &text, SIMULATED_BOT_ID, &user, &channel, guild.as_deref())
+                .await
+        }
+        ScriptStep::Tool { text, user, channel } => {
+            handler.handle_tool_with(responder, &text, &user, &channel, None).await;
+            None
+        }
+        ScriptStep::Timezone { zone, user } => {
+            handler.handle_timezone_with(responder, &user, None, &zone).await;
+            None
+        }
+        ScriptStep::Locale { code, user } => {
+            handler
+                .handle_locale_with(responder, &user, None, &code, false, false)
+                .await;
+            None
+        }
+    }
+}
+
+fn drain(events: &mut EventReceiver) -> Vec<String> {
+    let mut queued = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        let ids = event.ids();
+        let mut line = format!("event: {} user={}", ids.name, ids.user_id);
+        if let Some(channel_id) = ids.channel_id {
+            line.push_str(&format!(" channel={}", channel_id));
+        }
+        queued.push(line);
+    }
+    queued
+}
+
+// `simulate <script.jsonl>`. Settings the script changes are written under a
+// scratch DB_LOCATION so a run never touches the bot's real data.
+pub async fn simulate_command(args: &[String]) -> Result<String, String> {
+    let [path] = args else {
+        return Err("Usage: simulate <script.jsonl>".to_string());
+    };
+    let script = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let scratch = env::temp_dir().join(format!("reminderbot_simulate_{}", uuid::Uuid::new_v4()));
+    // Nothing else is running yet when the command line is handled.
+    unsafe {
+        env::set_var("DB_LOCATION", &scratch);
+    }
+    let output = run_script(&script).await;
+    let _ = std::fs::remove_dir_all(&scratch);
+    output
+}
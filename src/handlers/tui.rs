@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::io;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::America::New_York;
+use memory_db::{DB, load_db, save_db};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::models::notification::{self, AINotification, Notification};
+use crate::models::todo::{self, TodoItem};
+use crate::service::notification_normalizer::{self, NormalizedNotification};
+use crate::service::notification_service::user_notifications;
+use crate::service::openai_service::{OpenAIClient, parse_model_json};
+
+const HELP: &str = "tab switch · ↑/↓ move · a add · c complete · d delete · q quit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    Reminders,
+    Todos,
+}
+
+#[derive(Debug, Clone)]
+pub enum Mode {
+    Browse,
+    // Typing into the prompt box; what it creates depends on the panel.
+    Input(String),
+    // A reminder read from the prompt, waiting for y/n.
+    Confirm(NormalizedNotification),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyOutcome {
+    Continue,
+    Quit,
+    // Prompt text to run through the notification extraction.
+    Extract(String),
+}
+
+// RUN_MODE=tui: the user's upcoming reminders and open todos in two panels,
+// read from and saved to the same files as the bot. New reminders go
+// through the same OpenAI extraction as /notify and are shown for approval
+// before they're saved; todos are added as typed.
+pub struct TuiApp {
+    pub notifications: DB<Notification>,
+    pub todos: DB<TodoItem>,
+    user_id: String,
+    channel_id: String,
+    pub panel: Panel,
+    pub mode: Mode,
+    pub status: String,
+    reminder_state: ListState,
+    todo_state: ListState,
+}
+
+impl TuiApp {
+    pub fn new(
+        notifications: DB<Notification>,
+        todos: DB<TodoItem>,
+        user_id: impl Into<String>,
+        channel_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            notifications,
+            todos,
+            user_id: user_id.into(),
+            channel_id: channel_id.into(),
+            panel: Panel::Reminders,
+            mode: Mode::Browse,
+            status: HELP.to_string(),
+            reminder_state: ListState::default().with_selected(Some(0)),
+            todo_state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn reminders(&self) -> Vec<&Notification> {
+        user_notifications(&self.notifications, &self.user_id)
+    }
+
+    pub fn open_todos(&self) -> Vec<&TodoItem> {
+        let mut items: Vec<&TodoItem> = self
+            .todos
+            .values()
+            .filter(|item| item.user_id == self.user_id && item.completed_at.is_none())
+            .collect();
+        items.sort_by_key(|item| (item.created_at, item.id.clone()));
+        items
+    }
+
+    fn state(&mut self) -> &mut ListState {
+        match self.panel {
+            Panel::Reminders => &mut self.reminder_state,
+            Panel::Todos => &mut self.todo_state,
+        }
+    }
+
+    fn panel_len(&self) -> usize {
+        match self.panel {
+            Panel::Reminders => self.reminders().len(),
+            Panel::Todos => self.open_todos().len(),
+        }
+    }
+
+    // The id of the highlighted row, if the panel has any.
+    pub fn selected_id(&mut self) -> Option<String> {
+        let index = self.state().selected().unwrap_or(0);
+        match self.panel {
+            Panel::Reminders => self.reminders().get(index).map(|n| n.id.clone()),
+            Panel::Todos => self.open_todos().get(index).map(|item| item.id.clone()),
+        }
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let len = self.panel_len();
+        let state = self.state();
+        let index = state.selected().unwrap_or(0);
+        let index = if down { index + 1 } else { index.saturating_sub(1) };
+        state.select(Some(index.min(len.saturating_sub(1))));
+    }
+
+    // Keeps the highlight on a row after the list shrinks.
+    fn clamp_selection(&mut self) {
+        let last = self.panel_len().saturating_sub(1);
+        let state = self.state();
+        state.select(Some(state.selected().unwrap_or(0).min(last)));
+    }
+
+    pub async fn handle_key(&mut self, key: KeyCode, now: DateTime<Utc>) -> KeyOutcome {
+        match std::mem::replace(&mut self.mode, Mode::Browse) {
+            Mode::Input(mut text) => match key {
+                KeyCode::Enter if !text.trim().is_empty() => return self.submit(text.trim().to_string()),
+                KeyCode::Esc => self.status = HELP.to_string(),
+                KeyCode::Backspace => {
+                    text.pop();
+                    self.mode = Mode::Input(text);
+                }
+                KeyCode::Char(c) => {
+                    text.push(c);
+                    self.mode = Mode::Input(text);
+                }
+                _ => self.mode = Mode::Input(text),
+            },
+            Mode::Confirm(draft) => match key {
+                KeyCode::Char('y') | KeyCode::Enter => self.status = self.save_reminder(draft).await,
+                KeyCode::Char('n') | KeyCode::Esc => self.status = "Discarded.".to_string(),
+                _ => self.mode = Mode::Confirm(draft),
+            },
+            Mode::Browse => match key {
+                KeyCode::Char('q') | KeyCode::Esc => return KeyOutcome::Quit,
+                KeyCode::Tab | KeyCode::Left | KeyCode::Right => {
+                    self.panel = match self.panel {
+                        Panel::Reminders => Panel::Todos,
+                        Panel::Todos => Panel::Reminders,
+                    };
+                }
+                KeyCode::Up | KeyCode::Char('k') => self.move_selection(false),
+                KeyCode::Down | KeyCode::Char('j') => self.move_selection(true),
+                KeyCode::Char('a') => {
+                    self.mode = Mode::Input(String::new());
+                    self.status = match self.panel {
+                        Panel::Reminders => "New reminder, e.g. \"dentist friday at 3\". Enter to read it, Esc to cancel.",
+                        Panel::Todos => "New todo. Enter to add, Esc to cancel.",
+                    }
+                    .to_string();
+                }
+                KeyCode::Char('c') => self.status = self.complete_selected(now),
+                KeyCode::Char('d') => self.status = self.delete_selected(),
+                _ => {}
+            },
+        }
+        KeyOutcome::Continue
+    }
+
+    fn submit(&mut self, text: String) -> KeyOutcome {
+        match self.panel {
+            Panel::Reminders => {
+                self.status = "Reading the reminder…".to_string();
+                KeyOutcome::Extract(text)
+            }
+            Panel::Todos => {
+                self.status = match todo::create_todo(&mut self.todos, &self.user_id, &text) {
+                    Ok(_) => format!("Added \"{}\".", text),
+                    Err(err) => format!("Failed to save the todo: {}", err),
+                };
+                KeyOutcome::Continue
+            }
+        }
+    }
+
+    // The /notify extraction, minus the Discord prompt: the result waits in
+    // Mode::Confirm for y/n.
+    pub async fn draft_reminder(&mut self, openai: &dyn OpenAIClient, text: &str, now: DateTime<Utc>) {
+        let extracted = match openai.generate_prompt(text, "notification").await {
+            Ok(payload) => parse_model_json::<AINotification>(&payload)
+                .map_err(|err| format!("Couldn't read a reminder from that: {}", err)),
+            Err(err) => Err(format!("Failed to call OpenAI: {}", err)),
+        };
+        match extracted {
+            Ok(ai) => {
+                let draft = notification_normalizer::normalize(ai, text, now);
+                self.status = format!(
+                    "Remind you \"{}\" at {}? y to save, n to discard.",
+                    draft.content,
+                    format_time(draft.time)
+                );
+                self.mode = Mode::Confirm(draft);
+            }
+            Err(err) => self.status = err,
+        }
+    }
+
+    async fn save_reminder(&mut self, draft: NormalizedNotification) -> String {
+        let created = notification::create_notification(
+            &mut self.notifications,
+            &draft.content,
+            &self.user_id,
+            &draft.time,
+            &self.channel_id,
+            draft.recurrence,
+        )
+        .await;
+        let created = match created {
+            Ok(id) if !draft.offsets.is_empty() => {
+                notification::set_offsets(&mut self.notifications, &id, &draft.offsets).map(|_| id)
+            }
+            other => other,
+        };
+        match created {
+            Ok(_) => format!("Saved \"{}\".", draft.content),
+            Err(err) => format!("Failed to save the reminder: {}", err),
+        }
+    }
+
+    fn complete_selected(&mut self, now: DateTime<Utc>) -> String {
+        let Some(id) = self.selected_id() else {
+            return "Nothing selected.".to_string();
+        };
+        let saved = match self.panel {
+            Panel::Reminders => {
+                notification::complete_notification(&mut self.notifications, &id, &self.user_id, now).map(|_| ())
+            }
+            Panel::Todos => {
+                if let Some(item) = self.todos.get_mut(&id) {
+                    item.completed_at = Some(now);
+                }
+                save_db(&todo::get_db_location(), &self.todos)
+            }
+        };
+        self.clamp_selection();
+        match saved {
+            Ok(()) => "Completed.".to_string(),
+            Err(err) => format!("Failed to save: {}", err),
+        }
+    }
+
+    fn delete_selected(&mut self) -> String {
+        let Some(id) = self.selected_id() else {
+            return "Nothing selected.".to_string();
+        };
+        let deleted = match self.panel {
+            Panel::Reminders => notification::delete_notification(&mut self.notifications, &id),
+            Panel::Todos => todo::delete_todo(&mut self.todos, &id),
+        };
+        self.clamp_selection();
+        match deleted {
+            Ok(_) => "Deleted.".to_string(),
+            Err(err) => format!("Failed to save: {}", err),
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [panels, prompt] = Layout::vertical([Constraint::Min(3), Constraint::Length(3)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(panels);
+
+        let reminders: Vec<ListItem> = self
+            .reminders()
+            .into_iter()
+            .map(|n| {
+                let when = n
+                    .event_time
+                    .or_else(|| n.notification_times.first().copied())
+                    .map(format_time)
+                    .unwrap_or_default();
+                let paused = if n.paused { " [paused]" } else { "" };
+                ListItem::new(format!("{}  {}{}", when, n.content, paused))
+            })
+            .collect();
+        let todos: Vec<ListItem> = self
+            .open_todos()
+            .into_iter()
+            .map(|item| ListItem::new(item.content.clone()))
+            .collect();
+
+        let focused = |panel: Panel| {
+            if self.panel == panel { Style::new().bold() } else { Style::new().dim() }
+        };
+        let reminders = List::new(reminders)
+            .block(Block::bordered().title("Reminders").border_style(focused(Panel::Reminders)))
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        let todos = List::new(todos)
+            .block(Block::bordered().title("Todos").border_style(focused(Panel::Todos)))
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(reminders, left, &mut self.reminder_state);
+        frame.render_stateful_widget(todos, right, &mut self.todo_state);
+
+        let (title, body) = match &self.mode {
+            Mode::Input(text) => (self.status.as_str(), format!("{}▏", text)),
+            _ => ("", self.status.clone()),
+        };
+        frame.render_widget(Paragraph::new(body).block(Block::bordered().title(title)), prompt);
+    }
+}
+
+fn format_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&New_York).format("%a %b %-d, %-I:%M %p").to_string()
+}
+
+pub async fn run_tui(openai: &dyn OpenAIClient, user_id: String, channel_id: String) -> io::Result<()> {
+    let notifications: DB<Notification> =
+        load_db(&notification::get_db_location()).unwrap_or_else(|_| HashMap::new());
+    let todos: DB<TodoItem> = load_db(&todo::get_db_location()).unwrap_or_else(|_| HashMap::new());
+    let mut app = TuiApp::new(notifications, todos, user_id, channel_id);
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, openai).await;
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(terminal: &mut DefaultTerminal, app: &mut TuiApp, openai: &dyn OpenAIClient) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| app.render(frame))?;
+        // Polling blocks, so it's kept off the async worker threads.
+        let ready = tokio::task::block_in_place(|| event::poll(StdDuration::from_millis(250)))?;
+        if !ready {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match app.handle_key(key.code, Utc::now()).await {
+            KeyOutcome::Continue => {}
+            KeyOutcome::Quit => return Ok(()),
+            KeyOutcome::Extract(text) => {
+                terminal.draw(|frame| app.render(frame))?;
+                app.draft_reminder(openai, &text, Utc::now()).await;
+            }
+        }
+    }
+}
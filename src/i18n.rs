@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::models::settings::{GuildSettings, UserSettings};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+    De,
+    Fr,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 4] = [Locale::En, Locale::Es, Locale::De, Locale::Fr];
+
+    // Accepts bare language codes as well as Discord-style tags ("es-ES", "de").
+    pub fn from_code(code: &str) -> Option<Self> {
+        let lower = code.trim().to_lowercase();
+        let language = lower.split(['-', '_']).next().unwrap_or("");
+        match language {
+            "en" => Some(Locale::En),
+            "es" => Some(Locale::Es),
+            "de" => Some(Locale::De),
+            "fr" => Some(Locale::Fr),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+            Locale::De => "de",
+            Locale::Fr => "fr",
+        }
+    }
+
+    // For weekday and month names in dates written out for the user.
+    pub fn time_locale(&self) -> chrono::Locale {
+        match self {
+            Locale::En => chrono::Locale::en_US,
+            Locale::Es => chrono::Locale::es_ES,
+            Locale::De => chrono::Locale::de_DE,
+            Locale::Fr => chrono::Locale::fr_FR,
+        }
+    }
+
+    // User preference wins over the guild default; anything unset or
+    // unrecognised falls back to English.
+    pub fn resolve(user: Option<&UserSettings>, guild: Option<&GuildSettings>) -> Self {
+        user.and_then(|u| u.locale.as_deref())
+            .and_then(Locale::from_code)
+            .or_else(|| {
+                guild
+                    .and_then(|g| g.locale.as_deref())
+                    .and_then(Locale::from_code)
+            })
+            .unwrap_or_default()
+    }
+}
+
+// Names the entries of the catalogs under locales/.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum MessageKey {
+    NotifyMissingText,
+    NotifyProcessing,
+    ProgressInterpreting,
+    ProgressReady,
+    TodoProcessing,
+    CalendarEventProcessing,
+    NeedClarification,
+    ClarifyReminder,
+    ClarifyTodo,
+    ClarifyCalendarEvent,
+    ClarifyExisting,
+    ManyProcessing,
+    SkipRequested,
+    FindTimeRequested,
+    ListRequested,
+    CancelRequested,
+    SnoozeRequested,
+    WhichReminder,
+    SlotPickRequested,
+    ConversationCanceled,
+    ConversationGaveUp,
+    PauseRequested,
+    ResumeRequested,
+    RateLimited,
+    ProcessingRequest,
+    ContextModalTitle,
+    ContextModalLabel,
+    ContextModalPlaceholder,
+    ContextSubmitted,
+    LocaleUpdated,
+    LocaleUnsupported,
+    TimezoneUpdated,
+    TimezoneUnsupported,
+    StyleUpdated,
+    StyleUnknownCategory,
+    StyleInvalidColor,
+    StaleToTodoEnabled,
+    StaleToTodoDisabled,
+    StaleClarificationTodo,
+    IntegrationAllowed,
+    IntegrationRevoked,
+    VoiceUpdated,
+    VoiceUnsupported,
+    ChecklistSummary,
+    ChecklistComplete,
+    ChecklistItemMissing,
+    MustAckRequested,
+    EscalationChannelUpdated,
+    ApprovalReactionsOn,
+    ApprovalReactionsOff,
+    AgendaOn,
+    AgendaOff,
+    PushOn,
+    PushOff,
+    PushInvalid,
+    FeedUrl,
+    FeedOff,
+    SummaryAt,
+    SummaryDefault,
+    SummaryOff,
+    SummaryTimeInvalid,
+    QuietHoursOn,
+    QuietHoursOff,
+    QuietHoursInvalid,
+    QuietHoursDelayed,
+    LateDelivery,
+    MessageTitle,
+    Escalation,
+    EscalationNoBackup,
+    QueueFull,
+    ToolProcessing,
+    ImportProcessing,
+    ImportInvalidFile,
+    HistoryRequested,
+    GuildOnly,
+    ManageGuildRequired,
+    AdminRoleRequired,
+    SettingsFailed,
+    AliasSaved,
+    AliasRemoved,
+    AliasNotFound,
+    AliasInvalid,
+    AliasListEmpty,
+    AliasListHeader,
+    NotificationFallback,
+    NotificationFallbackAt,
+    NotificationFallbackTerse,
+    NotificationFallbackAtTerse,
+    ReminderDoneLabel,
+    ReminderDismissLabel,
+    ReminderDone,
+    ReminderDismissed,
+    TodoSummaryHeader,
+    AgendaHeader,
+    AgendaCalendar,
+    AgendaReminders,
+    AgendaTodos,
+    AgendaEmpty,
+    WeeklyDigestHeader,
+    WeeklyDigestDelivered,
+    WeeklyDigestCompleted,
+    WeeklyDigestOverdue,
+    WeeklyDigestUpcoming,
+    FocusOn,
+    FocusOff,
+    FocusInvalidDuration,
+    FocusDigestHeader,
+    NoSuchReminder,
+    ReminderSaveFailed,
+    NotRecurring,
+    AlreadyPaused,
+    NotPaused,
+    ReminderPaused,
+    ReminderResumed,
+    MustAckSet,
+    MustAckSetNoBackup,
+    TodoConfirmPrompt,
+    TodoConfirmButton,
+    TodoCanceled,
+    TodosAdded,
+    TodoSaveFailed,
+    ToolCanceled,
+    ImportCanceled,
+    SlotSearchCanceled,
+    NotificationCanceled,
+    HistoryEmpty,
+    HistoryHeader,
+    HistoryMore,
+    PushoverUnavailable,
+    AdminStats,
+    AdminFiringSoon,
+    AdminTasks,
+    AdminPurged,
+    AdminSaveFailed,
+    AdminRequeued,
+    AdminNoDeadLetters,
+    AdminNoSuchDeadLetter,
+    AdminDeadLetters,
+    AdminRetried,
+    DuplicateMerged,
+    DuplicateMergeFailed,
+    TimeInPast,
+    TimeTooFarAhead,
+    TimeDefaultFallback,
+    TimeUnsure,
+    TimeIssueWarning,
+    BusyDay,
+    BusyDayAlternatives,
+    MoveToDay,
+    ReminderNotYours,
+    SkipAmbiguous,
+    SkipNotFound,
+    Skipped,
+    SkippedWithNext,
+    ReminderGone,
+    CancelWhich,
+    SnoozeWhich,
+    ReminderNoMatch,
+    ReminderSeveralMatch,
+    ReminderCanceled,
+    ReminderCanceledForYou,
+    ReminderCancelFailed,
+    NothingToSnooze,
+    ReminderSnoozed,
+    FindTimeUnclear,
+    NoFreeSlot,
+    SlotBooked,
+    SlotBookedOnCalendar,
+    SlotBookedNotOnCalendar,
+    ImportNothingUpcoming,
+    ImportDone,
+    ImportFailed,
+    NoToolsAvailable,
+    NoToolForThat,
+    CalendarEventsUnavailable,
+}
+
+// One locale's replies, as read from its locales/<code>.toml file.
+pub type Catalog = HashMap<MessageKey, String>;
+
+pub fn parse_catalog(source: &str) -> Result<Catalog, String> {
+    toml::from_str(source).map_err(|e| e.to_string())
+}
+
+pub struct Catalogs(HashMap<Locale, Catalog>);
+
+impl Catalogs {
+    pub fn parse(sources: &[(Locale, &str)]) -> Result<Self, String> {
+        sources
+            .iter()
+            .map(|(locale, source)| {
+                parse_catalog(source)
+                    .map(|catalog| (*locale, catalog))
+                    .map_err(|err| format!("locales/{}.toml: {}", locale.code(), err))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    // Entries a locale leaves out come from English.
+    pub fn text(&self, locale: Locale, key: MessageKey) -> &str {
+        [locale, Locale::En]
+            .iter()
+            .find_map(|locale| self.0.get(locale)?.get(&key))
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+}
+
+static CATALOGS: LazyLock<Catalogs> = LazyLock::new(|| {
+    Catalogs::parse(&[
+        (Locale::En, include_str!("../locales/en.toml")),
+        (Locale::Es, include_str!("../locales/es.toml")),
+        (Locale::De, include_str!("../locales/de.toml")),
+        (Locale::Fr, include_str!("../locales/fr.toml")),
+    ])
+    .unwrap_or_else(|err| panic!("Invalid message catalog {}", err))
+});
+
+pub fn text(locale: Locale, key: MessageKey) -> &'static str {
+    CATALOGS.text(locale, key)
+}
+
+// Substitutes `{name}` placeholders in the catalog entry.
+pub fn render(locale: Locale, key: MessageKey, args: &[(&str, &str)]) -> String {
+    let mut out = text(locale, key).to_string();
+    for (name, value) in args {
+        out = out.replace(&format!("{{{}}}", name), value);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_catalogs_cover_the_same_keys() {
+        let english = parse_catalog(include_str!("../locales/en.toml")).unwrap();
+        for source in [
+            include_str!("../locales/es.toml"),
+            include_str!("../locales/de.toml"),
+            include_str!("../locales/fr.toml"),
+        ] {
+            let catalog = parse_catalog(source).unwrap();
+            let mut missing: Vec<_> = english.keys().filter(|key| !catalog.contains_key(key)).collect();
+            missing.sort_by_key(|key| format!("{:?}", key));
+            assert!(missing.is_empty(), "missing {:?}", missing);
+        }
+        assert_eq!(text(Locale::De, MessageKey::AgendaCalendar), "Kalender");
+    }
+
+    #[test]
+    fn missing_entries_fall_back_to_english() {
+        let catalogs = Catalogs::parse(&[
+            (Locale::En, "PushOff = \"No more push notifications.\"\nFocusOff = \"Focus mode off.\""),
+            (Locale::Fr, "FocusOff = \"Mode concentration terminé.\""),
+        ])
+        .unwrap();
+        assert_eq!(catalogs.text(Locale::Fr, MessageKey::FocusOff), "Mode concentration terminé.");
+        assert_eq!(catalogs.text(Locale::Fr, MessageKey::PushOff), "No more push notifications.");
+        assert_eq!(catalogs.text(Locale::Es, MessageKey::PushOff), "No more push notifications.");
+        assert_eq!(catalogs.text(Locale::Fr, MessageKey::QueueFull), "");
+
+        assert!(parse_catalog("NoSuchKey = \"x\"").is_err());
+    }
+}
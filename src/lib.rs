@@ -7,3 +7,9 @@ pub mod service;
 pub mod tasks;
 pub mod clients;
 pub mod runtime;
+pub mod i18n;
+pub mod logging;
+pub mod bot;
+pub mod config;
+pub mod tools;
+pub mod cli;
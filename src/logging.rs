@@ -0,0 +1,19 @@
+use tracing_subscriber::EnvFilter;
+
+// LOG_LEVEL takes an env-filter directive ("info", "reminderBot=debug,warn").
+// LOG_FORMAT is "pretty" (default), "compact", or "json" for production.
+pub fn init(level: Option<&str>, format: Option<&str>) {
+    let filter = EnvFilter::try_new(level.unwrap_or("info")).unwrap_or_else(|err| {
+        eprintln!("Invalid LOG_LEVEL, falling back to info: {}", err);
+        EnvFilter::new("info")
+    });
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    let result = match format.map(|f| f.trim().to_lowercase()).as_deref() {
+        Some("json") => builder.json().with_current_span(true).try_init(),
+        Some("compact") => builder.compact().try_init(),
+        _ => builder.try_init(),
+    };
+    if let Err(err) = result {
+        eprintln!("Logging already initialised: {}", err);
+    }
+}
@@ -8,45 +8,241 @@ mod runtime;
 mod tasks;
 mod events;
 mod config;
+mod i18n;
+mod logging;
+mod bot;
+mod tools;
+mod cli;
 
 use std::env;
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use memory_db::load_db;
 use memory_db::DB;
+use crate::bot::ReminderBot;
 use crate::models::notification;
 use crate::models::todo;
 use crate::config::AppConfig;
 
 #[tokio::main]
 async fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let [command, subcommand, rest @ ..] = args.as_slice() {
+        if command == "events" && subcommand == "tail" {
+            match events::trail::tail_command(rest) {
+                Ok(output) => println!("{}", output),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+    }
+
+    if let Some(output) = cli::command(&args).await {
+        match output {
+            Ok(output) => println!("{}", output),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let [command, rest @ ..] = args.as_slice() {
+        if command == "history" || command == "export" {
+            let output = if command == "history" {
+                handlers::action::history_command(rest)
+            } else {
+                service::ics::export_command(rest)
+            };
+            match output {
+                Ok(output) => println!("{}", output),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        if command == "watch" {
+            if let Err(err) = service::delivery_log::watch_command(rest).await {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+            return;
+        }
+        if command == "simulate" {
+            match handlers::simulate::simulate_command(rest).await {
+                Ok(output) => println!("{}", output),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        if command == "import" && !rest.iter().any(|arg| arg == "--file") {
+            match service::ics::import_command(rest).await {
+                Ok(output) => println!("{}", output),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+    }
+
     let config_path = env::var("CONFIG_FILE").unwrap_or_else(|_| "./config.properties".to_string());
     let config = AppConfig::from_file(&config_path).unwrap_or_default();
+    let shared_config = config.clone().shared();
 
     let get_prop = |key: &str| -> Option<String> {
         config.get(key).or_else(|| env::var(key).ok())
     };
 
-    let db: DB<notification::Notification> = load_db(&notification::get_db_location()).expect("Unable to load database.");
-    let shared_db = Arc::new(tokio::sync::Mutex::new(db));
-    let todo_db: DB<todo::TodoItem> =
-        load_db(&todo::get_db_location()).unwrap_or_else(|_| HashMap::new());
-    let shared_todo_db = Arc::new(tokio::sync::Mutex::new(todo_db));
-    if let Some(run_mode) = get_prop("RUN_MODE") {
-        if run_mode != "api" {
-            panic!("Unsupported RUN_MODE {}. Only api mode is supported.", run_mode);
+    if let [command, rest @ ..] = args.as_slice() {
+        if command == "import" {
+            let openai = get_prop("OPENAI_API_KEY")
+                .map(|key| service::openai_service::OpenAIService::new(key).with_config(shared_config.clone()));
+            let openai = openai.as_ref().map(|openai| openai as &dyn service::openai_service::OpenAIClient);
+            match cli::import_file_command(rest, openai, chrono::Utc::now()).await {
+                Ok(output) => println!("{}", output),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        if command == "route-eval" {
+            let openai_api_key = get_prop("OPENAI_API_KEY").unwrap_or_default();
+            let router = ReminderBot::new(String::new(), openai_api_key)
+                .with_config(shared_config.clone())
+                .intent_router();
+            match service::routing::eval::route_eval_command(rest, router).await {
+                Ok(output) => println!("{}", output),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        if command == "repl" || command == "prompt" {
+            let openai_api_key = get_prop("OPENAI_API_KEY")
+                .expect("OPENAI_API_KEY environment variable not set");
+            let openai: Arc<dyn service::openai_service::OpenAIClient> = Arc::new(
+                service::openai_service::OpenAIService::new(openai_api_key).with_config(shared_config.clone()),
+            );
+            let output = if command == "repl" {
+                let router = Arc::new(service::routing::OpenAIRouter::new(openai.clone()));
+                cli::repl_command(rest, openai, router).await
+            } else {
+                cli::prompt_command(rest, openai.as_ref(), chrono::Utc::now()).await
+            };
+            match output {
+                Ok(output) => println!("{}", output),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
         }
     }
 
+    logging::init(get_prop("LOG_LEVEL").as_deref(), get_prop("LOG_FORMAT").as_deref());
+    config.log_problems();
+
+    if let [command, subcommand, ..] = args.as_slice() {
+        if command == "calendar" && subcommand == "auth" {
+            let authorized = match config.get("CALENDAR_PROVIDER").as_deref().map(str::trim) {
+                Some("outlook") => clients::outlook_calendar::auth_command(&config).await,
+                _ => clients::google_calendar::auth_command(&config).await,
+            };
+            match authorized {
+                Ok(output) => println!("{}", output),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+    }
+
+    let run_mode = get_prop("RUN_MODE").unwrap_or_else(|| "api".to_string());
+    let modes = runtime::RunModes::parse(&run_mode).unwrap_or_else(|err| panic!("{}", err));
+    let http_addr: Option<SocketAddr> = modes.http.then(|| {
+        let bind = get_prop("HTTP_BIND").unwrap_or_else(|| "127.0.0.1:8080".to_string());
+        bind.parse()
+            .unwrap_or_else(|_| panic!("Invalid HTTP_BIND address {}", bind))
+    });
+    if modes.tui {
+        let openai_api_key = get_prop("OPENAI_API_KEY")
+            .expect("OPENAI_API_KEY environment variable not set");
+        let user_id = get_prop("TUI_USER_ID").expect("TUI_USER_ID must be set for RUN_MODE=tui");
+        let channel_id = get_prop("TUI_CHANNEL_ID").expect("TUI_CHANNEL_ID must be set for RUN_MODE=tui");
+        let openai = service::openai_service::OpenAIService::new(openai_api_key).with_config(shared_config);
+        if let Err(err) = handlers::tui::run_tui(&openai, user_id, channel_id).await {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if !modes.gateway && !modes.worker {
+        if let Some(addr) = http_addr {
+            let db: DB<notification::Notification> = load_db(&notification::get_db_location()).expect("Unable to load database.");
+            let todo_db: DB<todo::TodoItem> =
+                load_db(&todo::get_db_location()).unwrap_or_else(|_| HashMap::new());
+            runtime::run_http(
+                Arc::new(tokio::sync::Mutex::new(db)),
+                Arc::new(tokio::sync::Mutex::new(todo_db)),
+                addr,
+            )
+            .await;
+        }
+        return;
+    }
+
     let discord_client_secret = get_prop("DISCORD_CLIENT_SECRET")
         .expect("DISCORD_CLIENT_SECRET must be set for bot mode");
     let openai_api_key = get_prop("OPENAI_API_KEY")
         .expect("OPENAI_API_KEY environment variable not set");
-    runtime::run_api(
-        shared_db.clone(),
-        shared_todo_db.clone(),
-        discord_client_secret,
-        openai_api_key,
-    )
-    .await;
+    // Held for the life of the process; dropping the watcher stops reloads.
+    let _config_watcher = match crate::config::watch(&config_path, shared_config.clone()) {
+        Ok(watcher) => Some(watcher),
+        Err(err) => {
+            tracing::warn!(error = %err, "config hot reload disabled");
+            None
+        }
+    };
+    let mut bot = ReminderBot::new(discord_client_secret, openai_api_key)
+        .with_modes(modes)
+        .with_config(shared_config);
+    if let Some(addr) = http_addr {
+        bot = bot.with_http(addr);
+    }
+    if let Some(role_id) = get_prop("ADMIN_ROLE_ID") {
+        bot = bot.with_admin_role(role_id);
+    }
+    if let Some(token) = get_prop("WEBHOOK_TOKEN") {
+        let bind = get_prop("WEBHOOK_BIND").unwrap_or_else(|| "127.0.0.1:8081".to_string());
+        let addr = bind
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid WEBHOOK_BIND address {}", bind));
+        bot = bot.with_webhook(handlers::http::WebhookConfig { addr, token });
+    }
+    if let Some(bind) = get_prop("FEED_BIND") {
+        let addr = bind
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid FEED_BIND address {}", bind));
+        bot = bot.with_feeds(addr);
+    }
+    bot.run().await;
 }
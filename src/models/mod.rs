@@ -1,2 +1,4 @@
 pub mod notification;
+pub mod recurrence;
+pub mod settings;
 pub mod todo;
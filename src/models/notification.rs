@@ -8,6 +8,8 @@ use memory_db::DBError;
 use uuid::Uuid;
 use std::env;
 
+use crate::models::recurrence::Recurrence;
+
 // Returns the directory where DB + backups live.
 // Defaults to a relative "./data" directory.
 pub fn get_db_location() -> String {
@@ -21,12 +23,222 @@ pub struct Notification {
     pub notify: Vec<String>,
     pub notification_times: Vec<DateTime<Utc>>,
     pub channel: String,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+    // The time the reminder is about; notification_times are pings leading up to it.
+    #[serde(default)]
+    pub event_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    // Paused reminders keep their schedule but are not delivered.
+    #[serde(default)]
+    pub paused: bool,
+    // Things to tick off before the event, shown as buttons on delivery.
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    // Set for must-ack reminders: escalated if nobody acknowledges the final ping.
+    #[serde(default)]
+    pub ack: Option<AckRequirement>,
+    // The next ping was pushed back to the end of the owner's quiet hours;
+    // its message says so.
+    #[serde(default)]
+    pub quiet_delayed: bool,
+    // Where else each ping goes besides `channel`.
+    #[serde(default)]
+    pub targets: Vec<DeliveryTarget>,
+    // Set while the due ping keeps failing to go out; cleared once it does.
+    #[serde(default)]
+    pub delivery_retry: Option<DeliveryRetry>,
+}
+
+// Failed attempts at the current ping and when the next one is due. Waits
+// double from a minute, so MAX_DELIVERY_ATTEMPTS covers about an hour.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DeliveryRetry {
+    pub attempts: u32,
+    pub next_attempt: DateTime<Utc>,
+    pub last_error: String,
+}
+
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 6;
+
+impl DeliveryRetry {
+    pub fn after_failure(previous: Option<&DeliveryRetry>, error: &str, now: DateTime<Utc>) -> Self {
+        let attempts = previous.map_or(0, |retry| retry.attempts) + 1;
+        Self {
+            attempts,
+            next_attempt: now + Duration::minutes(1i64 << (attempts - 1).min(10)),
+            last_error: error.to_string(),
+        }
+    }
+}
+
+impl Notification {
+    // `channel` first, then the extra targets, without repeats.
+    pub fn delivery_targets(&self) -> Vec<DeliveryTarget> {
+        let mut targets = vec![DeliveryTarget::Channel(self.channel.clone())];
+        for target in &self.targets {
+            if !targets.contains(target) {
+                targets.push(target.clone());
+            }
+        }
+        targets
+    }
+}
+
+// One place a ping is delivered, e.g. {"kind": "email", "to": "a@b.c"}.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", content = "to", rename_all = "snake_case")]
+pub enum DeliveryTarget {
+    // A Discord channel id.
+    Channel(String),
+    // A Discord user id, with or without the "@".
+    Dm(String),
+    Email(String),
+    // An https URL the ping is POSTed to as JSON.
+    Webhook(String),
+    // An ntfy topic on NTFY_SERVER.
+    Ntfy(String),
+    // A Pushover user key, sent with PUSHOVER_APP_TOKEN.
+    Pushover(String),
+}
+
+impl DeliveryTarget {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DeliveryTarget::Channel(_) => "channel",
+            DeliveryTarget::Dm(_) => "dm",
+            DeliveryTarget::Email(_) => "email",
+            DeliveryTarget::Webhook(_) => "webhook",
+            DeliveryTarget::Ntfy(_) => "ntfy",
+            DeliveryTarget::Pushover(_) => "pushover",
+        }
+    }
+
+    // Push targets a user can pick in /settings push.
+    pub fn is_push(&self) -> bool {
+        matches!(self, DeliveryTarget::Ntfy(_) | DeliveryTarget::Pushover(_))
+    }
+
+    pub fn address(&self) -> &str {
+        match self {
+            DeliveryTarget::Channel(to)
+            | DeliveryTarget::Dm(to)
+            | DeliveryTarget::Email(to)
+            | DeliveryTarget::Webhook(to)
+            | DeliveryTarget::Ntfy(to)
+            | DeliveryTarget::Pushover(to) => to,
+        }
+    }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+// Two rows of five buttons, leaving room for the done/dismiss row.
+pub const MAX_CHECKLIST_ITEMS: usize = 10;
+
+// How long a must-ack reminder waits after its final ping before escalating.
+pub const DEFAULT_ACK_WINDOW_MINUTES: i64 = 30;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AckRequirement {
+    // Who gets tagged on escalation, e.g. "@alex".
+    #[serde(default)]
+    pub backup: Option<String>,
+    #[serde(default)]
+    pub window_minutes: Option<i64>,
+    #[serde(default)]
+    pub state: AckState,
+}
+
+impl AckRequirement {
+    pub fn window(&self) -> Duration {
+        Duration::minutes(self.window_minutes.unwrap_or(DEFAULT_ACK_WINDOW_MINUTES).max(1))
+    }
+}
+
+// Pending until the final ping goes out, then Awaiting until someone presses
+// done/dismiss or the window runs out. Recurring reminders start over on
+// each occurrence's final ping.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum AckState {
+    #[default]
+    Pending,
+    Awaiting { since: DateTime<Utc> },
+    Acknowledged { at: DateTime<Utc> },
+    Escalated { at: DateTime<Utc> },
+}
+
+// What the extraction prompts return. Only content and time are required;
+// everything else is optional so older prompts and partial answers still parse.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AINotification {
     pub content: String,
-    pub time: DateTime<Utc>
+    pub time: DateTime<Utc>,
+    // Recurrence phrase as the user said it, e.g. "every Friday until March".
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    // Who should be reminded, e.g. "me" or "@alex".
+    #[serde(default)]
+    pub target: Option<String>,
+    // Minutes before the event to ping; empty means the default schedule.
+    #[serde(default)]
+    pub offsets: Vec<i64>,
+    // The time expression from the message, e.g. "next Friday at 5".
+    #[serde(default)]
+    pub original_phrase: Option<String>,
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    // Items the user listed to bring or do, e.g. a packing list.
+    #[serde(default)]
+    pub checklist: Vec<String>,
+}
+
+// The pings sent ahead of an event: one day and one hour before it.
+pub fn reminder_times(event_time: &DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let mut notification_times: Vec<DateTime<Utc>> = Vec::new();
+    if let Some(one_hour_before) = event_time.checked_sub_signed(Duration::hours(1)) {
+        notification_times.push(one_hour_before);
+    }
+    if let Some(one_day_before) = event_time.checked_sub_signed(Duration::days(1)) {
+        notification_times.push(one_day_before);
+    }
+    notification_times.sort();
+    notification_times
+}
+
+// The pings for an event `offsets` minutes before it, or the default ones
+// when there are none.
+pub fn ping_times(event_time: &DateTime<Utc>, offsets: &[i64]) -> Vec<DateTime<Utc>> {
+    if offsets.is_empty() {
+        return reminder_times(event_time);
+    }
+    let mut notification_times: Vec<DateTime<Utc>> = offsets
+        .iter()
+        .filter_map(|minutes| event_time.checked_sub_signed(Duration::minutes(*minutes)))
+        .collect();
+    notification_times.sort();
+    notification_times.dedup();
+    notification_times
+}
+
+// Moves a reminder to `at` with the same pings as a new one, minus the ones
+// already in the past.
+pub fn reschedule(notification: &mut Notification, at: DateTime<Utc>, now: DateTime<Utc>) {
+    notification.event_time = Some(at);
+    notification.notification_times = reminder_times(&at)
+        .into_iter()
+        .filter(|ping| *ping > now)
+        .collect();
+    notification.completed_at = None;
 }
 
 pub async fn create_notification(
@@ -35,17 +247,11 @@ pub async fn create_notification(
     notify_users: &String,
     expires_at: &DateTime<Utc>,
     channel: &String,
-) -> Result<(), DBError> {
+    recurrence: Option<Recurrence>,
+) -> Result<String, DBError> {
     let users: Vec<String> = notify_users.split(",").map(|user| {user.to_string()}).collect();
     let id = Uuid::new_v4().to_string();
-    let mut notification_times: Vec<DateTime<Utc>> = Vec::new();
-    if let Some(one_hour_before) = expires_at.checked_sub_signed(Duration::hours(1)) {
-        notification_times.push(one_hour_before);
-    }
-    if let Some(one_day_before) = expires_at.checked_sub_signed(Duration::days(1)) {
-        notification_times.push(one_day_before);
-    }
-    notification_times.sort();
+    let notification_times = reminder_times(expires_at);
     db.insert(
         id.clone(),
         Notification {
@@ -54,7 +260,299 @@ pub async fn create_notification(
             notify: users,
             notification_times: notification_times,
             channel: channel.to_string(),
+            completed_at: None,
+            event_time: Some(*expires_at),
+            recurrence,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
         },
     );
-    save_db(&get_db_location(), db)
+    save_db(&get_db_location(), db)?;
+    Ok(id)
+}
+
+// Creates a reminder for each (content, time) or none of them: on a failed
+// save the new ones are taken back out, so confirming again doesn't
+// duplicate the ones that got in.
+pub fn create_notifications(
+    db: &mut DB<Notification>,
+    user_id: &str,
+    channel: &str,
+    items: &[(String, DateTime<Utc>)],
+) -> Result<Vec<String>, DBError> {
+    let ids: Vec<String> = items.iter().map(|_| Uuid::new_v4().to_string()).collect();
+    for (id, (content, at)) in ids.iter().zip(items) {
+        db.insert(
+            id.clone(),
+            Notification {
+                id: id.clone(),
+                content: content.clone(),
+                notify: vec![user_id.to_string()],
+                notification_times: reminder_times(at),
+                channel: channel.to_string(),
+                completed_at: None,
+                event_time: Some(*at),
+                recurrence: None,
+                paused: false,
+                checklist: Vec::new(),
+                ack: None,
+                quiet_delayed: false,
+                targets: Vec::new(),
+                delivery_retry: None,
+            },
+        );
+    }
+    if let Err(err) = save_db(&get_db_location(), db) {
+        for id in &ids {
+            db.remove(id);
+        }
+        return Err(err);
+    }
+    Ok(ids)
+}
+
+// Replaces a notification's checklist, dropping blank items and anything past
+// MAX_CHECKLIST_ITEMS. Returns whether the notification exists.
+pub fn set_checklist(db: &mut DB<Notification>, id: &str, items: &[String]) -> Result<bool, DBError> {
+    let Some(notification) = db.get_mut(id) else {
+        return Ok(false);
+    };
+    notification.checklist = items
+        .iter()
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .take(MAX_CHECKLIST_ITEMS)
+        .map(|item| ChecklistItem {
+            text: item.to_string(),
+            done: false,
+        })
+        .collect();
+    save_db(&get_db_location(), db)?;
+    Ok(true)
+}
+
+// Replaces the default pings with ones `offsets` minutes before the event.
+// Returns whether the notification exists.
+pub fn set_offsets(db: &mut DB<Notification>, id: &str, offsets: &[i64]) -> Result<bool, DBError> {
+    let Some(notification) = db.get_mut(id) else {
+        return Ok(false);
+    };
+    if let Some(event_time) = notification.event_time {
+        notification.notification_times = ping_times(&event_time, offsets);
+    }
+    save_db(&get_db_location(), db)?;
+    Ok(true)
+}
+
+// Replaces the extra delivery targets. Returns whether the notification
+// exists.
+pub fn set_targets(db: &mut DB<Notification>, id: &str, targets: Vec<DeliveryTarget>) -> Result<bool, DBError> {
+    let Some(notification) = db.get_mut(id) else {
+        return Ok(false);
+    };
+    notification.targets = targets;
+    save_db(&get_db_location(), db)?;
+    Ok(true)
+}
+
+// Flips one checklist item for a user the notification targets. Returns the
+// item's new state, or None when the notification, user or item doesn't match.
+pub fn toggle_checklist_item(
+    db: &mut DB<Notification>,
+    id: &str,
+    user_id: &str,
+    index: usize,
+) -> Result<Option<bool>, DBError> {
+    let Some(notification) = db.get_mut(id) else {
+        return Ok(None);
+    };
+    if !notification.notify.iter().any(|user| user == user_id) {
+        return Ok(None);
+    }
+    let Some(item) = notification.checklist.get_mut(index) else {
+        return Ok(None);
+    };
+    item.done = !item.done;
+    let done = item.done;
+    save_db(&get_db_location(), db)?;
+    Ok(Some(done))
+}
+
+pub fn checklist_complete(notification: &Notification) -> bool {
+    !notification.checklist.is_empty() && notification.checklist.iter().all(|item| item.done)
+}
+
+// Marks a reminder as must-ack. Returns its content, or None when it
+// doesn't exist.
+pub fn require_ack(
+    db: &mut DB<Notification>,
+    id: &str,
+    backup: Option<String>,
+    window_minutes: Option<i64>,
+) -> Result<Option<String>, DBError> {
+    let Some(notification) = db.get_mut(id) else {
+        return Ok(None);
+    };
+    notification.ack = Some(AckRequirement {
+        backup,
+        window_minutes,
+        state: AckState::Pending,
+    });
+    let content = notification.content.clone();
+    save_db(&get_db_location(), db)?;
+    Ok(Some(content))
+}
+
+pub fn delete_notification(db: &mut DB<Notification>, id: &str) -> Result<bool, DBError> {
+    if db.remove(id).is_none() {
+        return Ok(false);
+    }
+    save_db(&get_db_location(), db)?;
+    Ok(true)
+}
+
+// Moves a recurring notification on to its next occurrence once the current
+// one has been fully delivered. Returns false when there is nothing left to
+// schedule and the notification should expire.
+pub fn advance_recurrence(notification: &mut Notification, now: DateTime<Utc>) -> bool {
+    move_to_next_occurrence(notification, now, true)
+}
+
+// With `consume`, every occurrence moved past counts against the cap,
+// delivered or not; without it the cap is left alone.
+fn move_to_next_occurrence(notification: &mut Notification, now: DateTime<Utc>, consume: bool) -> bool {
+    let (Some(recurrence), Some(event_time)) = (&mut notification.recurrence, notification.event_time) else {
+        return false;
+    };
+    let mut next = recurrence.next_occurrence(event_time);
+    while let Some(candidate) = next {
+        if consume {
+            match recurrence.count {
+                Some(left) if left <= 1 => return false,
+                Some(left) => recurrence.count = Some(left - 1),
+                None => {}
+            }
+        }
+        let times: Vec<DateTime<Utc>> = reminder_times(&candidate)
+            .into_iter()
+            .filter(|t| *t > now)
+            .collect();
+        if !times.is_empty() {
+            notification.event_time = Some(candidate);
+            notification.notification_times = times;
+            return true;
+        }
+        next = recurrence.next_occurrence(candidate);
+    }
+    false
+}
+
+// Skips the upcoming occurrence of a recurring notification, recording the
+// skipped date on the recurrence. Returns the skipped event time.
+pub fn skip_next_occurrence(
+    notification: &mut Notification,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let upcoming = notification.event_time?;
+    let recurrence = notification.recurrence.as_mut()?;
+    recurrence.skip_date(upcoming.with_timezone(&chrono_tz::America::New_York).date_naive());
+    if !advance_recurrence(notification, now) {
+        notification.notification_times.clear();
+    }
+    Some(upcoming)
+}
+
+// Pushes the pings still to come back by `by`. Only a one-off reminder's
+// event time moves with them; a recurring one keeps its schedule, so just
+// this occurrence is late. Returns the new first ping, or None when nothing
+// is left to send.
+pub fn snooze_notification(notification: &mut Notification, by: Duration) -> Option<DateTime<Utc>> {
+    if notification.notification_times.is_empty() {
+        return None;
+    }
+    for time in notification.notification_times.iter_mut() {
+        *time += by;
+    }
+    if notification.recurrence.is_none() {
+        notification.event_time = notification.event_time.map(|time| time + by);
+    }
+    notification.notification_times.iter().min().copied()
+}
+
+// Drops the pings a paused reminder has slept through, moving a recurring one
+// on to its next occurrence. Occurrences missed while paused don't count
+// against the recurrence's cap, and the reminder is kept even once nothing
+// is left to schedule, so resuming it is still possible.
+pub fn skip_missed_while_paused(notification: &mut Notification, now: DateTime<Utc>) {
+    notification.notification_times.retain(|t| *t >= now);
+    if notification.notification_times.is_empty() {
+        move_to_next_occurrence(notification, now, false);
+    }
+}
+
+// Clears any remaining notification times so the reminder stops firing.
+// Only users the notification targets may complete it; returns whether the
+// notification was found and updated.
+pub fn complete_notification(
+    db: &mut DB<Notification>,
+    id: &str,
+    user_id: &str,
+    now: DateTime<Utc>,
+) -> Result<bool, DBError> {
+    let Some(notification) = db.get_mut(id) else {
+        return Ok(false);
+    };
+    if !notification.notify.iter().any(|user| user == user_id) {
+        return Ok(false);
+    }
+    // The occurrence has already been delivered in full; pressing done on its
+    // last ping just acknowledges it.
+    if let Some(ack) = notification.ack.as_mut() {
+        if matches!(ack.state, AckState::Awaiting { .. }) {
+            ack.state = AckState::Acknowledged { at: now };
+            save_db(&get_db_location(), db)?;
+            return Ok(true);
+        }
+    }
+    // Completing a recurring reminder only finishes the current occurrence.
+    if !advance_recurrence(notification, now) {
+        notification.notification_times.clear();
+        notification.completed_at = Some(now);
+    }
+    save_db(&get_db_location(), db)?;
+    Ok(true)
+}
+
+// Stops the reminder for one of its recipients; the others keep getting it.
+// Once nobody is left it is finished.
+pub fn dismiss_notification(
+    db: &mut DB<Notification>,
+    id: &str,
+    user_id: &str,
+    now: DateTime<Utc>,
+) -> Result<bool, DBError> {
+    let Some(notification) = db.get_mut(id) else {
+        return Ok(false);
+    };
+    let before = notification.notify.len();
+    notification.notify.retain(|user| user != user_id);
+    if notification.notify.len() == before {
+        return Ok(false);
+    }
+    // Pressing either button on a must-ack ping counts as seeing it.
+    if let Some(ack) = notification.ack.as_mut() {
+        if matches!(ack.state, AckState::Awaiting { .. }) {
+            ack.state = AckState::Acknowledged { at: now };
+        }
+    }
+    if notification.notify.is_empty() {
+        notification.notification_times.clear();
+        notification.completed_at = Some(now);
+    }
+    save_db(&get_db_location(), db)?;
+    Ok(true)
 }
@@ -0,0 +1,432 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, TimeZone, Utc, Weekday};
+use chrono_tz::America::New_York;
+use serde::{Deserialize, Serialize};
+
+// Upper bound on consecutive skipped occurrences before giving up, so a skip
+// list covering every future date cannot loop forever.
+const MAX_SKIPPED_OCCURRENCES: usize = 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    // Local (America/New_York) dates on which an occurrence is skipped.
+    #[serde(default)]
+    pub skip_dates: Vec<NaiveDate>,
+    #[serde(default)]
+    pub skip_holidays: bool,
+    // Last local date an occurrence may fall on.
+    #[serde(default)]
+    pub until: Option<NaiveDate>,
+    // Occurrences left, counting the upcoming one.
+    #[serde(default)]
+    pub count: Option<u32>,
+}
+
+impl Recurrence {
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            skip_dates: Vec::new(),
+            skip_holidays: false,
+            until: None,
+            count: None,
+        }
+    }
+
+    fn step(&self, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = from.with_timezone(&New_York).naive_local();
+        let interval = self.interval.max(1);
+        let next = match self.frequency {
+            Frequency::Daily => local.checked_add_signed(Duration::days(interval as i64))?,
+            Frequency::Weekly => local.checked_add_signed(Duration::weeks(interval as i64))?,
+            Frequency::Monthly => local.checked_add_months(Months::new(interval))?,
+        };
+        New_York
+            .from_local_datetime(&next)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+
+    pub fn is_skipped(&self, at: DateTime<Utc>) -> bool {
+        let date = at.with_timezone(&New_York).date_naive();
+        self.skip_dates.contains(&date) || (self.skip_holidays && is_us_holiday(date))
+    }
+
+    fn is_past_end(&self, at: DateTime<Utc>) -> bool {
+        self.until
+            .is_some_and(|until| at.with_timezone(&New_York).date_naive() > until)
+    }
+
+    // The first non-skipped occurrence strictly after `previous`, or None once
+    // the recurrence has run past its end date.
+    pub fn next_occurrence(&self, previous: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut candidate = self.step(previous)?;
+        for _ in 0..MAX_SKIPPED_OCCURRENCES {
+            if self.is_past_end(candidate) {
+                return None;
+            }
+            if !self.is_skipped(candidate) {
+                return Some(candidate);
+            }
+            candidate = self.step(candidate)?;
+        }
+        None
+    }
+
+    pub fn skip_date(&mut self, date: NaiveDate) {
+        if !self.skip_dates.contains(&date) {
+            self.skip_dates.push(date);
+            self.skip_dates.sort();
+        }
+    }
+
+    // Skips every occurrence that falls in the calendar week after `now`'s week.
+    pub fn skip_next_week(&mut self, now: DateTime<Utc>) {
+        let today = now.with_timezone(&New_York).date_naive();
+        let days_to_monday = 7 - today.weekday().num_days_from_monday() as i64;
+        let next_monday = today + Duration::days(days_to_monday);
+        for offset in 0..7 {
+            self.skip_date(next_monday + Duration::days(offset));
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let unit = match self.frequency {
+            Frequency::Daily => "day",
+            Frequency::Weekly => "week",
+            Frequency::Monthly => "month",
+        };
+        let mut out = if self.interval <= 1 {
+            format!("every {}", unit)
+        } else {
+            format!("every {} {}s", self.interval, unit)
+        };
+        if self.skip_holidays {
+            out.push_str(", except holidays");
+        }
+        if !self.skip_dates.is_empty() {
+            let dates = self
+                .skip_dates
+                .iter()
+                .map(|d| d.format("%b %-d").to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            out.push_str(&format!(", skipping {}", dates));
+        }
+        if let Some(until) = self.until {
+            out.push_str(&format!(", until {}", until.format("%b %-d, %Y")));
+        }
+        match self.count {
+            Some(1) => out.push_str(", 1 occurrence left"),
+            Some(n) => out.push_str(&format!(", {} occurrences left", n)),
+            None => {}
+        }
+        out
+    }
+}
+
+// Recognises the common English recurrence phrasings ("every day", "weekly",
+// "every Friday", "every 2 weeks", "monthly") plus exception phrases
+// ("except holidays", "skip next week").
+pub fn parse_recurrence(text: &str, now: DateTime<Utc>) -> Option<Recurrence> {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let mut recurrence = None;
+    for (idx, word) in words.iter().enumerate() {
+        let found = match *word {
+            "daily" => Some(Recurrence::new(Frequency::Daily)),
+            "weekly" => Some(Recurrence::new(Frequency::Weekly)),
+            "monthly" => Some(Recurrence::new(Frequency::Monthly)),
+            "every" | "each" => parse_every(&words[idx + 1..]),
+            _ => None,
+        };
+        if found.is_some() {
+            recurrence = found;
+            break;
+        }
+    }
+
+    let mut recurrence = recurrence?;
+    apply_exception_phrases(&mut recurrence, &lower, now);
+    apply_end_phrases(&mut recurrence, &words, now);
+    Some(recurrence)
+}
+
+fn parse_every(rest: &[&str]) -> Option<Recurrence> {
+    let (interval, rest) = match rest.first().and_then(|w| parse_count(w)) {
+        Some(n) => (n, &rest[1..]),
+        None => (1, rest),
+    };
+    let unit = rest.first()?;
+    let frequency = match unit.trim_end_matches('s') {
+        "day" | "morning" | "evening" | "night" => Frequency::Daily,
+        "week" => Frequency::Weekly,
+        "month" => Frequency::Monthly,
+        other if parse_weekday(other).is_some() => Frequency::Weekly,
+        _ => return None,
+    };
+    let mut recurrence = Recurrence::new(frequency);
+    recurrence.interval = interval;
+    Some(recurrence)
+}
+
+fn parse_count(word: &str) -> Option<u32> {
+    if let Ok(n) = word.parse::<u32>() {
+        return (n > 0).then_some(n);
+    }
+    match word {
+        "other" | "two" => Some(2),
+        "three" => Some(3),
+        "four" => Some(4),
+        _ => None,
+    }
+}
+
+fn parse_month(word: &str) -> Option<u32> {
+    let month = match word {
+        "january" | "jan" => 1,
+        "february" | "feb" => 2,
+        "march" | "mar" => 3,
+        "april" | "apr" => 4,
+        "may" => 5,
+        "june" | "jun" => 6,
+        "july" | "jul" => 7,
+        "august" | "aug" => 8,
+        "september" | "sep" | "sept" => 9,
+        "october" | "oct" => 10,
+        "november" | "nov" => 11,
+        "december" | "dec" => 12,
+        _ => return None,
+    };
+    Some(month)
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" => Some(Weekday::Mon),
+        "tuesday" => Some(Weekday::Tue),
+        "wednesday" => Some(Weekday::Wed),
+        "thursday" => Some(Weekday::Thu),
+        "friday" => Some(Weekday::Fri),
+        "saturday" => Some(Weekday::Sat),
+        "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+pub fn apply_exception_phrases(recurrence: &mut Recurrence, lower: &str, now: DateTime<Utc>) {
+    if lower.contains("except holidays")
+        || lower.contains("except on holidays")
+        || lower.contains("skip holidays")
+        || lower.contains("not on holidays")
+    {
+        recurrence.skip_holidays = true;
+    }
+    if lower.contains("skip next week") || lower.contains("except next week") {
+        recurrence.skip_next_week(now);
+    }
+}
+
+// End conditions: "until March", "until March 15", "until 2026-03-15",
+// "for the next 6 weeks", "for 3 months", "5 times".
+pub fn apply_end_phrases(recurrence: &mut Recurrence, words: &[&str], now: DateTime<Utc>) {
+    let today = now.with_timezone(&New_York).date_naive();
+    for (idx, word) in words.iter().enumerate() {
+        match *word {
+            "until" | "till" | "through" => {
+                if let Some(until) = parse_until(&words[idx + 1..], today) {
+                    recurrence.until = Some(until);
+                }
+            }
+            "for" => {
+                let mut rest = &words[idx + 1..];
+                while matches!(rest.first(), Some(&"the") | Some(&"next")) {
+                    rest = &rest[1..];
+                }
+                let Some(n) = rest.first().and_then(|w| parse_count(w)) else {
+                    continue;
+                };
+                let Some(unit) = rest.get(1) else {
+                    continue;
+                };
+                apply_span(recurrence, n, unit, today);
+            }
+            "times" | "occurrences" if idx > 0 => {
+                if let Some(n) = parse_count(words[idx - 1]) {
+                    recurrence.count = Some(n);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_until(rest: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    let first = rest.first()?;
+    if let Ok(year) = first.parse::<i32>() {
+        if year >= 1000 {
+            let month = rest.get(1)?.parse::<u32>().ok()?;
+            let day = rest.get(2)?.parse::<u32>().ok()?;
+            return NaiveDate::from_ymd_opt(year, month, day);
+        }
+    }
+    let month = parse_month(first)?;
+    let day = rest
+        .get(1)
+        .and_then(|w| w.trim_end_matches(|c: char| c.is_alphabetic()).parse::<u32>().ok());
+    let mut year = today.year();
+    let resolve = |year: i32| match day {
+        Some(day) => NaiveDate::from_ymd_opt(year, month, day),
+        None => last_day_of_month(year, month),
+    };
+    if resolve(year)? < today {
+        year += 1;
+    }
+    resolve(year)
+}
+
+fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)?.pred_opt()
+}
+
+// "for N <unit>": a count when the unit matches the recurrence (for the next
+// 6 weeks of a weekly reminder), otherwise an end date N units from today.
+fn apply_span(recurrence: &mut Recurrence, n: u32, unit: &str, today: NaiveDate) {
+    let unit = unit.trim_end_matches('s');
+    if unit == "time" || unit == "occurrence" {
+        recurrence.count = Some(n);
+        return;
+    }
+    let frequency = match unit {
+        "day" => Frequency::Daily,
+        "week" => Frequency::Weekly,
+        "month" => Frequency::Monthly,
+        _ => return,
+    };
+    if frequency == recurrence.frequency {
+        let interval = recurrence.interval.max(1);
+        recurrence.count = Some(n.div_ceil(interval));
+        return;
+    }
+    let end = match frequency {
+        Frequency::Daily => today.checked_add_signed(Duration::days(n as i64)),
+        Frequency::Weekly => today.checked_add_signed(Duration::weeks(n as i64)),
+        Frequency::Monthly => today.checked_add_months(Months::new(n)),
+    };
+    recurrence.until = end.and_then(|end| end.pred_opt());
+}
+
+// US federal holidays, which is what "except holidays" means for the
+// America/New_York users this bot currently assumes.
+pub fn is_us_holiday(date: NaiveDate) -> bool {
+    let year = date.year();
+    let fixed = [(1, 1), (6, 19), (7, 4), (11, 11), (12, 25)];
+    if fixed
+        .iter()
+        .any(|(m, d)| date.month() == *m && date.day() == *d)
+    {
+        return true;
+    }
+    let floating = [
+        NaiveDate::from_weekday_of_month_opt(year, 1, Weekday::Mon, 3),
+        NaiveDate::from_weekday_of_month_opt(year, 2, Weekday::Mon, 3),
+        last_weekday_of_month(year, 5, Weekday::Mon),
+        NaiveDate::from_weekday_of_month_opt(year, 9, Weekday::Mon, 1),
+        NaiveDate::from_weekday_of_month_opt(year, 10, Weekday::Mon, 2),
+        NaiveDate::from_weekday_of_month_opt(year, 11, Weekday::Thu, 4),
+    ];
+    floating.iter().flatten().any(|holiday| *holiday == date)
+}
+
+fn last_weekday_of_month(year: i32, month: u32, weekday: Weekday) -> Option<NaiveDate> {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .or_else(|| NaiveDate::from_weekday_of_month_opt(year, month, weekday, 4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_weekday_recurrence_with_holiday_exception() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 5, 12, 0, 0).unwrap();
+        let recurrence = parse_recurrence("standup every Monday at 9 except holidays", now)
+            .expect("recurrence parsed");
+        assert_eq!(recurrence.frequency, Frequency::Weekly);
+        assert!(recurrence.skip_holidays);
+    }
+
+    #[test]
+    fn next_occurrence_skips_holidays_and_skip_dates() {
+        let mut recurrence = Recurrence::new(Frequency::Weekly);
+        recurrence.skip_holidays = true;
+        // Mon Jan 12 2026, 9am New York.
+        let previous = Utc.with_ymd_and_hms(2026, 1, 12, 14, 0, 0).unwrap();
+        // Jan 19 is MLK day, so the next one is Jan 26.
+        let next = recurrence.next_occurrence(previous).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 1, 26, 14, 0, 0).unwrap());
+
+        recurrence.skip_date(NaiveDate::from_ymd_opt(2026, 1, 26).unwrap());
+        let next = recurrence.next_occurrence(previous).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 2, 2, 14, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn skip_next_week_covers_following_calendar_week() {
+        let mut recurrence = Recurrence::new(Frequency::Daily);
+        // Wednesday.
+        let now = Utc.with_ymd_and_hms(2026, 1, 7, 17, 0, 0).unwrap();
+        recurrence.skip_next_week(now);
+        assert_eq!(
+            recurrence.skip_dates.first(),
+            NaiveDate::from_ymd_opt(2026, 1, 12).as_ref()
+        );
+        assert_eq!(
+            recurrence.skip_dates.last(),
+            NaiveDate::from_ymd_opt(2026, 1, 18).as_ref()
+        );
+    }
+
+    #[test]
+    fn parses_until_and_count_phrases() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 7, 17, 0, 0).unwrap();
+        let until = parse_recurrence("team sync every Friday until March", now).unwrap();
+        assert_eq!(until.until, NaiveDate::from_ymd_opt(2026, 3, 31));
+
+        let count = parse_recurrence("stretch every week for the next 6 weeks", now).unwrap();
+        assert_eq!(count.count, Some(6));
+        assert_eq!(count.until, None);
+
+        let span = parse_recurrence("water plants every day for 2 weeks", now).unwrap();
+        assert_eq!(span.until, NaiveDate::from_ymd_opt(2026, 1, 20));
+    }
+
+    #[test]
+    fn next_occurrence_stops_after_until() {
+        let mut recurrence = Recurrence::new(Frequency::Weekly);
+        recurrence.until = NaiveDate::from_ymd_opt(2026, 1, 20);
+        let previous = Utc.with_ymd_and_hms(2026, 1, 12, 14, 0, 0).unwrap();
+        assert!(recurrence.next_occurrence(previous).is_some());
+        let last = Utc.with_ymd_and_hms(2026, 1, 19, 14, 0, 0).unwrap();
+        assert!(recurrence.next_occurrence(last).is_none());
+    }
+}
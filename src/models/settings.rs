@@ -0,0 +1,579 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use memory_db::{DB, DBError, save_db};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use crate::models::notification::DeliveryTarget;
+
+// Returns the directory where per-user settings live.
+// Defaults to a relative "./data/settings/users" directory.
+pub fn get_user_db_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/settings/users", base)
+}
+
+// Returns the directory where per-guild settings live.
+// Defaults to a relative "./data/settings/guilds" directory.
+pub fn get_guild_db_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/settings/guilds", base)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UserSettings {
+    pub user_id: String,
+    #[serde(default)]
+    pub locale: Option<String>,
+    // IANA zone name, e.g. "America/Los_Angeles".
+    #[serde(default)]
+    pub timezone: Option<String>,
+    // Category name (see service::categories) -> how its reminders look.
+    #[serde(default)]
+    pub styles: HashMap<String, CategoryStyle>,
+    #[serde(default)]
+    pub focus: Option<FocusMode>,
+    // Turn /notify requests that never got a time into todos once the
+    // clarification session expires.
+    #[serde(default)]
+    pub stale_to_todo: bool,
+    // Application ids of other bots allowed to create reminders for this user
+    // through the integrations endpoint.
+    #[serde(default)]
+    pub trusted_apps: Vec<String>,
+    // App id -> the guild it was allowed from; its reminders may only go to
+    // channels in that guild.
+    #[serde(default)]
+    pub trusted_app_guilds: HashMap<String, String>,
+    // Opted into the morning briefing (calendar, today's reminders and todos)
+    // in place of the plain todo summary.
+    #[serde(default)]
+    pub agenda: Option<AgendaPreference>,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    // Their own daily todo summary time, or opted out. None follows the
+    // bot-wide schedule.
+    #[serde(default)]
+    pub summary: Option<SummaryPreference>,
+    // An ntfy topic or Pushover key that gets every ping for this user too,
+    // for when Discord is muted on their phone.
+    #[serde(default)]
+    pub push: Option<DeliveryTarget>,
+    // Secret in their /feeds/<token>.ics calendar subscription URL. None
+    // means no feed.
+    #[serde(default)]
+    pub feed_token: Option<String>,
+}
+
+impl UserSettings {
+    // Their /settings timezone, New York when unset or unknown.
+    pub fn zone(&self) -> Tz {
+        self.timezone
+            .as_deref()
+            .and_then(|tz| tz.parse::<Tz>().ok())
+            .unwrap_or(chrono_tz::America::New_York)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SummaryPreference {
+    // Local time in the user's timezone; None means no summary at all.
+    pub time: Option<NaiveTime>,
+    // Local date of the last summary, so each day gets one.
+    #[serde(default)]
+    pub last_sent: Option<NaiveDate>,
+}
+
+// Local times in the user's timezone between which pings wait. The window
+// may run past midnight, e.g. 22:00 to 07:00.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    // When `at` falls inside the window, the moment the window ends.
+    pub fn end_after(&self, at: DateTime<Utc>, zone: Tz) -> Option<DateTime<Utc>> {
+        let local = at.with_timezone(&zone);
+        let time = local.time();
+        let inside = if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        };
+        if !inside {
+            return None;
+        }
+        let day = if time < self.end {
+            local.date_naive()
+        } else {
+            local.date_naive() + Duration::days(1)
+        };
+        let end = day.and_time(self.end);
+        Some(
+            zone.from_local_datetime(&end)
+                .earliest()
+                .unwrap_or_else(|| zone.from_utc_datetime(&end))
+                .with_timezone(&Utc),
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AgendaPreference {
+    // Local hour (0-23) in the user's timezone.
+    pub hour: u32,
+    // Local date of the last briefing, so each day gets one.
+    #[serde(default)]
+    pub last_sent: Option<NaiveDate>,
+}
+
+// While active, non-urgent reminders and todo nudges are rendered into `held`
+// instead of being sent, then delivered together once `until` passes.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FocusMode {
+    pub until: DateTime<Utc>,
+    #[serde(default)]
+    pub held: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CategoryStyle {
+    #[serde(default)]
+    pub emoji: Option<String>,
+    // RGB embed color, e.g. 0xff8800.
+    #[serde(default)]
+    pub color: Option<u32>,
+    #[serde(default)]
+    pub gif_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GuildSettings {
+    pub guild_id: String,
+    #[serde(default)]
+    pub locale: Option<String>,
+    // Lowercased single-word shorthand -> text it expands to.
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    #[serde(default)]
+    pub voice: Voice,
+    // Where unacknowledged must-ack reminders are escalated, e.g. #missed-reminders.
+    #[serde(default)]
+    pub escalation_channel: Option<String>,
+    // Approval prompts also take ✅/❌ reactions, for clients where buttons
+    // are awkward.
+    #[serde(default)]
+    pub approval_reactions: bool,
+}
+
+// How generated reminder messages sound in a guild.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Voice {
+    #[default]
+    Normal,
+    Formal,
+    Playful,
+    Terse,
+}
+
+impl Voice {
+    pub const ALL: [Voice; 4] = [Voice::Normal, Voice::Formal, Voice::Playful, Voice::Terse];
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Voice::ALL
+            .into_iter()
+            .find(|voice| voice.code() == code.trim().to_lowercase())
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Voice::Normal => "normal",
+            Voice::Formal => "formal",
+            Voice::Playful => "playful",
+            Voice::Terse => "terse",
+        }
+    }
+}
+
+pub fn set_user_locale(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    locale: &str,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    entry.locale = Some(locale.to_string());
+    save_db(&get_user_db_location(), db)
+}
+
+pub fn set_user_timezone(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    timezone: &str,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    entry.timezone = Some(timezone.to_string());
+    save_db(&get_user_db_location(), db)
+}
+
+pub fn set_user_stale_to_todo(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    enabled: bool,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    entry.stale_to_todo = enabled;
+    save_db(&get_user_db_location(), db)
+}
+
+// `hour` of None turns the briefing off.
+pub fn set_user_agenda(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    hour: Option<u32>,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    entry.agenda = hour.map(|hour| AgendaPreference {
+        hour: hour.min(23),
+        last_sent: entry.agenda.as_ref().and_then(|agenda| agenda.last_sent),
+    });
+    save_db(&get_user_db_location(), db)
+}
+
+// Disabled opts out of the daily summary; enabled without a time goes back
+// to the bot-wide schedule.
+pub fn set_user_summary(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    enabled: bool,
+    time: Option<NaiveTime>,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    let last_sent = entry.summary.as_ref().and_then(|summary| summary.last_sent);
+    entry.summary = match (enabled, time) {
+        (false, _) => Some(SummaryPreference { time: None, last_sent }),
+        (true, Some(time)) => Some(SummaryPreference {
+            time: Some(time),
+            last_sent,
+        }),
+        (true, None) => None,
+    };
+    save_db(&get_user_db_location(), db)
+}
+
+// None turns quiet hours off.
+pub fn set_user_quiet_hours(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    quiet_hours: Option<QuietHours>,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    entry.quiet_hours = quiet_hours;
+    save_db(&get_user_db_location(), db)
+}
+
+// None turns push notifications off.
+pub fn set_user_push(db: &mut DB<UserSettings>, user_id: &str, push: Option<DeliveryTarget>) -> Result<(), DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    entry.push = push;
+    save_db(&get_user_db_location(), db)
+}
+
+// The user's calendar feed token, made on first use. `rotate` replaces it so
+// the old URL stops working.
+pub fn user_feed_token(db: &mut DB<UserSettings>, user_id: &str, rotate: bool) -> Result<String, DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    if let Some(token) = entry.feed_token.clone().filter(|_| !rotate) {
+        return Ok(token);
+    }
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    entry.feed_token = Some(token.clone());
+    save_db(&get_user_db_location(), db)?;
+    Ok(token)
+}
+
+pub fn disable_user_feed(db: &mut DB<UserSettings>, user_id: &str) -> Result<(), DBError> {
+    if let Some(entry) = db.get_mut(user_id) {
+        entry.feed_token = None;
+    }
+    save_db(&get_user_db_location(), db)
+}
+
+pub fn user_for_feed_token<'a>(db: &'a DB<UserSettings>, token: &str) -> Option<&'a str> {
+    db.values()
+        .find(|settings| settings.feed_token.as_deref() == Some(token))
+        .map(|settings| settings.user_id.as_str())
+}
+
+// When the user is in their quiet hours at `at`, the moment those end.
+pub fn quiet_hours_end(db: &DB<UserSettings>, user_id: &str, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let settings = db.get(user_id)?;
+    settings.quiet_hours?.end_after(at, settings.zone())
+}
+
+pub fn set_user_trusted_app(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    app_id: &str,
+    guild_id: Option<&str>,
+    allowed: bool,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    entry.trusted_apps.retain(|app| app != app_id);
+    entry.trusted_app_guilds.remove(app_id);
+    if allowed {
+        entry.trusted_apps.push(app_id.to_string());
+        if let Some(guild_id) = guild_id {
+            entry.trusted_app_guilds.insert(app_id.to_string(), guild_id.to_string());
+        }
+    }
+    save_db(&get_user_db_location(), db)
+}
+
+pub fn user_trusts_app(db: &DB<UserSettings>, user_id: &str, app_id: &str) -> bool {
+    db.get(user_id)
+        .is_some_and(|settings| settings.trusted_apps.iter().any(|app| app == app_id))
+}
+
+// The guild the user allowed the app from. None when it isn't allowed or was
+// allowed from a DM.
+pub fn trusted_app_guild<'a>(db: &'a DB<UserSettings>, user_id: &str, app_id: &str) -> Option<&'a str> {
+    db.get(user_id)?.trusted_app_guilds.get(app_id).map(String::as_str)
+}
+
+pub fn set_user_category_style(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    category: &str,
+    style: CategoryStyle,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    entry.styles.insert(category.to_string(), style);
+    save_db(&get_user_db_location(), db)
+}
+
+pub fn set_guild_locale(
+    db: &mut DB<GuildSettings>,
+    guild_id: &str,
+    locale: &str,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(guild_id.to_string())
+        .or_insert_with(|| GuildSettings {
+            guild_id: guild_id.to_string(),
+            ..Default::default()
+        });
+    entry.locale = Some(locale.to_string());
+    save_db(&get_guild_db_location(), db)
+}
+
+pub fn set_guild_alias(
+    db: &mut DB<GuildSettings>,
+    guild_id: &str,
+    alias: &str,
+    expansion: &str,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(guild_id.to_string())
+        .or_insert_with(|| GuildSettings {
+            guild_id: guild_id.to_string(),
+            ..Default::default()
+        });
+    entry
+        .aliases
+        .insert(alias.trim().to_lowercase(), expansion.trim().to_string());
+    save_db(&get_guild_db_location(), db)
+}
+
+pub fn set_guild_voice(
+    db: &mut DB<GuildSettings>,
+    guild_id: &str,
+    voice: Voice,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(guild_id.to_string())
+        .or_insert_with(|| GuildSettings {
+            guild_id: guild_id.to_string(),
+            ..Default::default()
+        });
+    entry.voice = voice;
+    save_db(&get_guild_db_location(), db)
+}
+
+pub fn set_guild_escalation_channel(
+    db: &mut DB<GuildSettings>,
+    guild_id: &str,
+    channel_id: &str,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(guild_id.to_string())
+        .or_insert_with(|| GuildSettings {
+            guild_id: guild_id.to_string(),
+            ..Default::default()
+        });
+    entry.escalation_channel = Some(channel_id.to_string());
+    save_db(&get_guild_db_location(), db)
+}
+
+pub fn set_guild_approval_reactions(
+    db: &mut DB<GuildSettings>,
+    guild_id: &str,
+    enabled: bool,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(guild_id.to_string())
+        .or_insert_with(|| GuildSettings {
+            guild_id: guild_id.to_string(),
+            ..Default::default()
+        });
+    entry.approval_reactions = enabled;
+    save_db(&get_guild_db_location(), db)
+}
+
+// Returns whether an alias was actually removed.
+pub fn remove_guild_alias(
+    db: &mut DB<GuildSettings>,
+    guild_id: &str,
+    alias: &str,
+) -> Result<bool, DBError> {
+    let removed = db
+        .get_mut(guild_id)
+        .and_then(|settings| settings.aliases.remove(&alias.trim().to_lowercase()))
+        .is_some();
+    if removed {
+        save_db(&get_guild_db_location(), db)?;
+    }
+    Ok(removed)
+}
+
+// Starts (or extends) focus mode, keeping anything already held.
+pub fn set_user_focus(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    until: DateTime<Utc>,
+) -> Result<(), DBError> {
+    let entry = db
+        .entry(user_id.to_string())
+        .or_insert_with(|| UserSettings {
+            user_id: user_id.to_string(),
+            ..Default::default()
+        });
+    match entry.focus.as_mut() {
+        Some(focus) => focus.until = until,
+        None => {
+            entry.focus = Some(FocusMode {
+                until,
+                held: Vec::new(),
+            })
+        }
+    }
+    save_db(&get_user_db_location(), db)
+}
+
+pub fn in_focus(db: &DB<UserSettings>, user_id: &str, now: DateTime<Utc>) -> bool {
+    db.get(user_id)
+        .and_then(|settings| settings.focus.as_ref())
+        .is_some_and(|focus| focus.until > now)
+}
+
+// Queues `message` for the user's digest. Returns false when they are not in
+// focus mode, so the caller should deliver it normally.
+pub fn hold_for_focus(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    message: &str,
+    now: DateTime<Utc>,
+) -> bool {
+    let Some(focus) = db
+        .get_mut(user_id)
+        .and_then(|settings| settings.focus.as_mut())
+        .filter(|focus| focus.until > now)
+    else {
+        return false;
+    };
+    focus.held.push(message.to_string());
+    true
+}
+
+// Puts back a digest that couldn't be sent so the next tick tries again. The
+// session stays ended; if a new one has started, the messages go ahead of
+// whatever it holds.
+pub fn return_held(
+    db: &mut DB<UserSettings>,
+    user_id: &str,
+    held: Vec<String>,
+    now: DateTime<Utc>,
+) {
+    let Some(settings) = db.get_mut(user_id) else {
+        return;
+    };
+    match settings.focus.as_mut() {
+        Some(focus) => {
+            focus.held.splice(0..0, held);
+        }
+        None => settings.focus = Some(FocusMode { until: now, held }),
+    }
+}
+
+// Ends every focus session that has run out and returns what each user missed.
+pub fn take_finished_focus(db: &mut DB<UserSettings>, now: DateTime<Utc>) -> Vec<(String, Vec<String>)> {
+    db.values_mut()
+        .filter(|settings| settings.focus.as_ref().is_some_and(|focus| focus.until <= now))
+        .filter_map(|settings| {
+            let focus = settings.focus.take()?;
+            Some((settings.user_id.clone(), focus.held))
+        })
+        .collect()
+}
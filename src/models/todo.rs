@@ -20,6 +20,29 @@ pub struct TodoItem {
     pub completed_at: Option<DateTime<Utc>>,
 }
 
+// What the todo_extraction prompt returns.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AITodo {
+    #[serde(default)]
+    pub items: Vec<String>,
+}
+
+// Trimmed, de-duplicated items, falling back to the message itself when the
+// model found nothing to split out.
+pub fn draft_items(extracted: AITodo, original_text: &str) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    for item in extracted.items {
+        let item = item.trim();
+        if !item.is_empty() && !items.iter().any(|seen| seen.eq_ignore_ascii_case(item)) {
+            items.push(item.to_string());
+        }
+    }
+    if items.is_empty() && !original_text.trim().is_empty() {
+        items.push(original_text.trim().to_string());
+    }
+    items
+}
+
 pub fn create_todo(
     db: &mut DB<TodoItem>,
     user_id: &str,
@@ -39,3 +62,83 @@ pub fn create_todo(
     save_db(&get_db_location(), db)?;
     Ok(id)
 }
+
+// Saves every item or none of them: on a failed save the new items are taken
+// back out, so confirming again doesn't duplicate the ones that got in.
+pub fn create_todos(db: &mut DB<TodoItem>, user_id: &str, items: &[String]) -> Result<Vec<String>, DBError> {
+    let now = Utc::now();
+    let ids: Vec<String> = items.iter().map(|_| Uuid::new_v4().to_string()).collect();
+    for (id, content) in ids.iter().zip(items) {
+        db.insert(
+            id.clone(),
+            TodoItem {
+                id: id.clone(),
+                user_id: user_id.to_string(),
+                content: content.to_string(),
+                created_at: now,
+                completed_at: None,
+            },
+        );
+    }
+    if let Err(err) = save_db(&get_db_location(), db) {
+        for id in &ids {
+            db.remove(id);
+        }
+        return Err(err);
+    }
+    Ok(ids)
+}
+
+pub fn delete_todo(db: &mut DB<TodoItem>, id: &str) -> Result<bool, DBError> {
+    if db.remove(id).is_none() {
+        return Ok(false);
+    }
+    save_db(&get_db_location(), db)?;
+    Ok(true)
+}
+
+// Drops todos finished before `cutoff` and returns how many went; the
+// caller saves.
+pub fn prune_finished(db: &mut DB<TodoItem>, cutoff: DateTime<Utc>) -> usize {
+    let before = db.len();
+    db.retain(|_, item| item.completed_at.is_none_or(|done| done >= cutoff));
+    before - db.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::collections::HashMap;
+
+    #[test]
+    fn draft_items_dedupes_and_falls_back_to_the_message() {
+        let extracted = AITodo {
+            items: vec![" buy milk ".to_string(), "Buy milk".to_string(), "".to_string(), "call plumber".to_string()],
+        };
+        assert_eq!(draft_items(extracted, "buy milk and call plumber"), vec!["buy milk", "call plumber"]);
+        assert_eq!(draft_items(AITodo::default(), " tidy desk "), vec!["tidy desk"]);
+    }
+
+    #[test]
+    fn prune_finished_keeps_open_and_recent_todos() {
+        let now = Utc::now();
+        let item = |id: &str, completed_at: Option<DateTime<Utc>>| {
+            let item = TodoItem {
+                id: id.to_string(),
+                user_id: "@u".to_string(),
+                content: id.to_string(),
+                created_at: now - Duration::days(90),
+                completed_at,
+            };
+            (id.to_string(), item)
+        };
+        let mut db: DB<TodoItem> = HashMap::from([
+            item("open", None),
+            item("recent", Some(now - Duration::days(2))),
+            item("old", Some(now - Duration::days(45))),
+        ]);
+        assert_eq!(prune_finished(&mut db, now - Duration::days(30)), 1);
+        assert!(db.contains_key("open") && db.contains_key("recent"));
+    }
+}
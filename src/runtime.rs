@@ -1,96 +1,553 @@
+use std::net::SocketAddr;
 use std::sync::Arc;
 
-use memory_db::DB;
+use memory_db::{DB, load_db, save_db};
 use serenity::model::gateway::GatewayIntents;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, watch};
+use warp::Filter;
 
-use crate::handlers::action::{ActionEngine, ActionStore};
+use crate::handlers::action::{self, ActionEngine, ActionStore};
 use crate::handlers::discord;
+use crate::handlers::http;
 use std::collections::HashMap;
-use crate::models::notification::Notification;
-use crate::models::todo::TodoItem;
+use crate::models::notification::{self, Notification};
+use crate::models::settings::{self, GuildSettings, UserSettings};
+use crate::models::todo::{self, TodoItem};
 use crate::tasks::calendar_loop;
+use crate::tasks::focus_loop;
+use crate::tasks::latency_loop;
 use crate::tasks::notification_loop;
+use crate::tasks::{approval_sweep_loop, session_loop};
 use crate::tasks::todo_loop;
-use crate::tasks::task_runner::TaskRunner;
+use crate::tasks::cleanup_job;
+use crate::tasks::email_loop;
+use crate::tasks::task_runner::{TaskHealth, TaskRunner};
+use crate::events::dead_letter::{self, DeadLetterStore};
+use crate::events::forwarder::run_event_forwarder;
+use crate::events::journal::{EventJournal, get_journal_location};
+use crate::events::prompt_watch::PromptWatch;
 use crate::events::queue::EventBus;
-use crate::events::worker::run_event_worker;
+use crate::events::trail::{EventTrail, get_event_log_location};
+use crate::events::worker::run_event_worker_pool_until;
 use crate::service::approval_prompt::DiscordApprovalPromptService;
+use crate::bot::ReminderBot;
+use crate::config::SharedConfig;
+use crate::service::audit::{AuditLog, get_audit_location};
 use crate::service::openai_service::OpenAIClient;
-use crate::service::openai_service::OpenAIService;
-use crate::service::notify_flow::{PendingSession, SessionKey};
-use crate::service::routing::OpenAIRouter;
+use crate::service::notify_flow::{self, PendingSession, SessionKey};
+use crate::service::routing::IntentRouter;
+use crate::service::latency::LatencyTracker;
+use crate::service::scheduler::SchedulerWake;
+use crate::service::upcoming::{SharedUpcoming, UpcomingWindow};
+use crate::tools::ToolRegistry;
+use crate::tools::calendar::CreateCalendarEventTool;
 
-pub async fn run_api(
+// Which parts of the bot this process runs. Splitting them lets the gateway,
+// the REST API and the delivery loops live in separate deployments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunModes {
+    // Discord gateway handler, plus the event worker and webhook that feed it.
+    pub gateway: bool,
+    pub http: bool,
+    // Notification, todo and calendar loops.
+    pub worker: bool,
+    // The terminal UI; takes over the terminal, so it runs on its own.
+    pub tui: bool,
+}
+
+impl RunModes {
+    // Accepts a comma-separated list such as "gateway,http". "api" keeps its
+    // original meaning of gateway plus background loops.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut modes = RunModes::default();
+        for part in value.split(',').map(|p| p.trim().to_lowercase()) {
+            match part.as_str() {
+                "api" => {
+                    modes.gateway = true;
+                    modes.worker = true;
+                }
+                "gateway" => modes.gateway = true,
+                "http" => modes.http = true,
+                "worker" => modes.worker = true,
+                "tui" => modes.tui = true,
+                "" => {}
+                other => {
+                    return Err(format!(
+                        "Unsupported RUN_MODE {}. Use a comma-separated list of api, gateway, http and worker, or tui.",
+                        other
+                    ));
+                }
+            }
+        }
+        if modes.tui && (modes.gateway || modes.http || modes.worker) {
+            return Err("RUN_MODE=tui can't be combined with other modes.".to_string());
+        }
+        if modes == RunModes::default() {
+            return Err("RUN_MODE must enable at least one of api, gateway, http or worker.".to_string());
+        }
+        Ok(modes)
+    }
+}
+
+pub async fn run(
+    bot: ReminderBot,
     shared_db: Arc<Mutex<DB<Notification>>>,
     shared_todo_db: Arc<Mutex<DB<TodoItem>>>,
-    discord_client_secret: String,
-    openai_api_key: String,
+    shared_user_settings: Arc<Mutex<DB<UserSettings>>>,
+    shared_guild_settings: Arc<Mutex<DB<GuildSettings>>>,
 ) {
-    let discord_client_secret_arc = Arc::new(discord_client_secret.clone());
-    let openai_api_key_arc = Arc::new(openai_api_key);
-
-    let mut task_runner = TaskRunner::new();
-    task_runner.add_task({
-        let db = shared_db.clone();
+    let modes = bot.modes;
+    let openai = bot.openai_client();
+    let router = bot.intent_router_with(openai.clone());
+    let discord_client_secret_arc = Arc::new(bot.discord_client_secret.clone());
+    let upcoming: SharedUpcoming = Arc::new(Mutex::new(UpcomingWindow::build(
+        &*shared_db.lock().await,
+        chrono::Utc::now(),
+    )));
+    // Prompts are timed by the gateway and deliveries by the worker; each
+    // process alerts on what it sees.
+    let latency = Arc::new(LatencyTracker::new());
+    // Only reaches the notification loop when the gateway runs in the same
+    // process as the worker.
+    let scheduler = SchedulerWake::default();
+    // Every supervised loop in this process reports here, for /healthz.
+    let health = TaskHealth::default();
+    let mut task_runner = TaskRunner::new().with_health(health.clone());
+    task_runner.add_task("latency_alerts", {
+        let latency = latency.clone();
         let secret = discord_client_secret_arc.clone();
-        let openai = openai_api_key_arc.clone();
-        move || {
-            tokio::spawn(async move {
-                notification_loop::run_notification_loop(db, secret, openai).await;
-            });
-        }
-    });
-    task_runner.add_task({
-        let todo_db = shared_todo_db.clone();
-        let secret = discord_client_secret_arc.clone();
-        move || {
-            tokio::spawn(async move {
-                todo_loop::run_todo_loop(todo_db, secret).await;
-            });
-        }
+        let config = bot.config.clone();
+        move || latency_loop::run_latency_alert_loop(latency.clone(), secret.clone(), config.clone())
     });
-    task_runner.add_task(|| {
-        tokio::spawn(async move {
-            calendar_loop::run_calendar_loop().await;
+
+    if modes.worker {
+        task_runner.add_task("notifications", {
+            let db = shared_db.clone();
+            let settings = shared_user_settings.clone();
+            let guilds = shared_guild_settings.clone();
+            let secret = discord_client_secret_arc.clone();
+            let openai = openai.clone();
+            let config = bot.config.clone();
+            let upcoming = upcoming.clone();
+            let latency = latency.clone();
+            let scheduler = scheduler.clone();
+            move || {
+                notification_loop::run_notification_loop(
+                    db.clone(),
+                    settings.clone(),
+                    guilds.clone(),
+                    secret.clone(),
+                    openai.clone(),
+                    config.clone(),
+                    upcoming.clone(),
+                    latency.clone(),
+                    scheduler.clone(),
+                )
+            }
         });
-    });
-    task_runner.start_all();
+        task_runner.add_task("todos", {
+            let todo_db = shared_todo_db.clone();
+            let upcoming = upcoming.clone();
+            let settings = shared_user_settings.clone();
+            let config = bot.config.clone();
+            let secret = discord_client_secret_arc.clone();
+            move || {
+                todo_loop::run_todo_loop(
+                    todo_db.clone(),
+                    upcoming.clone(),
+                    settings.clone(),
+                    config.clone(),
+                    secret.clone(),
+                )
+            }
+        });
+        task_runner.add_task("focus_digest", {
+            let settings = shared_user_settings.clone();
+            let secret = discord_client_secret_arc.clone();
+            let config = bot.config.clone();
+            move || focus_loop::run_focus_loop(settings.clone(), secret.clone(), config.clone())
+        });
+        task_runner.add_cron_task(
+            "todo_summary",
+            {
+                let config = bot.config.clone();
+                move || Some(config.load().todo_summary_cron())
+            },
+            {
+                let todo_db = shared_todo_db.clone();
+                let settings = shared_user_settings.clone();
+                let secret = discord_client_secret_arc.clone();
+                move || todo_loop::run_daily_summary(todo_db.clone(), settings.clone(), secret.clone())
+            },
+        );
+        task_runner.add_cron_task(
+            "weekly_digest",
+            {
+                let config = bot.config.clone();
+                move || config.load().weekly_digest_cron()
+            },
+            {
+                let todo_db = shared_todo_db.clone();
+                let db = shared_db.clone();
+                let settings = shared_user_settings.clone();
+                let secret = discord_client_secret_arc.clone();
+                move || todo_loop::run_weekly_digest(todo_db.clone(), db.clone(), settings.clone(), secret.clone())
+            },
+        );
+        task_runner.add_cron_task(
+            "cleanup",
+            {
+                let config = bot.config.clone();
+                move || config.load().cleanup_cron()
+            },
+            {
+                let todo_db = shared_todo_db.clone();
+                let config = bot.config.clone();
+                move || cleanup_job::run_cleanup(todo_db.clone(), config.clone())
+            },
+        );
+        task_runner.add_task("calendar_sync", {
+            let db = shared_db.clone();
+            let config = bot.config.clone();
+            move || calendar_loop::run_calendar_loop(db.clone(), config.clone())
+        });
+    }
+    let tasks = task_runner.start_all();
+
+    if let Some(addr) = bot.http_addr.filter(|_| modes.http) {
+        tracing::info!(%addr, "HTTP API listening");
+        let routes = http::routes(shared_db.clone(), shared_todo_db.clone())
+            .or(http::health_route(health.clone()));
+        tokio::spawn(warp::serve(routes).run(addr));
+    }
+    if let Some(addr) = bot.feed_addr {
+        tracing::info!(%addr, "calendar feeds listening");
+        let routes = http::feed_route(shared_db.clone(), shared_user_settings.clone());
+        tokio::spawn(warp::serve(routes).run(addr));
+    }
+
+    if modes.gateway {
+        run_gateway(
+            &shared_db,
+            &shared_todo_db,
+            &shared_user_settings,
+            &shared_guild_settings,
+            bot.discord_client_secret,
+            openai,
+            router,
+            bot.webhook,
+            bot.admin_role,
+            bot.config.clone(),
+            upcoming,
+            latency,
+            scheduler,
+            health,
+        )
+        .await;
+    } else {
+        if bot.webhook.is_some() {
+            tracing::warn!("WEBHOOK_TOKEN is set but the gateway is disabled, webhook not started");
+        }
+        shutdown_signal().await;
+        tracing::info!("shutdown requested");
+    }
+
+    // Scheduled jobs finish what they started and the loops stop touching
+    // the stores before they are saved.
+    tasks.stop().await;
+    flush_stores(
+        &shared_db,
+        &shared_todo_db,
+        &shared_user_settings,
+        &shared_guild_settings,
+    )
+    .await;
+}
 
-    let action_store = Arc::new(Mutex::new(ActionStore::new()));
+#[allow(clippy::too_many_arguments)]
+async fn run_gateway(
+    shared_db: &Arc<Mutex<DB<Notification>>>,
+    shared_todo_db: &Arc<Mutex<DB<TodoItem>>>,
+    shared_user_settings: &Arc<Mutex<DB<UserSettings>>>,
+    shared_guild_settings: &Arc<Mutex<DB<GuildSettings>>>,
+    discord_client_secret: String,
+    openai: Arc<dyn OpenAIClient>,
+    router: Arc<dyn IntentRouter>,
+    webhook: Option<http::WebhookConfig>,
+    admin_role: Option<String>,
+    config: SharedConfig,
+    upcoming: SharedUpcoming,
+    latency: Arc<LatencyTracker>,
+    scheduler: SchedulerWake,
+    health: TaskHealth,
+) {
+    // Actions and sessions saved by a previous graceful shutdown pick up where they left off.
+    let action_store = Arc::new(Mutex::new(ActionStore::from_db(
+        load_db(&action::get_store_location()).unwrap_or_else(|_| HashMap::new()),
+    )));
     let sessions: Arc<Mutex<HashMap<SessionKey, PendingSession>>> =
-        Arc::new(Mutex::new(HashMap::new()));
-    let (event_bus, event_rx) = EventBus::new(256);
-    let worker_openai: Arc<dyn OpenAIClient> =
-        Arc::new(OpenAIService::new(openai_api_key_arc.as_ref().to_string()));
-    let router: Arc<dyn crate::service::routing::IntentRouter> =
-        Arc::new(OpenAIRouter::new(worker_openai.clone()));
-    let worker_secret = discord_client_secret_arc.clone();
+        Arc::new(Mutex::new(notify_flow::sessions_from_db(
+            load_db(&notify_flow::get_session_db_location()).unwrap_or_else(|_| HashMap::new()),
+        )));
+    // Events a crash left between emit and the worker are replayed first.
+    let journal = match EventJournal::open(get_journal_location()) {
+        Ok(journal) => Some(Arc::new(journal)),
+        Err(err) => {
+            tracing::error!(error = %err, "event journal unavailable, queue is in-memory only");
+            None
+        }
+    };
+    let trail = Arc::new(EventTrail::new(get_event_log_location()));
+    let (event_bus, event_rx) = {
+        let config = config.load();
+        EventBus::with_overflow(config.event_queue_capacity(), config.event_overflow())
+    };
+    let event_bus = event_bus.with_trail(trail.clone());
+    let event_bus = match &journal {
+        Some(journal) => event_bus.with_journal(journal.clone()),
+        None => event_bus,
+    };
+    let worker_secret = Arc::new(discord_client_secret.clone());
     let approval_service: Arc<dyn crate::service::approval_prompt::ApprovalPromptService> =
-        Arc::new(DiscordApprovalPromptService::new(worker_secret.clone()));
-    let engine = ActionEngine::new(
+        Arc::new(
+            DiscordApprovalPromptService::new(worker_secret)
+                .with_guild_settings(shared_guild_settings.clone())
+                .with_user_settings(shared_user_settings.clone()),
+        );
+    let dead_letters = Arc::new(Mutex::new(DeadLetterStore::from_db(
+        load_db(&dead_letter::get_dead_letter_location()).unwrap_or_else(|_| HashMap::new()),
+    )));
+    let mut tools = ToolRegistry::builtin();
+    let calendar = config
+        .load()
+        .calendar_provider()
+        .map(|provider| (calendar_loop::connect(&provider), provider.user_id().to_string()));
+    if let Some((client, user_id)) = &calendar {
+        tools = tools.with_tool(Arc::new(
+            CreateCalendarEventTool::new().with_client(client.clone(), user_id.clone()),
+        ));
+    }
+    let prompt_watch = PromptWatch::new();
+    let mut engine = ActionEngine::new(
         action_store.clone(),
-        worker_openai,
-        approval_service,
+        openai,
+        approval_service.clone(),
         shared_db.clone(),
-    );
-    tokio::spawn(async move {
-        run_event_worker(event_rx, engine).await;
+    )
+    .with_dead_letters(dead_letters)
+    .with_trail(trail)
+    .with_upcoming(upcoming)
+    .with_scheduler(scheduler)
+    .with_latency(latency)
+    .with_task_health(health.clone())
+    .with_todos(shared_todo_db.clone())
+    .with_user_settings(shared_user_settings.clone())
+    .with_dm_sender(Arc::new(todo_loop::DiscordDmSender::new(discord_client_secret.clone())))
+    .with_event_bus(event_bus.clone())
+    .with_prompt_watch(prompt_watch.clone())
+    .with_tools(tools);
+    if let Some((client, user_id)) = calendar {
+        engine = engine.with_calendar(client, user_id);
+    }
+    if let Some(url) = config.load().event_forward_url() {
+        tracing::info!(%url, "forwarding events");
+        tokio::spawn(run_event_forwarder(event_bus.subscribe("forwarder"), url));
+    }
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let worker = tokio::spawn(run_event_worker_pool_until(
+        event_rx,
+        engine,
+        config.load().event_workers(),
+        shutdown_rx,
+        journal,
+    ));
+
+    if let Some(webhook) = webhook {
+        let audit = Arc::new(AuditLog::new(get_audit_location()));
+        let routes = http::webhook_routes(event_bus.clone(), webhook.token)
+            .or(http::integration_routes(
+                shared_db.clone(),
+                shared_user_settings.clone(),
+                audit,
+                config.clone(),
+                Arc::new(notification_loop::DiscordSender::new(discord_client_secret.clone())),
+            ))
+            .unify()
+            .or(http::metrics_route(event_bus.clone()))
+            .unify()
+            .or(http::health_route(health.clone()))
+            .unify();
+        tracing::info!(addr = %webhook.addr, "webhook listener started");
+        tokio::spawn(warp::serve(routes).run(webhook.addr));
+    }
+
+    let mut task_runner = TaskRunner::new().with_health(health);
+    // Sessions only exist alongside the gateway, so their sweep runs here
+    // rather than with the worker loops.
+    task_runner.add_task("session_sweep", {
+        let sessions = sessions.clone();
+        let todo_db = shared_todo_db.clone();
+        let settings = shared_user_settings.clone();
+        let token = discord_client_secret.clone();
+        let config = config.clone();
+        move || {
+            session_loop::run_session_loop(
+                sessions.clone(),
+                todo_db.clone(),
+                settings.clone(),
+                token.clone(),
+                config.clone(),
+            )
+        }
+    });
+    // Approval prompts are posted by the gateway's worker, so lapsed ones
+    // are swept here too.
+    task_runner.add_task("approval_sweep", {
+        let store = action_store.clone();
+        let config = config.clone();
+        move || approval_sweep_loop::run_approval_sweep_loop(store.clone(), approval_service.clone(), config.clone())
     });
+    // Emailed requests go through the event bus, which lives here.
+    task_runner.add_task("email_inbox", {
+        let event_bus = event_bus.clone();
+        let config = config.clone();
+        move || email_loop::run_email_loop(event_bus.clone(), config.clone())
+    });
+    let sweeps = task_runner.start_all();
 
     let token = discord_client_secret;
-    let intents = GatewayIntents::GUILD_MESSAGES | GatewayIntents::DIRECT_MESSAGES;
+    let intents = GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::DIRECT_MESSAGES
+        | GatewayIntents::MESSAGE_CONTENT
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS;
     let mut client = serenity::Client::builder(token, intents)
         .event_handler(discord::BotHandler::new(
-            shared_todo_db,
+            shared_todo_db.clone(),
             event_bus,
-            sessions,
+            sessions.clone(),
             router,
-        ))
+        )
+        .with_settings(shared_user_settings.clone(), shared_guild_settings.clone())
+        .with_notifications(shared_db.clone())
+        .with_confidence_threshold(config.load().router_confidence_threshold())
+        .with_max_turns(config.load().conversation_max_turns())
+        .with_admin_role(admin_role)
+        .with_feed_base_url(config.load().feed_base_url())
+        .with_pushover(config.load().pushover_app_token().is_some())
+        .with_prompt_watch(prompt_watch))
         .await
         .expect("Error creating Serenity client");
 
+    let shard_manager = client.shard_manager.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        tracing::info!("shutdown requested, disconnecting from Discord");
+        shard_manager.shutdown_all().await;
+    });
+
     if let Err(why) = client.start().await {
-        eprintln!("Client error: {:?}", why);
+        tracing::error!(error = ?why, "Discord client error");
+    }
+
+    // The gateway is closed, so no new interactions arrive; finish queued
+    // events before saving everything.
+    let _ = shutdown_tx.send(true);
+    if let Err(err) = worker.await {
+        tracing::error!(error = ?err, "event worker failed during shutdown");
+    }
+    sweeps.stop().await;
+    if let Err(err) = action_store.lock().await.save() {
+        tracing::error!(error = %err, "failed to save pending actions");
+    }
+    if let Err(err) = notify_flow::save_sessions(&*sessions.lock().await) {
+        tracing::error!(error = %err, "failed to save pending sessions");
+    }
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %err, "failed to listen for SIGINT");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                tracing::error!(error = %err, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+// Taking each lock also waits out any loop tick that is mid-save.
+async fn flush_stores(
+    shared_db: &Mutex<DB<Notification>>,
+    shared_todo_db: &Mutex<DB<TodoItem>>,
+    shared_user_settings: &Mutex<DB<UserSettings>>,
+    shared_guild_settings: &Mutex<DB<GuildSettings>>,
+) {
+    if let Err(err) = save_db(&notification::get_db_location(), &*shared_db.lock().await) {
+        tracing::error!(error = %err, "failed to save notifications");
+    }
+    if let Err(err) = save_db(&todo::get_db_location(), &*shared_todo_db.lock().await) {
+        tracing::error!(error = %err, "failed to save todos");
+    }
+    if let Err(err) = save_db(&settings::get_user_db_location(), &*shared_user_settings.lock().await) {
+        tracing::error!(error = %err, "failed to save user settings");
+    }
+    if let Err(err) = save_db(&settings::get_guild_db_location(), &*shared_guild_settings.lock().await) {
+        tracing::error!(error = %err, "failed to save guild settings");
+    }
+    tracing::info!("state flushed, exiting");
+}
+
+// Serves the REST API over the notification and todo stores without starting
+// the Discord gateway or the background loops.
+pub async fn run_http(
+    shared_db: Arc<Mutex<DB<Notification>>>,
+    shared_todo_db: Arc<Mutex<DB<TodoItem>>>,
+    addr: SocketAddr,
+) {
+    tracing::info!(%addr, "HTTP API listening");
+    warp::serve(http::routes(shared_db, shared_todo_db)).run(addr).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_modes_parse_component_lists() {
+        let modes = RunModes::parse("worker").unwrap();
+        assert!(modes.worker && !modes.gateway && !modes.http);
+
+        let modes = RunModes::parse("gateway, HTTP").unwrap();
+        assert!(modes.gateway && modes.http && !modes.worker);
+
+        let modes = RunModes::parse("api").unwrap();
+        assert!(modes.gateway && modes.worker && !modes.http);
+
+        let modes = RunModes::parse("tui").unwrap();
+        assert!(modes.tui && !modes.gateway && !modes.worker);
+    }
+
+    #[test]
+    fn run_modes_reject_unknown_or_empty() {
+        assert!(RunModes::parse("cli").is_err());
+        assert!(RunModes::parse(" , ").is_err());
+        assert!(RunModes::parse("tui,worker").is_err());
     }
 }
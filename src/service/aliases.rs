@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+
+// Replaces whole-word alias occurrences with their expansion. Matching is
+// case-insensitive and ignores punctuation glued to the word, which is kept.
+pub fn expand_aliases(text: &str, aliases: &HashMap<String, String>) -> String {
+    if aliases.is_empty() {
+        return text.to_string();
+    }
+    text.split_whitespace()
+        .map(|word| {
+            let start = word
+                .find(|c: char| c.is_alphanumeric())
+                .unwrap_or(word.len());
+            let end = word
+                .rfind(|c: char| c.is_alphanumeric())
+                .map_or(start, |idx| idx + word[idx..].chars().next().map_or(1, char::len_utf8));
+            if start >= end {
+                return word.to_string();
+            }
+            let core = word[start..end].to_lowercase();
+            match aliases.get(&core) {
+                Some(expansion) => format!("{}{}{}", &word[..start], expansion, &word[end..]),
+                None => word.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn is_valid_alias(alias: &str) -> bool {
+    let trimmed = alias.trim();
+    !trimmed.is_empty() && trimmed.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
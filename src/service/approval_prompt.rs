@@ -1,10 +1,19 @@
 use std::sync::Arc;
 
+use chrono_tz::Tz;
+use memory_db::DB;
 use serenity::http::Http;
-use serenity::model::id::ChannelId;
+use serenity::model::channel::{Channel, ReactionType};
+use serenity::model::id::{ChannelId, MessageId};
+use tokio::sync::Mutex;
 
 use crate::handlers::action::{Action, ActionPayload};
-use crate::service::notification_service::{pending_buttons, render_pending_message};
+use crate::i18n::{self, Locale, MessageKey};
+use crate::models::settings::{GuildSettings, UserSettings};
+use crate::service::notification_service::{
+    pending_components, pending_confirm_buttons, render_pending_import, render_pending_message,
+    render_pending_slots, render_pending_todo, render_pending_tool, slot_components,
+};
 
 #[serenity::async_trait]
 pub trait ApprovalPromptService: Send + Sync {
@@ -16,15 +25,88 @@ pub trait ApprovalPromptService: Send + Sync {
         user_id: &str,
         message: &str,
     ) -> Result<(), String>;
+    // Called once a draft lapses unanswered, so its prompt stops offering
+    // buttons that no longer do anything.
+    async fn expire(&self, _action: &Action) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// Reactions that answer a prompt in guilds with approval_reactions on.
+pub const APPROVE_REACTION: &str = "✅";
+pub const CANCEL_REACTION: &str = "❌";
+
+// The prompt text for a draft, as first posted.
+fn render_prompt(action: &Action, locale: Locale, zone: Tz) -> Option<String> {
+    match action.payload.as_ref()? {
+        ActionPayload::NotificationDraft(draft) => Some(render_pending_message(draft, locale, zone)),
+        ActionPayload::TodoDraft(draft) => Some(render_pending_todo(draft, locale)),
+        ActionPayload::ToolDraft(draft) => Some(render_pending_tool(draft)),
+        ActionPayload::ImportDraft(draft) => Some(render_pending_import(draft)),
+        ActionPayload::SlotDraft(draft) => Some(render_pending_slots(draft)),
+    }
 }
 
 pub struct DiscordApprovalPromptService {
     token: Arc<String>,
+    guild_settings: Option<Arc<Mutex<DB<GuildSettings>>>>,
+    user_settings: Option<Arc<Mutex<DB<UserSettings>>>>,
 }
 
 impl DiscordApprovalPromptService {
     pub fn new(token: Arc<String>) -> Self {
-        Self { token }
+        Self {
+            token,
+            guild_settings: None,
+            user_settings: None,
+        }
+    }
+
+    // Prompts are written in the requester's /settings language; English
+    // without it.
+    pub fn with_user_settings(mut self, user_settings: Arc<Mutex<DB<UserSettings>>>) -> Self {
+        self.user_settings = Some(user_settings);
+        self
+    }
+
+    async fn locale_for(&self, user_id: &str) -> Locale {
+        match &self.user_settings {
+            Some(settings) => Locale::resolve(settings.lock().await.get(user_id), None),
+            None => Locale::default(),
+        }
+    }
+
+    async fn zone_for(&self, user_id: &str) -> Tz {
+        if let Some(settings) = &self.user_settings {
+            if let Some(user) = settings.lock().await.get(user_id) {
+                return user.zone();
+            }
+        }
+        UserSettings::default().zone()
+    }
+
+    // Lets prompts in guilds that opted in offer reactions as well as buttons.
+    pub fn with_guild_settings(mut self, guild_settings: Arc<Mutex<DB<GuildSettings>>>) -> Self {
+        self.guild_settings = Some(guild_settings);
+        self
+    }
+
+    // Only resolves the channel's guild when some guild has reactions on.
+    async fn reactions_enabled(&self, http: &Http, channel: ChannelId) -> bool {
+        let Some(guild_settings) = &self.guild_settings else {
+            return false;
+        };
+        if !guild_settings.lock().await.values().any(|g| g.approval_reactions) {
+            return false;
+        }
+        let Ok(Channel::Guild(channel)) = channel.to_channel(http).await else {
+            return false;
+        };
+        guild_settings
+            .lock()
+            .await
+            .get(&channel.guild_id.to_string())
+            .is_some_and(|g| g.approval_reactions)
     }
 
     fn channel_from(&self, channel_id: &str) -> Result<ChannelId, String> {
@@ -38,27 +120,71 @@ impl DiscordApprovalPromptService {
 #[serenity::async_trait]
 impl ApprovalPromptService for DiscordApprovalPromptService {
     async fn prompt(&self, action: &mut Action) -> Result<(), String> {
-        let draft = match action.payload.as_mut() {
-            Some(ActionPayload::NotificationDraft(draft)) => draft,
-            _ => return Err("unsupported action payload".to_string()),
+        let locale = self.locale_for(&action.user_id).await;
+        let zone = self.zone_for(&action.user_id).await;
+        let (message_body, components, channel_id, message_id) = match action.payload.as_mut() {
+            Some(ActionPayload::NotificationDraft(draft)) => (
+                render_pending_message(draft, locale, zone),
+                pending_components(&action.id, draft, locale, zone),
+                draft.channel_id.clone(),
+                &mut draft.message_id,
+            ),
+            Some(ActionPayload::TodoDraft(draft)) => (
+                render_pending_todo(draft, locale),
+                vec![pending_confirm_buttons(&action.id, i18n::text(locale, MessageKey::TodoConfirmButton))],
+                draft.channel_id.clone(),
+                &mut draft.message_id,
+            ),
+            Some(ActionPayload::ToolDraft(draft)) => (
+                render_pending_tool(draft),
+                vec![pending_confirm_buttons(&action.id, "Run it")],
+                draft.channel_id.clone(),
+                &mut draft.message_id,
+            ),
+            Some(ActionPayload::ImportDraft(draft)) => (
+                render_pending_import(draft),
+                vec![pending_confirm_buttons(&action.id, "Import")],
+                draft.channel_id.clone(),
+                &mut draft.message_id,
+            ),
+            Some(ActionPayload::SlotDraft(draft)) => (
+                render_pending_slots(draft),
+                slot_components(&action.id, draft),
+                draft.channel_id.clone(),
+                &mut draft.message_id,
+            ),
+            None => return Err("unsupported action payload".to_string()),
         };
-
-        let message_body = render_pending_message(draft);
-        let buttons = pending_buttons(&action.id);
         let http: Http = Http::new(self.token.as_ref());
-        let channel = self.channel_from(&draft.channel_id)?;
+        let channel = self.channel_from(&channel_id)?;
+        let reactions = self.reactions_enabled(&http, channel).await;
+        let message_body = if reactions {
+            format!(
+                "{}\nOr react {} to confirm, {} to cancel.",
+                message_body, APPROVE_REACTION, CANCEL_REACTION
+            )
+        } else {
+            message_body
+        };
 
         let message = channel
             .send_message(
                 &http,
                 serenity::builder::CreateMessage::new()
                     .content(message_body)
-                    .components(vec![buttons]),
+                    .components(components),
             )
             .await
             .map_err(|err| format!("Failed to send approval prompt: {err}"))?;
 
-        draft.message_id = Some(message.id.get());
+        *message_id = Some(message.id.get());
+        if reactions {
+            for emoji in [APPROVE_REACTION, CANCEL_REACTION] {
+                if let Err(err) = message.react(&http, ReactionType::Unicode(emoji.to_string())).await {
+                    tracing::warn!(error = %err, "failed to add approval reaction");
+                }
+            }
+        }
         Ok(())
     }
 
@@ -90,4 +216,25 @@ impl ApprovalPromptService for DiscordApprovalPromptService {
             .map_err(|err| format!("Failed to send status message: {err}"))?;
         Ok(())
     }
+
+    async fn expire(&self, action: &Action) -> Result<(), String> {
+        let locale = self.locale_for(&action.user_id).await;
+        let zone = self.zone_for(&action.user_id).await;
+        let (Some(message_id), Some(body)) = (action.draft_message_id(), render_prompt(action, locale, zone)) else {
+            return Ok(());
+        };
+        let http: Http = Http::new(self.token.as_ref());
+        let channel = self.channel_from(&action.channel_id)?;
+        channel
+            .edit_message(
+                &http,
+                MessageId::new(message_id),
+                serenity::builder::EditMessage::new()
+                    .content(format!("{}\n⌛ This request expired. Send it again if you still need it.", body))
+                    .components(Vec::new()),
+            )
+            .await
+            .map_err(|err| format!("Failed to expire approval prompt: {err}"))?;
+        Ok(())
+    }
 }
@@ -0,0 +1,72 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Returns the file actions taken on someone's behalf are logged to.
+// Defaults to a relative "./data/audit.log" file.
+pub fn get_audit_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/audit.log", base)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    // Who acted, e.g. "app:1234" for an integration.
+    pub actor: String,
+    // Whose data it touched.
+    pub user_id: String,
+    pub action: String,
+    pub detail: String,
+}
+
+// Append-only JSON-lines log. Entries are never rewritten or pruned here.
+pub struct AuditLog {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn record(&self, entry: &AuditEntry) -> Result<(), String> {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+        let _guard = self.write_lock.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open audit log {}: {}", self.path.display(), e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to append to audit log: {}", e))
+    }
+
+    // Oldest first. A missing file is an empty log.
+    pub fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let raw = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read audit log {}: {}", self.path.display(), e))?;
+        raw.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|e| format!("Unreadable audit entry: {}", e))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use memory_db::DB;
+
+use crate::models::notification::Notification;
+use crate::service::notification_service::user_notifications;
+
+// A day with this many reminders already is worth pointing out.
+pub const BUSY_DAY_THRESHOLD: usize = 5;
+
+// How many alternative days the approval prompt offers.
+const MAX_ALTERNATIVES: usize = 3;
+
+// What a user already has on their plate, with days read in their own
+// timezone.
+pub struct DayLoad<'a> {
+    pub db: &'a DB<Notification>,
+    pub user_id: &'a str,
+    pub zone: Tz,
+    // Todos carry no date, so the open ones count towards today.
+    pub open_todos: usize,
+    pub now: DateTime<Utc>,
+}
+
+impl DayLoad<'_> {
+    pub fn day_of(&self, time: DateTime<Utc>) -> NaiveDate {
+        time.with_timezone(&self.zone).date_naive()
+    }
+
+    pub fn on(&self, day: NaiveDate) -> usize {
+        let reminders = user_notifications(self.db, self.user_id)
+            .into_iter()
+            .filter_map(|n| n.event_time.or_else(|| n.notification_times.last().copied()))
+            .filter(|time| self.day_of(*time) == day)
+            .count();
+        let todos = if day == self.day_of(self.now) { self.open_todos } else { 0 };
+        reminders + todos
+    }
+
+    // Same time of day within a week either side, future only, lightest and
+    // then closest first.
+    pub fn lighter_days(&self, time: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let mut candidates: Vec<(usize, i64, DateTime<Utc>)> = (1..=7i64)
+            .flat_map(|offset| [offset, -offset])
+            .map(|offset| (offset, time + Duration::days(offset)))
+            .filter(|(_, candidate)| *candidate > self.now)
+            .map(|(offset, candidate)| (self.on(self.day_of(candidate)), offset.abs(), candidate))
+            .filter(|(load, _, _)| *load < BUSY_DAY_THRESHOLD)
+            .collect();
+        candidates.sort_by_key(|(load, distance, candidate)| (*load, *distance, *candidate));
+        let mut picked: Vec<DateTime<Utc>> = candidates
+            .into_iter()
+            .take(MAX_ALTERNATIVES)
+            .map(|(_, _, candidate)| candidate)
+            .collect();
+        picked.sort();
+        picked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn reminder(id: &str, at: DateTime<Utc>) -> Notification {
+        Notification {
+            id: id.to_string(),
+            content: id.to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![at - Duration::hours(1)],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: Some(at),
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        }
+    }
+
+    #[test]
+    fn lighter_days_skip_busy_and_past_days() {
+        let now = Utc.with_ymd_and_hms(2026, 2, 10, 8, 0, 0).unwrap();
+        let friday = Utc.with_ymd_and_hms(2026, 2, 13, 12, 0, 0).unwrap();
+        let mut db: DB<Notification> = HashMap::new();
+        for i in 0..6 {
+            let id = format!("fri{}", i);
+            db.insert(id.clone(), reminder(&id, friday + Duration::minutes(i)));
+        }
+        for i in 0..5 {
+            let id = format!("sat{}", i);
+            db.insert(id.clone(), reminder(&id, friday + Duration::days(1) + Duration::minutes(i)));
+        }
+
+        let load = DayLoad {
+            db: &db,
+            user_id: "@u",
+            zone: chrono_tz::UTC,
+            open_todos: 0,
+            now,
+        };
+        assert_eq!(load.on(friday.date_naive()), 6);
+        let days = load.lighter_days(friday);
+        assert_eq!(
+            days,
+            vec![
+                friday - Duration::days(2),
+                friday - Duration::days(1),
+                friday + Duration::days(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn days_follow_the_users_zone_and_count_todos_today() {
+        // 21:00 in New York on the 10th is already the 11th in UTC.
+        let now = Utc.with_ymd_and_hms(2026, 2, 10, 14, 0, 0).unwrap();
+        let evening = Utc.with_ymd_and_hms(2026, 2, 11, 2, 0, 0).unwrap();
+        let mut db: DB<Notification> = HashMap::new();
+        db.insert("late".to_string(), reminder("late", evening));
+        let load = DayLoad {
+            db: &db,
+            user_id: "@u",
+            zone: chrono_tz::America::New_York,
+            open_todos: 3,
+            now,
+        };
+
+        let today = load.day_of(now);
+        assert_eq!(load.day_of(evening), today);
+        assert_eq!(load.on(today), 4);
+        assert_eq!(load.on(today.succ_opt().unwrap()), 0);
+    }
+}
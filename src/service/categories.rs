@@ -0,0 +1,69 @@
+use crate::models::settings::{CategoryStyle, UserSettings};
+
+// Categories a reminder can be styled by, in the order they are matched.
+pub const CATEGORIES: [&str; 5] = ["bill", "birthday", "meeting", "health", "chore"];
+
+const KEYWORDS: [(&str, &[&str]); 5] = [
+    ("bill", &["bill", "rent", "pay", "invoice", "payment", "subscription", "tax", "taxes"]),
+    ("birthday", &["birthday", "bday", "anniversary"]),
+    ("meeting", &["meeting", "standup", "sync", "interview", "1:1"]),
+    ("health", &["medication", "meds", "pill", "pills", "doctor", "dentist", "appointment"]),
+    ("chore", &["laundry", "trash", "dishes", "clean", "groceries", "plants"]),
+];
+
+// Picks a category from the reminder text by whole-word keyword match.
+pub fn categorize(content: &str) -> Option<&'static str> {
+    let lower = content.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !(c.is_alphanumeric() || c == ':'))
+        .filter(|w| !w.is_empty())
+        .collect();
+    KEYWORDS
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|k| words.contains(k)))
+        .map(|(category, _)| *category)
+}
+
+pub fn style_for<'a>(content: &str, settings: Option<&'a UserSettings>) -> Option<&'a CategoryStyle> {
+    let category = categorize(content)?;
+    settings?.styles.get(category)
+}
+
+// Emoji prefix for the delivery text; embed color and GIF are applied by the sender.
+pub fn apply_style_prefix(body: &str, style: Option<&CategoryStyle>) -> String {
+    match style.and_then(|s| s.emoji.as_deref()) {
+        Some(emoji) if !emoji.trim().is_empty() => format!("{} {}", emoji.trim(), body),
+        _ => body.to_string(),
+    }
+}
+
+// Accepts "#ff8800", "ff8800" or "0xff8800".
+pub fn parse_color(value: &str) -> Option<u32> {
+    let hex = value
+        .trim()
+        .trim_start_matches('#')
+        .trim_start_matches("0x");
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categorize_matches_whole_words() {
+        assert_eq!(categorize("Pay rent on the 1st"), Some("bill"));
+        assert_eq!(categorize("Sam's birthday dinner"), Some("birthday"));
+        assert_eq!(categorize("repay Alex"), None);
+    }
+
+    #[test]
+    fn parse_color_accepts_common_hex_forms() {
+        assert_eq!(parse_color("#ff8800"), Some(0xff8800));
+        assert_eq!(parse_color("0x00ff00"), Some(0x00ff00));
+        assert_eq!(parse_color("orange"), None);
+    }
+}
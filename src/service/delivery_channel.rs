@@ -0,0 +1,449 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use serenity::async_trait;
+use serenity::builder::CreateActionRow;
+use serenity::http::Http;
+use serenity::model::id::UserId;
+
+use crate::i18n::{self, Locale, MessageKey};
+use crate::models::notification::{DeliveryTarget, Notification};
+use crate::models::settings::CategoryStyle;
+use crate::service::{categories, focus};
+use crate::tasks::notification_loop::MessageSender;
+
+// A rendered ping on its way out. Discord shows the style and buttons; the
+// other channels only use the text.
+pub struct OutgoingMessage<'a> {
+    pub notification: &'a Notification,
+    // When the ping was due.
+    pub due: DateTime<Utc>,
+    pub content: &'a str,
+    pub locale: Locale,
+    pub style: Option<&'a CategoryStyle>,
+    pub components: Vec<CreateActionRow>,
+}
+
+// One way of delivering pings. The notification loop sends each ping to every
+// target of the reminder through the first channel that handles it.
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    fn handles(&self, target: &DeliveryTarget) -> bool;
+
+    async fn deliver(&self, target: &DeliveryTarget, message: &OutgoingMessage<'_>) -> Result<(), String>;
+
+    // Called for every ping that went out, whatever its targets, for
+    // channels that mirror all of them somewhere.
+    async fn delivered(&self, _message: &OutgoingMessage<'_>) {}
+}
+
+async fn send_to_discord<S: MessageSender + ?Sized>(
+    sender: &S,
+    channel_id: &str,
+    message: &OutgoingMessage<'_>,
+) -> Result<(), String> {
+    if let Some(style) = message.style {
+        sender
+            .send_styled(channel_id, message.content, style, message.components.clone())
+            .await
+    } else if !message.components.is_empty() {
+        sender
+            .send_with_components(channel_id, message.content, message.components.clone())
+            .await
+    } else {
+        sender.send_message(channel_id, message.content).await
+    }
+}
+
+// Discord channels, through the loop's (rate limited) sender.
+pub struct DiscordChannel<'a, S: ?Sized>(pub &'a S);
+
+#[async_trait]
+impl<S: MessageSender + ?Sized> NotificationChannel for DiscordChannel<'_, S> {
+    fn handles(&self, target: &DeliveryTarget) -> bool {
+        matches!(target, DeliveryTarget::Channel(_))
+    }
+
+    async fn deliver(&self, target: &DeliveryTarget, message: &OutgoingMessage<'_>) -> Result<(), String> {
+        send_to_discord(self.0, target.address(), message).await
+    }
+}
+
+// Discord DMs: opens the DM channel, then sends through `sender` so DMs share
+// the channel sends' pacing.
+pub struct DiscordDmChannel<S> {
+    http: Arc<Http>,
+    sender: Arc<S>,
+}
+
+impl<S: MessageSender> DiscordDmChannel<S> {
+    pub fn new(http: Arc<Http>, sender: Arc<S>) -> Self {
+        Self { http, sender }
+    }
+}
+
+#[async_trait]
+impl<S: MessageSender> NotificationChannel for DiscordDmChannel<S> {
+    fn handles(&self, target: &DeliveryTarget) -> bool {
+        matches!(target, DeliveryTarget::Dm(_))
+    }
+
+    async fn deliver(&self, target: &DeliveryTarget, message: &OutgoingMessage<'_>) -> Result<(), String> {
+        let user = target
+            .address()
+            .trim_start_matches('@')
+            .parse::<u64>()
+            .map(UserId::new)
+            .map_err(|_| "Failed to parse user id".to_string())?;
+        let channel = user
+            .create_dm_channel(&*self.http)
+            .await
+            .map_err(|e| format!("Failed to create DM channel: {:?}", e))?;
+        send_to_discord(self.sender.as_ref(), &channel.id.to_string(), message).await
+    }
+}
+
+// Labels for integrations to filter on: the category plus "urgent",
+// "recurring" and "checklist" where they apply.
+pub fn tags(notification: &Notification) -> Vec<&'static str> {
+    let mut tags: Vec<&'static str> = categories::categorize(&notification.content).into_iter().collect();
+    if focus::is_urgent(&notification.content) {
+        tags.push("urgent");
+    }
+    if notification.recurrence.is_some() {
+        tags.push("recurring");
+    }
+    if !notification.checklist.is_empty() {
+        tags.push("checklist");
+    }
+    tags
+}
+
+// What webhook targets and sinks receive.
+pub fn webhook_body(message: &OutgoingMessage<'_>) -> serde_json::Value {
+    let notification = message.notification;
+    json!({
+        "notification_id": notification.id,
+        "content": notification.content,
+        "message": message.content,
+        "time": message.due,
+        "event_time": notification.event_time,
+        "user": notification.notify.first(),
+        "notify": notification.notify,
+        "tags": tags(notification),
+    })
+}
+
+// POSTs the ping as JSON to the target URL; anything but a 2xx is a failure.
+#[derive(Default)]
+pub struct WebhookChannel {
+    client: reqwest::Client,
+}
+
+impl WebhookChannel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    fn handles(&self, target: &DeliveryTarget) -> bool {
+        matches!(target, DeliveryTarget::Webhook(_))
+    }
+
+    async fn deliver(&self, target: &DeliveryTarget, message: &OutgoingMessage<'_>) -> Result<(), String> {
+        let response = self
+            .client
+            .post(target.address())
+            .json(&webhook_body(message))
+            .send()
+            .await
+            .map_err(|e| format!("Webhook request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Webhook answered {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+// Posts every ping that went out to the configured URLs, for Home Assistant,
+// n8n and the like. Best effort: posts run in the background, and a failed
+// one is logged and not retried, so a slow endpoint can't hold up delivery.
+pub struct WebhookSink {
+    urls: Vec<String>,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new(urls: Vec<String>) -> Self {
+        Self {
+            urls,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookSink {
+    fn handles(&self, _target: &DeliveryTarget) -> bool {
+        false
+    }
+
+    async fn deliver(&self, target: &DeliveryTarget, _message: &OutgoingMessage<'_>) -> Result<(), String> {
+        Err(format!("the webhook sink does not deliver to {} targets", target.kind()))
+    }
+
+    async fn delivered(&self, message: &OutgoingMessage<'_>) {
+        let body = webhook_body(message);
+        for url in &self.urls {
+            let request = self.client.post(url).json(&body);
+            let url = url.clone();
+            tokio::spawn(async move {
+                match request.send().await {
+                    Ok(response) if !response.status().is_success() => {
+                        tracing::warn!(%url, status = %response.status(), "webhook sink rejected a ping");
+                    }
+                    Ok(_) => {}
+                    Err(err) => tracing::warn!(%url, error = %err, "failed to post a ping to the webhook sink"),
+                }
+            });
+        }
+    }
+}
+
+// An HTTP mail API taking {from, to, subject, text} with a bearer token,
+// such as Resend's /emails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailConfig {
+    pub api_url: String,
+    pub api_token: String,
+    pub from: String,
+}
+
+pub struct EmailChannel {
+    config: EmailConfig,
+    client: reqwest::Client,
+}
+
+impl EmailChannel {
+    pub fn new(config: EmailConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+// "Reminder: <first line of the reminder>", for email subjects and push titles.
+fn title(message: &OutgoingMessage<'_>) -> String {
+    let topic = message.notification.content.lines().next().unwrap_or_default();
+    i18n::render(message.locale, MessageKey::MessageTitle, &[("content", topic)])
+}
+
+pub fn email_body(from: &str, to: &str, message: &OutgoingMessage<'_>) -> serde_json::Value {
+    json!({
+        "from": from,
+        "to": [to],
+        "subject": title(message),
+        "text": message.content,
+    })
+}
+
+#[async_trait]
+impl NotificationChannel for EmailChannel {
+    fn handles(&self, target: &DeliveryTarget) -> bool {
+        matches!(target, DeliveryTarget::Email(_))
+    }
+
+    async fn deliver(&self, target: &DeliveryTarget, message: &OutgoingMessage<'_>) -> Result<(), String> {
+        let response = self
+            .client
+            .post(&self.config.api_url)
+            .bearer_auth(&self.config.api_token)
+            .json(&email_body(&self.config.from, target.address(), message))
+            .send()
+            .await
+            .map_err(|e| format!("Email request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Email API answered {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+pub const DEFAULT_NTFY_SERVER: &str = "https://ntfy.sh";
+
+// Publishes to ntfy topics as JSON, so titles and tags needn't fit in
+// headers. Urgent reminders go out at high priority.
+pub struct NtfyChannel {
+    server: String,
+    token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl NtfyChannel {
+    pub fn new(server: String) -> Self {
+        Self {
+            server: server.trim_end_matches('/').to_string(),
+            token: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    // For servers that require an access token to publish.
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+}
+
+pub fn ntfy_body(topic: &str, message: &OutgoingMessage<'_>) -> serde_json::Value {
+    let urgent = focus::is_urgent(&message.notification.content);
+    json!({
+        "topic": topic,
+        "title": title(message),
+        "message": message.content,
+        "tags": tags(message.notification),
+        "priority": if urgent { 4 } else { 3 },
+    })
+}
+
+#[async_trait]
+impl NotificationChannel for NtfyChannel {
+    fn handles(&self, target: &DeliveryTarget) -> bool {
+        matches!(target, DeliveryTarget::Ntfy(_))
+    }
+
+    async fn deliver(&self, target: &DeliveryTarget, message: &OutgoingMessage<'_>) -> Result<(), String> {
+        let mut request = self.client.post(&self.server).json(&ntfy_body(target.address(), message));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(|e| format!("ntfy request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("ntfy answered {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+const PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+
+// Sends through the bot's Pushover application to each user's key.
+pub struct PushoverChannel {
+    app_token: String,
+    client: reqwest::Client,
+}
+
+impl PushoverChannel {
+    pub fn new(app_token: String) -> Self {
+        Self {
+            app_token,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+pub fn pushover_body(app_token: &str, user_key: &str, message: &OutgoingMessage<'_>) -> serde_json::Value {
+    let urgent = focus::is_urgent(&message.notification.content);
+    json!({
+        "token": app_token,
+        "user": user_key,
+        "title": title(message),
+        "message": message.content,
+        "priority": if urgent { 1 } else { 0 },
+    })
+}
+
+#[async_trait]
+impl NotificationChannel for PushoverChannel {
+    fn handles(&self, target: &DeliveryTarget) -> bool {
+        matches!(target, DeliveryTarget::Pushover(_))
+    }
+
+    async fn deliver(&self, target: &DeliveryTarget, message: &OutgoingMessage<'_>) -> Result<(), String> {
+        let response = self
+            .client
+            .post(PUSHOVER_URL)
+            .json(&pushover_body(&self.app_token, target.address(), message))
+            .send()
+            .await
+            .map_err(|e| format!("Pushover request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Pushover answered {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reminder() -> Notification {
+        Notification {
+            id: "n1".to_string(),
+            content: "Dentist\nbring the forms".to_string(),
+            notify: vec!["@42".to_string()],
+            notification_times: Vec::new(),
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: vec![
+                DeliveryTarget::Email("me@example.com".to_string()),
+                DeliveryTarget::Channel("123".to_string()),
+            ],
+            delivery_retry: None,
+        }
+    }
+
+    #[test]
+    fn targets_start_with_the_channel_and_parse_from_json() {
+        let notification = reminder();
+        assert_eq!(
+            notification.delivery_targets(),
+            vec![
+                DeliveryTarget::Channel("123".to_string()),
+                DeliveryTarget::Email("me@example.com".to_string()),
+            ]
+        );
+        let parsed: Vec<DeliveryTarget> =
+            serde_json::from_str(r#"[{"kind": "dm", "to": "@42"}, {"kind": "webhook", "to": "https://x.test/hook"}]"#)
+                .unwrap();
+        assert_eq!(parsed[0], DeliveryTarget::Dm("@42".to_string()));
+        assert_eq!(parsed[1].kind(), "webhook");
+    }
+
+    #[test]
+    fn payloads_are_titled_with_the_first_line_of_the_reminder() {
+        let notification = reminder();
+        let message = OutgoingMessage {
+            notification: &notification,
+            due: chrono::Utc::now(),
+            content: "Don't forget the dentist!",
+            locale: Locale::En,
+            style: None,
+            components: Vec::new(),
+        };
+        let body = email_body("bot@example.com", "me@example.com", &message);
+        assert_eq!(body["to"][0], "me@example.com");
+        assert_eq!(body["subject"], "Reminder: Dentist");
+        assert_eq!(body["text"], "Don't forget the dentist!");
+        let hook = webhook_body(&message);
+        assert_eq!(hook["notification_id"], "n1");
+        assert_eq!(hook["user"], "@42");
+        assert_eq!(hook["tags"], json!(["health", "urgent"]));
+        let push = ntfy_body("alex-reminders", &message);
+        assert_eq!(push["topic"], "alex-reminders");
+        assert_eq!(push["title"], "Reminder: Dentist");
+        assert_eq!(push["priority"], 4);
+        assert_eq!(pushover_body("app", "ukey", &message)["priority"], 1);
+    }
+}
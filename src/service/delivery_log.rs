@@ -0,0 +1,252 @@
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::notification::Notification;
+
+// Returns the file the notification loop reports what it did to.
+// Defaults to a relative "./data/deliveries.log" file.
+pub fn get_delivery_log_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/deliveries.log", base)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryKind {
+    // A ping came due and is about to be sent.
+    Attempt,
+    Delivered,
+    // Due, but kept back for the owner's focus digest.
+    Held,
+    // Due in the owner's quiet hours; moved to when they end.
+    Deferred,
+    // Missed while the bot was down, for an event that is already over.
+    Skipped,
+    Failed,
+    Escalated,
+    // Removed from the reminders; nothing is left to send.
+    Expired,
+}
+
+impl DeliveryKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            DeliveryKind::Attempt => "attempt",
+            DeliveryKind::Delivered => "delivered",
+            DeliveryKind::Held => "held",
+            DeliveryKind::Deferred => "deferred",
+            DeliveryKind::Skipped => "skipped",
+            DeliveryKind::Failed => "failed",
+            DeliveryKind::Escalated => "escalated",
+            DeliveryKind::Expired => "expired",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryRecord {
+    pub at: DateTime<Utc>,
+    pub kind: DeliveryKind,
+    pub notification_id: String,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub channel_id: String,
+    pub content: String,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+impl DeliveryRecord {
+    pub fn new(kind: DeliveryKind, notification: &Notification, at: DateTime<Utc>) -> Self {
+        Self {
+            at,
+            kind,
+            notification_id: notification.id.clone(),
+            user_id: notification.notify.first().cloned(),
+            channel_id: notification.channel.clone(),
+            content: notification.content.clone(),
+            detail: None,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+// Append-only JSON-lines log written by the notification loop, so another
+// process (`watch`) can follow deliveries as they happen.
+pub struct DeliveryLog {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl DeliveryLog {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn record(&self, record: &DeliveryRecord) -> Result<(), String> {
+        let line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to serialize delivery record: {}", e))?;
+        let _guard = self.write_lock.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open delivery log {}: {}", self.path.display(), e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to append to delivery log: {}", e))
+    }
+
+    // Best effort, as with the event log.
+    pub fn log(&self, record: DeliveryRecord) {
+        if let Err(err) = self.record(&record) {
+            tracing::warn!(error = %err, "failed to write delivery log");
+        }
+    }
+
+    // The size of the log now, for starting a watch at the end.
+    pub fn end(&self) -> u64 {
+        fs::metadata(&self.path).map(|meta| meta.len()).unwrap_or(0)
+    }
+
+    // Records written since byte `offset`, and the offset to read from next.
+    // A line still being written is left for the next read; a log that shrank
+    // (deleted or replaced) is read again from the start.
+    pub fn read_from(&self, offset: u64) -> Result<(Vec<DeliveryRecord>, u64), String> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(_) => return Ok((Vec::new(), 0)),
+        };
+        let len = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        let offset = if len < offset { 0 } else { offset };
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| format!("Failed to read delivery log {}: {}", self.path.display(), e))?;
+        let mut raw = String::new();
+        file.read_to_string(&mut raw)
+            .map_err(|e| format!("Failed to read delivery log {}: {}", self.path.display(), e))?;
+        let complete = raw.rfind('\n').map(|at| at + 1).unwrap_or(0);
+        let records = raw[..complete]
+            .lines()
+            .filter_map(|line| serde_json::from_str::<DeliveryRecord>(line).ok())
+            .collect();
+        Ok((records, offset + complete as u64))
+    }
+}
+
+// One line per record for `watch`.
+pub fn render_delivery(record: &DeliveryRecord) -> String {
+    let mut line = format!(
+        "{} {:<9} {} channel={}",
+        record.at.to_rfc3339(),
+        record.kind.code(),
+        record.notification_id,
+        record.channel_id
+    );
+    if let Some(user_id) = &record.user_id {
+        line.push_str(&format!(" user={}", user_id));
+    }
+    line.push_str(&format!(" \"{}\"", record.content));
+    if let Some(detail) = &record.detail {
+        line.push_str(&format!(" — {}", detail));
+    }
+    line
+}
+
+const WATCH_POLL: StdDuration = StdDuration::from_millis(500);
+
+// `watch [--user <id>] [--from-start]`: prints the notification loop's
+// deliveries, holds, failures and expirations as a running worker logs them,
+// until interrupted. Starts at the end of the log unless --from-start.
+pub async fn watch_command(args: &[String]) -> Result<(), String> {
+    let mut user_id: Option<String> = None;
+    let mut from_start = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--user" => user_id = Some(args.next().ok_or("--user needs a user id")?.clone()),
+            "--from-start" => from_start = true,
+            other => return Err(format!("Unknown option '{}'. Usage: watch [--user <id>] [--from-start]", other)),
+        }
+    }
+    let log = DeliveryLog::new(get_delivery_log_location());
+    let mut offset = if from_start { 0 } else { log.end() };
+    println!("Watching {} (Ctrl-C to stop)", log.path.display());
+    loop {
+        let (records, next) = log.read_from(offset)?;
+        offset = next;
+        for record in records
+            .iter()
+            .filter(|record| user_id.is_none() || record.user_id == user_id)
+        {
+            println!("{}", render_delivery(record));
+        }
+        tokio::time::sleep(WATCH_POLL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn notification() -> Notification {
+        Notification {
+            id: "r1".to_string(),
+            content: "call mom".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: Vec::new(),
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        }
+    }
+
+    #[test]
+    fn read_from_picks_up_only_new_complete_lines() {
+        let path = env::temp_dir().join(format!("deliveries_{}.log", uuid::Uuid::new_v4()));
+        let log = DeliveryLog::new(&path);
+        let at = Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap();
+        assert_eq!(log.read_from(0).unwrap(), (Vec::new(), 0));
+
+        log.log(DeliveryRecord::new(DeliveryKind::Attempt, &notification(), at));
+        let (records, offset) = log.read_from(0).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(offset, log.end());
+
+        log.log(DeliveryRecord::new(DeliveryKind::Failed, &notification(), at).with_detail("timeout"));
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{{\"at\":").unwrap();
+        let (records, next) = log.read_from(offset).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, DeliveryKind::Failed);
+        assert!(render_delivery(&records[0]).ends_with("user=@u \"call mom\" — timeout"));
+        assert!(next < log.end());
+
+        fs::write(&path, "").unwrap();
+        assert_eq!(log.read_from(next).unwrap(), (Vec::new(), 0));
+        let _ = fs::remove_file(path);
+    }
+}
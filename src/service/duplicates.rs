@@ -0,0 +1,110 @@
+use chrono::{DateTime, Duration, Utc};
+use memory_db::DB;
+use std::collections::HashSet;
+
+use crate::models::notification::Notification;
+
+// How alike two reminders' words must be, and how close in time, before a
+// new draft is flagged as a possible repeat of an existing one.
+pub const SIMILARITY_THRESHOLD: f32 = 0.6;
+pub const TIME_WINDOW_DAYS: i64 = 3;
+
+const FILLER_WORDS: [&str; 12] = [
+    "remind", "me", "to", "the", "a", "an", "my", "about", "for", "on", "at", "please",
+];
+
+fn words(content: &str) -> HashSet<String> {
+    content
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !FILLER_WORDS.contains(word))
+        .map(str::to_string)
+        .collect()
+}
+
+// Shared words over all words, ignoring case, punctuation and filler.
+pub fn similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (words(a), words(b));
+    let all = a.union(&b).count();
+    if all == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f32 / all as f32
+}
+
+// When an existing reminder is next due: its event, or its next ping.
+pub fn scheduled_for(notification: &Notification) -> Option<DateTime<Utc>> {
+    notification
+        .event_time
+        .or_else(|| notification.notification_times.first().copied())
+}
+
+// The user's closest match to a new reminder for `content` at `time`, if any
+// is alike enough and scheduled within a few days of it.
+pub fn find_similar<'a>(
+    db: &'a DB<Notification>,
+    user_id: &str,
+    content: &str,
+    time: DateTime<Utc>,
+) -> Option<&'a Notification> {
+    db.values()
+        .filter(|n| n.completed_at.is_none() && n.notify.iter().any(|u| u == user_id))
+        .filter(|n| {
+            scheduled_for(n).is_some_and(|at| (at - time).abs() <= Duration::days(TIME_WINDOW_DAYS))
+        })
+        .map(|n| (similarity(&n.content, content), n))
+        .filter(|(score, _)| *score >= SIMILARITY_THRESHOLD)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, n)| n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn reminder(id: &str, content: &str, at: DateTime<Utc>) -> Notification {
+        Notification {
+            id: id.to_string(),
+            content: content.to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![at - Duration::hours(1)],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: Some(at),
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        }
+    }
+
+    #[test]
+    fn wording_differences_still_match() {
+        assert_eq!(similarity("Pay rent", "remind me to pay the rent!"), 1.0);
+        assert!(similarity("pay rent", "pay the electricity bill") < SIMILARITY_THRESHOLD);
+        assert_eq!(similarity("the", "a"), 0.0);
+    }
+
+    #[test]
+    fn only_the_users_nearby_reminders_count() {
+        let at = Utc.with_ymd_and_hms(2026, 2, 1, 9, 0, 0).unwrap();
+        let mut someone_else = reminder("other", "pay rent", at);
+        someone_else.notify = vec!["@other".to_string()];
+        let db: DB<Notification> = HashMap::from([
+            ("rent".to_string(), reminder("rent", "Pay rent", at)),
+            ("later".to_string(), reminder("later", "pay rent", at + Duration::days(30))),
+            ("other".to_string(), someone_else),
+            ("bill".to_string(), reminder("bill", "pay phone bill", at)),
+        ]);
+
+        let found = find_similar(&db, "@u", "pay the rent", at + Duration::days(1));
+        assert_eq!(found.map(|n| n.id.as_str()), Some("rent"));
+        assert!(find_similar(&db, "@u", "pay rent", at + Duration::days(10)).is_none());
+        assert!(find_similar(&db, "@u", "water the plants", at).is_none());
+    }
+}
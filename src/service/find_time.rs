@@ -0,0 +1,260 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use chrono_tz::Tz;
+
+pub const DEFAULT_MINUTES: i64 = 30;
+// Slots are offered on the half hour inside these local hours.
+const DAY_START_HOUR: u32 = 9;
+const DAY_END_HOUR: u32 = 18;
+const SLOT_STEP_MINUTES: i64 = 30;
+pub const MAX_SLOTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayHint {
+    Today,
+    Tomorrow,
+    // The next such day, today included.
+    Weekday(Weekday),
+}
+
+// "find 30 minutes tomorrow for a dentist call": how long, which day and
+// what the slot is for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FindTimeRequest {
+    pub minutes: i64,
+    pub day: DayHint,
+    pub title: String,
+    // The text also asked for the slot to go on the calendar.
+    pub add_to_calendar: bool,
+}
+
+fn day_hint(word: &str) -> Option<DayHint> {
+    let word = word.trim_end_matches([',', '.', '?', '!']).to_lowercase();
+    match word.as_str() {
+        "today" => Some(DayHint::Today),
+        "tomorrow" => Some(DayHint::Tomorrow),
+        _ => word.parse::<Weekday>().ok().map(DayHint::Weekday),
+    }
+}
+
+fn unit_minutes(word: &str) -> Option<i64> {
+    match word.to_lowercase().as_str() {
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(1),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(60),
+        _ => None,
+    }
+}
+
+// Reads the length at the start of `words` ("30 minutes", "45min", "an
+// hour", "half an hour", "time"), returning it and how many words it took.
+fn parse_length(words: &[&str]) -> Option<(i64, usize)> {
+    let lower: Vec<String> = words.iter().take(3).map(|word| word.to_lowercase()).collect();
+    let lower: Vec<&str> = lower.iter().map(String::as_str).collect();
+    match lower.as_slice() {
+        ["half", "an", "hour", ..] => return Some((30, 3)),
+        ["an" | "a", "hour", ..] => return Some((60, 2)),
+        ["a" | "some", "time", ..] => return Some((DEFAULT_MINUTES, 2)),
+        ["time", ..] => return Some((DEFAULT_MINUTES, 1)),
+        _ => {}
+    }
+    let first = lower.first()?;
+    let digits: String = first.chars().take_while(char::is_ascii_digit).collect();
+    let amount = digits.parse::<i64>().ok().filter(|n| *n > 0)?;
+    match &first[digits.len()..] {
+        "" => Some((amount * unit_minutes(lower.get(1)?)?, 2)),
+        unit => Some((amount * unit_minutes(unit)?, 1)),
+    }
+}
+
+// Recognises "find [me] <length> [day] for|to <what>", with the day also
+// allowed at the end. Anything else, e.g. "find my keys", is not a request
+// for a time.
+pub fn parse_find_time(text: &str) -> Option<FindTimeRequest> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if !words.first()?.eq_ignore_ascii_case("find") {
+        return None;
+    }
+    let mut rest = &words[1..];
+    if rest.first().is_some_and(|word| word.eq_ignore_ascii_case("me")) {
+        rest = &rest[1..];
+    }
+    let (minutes, used) = parse_length(rest)?;
+    rest = &rest[used..];
+
+    let mut day = None;
+    while let Some(word) = rest.first() {
+        let lower = word.to_lowercase();
+        if lower == "for" || lower == "to" {
+            rest = &rest[1..];
+            break;
+        }
+        if let Some(hint) = day_hint(word) {
+            day = Some(hint);
+        }
+        rest = &rest[1..];
+    }
+
+    let mut title_words: Vec<&str> = rest.to_vec();
+    if let Some(at) = title_words.iter().position(|word| word.to_lowercase().starts_with("calendar")) {
+        // "... and add it to my calendar": everything from the joining word on
+        // is about the calendar, not the title.
+        let before = &title_words[..at];
+        let cut = before
+            .iter()
+            .rposition(|word| word.eq_ignore_ascii_case("and"))
+            .or_else(|| {
+                before
+                    .iter()
+                    .rposition(|word| matches!(word.to_lowercase().as_str(), "on" | "to" | "in"))
+            })
+            .unwrap_or(at);
+        title_words.truncate(cut);
+        return Some(finish(minutes, day, title_words, true));
+    }
+    Some(finish(minutes, day, title_words, false))
+}
+
+fn finish(minutes: i64, mut day: Option<DayHint>, mut title: Vec<&str>, add_to_calendar: bool) -> FindTimeRequest {
+    if let Some(hint) = title.last().and_then(|word| day_hint(word)) {
+        day = day.or(Some(hint));
+        title.pop();
+        if title.last().is_some_and(|word| word.eq_ignore_ascii_case("on")) {
+            title.pop();
+        }
+    }
+    if title
+        .first()
+        .is_some_and(|word| matches!(word.to_lowercase().as_str(), "a" | "an" | "the" | "my"))
+    {
+        title.remove(0);
+    }
+    let title = title.join(" ");
+    FindTimeRequest {
+        minutes,
+        day: day.unwrap_or(DayHint::Today),
+        title: if title.is_empty() { "Busy".to_string() } else { title },
+        add_to_calendar,
+    }
+}
+
+// The date in `zone` the hint refers to.
+pub fn resolve_day(hint: DayHint, now: DateTime<Utc>, zone: Tz) -> NaiveDate {
+    let today = now.with_timezone(&zone).date_naive();
+    match hint {
+        DayHint::Today => today,
+        DayHint::Tomorrow => today + Duration::days(1),
+        DayHint::Weekday(weekday) => {
+            let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            today + Duration::days(ahead as i64)
+        }
+    }
+}
+
+fn local_time(day: NaiveDate, hour: u32, zone: Tz) -> Option<DateTime<Utc>> {
+    day.and_hms_opt(hour, 0, 0)?
+        .and_local_timezone(zone)
+        .earliest()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+// The UTC span of the working hours searched on `day` in `zone`, for
+// reading the calendar.
+pub fn search_window(day: NaiveDate, zone: Tz) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    Some((local_time(day, DAY_START_HOUR, zone)?, local_time(day, DAY_END_HOUR, zone)?))
+}
+
+// Up to MAX_SLOTS starts, earliest first, where `minutes` fit inside the
+// day's working hours without touching anything in `busy` or starting in
+// the past.
+pub fn free_slots(
+    day: NaiveDate,
+    minutes: i64,
+    busy: &[(DateTime<Utc>, DateTime<Utc>)],
+    now: DateTime<Utc>,
+    zone: Tz,
+) -> Vec<DateTime<Utc>> {
+    let Some((open, close)) = search_window(day, zone) else {
+        return Vec::new();
+    };
+    let length = Duration::minutes(minutes);
+    let mut slots = Vec::new();
+    let mut start = open;
+    while start + length <= close && slots.len() < MAX_SLOTS {
+        let end = start + length;
+        let clashes = busy.iter().any(|(from, to)| start < *to && end > *from);
+        if start >= now && !clashes {
+            slots.push(start);
+        }
+        start += Duration::minutes(SLOT_STEP_MINUTES);
+    }
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::America::New_York;
+    use chrono_tz::Europe::Berlin;
+
+    #[test]
+    fn parses_length_day_and_title() {
+        assert_eq!(
+            parse_find_time("find 30 minutes tomorrow for a dentist call"),
+            Some(FindTimeRequest {
+                minutes: 30,
+                day: DayHint::Tomorrow,
+                title: "dentist call".to_string(),
+                add_to_calendar: false,
+            })
+        );
+        assert_eq!(
+            parse_find_time("Find me an hour to do taxes on Friday and add it to my calendar"),
+            Some(FindTimeRequest {
+                minutes: 60,
+                day: DayHint::Weekday(Weekday::Fri),
+                title: "do taxes".to_string(),
+                add_to_calendar: true,
+            })
+        );
+        assert_eq!(parse_find_time("find 45min for gym").map(|r| (r.minutes, r.day)), Some((45, DayHint::Today)));
+        assert_eq!(parse_find_time("find my keys tomorrow"), None);
+        assert_eq!(parse_find_time("remind me to find 30 minutes"), None);
+    }
+
+    #[test]
+    fn free_slots_skip_busy_and_past_times() {
+        let day = NaiveDate::from_ymd_opt(2026, 2, 10).unwrap();
+        // 9:40am New York, with a meeting from 10:00 to 11:00.
+        let now = Utc.with_ymd_and_hms(2026, 2, 10, 14, 40, 0).unwrap();
+        let meeting = (
+            Utc.with_ymd_and_hms(2026, 2, 10, 15, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2026, 2, 10, 16, 0, 0).unwrap(),
+        );
+        assert_eq!(
+            free_slots(day, 30, &[meeting], now, New_York),
+            vec![
+                Utc.with_ymd_and_hms(2026, 2, 10, 16, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2026, 2, 10, 16, 30, 0).unwrap(),
+                Utc.with_ymd_and_hms(2026, 2, 10, 17, 0, 0).unwrap(),
+            ]
+        );
+        let late = Utc.with_ymd_and_hms(2026, 2, 10, 23, 0, 0).unwrap();
+        assert!(free_slots(day, 30, &[], late, New_York).is_empty());
+    }
+
+    #[test]
+    fn days_and_hours_are_the_users_own() {
+        // Already Wednesday in Berlin, still Tuesday in New York.
+        let now = Utc.with_ymd_and_hms(2026, 2, 10, 23, 30, 0).unwrap();
+        let wednesday = NaiveDate::from_ymd_opt(2026, 2, 11).unwrap();
+        assert_eq!(resolve_day(DayHint::Today, now, Berlin), wednesday);
+        assert_eq!(resolve_day(DayHint::Tomorrow, now, New_York), wednesday);
+        assert_eq!(
+            search_window(wednesday, Berlin),
+            Some((
+                Utc.with_ymd_and_hms(2026, 2, 11, 8, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2026, 2, 11, 17, 0, 0).unwrap(),
+            ))
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use chrono::Duration;
+
+use crate::service::categories;
+
+// Focus mode never holds health reminders (medication, appointments) or
+// anything the user marked urgent.
+pub fn is_urgent(content: &str) -> bool {
+    if categories::categorize(content) == Some("health") {
+        return true;
+    }
+    content
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.eq_ignore_ascii_case("urgent") || word.eq_ignore_ascii_case("asap"))
+}
+
+// Accepts "45m", "2h", "1h30m", "90 minutes" or "2 hours". Capped at a day so
+// a typo can't silence reminders for weeks.
+pub fn parse_focus_duration(text: &str) -> Option<Duration> {
+    let compact: String = text
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if compact.is_empty() {
+        return None;
+    }
+
+    let mut total = Duration::zero();
+    let mut rest = compact.as_str();
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+        let amount: i64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit = &rest[..unit_len];
+        rest = &rest[unit_len..];
+        total += match unit {
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(amount),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::hours(amount),
+            _ => return None,
+        };
+    }
+
+    (total > Duration::zero() && total <= Duration::days(1)).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_focus_duration_accepts_common_forms() {
+        assert_eq!(parse_focus_duration("45m"), Some(Duration::minutes(45)));
+        assert_eq!(parse_focus_duration("1h30m"), Some(Duration::minutes(90)));
+        assert_eq!(parse_focus_duration("2 hours"), Some(Duration::hours(2)));
+        assert_eq!(parse_focus_duration("soon"), None);
+        assert_eq!(parse_focus_duration("30"), None);
+        assert_eq!(parse_focus_duration("48h"), None);
+    }
+
+    #[test]
+    fn urgent_reminders_are_never_held() {
+        assert!(is_urgent("take my medication"));
+        assert!(is_urgent("URGENT: call the bank"));
+        assert!(!is_urgent("water the plants"));
+    }
+}
@@ -0,0 +1,493 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::America::New_York;
+use chrono_tz::Tz;
+use memory_db::{DB, load_db};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use crate::models::notification::{self, Notification, get_db_location};
+use crate::models::recurrence::{Frequency, Recurrence};
+use crate::tasks::calendar_loop::CalendarEvent;
+
+// Reminders have no length of their own; this is how long they show for.
+const EVENT_MINUTES: i64 = 15;
+
+// Most reminders one import creates; the rest of the file is ignored.
+pub const IMPORT_LIMIT: usize = 50;
+
+// A VEVENT read from an imported file: SUMMARY becomes the reminder text and
+// DTSTART its time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportedEvent {
+    pub summary: String,
+    pub start: DateTime<Utc>,
+}
+
+// Reminders still scheduled to fire, optionally just one user's, soonest
+// first.
+pub fn scheduled(db: &DB<Notification>, user_id: Option<&str>) -> Vec<&Notification> {
+    let mut notifications: Vec<&Notification> = db
+        .values()
+        .filter(|n| user_id.is_none_or(|user| n.notify.iter().any(|u| u == user)))
+        .filter(|n| !n.notification_times.is_empty())
+        .collect();
+    notifications.sort_by_key(|n| (start_of(n), n.id.clone()));
+    notifications
+}
+
+fn start_of(notification: &Notification) -> Option<DateTime<Utc>> {
+    notification
+        .event_time
+        .or_else(|| notification.notification_times.first().copied())
+}
+
+fn format_utc(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// RFC 5545 TEXT escaping.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Lines longer than 75 octets continue on the next line after a space,
+// never splitting a character.
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for ch in line.chars() {
+        if width + ch.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += ch.len_utf8();
+    }
+    out
+}
+
+// Skip dates are local, so each becomes the occurrence at the series' local
+// start time that day. Holiday skipping has no iCalendar equivalent and is
+// left out.
+fn rrule_lines(recurrence: &Recurrence, start: DateTime<Utc>) -> Vec<String> {
+    let freq = match recurrence.frequency {
+        Frequency::Daily => "DAILY",
+        Frequency::Weekly => "WEEKLY",
+        Frequency::Monthly => "MONTHLY",
+    };
+    let mut rule = format!("RRULE:FREQ={};INTERVAL={}", freq, recurrence.interval.max(1));
+    let local_time = start.with_timezone(&New_York).time();
+    let local_instant = |date: NaiveDate| {
+        date.and_time(local_time)
+            .and_local_timezone(New_York)
+            .earliest()
+            .map(|at| at.with_timezone(&Utc))
+    };
+    if let Some(until) = recurrence.until.and_then(local_instant) {
+        rule.push_str(&format!(";UNTIL={}", format_utc(until)));
+    } else if let Some(count) = recurrence.count {
+        rule.push_str(&format!(";COUNT={}", count));
+    }
+    let mut lines = vec![rule];
+    lines.extend(
+        recurrence
+            .skip_dates
+            .iter()
+            .filter_map(|date| local_instant(*date))
+            .map(|at| format!("EXDATE:{}", format_utc(at))),
+    );
+    lines
+}
+
+// An iCalendar feed with one event per reminder, so the reminders can be
+// subscribed to from a calendar app.
+pub fn render_calendar(notifications: &[&Notification], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//reminderBot//reminders//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Reminders".to_string(),
+        // Subscribed calendars otherwise refresh as rarely as once a day.
+        "REFRESH-INTERVAL;VALUE=DURATION:PT15M".to_string(),
+        "X-PUBLISHED-TTL:PT15M".to_string(),
+    ];
+    for notification in notifications {
+        let Some(start) = start_of(notification) else {
+            continue;
+        };
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@reminderbot", notification.id));
+        lines.push(format!("DTSTAMP:{}", format_utc(now)));
+        lines.push(format!("DTSTART:{}", format_utc(start)));
+        lines.push(format!(
+            "DTEND:{}",
+            format_utc(start + Duration::minutes(EVENT_MINUTES))
+        ));
+        lines.push(format!("SUMMARY:{}", escape(&notification.content)));
+        if !notification.checklist.is_empty() {
+            let items: Vec<&str> = notification.checklist.iter().map(|item| item.text.as_str()).collect();
+            lines.push(format!("DESCRIPTION:{}", escape(&format!("Bring: {}", items.join(", ")))));
+        }
+        if let Some(recurrence) = &notification.recurrence {
+            lines.extend(rrule_lines(recurrence, start));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    let mut body = lines.iter().map(|line| fold(line)).collect::<Vec<_>>().join("\r\n");
+    body.push_str("\r\n");
+    body
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+// Floating times and all-day dates are read in the bot's default zone;
+// all-day events are placed at noon, like dates without a time in /notify.
+fn parse_time(params: &str, value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|at| at.and_utc());
+    }
+    let zone = params
+        .split(';')
+        .find_map(|param| param.strip_prefix("TZID="))
+        .and_then(|tz| tz.trim_matches('"').parse::<Tz>().ok())
+        .unwrap_or(New_York);
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y%m%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(12, 0, 0))
+        })?;
+    zone.from_local_datetime(&local)
+        .earliest()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+#[derive(Default)]
+struct PendingEvent {
+    uid: Option<String>,
+    recurrence_id: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    all_day: bool,
+    cancelled: bool,
+}
+
+impl PendingEvent {
+    fn title(&self) -> String {
+        self.summary
+            .clone()
+            .filter(|summary| !summary.is_empty())
+            .unwrap_or_else(|| "(untitled event)".to_string())
+    }
+
+    fn into_import(self) -> Option<ImportedEvent> {
+        if self.cancelled {
+            return None;
+        }
+        Some(ImportedEvent {
+            summary: self.title(),
+            start: self.start?,
+        })
+    }
+
+    // Expanded occurrences of a repeating event share its UID, so the
+    // RECURRENCE-ID keeps their ids apart.
+    fn into_calendar_event(self) -> Option<CalendarEvent> {
+        if self.cancelled || self.all_day {
+            return None;
+        }
+        let start_time = self.start?;
+        let id = self.uid.as_ref().map(|uid| match &self.recurrence_id {
+            Some(recurrence_id) => format!("{}/{}", uid, recurrence_id),
+            None => uid.clone(),
+        });
+        Some(CalendarEvent {
+            id,
+            title: self.title(),
+            start_time,
+            end_time: self.end.unwrap_or(start_time),
+            description: self.description,
+        })
+    }
+}
+
+// Every VEVENT in the text, in order. Lines outside events are ignored, so
+// this also copes with several calendars run together.
+fn vevents(ics: &str) -> Vec<PendingEvent> {
+    let unfolded = ics
+        .replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+    let mut events = Vec::new();
+    let mut current: Option<PendingEvent> = None;
+    for line in unfolded.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+        let name = name.to_ascii_uppercase();
+        if name == "BEGIN" && value.eq_ignore_ascii_case("VEVENT") {
+            current = Some(PendingEvent::default());
+            continue;
+        }
+        if name == "END" && value.eq_ignore_ascii_case("VEVENT") {
+            events.extend(current.take());
+            continue;
+        }
+        let Some(event) = current.as_mut() else {
+            continue;
+        };
+        match name.as_str() {
+            "UID" => event.uid = Some(value.trim().to_string()),
+            "RECURRENCE-ID" => event.recurrence_id = Some(value.trim().to_string()),
+            "SUMMARY" => event.summary = Some(unescape(value).trim().to_string()),
+            "DESCRIPTION" => event.description = Some(unescape(value)),
+            "DTSTART" => {
+                event.start = parse_time(params, value);
+                event.all_day = value.trim().len() == 8;
+            }
+            "DTEND" => event.end = parse_time(params, value),
+            "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+    events
+}
+
+// Every VEVENT with a readable DTSTART, in file order. Cancelled events are
+// skipped and untitled ones get a placeholder.
+pub fn parse_calendar(ics: &str) -> Result<Vec<ImportedEvent>, String> {
+    if !ics.trim_start().starts_with("BEGIN:VCALENDAR") {
+        return Err("That isn't an iCalendar (.ics) file.".to_string());
+    }
+    Ok(vevents(ics)
+        .into_iter()
+        .filter_map(PendingEvent::into_import)
+        .collect())
+}
+
+// Timed events as the calendar sync sees them. Like Google's all-day
+// entries, events without a time are left out.
+pub fn calendar_events(ics: &str) -> Vec<CalendarEvent> {
+    vevents(ics)
+        .into_iter()
+        .filter_map(PendingEvent::into_calendar_event)
+        .collect()
+}
+
+// A single-event calendar object, as stored by CalDAV servers.
+pub fn render_event(event: &CalendarEvent, uid: &str, now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//reminderBot//reminders//EN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", format_utc(now)),
+        format!("DTSTART:{}", format_utc(event.start_time)),
+        format!("DTEND:{}", format_utc(event.end_time)),
+        format!("SUMMARY:{}", escape(&event.title)),
+    ];
+    if let Some(description) = &event.description {
+        lines.push(format!("DESCRIPTION:{}", escape(description)));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+    let mut body = lines.iter().map(|line| fold(line)).collect::<Vec<_>>().join("\r\n");
+    body.push_str("\r\n");
+    body
+}
+
+// The events worth a reminder: upcoming ones, soonest first, at most
+// IMPORT_LIMIT of them.
+pub fn importable(mut events: Vec<ImportedEvent>, now: DateTime<Utc>) -> Vec<ImportedEvent> {
+    events.retain(|event| event.start > now);
+    events.sort_by_key(|event| event.start);
+    events.truncate(IMPORT_LIMIT);
+    events
+}
+
+pub fn render_import_preview(events: &[ImportedEvent]) -> String {
+    let mut body = format!("{} reminders to import:", events.len());
+    for event in events {
+        body.push_str(&format!(
+            "\n- {} {}",
+            event.start.with_timezone(&New_York).format("%a %b %-d %-I:%M%P %Z"),
+            event.summary
+        ));
+    }
+    body
+}
+
+// `import --ics <file> --user <id> --channel <id> [--yes]`: previews the
+// reminders a calendar file would create and, once confirmed, saves them.
+pub async fn import_command(args: &[String]) -> Result<String, String> {
+    let mut path: Option<&str> = None;
+    let mut user_id: Option<&str> = None;
+    let mut channel_id: Option<&str> = None;
+    let mut yes = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ics" => path = Some(args.next().ok_or("--ics needs a file")?.as_str()),
+            "--user" => user_id = Some(args.next().ok_or("--user needs a user id")?.as_str()),
+            "--channel" => channel_id = Some(args.next().ok_or("--channel needs a channel id")?.as_str()),
+            "--yes" => yes = true,
+            other => return Err(format!("Unknown option '{}'", other)),
+        }
+    }
+    let usage = "Usage: import --ics <file> --user <id> --channel <id> [--yes]";
+    let (Some(path), Some(user_id), Some(channel_id)) = (path, user_id, channel_id) else {
+        return Err(usage.to_string());
+    };
+    let raw = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let events = importable(parse_calendar(&raw)?, Utc::now());
+    if events.is_empty() {
+        return Ok("No upcoming events found.".to_string());
+    }
+    println!("{}", render_import_preview(&events));
+    if !yes {
+        print!("Create these reminders? [y/N] ");
+        io::stdout().flush().map_err(|e| e.to_string())?;
+        let mut answer = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut answer)
+            .map_err(|e| e.to_string())?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            return Ok("Nothing imported.".to_string());
+        }
+    }
+    let mut db: DB<Notification> = load_db(&get_db_location()).unwrap_or_else(|_| HashMap::new());
+    for event in &events {
+        notification::create_notification(
+            &mut db,
+            &event.summary,
+            &user_id.to_string(),
+            &event.start,
+            &channel_id.to_string(),
+            None,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(format!("Imported {} reminders.", events.len()))
+}
+
+// `export --ics [--user <id>]`: the saved reminders as an iCalendar feed.
+pub fn export_command(args: &[String]) -> Result<String, String> {
+    let mut ics = false;
+    let mut user_id: Option<&str> = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ics" => ics = true,
+            "--user" => user_id = Some(args.next().ok_or("--user needs a user id")?.as_str()),
+            other => return Err(format!("Unknown option '{}'", other)),
+        }
+    }
+    if !ics {
+        return Err("Usage: export --ics [--user <id>]".to_string());
+    }
+    let db: DB<Notification> = load_db(&get_db_location()).unwrap_or_else(|_| HashMap::new());
+    Ok(render_calendar(&scheduled(&db, user_id), Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recurring_reminder_renders_as_a_repeating_event() {
+        let start = Utc.with_ymd_and_hms(2026, 2, 10, 17, 0, 0).unwrap();
+        let mut recurrence = Recurrence::new(Frequency::Weekly);
+        recurrence.until = NaiveDate::from_ymd_opt(2026, 3, 31);
+        recurrence.skip_dates = vec![NaiveDate::from_ymd_opt(2026, 2, 17).unwrap()];
+        let notification = Notification {
+            id: "n1".to_string(),
+            content: "standup; bring notes, laptop".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![start],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: Some(start),
+            recurrence: Some(recurrence),
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        };
+
+        let ics = render_calendar(&[&notification], start);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:n1@reminderbot\r\n"));
+        assert!(ics.contains("DTSTART:20260210T170000Z\r\n"));
+        assert!(ics.contains("SUMMARY:standup\\; bring notes\\, laptop\r\n"));
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;INTERVAL=1;UNTIL=20260331T160000Z\r\n"));
+        assert!(ics.contains("EXDATE:20260217T170000Z\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+    }
+
+    #[test]
+    fn imported_events_map_summary_and_start() {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+                   BEGIN:VEVENT\r\nSUMMARY:Dentist\\, bring forms\r\nDTSTART:20260310T150000Z\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nSUMMARY:Standup with a very long title that the exporter folded \r\n onto two lines\r\n\
+                   DTSTART;TZID=America/Los_Angeles:20260302T090000\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20260303\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nSUMMARY:Called off\r\nSTATUS:CANCELLED\r\nDTSTART:20260304T150000Z\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nSUMMARY:Last year\r\nDTSTART:20250304T150000Z\r\nEND:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+
+        let events = importable(parse_calendar(ics).unwrap(), now);
+        assert_eq!(
+            events,
+            vec![
+                ImportedEvent {
+                    summary: "Standup with a very long title that the exporter folded onto two lines".to_string(),
+                    start: Utc.with_ymd_and_hms(2026, 3, 2, 17, 0, 0).unwrap(),
+                },
+                ImportedEvent {
+                    summary: "Holiday".to_string(),
+                    start: Utc.with_ymd_and_hms(2026, 3, 3, 17, 0, 0).unwrap(),
+                },
+                ImportedEvent {
+                    summary: "Dentist, bring forms".to_string(),
+                    start: Utc.with_ymd_and_hms(2026, 3, 10, 15, 0, 0).unwrap(),
+                },
+            ]
+        );
+        assert!(parse_calendar("not a calendar").is_err());
+    }
+}
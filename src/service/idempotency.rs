@@ -0,0 +1,76 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// The same user asking for the same thing in the same channel, ignoring case
+// and spacing.
+pub fn idempotency_key(user_id: &str, channel_id: &str, text: &str) -> u64 {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    let mut hasher = DefaultHasher::new();
+    (user_id, channel_id, text).hash(&mut hasher);
+    hasher.finish()
+}
+
+// Requests seen in the last `window`, so a retried interaction or a double
+// click doesn't draft the same reminder twice. A key is claimed before the
+// request is handled and released if handling fails, leaving retries free to
+// go through.
+#[derive(Debug, Clone)]
+pub struct RecentRequests {
+    window: Duration,
+    seen: HashMap<u64, DateTime<Utc>>,
+}
+
+impl Default for RecentRequests {
+    fn default() -> Self {
+        Self::new(Duration::seconds(30))
+    }
+}
+
+impl RecentRequests {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: HashMap::new(),
+        }
+    }
+
+    // False when `key` was claimed less than `window` before `at`.
+    pub fn claim(&mut self, key: u64, at: DateTime<Utc>) -> bool {
+        let window = self.window;
+        self.seen.retain(|_, seen| at - *seen < window);
+        if self.seen.contains_key(&key) {
+            return false;
+        }
+        self.seen.insert(key, at);
+        true
+    }
+
+    pub fn release(&mut self, key: u64) {
+        self.seen.remove(&key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn repeats_inside_the_window_are_refused() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        let mut recent = RecentRequests::new(Duration::seconds(30));
+        let key = idempotency_key("@u", "123", "Pay rent  tomorrow");
+        assert_eq!(key, idempotency_key("@u", "123", "pay rent tomorrow"));
+        assert_ne!(key, idempotency_key("@other", "123", "pay rent tomorrow"));
+
+        assert!(recent.claim(key, now));
+        assert!(!recent.claim(key, now + Duration::seconds(10)));
+        assert!(recent.claim(key, now + Duration::seconds(30)));
+
+        // A failed attempt gives the key back for its retry.
+        recent.release(key);
+        assert!(recent.claim(key, now + Duration::seconds(31)));
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+
+// Samples kept per measurement; older ones roll off.
+pub const LATENCY_WINDOW: usize = 200;
+// Too few samples make p95 just "the slowest one".
+pub const MIN_SAMPLES_FOR_ALERT: usize = 20;
+// A breach that persists is re-announced at most this often.
+pub const ALERT_COOLDOWN_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LatencyKind {
+    // Interaction received to approval prompt sent.
+    Prompt,
+    // Scheduled fire time to the ping being sent.
+    Delivery,
+}
+
+impl LatencyKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LatencyKind::Prompt => "Approval prompt",
+            LatencyKind::Delivery => "Reminder delivery",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencySlo {
+    pub prompt_p95: Duration,
+    pub delivery_p95: Duration,
+}
+
+impl LatencySlo {
+    pub fn threshold(&self, kind: LatencyKind) -> Duration {
+        match kind {
+            LatencyKind::Prompt => self.prompt_p95,
+            LatencyKind::Delivery => self.delivery_p95,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyBreach {
+    pub kind: LatencyKind,
+    pub p95: Duration,
+    pub threshold: Duration,
+    pub samples: usize,
+}
+
+impl LatencyBreach {
+    pub fn message(&self) -> String {
+        format!(
+            "⚠️ {} p95 is {} over the last {} samples (SLO {}).",
+            self.kind.label(),
+            format_latency(self.p95),
+            self.samples,
+            format_latency(self.threshold)
+        )
+    }
+}
+
+#[derive(Default)]
+struct LatencyState {
+    samples: HashMap<LatencyKind, VecDeque<Duration>>,
+    last_alert: HashMap<LatencyKind, DateTime<Utc>>,
+}
+
+// Rolling end-to-end latencies, shared by the engine (prompts) and the
+// notification loop (deliveries), so a backed-up worker shows up as a p95
+// breach before users start asking where their reminder went.
+#[derive(Default)]
+pub struct LatencyTracker {
+    state: Mutex<LatencyState>,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Negative latencies (clock skew) count as zero.
+    pub fn record(&self, kind: LatencyKind, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let samples = state.samples.entry(kind).or_default();
+        if samples.len() == LATENCY_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(latency.max(Duration::zero()));
+    }
+
+    pub fn sample_count(&self, kind: LatencyKind) -> usize {
+        let state = self.state.lock().unwrap();
+        state.samples.get(&kind).map_or(0, VecDeque::len)
+    }
+
+    // Nearest-rank percentile over the current window.
+    pub fn percentile(&self, kind: LatencyKind, pct: f64) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        let mut samples: Vec<Duration> = state.samples.get(&kind)?.iter().copied().collect();
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort();
+        let rank = ((pct / 100.0) * samples.len() as f64).ceil() as usize;
+        Some(samples[rank.clamp(1, samples.len()) - 1])
+    }
+
+    pub fn p95(&self, kind: LatencyKind) -> Option<Duration> {
+        self.percentile(kind, 95.0)
+    }
+
+    // Measurements whose p95 is over the SLO and haven't been alerted on
+    // within the cooldown. Returned breaches count as alerted.
+    pub fn breaches(&self, slo: &LatencySlo, now: DateTime<Utc>) -> Vec<LatencyBreach> {
+        let mut breaches = Vec::new();
+        for kind in [LatencyKind::Prompt, LatencyKind::Delivery] {
+            let samples = self.sample_count(kind);
+            if samples < MIN_SAMPLES_FOR_ALERT {
+                continue;
+            }
+            let Some(p95) = self.p95(kind) else {
+                continue;
+            };
+            let threshold = slo.threshold(kind);
+            if p95 <= threshold {
+                continue;
+            }
+            let mut state = self.state.lock().unwrap();
+            if state
+                .last_alert
+                .get(&kind)
+                .is_some_and(|at| now - *at < Duration::minutes(ALERT_COOLDOWN_MINUTES))
+            {
+                continue;
+            }
+            state.last_alert.insert(kind, now);
+            breaches.push(LatencyBreach {
+                kind,
+                p95,
+                threshold,
+                samples,
+            });
+        }
+        breaches
+    }
+}
+
+pub fn format_latency(latency: Duration) -> String {
+    let ms = latency.num_milliseconds();
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else if ms < 60_000 {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    } else {
+        format!("{}m {}s", ms / 60_000, (ms % 60_000) / 1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn p95_breach_alerts_once_per_cooldown() {
+        let tracker = LatencyTracker::new();
+        let slo = LatencySlo {
+            prompt_p95: Duration::seconds(10),
+            delivery_p95: Duration::seconds(30),
+        };
+        let now = Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap();
+        for secs in 1..=19 {
+            tracker.record(LatencyKind::Delivery, Duration::seconds(secs * 3));
+        }
+        assert!(tracker.breaches(&slo, now).is_empty(), "too few samples");
+
+        tracker.record(LatencyKind::Delivery, Duration::seconds(60));
+        assert_eq!(tracker.p95(LatencyKind::Delivery), Some(Duration::seconds(57)));
+        let breaches = tracker.breaches(&slo, now);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].kind, LatencyKind::Delivery);
+        assert!(breaches[0].message().contains("57.0s"));
+
+        assert!(tracker.breaches(&slo, now + Duration::minutes(5)).is_empty());
+        assert_eq!(tracker.breaches(&slo, now + Duration::minutes(31)).len(), 1);
+        assert_eq!(tracker.p95(LatencyKind::Prompt), None);
+    }
+}
@@ -1,6 +1,24 @@
+pub mod aliases;
+pub mod audit;
+pub mod capacity;
+pub mod categories;
+pub mod delivery_channel;
+pub mod delivery_log;
+pub mod duplicates;
+pub mod find_time;
+pub mod focus;
+pub mod ics;
+pub mod idempotency;
+pub mod latency;
+pub mod notification_normalizer;
 pub mod notify_flow;
 pub mod notification_message_service;
 pub mod notification_service;
 pub mod openai_service;
+pub mod rate_limit;
 pub mod routing;
+pub mod rules_router;
+pub mod scheduler;
+pub mod time_sanity;
+pub mod upcoming;
 pub mod approval_prompt;
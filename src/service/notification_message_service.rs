@@ -1,7 +1,11 @@
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use memory_db::DB;
 use serde::Serialize;
 
+use crate::i18n::{self, Locale, MessageKey};
 use crate::models::notification::Notification;
+use crate::models::settings::{UserSettings, Voice};
 use crate::service::openai_service::OpenAIClient;
 
 #[derive(Serialize)]
@@ -10,6 +14,43 @@ struct MessageContext<'a> {
     event_time: DateTime<Utc>,
     next_notification_time: Option<DateTime<Utc>>,
     hours_remaining: Option<i64>,
+    // The guild's voice (normal, formal, playful, terse); the prompt asks the
+    // model to write in it.
+    voice: &'static str,
+}
+
+// "9:00am PST / 12:00pm EST / 5:00pm UTC": the event time in each recipient's
+// zone (users without one get the bot's default America/New_York), then UTC.
+pub fn recipient_local_times(
+    event_time: DateTime<Utc>,
+    notify: &[String],
+    settings: &DB<UserSettings>,
+) -> String {
+    let mut zones: Vec<Tz> = Vec::new();
+    for user in notify {
+        let zone = settings
+            .get(user)
+            .and_then(|s| s.timezone.as_deref())
+            .and_then(|tz| tz.parse::<Tz>().ok())
+            .unwrap_or(chrono_tz::America::New_York);
+        if !zones.contains(&zone) {
+            zones.push(zone);
+        }
+    }
+    zones.sort_by_key(|zone| event_time.with_timezone(zone).naive_local());
+    if !zones.contains(&chrono_tz::UTC) {
+        zones.push(chrono_tz::UTC);
+    }
+    zones
+        .iter()
+        .map(|zone| {
+            event_time
+                .with_timezone(zone)
+                .format("%-I:%M%P %Z")
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(" / ")
 }
 
 pub struct NotificationMessageService;
@@ -17,14 +58,48 @@ pub struct NotificationMessageService;
 impl NotificationMessageService {
     pub async fn build_message<C: OpenAIClient + ?Sized>(
         notification: &Notification,
+        locale: Locale,
+        voice: Voice,
+        settings: &DB<UserSettings>,
         openai: &C,
     ) -> String {
         let event_time = match notification.notification_times.last() {
             Some(t) => *t,
             None => {
-                return format!("Notification: {}", notification.content);
+                let key = match voice {
+                    Voice::Terse => MessageKey::NotificationFallbackTerse,
+                    _ => MessageKey::NotificationFallback,
+                };
+                return i18n::render(
+                    locale,
+                    key,
+                    &[("content", notification.content.as_str())],
+                );
             }
         };
+        // Shared reminders show the time for every recipient instead of one UTC stamp.
+        let local_times = (notification.notify.len() > 1).then(|| {
+            recipient_local_times(
+                notification.event_time.unwrap_or(event_time),
+                &notification.notify,
+                settings,
+            )
+        });
+        let fallback = || {
+            let time = local_times.clone().unwrap_or_else(|| event_time.to_string());
+            let key = match voice {
+                Voice::Terse => MessageKey::NotificationFallbackAtTerse,
+                _ => MessageKey::NotificationFallbackAt,
+            };
+            i18n::render(
+                locale,
+                key,
+                &[
+                    ("content", notification.content.as_str()),
+                    ("time", time.as_str()),
+                ],
+            )
+        };
         let next_time = notification.notification_times.first().copied();
         let hours_remaining = next_time.map(|t| (t - Utc::now()).num_hours());
         let context = MessageContext {
@@ -32,15 +107,19 @@ impl NotificationMessageService {
             event_time,
             next_notification_time: next_time,
             hours_remaining,
+            voice: voice.code(),
         };
         let structured = match serde_json::to_string(&context) {
             Ok(v) => v,
-            Err(_) => return format!("Notification: {} at {}", notification.content, event_time),
+            Err(_) => return fallback(),
         };
 
         match openai.generate_prompt(&structured, "notification_message").await {
-            Ok(body) if !body.trim().is_empty() => body,
-            _ => format!("Notification: {} at {}", notification.content, event_time),
+            Ok(body) if !body.trim().is_empty() => match &local_times {
+                Some(times) => format!("{}\n🕒 {}", body, times),
+                None => body,
+            },
+            _ => fallback(),
         }
     }
 }
@@ -49,6 +128,7 @@ impl NotificationMessageService {
 mod tests {
     use super::*;
     use chrono::TimeZone;
+    use std::collections::HashMap;
 
     struct FakeOpenAI {
         response: Result<String, String>,
@@ -76,12 +156,21 @@ mod tests {
             notify: vec!["@u".to_string()],
             notification_times: vec![Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap()],
             channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
         };
         let fake = FakeOpenAI {
             response: Ok("Pay rent at noon.".to_string()),
         };
 
-        let msg = NotificationMessageService::build_message(&notification, &fake).await;
+        let msg = NotificationMessageService::build_message(&notification, Locale::En, Voice::Normal, &HashMap::new(), &fake).await;
         assert_eq!(msg, "Pay rent at noon.");
     }
 
@@ -94,13 +183,67 @@ mod tests {
             notify: vec!["@u".to_string()],
             notification_times: vec![event_time],
             channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
         };
         let fake = FakeOpenAI {
             response: Err("boom".to_string()),
         };
 
-        let msg = NotificationMessageService::build_message(&notification, &fake).await;
+        let msg = NotificationMessageService::build_message(&notification, Locale::En, Voice::Normal, &HashMap::new(), &fake).await;
         assert!(msg.contains("Notification: pay rent"));
         assert!(msg.contains("2026-02-10"));
     }
+
+    #[tokio::test]
+    async fn terse_voice_falls_back_to_a_bare_template() {
+        let event_time = Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap();
+        let notification = Notification {
+            id: "n1".to_string(),
+            content: "pay rent".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![event_time],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        };
+        let fake = FakeOpenAI {
+            response: Err("boom".to_string()),
+        };
+
+        let msg = NotificationMessageService::build_message(&notification, Locale::En, Voice::Terse, &HashMap::new(), &fake).await;
+        assert_eq!(msg, format!("pay rent — {}", event_time));
+    }
+
+    #[test]
+    fn recipient_local_times_lists_each_zone_then_utc() {
+        let mut settings: DB<UserSettings> = HashMap::new();
+        settings.insert(
+            "@west".to_string(),
+            UserSettings {
+                user_id: "@west".to_string(),
+                timezone: Some("America/Los_Angeles".to_string()),
+                ..Default::default()
+            },
+        );
+        let event_time = Utc.with_ymd_and_hms(2026, 2, 10, 17, 0, 0).unwrap();
+        let notify = vec!["@west".to_string(), "@east".to_string()];
+
+        let times = recipient_local_times(event_time, &notify, &settings);
+        assert_eq!(times, "9:00am PST / 12:00pm EST / 5:00pm UTC");
+    }
 }
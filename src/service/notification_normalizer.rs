@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::notification::AINotification;
+use crate::models::recurrence::{self, Recurrence};
+use crate::service::time_sanity::{self, TimeIssue};
+
+// Longest lead time honoured for a custom ping offset (30 days, in minutes).
+const MAX_OFFSET_MINUTES: i64 = 60 * 24 * 30;
+
+// AINotification with every optional field resolved to something usable.
+#[derive(Debug, Clone)]
+pub struct NormalizedNotification {
+    pub content: String,
+    pub time: DateTime<Utc>,
+    pub recurrence: Option<Recurrence>,
+    pub offsets: Vec<i64>,
+    pub original_phrase: Option<String>,
+    pub confidence: Option<f32>,
+}
+
+impl NormalizedNotification {
+    // What looks off about the time: the checks in time_sanity, then
+    // whether the model itself was unsure of it.
+    pub fn time_issue(&self, now: DateTime<Utc>) -> Option<TimeIssue> {
+        time_sanity::check_time(self.time, now).or_else(|| {
+            self.confidence
+                .filter(|confidence| *confidence < time_sanity::MIN_CONFIDENCE)
+                .map(|_| TimeIssue::Unsure)
+        })
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+// Fills gaps the model left: recurrence falls back to parsing the user's own
+// text, and offsets/confidence are clamped to sane ranges. The category and
+// target the model gives aren't used: a reminder's category comes from its
+// content when it goes out, and reminders are for whoever asked.
+pub fn normalize(ai: AINotification, original_text: &str, now: DateTime<Utc>) -> NormalizedNotification {
+    let content = non_empty(Some(ai.content)).unwrap_or_else(|| original_text.trim().to_string());
+
+    let recurrence = non_empty(ai.recurrence)
+        .and_then(|phrase| recurrence::parse_recurrence(&phrase, now))
+        .or_else(|| recurrence::parse_recurrence(original_text, now));
+
+    let mut offsets: Vec<i64> = ai
+        .offsets
+        .into_iter()
+        .filter(|minutes| *minutes > 0 && *minutes <= MAX_OFFSET_MINUTES)
+        .collect();
+    offsets.sort_unstable_by(|a, b| b.cmp(a));
+    offsets.dedup();
+
+    NormalizedNotification {
+        content,
+        time: ai.time,
+        recurrence,
+        offsets,
+        original_phrase: non_empty(ai.original_phrase),
+        confidence: ai.confidence.filter(|c| c.is_finite()).map(|c| c.clamp(0.0, 1.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn two_field_payload_gets_defaults_from_text() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 7, 17, 0, 0).unwrap();
+        let ai: AINotification =
+            serde_json::from_str("{\"content\":\"pay rent\",\"time\":\"2026-02-01T17:00:00Z\"}").unwrap();
+
+        let normalized = normalize(ai, "pay rent every month", now);
+        assert_eq!(normalized.content, "pay rent");
+        assert!(normalized.recurrence.is_some());
+        assert!(normalized.offsets.is_empty());
+        assert_eq!(normalized.confidence, None);
+    }
+
+    #[test]
+    fn optional_fields_are_validated() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 7, 17, 0, 0).unwrap();
+        let ai: AINotification = serde_json::from_str(
+            "{\"content\":\" \",\"time\":\"2026-02-01T17:00:00Z\",\"recurrence\":\"every Friday until March\",\
+             \"category\":\"Party\",\"offsets\":[30,-5,1440,30],\"confidence\":1.7}",
+        )
+        .unwrap();
+
+        let normalized = normalize(ai, "standup notes", now);
+        assert_eq!(normalized.content, "standup notes");
+        assert_eq!(normalized.offsets, vec![1440, 30]);
+        assert_eq!(normalized.confidence, Some(1.0));
+        let recurrence = normalized.recurrence.unwrap();
+        assert!(recurrence.until.is_some());
+    }
+
+    #[test]
+    fn unsure_reads_are_flagged_for_confirmation() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 7, 17, 0, 0).unwrap();
+        let ai: AINotification = serde_json::from_str(
+            "{\"content\":\"dentist\",\"time\":\"2026-01-09T15:00:00Z\",\"confidence\":0.3}",
+        )
+        .unwrap();
+        assert_eq!(normalize(ai, "dentist friday-ish", now).time_issue(now), Some(TimeIssue::Unsure));
+
+        let ai: AINotification = serde_json::from_str(
+            "{\"content\":\"dentist\",\"time\":\"2026-01-09T15:00:00Z\",\"confidence\":0.9}",
+        )
+        .unwrap();
+        assert_eq!(normalize(ai, "dentist friday at 3", now).time_issue(now), None);
+    }
+}
@@ -1,21 +1,79 @@
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use memory_db::{DB, DBError};
 use serenity::builder::{CreateActionRow, CreateButton};
 
-use crate::handlers::action::NotificationDraft;
+use crate::handlers::action::{ImportDraft, NotificationDraft, SlotDraft, TodoDraft, ToolDraft};
+use crate::i18n::{self, Locale, MessageKey};
 use crate::models::notification::{self, Notification};
+use crate::models::recurrence::Recurrence;
+use crate::service::capacity;
 
-pub fn render_pending_message(pending: &NotificationDraft) -> String {
+pub fn render_pending_message(pending: &NotificationDraft, locale: Locale, zone: Tz) -> String {
     let mut body: String = format!(
         "Please confirm your notification:\nContent: {}\nTime: {}",
         pending.content,
         pending.time
     );
+    if let Some(phrase) = &pending.original_phrase {
+        body.push_str(&format!(" (from \"{}\")", phrase));
+    }
+    if !pending.offsets.is_empty() {
+        let offsets: Vec<String> = pending.offsets.iter().map(|minutes| minutes.to_string()).collect();
+        body.push_str(&format!("\nPings: {} minutes before", offsets.join(", ")));
+    }
+    if let Some(recurrence) = &pending.recurrence {
+        body.push_str(&format!("\nRepeats: {}", recurrence.describe()));
+    }
     if let Some(ctx) = &pending.extra_context {
         if !ctx.trim().is_empty() {
             body.push_str(&format!("\nAdditional context: {}", ctx.trim()));
         }
     }
+    if !pending.checklist.is_empty() {
+        body.push_str(&format!("\nChecklist: {}", pending.checklist.join(", ")));
+    }
+    if pending.day_load >= capacity::BUSY_DAY_THRESHOLD {
+        let day = pending
+            .time
+            .with_timezone(&zone)
+            .format_localized("%A", locale.time_locale())
+            .to_string();
+        body.push('\n');
+        body.push_str(&i18n::render(
+            locale,
+            MessageKey::BusyDay,
+            &[("count", &pending.day_load.to_string()), ("day", &day)],
+        ));
+        if !pending.alternatives.is_empty() {
+            body.push(' ');
+            body.push_str(i18n::text(locale, MessageKey::BusyDayAlternatives));
+        }
+    }
+    for conflict in &pending.conflicts {
+        let local = |at: DateTime<Utc>| at.with_timezone(&zone).format("%H:%M").to_string();
+        body.push_str(&format!(
+            "\n⚠️ conflicts with '{}' {}–{}",
+            conflict.title,
+            local(conflict.start),
+            local(conflict.end)
+        ));
+    }
+    if let Some(duplicate) = &pending.duplicate {
+        body.push_str(&format!(
+            "\n⚠️ You already have '{}' on {} — create anyway?",
+            duplicate.content,
+            duplicate.time.format("%b %-d")
+        ));
+    }
+    if let Some(issue) = &pending.time_issue {
+        body.push('\n');
+        body.push_str(&i18n::render(
+            locale,
+            MessageKey::TimeIssueWarning,
+            &[("issue", &issue.describe(locale))],
+        ));
+    }
     body
 }
 
@@ -33,6 +91,283 @@ pub fn pending_buttons(action_id: &str) -> CreateActionRow {
     ])
 }
 
+pub fn render_pending_todo(pending: &TodoDraft, locale: Locale) -> String {
+    let mut body = i18n::text(locale, MessageKey::TodoConfirmPrompt).to_string();
+    for item in &pending.items {
+        body.push_str(&format!("\n- {}", item));
+    }
+    body
+}
+
+pub fn render_pending_tool(pending: &ToolDraft) -> String {
+    format!("Please confirm: {}", pending.summary)
+}
+
+// Long files are summarised so the prompt stays inside Discord's message
+// limit.
+const IMPORT_PREVIEW_LINES: usize = 15;
+
+pub fn render_pending_import(pending: &ImportDraft) -> String {
+    let mut body = format!("Please confirm importing {} reminders:", pending.events.len());
+    for event in pending.events.iter().take(IMPORT_PREVIEW_LINES) {
+        body.push_str(&format!("\n- <t:{}:f> {}", event.start.timestamp(), event.summary));
+    }
+    if pending.events.len() > IMPORT_PREVIEW_LINES {
+        body.push_str(&format!("\n…and {} more", pending.events.len() - IMPORT_PREVIEW_LINES));
+    }
+    body
+}
+
+pub fn render_pending_slots(pending: &SlotDraft) -> String {
+    let mut body = format!(
+        "Free {}-minute slots for \"{}\" — pick one:",
+        pending.minutes, pending.title
+    );
+    if pending.add_to_calendar {
+        body.push_str("\nThe one you pick also goes on your calendar.");
+    }
+    body
+}
+
+// One button per slot, labelled in New York time, then Cancel.
+pub fn slot_components(action_id: &str, pending: &SlotDraft) -> Vec<CreateActionRow> {
+    let slots = pending
+        .slots
+        .iter()
+        .map(|time| {
+            CreateButton::new(format!("action_slot:{}:{}", action_id, time.timestamp()))
+                .label(
+                    time.with_timezone(&chrono_tz::America::New_York)
+                        .format("%a %-I:%M%P")
+                        .to_string(),
+                )
+                .style(serenity::all::ButtonStyle::Primary)
+        })
+        .collect();
+    vec![
+        CreateActionRow::Buttons(slots),
+        CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("action_cancel:{}", action_id))
+                .label("Cancel")
+                .style(serenity::all::ButtonStyle::Danger),
+        ]),
+    ]
+}
+
+// Todos and tool calls have no time to correct, so just confirm or cancel.
+pub fn pending_confirm_buttons(action_id: &str, confirm_label: &str) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("action_confirm:{}", action_id))
+            .label(confirm_label)
+            .style(serenity::all::ButtonStyle::Success),
+        CreateButton::new(format!("action_cancel:{}", action_id))
+            .label("Cancel")
+            .style(serenity::all::ButtonStyle::Danger),
+    ])
+}
+
+// The confirm row, plus a row of lighter days when the draft lands on a busy
+// one, named as they fall in the user's zone.
+pub fn pending_components(
+    action_id: &str,
+    pending: &NotificationDraft,
+    locale: Locale,
+    zone: Tz,
+) -> Vec<CreateActionRow> {
+    let mut rows = vec![pending_buttons(action_id)];
+    if !pending.alternatives.is_empty() {
+        rows.push(CreateActionRow::Buttons(
+            pending
+                .alternatives
+                .iter()
+                .map(|time| {
+                    let day = time
+                        .with_timezone(&zone)
+                        .format_localized("%a %-d %b", locale.time_locale())
+                        .to_string();
+                    CreateButton::new(format!("action_move:{}:{}", action_id, time.timestamp()))
+                        .label(i18n::render(locale, MessageKey::MoveToDay, &[("day", &day)]))
+                        .style(serenity::all::ButtonStyle::Secondary)
+                })
+                .collect(),
+        ));
+    }
+    if pending.duplicate.is_some() {
+        rows.push(CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("action_merge:{}", action_id))
+                .label("Merge into existing")
+                .style(serenity::all::ButtonStyle::Primary),
+            CreateButton::new(format!("action_skip:{}", action_id))
+                .label("Skip, keep existing")
+                .style(serenity::all::ButtonStyle::Secondary),
+        ]));
+    }
+    rows
+}
+
+// Attached to a delivered reminder that still has pings left, so the user can
+// stop the remaining ones.
+pub fn delivered_buttons(notification_id: &str, locale: Locale) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("reminder_done:{}", notification_id))
+            .label(i18n::text(locale, MessageKey::ReminderDoneLabel))
+            .style(serenity::all::ButtonStyle::Success),
+        CreateButton::new(format!("reminder_dismiss:{}", notification_id))
+            .label(i18n::text(locale, MessageKey::ReminderDismissLabel))
+            .style(serenity::all::ButtonStyle::Secondary),
+    ])
+}
+
+// One line per item, ticked or not, for the delivery message and its updates.
+pub fn render_checklist(notification: &Notification) -> String {
+    notification
+        .checklist
+        .iter()
+        .map(|item| format!("{} {}", if item.done { "☑" } else { "☐" }, item.text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Posted once the event passes with items still open.
+pub fn render_checklist_summary(notification: &Notification, locale: Locale) -> String {
+    let done = notification.checklist.iter().filter(|item| item.done).count();
+    let open = notification
+        .checklist
+        .iter()
+        .filter(|item| !item.done)
+        .map(|item| item.text.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    i18n::render(
+        locale,
+        MessageKey::ChecklistSummary,
+        &[
+            ("content", notification.content.as_str()),
+            ("done", done.to_string().as_str()),
+            ("total", notification.checklist.len().to_string().as_str()),
+            ("open", open.as_str()),
+        ],
+    )
+}
+
+// A toggle button per checklist item, five to a row.
+pub fn checklist_buttons(notification: &Notification) -> Vec<CreateActionRow> {
+    notification
+        .checklist
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let label: String = item.text.chars().take(80).collect();
+            CreateButton::new(format!("checklist:{}:{}", notification.id, index))
+                .label(label)
+                .style(if item.done {
+                    serenity::all::ButtonStyle::Success
+                } else {
+                    serenity::all::ButtonStyle::Secondary
+                })
+        })
+        .collect::<Vec<_>>()
+        .chunks(5)
+        .map(|row| CreateActionRow::Buttons(row.to_vec()))
+        .collect()
+}
+
+// Upcoming reminders for one user, soonest first. "/notify list" numbers them
+// in this order and pause/resume refer to those numbers.
+pub fn user_notifications<'a>(db: &'a DB<Notification>, user_id: &str) -> Vec<&'a Notification> {
+    let mut upcoming: Vec<&Notification> = db
+        .values()
+        .filter(|n| n.notify.iter().any(|u| u == user_id))
+        .filter(|n| !n.notification_times.is_empty())
+        .collect();
+    upcoming.sort_by_key(|n| {
+        (
+            n.event_time.or_else(|| n.notification_times.first().copied()),
+            n.id.clone(),
+        )
+    });
+    upcoming
+}
+
+fn notification_when(notification: &Notification) -> Option<DateTime<Utc>> {
+    notification
+        .event_time
+        .or_else(|| notification.notification_times.first().copied())
+}
+
+pub fn render_notification_list(db: &DB<Notification>, user_id: &str) -> String {
+    render_notifications(db, user_id, None)
+}
+
+// The reminders falling in [from, until), still numbered as in the full list
+// so pause/resume work off either.
+pub fn render_notifications_between(
+    db: &DB<Notification>,
+    user_id: &str,
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> String {
+    render_notifications(db, user_id, Some((from, until)))
+}
+
+fn render_notifications(
+    db: &DB<Notification>,
+    user_id: &str,
+    window: Option<(DateTime<Utc>, DateTime<Utc>)>,
+) -> String {
+    let upcoming: Vec<(usize, &Notification)> = user_notifications(db, user_id)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, notification)| match (window, notification_when(notification)) {
+            (None, _) => true,
+            (Some((from, until)), Some(when)) => from <= when && when < until,
+            (Some(_), None) => false,
+        })
+        .collect();
+    if upcoming.is_empty() {
+        return match window {
+            None => "You have no upcoming reminders.".to_string(),
+            Some(_) => "You have no reminders then.".to_string(),
+        };
+    }
+
+    let mut body = String::from("Your upcoming reminders:");
+    for (idx, notification) in upcoming {
+        let when = notification_when(notification)
+            .map(|t| {
+                t.with_timezone(&chrono_tz::America::New_York)
+                    .format("%a %b %-d, %-I:%M %p")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        body.push_str(&format!("\n{}. {} — {}", idx + 1, notification.content, when));
+        if let Some(recurrence) = &notification.recurrence {
+            body.push_str(&format!(" ({})", recurrence.describe()));
+        }
+        if notification.paused {
+            body.push_str(" [paused]");
+        }
+    }
+    body
+}
+
+// The user's upcoming reminders whose content has every word of `query`,
+// e.g. "dentist" for "Dentist appointment". An empty query matches them all.
+pub fn find_user_notifications<'a>(
+    db: &'a DB<Notification>,
+    user_id: &str,
+    query: &str,
+) -> Vec<&'a Notification> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    user_notifications(db, user_id)
+        .into_iter()
+        .filter(|n| {
+            let content = n.content.to_lowercase();
+            words.iter().all(|word| content.contains(word.as_str()))
+        })
+        .collect()
+}
+
 pub struct NotificationService;
 
 impl NotificationService {
@@ -42,8 +377,10 @@ impl NotificationService {
         notify_users: &String,
         expires_at: &DateTime<Utc>,
         channel: &String,
-    ) -> Result<(), DBError> {
-        notification::create_notification(db, content, notify_users, expires_at, channel).await
+        recurrence: Option<Recurrence>,
+    ) -> Result<String, DBError> {
+        notification::create_notification(db, content, notify_users, expires_at, channel, recurrence)
+            .await
     }
 }
 
@@ -71,7 +408,7 @@ mod tests {
         let channel = "123".to_string();
         let expires_at = Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap();
 
-        NotificationService::create(&mut db, &content, &notify_users, &expires_at, &channel)
+        NotificationService::create(&mut db, &content, &notify_users, &expires_at, &channel, None)
             .await
             .expect("create notification should succeed");
 
@@ -99,9 +436,18 @@ mod tests {
             extra_context: Some("add eggs".to_string()),
             expires_at: Utc.with_ymd_and_hms(2026, 2, 10, 12, 5, 0).unwrap(),
             message_id: None,
+            recurrence: None,
+            day_load: 0,
+            alternatives: Vec::new(),
+            checklist: Vec::new(),
+            conflicts: Vec::new(),
+            duplicate: None,
+            time_issue: None,
+            offsets: Vec::new(),
+            original_phrase: None,
         };
 
-        let body = render_pending_message(&pending);
+        let body = render_pending_message(&pending, Locale::En, chrono_tz::UTC);
         assert!(body.contains("buy milk"));
         assert!(body.contains("Additional context: add eggs"));
     }
@@ -114,4 +460,78 @@ mod tests {
         assert!(debug.contains("action_context:abc123"));
         assert!(debug.contains("action_cancel:abc123"));
     }
+
+    #[test]
+    fn busy_day_draft_notes_load_and_offers_moves() {
+        let time = Utc.with_ymd_and_hms(2026, 2, 13, 12, 0, 0).unwrap();
+        let pending = NotificationDraft {
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            content: "buy milk".to_string(),
+            time,
+            original_text: "buy milk friday".to_string(),
+            extra_context: None,
+            expires_at: time,
+            message_id: None,
+            recurrence: None,
+            day_load: 6,
+            alternatives: vec![time - Duration::days(1)],
+            checklist: Vec::new(),
+            conflicts: Vec::new(),
+            duplicate: None,
+            time_issue: None,
+            offsets: Vec::new(),
+            original_phrase: None,
+        };
+
+        let body = render_pending_message(&pending, Locale::En, chrono_tz::UTC);
+        assert!(body.contains("you already have 6 things on Friday"));
+        let rows = format!("{:?}", pending_components("abc", &pending, Locale::En, chrono_tz::UTC));
+        assert!(rows.contains(&format!("action_move:abc:{}", (time - Duration::days(1)).timestamp())));
+        assert!(rows.contains("Move to Thu 12 Feb"));
+
+        // Friday noon in UTC is already Saturday at UTC+14.
+        let body = render_pending_message(&pending, Locale::De, chrono_tz::Pacific::Kiritimati);
+        assert!(body.contains("Am Samstag hast du schon 6 Dinge vor."), "{}", body);
+    }
+
+    #[test]
+    fn render_notification_list_shows_occurrences_left() {
+        let mut recurrence = Recurrence::new(crate::models::recurrence::Frequency::Weekly);
+        recurrence.count = Some(4);
+        let event_time = Utc.with_ymd_and_hms(2026, 2, 13, 17, 0, 0).unwrap();
+        let mut db: DB<Notification> = HashMap::new();
+        db.insert(
+            "n1".to_string(),
+            Notification {
+                id: "n1".to_string(),
+                content: "team sync".to_string(),
+                notify: vec!["@u".to_string()],
+                notification_times: vec![event_time - Duration::hours(1)],
+                channel: "123".to_string(),
+                completed_at: None,
+                event_time: Some(event_time),
+                recurrence: Some(recurrence),
+                paused: false,
+                checklist: Vec::new(),
+                ack: None,
+                quiet_delayed: false,
+                targets: Vec::new(),
+                delivery_retry: None,
+            },
+        );
+
+        let body = render_notification_list(&db, "@u");
+        assert!(body.contains("team sync"));
+        assert!(body.contains("4 occurrences left"));
+        assert_eq!(render_notification_list(&db, "@other"), "You have no upcoming reminders.");
+    }
+
+    #[test]
+    fn delivered_buttons_include_namespaced_ids() {
+        let buttons = delivered_buttons("n1", Locale::En);
+        let debug = format!("{:?}", buttons);
+        assert!(debug.contains("reminder_done:n1"));
+        assert!(debug.contains("reminder_dismiss:n1"));
+    }
 }
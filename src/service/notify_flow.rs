@@ -1,28 +1,367 @@
-use crate::service::routing::{Intent, IntentRouter};
-use chrono::{DateTime, Duration, Utc};
+use crate::i18n::{self, Locale, MessageKey};
+use crate::service::routing::{DEFAULT_CONFIDENCE_THRESHOLD, Intent, IntentResult, IntentRouter};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::America::New_York;
+use memory_db::{DB, DBError, save_db};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::env;
 
 pub type SessionKey = (String, String);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// How long a follow-up /notify still continues an earlier prompt.
+pub const SESSION_TTL_MINUTES: i64 = 5;
+// Follow-ups an unclear request gets before the bot stops asking.
+pub const DEFAULT_MAX_TURNS: u32 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReminderAction {
+    Cancel,
+    Snooze { minutes: i64 },
+}
+
+// The question the bot is waiting on an answer to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SessionState {
+    // What the request is about, or when; the answer is read together with
+    // the original text.
     Unknown,
     PendingNotification,
     PendingTodo,
+    // Which of several matching reminders a cancel or snooze meant, as
+    // (id, content) in the order offered.
+    ChooseReminder {
+        action: ReminderAction,
+        candidates: Vec<(String, String)>,
+    },
+    // Which of the free slots offered for a find-time request to take.
+    ChooseSlot,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PendingSession {
     pub state: SessionState,
     pub original_text: String,
     pub last_prompt_at: DateTime<Utc>,
+    // Follow-ups so far; sessions saved before turns were counted start at 0.
+    #[serde(default)]
+    pub turns: u32,
+}
+
+impl PendingSession {
+    pub fn new(state: SessionState, original_text: impl Into<String>, last_prompt_at: DateTime<Utc>) -> Self {
+        Self {
+            state,
+            original_text: original_text.into(),
+            last_prompt_at,
+            turns: 0,
+        }
+    }
+}
+
+// On-disk form of a pending session; the DB is keyed by string so the
+// (user, channel) key is stored alongside it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSession {
+    pub user_id: String,
+    pub channel_id: String,
+    pub session: PendingSession,
+}
+
+// Returns the directory where pending /notify sessions are kept across restarts.
+// Defaults to a relative "./data/sessions" directory.
+pub fn get_session_db_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/sessions", base)
+}
+
+pub fn save_sessions(sessions: &HashMap<SessionKey, PendingSession>) -> Result<(), DBError> {
+    let db: DB<StoredSession> = sessions
+        .iter()
+        .map(|((user_id, channel_id), session)| {
+            (
+                format!("{}:{}", user_id, channel_id),
+                StoredSession {
+                    user_id: user_id.clone(),
+                    channel_id: channel_id.clone(),
+                    session: session.clone(),
+                },
+            )
+        })
+        .collect();
+    save_db(&get_session_db_location(), &db)
+}
+
+pub fn sessions_from_db(db: DB<StoredSession>) -> HashMap<SessionKey, PendingSession> {
+    db.into_values()
+        .map(|stored| ((stored.user_id, stored.channel_id), stored.session))
+        .collect()
 }
 
+// Drops every session past its TTL and returns the keys and text of those
+// still waiting on clarification, i.e. requests the user never followed up on.
+pub fn take_expired_clarifications(
+    sessions: &mut HashMap<SessionKey, PendingSession>,
+    now: DateTime<Utc>,
+) -> Vec<(SessionKey, String)> {
+    let expired: Vec<SessionKey> = sessions
+        .iter()
+        .filter(|(_, session)| now - session.last_prompt_at > Duration::minutes(SESSION_TTL_MINUTES))
+        .map(|(key, _)| key.clone())
+        .collect();
+    expired
+        .into_iter()
+        .filter_map(|key| {
+            let session = sessions.remove(&key)?;
+            (session.state == SessionState::Unknown).then_some((key, session.original_text))
+        })
+        .collect()
+}
+
+#[derive(Debug)]
 pub enum NotifyDecision {
     EmitNotify { normalized_text: String },
     EmitTodo { normalized_text: String },
-    TodoFailed { error: String },
+    EmitCalendarEvent { normalized_text: String },
+    // About reminders the user already has, not a new one.
+    EmitQuery { normalized_text: String },
+    EmitCancel { normalized_text: String },
+    EmitSnooze { normalized_text: String },
     NeedClarification,
+    // The router leaned towards `intent` but not confidently enough to act.
+    ClarifyIntent { intent: Intent, normalized_text: String },
+    // Several clear requests in one message, each drafted on its own.
+    EmitMany { parts: Vec<IntentResult> },
+    // Several reminders matched a cancel or snooze; `options` lists them.
+    ChooseReminder { options: Vec<String> },
+    ActOnReminder { action: ReminderAction, reminder_id: String },
+    // The 1-based slot picked from a find-time offer.
+    SlotPicked { index: usize },
+    // "never mind": whatever was being asked about is dropped.
+    ConversationCanceled,
+    // Still unclear after the turn limit; the session was dropped.
+    GaveUp,
+    SkipRequested,
+    ListRequested,
+    PauseRequested { paused: bool },
+    MustAckRequested,
+    FindTimeRequested,
+    // The event queue stayed full; nothing was queued.
+    QueueFull,
+    RateLimited { retry_at: DateTime<Utc> },
+}
+
+pub fn is_list_command(text: &str) -> bool {
+    text.trim().eq_ignore_ascii_case("list")
+}
+
+const CANCEL_PHRASES: &[&str] = &[
+    "never mind", "nevermind", "nvm", "forget it", "cancel that", "stop", "olvídalo", "olvidalo",
+    "déjalo", "dejalo", "vergiss es", "egal", "laisse tomber", "oublie",
+];
+
+pub fn is_cancel_phrase(text: &str) -> bool {
+    let lower = text.trim().trim_end_matches(['.', '!']).to_lowercase();
+    CANCEL_PHRASES.contains(&lower.as_str())
+}
+
+// "2", "#2" or "number 2" picks the second option.
+fn parse_choice(text: &str, options: usize) -> Option<usize> {
+    let lower = text.trim().to_lowercase();
+    let number = lower.strip_prefix("number").unwrap_or(&lower).trim().trim_start_matches('#');
+    number.parse::<usize>().ok().filter(|n| (1..=options).contains(n))
+}
+
+// A number, or words that fit exactly one of the candidates' text.
+fn pick_candidate(text: &str, candidates: &[(String, String)]) -> Option<String> {
+    if let Some(index) = parse_choice(text, candidates.len()) {
+        return Some(candidates[index - 1].0.clone());
+    }
+    let words: Vec<String> = reminder_query(text).split_whitespace().map(str::to_lowercase).collect();
+    if words.is_empty() {
+        return None;
+    }
+    let mut matches = candidates.iter().filter(|(_, content)| {
+        let content = content.to_lowercase();
+        words.iter().all(|word| content.contains(word.as_str()))
+    });
+    match (matches.next(), matches.next()) {
+        (Some((id, _)), None) => Some(id.clone()),
+        _ => None,
+    }
+}
+
+// "/notify pause 2" / "/notify resume 2", numbered as in "/notify list".
+// Returns the 1-based index and whether the reminder should be paused.
+pub fn parse_pause_command(text: &str) -> Option<(usize, bool)> {
+    let mut words = text.split_whitespace();
+    let paused = match words.next()?.to_lowercase().as_str() {
+        "pause" => true,
+        "resume" | "unpause" => false,
+        _ => return None,
+    };
+    let index = words.next()?.trim_start_matches('#').parse::<usize>().ok()?;
+    if words.next().is_some() || index == 0 {
+        return None;
+    }
+    Some((index, paused))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MustAckCommand {
+    pub index: usize,
+    pub backup: Option<String>,
+    pub window_minutes: Option<i64>,
+}
+
+// "/notify must-ack 2 [@backup] [45m]", numbered as in "/notify list". The
+// backup is tagged if the final ping goes unacknowledged for the window.
+pub fn parse_must_ack_command(text: &str) -> Option<MustAckCommand> {
+    let mut words = text.split_whitespace();
+    if !words.next()?.eq_ignore_ascii_case("must-ack") {
+        return None;
+    }
+    let index = words.next()?.trim_start_matches('#').parse::<usize>().ok()?;
+    if index == 0 {
+        return None;
+    }
+    let mut command = MustAckCommand {
+        index,
+        backup: None,
+        window_minutes: None,
+    };
+    for word in words {
+        if word.starts_with('@') || word.starts_with("<@") {
+            if command.backup.is_some() {
+                return None;
+            }
+            command.backup = Some(word.to_string());
+        } else {
+            let minutes = word.trim_end_matches('m').parse::<i64>().ok().filter(|m| *m > 0)?;
+            if command.window_minutes.is_some() {
+                return None;
+            }
+            command.window_minutes = Some(minutes);
+        }
+    }
+    Some(command)
+}
+
+// "/notify skip next [which]" targets an existing recurring reminder instead of
+// creating a new one. Returns the (possibly empty) text identifying it.
+pub fn parse_skip_command(text: &str) -> Option<String> {
+    let trimmed = text.trim();
+    let lower = trimmed.to_lowercase();
+    if !lower.starts_with("skip next") {
+        return None;
+    }
+    let rest = &trimmed["skip next".len()..];
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim().to_string())
+}
+
+// Words that don't help pick a reminder out in "cancel the dentist one" or
+// "snooze my rent reminder a day".
+const FILLER_WORDS: &[&str] = &[
+    "cancel", "delete", "remove", "snooze", "postpone", "push", "back", "delay", "the", "my", "a",
+    "an", "one", "reminder", "for", "about", "by", "until", "cancela", "borra", "elimina", "pospón",
+    "pospon", "aplaza", "retrasa", "el", "la", "mi", "recordatorio", "de", "lösche", "storniere",
+    "entferne", "verschiebe", "die", "den", "das", "meine", "meinen", "erinnerung", "um", "annule",
+    "supprime", "efface", "repousse", "reporte", "décale", "le", "mon", "ma", "rappel", "du",
+];
+
+// The words identifying the reminder a cancel or snooze is about, e.g.
+// "dentist" from "cancel the dentist one". Empty when nothing is left.
+pub fn reminder_query(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty() && !FILLER_WORDS.contains(&word.to_lowercase().as_str()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// How long a snooze without a duration lasts.
+pub const DEFAULT_SNOOZE_MINUTES: i64 = 60;
+
+fn unit_minutes(word: &str) -> Option<i64> {
+    match word {
+        "min" | "mins" | "minute" | "minutes" | "minuto" | "minutos" | "minuten" => Some(1),
+        "hour" | "hours" | "hr" | "hrs" | "hora" | "horas" | "stunde" | "stunden" | "heure" | "heures" => {
+            Some(60)
+        }
+        "day" | "days" | "día" | "dia" | "días" | "dias" | "tag" | "tage" | "jour" | "jours" => Some(60 * 24),
+        "week" | "weeks" | "semana" | "semanas" | "woche" | "wochen" | "semaine" | "semaines" => {
+            Some(60 * 24 * 7)
+        }
+        _ => None,
+    }
+}
+
+// "snooze my rent reminder a day" → ("rent", 1440). The first "<amount>
+// <unit>" pair is the duration; "a", "an" and "one" count as 1.
+pub fn parse_snooze(text: &str) -> (String, i64) {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .collect();
+    for (at, pair) in words.windows(2).enumerate() {
+        let amount = match pair[0].as_str() {
+            "a" | "an" | "one" | "un" | "una" | "einen" | "eine" | "einem" => Some(1),
+            other => other.parse::<i64>().ok().filter(|n| *n > 0),
+        };
+        if let (Some(amount), Some(unit)) = (amount, unit_minutes(&pair[1])) {
+            let rest: Vec<&str> = text
+                .split_whitespace()
+                .enumerate()
+                .filter(|(index, _)| *index != at && *index != at + 1)
+                .map(|(_, word)| word)
+                .collect();
+            return (reminder_query(&rest.join(" ")), amount * unit);
+        }
+    }
+    (reminder_query(text), DEFAULT_SNOOZE_MINUTES)
+}
+
+// The stretch of time a question like "what do I have this week" asks about,
+// in New York time as the reminder list is. None lists everything upcoming.
+pub fn parse_query_window(text: &str, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let lower = text.to_lowercase();
+    let today = now.with_timezone(&New_York).date_naive();
+    let start_of = |date: chrono::NaiveDate| {
+        New_York
+            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+    };
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let mentions = |words: &[&str]| words.iter().any(|word| lower.contains(word));
+    let (from, until) = if mentions(&["tomorrow", "mañana", "morgen", "demain"]) {
+        (today + Duration::days(1), today + Duration::days(2))
+    } else if mentions(&["today", "tonight", "hoy", "heute", "aujourd"]) {
+        (today, today + Duration::days(1))
+    } else if mentions(&["next week", "próxima semana", "nächste woche", "semaine prochaine"]) {
+        (monday + Duration::days(7), monday + Duration::days(14))
+    } else if mentions(&["this week", "esta semana", "diese woche", "cette semaine"]) {
+        (today, monday + Duration::days(7))
+    } else {
+        return None;
+    };
+    let from = start_of(from)?.max(now);
+    Some((from, start_of(until)?))
+}
+
+// The question asked for a ClarifyIntent, naming what the router guessed.
+pub fn clarify_question(locale: Locale, intent: &Intent, text: &str) -> String {
+    let key = match intent {
+        Intent::Notification => MessageKey::ClarifyReminder,
+        Intent::Todolist => MessageKey::ClarifyTodo,
+        Intent::ToolUse => MessageKey::ClarifyCalendarEvent,
+        Intent::Query | Intent::Cancel | Intent::Snooze => MessageKey::ClarifyExisting,
+        Intent::Unknown => MessageKey::NeedClarification,
+    };
+    i18n::render(locale, key, &[("text", text)])
 }
 
 pub async fn route_notify(
@@ -30,49 +369,214 @@ pub async fn route_notify(
     sessions: &mut HashMap<SessionKey, PendingSession>,
     session_key: SessionKey,
     text: String,
+    locale: Locale,
     now: DateTime<Utc>,
 ) -> NotifyDecision {
-    let mut combined_text = text;
-    if let Some(session) = sessions.get(&session_key) {
-        if now - session.last_prompt_at > Duration::minutes(5) {
-            sessions.remove(&session_key);
-        } else if session.state == SessionState::Unknown {
-            combined_text = format!("{} {}", session.original_text, combined_text);
+    ConversationManager::default()
+        .handle(router, sessions, session_key, text, locale, now)
+        .await
+}
+
+// Like route_notify, asking about any guess the router is less than
+// `threshold` sure of.
+pub async fn route_notify_with(
+    router: &dyn IntentRouter,
+    sessions: &mut HashMap<SessionKey, PendingSession>,
+    session_key: SessionKey,
+    text: String,
+    locale: Locale,
+    now: DateTime<Utc>,
+    threshold: f32,
+) -> NotifyDecision {
+    ConversationManager::default()
+        .with_threshold(threshold)
+        .handle(router, sessions, session_key, text, locale, now)
+        .await
+}
+
+// Runs the back-and-forth of /notify over the pending sessions: an unclear
+// request is asked about and read again with the answer, a cancel or snooze
+// that matched several reminders waits for a pick, as does a find-time offer.
+// "never mind" drops whatever is pending, and an unclear request is given up
+// on after `max_turns` follow-ups.
+#[derive(Debug, Clone, Copy)]
+pub struct ConversationManager {
+    threshold: f32,
+    max_turns: u32,
+}
+
+impl Default for ConversationManager {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_CONFIDENCE_THRESHOLD,
+            max_turns: DEFAULT_MAX_TURNS,
         }
     }
+}
 
-    let routing = router.route(&combined_text).await;
-    match routing.intent {
-        Intent::Notification => {
-            let session = PendingSession {
-                state: SessionState::PendingNotification,
-                original_text: combined_text,
-                last_prompt_at: now,
-            };
-            sessions.insert(session_key, session);
-            NotifyDecision::EmitNotify {
-                normalized_text: routing.normalized_text,
+impl ConversationManager {
+    // Guesses the router is less sure of than this are asked about.
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_max_turns(mut self, max_turns: u32) -> Self {
+        self.max_turns = max_turns.max(1);
+        self
+    }
+
+    // Asks which reminder was meant; the next message picks one.
+    pub fn ask_which_reminder(
+        &self,
+        sessions: &mut HashMap<SessionKey, PendingSession>,
+        session_key: SessionKey,
+        action: ReminderAction,
+        candidates: Vec<(String, String)>,
+        text: &str,
+        now: DateTime<Utc>,
+    ) -> NotifyDecision {
+        let options = candidates.iter().map(|(_, content)| content.clone()).collect();
+        let state = SessionState::ChooseReminder { action, candidates };
+        sessions.insert(session_key, PendingSession::new(state, text, now));
+        NotifyDecision::ChooseReminder { options }
+    }
+
+    // A find-time request went out; a number in the next message picks one
+    // of the slots it offers.
+    pub fn offer_slots(
+        &self,
+        sessions: &mut HashMap<SessionKey, PendingSession>,
+        session_key: SessionKey,
+        text: &str,
+        now: DateTime<Utc>,
+    ) {
+        sessions.insert(session_key, PendingSession::new(SessionState::ChooseSlot, text, now));
+    }
+
+    pub async fn handle(
+        &self,
+        router: &dyn IntentRouter,
+        sessions: &mut HashMap<SessionKey, PendingSession>,
+        session_key: SessionKey,
+        text: String,
+        locale: Locale,
+        now: DateTime<Utc>,
+    ) -> NotifyDecision {
+        let expired = sessions
+            .get(&session_key)
+            .is_some_and(|session| now - session.last_prompt_at > Duration::minutes(SESSION_TTL_MINUTES));
+        if expired {
+            sessions.remove(&session_key);
+        }
+        if is_cancel_phrase(&text) {
+            sessions.remove(&session_key);
+            return NotifyDecision::ConversationCanceled;
+        }
+
+        let mut combined_text = text;
+        let mut turns = 0;
+        if let Some(session) = sessions.get(&session_key).cloned() {
+            match session.state {
+                SessionState::Unknown => {
+                    combined_text = format!("{} {}", session.original_text, combined_text);
+                    turns = session.turns + 1;
+                }
+                // Anything but a pick is a new request.
+                SessionState::ChooseReminder { action, candidates } => {
+                    sessions.remove(&session_key);
+                    if let Some(reminder_id) = pick_candidate(&combined_text, &candidates) {
+                        return NotifyDecision::ActOnReminder { action, reminder_id };
+                    }
+                }
+                SessionState::ChooseSlot => {
+                    sessions.remove(&session_key);
+                    if let Some(index) = parse_choice(&combined_text, usize::MAX) {
+                        return NotifyDecision::SlotPicked { index };
+                    }
+                }
+                SessionState::PendingNotification | SessionState::PendingTodo => {}
             }
         }
-        Intent::Todolist => {
-            let session = PendingSession {
-                state: SessionState::PendingTodo,
-                original_text: combined_text,
-                last_prompt_at: now,
-            };
+
+        let mut routes = router.route_many(&combined_text, locale).await;
+        if routes.len() > 1
+            && routes
+                .iter()
+                .all(|route| route.intent != Intent::Unknown && route.confidence >= self.threshold)
+        {
+            sessions.remove(&session_key);
+            return NotifyDecision::EmitMany { parts: routes };
+        }
+        // Several requests that aren't all clear are asked about as a whole.
+        let routing = match routes.len() {
+            1 => routes.remove(0),
+            _ => IntentResult {
+                intent: Intent::Unknown,
+                normalized_text: combined_text.trim().to_string(),
+                confidence: 0.0,
+            },
+        };
+        let unsure = routing.intent != Intent::Unknown && routing.confidence < self.threshold;
+        if (unsure || routing.intent == Intent::Unknown) && turns >= self.max_turns {
+            sessions.remove(&session_key);
+            return NotifyDecision::GaveUp;
+        }
+        if unsure {
+            let mut session = PendingSession::new(SessionState::Unknown, combined_text, now);
+            session.turns = turns;
             sessions.insert(session_key, session);
-            NotifyDecision::EmitTodo {
+            return NotifyDecision::ClarifyIntent {
+                intent: routing.intent,
                 normalized_text: routing.normalized_text,
-            }
-        }
-        Intent::Unknown => {
-            let session = PendingSession {
-                state: SessionState::Unknown,
-                original_text: combined_text,
-                last_prompt_at: now,
             };
-            sessions.insert(session_key, session);
-            NotifyDecision::NeedClarification
+        }
+        match routing.intent {
+            Intent::Notification => {
+                let session = PendingSession::new(SessionState::PendingNotification, combined_text, now);
+                sessions.insert(session_key, session);
+                NotifyDecision::EmitNotify {
+                    normalized_text: routing.normalized_text,
+                }
+            }
+            Intent::Todolist => {
+                let session = PendingSession::new(SessionState::PendingTodo, combined_text, now);
+                sessions.insert(session_key, session);
+                NotifyDecision::EmitTodo {
+                    normalized_text: routing.normalized_text,
+                }
+            }
+            // Nothing to follow up on; the approval prompt takes it from here.
+            Intent::ToolUse => {
+                sessions.remove(&session_key);
+                NotifyDecision::EmitCalendarEvent {
+                    normalized_text: routing.normalized_text,
+                }
+            }
+            Intent::Query => {
+                sessions.remove(&session_key);
+                NotifyDecision::EmitQuery {
+                    normalized_text: routing.normalized_text,
+                }
+            }
+            Intent::Cancel => {
+                sessions.remove(&session_key);
+                NotifyDecision::EmitCancel {
+                    normalized_text: routing.normalized_text,
+                }
+            }
+            Intent::Snooze => {
+                sessions.remove(&session_key);
+                NotifyDecision::EmitSnooze {
+                    normalized_text: routing.normalized_text,
+                }
+            }
+            Intent::Unknown => {
+                let mut session = PendingSession::new(SessionState::Unknown, combined_text, now);
+                session.turns = turns;
+                sessions.insert(session_key, session);
+                NotifyDecision::NeedClarification
+            }
         }
     }
 }
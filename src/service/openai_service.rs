@@ -1,5 +1,13 @@
+use crate::clients::cassette::Cassette;
 use crate::clients::openai_client;
+use crate::config::{AppConfig, SharedConfig};
+use chrono_tz::Tz;
+use serde::de::DeserializeOwned;
 use serenity::async_trait;
+use std::env;
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::Instrument;
 
 #[async_trait]
 pub trait OpenAIClient: Send + Sync {
@@ -8,23 +16,236 @@ pub trait OpenAIClient: Send + Sync {
         prompt: &str,
         prompt_type: &str,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+
+    // For prompts that read or write times: `zone` is the user's timezone,
+    // which the prompt tells the model to work in. Clients that ignore it get
+    // plain generate_prompt.
+    async fn generate_prompt_in(
+        &self,
+        prompt: &str,
+        prompt_type: &str,
+        zone: Tz,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let _ = zone;
+        self.generate_prompt(prompt, prompt_type).await
+    }
+}
+
+// Reads a JSON reply from the model, falling back to repair_json when it
+// doesn't parse as sent. The error is from the reply as sent.
+pub fn parse_model_json<T: DeserializeOwned>(payload: &str) -> Result<T, serde_json::Error> {
+    serde_json::from_str(payload).or_else(|err| serde_json::from_str(&repair_json(payload)).map_err(|_| err))
+}
+
+// Undoes what models tend to do to JSON despite being told not to: wrap it in
+// a markdown code fence or a sentence, use curly quotes, leave trailing
+// commas. Returns the first object or array in `payload`.
+pub fn repair_json(payload: &str) -> String {
+    let mut text = strip_code_fence(payload).to_string();
+    // Curly quotes are only delimiters when there are no straight ones;
+    // otherwise they are someone's quoted words inside a string.
+    if !text.contains('"') {
+        text = text.replace(['\u{201C}', '\u{201D}'], "\"");
+    }
+    drop_trailing_commas(first_json_value(&text))
+}
+
+fn strip_code_fence(text: &str) -> &str {
+    let Some(start) = text.find("```") else {
+        return text;
+    };
+    // Skips the language tag, e.g. ```json.
+    let body = &text[start + 3..];
+    let body = body.find('\n').map_or(body, |newline| &body[newline + 1..]);
+    body.find("```").map_or(body, |end| &body[..end])
+}
+
+// Tracks whether a scan is inside a JSON string, escapes included.
+#[derive(Default)]
+struct StringState {
+    in_string: bool,
+    escaped: bool,
+}
+
+impl StringState {
+    // True when `c` is part of a string, opening and closing quotes included.
+    fn step(&mut self, c: char) -> bool {
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if c == '\\' {
+                self.escaped = true;
+            } else if c == '"' {
+                self.in_string = false;
+            }
+            return true;
+        }
+        self.in_string = c == '"';
+        self.in_string
+    }
+}
+
+fn first_json_value(text: &str) -> &str {
+    let Some(start) = text.find(['{', '[']) else {
+        return text.trim();
+    };
+    let mut state = StringState::default();
+    let mut depth = 0;
+    for (offset, c) in text[start..].char_indices() {
+        if state.step(c) {
+            continue;
+        }
+        match c {
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &text[start..start + offset + c.len_utf8()];
+                }
+            }
+            _ => {}
+        }
+    }
+    text[start..].trim_end()
+}
+
+fn drop_trailing_commas(json: &str) -> String {
+    let chars: Vec<char> = json.chars().collect();
+    let mut state = StringState::default();
+    let mut repaired = String::with_capacity(json.len());
+    for (index, &c) in chars.iter().enumerate() {
+        let in_string = state.step(c);
+        let trailing = !in_string
+            && c == ','
+            && chars[index + 1..]
+                .iter()
+                .find(|next| !next.is_whitespace())
+                .is_some_and(|next| matches!(next, '}' | ']'));
+        if !trailing {
+            repaired.push(c);
+        }
+    }
+    repaired
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    // Call OpenAI and save every response to the cassette.
+    Record,
+    // Answer only from the cassette; never touches the network.
+    Replay,
+}
+
+impl CassetteMode {
+    pub fn from_env_value(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "record" => Some(Self::Record),
+            "replay" => Some(Self::Replay),
+            _ => None,
+        }
+    }
+}
+
+pub const DEFAULT_CASSETTE_PATH: &str = "tests/fixtures/openai_cassette.json";
+
+enum Transport {
+    Live,
+    Record(Mutex<Cassette>),
+    Replay(Cassette),
+}
+
+impl Transport {
+    fn name(&self) -> &'static str {
+        match self {
+            Transport::Live => "live",
+            Transport::Record(_) => "record",
+            Transport::Replay(_) => "replay",
+        }
+    }
 }
 
 pub struct OpenAIService {
     api_key: String,
+    transport: Transport,
+    config: SharedConfig,
 }
 
 impl OpenAIService {
+    // OPENAI_CASSETTE=record|replay swaps the live API for a recorded cassette
+    // at OPENAI_CASSETTE_PATH (default tests/fixtures/openai_cassette.json).
     pub fn new(api_key: String) -> Self {
-        Self { api_key }
+        let Some(mode) = env::var("OPENAI_CASSETTE")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+        else {
+            return Self {
+                api_key,
+                transport: Transport::Live,
+                config: AppConfig::default().shared(),
+            };
+        };
+        let mode = CassetteMode::from_env_value(&mode)
+            .unwrap_or_else(|| panic!("Unsupported OPENAI_CASSETTE {}. Use record or replay.", mode));
+        let path = env::var("OPENAI_CASSETTE_PATH").unwrap_or(DEFAULT_CASSETTE_PATH.to_string());
+        Self::with_cassette(api_key, mode, &path).expect("Unable to load OpenAI cassette.")
+    }
+
+    pub fn with_cassette(api_key: String, mode: CassetteMode, path: &str) -> Result<Self, String> {
+        let cassette = Cassette::load(path)?;
+        let transport = match mode {
+            CassetteMode::Record => Transport::Record(Mutex::new(cassette)),
+            CassetteMode::Replay => Transport::Replay(cassette),
+        };
+        Ok(Self {
+            api_key,
+            transport,
+            config: AppConfig::default().shared(),
+        })
+    }
+
+    // Model choice and prompt overrides are read from `config` on every call,
+    // so a reloaded config file applies to the next request.
+    pub fn with_config(mut self, config: SharedConfig) -> Self {
+        self.config = config;
+        self
     }
 
     async fn generate_prompt_internal(
         &self,
         prompt: &str,
         prompt_type: &str,
+        zone: Tz,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        openai_client::generate_openai_prompt(prompt, prompt_type, &self.api_key).await
+        let config = self.config.load_full();
+        match &self.transport {
+            Transport::Live => {
+                openai_client::generate_openai_prompt(prompt, prompt_type, zone, &self.api_key, &config)
+                    .await
+            }
+            Transport::Replay(cassette) => cassette
+                .lookup(prompt_type, prompt)
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    format!(
+                        "No recorded {} response for {:?}; re-record with OPENAI_CASSETTE=record",
+                        prompt_type, prompt
+                    )
+                    .into()
+                }),
+            Transport::Record(cassette) => {
+                let response = openai_client::generate_openai_prompt(
+                    prompt,
+                    prompt_type,
+                    zone,
+                    &self.api_key,
+                    &config,
+                )
+                .await?;
+                let mut cassette = cassette.lock().unwrap_or_else(|e| e.into_inner());
+                cassette.record(prompt_type, prompt, &response)?;
+                Ok(response)
+            }
+        }
     }
 }
 
@@ -35,6 +256,53 @@ impl OpenAIClient for OpenAIService {
         prompt: &str,
         prompt_type: &str,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        self.generate_prompt_internal(prompt, prompt_type).await
+        self.generate_prompt_in(prompt, prompt_type, chrono_tz::America::New_York).await
+    }
+
+    async fn generate_prompt_in(
+        &self,
+        prompt: &str,
+        prompt_type: &str,
+        zone: Tz,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let span = tracing::info_span!(
+            "llm_call",
+            prompt_type,
+            transport = self.transport.name()
+        );
+        async {
+            let started = Instant::now();
+            let result = self.generate_prompt_internal(prompt, prompt_type, zone).await;
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => tracing::info!(elapsed_ms, "llm call finished"),
+                Err(err) => tracing::warn!(elapsed_ms, error = %err, "llm call failed"),
+            }
+            result
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::notification::AINotification;
+
+    #[test]
+    fn common_model_mistakes_are_repaired() {
+        let fenced = "Here you go:\n```json\n{\"content\":\"call mom\",\"time\":\"2026-02-01T17:00:00Z\",\"offsets\":[30,],}\n```";
+        let ai: AINotification = parse_model_json(fenced).unwrap();
+        assert_eq!((ai.content.as_str(), ai.offsets), ("call mom", vec![30]));
+
+        let curly = "{\u{201C}content\u{201D}: \u{201C}pay rent\u{201D}, \u{201C}time\u{201D}: \u{201C}2026-02-01T17:00:00Z\u{201D}}";
+        assert_eq!(parse_model_json::<AINotification>(curly).unwrap().content, "pay rent");
+
+        // Quotes, commas and braces inside strings are left alone.
+        let quoted = "{\"content\":\"read \u{201C}Dune, }\u{201D}\",\"time\":\"2026-02-01T17:00:00Z\"} and more";
+        assert_eq!(parse_model_json::<AINotification>(quoted).unwrap().content, "read \u{201C}Dune, }\u{201D}");
+
+        assert!(parse_model_json::<AINotification>("Sorry, I can't help with that.").is_err());
     }
 }
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    tokens: f64,
+    updated_at: DateTime<Utc>,
+}
+
+// Token bucket per key (user id): `capacity` requests in a burst, then one
+// more every `refill_every`.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    capacity: u32,
+    refill_every: Duration,
+    buckets: HashMap<String, Bucket>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(5, Duration::seconds(12))
+    }
+}
+
+impl RateLimiter {
+    pub fn new(capacity: u32, refill_every: Duration) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            refill_every,
+            buckets: HashMap::new(),
+        }
+    }
+
+    // Takes a token for `key`, or returns when the next one becomes available.
+    pub fn check(&mut self, key: &str, now: DateTime<Utc>) -> Result<(), DateTime<Utc>> {
+        let capacity = f64::from(self.capacity);
+        let refill_ms = self.refill_every.num_milliseconds().max(1) as f64;
+        let bucket = self.buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+        let elapsed_ms = (now - bucket.updated_at).num_milliseconds().max(0) as f64;
+        bucket.tokens = (bucket.tokens + elapsed_ms / refill_ms).min(capacity);
+        bucket.updated_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let wait_ms = ((1.0 - bucket.tokens) * refill_ms).ceil() as i64;
+        Err(now + Duration::milliseconds(wait_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn allows_burst_then_reports_reset_time() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        let mut limiter = RateLimiter::new(2, Duration::seconds(10));
+        assert!(limiter.check("@u", now).is_ok());
+        assert!(limiter.check("@u", now).is_ok());
+        assert_eq!(limiter.check("@u", now), Err(now + Duration::seconds(10)));
+        // Other users have their own bucket.
+        assert!(limiter.check("@other", now).is_ok());
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        let mut limiter = RateLimiter::new(1, Duration::seconds(10));
+        assert!(limiter.check("@u", now).is_ok());
+        assert_eq!(
+            limiter.check("@u", now + Duration::seconds(4)),
+            Err(now + Duration::seconds(10))
+        );
+        assert!(limiter.check("@u", now + Duration::seconds(10)).is_ok());
+    }
+}
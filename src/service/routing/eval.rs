@@ -0,0 +1,238 @@
+use std::fs;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::i18n::Locale;
+use crate::service::routing::{HeuristicRouter, Intent, IntentRouter};
+
+// One line of a corpus file:
+//   {"text": "call mom tomorrow at 5", "intent": "notification"}
+//   {"text": "Mama anrufen", "intent": "todolist", "locale": "de"}
+#[derive(Debug, Deserialize)]
+struct RawUtterance {
+    text: String,
+    intent: String,
+    #[serde(default)]
+    locale: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LabeledUtterance {
+    pub text: String,
+    pub expected: Intent,
+    pub locale: Locale,
+}
+
+// Blank lines and lines starting with '#' are skipped, so a corpus can be
+// grouped and annotated.
+pub fn parse_corpus(raw: &str) -> Result<Vec<LabeledUtterance>, String> {
+    raw.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(number, line)| {
+            let parsed: RawUtterance =
+                serde_json::from_str(line).map_err(|err| format!("line {}: {}", number + 1, err))?;
+            let expected = Intent::from_name(&parsed.intent)
+                .ok_or_else(|| format!("line {}: unknown intent '{}'", number + 1, parsed.intent))?;
+            let locale = match parsed.locale.as_deref() {
+                Some(code) => Locale::from_code(code)
+                    .ok_or_else(|| format!("line {}: unknown locale '{}'", number + 1, code))?,
+                None => Locale::En,
+            };
+            Ok(LabeledUtterance {
+                text: parsed.text,
+                expected,
+                locale,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntentScore {
+    pub intent: Intent,
+    // How often it was labeled; true positives plus false negatives.
+    pub support: usize,
+    pub true_positives: usize,
+    pub false_positives: usize,
+}
+
+impl IntentScore {
+    // None when the router never chose this intent.
+    pub fn precision(&self) -> Option<f32> {
+        let chosen = self.true_positives + self.false_positives;
+        (chosen > 0).then(|| self.true_positives as f32 / chosen as f32)
+    }
+
+    // None when no utterance is labeled with this intent.
+    pub fn recall(&self) -> Option<f32> {
+        (self.support > 0).then(|| self.true_positives as f32 / self.support as f32)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Miss {
+    pub text: String,
+    pub expected: Intent,
+    pub got: Intent,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct EvalReport {
+    pub total: usize,
+    pub correct: usize,
+    // Every intent that was labeled or chosen at least once.
+    pub scores: Vec<IntentScore>,
+    pub misses: Vec<Miss>,
+}
+
+// Routes each utterance in its own locale and compares the first intent the
+// router returns with the label.
+pub async fn evaluate(router: &dyn IntentRouter, corpus: &[LabeledUtterance]) -> EvalReport {
+    let mut scores: Vec<IntentScore> = Intent::ALL
+        .into_iter()
+        .map(|intent| IntentScore {
+            intent,
+            support: 0,
+            true_positives: 0,
+            false_positives: 0,
+        })
+        .collect();
+    let mut misses = Vec::new();
+    for utterance in corpus {
+        let result = router.route_for_locale(&utterance.text, utterance.locale).await;
+        for score in scores.iter_mut() {
+            if score.intent == utterance.expected {
+                score.support += 1;
+                if result.intent == utterance.expected {
+                    score.true_positives += 1;
+                }
+            } else if score.intent == result.intent {
+                score.false_positives += 1;
+            }
+        }
+        if result.intent != utterance.expected {
+            misses.push(Miss {
+                text: utterance.text.clone(),
+                expected: utterance.expected.clone(),
+                got: result.intent,
+                confidence: result.confidence,
+            });
+        }
+    }
+    scores.retain(|score| score.support > 0 || score.false_positives > 0);
+    EvalReport {
+        total: corpus.len(),
+        correct: corpus.len() - misses.len(),
+        scores,
+        misses,
+    }
+}
+
+fn ratio(value: Option<f32>) -> String {
+    value.map(|value| format!("{:.2}", value)).unwrap_or_else(|| "-".to_string())
+}
+
+pub fn render_report(report: &EvalReport) -> String {
+    let accuracy = if report.total == 0 {
+        0.0
+    } else {
+        report.correct as f32 * 100.0 / report.total as f32
+    };
+    let mut out = format!(
+        "Routed {} utterances: {} correct ({:.1}%)\n\n{:<14}{:>10}{:>8}{:>9}",
+        report.total, report.correct, accuracy, "intent", "precision", "recall", "support"
+    );
+    for score in &report.scores {
+        out.push_str(&format!(
+            "\n{:<14}{:>10}{:>8}{:>9}",
+            score.intent.name(),
+            ratio(score.precision()),
+            ratio(score.recall()),
+            score.support
+        ));
+    }
+    if !report.misses.is_empty() {
+        out.push_str("\n\nMisses:");
+        for miss in &report.misses {
+            out.push_str(&format!(
+                "\n  \"{}\": expected {}, got {} ({:.2})",
+                miss.text,
+                miss.expected.name(),
+                miss.got.name(),
+                miss.confidence
+            ));
+        }
+    }
+    out
+}
+
+// `route-eval --corpus <file.jsonl> [--router configured|heuristic]`. The
+// configured router is the one the bot would use (ROUTER_MODE, ROUTER_RULES_FILE);
+// `heuristic` measures the keyword rules alone and needs no API key.
+pub async fn route_eval_command(args: &[String], configured: Arc<dyn IntentRouter>) -> Result<String, String> {
+    let usage = "Usage: route-eval --corpus <file.jsonl> [--router configured|heuristic]";
+    let mut corpus_path: Option<&String> = None;
+    let mut router = configured;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--corpus" => corpus_path = Some(args.next().ok_or(usage)?),
+            "--router" => match args.next().map(String::as_str) {
+                Some("configured") => {}
+                Some("heuristic") => router = Arc::new(HeuristicRouter),
+                _ => return Err(usage.to_string()),
+            },
+            _ => return Err(usage.to_string()),
+        }
+    }
+    let path = corpus_path.ok_or(usage)?;
+    let raw = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let corpus = parse_corpus(&raw)?;
+    if corpus.is_empty() {
+        return Err(format!("{} has no utterances", path));
+    }
+    Ok(render_report(&evaluate(router.as_ref(), &corpus).await))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORPUS: &str = r#"
+# reminders
+{"text": "call mom tomorrow at 5", "intent": "notification"}
+{"text": "Zahnarzt Freitag", "intent": "notification", "locale": "de"}
+{"text": "buy milk", "intent": "todolist"}
+{"text": "just a thought", "intent": "todolist"}
+"#;
+
+    #[tokio::test]
+    async fn scores_each_intent_against_the_labels() {
+        let corpus = parse_corpus(CORPUS).unwrap();
+        assert_eq!(corpus.len(), 4);
+        let report = evaluate(&HeuristicRouter, &corpus).await;
+        assert_eq!((report.total, report.correct), (4, 3));
+
+        let score = |intent: Intent| report.scores.iter().find(|score| score.intent == intent).unwrap();
+        assert_eq!(score(Intent::Notification).precision(), Some(1.0));
+        assert_eq!(score(Intent::Todolist).recall(), Some(0.5));
+        assert_eq!(score(Intent::Unknown).support, 0);
+        assert_eq!(score(Intent::Unknown).precision(), Some(0.0));
+        assert!(!report.scores.iter().any(|score| score.intent == Intent::Snooze));
+
+        let rendered = render_report(&report);
+        assert!(rendered.starts_with("Routed 4 utterances: 3 correct (75.0%)"), "{}", rendered);
+        assert!(rendered.ends_with("\"just a thought\": expected todolist, got unknown (0.00)"), "{}", rendered);
+    }
+
+    #[test]
+    fn bad_corpus_lines_are_reported_by_number() {
+        let err = parse_corpus("{\"text\": \"x\", \"intent\": \"remind\"}").unwrap_err();
+        assert_eq!(err, "line 1: unknown intent 'remind'");
+        let err = parse_corpus("\n{\"text\": \"x\", \"intent\": \"todolist\", \"locale\": \"xx\"}").unwrap_err();
+        assert_eq!(err, "line 2: unknown locale 'xx'");
+    }
+}
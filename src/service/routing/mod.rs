@@ -0,0 +1,982 @@
+use crate::i18n::Locale;
+use crate::service::openai_service::{OpenAIClient, parse_model_json};
+use serde::Deserialize;
+use serenity::async_trait;
+use std::sync::Arc;
+
+pub mod eval;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Intent {
+    Notification,
+    Todolist,
+    // Asks the bot to act elsewhere, e.g. put an event on the calendar.
+    ToolUse,
+    // About reminders that already exist: "what do I have this week",
+    // "cancel the dentist one", "snooze my rent reminder a day".
+    Query,
+    Cancel,
+    Snooze,
+    Unknown,
+}
+
+impl Intent {
+    pub const ALL: [Intent; 7] = [
+        Intent::Notification,
+        Intent::Todolist,
+        Intent::ToolUse,
+        Intent::Query,
+        Intent::Cancel,
+        Intent::Snooze,
+        Intent::Unknown,
+    ];
+
+    // The names used by the router prompt and the rules file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Intent::Notification => "notification",
+            Intent::Todolist => "todolist",
+            Intent::ToolUse => "tooluse",
+            Intent::Query => "query",
+            Intent::Cancel => "cancel",
+            Intent::Snooze => "snooze",
+            Intent::Unknown => "unknown",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Intent::ALL.into_iter().find(|intent| intent.name() == name)
+    }
+}
+
+// Below this, route_notify asks before acting on the router's guess.
+pub const DEFAULT_CONFIDENCE_THRESHOLD: f32 = 0.5;
+// The keyword rules are never certain. A date, weekday or clock time is a
+// strong sign of a reminder; "in", "on", "this" or a task verb alone is a
+// guess worth checking.
+const STRONG_CONFIDENCE: f32 = 0.9;
+const WEAK_CONFIDENCE: f32 = 0.6;
+// A CascadeRouter keeps heuristic results at least this sure.
+pub const DEFAULT_CASCADE_CONFIDENCE: f32 = 0.8;
+
+#[derive(Debug, Clone)]
+pub struct IntentResult {
+    pub intent: Intent,
+    pub normalized_text: String,
+    // 0.0 to 1.0: how sure the router is of `intent`.
+    pub confidence: f32,
+}
+
+#[async_trait]
+pub trait IntentRouter: Send + Sync {
+    async fn route(&self, text: &str) -> IntentResult;
+
+    // Routers that understand other languages on their own ignore the locale.
+    async fn route_for_locale(&self, text: &str, _locale: Locale) -> IntentResult {
+        self.route(text).await
+    }
+
+    // Each separate request in the text, in order, e.g. a todo and a reminder
+    // in one message. Routers that can't tell them apart return one entry.
+    async fn route_many(&self, text: &str, locale: Locale) -> Vec<IntentResult> {
+        vec![self.route_for_locale(text, locale).await]
+    }
+}
+
+pub struct HeuristicRouter;
+
+#[async_trait]
+impl IntentRouter for HeuristicRouter {
+    async fn route(&self, text: &str) -> IntentResult {
+        route_intent(text)
+    }
+
+    async fn route_for_locale(&self, text: &str, locale: Locale) -> IntentResult {
+        route_intent_for_locale(text, locale)
+    }
+
+    async fn route_many(&self, text: &str, locale: Locale) -> Vec<IntentResult> {
+        route_intents_for_locale(text, locale)
+    }
+}
+
+pub struct OpenAIRouter {
+    openai: Arc<dyn OpenAIClient>,
+}
+
+impl OpenAIRouter {
+    pub fn new(openai: Arc<dyn OpenAIClient>) -> Self {
+        Self { openai }
+    }
+}
+
+impl OpenAIRouter {
+    // Never empty.
+    async fn ask(&self, text: &str) -> Vec<IntentResult> {
+        match self.openai.generate_prompt(text, "intent_router").await {
+            Ok(payload) => {
+                if let Some(results) = parse_router_payloads(&payload) {
+                    return results;
+                }
+                tracing::warn!(%payload, "intent router returned an invalid payload");
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "intent router call failed");
+            }
+        }
+        vec![IntentResult {
+            intent: Intent::Unknown,
+            normalized_text: text.trim().to_string(),
+            confidence: 0.0,
+        }]
+    }
+}
+
+#[async_trait]
+impl IntentRouter for OpenAIRouter {
+    // Callers that want one intent get the first request named.
+    async fn route(&self, text: &str) -> IntentResult {
+        self.ask(text).await.remove(0)
+    }
+
+    async fn route_many(&self, text: &str, _locale: Locale) -> Vec<IntentResult> {
+        self.ask(text).await
+    }
+}
+
+// Answers from the keyword rules when they are sure enough and asks a slower
+// router (normally the LLM) only about the rest, so "call mom tomorrow at 5"
+// costs no API call.
+pub struct CascadeRouter {
+    fast: Arc<dyn IntentRouter>,
+    slow: Arc<dyn IntentRouter>,
+    min_confidence: f32,
+}
+
+impl CascadeRouter {
+    pub fn new(slow: Arc<dyn IntentRouter>) -> Self {
+        Self {
+            fast: Arc::new(HeuristicRouter),
+            slow,
+            min_confidence: DEFAULT_CASCADE_CONFIDENCE,
+        }
+    }
+
+    pub fn with_fast(mut self, fast: Arc<dyn IntentRouter>) -> Self {
+        self.fast = fast;
+        self
+    }
+
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    fn settled(&self, result: &IntentResult) -> bool {
+        result.intent != Intent::Unknown && result.confidence >= self.min_confidence
+    }
+}
+
+#[async_trait]
+impl IntentRouter for CascadeRouter {
+    async fn route(&self, text: &str) -> IntentResult {
+        let result = self.fast.route(text).await;
+        if self.settled(&result) {
+            return result;
+        }
+        self.slow.route(text).await
+    }
+
+    async fn route_for_locale(&self, text: &str, locale: Locale) -> IntentResult {
+        let result = self.fast.route_for_locale(text, locale).await;
+        if self.settled(&result) {
+            return result;
+        }
+        self.slow.route_for_locale(text, locale).await
+    }
+
+    async fn route_many(&self, text: &str, locale: Locale) -> Vec<IntentResult> {
+        let results = self.fast.route_many(text, locale).await;
+        if results.iter().all(|result| self.settled(result)) {
+            return results;
+        }
+        self.slow.route_many(text, locale).await
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RouterPayload {
+    intent: String,
+    normalized_text: String,
+    // Custom prompt templates written before scores existed leave it out.
+    #[serde(default = "full_confidence")]
+    confidence: f32,
+}
+
+fn full_confidence() -> f32 {
+    1.0
+}
+
+// The prompt asks for a list; a bare object is what custom templates written
+// before multi-intent messages return.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RouterReply {
+    Many { intents: Vec<RouterPayload> },
+    One(RouterPayload),
+}
+
+fn to_result(parsed: RouterPayload) -> Option<IntentResult> {
+    let intent = Intent::from_name(&parsed.intent).unwrap_or(Intent::Unknown);
+    let normalized_text = parsed.normalized_text.trim().to_string();
+    if normalized_text.is_empty() {
+        return None;
+    }
+    Some(IntentResult {
+        intent,
+        normalized_text,
+        confidence: parsed.confidence.clamp(0.0, 1.0),
+    })
+}
+
+fn parse_router_payloads(payload: &str) -> Option<Vec<IntentResult>> {
+    let results: Vec<IntentResult> = match parse_model_json(payload).ok()? {
+        RouterReply::Many { intents } => intents.into_iter().filter_map(to_result).collect(),
+        RouterReply::One(parsed) => vec![to_result(parsed)?],
+    };
+    (!results.is_empty()).then_some(results)
+}
+
+#[cfg(test)]
+fn parse_router_payload(payload: &str) -> Option<IntentResult> {
+    parse_router_payloads(payload)?.into_iter().next()
+}
+
+// Splits on "and", "then" and semicolons, keeping the split only when every
+// part is recognised, stands on its own and they aren't all the same kind:
+// "buy milk and call mom at 6" is a todo and a reminder, "call mom at 6 and
+// dad at 7" is left whole, since the second half makes no sense on its own,
+// and so is "buy bread and butter tomorrow at 5pm", whose second half has no
+// verb of its own.
+pub fn route_intents_for_locale(text: &str, locale: Locale) -> Vec<IntentResult> {
+    let parts = split_requests(text);
+    if parts.len() > 1 {
+        let routed: Vec<IntentResult> = parts
+            .iter()
+            .map(|part| route_intent_for_locale(part, locale))
+            .collect();
+        let mixed = routed.iter().any(|result| result.intent != routed[0].intent);
+        let standalone = routed.iter().all(|result| is_standalone_request(result, locale));
+        if mixed && standalone {
+            return routed;
+        }
+    }
+    vec![route_intent_for_locale(text, locale)]
+}
+
+// A part of a split message is a request of its own when it says what to do:
+// a task verb, or a cancel/snooze/query, which open with their own command.
+fn is_standalone_request(result: &IntentResult, locale: Locale) -> bool {
+    match result.intent {
+        Intent::Unknown => false,
+        Intent::Cancel | Intent::Snooze | Intent::Query => true,
+        _ => {
+            let lower = result.normalized_text.to_lowercase();
+            [locale, Locale::En]
+                .into_iter()
+                .any(|locale| has_action_verb(&lower, locale_tokens(locale).verbs))
+        }
+    }
+}
+
+fn split_requests(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for word in text.split_whitespace() {
+        let lower = word.to_lowercase();
+        if matches!(lower.as_str(), "and" | "then" | "y" | "und" | "dann") {
+            parts.push(current.join(" "));
+            current.clear();
+        } else if let Some(word) = word.strip_suffix(';') {
+            current.push(word);
+            parts.push(current.join(" "));
+            current.clear();
+        } else {
+            current.push(word);
+        }
+    }
+    parts.push(current.join(" "));
+    parts.retain(|part| !part.trim_matches(|c: char| !c.is_alphanumeric()).is_empty());
+    parts
+}
+
+pub fn route_intent(text: &str) -> IntentResult {
+    route_intent_for_locale(text, Locale::En)
+}
+
+// The user's (or guild's) locale picks the word table; English is always
+// checked too, since mixed-language requests ("dentist tomorrow 3pm") are
+// common.
+pub fn route_intent_for_locale(text: &str, locale: Locale) -> IntentResult {
+    let normalized = text.trim().to_string();
+    if normalized.is_empty() {
+        return IntentResult {
+            intent: Intent::Unknown,
+            normalized_text: normalized,
+            confidence: 0.0,
+        };
+    }
+
+    let lower = normalized.to_lowercase();
+    let mut tables = vec![locale_tokens(locale)];
+    if locale != Locale::En {
+        tables.push(locale_tokens(Locale::En));
+    }
+    // Checked first: "snooze my rent reminder a day" names a time and
+    // "cancel the dentist one" starts with a task verb.
+    if let Some(intent) = tables.iter().find_map(|tokens| manage_intent(&lower, tokens.manage)) {
+        return IntentResult {
+            intent,
+            normalized_text: normalized,
+            confidence: STRONG_CONFIDENCE,
+        };
+    }
+    if CALENDAR_PHRASES.iter().any(|phrase| contains_phrase(&lower, phrase)) {
+        return IntentResult {
+            intent: Intent::ToolUse,
+            normalized_text: normalized,
+            confidence: STRONG_CONFIDENCE,
+        };
+    }
+    if tables.iter().any(|tokens| has_strong_time(&lower, tokens.time)) {
+        return IntentResult {
+            intent: Intent::Notification,
+            normalized_text: normalized,
+            confidence: STRONG_CONFIDENCE,
+        };
+    }
+    if tables.iter().any(|tokens| tokens.time.vague.iter().any(|t| contains_token(&lower, t))) {
+        return IntentResult {
+            intent: Intent::Notification,
+            normalized_text: normalized,
+            confidence: WEAK_CONFIDENCE,
+        };
+    }
+
+    // Without a time, something to do ("buy milk", "Mama anrufen") is a
+    // todo; anything else ("just a thought") needs clarifying.
+    if tables.iter().any(|tokens| has_action_verb(&lower, tokens.verbs)) {
+        return IntentResult {
+            intent: Intent::Todolist,
+            normalized_text: normalized,
+            confidence: WEAK_CONFIDENCE,
+        };
+    }
+
+    IntentResult {
+        intent: Intent::Unknown,
+        normalized_text: normalized,
+        confidence: 0.0,
+    }
+}
+
+const ENGLISH_VERBS: &[&str] = &[
+    "ask", "book", "buy", "call", "cancel", "check", "clean", "cook", "do", "email", "feed", "file",
+    "finish", "fix", "follow", "get", "grab", "install", "mail", "make", "message", "order", "pack",
+    "pay", "pick", "ping", "plan", "prep", "prepare", "print", "read", "renew", "replace", "reply",
+    "return", "review", "schedule", "send", "sign", "study", "submit", "take", "text", "update",
+    "visit", "walk", "wash", "water", "write",
+];
+
+const SPANISH_VERBS: &[&str] = &[
+    "arreglar", "cancelar", "cocinar", "comprar", "devolver", "enviar", "escribir", "estudiar",
+    "hacer", "lavar", "leer", "limpiar", "llamar", "mandar", "pagar", "pedir", "preparar",
+    "recoger", "regar", "renovar", "reservar", "revisar", "sacar", "terminar",
+];
+
+const GERMAN_VERBS: &[&str] = &[
+    "abholen", "anrufen", "aufräumen", "bestellen", "bezahlen", "buchen", "einkaufen", "erledigen",
+    "gießen", "kaufen", "kochen", "kündigen", "lernen", "lesen", "machen", "prüfen", "putzen",
+    "reparieren", "schicken", "schreiben", "senden", "verlängern", "vorbereiten", "waschen",
+    "zahlen", "zurückgeben",
+];
+
+const FRENCH_VERBS: &[&str] = &[
+    "acheter", "annuler", "appeler", "arroser", "chercher", "commander", "cuisiner", "écrire",
+    "envoyer", "faire", "finir", "imprimer", "laver", "lire", "nettoyer", "payer", "préparer",
+    "ranger", "récupérer", "rendre", "renouveler", "réparer", "réserver", "réviser", "sortir",
+    "terminer", "vérifier",
+];
+
+// Whole words only, so "dog" doesn't count as "do".
+fn has_action_verb(lower: &str, verbs: &[&str]) -> bool {
+    lower
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .any(|word| verbs.contains(&word))
+}
+
+struct ManageTokens {
+    // How a question about upcoming reminders starts.
+    query: &'static [&'static str],
+    cancel: &'static [&'static str],
+    snooze: &'static [&'static str],
+    // "cancel my gym membership" is a todo; a cancel has to name a reminder.
+    reminder: &'static [&'static str],
+}
+
+const ENGLISH_MANAGE: ManageTokens = ManageTokens {
+    query: &["what do i have", "what's on", "whats on", "what is on", "show my", "show me my", "list my"],
+    cancel: &["cancel", "delete", "remove"],
+    snooze: &["snooze", "postpone", "push back", "delay"],
+    reminder: &["reminder", "reminders", "one", "ping", "alarm"],
+};
+
+const SPANISH_MANAGE: ManageTokens = ManageTokens {
+    query: &["qué tengo", "que tengo", "muestra mis", "muéstrame mis", "muestrame mis"],
+    cancel: &["cancela", "borra", "elimina"],
+    snooze: &["pospón", "pospon", "aplaza", "retrasa"],
+    reminder: &["recordatorio", "recordatorios", "aviso"],
+};
+
+const GERMAN_MANAGE: ManageTokens = ManageTokens {
+    query: &["was habe ich", "was hab ich", "was steht", "zeig meine", "zeige meine"],
+    cancel: &["lösche", "storniere", "entferne"],
+    snooze: &["verschiebe", "schlummer"],
+    reminder: &["erinnerung", "erinnerungen"],
+};
+
+const FRENCH_MANAGE: ManageTokens = ManageTokens {
+    query: &["qu'est-ce que j'ai", "qu’est-ce que j’ai", "montre mes", "montre-moi mes", "affiche mes", "quels sont mes"],
+    cancel: &["annule", "supprime", "efface"],
+    snooze: &["repousse", "reporte", "décale"],
+    reminder: &["rappel", "rappels"],
+};
+
+fn starts_with_phrase(lower: &str, phrases: &[&str]) -> bool {
+    phrases.iter().any(|phrase| {
+        lower
+            .strip_prefix(phrase)
+            .is_some_and(|rest| rest.is_empty() || !rest.starts_with(char::is_alphanumeric))
+    })
+}
+
+fn manage_intent(lower: &str, tokens: &ManageTokens) -> Option<Intent> {
+    if starts_with_phrase(lower, tokens.query) {
+        return Some(Intent::Query);
+    }
+    if starts_with_phrase(lower, tokens.snooze) {
+        return Some(Intent::Snooze);
+    }
+    if starts_with_phrase(lower, tokens.cancel) && has_action_verb(lower, tokens.reminder) {
+        return Some(Intent::Cancel);
+    }
+    None
+}
+
+// "put it on my calendar" in any supported language means a calendar event
+// rather than a reminder. The bare word isn't enough: "review the calendar
+// export tomorrow" is still a reminder.
+const CALENDAR_PHRASES: &[&str] = &[
+    "to my calendar",
+    "on my calendar",
+    "in my calendar",
+    "to the calendar",
+    "on the calendar",
+    "a mi calendario",
+    "en mi calendario",
+    "al calendario",
+    "en el calendario",
+    "in meinen kalender",
+    "in meinem kalender",
+    "in den kalender",
+    "im kalender",
+    "zum kalender",
+    "à mon calendrier",
+    "dans mon calendrier",
+    "au calendrier",
+    "dans le calendrier",
+];
+
+// `phrase` as whole words somewhere in `lower`.
+fn contains_phrase(lower: &str, phrase: &str) -> bool {
+    lower.match_indices(phrase).any(|(start, _)| {
+        let before = lower[..start].chars().next_back();
+        let after = lower[start + phrase.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+// Tokens written with a trailing space ("um ", "in ") are short words that
+// only count on their own, not as the end of a longer one ("warum", "ein").
+fn contains_token(lower: &str, token: &str) -> bool {
+    match token.strip_suffix(' ') {
+        Some(word) => contains_phrase(lower, word),
+        None => lower.contains(token),
+    }
+}
+
+struct TimeTokens {
+    phrases: &'static [&'static str],
+    // Also common outside times ("in the garage", "this one").
+    vague: &'static [&'static str],
+    weekdays: &'static [&'static str],
+    months: &'static [&'static str],
+}
+
+const ENGLISH_TOKENS: TimeTokens = TimeTokens {
+    phrases: &["today", "tomorrow", "tonight"],
+    vague: &["morning", "afternoon", "evening", "next ", "this ", "at ", "in ", "on "],
+    weekdays: &[
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+        "sunday",
+    ],
+    months: &[
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ],
+};
+
+const SPANISH_TOKENS: TimeTokens = TimeTokens {
+    phrases: &["hoy", "mañana", "manana", "esta noche", "a las ", "dentro de "],
+    vague: &[
+        "tarde",
+        "noche",
+        "próximo",
+        "próxima",
+        "proximo",
+        "proxima",
+        "que viene",
+    ],
+    weekdays: &[
+        "lunes",
+        "martes",
+        "miércoles",
+        "miercoles",
+        "jueves",
+        "viernes",
+        "sábado",
+        "sabado",
+        "domingo",
+    ],
+    months: &[
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ],
+};
+
+const GERMAN_TOKENS: TimeTokens = TimeTokens {
+    phrases: &["heute", "morgen", "übermorgen", "um ", "uhr"],
+    vague: &["abend", "nachmittag", "nächste", "nächsten", "naechste", "am ", "in "],
+    weekdays: &[
+        "montag",
+        "dienstag",
+        "mittwoch",
+        "donnerstag",
+        "freitag",
+        "samstag",
+        "sonntag",
+    ],
+    months: &[
+        "januar",
+        "februar",
+        "märz",
+        "maerz",
+        "april",
+        "mai",
+        "juni",
+        "juli",
+        "august",
+        "september",
+        "oktober",
+        "november",
+        "dezember",
+    ],
+};
+
+const FRENCH_TOKENS: TimeTokens = TimeTokens {
+    phrases: &["aujourd'hui", "aujourd’hui", "demain", "ce soir", "cet après-midi"],
+    vague: &["matin", "après-midi", "apres-midi", "soir", "prochain", "prochaine", "à ", "dans "],
+    weekdays: &[
+        "lundi",
+        "mardi",
+        "mercredi",
+        "jeudi",
+        "vendredi",
+        "samedi",
+        "dimanche",
+    ],
+    months: &[
+        "janvier",
+        "février",
+        "fevrier",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "aout",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+        "decembre",
+    ],
+};
+
+// Everything the keyword router knows about one language. Supporting another
+// is one more of these and an arm in `locale_tokens`.
+struct LocaleTokens {
+    time: &'static TimeTokens,
+    verbs: &'static [&'static str],
+    manage: &'static ManageTokens,
+}
+
+const ENGLISH: LocaleTokens = LocaleTokens {
+    time: &ENGLISH_TOKENS,
+    verbs: ENGLISH_VERBS,
+    manage: &ENGLISH_MANAGE,
+};
+
+const SPANISH: LocaleTokens = LocaleTokens {
+    time: &SPANISH_TOKENS,
+    verbs: SPANISH_VERBS,
+    manage: &SPANISH_MANAGE,
+};
+
+const GERMAN: LocaleTokens = LocaleTokens {
+    time: &GERMAN_TOKENS,
+    verbs: GERMAN_VERBS,
+    manage: &GERMAN_MANAGE,
+};
+
+const FRENCH: LocaleTokens = LocaleTokens {
+    time: &FRENCH_TOKENS,
+    verbs: FRENCH_VERBS,
+    manage: &FRENCH_MANAGE,
+};
+
+fn locale_tokens(locale: Locale) -> &'static LocaleTokens {
+    match locale {
+        Locale::En => &ENGLISH,
+        Locale::Es => &SPANISH,
+        Locale::De => &GERMAN,
+        Locale::Fr => &FRENCH,
+    }
+}
+
+fn has_strong_time(lower: &str, tokens: &TimeTokens) -> bool {
+    if tokens.phrases.iter().any(|t| contains_token(lower, t)) {
+        return true;
+    }
+    if tokens.weekdays.iter().any(|d| lower.contains(d)) {
+        return true;
+    }
+    if tokens.months.iter().any(|m| lower.contains(m)) {
+        return true;
+    }
+
+    if lower.contains('/') || lower.contains(':') {
+        return lower.chars().any(|c| c.is_ascii_digit());
+    }
+
+    has_am_pm(lower) || has_hour_suffix(lower) || has_misspelled_time(lower, tokens)
+}
+
+// Day, weekday and month words short enough to be a letter away from too many
+// others ("may", "june", "today") have to be spelled right.
+const FUZZY_MIN_LEN: usize = 6;
+
+// "wednsday", "fridya", and "tommorow" once doubled letters are folded: one
+// letter added, dropped, changed or swapped with its neighbour. The first
+// letter has to match.
+fn has_misspelled_time(lower: &str, tokens: &TimeTokens) -> bool {
+    let targets: Vec<Vec<char>> = tokens
+        .phrases
+        .iter()
+        .chain(tokens.weekdays)
+        .chain(tokens.months)
+        .filter(|token| !token.contains(' ') && token.chars().count() >= FUZZY_MIN_LEN)
+        .map(|token| fold_doubles(token))
+        .collect();
+    lower
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| word.chars().count() >= FUZZY_MIN_LEN - 1)
+        .any(|word| {
+            let word = fold_doubles(word);
+            targets
+                .iter()
+                .any(|target| target.first() == word.first() && within_one_edit(&word, target))
+        })
+}
+
+fn fold_doubles(word: &str) -> Vec<char> {
+    let mut chars: Vec<char> = word.chars().collect();
+    chars.dedup();
+    chars
+}
+
+fn within_one_edit(a: &[char], b: &[char]) -> bool {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if long.len() - short.len() > 1 {
+        return false;
+    }
+    let same = short.iter().zip(long).take_while(|(x, y)| x == y).count();
+    if same == short.len() {
+        return true;
+    }
+    if short.len() < long.len() {
+        return short[same..] == long[same + 1..];
+    }
+    let swapped = same + 1 < short.len()
+        && short[same] == long[same + 1]
+        && short[same + 1] == long[same]
+        && short[same + 2..] == long[same + 2..];
+    swapped || short[same + 1..] == long[same + 1..]
+}
+
+// "17h", "9h30": how French (and often Spanish and German) write the time.
+fn has_hour_suffix(lower: &str) -> bool {
+    lower.split_whitespace().any(|word| {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+        match word.split_once('h') {
+            Some((hours, minutes)) => {
+                !hours.is_empty()
+                    && hours.len() <= 2
+                    && hours.chars().all(|c| c.is_ascii_digit())
+                    && (minutes.is_empty() || (minutes.len() == 2 && minutes.chars().all(|c| c.is_ascii_digit())))
+            }
+            None => false,
+        }
+    })
+}
+
+fn has_am_pm(lower: &str) -> bool {
+    let bytes = lower.as_bytes();
+    for i in 0..bytes.len().saturating_sub(1) {
+        let first = bytes[i];
+        let second = bytes[i + 1];
+        if (first == b'a' || first == b'p') && second == b'm' {
+            let before = if i == 0 { None } else { Some(bytes[i - 1]) };
+            let after = if i + 2 >= bytes.len() { None } else { Some(bytes[i + 2]) };
+            let boundary_before = before.map_or(true, |b| !b.is_ascii_alphabetic());
+            let boundary_after = after.map_or(true, |b| !b.is_ascii_alphabetic());
+            if boundary_before && boundary_after {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spanish_time_phrases_route_to_notification() {
+        let result = route_intent_for_locale("llamar a mamá mañana", Locale::Es);
+        assert_eq!(result.intent, Intent::Notification);
+        let result = route_intent_for_locale("dentista el viernes", Locale::Es);
+        assert_eq!(result.intent, Intent::Notification);
+        let result = route_intent_for_locale("comprar leche", Locale::Es);
+        assert_eq!(result.intent, Intent::Todolist);
+    }
+
+    #[test]
+    fn german_time_phrases_route_to_notification() {
+        let result = route_intent_for_locale("Mama anrufen morgen", Locale::De);
+        assert_eq!(result.intent, Intent::Notification);
+        let result = route_intent_for_locale("Zahnarzt Freitag", Locale::De);
+        assert_eq!(result.intent, Intent::Notification);
+        // "warum" and "ein" aren't "um" and "in".
+        let result = route_intent_for_locale("Buch kaufen, warum nicht", Locale::De);
+        assert_eq!(result.intent, Intent::Todolist);
+        let result = route_intent_for_locale("ein Geschenk kaufen", Locale::De);
+        assert_eq!(result.intent, Intent::Todolist);
+        let result = route_intent_for_locale("Zahnarzt um 15 Uhr", Locale::De);
+        assert_eq!(result.intent, Intent::Notification);
+        // Locale tables only apply to users who chose that locale.
+        let result = route_intent_for_locale("Zahnarzt Freitag", Locale::En);
+        assert_eq!(result.intent, Intent::Unknown);
+    }
+
+    #[test]
+    fn french_time_phrases_route_to_notification() {
+        let result = route_intent_for_locale("appeler maman demain", Locale::Fr);
+        assert_eq!(result.intent, Intent::Notification);
+        assert_eq!(result.confidence, STRONG_CONFIDENCE);
+        let result = route_intent_for_locale("dentiste vendredi à 17h30", Locale::Fr);
+        assert_eq!(result.intent, Intent::Notification);
+        let result = route_intent_for_locale("acheter du lait", Locale::Fr);
+        assert_eq!(result.intent, Intent::Todolist);
+        let result = route_intent_for_locale("annule le rappel dentiste", Locale::Fr);
+        assert_eq!(result.intent, Intent::Cancel);
+        // Without the French table neither word means anything.
+        let result = route_intent_for_locale("dentiste vendredi", Locale::En);
+        assert_eq!(result.intent, Intent::Unknown);
+    }
+
+    #[test]
+    fn misspelled_days_still_route_to_notification() {
+        for text in ["call mom tommorow", "dentist wednsday", "pay rent on fridya", "renew passport by septmber"] {
+            let result = route_intent(text);
+            assert_eq!(result.intent, Intent::Notification, "{}", text);
+            assert_eq!(result.confidence, STRONG_CONFIDENCE, "{}", text);
+        }
+        let result = route_intent_for_locale("Zahnarzt Mitwoch", Locale::De);
+        assert_eq!(result.intent, Intent::Notification);
+        // Too short to guess at, and too far from any day.
+        assert_eq!(route_intent("buy a maze").intent, Intent::Todolist);
+        assert_eq!(route_intent("water the sunflowers").intent, Intent::Todolist);
+    }
+
+    #[test]
+    fn action_verbs_without_a_time_route_to_todolist() {
+        assert_eq!(route_intent("buy milk").intent, Intent::Todolist);
+        assert_eq!(route_intent("Pick up the dry cleaning!").intent, Intent::Todolist);
+        assert_eq!(route_intent_for_locale("Mama anrufen", Locale::De).intent, Intent::Todolist);
+        assert_eq!(route_intent("just a thought").intent, Intent::Unknown);
+        assert_eq!(route_intent("walk the dog").intent, Intent::Todolist);
+        assert_eq!(route_intent("dog").intent, Intent::Unknown);
+    }
+
+    #[test]
+    fn questions_about_existing_reminders_are_not_new_ones() {
+        assert_eq!(route_intent("what do I have this week").intent, Intent::Query);
+        assert_eq!(route_intent("cancel the dentist one").intent, Intent::Cancel);
+        assert_eq!(route_intent("snooze my rent reminder a day").intent, Intent::Snooze);
+        assert_eq!(route_intent_for_locale("lösche die Erinnerung Zahnarzt", Locale::De).intent, Intent::Cancel);
+        assert_eq!(route_intent("cancel my gym membership").intent, Intent::Todolist);
+        assert_eq!(route_intent("showroom visit tomorrow at 5").intent, Intent::Notification);
+    }
+
+    #[test]
+    fn calendar_requests_route_to_tool_use() {
+        let result = route_intent("add a sync with Alex to my calendar Thursday at 2");
+        assert_eq!(result.intent, Intent::ToolUse);
+        let result = route_intent_for_locale("Termin in den Kalender: Zahnarzt Freitag", Locale::De);
+        assert_eq!(result.intent, Intent::ToolUse);
+        // Mentioning a calendar isn't asking for an event on it.
+        let result = route_intent("review the calendar export tomorrow at 10");
+        assert_eq!(result.intent, Intent::Notification);
+        assert_ne!(route_intent("file it in my calendars folder").intent, Intent::ToolUse);
+        let payload = r#"{"intent":"tooluse","normalized_text":"30-minute sync with Alex Thursday at 2"}"#;
+        assert_eq!(parse_router_payload(payload).unwrap().intent, Intent::ToolUse);
+    }
+
+    struct CountingRouter {
+        calls: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl IntentRouter for CountingRouter {
+        async fn route(&self, text: &str) -> IntentResult {
+            self.calls.lock().unwrap().push(text.to_string());
+            IntentResult {
+                intent: Intent::Todolist,
+                normalized_text: text.to_string(),
+                confidence: 0.95,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn cascade_only_escalates_unsure_heuristics() {
+        let slow = Arc::new(CountingRouter {
+            calls: std::sync::Mutex::new(Vec::new()),
+        });
+        let router = CascadeRouter::new(slow.clone());
+
+        let result = router.route("call mom tomorrow at 5").await;
+        assert_eq!(result.intent, Intent::Notification);
+        assert_eq!(result.confidence, STRONG_CONFIDENCE);
+        let result = router.route("add a sync with Alex to my calendar").await;
+        assert_eq!(result.intent, Intent::ToolUse);
+        assert!(slow.calls.lock().unwrap().is_empty());
+
+        // A task verb, a vague "in" and no signal at all all go to the LLM.
+        for text in ["buy milk", "keys in the car", "just a thought"] {
+            assert_eq!(router.route(text).await.intent, Intent::Todolist);
+        }
+        assert_eq!(*slow.calls.lock().unwrap(), vec!["buy milk", "keys in the car", "just a thought"]);
+
+        let eager = CascadeRouter::new(slow.clone()).with_min_confidence(0.5);
+        assert_eq!(eager.route("buy milk").await.confidence, WEAK_CONFIDENCE);
+        assert_eq!(slow.calls.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn separate_requests_in_one_message_are_split() {
+        let results = route_intents_for_locale(
+            "Add buy milk to my list and remind me to call mom at 6pm",
+            Locale::En,
+        );
+        let parts: Vec<(Intent, &str)> = results
+            .iter()
+            .map(|result| (result.intent.clone(), result.normalized_text.as_str()))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                (Intent::Todolist, "Add buy milk to my list"),
+                (Intent::Notification, "remind me to call mom at 6pm"),
+            ]
+        );
+        assert_eq!(route_intents_for_locale("call mom at 6pm and dad at 7pm", Locale::En).len(), 1);
+        assert_eq!(route_intents_for_locale("buy milk and eggs", Locale::En).len(), 1);
+        // An item list isn't cut into a todo and a verb-less reminder.
+        let whole = route_intents_for_locale("remind me to buy bread and butter tomorrow at 5pm", Locale::En);
+        assert_eq!(whole.len(), 1);
+        assert_eq!(whole[0].intent, Intent::Notification);
+
+        let payload = r#"{"intents":[
+            {"intent":"todolist","normalized_text":"buy milk","confidence":0.9},
+            {"intent":"notification","normalized_text":"call mom at 6pm","confidence":0.95}
+        ]}"#;
+        let results = parse_router_payloads(payload).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].intent, Intent::Notification);
+        assert!(parse_router_payloads(r#"{"intents":[]}"#).is_none());
+    }
+
+    #[test]
+    fn router_payload_confidence_is_clamped_and_optional() {
+        let payload = r#"{"intent":"todolist","normalized_text":"pay rent","confidence":0.35}"#;
+        assert_eq!(parse_router_payload(payload).unwrap().confidence, 0.35);
+        let payload = r#"{"intent":"todolist","normalized_text":"pay rent","confidence":7}"#;
+        assert_eq!(parse_router_payload(payload).unwrap().confidence, 1.0);
+        let payload = r#"{"intent":"todolist","normalized_text":"pay rent"}"#;
+        assert_eq!(parse_router_payload(payload).unwrap().confidence, 1.0);
+        let payload = "```json\n{\"intent\":\"todolist\",\"normalized_text\":\"pay rent\",}\n```";
+        assert_eq!(parse_router_payload(payload).unwrap().intent, Intent::Todolist);
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs;
+use std::sync::Arc;
+
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use serenity::async_trait;
+
+use crate::i18n::Locale;
+use crate::service::routing::{Intent, IntentResult, IntentRouter};
+
+// One entry of the rules file:
+//
+//   [[rule]]
+//   pattern = "^standup\\b"              # a regex, or
+//   keywords = ["groceries", "shopping"] # any of these words
+//   intent = "notification"              # notification|todolist|tooluse|query|cancel|snooze|unknown
+//   normalize = "team standup at 9:30am" # optional; replaces the match
+#[derive(Debug, Deserialize)]
+struct RawRule {
+    pattern: Option<String>,
+    #[serde(default)]
+    keywords: Vec<String>,
+    intent: String,
+    normalize: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<RawRule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    // Keywords are compiled to a whole-word alternation, so both kinds of
+    // rule match and normalize the same way.
+    matcher: Regex,
+    intent: Intent,
+    normalize: Option<String>,
+}
+
+// Operator-written rules checked before another router, so a recurring
+// misroute can be fixed in a file instead of in code or the prompt. The first
+// matching rule wins and is trusted fully; anything no rule matches goes to
+// the wrapped router.
+pub struct RulesRouter {
+    rules: Vec<Rule>,
+    fallback: Arc<dyn IntentRouter>,
+}
+
+impl RulesRouter {
+    pub fn from_toml(raw: &str, fallback: Arc<dyn IntentRouter>) -> Result<Self, String> {
+        let file: RulesFile = toml::from_str(raw).map_err(|e| format!("Invalid rules file: {}", e))?;
+        let rules = file
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(index, rule)| compile(rule).map_err(|err| format!("Rule {}: {}", index + 1, err)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules, fallback })
+    }
+
+    pub fn from_file(path: &str, fallback: Arc<dyn IntentRouter>) -> Result<Self, String> {
+        let raw = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_toml(&raw, fallback)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    fn apply(&self, text: &str) -> Option<IntentResult> {
+        let text = text.trim();
+        let rule = self.rules.iter().find(|rule| rule.matcher.is_match(text))?;
+        let normalized_text = match &rule.normalize {
+            Some(template) => rule.matcher.replace(text, template.as_str()).trim().to_string(),
+            None => text.to_string(),
+        };
+        Some(IntentResult {
+            intent: rule.intent.clone(),
+            normalized_text,
+            confidence: 1.0,
+        })
+    }
+}
+
+fn compile(rule: RawRule) -> Result<Rule, String> {
+    let intent = Intent::from_name(&rule.intent).ok_or_else(|| {
+        format!(
+            "unknown intent '{}' (expected notification, todolist, tooluse, query, cancel, snooze or unknown)",
+            rule.intent
+        )
+    })?;
+    let pattern = match (rule.pattern, rule.keywords.is_empty()) {
+        (Some(pattern), true) => pattern,
+        (None, false) => {
+            let words: Vec<String> = rule.keywords.iter().map(|word| regex::escape(word.trim())).collect();
+            format!(r"\b(?:{})\b", words.join("|"))
+        }
+        _ => return Err("give either a pattern or keywords".to_string()),
+    };
+    let matcher = RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("invalid pattern: {}", e))?;
+    Ok(Rule {
+        matcher,
+        intent,
+        normalize: rule.normalize,
+    })
+}
+
+#[async_trait]
+impl IntentRouter for RulesRouter {
+    async fn route(&self, text: &str) -> IntentResult {
+        match self.apply(text) {
+            Some(result) => result,
+            None => self.fallback.route(text).await,
+        }
+    }
+
+    async fn route_for_locale(&self, text: &str, locale: Locale) -> IntentResult {
+        match self.apply(text) {
+            Some(result) => result,
+            None => self.fallback.route_for_locale(text, locale).await,
+        }
+    }
+
+    async fn route_many(&self, text: &str, locale: Locale) -> Vec<IntentResult> {
+        match self.apply(text) {
+            Some(result) => vec![result],
+            None => self.fallback.route_many(text, locale).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::routing::HeuristicRouter;
+
+    const RULES: &str = r#"
+[[rule]]
+pattern = "^standup\\b"
+intent = "notification"
+normalize = "team standup at 9:30am"
+
+[[rule]]
+keywords = ["groceries", "costco"]
+intent = "todolist"
+"#;
+
+    #[tokio::test]
+    async fn first_matching_rule_wins_before_the_fallback() {
+        let router = RulesRouter::from_toml(RULES, Arc::new(HeuristicRouter)).unwrap();
+        assert_eq!(router.len(), 2);
+
+        let result = router.route("Standup tomorrow").await;
+        assert_eq!(result.intent, Intent::Notification);
+        assert_eq!(result.normalized_text, "team standup at 9:30am tomorrow");
+        assert_eq!(result.confidence, 1.0);
+
+        // The heuristic router would have read "on" as a time.
+        let result = router.route("eggs and flour on the Costco run").await;
+        assert_eq!(result.intent, Intent::Todolist);
+        assert_eq!(result.normalized_text, "eggs and flour on the Costco run");
+
+        assert_eq!(router.route("just a thought").await.intent, Intent::Unknown);
+        assert_eq!(router.route("standups are long").await.intent, Intent::Unknown);
+    }
+
+    #[test]
+    fn bad_rules_are_reported_by_position() {
+        let fallback: Arc<dyn IntentRouter> = Arc::new(HeuristicRouter);
+        let err = RulesRouter::from_toml("[[rule]]\npattern = \"x\"\nintent = \"remind\"", fallback.clone())
+            .err()
+            .unwrap();
+        assert!(err.starts_with("Rule 1: unknown intent 'remind'"), "{}", err);
+        let err = RulesRouter::from_toml(
+            "[[rule]]\npattern = \"x\"\nintent = \"todolist\"\n[[rule]]\npattern = \"(\"\nintent = \"todolist\"",
+            fallback.clone(),
+        )
+        .err()
+        .unwrap();
+        assert!(err.starts_with("Rule 2: invalid pattern"), "{}", err);
+        let err = RulesRouter::from_toml("[[rule]]\nintent = \"todolist\"", fallback).err().unwrap();
+        assert_eq!(err, "Rule 1: give either a pattern or keywords");
+    }
+}
@@ -0,0 +1,151 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use memory_db::DB;
+use tokio::sync::Notify;
+
+use crate::models::notification::{AckState, Notification};
+
+// When the notification loop next has something to do for `notification`:
+// the end of its acknowledgement window, its next ping (paused ones too, as
+// missed pings are dropped then) or the retry of one that failed, the event a
+// checklist waits on, or now when it is only left to expire.
+pub fn next_fire_time(notification: &Notification, now: DateTime<Utc>) -> DateTime<Utc> {
+    if let Some(ack) = &notification.ack {
+        if let AckState::Awaiting { since } = ack.state {
+            return since + ack.window();
+        }
+    }
+    if let Some(first) = notification.notification_times.first() {
+        return match &notification.delivery_retry {
+            Some(retry) => retry.next_attempt.max(*first),
+            None => *first,
+        };
+    }
+    match notification.event_time {
+        Some(event_time) if !notification.checklist.is_empty() && event_time > now => event_time,
+        _ => now,
+    }
+}
+
+// (next_fire_time, notification_id) for every reminder, soonest on top, so
+// the notification loop can sleep until exactly the next deadline instead of
+// polling. Rebuilt from the store after every tick and whenever it is woken
+// about a change.
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    heap: BinaryHeap<Reverse<(DateTime<Utc>, String)>>,
+}
+
+impl Scheduler {
+    pub fn build(db: &DB<Notification>, now: DateTime<Utc>) -> Self {
+        let heap = db
+            .values()
+            .map(|n| Reverse((next_fire_time(n, now), n.id.clone())))
+            .collect();
+        Self { heap }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn next_deadline(&self) -> Option<DateTime<Utc>> {
+        self.heap.peek().map(|Reverse((at, _))| *at)
+    }
+
+    // Takes the reminders due by `now`, soonest first.
+    pub fn pop_due(&mut self, now: DateTime<Utc>) -> Vec<String> {
+        let mut due = Vec::new();
+        while self.heap.peek().is_some_and(|Reverse((at, _))| *at <= now) {
+            if let Some(Reverse((_, id))) = self.heap.pop() {
+                due.push(id);
+            }
+        }
+        due
+    }
+}
+
+// Handed to whatever changes reminders so the notification loop re-reads the
+// store instead of sleeping through a new, earlier deadline. A wake-up sent
+// while the loop is busy is kept for its next sleep.
+#[derive(Debug, Clone, Default)]
+pub struct SchedulerWake(Arc<Notify>);
+
+impl SchedulerWake {
+    pub fn wake(&self) {
+        self.0.notify_one();
+    }
+
+    pub async fn woken(&self) {
+        self.0.notified().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use std::collections::HashMap;
+
+    use crate::models::notification::{AckRequirement, ChecklistItem};
+
+    fn reminder(id: &str, times: Vec<DateTime<Utc>>) -> Notification {
+        Notification {
+            id: id.to_string(),
+            content: id.to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: times,
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        }
+    }
+
+    #[test]
+    fn deadlines_come_out_soonest_first() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 12, 0, 0).unwrap();
+        let mut awaiting = reminder("ack", Vec::new());
+        awaiting.ack = Some(AckRequirement {
+            backup: None,
+            window_minutes: Some(30),
+            state: AckState::Awaiting { since: now },
+        });
+        let mut checklist = reminder("trip", Vec::new());
+        checklist.event_time = Some(now + Duration::hours(3));
+        checklist.checklist = vec![ChecklistItem {
+            text: "passport".to_string(),
+            done: false,
+        }];
+        let db: DB<Notification> = HashMap::from([
+            ("later".to_string(), reminder("later", vec![now + Duration::days(2)])),
+            ("soon".to_string(), reminder("soon", vec![now + Duration::minutes(5), now + Duration::days(1)])),
+            ("ack".to_string(), awaiting),
+            ("trip".to_string(), checklist),
+            ("done".to_string(), reminder("done", Vec::new())),
+        ]);
+
+        let mut scheduler = Scheduler::build(&db, now);
+        assert_eq!(scheduler.len(), 5);
+        // Nothing left to send: due straight away so the loop expires it.
+        assert_eq!(scheduler.next_deadline(), Some(now));
+        assert_eq!(scheduler.pop_due(now), vec!["done"]);
+        assert_eq!(scheduler.next_deadline(), Some(now + Duration::minutes(5)));
+        assert_eq!(scheduler.pop_due(now + Duration::hours(1)), vec!["soon", "ack"]);
+        assert_eq!(scheduler.pop_due(now + Duration::hours(3)), vec!["trip"]);
+        assert_eq!(scheduler.next_deadline(), Some(now + Duration::days(2)));
+    }
+}
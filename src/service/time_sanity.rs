@@ -0,0 +1,78 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{self, Locale, MessageKey};
+
+// Furthest ahead a reminder can be read as without asking first.
+pub const MAX_YEARS_AHEAD: i64 = 5;
+
+// How far from "exactly a day from now" still counts as the extraction
+// prompt's fallback for a missing time, allowing for the model's clock.
+const FALLBACK_TOLERANCE_MINUTES: i64 = 5;
+
+// Below this the model's own confidence in a time it read is worth a check.
+pub const MIN_CONFIDENCE: f32 = 0.5;
+
+// Something off about a time the model read from a request, worth checking
+// with the user before it is scheduled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeIssue {
+    InPast,
+    TooFarAhead,
+    // Exactly 24 hours out: what the prompt answers when it found no time.
+    DefaultFallback,
+    // The model said it wasn't sure of the time.
+    Unsure,
+}
+
+impl TimeIssue {
+    pub fn describe(&self, locale: Locale) -> String {
+        match self {
+            TimeIssue::InPast => i18n::text(locale, MessageKey::TimeInPast).to_string(),
+            TimeIssue::TooFarAhead => i18n::render(
+                locale,
+                MessageKey::TimeTooFarAhead,
+                &[("years", &MAX_YEARS_AHEAD.to_string())],
+            ),
+            TimeIssue::DefaultFallback => i18n::text(locale, MessageKey::TimeDefaultFallback).to_string(),
+            TimeIssue::Unsure => i18n::text(locale, MessageKey::TimeUnsure).to_string(),
+        }
+    }
+}
+
+pub fn check_time(time: DateTime<Utc>, now: DateTime<Utc>) -> Option<TimeIssue> {
+    if time < now {
+        Some(TimeIssue::InPast)
+    } else if time > now + Duration::days(365 * MAX_YEARS_AHEAD) {
+        Some(TimeIssue::TooFarAhead)
+    } else if (time - (now + Duration::hours(24))).abs() <= Duration::minutes(FALLBACK_TOLERANCE_MINUTES) {
+        Some(TimeIssue::DefaultFallback)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn odd_times_are_flagged() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(check_time(now - Duration::days(2), now), Some(TimeIssue::InPast));
+        assert_eq!(check_time(now + Duration::days(365 * 10), now), Some(TimeIssue::TooFarAhead));
+        assert_eq!(
+            check_time(now + Duration::hours(24) + Duration::seconds(40), now),
+            Some(TimeIssue::DefaultFallback)
+        );
+        assert_eq!(check_time(now + Duration::hours(27), now), None);
+        assert_eq!(check_time(now + Duration::days(300), now), None);
+    }
+
+    #[test]
+    fn issues_are_described_in_the_users_language() {
+        assert_eq!(TimeIssue::TooFarAhead.describe(Locale::En), "That's more than 5 years away");
+        assert_eq!(TimeIssue::InPast.describe(Locale::De), "Dieser Zeitpunkt ist schon vorbei");
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use memory_db::DB;
+use tokio::sync::Mutex;
+
+use crate::models::notification::Notification;
+
+// How far ahead the projection looks.
+pub const UPCOMING_WINDOW_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpcomingPing {
+    pub notification_id: String,
+    pub content: String,
+    pub at: DateTime<Utc>,
+    pub event_time: Option<DateTime<Utc>>,
+}
+
+// Every ping due in the next 24 hours, indexed by user, so features that only
+// care about the near future (the morning briefing, /admin stats) don't each
+// scan the whole store. Rebuilt by the notification loop each time it wakes
+// and by the engine after it changes a reminder; anything else shows up on
+// the loop's next wake-up.
+#[derive(Debug, Clone, Default)]
+pub struct UpcomingWindow {
+    built_at: Option<DateTime<Utc>>,
+    by_user: HashMap<String, Vec<UpcomingPing>>,
+}
+
+pub type SharedUpcoming = Arc<Mutex<UpcomingWindow>>;
+
+impl UpcomingWindow {
+    // Paused reminders are left out since they won't fire. One whose pings
+    // are done but whose event is still in the window (a checklist, or one
+    // waiting on an acknowledgement) is listed at the event itself.
+    pub fn build(db: &DB<Notification>, now: DateTime<Utc>) -> Self {
+        let until = now + Duration::hours(UPCOMING_WINDOW_HOURS);
+        let within = |at: &DateTime<Utc>| *at >= now && *at < until;
+        let mut window = UpcomingWindow {
+            built_at: Some(now),
+            ..Default::default()
+        };
+        for notification in db.values().filter(|n| !n.paused) {
+            let mut times: Vec<DateTime<Utc>> =
+                notification.notification_times.iter().copied().filter(within).collect();
+            if times.is_empty() {
+                times.extend(notification.event_time.filter(within));
+            }
+            for at in times {
+                let ping = UpcomingPing {
+                    notification_id: notification.id.clone(),
+                    content: notification.content.clone(),
+                    at,
+                    event_time: notification.event_time,
+                };
+                for user in &notification.notify {
+                    window.by_user.entry(user.clone()).or_default().push(ping.clone());
+                }
+            }
+        }
+        for pings in window.by_user.values_mut() {
+            pings.sort_by(|a, b| (a.at, &a.notification_id).cmp(&(b.at, &b.notification_id)));
+        }
+        window
+    }
+
+    pub fn built_at(&self) -> Option<DateTime<Utc>> {
+        self.built_at
+    }
+
+    // Soonest first.
+    pub fn for_user(&self, user_id: &str) -> &[UpcomingPing] {
+        self.by_user.get(user_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    // Distinct reminders firing in the window, across everyone.
+    pub fn reminder_count(&self) -> usize {
+        let mut ids: Vec<&str> = self
+            .by_user
+            .values()
+            .flatten()
+            .map(|ping| ping.notification_id.as_str())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids.len()
+    }
+}
+
+pub async fn refresh(upcoming: &SharedUpcoming, db: &DB<Notification>, now: DateTime<Utc>) {
+    *upcoming.lock().await = UpcomingWindow::build(db, now);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn reminder(id: &str, channel: &str, times: Vec<DateTime<Utc>>, paused: bool) -> Notification {
+        Notification {
+            id: id.to_string(),
+            content: id.to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: times,
+            channel: channel.to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        }
+    }
+
+    #[test]
+    fn window_keeps_the_next_day_of_unpaused_pings() {
+        let now = Utc.with_ymd_and_hms(2026, 2, 10, 8, 0, 0).unwrap();
+        let mut db: DB<Notification> = HashMap::new();
+        db.insert(
+            "soon".to_string(),
+            reminder("soon", "1", vec![now + Duration::hours(3), now + Duration::days(2)], false),
+        );
+        db.insert("sooner".to_string(), reminder("sooner", "2", vec![now + Duration::hours(1)], false));
+        db.insert("paused".to_string(), reminder("paused", "1", vec![now + Duration::hours(2)], true));
+        db.insert("past".to_string(), reminder("past", "1", vec![now - Duration::hours(1)], false));
+        let mut checklist = reminder("checklist", "1", Vec::new(), false);
+        checklist.event_time = Some(now + Duration::hours(5));
+        db.insert("checklist".to_string(), checklist);
+
+        let window = UpcomingWindow::build(&db, now);
+        let ids: Vec<&str> = window
+            .for_user("@u")
+            .iter()
+            .map(|ping| ping.notification_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["sooner", "soon", "checklist"]);
+        assert!(window.for_user("@other").is_empty());
+        assert_eq!(window.reminder_count(), 3);
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use crate::config::SharedConfig;
+use crate::handlers::action::ActionStore;
+use crate::service::approval_prompt::ApprovalPromptService;
+
+pub async fn run_approval_sweep_loop(
+    store: Arc<Mutex<ActionStore>>,
+    approval: Arc<dyn ApprovalPromptService>,
+    config: SharedConfig,
+) {
+    loop {
+        sleep(config.load().approval_sweep_interval()).await;
+        approval_sweep_tick(&store, approval.as_ref(), Utc::now()).await;
+    }
+}
+
+// Marks drafts nobody answered in time as expired and strips the buttons
+// from their prompts. The store lock is released before any Discord call.
+pub async fn approval_sweep_tick(
+    store: &Mutex<ActionStore>,
+    approval: &dyn ApprovalPromptService,
+    now: DateTime<Utc>,
+) -> usize {
+    let expired = store.lock().await.expire_lapsed(now);
+    for action in &expired {
+        tracing::info!(action_id = %action.id, "approval prompt expired");
+        if let Err(err) = approval.expire(action).await {
+            tracing::warn!(action_id = %action.id, error = %err, "failed to expire approval prompt");
+        }
+    }
+    expired.len()
+}
@@ -1,20 +1,307 @@
-#![allow(dead_code)]
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use memory_db::{DB, DBError, load_db, save_db};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
 
-use chrono;
+use crate::clients::caldav::CalDavClient;
+use crate::clients::google_calendar::GoogleCalendarClient;
+use crate::clients::outlook_calendar::OutlookCalendarClient;
+use crate::config::{CalendarProvider, SharedConfig};
+use crate::models::notification::{self, Notification, get_db_location};
 
-pub async fn run_calendar_loop() {
-    // TODO: Hook into real calendar client.
+// How often the calendar is checked for new or moved events.
+pub const DEFAULT_LEAD_MINUTES: i64 = 60;
+
+// Returns the directory recording which calendar events became which
+// reminders. Defaults to a relative "./data/calendar_sync" directory.
+pub fn get_sync_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/calendar_sync", base)
 }
 
+// Who a provider's synced events remind, and in which channel. Also the one
+// user whose calendar approved calendar events are added to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncTarget {
+    pub user_id: String,
+    pub channel_id: String,
+}
 
-struct CalendarEvent {
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    // None for events not yet created on the calendar.
+    pub id: Option<String>,
     pub title: String,
-    pub start_time: chrono::DateTime<chrono::Utc>,
-    pub end_time: chrono::DateTime<chrono::Utc>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
     pub description: Option<String>,
 }
 
-trait CalendarClient {
-    async fn get_events_for_day(&self, day: chrono::NaiveDate) -> Vec<CalendarEvent>;
-    async fn create_event(&self, event: CalendarEvent) -> Result<(), Box<dyn std::error::Error>>;
+#[serenity::async_trait]
+pub trait CalendarClient: Send + Sync {
+    // Events starting on `day` (UTC).
+    async fn get_events_for_day(&self, day: NaiveDate) -> Result<Vec<CalendarEvent>, String>;
+    async fn create_event(&self, event: CalendarEvent) -> Result<(), String>;
+
+    // Events overlapping [start, end), wherever they started. Clients that
+    // can only list by day look through each day the range touches and the
+    // one before it, which catches events running over midnight.
+    async fn get_events_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let mut events = Vec::new();
+        let mut day = start.date_naive() - ChronoDuration::days(1);
+        while day <= end.date_naive() {
+            events.extend(self.get_events_for_day(day).await?);
+            day += ChronoDuration::days(1);
+        }
+        Ok(events
+            .into_iter()
+            .filter(|event| event.start_time < end && (event.end_time > start || event.start_time >= start))
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedEvent {
+    pub event_id: String,
+    pub notification_id: String,
+    pub start_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncMode {
+    // Each event gets a reminder with the usual lead times (see
+    // notification::reminder_times).
+    #[default]
+    Mirror,
+    // Each event gets a single ping this many minutes before it starts.
+    LeadTime { minutes: i64 },
+}
+
+// Which events are synced, and how. Filters are case-insensitive words
+// matched against the title: with `include` set only matching events are
+// synced, and anything matching `exclude` never is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncOptions {
+    pub mode: SyncMode,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl SyncOptions {
+    pub fn matches(&self, event: &CalendarEvent) -> bool {
+        let title = event.title.to_lowercase();
+        let found = |words: &[String]| words.iter().any(|word| title.contains(word.as_str()));
+        (self.include.is_empty() || found(&self.include)) && !found(&self.exclude)
+    }
+
+    fn reminder_times(&self, start: DateTime<Utc>, now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        match self.mode {
+            SyncMode::Mirror => notification::reminder_times(&start),
+            // Still reminded, straight away, when the lead time has passed.
+            SyncMode::LeadTime { minutes } => vec![(start - ChronoDuration::minutes(minutes)).max(now)],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncOutcome {
+    pub created: usize,
+    pub moved: usize,
+    pub removed: usize,
+}
+
+// A client for the configured provider.
+pub fn connect(provider: &CalendarProvider) -> Arc<dyn CalendarClient> {
+    match provider {
+        CalendarProvider::Google(settings) => Arc::new(GoogleCalendarClient::new(settings.clone())),
+        CalendarProvider::CalDav(settings) => Arc::new(CalDavClient::new(settings.clone())),
+        CalendarProvider::Outlook(settings) => Arc::new(OutlookCalendarClient::new(settings.clone())),
+    }
+}
+
+pub async fn run_calendar_loop(db: Arc<Mutex<DB<Notification>>>, config: SharedConfig) {
+    let Some(provider) = config.load().calendar_provider() else {
+        tracing::info!("calendar sync not configured");
+        return;
+    };
+    let authorized = match &provider {
+        CalendarProvider::Google(settings) => GoogleCalendarClient::new(settings.clone()).is_authorized().await,
+        CalendarProvider::Outlook(settings) => OutlookCalendarClient::new(settings.clone()).is_authorized().await,
+        CalendarProvider::CalDav(_) => true,
+    };
+    if !authorized {
+        tracing::warn!("the calendar isn't authorized yet; run `reminderBot calendar auth`");
+    }
+    let client = connect(&provider);
+    let mut synced: DB<SyncedEvent> = load_db(&get_sync_location()).unwrap_or_else(|_| HashMap::new());
+    loop {
+        let options = config.load().calendar_sync_options();
+        let outcome = calendar_sync_tick_with(
+            client.as_ref(),
+            &db,
+            &mut synced,
+            provider.user_id(),
+            provider.channel_id(),
+            &options,
+            Utc::now(),
+        )
+        .await;
+        match outcome {
+            Ok(outcome) if outcome != SyncOutcome::default() => {
+                tracing::info!(
+                    created = outcome.created,
+                    moved = outcome.moved,
+                    removed = outcome.removed,
+                    "calendar synced"
+                );
+                if let Err(err) = save_synced(&db, &synced).await {
+                    tracing::error!(error = %err, "failed to save calendar sync state");
+                }
+            }
+            Ok(_) => {}
+            Err(err) => tracing::warn!(error = %err, "calendar sync failed"),
+        }
+        sleep(config.load().calendar_sync_interval()).await;
+    }
+}
+
+async fn save_synced(db: &Mutex<DB<Notification>>, synced: &DB<SyncedEvent>) -> Result<(), DBError> {
+    save_db(&get_sync_location(), synced)?;
+    save_db(&get_db_location(), &*db.lock().await)
+}
+
+// Mirrors today's and tomorrow's timed events into reminders for `user_id`:
+// new events get a reminder, moved ones have theirs replaced, and ones that
+// vanished from the calendar lose theirs. Events that already started are
+// left alone. The calendar is read before the reminders are locked, and
+// nothing is saved here; the caller persists both stores.
+pub async fn calendar_sync_tick<C: CalendarClient + ?Sized>(
+    client: &C,
+    db: &Mutex<DB<Notification>>,
+    synced: &mut DB<SyncedEvent>,
+    user_id: &str,
+    channel_id: &str,
+    now: DateTime<Utc>,
+) -> Result<SyncOutcome, String> {
+    calendar_sync_tick_with(client, db, synced, user_id, channel_id, &SyncOptions::default(), now).await
+}
+
+// calendar_sync_tick with filters and a choice of reminder times. Events
+// that stop matching the filters are treated like deleted ones, and an
+// event the user already has a reminder for (same text and time) is not
+// given a second one.
+pub async fn calendar_sync_tick_with<C: CalendarClient + ?Sized>(
+    client: &C,
+    db: &Mutex<DB<Notification>>,
+    synced: &mut DB<SyncedEvent>,
+    user_id: &str,
+    channel_id: &str,
+    options: &SyncOptions,
+    now: DateTime<Utc>,
+) -> Result<SyncOutcome, String> {
+    let today = now.date_naive();
+    let tomorrow = today + ChronoDuration::days(1);
+    let mut events = client.get_events_for_day(today).await?;
+    events.extend(client.get_events_for_day(tomorrow).await?);
+
+    let mut db = db.lock().await;
+    let mut outcome = SyncOutcome::default();
+    let mut seen = HashSet::new();
+    for event in events
+        .into_iter()
+        .filter(|event| event.start_time > now && options.matches(event))
+    {
+        let Some(event_id) = event.id.clone() else {
+            continue;
+        };
+        seen.insert(event_id.clone());
+        match synced.get(&event_id) {
+            Some(existing) if existing.start_time == event.start_time => continue,
+            Some(existing) => {
+                db.remove(&existing.notification_id);
+                outcome.moved += 1;
+            }
+            None if already_reminded(&db, &event, user_id) => continue,
+            None => outcome.created += 1,
+        }
+        let times = options.reminder_times(event.start_time, now);
+        let notification_id = add_reminder(&mut db, &event, user_id, channel_id, times);
+        synced.insert(
+            event_id.clone(),
+            SyncedEvent {
+                event_id,
+                notification_id,
+                start_time: event.start_time,
+            },
+        );
+    }
+
+    let window_end = (tomorrow + ChronoDuration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    let vanished: Vec<String> = synced
+        .values()
+        .filter(|entry| entry.start_time > now && entry.start_time < window_end)
+        .filter(|entry| !seen.contains(&entry.event_id))
+        .map(|entry| entry.event_id.clone())
+        .collect();
+    for event_id in vanished {
+        if let Some(entry) = synced.remove(&event_id) {
+            db.remove(&entry.notification_id);
+            outcome.removed += 1;
+        }
+    }
+    // Past events can't move any more, so their entries are only kept a
+    // couple of days.
+    synced.retain(|_, entry| entry.start_time > now - ChronoDuration::days(2));
+    Ok(outcome)
+}
+
+fn already_reminded(db: &DB<Notification>, event: &CalendarEvent, user_id: &str) -> bool {
+    db.values().any(|n| {
+        n.event_time == Some(event.start_time)
+            && n.content.eq_ignore_ascii_case(&event.title)
+            && n.notify.iter().any(|u| u == user_id)
+    })
+}
+
+// Same shape as notification::create_notification, minus the save so the
+// tick can be run against a scratch store.
+fn add_reminder(
+    db: &mut DB<Notification>,
+    event: &CalendarEvent,
+    user_id: &str,
+    channel_id: &str,
+    notification_times: Vec<DateTime<Utc>>,
+) -> String {
+    let id = uuid::Uuid::new_v4().to_string();
+    db.insert(
+        id.clone(),
+        Notification {
+            id: id.clone(),
+            content: event.title.clone(),
+            notify: vec![user_id.to_string()],
+            notification_times,
+            channel: channel_id.to_string(),
+            completed_at: None,
+            event_time: Some(event.start_time),
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+    id
 }
@@ -0,0 +1,22 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use memory_db::{DB, save_db};
+use tokio::sync::Mutex;
+
+use crate::config::SharedConfig;
+use crate::models::todo::{self, TodoItem};
+
+// The "cleanup" cron task: forgets todos finished longer ago than
+// TODO_RETENTION_DAYS.
+pub async fn run_cleanup(todo_db: Arc<Mutex<DB<TodoItem>>>, config: SharedConfig) -> Result<(), String> {
+    let cutoff = Utc::now() - config.load().todo_retention();
+    let mut db = todo_db.lock().await;
+    let removed = todo::prune_finished(&mut db, cutoff);
+    if removed == 0 {
+        return Ok(());
+    }
+    save_db(&todo::get_db_location(), &*db).map_err(|err| format!("failed to save pruned todos: {}", err))?;
+    tracing::info!(removed, "pruned finished todos");
+    Ok(())
+}
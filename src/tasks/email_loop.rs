@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use mailparse::{MailHeaderMap, ParsedMail};
+use std::collections::HashMap;
+use tokio::time::sleep;
+
+use crate::clients::imap_inbox::ImapInbox;
+use crate::config::SharedConfig;
+use crate::events::queue::EventBus;
+use crate::handlers::action::ActionEvent;
+
+#[serenity::async_trait]
+pub trait Mailbox: Send + Sync {
+    // Raw RFC 822 messages not marked seen yet, by uid. Fetching doesn't
+    // mark them.
+    async fn fetch_unseen(&self) -> Result<Vec<(u32, Vec<u8>)>, String>;
+    async fn mark_seen(&self, uids: &[u32]) -> Result<(), String>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboundEmail {
+    // Lowercased sender address.
+    pub from: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub fn parse_email(raw: &[u8]) -> Result<InboundEmail, String> {
+    let mail = mailparse::parse_mail(raw).map_err(|err| err.to_string())?;
+    let from = mail
+        .headers
+        .get_first_value("From")
+        .and_then(|from| mailparse::addrparse(&from).ok())
+        .and_then(|addresses| addresses.extract_single_info())
+        .map(|info| info.addr.trim().to_lowercase())
+        .ok_or("no sender address")?;
+    Ok(InboundEmail {
+        from,
+        subject: mail.headers.get_first_value("Subject").unwrap_or_default(),
+        body: plain_text(&mail).unwrap_or_default(),
+    })
+}
+
+fn plain_text(part: &ParsedMail) -> Option<String> {
+    if part.subparts.is_empty() {
+        return (part.ctype.mimetype == "text/plain")
+            .then(|| part.get_body().ok())
+            .flatten();
+    }
+    part.subparts.iter().find_map(plain_text)
+}
+
+// What the email asks for, as the text a /notify would get: the subject,
+// then the body without quoted replies or the signature.
+pub fn request_text(email: &InboundEmail) -> String {
+    let body: Vec<&str> = email
+        .body
+        .lines()
+        .take_while(|line| line.trim_end() != "--")
+        .filter(|line| !line.trim_start().starts_with('>'))
+        .collect();
+    [email.subject.trim(), body.join("\n").trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Turns each new email from a known sender into a reminder request, answered
+// in `channel_id`. `senders` maps a lowercased address to its user id; mail
+// from anyone else is dropped. Every email is marked seen once dealt with,
+// except one whose request couldn't be queued, which is tried again next
+// time. Returns how many requests were raised.
+pub async fn email_tick<M: Mailbox + ?Sized>(
+    mailbox: &M,
+    senders: &HashMap<String, String>,
+    channel_id: &str,
+    event_bus: &EventBus,
+    now: DateTime<Utc>,
+) -> Result<usize, String> {
+    let mut raised = 0;
+    let mut seen = Vec::new();
+    for (uid, raw) in mailbox.fetch_unseen().await? {
+        let email = match parse_email(&raw) {
+            Ok(email) => email,
+            Err(err) => {
+                tracing::warn!(error = %err, "skipping unreadable email");
+                seen.push(uid);
+                continue;
+            }
+        };
+        let Some(user_id) = senders.get(&email.from) else {
+            tracing::warn!(from = %email.from, "ignoring email from an unknown sender");
+            seen.push(uid);
+            continue;
+        };
+        let text = request_text(&email);
+        if text.is_empty() {
+            seen.push(uid);
+            continue;
+        }
+        let event = ActionEvent::NotifyRequested {
+            text,
+            user_id: user_id.clone(),
+            channel_id: channel_id.to_string(),
+            received_at: Some(now),
+        };
+        match event_bus.emit(event).await {
+            Ok(()) => {
+                raised += 1;
+                seen.push(uid);
+            }
+            Err(err) => tracing::error!(error = %err, from = %email.from, "failed to queue emailed reminder"),
+        }
+    }
+    mailbox.mark_seen(&seen).await?;
+    Ok(raised)
+}
+
+pub async fn run_email_loop(event_bus: EventBus, config: SharedConfig) {
+    let (Some(imap), Some(channel_id)) = (config.load().imap_inbox(), config.load().inbound_email_channel()) else {
+        tracing::info!("inbound email not configured");
+        return;
+    };
+    let mailbox = ImapInbox::new(imap);
+    loop {
+        let senders = config.load().inbound_email_senders();
+        match email_tick(&mailbox, &senders, &channel_id, &event_bus, Utc::now()).await {
+            Ok(0) => {}
+            Ok(raised) => tracing::info!(raised, "reminder requests received by email"),
+            Err(err) => tracing::warn!(error = %err, "checking the inbox failed"),
+        }
+        sleep(config.load().email_poll_interval()).await;
+    }
+}
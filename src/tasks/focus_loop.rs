@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use memory_db::{DB, save_db};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use crate::config::SharedConfig;
+use crate::i18n::{self, Locale, MessageKey};
+use crate::models::settings::{UserSettings, get_user_db_location, return_held, take_finished_focus};
+use crate::tasks::todo_loop::{DiscordDmSender, DmSender};
+
+pub async fn run_focus_loop(
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+    discord_token: Arc<String>,
+    config: SharedConfig,
+) {
+    let sender = DiscordDmSender::new(discord_token.to_string());
+    loop {
+        sleep(config.load().focus_digest_interval()).await;
+        let mut settings = user_settings.lock().await;
+        if let Err(err) = focus_digest_tick(&mut settings, &sender, Utc::now()).await {
+            tracing::warn!(error = %err, "failed to save ended focus sessions");
+        }
+    }
+}
+
+// Sends one catch-up DM per user whose focus session has ended. A digest
+// that fails to send is put back for the next tick, and settings are saved
+// only once the sends are settled.
+pub async fn focus_digest_tick<S: DmSender + ?Sized>(
+    settings: &mut DB<UserSettings>,
+    sender: &S,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let finished = take_finished_focus(settings, now);
+    if finished.is_empty() {
+        return Ok(());
+    }
+
+    for (user_id, held) in finished {
+        if held.is_empty() {
+            continue;
+        }
+        let locale = Locale::resolve(settings.get(&user_id), None);
+        let mut body = format!("{}\n", i18n::text(locale, MessageKey::FocusDigestHeader));
+        for message in &held {
+            body.push_str(&format!("- {}\n", message.replace('\n', "\n  ")));
+        }
+        if let Err(err) = sender
+            .send_dm(user_id.trim_start_matches('@'), body.trim_end())
+            .await
+        {
+            tracing::warn!(
+                %user_id,
+                error = %err,
+                "failed to send focus digest, keeping it for the next tick"
+            );
+            return_held(settings, &user_id, held, now);
+        }
+    }
+    save_db(&get_user_db_location(), settings).map_err(|e| e.to_string())
+}
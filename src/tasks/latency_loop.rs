@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio::time::sleep;
+
+use crate::config::SharedConfig;
+use crate::service::latency::{LatencySlo, LatencyTracker};
+use crate::tasks::notification_loop::{DiscordSender, MessageSender};
+
+pub async fn run_latency_alert_loop(
+    latency: Arc<LatencyTracker>,
+    discord_token: Arc<String>,
+    config: SharedConfig,
+) {
+    let sender = DiscordSender::new(discord_token.to_string());
+    loop {
+        sleep(config.load().latency_check_interval()).await;
+        let config = config.load();
+        latency_alert_tick(
+            &latency,
+            &sender,
+            &config.latency_slo(),
+            config.operator_channel().as_deref(),
+            Utc::now(),
+        )
+        .await;
+    }
+}
+
+// Posts each p95 breach to the operator channel. Breaches are always logged,
+// so an unset channel still leaves a trace.
+pub async fn latency_alert_tick<S: MessageSender + ?Sized>(
+    latency: &LatencyTracker,
+    sender: &S,
+    slo: &LatencySlo,
+    operator_channel: Option<&str>,
+    now: DateTime<Utc>,
+) {
+    for breach in latency.breaches(slo, now) {
+        tracing::warn!(
+            kind = breach.kind.label(),
+            p95_ms = breach.p95.num_milliseconds(),
+            threshold_ms = breach.threshold.num_milliseconds(),
+            "latency SLO breached"
+        );
+        if let Some(channel) = operator_channel {
+            if let Err(err) = sender.send_message(channel, &breach.message()).await {
+                tracing::warn!(error = %err, "failed to post latency alert");
+            }
+        }
+    }
+}
@@ -1,4 +1,10 @@
+pub mod approval_sweep_loop;
 pub mod calendar_loop;
+pub mod cleanup_job;
+pub mod email_loop;
+pub mod focus_loop;
+pub mod latency_loop;
 pub mod notification_loop;
+pub mod session_loop;
 pub mod todo_loop;
 pub mod task_runner;
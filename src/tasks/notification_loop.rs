@@ -1,29 +1,117 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{FuturesUnordered, StreamExt};
 use tokio::time::sleep;
-use std::time::Duration;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration as StdDuration;
 
 use memory_db::{DB, save_db};
-use crate::models::notification::{Notification, get_db_location};
-use serenity::http::Http;
+use crate::config::SharedConfig;
+use crate::i18n::{self, Locale, MessageKey};
+use crate::models::notification::{
+    AckState, DeliveryRetry, MAX_DELIVERY_ATTEMPTS, Notification, advance_recurrence, checklist_complete,
+    get_db_location, skip_missed_while_paused,
+};
+use crate::models::settings::{
+    CategoryStyle, GuildSettings, UserSettings, get_user_db_location, hold_for_focus, in_focus, quiet_hours_end,
+};
+use crate::service::delivery_channel::{
+    DiscordChannel, DiscordDmChannel, EmailChannel, NotificationChannel, NtfyChannel, OutgoingMessage, PushoverChannel,
+    WebhookChannel, WebhookSink,
+};
+use crate::service::delivery_log::{DeliveryKind, DeliveryLog, DeliveryRecord, get_delivery_log_location};
+use crate::service::latency::{LatencyKind, LatencyTracker};
+use crate::service::scheduler::{Scheduler, SchedulerWake};
+use crate::service::upcoming::{self, SharedUpcoming};
+use crate::service::{categories, focus};
+use serenity::http::{Http, HttpError};
+use serenity::model::channel::Channel;
 use serenity::model::id::ChannelId;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::time::Instant;
 use crate::service::notification_message_service::NotificationMessageService;
-use crate::service::openai_service::{OpenAIClient, OpenAIService};
+use crate::service::openai_service::OpenAIClient;
+use crate::service::notification_service::{
+    checklist_buttons, delivered_buttons, render_checklist, render_checklist_summary,
+};
 use serenity::async_trait;
+use serenity::builder::{CreateActionRow, CreateEmbed, CreateMessage};
 
 #[async_trait]
 pub trait MessageSender: Send + Sync {
     async fn send_message(&self, channel_id: &str, content: &str) -> Result<(), String>;
+
+    // Senders that cannot render components fall back to plain text.
+    async fn send_with_components(
+        &self,
+        channel_id: &str,
+        content: &str,
+        _components: Vec<CreateActionRow>,
+    ) -> Result<(), String> {
+        self.send_message(channel_id, content).await
+    }
+
+    // Senders that cannot render embeds ignore the color and GIF.
+    async fn send_styled(
+        &self,
+        channel_id: &str,
+        content: &str,
+        _style: &CategoryStyle,
+        components: Vec<CreateActionRow>,
+    ) -> Result<(), String> {
+        self.send_with_components(channel_id, content, components).await
+    }
+
+    // The guild a channel belongs to, so its voice setting applies. None for
+    // DMs and for senders that can't tell.
+    async fn guild_for_channel(&self, _channel_id: &str) -> Option<String> {
+        None
+    }
 }
 
+// One Http client for every send, so serenity's own per-route buckets see
+// all of them. A channel never moves between guilds, so its guild is looked
+// up once and remembered.
 pub struct DiscordSender {
-    token: String,
+    http: Arc<Http>,
+    guilds: std::sync::Mutex<HashMap<String, Option<String>>>,
 }
 
 impl DiscordSender {
     pub fn new(token: String) -> Self {
-        Self { token }
+        Self {
+            http: Arc::new(Http::new(&token)),
+            guilds: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn http(&self) -> Arc<Http> {
+        self.http.clone()
+    }
+}
+
+// Prefix of the error a sender returns when Discord answered 429, so the
+// dispatcher knows the send is worth retrying.
+pub const RATE_LIMITED: &str = "rate limited";
+
+pub fn is_rate_limited(err: &str) -> bool {
+    err.starts_with(RATE_LIMITED)
+}
+
+// Failures no retry will fix: the channel is gone, the id is bad, or the
+// target's kind of delivery isn't set up.
+pub fn is_permanent_failure(err: &str) -> bool {
+    err.contains("Unknown Channel")
+        || err.starts_with("Failed to parse channel id")
+        || (err.starts_with("no ") && err.ends_with(" delivery configured"))
+}
+
+fn send_error(err: serenity::Error) -> String {
+    match &err {
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response)) if response.status_code.as_u16() == 429 => {
+            format!("{}: {:?}", RATE_LIMITED, err)
+        }
+        _ => format!("Error sending message: {:?}", err),
     }
 }
 
@@ -34,59 +122,937 @@ impl MessageSender for DiscordSender {
             .parse::<u64>()
             .map(ChannelId::new)
             .map_err(|_| "Failed to parse channel id".to_string())?;
-        let http: Http = Http::new(&self.token);
+        channel.say(&self.http, content).await.map_err(send_error)?;
+        Ok(())
+    }
+
+    async fn send_with_components(
+        &self,
+        channel_id: &str,
+        content: &str,
+        components: Vec<CreateActionRow>,
+    ) -> Result<(), String> {
+        let channel = channel_id
+            .parse::<u64>()
+            .map(ChannelId::new)
+            .map_err(|_| "Failed to parse channel id".to_string())?;
         channel
-            .say(&http, content)
+            .send_message(
+                &self.http,
+                CreateMessage::new().content(content).components(components),
+            )
             .await
-            .map_err(|e| format!("Error sending message: {:?}", e))?;
+            .map_err(send_error)?;
         Ok(())
     }
+
+    async fn send_styled(
+        &self,
+        channel_id: &str,
+        content: &str,
+        style: &CategoryStyle,
+        components: Vec<CreateActionRow>,
+    ) -> Result<(), String> {
+        if style.color.is_none() && style.gif_url.is_none() {
+            return self.send_with_components(channel_id, content, components).await;
+        }
+        let channel = channel_id
+            .parse::<u64>()
+            .map(ChannelId::new)
+            .map_err(|_| "Failed to parse channel id".to_string())?;
+        let mut embed = CreateEmbed::new().description(content);
+        if let Some(color) = style.color {
+            embed = embed.color(color);
+        }
+        if let Some(gif_url) = &style.gif_url {
+            embed = embed.image(gif_url);
+        }
+        channel
+            .send_message(
+                &self.http,
+                CreateMessage::new().embed(embed).components(components),
+            )
+            .await
+            .map_err(send_error)?;
+        Ok(())
+    }
+
+    async fn guild_for_channel(&self, channel_id: &str) -> Option<String> {
+        if let Some(guild) = self.guilds.lock().unwrap().get(channel_id) {
+            return guild.clone();
+        }
+        let channel = ChannelId::new(channel_id.parse::<u64>().ok()?);
+        // Only answers Discord actually gave are cached; a failed lookup is
+        // tried again next time.
+        let guild = match channel.to_channel(&self.http).await {
+            Ok(Channel::Guild(channel)) => Some(channel.guild_id.to_string()),
+            Ok(_) => None,
+            Err(err) => {
+                tracing::warn!(%channel_id, error = %err, "failed to look up channel's guild");
+                return None;
+            }
+        };
+        self.guilds.lock().unwrap().insert(channel_id.to_string(), guild.clone());
+        guild
+    }
+}
+
+pub const DEFAULT_DELIVERIES_PER_SECOND: u32 = 5;
+const DEFAULT_DELIVERY_CONCURRENCY: usize = 4;
+const DEFAULT_RATE_LIMIT_RETRIES: u32 = 3;
+const DEFAULT_RATE_LIMIT_BACKOFF: StdDuration = StdDuration::from_secs(1);
+
+// Sits in front of another sender so a burst of due reminders (say, after
+// downtime) goes out at a steady pace: at most `concurrency` sends in
+// flight, starts spaced 1/rate apart with up to half a gap of jitter, and a
+// send Discord answered with 429 is retried after a doubling backoff.
+pub struct RateLimitedSender<S> {
+    inner: S,
+    permits: Semaphore,
+    gap: StdDuration,
+    next_start: Mutex<Instant>,
+    max_retries: u32,
+    backoff: StdDuration,
+}
+
+impl<S: MessageSender> RateLimitedSender<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            permits: Semaphore::new(DEFAULT_DELIVERY_CONCURRENCY),
+            gap: StdDuration::from_secs(1) / DEFAULT_DELIVERIES_PER_SECOND,
+            next_start: Mutex::new(Instant::now()),
+            max_retries: DEFAULT_RATE_LIMIT_RETRIES,
+            backoff: DEFAULT_RATE_LIMIT_BACKOFF,
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn with_rate(mut self, per_second: u32) -> Self {
+        self.gap = StdDuration::from_secs(1) / per_second.max(1);
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.permits = Semaphore::new(concurrency.max(1));
+        self
+    }
+
+    pub fn with_retries(mut self, max_retries: u32, backoff: StdDuration) -> Self {
+        self.max_retries = max_retries;
+        self.backoff = backoff;
+        self
+    }
+
+    fn jitter(&self, up_to: StdDuration) -> StdDuration {
+        let millis = up_to.as_millis() as u64;
+        StdDuration::from_millis(if millis == 0 { 0 } else { rand::random_range(0..millis) })
+    }
+
+    async fn dispatch<F, Fut>(&self, send: F) -> Result<(), String>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<(), String>>,
+    {
+        let _permit = self.permits.acquire().await.map_err(|e| e.to_string())?;
+        let mut backoff = self.backoff;
+        let mut retries = 0;
+        loop {
+            let start = {
+                let mut next_start = self.next_start.lock().await;
+                let start = (*next_start).max(Instant::now());
+                *next_start = start + self.gap;
+                start
+            };
+            tokio::time::sleep_until(start + self.jitter(self.gap / 2)).await;
+            match send().await {
+                Err(err) if is_rate_limited(&err) && retries < self.max_retries => {
+                    retries += 1;
+                    tracing::warn!(error = %err, retry = retries, "Discord rate limited a send, backing off");
+                    sleep(backoff + self.jitter(backoff / 2)).await;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S: MessageSender> MessageSender for RateLimitedSender<S> {
+    async fn send_message(&self, channel_id: &str, content: &str) -> Result<(), String> {
+        self.dispatch(|| self.inner.send_message(channel_id, content)).await
+    }
+
+    async fn send_with_components(
+        &self,
+        channel_id: &str,
+        content: &str,
+        components: Vec<CreateActionRow>,
+    ) -> Result<(), String> {
+        self.dispatch(|| self.inner.send_with_components(channel_id, content, components.clone()))
+            .await
+    }
+
+    async fn send_styled(
+        &self,
+        channel_id: &str,
+        content: &str,
+        style: &CategoryStyle,
+        components: Vec<CreateActionRow>,
+    ) -> Result<(), String> {
+        self.dispatch(|| self.inner.send_styled(channel_id, content, style, components.clone()))
+            .await
+    }
+
+    // A lookup, not a send; not rate limited here.
+    async fn guild_for_channel(&self, channel_id: &str) -> Option<String> {
+        self.inner.guild_for_channel(channel_id).await
+    }
+}
+
+pub const DEFAULT_LATE_AFTER_MINUTES: i64 = 10;
+
+// How a tick treats pings that are well past due, usually because the bot
+// was down when they came up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CatchUpPolicy {
+    // Pings sent this long after they were due say so.
+    pub late_after: Duration,
+    // Drop overdue pings for an event that has already happened.
+    pub skip_passed_events: bool,
+}
+
+impl Default for CatchUpPolicy {
+    fn default() -> Self {
+        Self {
+            late_after: Duration::minutes(DEFAULT_LATE_AFTER_MINUTES),
+            skip_passed_events: false,
+        }
+    }
 }
 
+// A failed send leaves its ping due; it is retried after this rather than
+// straight away.
+const RETRY_AFTER_FAILURE: StdDuration = StdDuration::from_secs(5);
+
+// Sleeps until the soonest deadline in the store (or until `wake` says the
+// store changed, or the configured interval passes) and only runs a tick
+// when something is actually due.
+#[allow(clippy::too_many_arguments)]
 pub async fn run_notification_loop(
     db: Arc<Mutex<DB<Notification>>>,
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+    guild_settings: Arc<Mutex<DB<GuildSettings>>>,
     client_secret: Arc<String>,
-    openai_api_key: Arc<String>,
+    openai: Arc<dyn OpenAIClient>,
+    config: SharedConfig,
+    upcoming: SharedUpcoming,
+    latency: Arc<LatencyTracker>,
+    wake: SchedulerWake,
 ) {
-    let sender = DiscordSender::new(client_secret.to_string());
-    let openai = OpenAIService::new(openai_api_key.to_string());
+    let discord = DiscordSender::new(client_secret.to_string());
+    let http = discord.http();
+    let sender = Arc::new(RateLimitedSender::new(discord).with_rate(config.load().deliveries_per_second()));
+    // Reminder channels go through `sender` itself; these cover the other
+    // targets a reminder can have.
+    let mut channels: Vec<Arc<dyn NotificationChannel>> = vec![
+        Arc::new(DiscordDmChannel::new(http, sender.clone())),
+        Arc::new(WebhookChannel::new()),
+    ];
+    if let Some(email) = config.load().email_delivery() {
+        channels.push(Arc::new(EmailChannel::new(email)));
+    }
+    let ntfy = NtfyChannel::new(config.load().ntfy_server());
+    channels.push(Arc::new(match config.load().ntfy_token() {
+        Some(token) => ntfy.with_token(token),
+        None => ntfy,
+    }));
+    if let Some(token) = config.load().pushover_app_token() {
+        channels.push(Arc::new(PushoverChannel::new(token)));
+    }
+    let sink_urls = config.load().webhook_sink_urls();
+    if !sink_urls.is_empty() {
+        channels.push(Arc::new(WebhookSink::new(sink_urls)));
+    }
+    let log = DeliveryLog::new(get_delivery_log_location());
+    let mut scheduler = Scheduler::build(&*db.lock().await, Utc::now());
     loop {
-        sleep(Duration::from_secs(5)).await;
-        let mut db = db.lock().await;
-        let _ = notification_tick(&mut db, &sender, &openai, Utc::now()).await;
+        let ceiling = config.load().notification_interval();
+        let wait = match scheduler.next_deadline() {
+            Some(at) => (at - Utc::now()).to_std().unwrap_or_default().min(ceiling),
+            None => ceiling,
+        };
+        tokio::select! {
+            _ = sleep(wait) => {}
+            _ = wake.woken() => {}
+        }
+        {
+            let db = db.lock().await;
+            scheduler = Scheduler::build(&db, Utc::now());
+            if scheduler.next_deadline().is_none_or(|at| at > Utc::now()) {
+                upcoming::refresh(&upcoming, &db, Utc::now()).await;
+                continue;
+            }
+        }
+        let ticked = shared_notification_tick(
+            &db,
+            &user_settings,
+            &guild_settings,
+            sender.as_ref(),
+            &channels,
+            openai.as_ref(),
+            Some(&latency),
+            Some(&log),
+            &config.load().catch_up_policy(),
+        )
+        .await;
+        {
+            let db = db.lock().await;
+            upcoming::refresh(&upcoming, &db, Utc::now()).await;
+            scheduler = Scheduler::build(&db, Utc::now());
+        }
+        if let Err(err) = ticked {
+            tracing::warn!(error = %err, "notification tick failed, retrying shortly");
+            sleep(RETRY_AFTER_FAILURE).await;
+        }
     }
 }
 
+// notification_tick_with_channels for the stores the bot shares with its
+// handlers: they are locked to plan the tick and again to record how it
+// went, but not while the pings go out, so a slow or paced send holds up
+// neither the handlers nor the other sends.
+#[allow(clippy::too_many_arguments)]
+async fn shared_notification_tick<C: OpenAIClient + ?Sized, S: MessageSender + ?Sized>(
+    db: &Mutex<DB<Notification>>,
+    user_settings: &Mutex<DB<UserSettings>>,
+    guild_settings: &Mutex<DB<GuildSettings>>,
+    sender: &S,
+    channels: &[Arc<dyn NotificationChannel>],
+    openai: &C,
+    latency: Option<&LatencyTracker>,
+    log: Option<&DeliveryLog>,
+    catch_up: &CatchUpPolicy,
+) -> Result<(), String> {
+    let now = Utc::now();
+    let (plan, guilds) = {
+        let mut db = db.lock().await;
+        let settings = user_settings.lock().await;
+        let guilds = guild_settings.lock().await.clone();
+        (plan_tick(&mut db, &settings, now, catch_up, log), guilds)
+    };
+    let context = TickContext {
+        sender,
+        channels,
+        openai,
+        guild_settings: &guilds,
+        settings: &plan.settings,
+        now,
+        started: now,
+        latency,
+        log,
+    };
+    let outcomes = dispatch_tick(&context, &plan.jobs).await;
+    let mut db = db.lock().await;
+    let mut settings = user_settings.lock().await;
+    finish_tick(&mut db, &mut settings, outcomes, now, log)
+}
+
 pub async fn notification_tick<C: OpenAIClient + ?Sized, S: MessageSender + ?Sized>(
     db: &mut DB<Notification>,
+    settings: &mut DB<UserSettings>,
+    guild_settings: &DB<GuildSettings>,
     sender: &S,
     openai: &C,
     now: DateTime<Utc>,
 ) -> Result<(), String> {
+    notification_tick_with_latency(db, settings, guild_settings, sender, openai, now, None).await
+}
+
+// Like notification_tick, also recording how late each delivered ping went
+// out. Time spent earlier in the tick counts, since a long tick is exactly
+// what delays the pings behind it.
+pub async fn notification_tick_with_latency<C: OpenAIClient + ?Sized, S: MessageSender + ?Sized>(
+    db: &mut DB<Notification>,
+    settings: &mut DB<UserSettings>,
+    guild_settings: &DB<GuildSettings>,
+    sender: &S,
+    openai: &C,
+    now: DateTime<Utc>,
+    latency: Option<&LatencyTracker>,
+) -> Result<(), String> {
+    notification_tick_logged(db, settings, guild_settings, sender, openai, now, latency, None).await
+}
+
+// Like notification_tick_with_latency, also writing each attempt, delivery,
+// hold, failure, escalation and expiry to `log` for `watch`.
+#[allow(clippy::too_many_arguments)]
+pub async fn notification_tick_logged<C: OpenAIClient + ?Sized, S: MessageSender + ?Sized>(
+    db: &mut DB<Notification>,
+    settings: &mut DB<UserSettings>,
+    guild_settings: &DB<GuildSettings>,
+    sender: &S,
+    openai: &C,
+    now: DateTime<Utc>,
+    latency: Option<&LatencyTracker>,
+    log: Option<&DeliveryLog>,
+) -> Result<(), String> {
+    notification_tick_with_catch_up(
+        db,
+        settings,
+        guild_settings,
+        sender,
+        openai,
+        now,
+        latency,
+        log,
+        &CatchUpPolicy::default(),
+    )
+    .await
+}
+
+// Like notification_tick_logged, handling overdue pings as `catch_up` says.
+#[allow(clippy::too_many_arguments)]
+pub async fn notification_tick_with_catch_up<C: OpenAIClient + ?Sized, S: MessageSender + ?Sized>(
+    db: &mut DB<Notification>,
+    settings: &mut DB<UserSettings>,
+    guild_settings: &DB<GuildSettings>,
+    sender: &S,
+    openai: &C,
+    now: DateTime<Utc>,
+    latency: Option<&LatencyTracker>,
+    log: Option<&DeliveryLog>,
+    catch_up: &CatchUpPolicy,
+) -> Result<(), String> {
+    notification_tick_with_channels(
+        db,
+        settings,
+        guild_settings,
+        sender,
+        &[],
+        openai,
+        now,
+        latency,
+        log,
+        catch_up,
+    )
+    .await
+}
+
+// Like notification_tick_with_catch_up, delivering each ping to every target
+// of its reminder: Discord channels through `sender`, the rest through the
+// first of `channels` that handles them. A ping that reached some target is
+// done, with the misses logged as failures. One that reached none stays due
+// and is tried again after a doubling wait (see DeliveryRetry), until it
+// fails permanently or MAX_DELIVERY_ATTEMPTS times and is dropped. The rest
+// of the tick carries on either way; the first such failure is returned once
+// the store is saved.
+#[allow(clippy::too_many_arguments)]
+pub async fn notification_tick_with_channels<C: OpenAIClient + ?Sized, S: MessageSender + ?Sized>(
+    db: &mut DB<Notification>,
+    settings: &mut DB<UserSettings>,
+    guild_settings: &DB<GuildSettings>,
+    sender: &S,
+    channels: &[Arc<dyn NotificationChannel>],
+    openai: &C,
+    now: DateTime<Utc>,
+    latency: Option<&LatencyTracker>,
+    log: Option<&DeliveryLog>,
+    catch_up: &CatchUpPolicy,
+) -> Result<(), String> {
+    let started = Utc::now();
+    let plan = plan_tick(db, settings, now, catch_up, log);
+    let context = TickContext {
+        sender,
+        channels,
+        openai,
+        guild_settings,
+        settings: &plan.settings,
+        now,
+        started,
+        latency,
+        log,
+    };
+    let outcomes = dispatch_tick(&context, &plan.jobs).await;
+    finish_tick(db, settings, outcomes, now, log)
+}
+
+fn note(log: Option<&DeliveryLog>, record: DeliveryRecord) {
+    if let Some(log) = log {
+        log.log(record);
+    }
+}
+
+// A send the tick settled on while it had the store, made once it no
+// longer needs it. Each carries a copy of its reminder as it was then.
+enum TickJob {
+    Escalate { notification: Notification, since: DateTime<Utc>, locale: Locale },
+    Summary { notification: Notification, locale: Locale },
+    Ping { notification: Notification, due: DateTime<Utc>, late_since: Option<DateTime<Utc>>, locale: Locale },
+}
+
+struct TickPlan {
+    jobs: Vec<TickJob>,
+    // Settings of everyone the pings go to, as of planning.
+    settings: DB<UserSettings>,
+}
+
+enum TickOutcome {
+    Escalated { id: String, since: DateTime<Utc> },
+    Pinged { id: String, due: DateTime<Utc>, result: PingResult },
+}
+
+enum PingResult {
+    Delivered,
+    // Not sent: the owner is in focus mode. Carries the message for their
+    // digest.
+    Held(String),
+    Undelivered(String),
+}
+
+struct TickContext<'a, C: ?Sized, S: ?Sized> {
+    sender: &'a S,
+    channels: &'a [Arc<dyn NotificationChannel>],
+    openai: &'a C,
+    guild_settings: &'a DB<GuildSettings>,
+    settings: &'a DB<UserSettings>,
+    now: DateTime<Utc>,
+    // When the tick really started, so latency counts the time spent in it.
+    started: DateTime<Utc>,
+    latency: Option<&'a LatencyTracker>,
+    log: Option<&'a DeliveryLog>,
+}
+
+// Everything that doesn't need a send: pauses, quiet hours, missed pings
+// and expiry are settled in the store, and the sends that are due become
+// jobs.
+fn plan_tick(
+    db: &mut DB<Notification>,
+    settings: &DB<UserSettings>,
+    now: DateTime<Utc>,
+    catch_up: &CatchUpPolicy,
+    log: Option<&DeliveryLog>,
+) -> TickPlan {
+    let mut jobs = Vec::new();
     let mut notifications_expired: Vec<String> = Vec::new();
     for notification in db.values_mut() {
+        let locale = Locale::resolve(notification.notify.first().and_then(|user| settings.get(user)), None);
+        if let Some(ack) = notification.ack.as_ref() {
+            if let AckState::Awaiting { since } = ack.state {
+                if now - since >= ack.window() {
+                    jobs.push(TickJob::Escalate { notification: notification.clone(), since, locale });
+                }
+            }
+        }
+        if notification.paused {
+            skip_missed_while_paused(notification, now);
+            continue;
+        }
         if notification.notification_times.is_empty() {
+            // Kept around until it is acknowledged or escalated.
+            if notification
+                .ack
+                .as_ref()
+                .is_some_and(|ack| matches!(ack.state, AckState::Awaiting { .. }))
+            {
+                continue;
+            }
+            // Checklists stay tickable until the event itself has passed.
+            if !notification.checklist.is_empty() {
+                if notification.event_time.is_some_and(|time| time > now) {
+                    continue;
+                }
+                if !checklist_complete(notification) {
+                    jobs.push(TickJob::Summary { notification: notification.clone(), locale });
+                }
+            }
             notifications_expired.push(notification.id.clone());
             continue;
         }
-        let notification_time_result = notification.notification_times.first();
-        if let Some(notification_time) = notification_time_result {
-            if *notification_time < now {
-                let message_body = NotificationMessageService::build_message(notification, openai).await;
-                sender
-                    .send_message(&notification.channel, &message_body)
-                    .await?;
-                notification.notification_times.remove(0);
-                if notification.notification_times.is_empty() {
+        let Some(&notification_time) = notification.notification_times.first() else {
+            continue;
+        };
+        if notification_time >= now {
+            continue;
+        }
+        if notification.delivery_retry.as_ref().is_some_and(|retry| retry.next_attempt > now) {
+            continue;
+        }
+        // Pings due in the owner's quiet hours wait for the end of them,
+        // folded with any others due by then; urgent ones don't.
+        let quiet_until = notification
+            .notify
+            .first()
+            .filter(|_| !focus::is_urgent(&notification.content))
+            .and_then(|owner| quiet_hours_end(settings, owner, now));
+        if let Some(until) = quiet_until {
+            notification.notification_times.retain(|time| *time > until);
+            notification.notification_times.insert(0, until);
+            notification.quiet_delayed = true;
+            note(log, DeliveryRecord::new(DeliveryKind::Deferred, notification, now)
+                .with_detail(format!("quiet hours until {}", until.to_rfc3339())));
+            continue;
+        }
+        // Overdue by more than a normal tick, e.g. the bot was down: pings
+        // for an event that is over can be dropped, and the rest missed so
+        // far go out as one that says how late it is.
+        let mut late_since = None;
+        if now - notification_time >= catch_up.late_after {
+            if catch_up.skip_passed_events && notification.event_time.is_some_and(|event| event < now) {
+                notification.notification_times.retain(|time| *time >= now);
+                notification.delivery_retry = None;
+                tracing::info!(notification_id = %notification.id, "event already passed, skipping missed pings");
+                note(log, DeliveryRecord::new(DeliveryKind::Skipped, notification, now)
+                    .with_detail(format!("event passed; due {}", notification_time.to_rfc3339())));
+                if notification.notification_times.is_empty()
+                    && !advance_recurrence(notification, now)
+                    && notification.checklist.is_empty()
+                {
                     notifications_expired.push(notification.id.clone());
                 }
+                continue;
             }
+            let missed = notification
+                .notification_times
+                .iter()
+                .take_while(|time| **time < now)
+                .count();
+            notification.notification_times.drain(..missed.saturating_sub(1));
+            late_since = Some(notification_time);
         }
+        note(log, DeliveryRecord::new(DeliveryKind::Attempt, notification, now)
+            .with_detail(format!("due {}", notification_time.to_rfc3339())));
+        jobs.push(TickJob::Ping {
+            notification: notification.clone(),
+            due: notification_time,
+            late_since,
+            locale,
+        });
     }
     for notification_id in notifications_expired {
-        println!("No more notifications for {}. expiring", notification_id);
-        db.remove(notification_id.as_str());
+        tracing::info!(%notification_id, "no more notifications, expiring");
+        if let Some(notification) = db.remove(notification_id.as_str()) {
+            note(log, DeliveryRecord::new(DeliveryKind::Expired, &notification, now));
+        }
+    }
+    let settings = jobs
+        .iter()
+        .filter_map(|job| match job {
+            TickJob::Ping { notification, .. } => Some(&notification.notify),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|user| Some((user.clone(), settings.get(user)?.clone())))
+        .collect();
+    TickPlan { jobs, settings }
+}
+
+// Makes every job's sends at once; the sender paces and bounds them.
+async fn dispatch_tick<C: OpenAIClient + ?Sized, S: MessageSender + ?Sized>(
+    context: &TickContext<'_, C, S>,
+    jobs: &[TickJob],
+) -> Vec<TickOutcome> {
+    let mut pending: FuturesUnordered<_> = jobs.iter().map(|job| run_job(context, job)).collect();
+    let mut outcomes = Vec::new();
+    while let Some(outcome) = pending.next().await {
+        outcomes.extend(outcome);
+    }
+    outcomes
+}
+
+async fn run_job<C: OpenAIClient + ?Sized, S: MessageSender + ?Sized>(
+    context: &TickContext<'_, C, S>,
+    job: &TickJob,
+) -> Option<TickOutcome> {
+    let (sender, now, log) = (context.sender, context.now, context.log);
+    match job {
+        TickJob::Escalate { notification, since, locale } => {
+            let ack = notification.ack.as_ref()?;
+            let owner = notification.notify.join(", ");
+            let since_text = format!("<t:{}:R>", since.timestamp());
+            let mut args = vec![
+                ("owner", owner.as_str()),
+                ("content", notification.content.as_str()),
+                ("since", since_text.as_str()),
+            ];
+            let key = match &ack.backup {
+                Some(backup) => {
+                    args.push(("backup", backup.as_str()));
+                    MessageKey::Escalation
+                }
+                None => MessageKey::EscalationNoBackup,
+            };
+            let channel = escalation_channel(sender, context.guild_settings, &notification.channel).await;
+            match sender.send_message(&channel, &i18n::render(*locale, key, &args)).await {
+                Ok(()) => {
+                    tracing::info!(notification_id = %notification.id, %channel, "escalated unacknowledged reminder");
+                    note(log, DeliveryRecord::new(DeliveryKind::Escalated, notification, now)
+                        .with_detail(format!("to channel {}", channel)));
+                    Some(TickOutcome::Escalated { id: notification.id.clone(), since: *since })
+                }
+                // Still awaiting, so the next tick tries again; the rest of
+                // this tick's reminders go out regardless.
+                Err(err) => {
+                    tracing::warn!(notification_id = %notification.id, %err, "failed to escalate reminder");
+                    note(log, DeliveryRecord::new(DeliveryKind::Failed, notification, now)
+                        .with_detail(format!("escalation: {}", err)));
+                    None
+                }
+            }
+        }
+        // Best effort: the reminder is over either way, so a failed summary
+        // is logged rather than retried.
+        TickJob::Summary { notification, locale } => {
+            let summary = render_checklist_summary(notification, *locale);
+            if let Err(err) = sender.send_message(&notification.channel, &summary).await {
+                tracing::warn!(notification_id = %notification.id, %err, "failed to send checklist summary");
+                note(log, DeliveryRecord::new(DeliveryKind::Failed, notification, now)
+                    .with_detail(format!("checklist summary: {}", err)));
+            }
+            None
+        }
+        TickJob::Ping { notification, due, late_since, locale } => Some(TickOutcome::Pinged {
+            id: notification.id.clone(),
+            due: *due,
+            result: send_ping(context, notification, *due, *late_since, *locale).await,
+        }),
+    }
+}
+
+async fn send_ping<C: OpenAIClient + ?Sized, S: MessageSender + ?Sized>(
+    context: &TickContext<'_, C, S>,
+    notification: &Notification,
+    due: DateTime<Utc>,
+    late_since: Option<DateTime<Utc>>,
+    locale: Locale,
+) -> PingResult {
+    let (settings, now, log) = (context.settings, context.now, context.log);
+    let voice = match context.sender.guild_for_channel(&notification.channel).await {
+        Some(guild_id) => context
+            .guild_settings
+            .get(&guild_id)
+            .map(|guild| guild.voice)
+            .unwrap_or_default(),
+        None => Default::default(),
+    };
+    let mut message_body =
+        NotificationMessageService::build_message(notification, locale, voice, settings, context.openai).await;
+    if notification.quiet_delayed {
+        message_body = format!(
+            "{} {}",
+            message_body,
+            i18n::text(locale, MessageKey::QuietHoursDelayed)
+        );
+    }
+    if let Some(since) = late_since {
+        let ago = format!("<t:{}:R>", since.timestamp());
+        message_body = format!(
+            "{} {}",
+            message_body,
+            i18n::render(locale, MessageKey::LateDelivery, &[("ago", ago.as_str())])
+        );
+    }
+    if !notification.checklist.is_empty() {
+        message_body = format!("{}\n{}", message_body, render_checklist(notification));
+    }
+    // Must-ack reminders go out even in focus mode, since the
+    // acknowledgement window starts with the final ping.
+    if notification.ack.is_none()
+        && !focus::is_urgent(&notification.content)
+        && notification.notify.first().is_some_and(|owner| in_focus(settings, owner, now))
+    {
+        return PingResult::Held(message_body);
+    }
+    let style = categories::style_for(
+        &notification.content,
+        notification.notify.first().and_then(|user| settings.get(user)),
+    );
+    let message_body = categories::apply_style_prefix(&message_body, style);
+    let mut components = if notification.notification_times.len() > 1 || notification.ack.is_some() {
+        vec![delivered_buttons(&notification.id, locale)]
+    } else {
+        Vec::new()
+    };
+    components.extend(checklist_buttons(notification));
+    let message = OutgoingMessage {
+        notification,
+        due,
+        content: &message_body,
+        locale,
+        style,
+        components,
+    };
+    let mut targets = notification.delivery_targets();
+    // Everyone pinged who set up /settings push gets it on their phone too.
+    for push in notification
+        .notify
+        .iter()
+        .filter_map(|user| settings.get(user)?.push.clone())
+    {
+        if !targets.contains(&push) {
+            targets.push(push);
+        }
+    }
+    let discord = DiscordChannel(context.sender);
+    let mut delivered = 0;
+    let mut first_error = None;
+    for (index, target) in targets.into_iter().enumerate() {
+        let channel = if discord.handles(&target) {
+            Some(&discord as &dyn NotificationChannel)
+        } else {
+            context
+                .channels
+                .iter()
+                .find(|channel| channel.handles(&target))
+                .map(|channel| channel.as_ref())
+        };
+        let sent = match channel {
+            Some(channel) => channel.deliver(&target, &message).await,
+            None => Err(format!("no {} delivery configured", target.kind())),
+        };
+        match sent {
+            Ok(()) => delivered += 1,
+            Err(err) => {
+                // The reminder's own channel (always first) keeps the bare
+                // error; extra targets say which one failed.
+                let detail = if index == 0 {
+                    err.clone()
+                } else {
+                    format!("{} {}: {}", target.kind(), target.address(), err)
+                };
+                note(log, DeliveryRecord::new(DeliveryKind::Failed, notification, now).with_detail(detail));
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    if let (0, Some(err)) = (delivered, first_error) {
+        return PingResult::Undelivered(err);
+    }
+    for channel in context.channels {
+        channel.delivered(&message).await;
+    }
+    note(log, DeliveryRecord::new(DeliveryKind::Delivered, notification, now));
+    if let Some(latency) = context.latency {
+        let sent_at = now + (Utc::now() - context.started);
+        latency.record(LatencyKind::Delivery, sent_at - due);
+    }
+    PingResult::Delivered
+}
+
+// Writes back what the sends did. A reminder removed, paused or rescheduled
+// while they were out is left as the change made it.
+fn finish_tick(
+    db: &mut DB<Notification>,
+    settings: &mut DB<UserSettings>,
+    outcomes: Vec<TickOutcome>,
+    now: DateTime<Utc>,
+    log: Option<&DeliveryLog>,
+) -> Result<(), String> {
+    let mut notifications_expired: Vec<String> = Vec::new();
+    let mut held_any = false;
+    let mut undelivered = None;
+    for outcome in outcomes {
+        let (id, due, result) = match outcome {
+            TickOutcome::Escalated { id, since } => {
+                if let Some(ack) = db.get_mut(&id).and_then(|notification| notification.ack.as_mut()) {
+                    if ack.state == (AckState::Awaiting { since }) {
+                        ack.state = AckState::Escalated { at: now };
+                    }
+                }
+                continue;
+            }
+            TickOutcome::Pinged { id, due, result } => (id, due, result),
+        };
+        let Some(notification) = db.get_mut(&id) else {
+            continue;
+        };
+        if notification.paused || notification.notification_times.first() != Some(&due) {
+            tracing::debug!(notification_id = %id, "reminder changed during delivery, leaving it be");
+            continue;
+        }
+        match result {
+            PingResult::Delivered => {}
+            // Focus may have ended while the message was built; then the
+            // ping is still due and goes out next tick.
+            PingResult::Held(message) => {
+                let held = notification
+                    .notify
+                    .first()
+                    .is_some_and(|owner| hold_for_focus(settings, owner, &message, now));
+                if !held {
+                    continue;
+                }
+                held_any = true;
+                note(log, DeliveryRecord::new(DeliveryKind::Held, notification, now).with_detail("focus mode"));
+            }
+            PingResult::Undelivered(err) => {
+                let retry = DeliveryRetry::after_failure(notification.delivery_retry.as_ref(), &err, now);
+                if retry.attempts < MAX_DELIVERY_ATTEMPTS && !is_permanent_failure(&err) {
+                    tracing::warn!(
+                        notification_id = %notification.id,
+                        attempts = retry.attempts,
+                        next_attempt = %retry.next_attempt,
+                        error = %err,
+                        "delivery failed, will retry"
+                    );
+                    notification.delivery_retry = Some(retry);
+                    undelivered.get_or_insert(err);
+                    continue;
+                }
+                tracing::error!(notification_id = %notification.id, attempts = retry.attempts, error = %err, "giving up on ping");
+                note(log, DeliveryRecord::new(DeliveryKind::Failed, notification, now)
+                    .with_detail(format!("gave up after {} attempts", retry.attempts)));
+                undelivered.get_or_insert(err);
+            }
+        }
+        notification.quiet_delayed = false;
+        notification.delivery_retry = None;
+        notification.notification_times.remove(0);
+        if notification.notification_times.is_empty() {
+            if let Some(ack) = notification.ack.as_mut() {
+                ack.state = AckState::Awaiting { since: now };
+            }
+        }
+        // Checklists and acknowledgements are settled by later ticks.
+        if notification.notification_times.is_empty()
+            && !advance_recurrence(notification, now)
+            && notification.checklist.is_empty()
+            && notification.ack.is_none()
+        {
+            notifications_expired.push(notification.id.clone());
+        }
+    }
+    for notification_id in notifications_expired {
+        tracing::info!(%notification_id, "no more notifications, expiring");
+        if let Some(notification) = db.remove(notification_id.as_str()) {
+            note(log, DeliveryRecord::new(DeliveryKind::Expired, &notification, now));
+        }
     }
     save_db(&get_db_location(), db).map_err(|e| e.to_string())?;
-    Ok(())
+    if held_any {
+        save_db(&get_user_db_location(), settings).map_err(|e| e.to_string())?;
+    }
+    match undelivered {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+// The guild's escalation channel, or the reminder's own channel when the
+// guild hasn't set one.
+async fn escalation_channel<S: MessageSender + ?Sized>(
+    sender: &S,
+    guild_settings: &DB<GuildSettings>,
+    channel_id: &str,
+) -> String {
+    sender
+        .guild_for_channel(channel_id)
+        .await
+        .and_then(|guild_id| guild_settings.get(&guild_id))
+        .and_then(|guild| guild.escalation_channel.clone())
+        .unwrap_or_else(|| channel_id.to_string())
 }
@@ -0,0 +1,69 @@
+use chrono::Utc;
+use memory_db::DB;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use crate::config::SharedConfig;
+use crate::i18n::{self, Locale, MessageKey};
+use crate::models::settings::UserSettings;
+use crate::models::todo::{self, TodoItem};
+use crate::service::notify_flow::{PendingSession, SessionKey, take_expired_clarifications};
+use crate::tasks::todo_loop::{DiscordDmSender, DmSender};
+
+pub async fn run_session_loop(
+    sessions: Arc<Mutex<HashMap<SessionKey, PendingSession>>>,
+    todo_db: Arc<Mutex<DB<TodoItem>>>,
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+    discord_token: String,
+    config: SharedConfig,
+) {
+    let sender = DiscordDmSender::new(discord_token);
+    loop {
+        sleep(config.load().session_sweep_interval()).await;
+        // Release the sessions lock before touching the other stores so a
+        // /notify in flight is never blocked behind the todo save.
+        let expired = {
+            let mut sessions = sessions.lock().await;
+            take_expired_clarifications(&mut sessions, Utc::now())
+        };
+        if expired.is_empty() {
+            continue;
+        }
+        let settings = user_settings.lock().await;
+        let mut todos = todo_db.lock().await;
+        stale_clarification_tick(expired, &mut todos, &settings, &sender).await;
+    }
+}
+
+// Files each abandoned clarification as a todo for users who opted in, and
+// tells them so. Everyone else's are simply dropped.
+pub async fn stale_clarification_tick<S: DmSender + ?Sized>(
+    expired: Vec<(SessionKey, String)>,
+    todos: &mut DB<TodoItem>,
+    settings: &DB<UserSettings>,
+    sender: &S,
+) {
+    for ((user_id, _channel_id), text) in expired {
+        let Some(user) = settings.get(&user_id).filter(|user| user.stale_to_todo) else {
+            continue;
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        if let Err(err) = todo::create_todo(todos, &user_id, text) {
+            tracing::warn!(%user_id, error = %err, "failed to save stale clarification as todo");
+            continue;
+        }
+        let locale = Locale::resolve(Some(user), None);
+        let message = i18n::render(locale, MessageKey::StaleClarificationTodo, &[("text", text)]);
+        if let Err(err) = sender
+            .send_dm(user_id.trim_start_matches('@'), &message)
+            .await
+        {
+            tracing::warn!(%user_id, error = %err, "failed to send stale clarification notice");
+        }
+    }
+}
@@ -1,22 +1,489 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+type TaskFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type TaskFactory = Box<dyn Fn(TaskContext) -> TaskFuture + Send + Sync>;
+
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+const DEFAULT_STOP_GRACE: Duration = Duration::from_secs(10);
+// A task that stayed up this long before crashing starts over from the
+// initial backoff.
+const STABLE_AFTER: Duration = Duration::from_secs(5 * 60);
+// How often a cron task re-reads its schedule while waiting, so a config
+// reload is picked up without a restart.
+const CRON_RECHECK: Duration = Duration::from_secs(60);
+
+// A cron expression read in a time zone. Takes the usual five fields
+// (minute hour day month weekday) or six with seconds first, e.g.
+// "0 7 * * MON-FRI" or "0 30 3 * * *".
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    expression: String,
+    schedule: cron::Schedule,
+    zone: Tz,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str, zone: Tz) -> Result<Self, String> {
+        let expression = expression.split_whitespace().collect::<Vec<_>>().join(" ");
+        let full = if expression.split(' ').count() == 5 {
+            format!("0 {}", expression)
+        } else {
+            expression.clone()
+        };
+        let schedule = cron::Schedule::from_str(&full)
+            .map_err(|err| format!("'{}' is not a cron expression: {}", expression, err))?;
+        Ok(Self {
+            expression,
+            schedule,
+            zone,
+        })
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn next_after(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.schedule
+            .after(&now.with_timezone(&self.zone))
+            .next()
+            .map(|at| at.with_timezone(&Utc))
+    }
+}
+
+impl PartialEq for CronSchedule {
+    fn eq(&self, other: &Self) -> bool {
+        self.expression == other.expression && self.zone == other.zone
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    // Crashed and waiting out its backoff before the next start.
+    Restarting,
+    // Returned on its own, e.g. the calendar loop without a calendar.
+    Finished,
+    // Ended by a shutdown.
+    Stopped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskStatus {
+    pub name: String,
+    pub state: TaskState,
+    pub restarts: u32,
+    pub started_at: DateTime<Utc>,
+    // Only tasks that run jobs, such as cron tasks, report runs.
+    pub last_run: Option<DateTime<Utc>>,
+    pub next_run: Option<DateTime<Utc>>,
+    // The last panic or failed run.
+    pub last_error: Option<String>,
+}
+
+// What every supervised task is doing, for /healthz.
+#[derive(Debug, Clone, Default)]
+pub struct TaskHealth(Arc<Mutex<BTreeMap<String, TaskStatus>>>);
+
+impl TaskHealth {
+    // By name.
+    pub fn snapshot(&self) -> Vec<TaskStatus> {
+        self.0.lock().unwrap().values().cloned().collect()
+    }
+
+    // False while any task is down between a crash and its restart.
+    pub fn is_healthy(&self) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .values()
+            .all(|status| status.state != TaskState::Restarting)
+    }
+
+    fn update(&self, name: &str, change: impl FnOnce(&mut TaskStatus)) {
+        let mut tasks = self.0.lock().unwrap();
+        let status = tasks.entry(name.to_string()).or_insert_with(|| TaskStatus {
+            name: name.to_string(),
+            state: TaskState::Running,
+            restarts: 0,
+            started_at: Utc::now(),
+            last_run: None,
+            next_run: None,
+            last_error: None,
+        });
+        change(status);
+    }
+}
+
+// One line for /admin stats, e.g. "cleanup: running, last run <t:..:R>,
+// next run <t:..:R>".
+pub fn format_task_status(status: &TaskStatus) -> String {
+    let state = match status.state {
+        TaskState::Running => "running",
+        TaskState::Restarting => "restarting",
+        TaskState::Finished => "finished",
+        TaskState::Stopped => "stopped",
+    };
+    let mut line = format!("{}: {}", status.name, state);
+    if status.restarts > 0 {
+        line.push_str(&format!(", {} restarts", status.restarts));
+    }
+    if let Some(at) = status.last_run {
+        line.push_str(&format!(", last run <t:{}:R>", at.timestamp()));
+    }
+    if let Some(at) = status.next_run {
+        line.push_str(&format!(", next run <t:{}:R>", at.timestamp()));
+    }
+    if let Some(err) = &status.last_error {
+        line.push_str(&format!(", last error: {}", err));
+    }
+    line
+}
+
+// Flips once when the runner is stopped. Dropping the runner's side without
+// stopping never fires it.
+#[derive(Debug, Clone)]
+pub struct ShutdownToken(watch::Receiver<bool>);
+
+impl ShutdownToken {
+    pub fn is_stopping(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    pub async fn stopped(&self) {
+        let mut rx = self.0.clone();
+        if rx.wait_for(|stopping| *stopping).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+// Handed to a cooperative task on every start: its name, the shutdown token
+// to watch and a way to report its runs into the health map.
+#[derive(Debug, Clone)]
+pub struct TaskContext {
+    name: String,
+    health: TaskHealth,
+    shutdown: ShutdownToken,
+}
+
+impl TaskContext {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn shutdown(&self) -> &ShutdownToken {
+        &self.shutdown
+    }
+
+    // A failed run is kept as the last error; a good one leaves it alone.
+    pub fn record_run(&self, at: DateTime<Utc>, result: &Result<(), String>) {
+        self.health.update(&self.name, |status| {
+            status.last_run = Some(at);
+            if let Err(err) = result {
+                status.last_error = Some(err.clone());
+            }
+        });
+    }
+
+    pub fn set_next_run(&self, at: Option<DateTime<Utc>>) {
+        self.health.update(&self.name, |status| status.next_run = at);
+    }
+}
+
+struct RegisteredTask {
+    name: String,
+    factory: TaskFactory,
+    // Returns by itself once the shutdown token fires, rather than being
+    // aborted.
+    cooperative: bool,
+}
+
+// Starts the background loops and keeps them running: a task that panics is
+// logged and started again after a backoff that doubles up to a cap, and
+// its state is kept in `health`.
 pub struct TaskRunner {
-    tasks: Vec<Box<dyn FnOnce() + Send>>,
+    tasks: Vec<RegisteredTask>,
+    health: TaskHealth,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    stop_grace: Duration,
 }
 
 impl TaskRunner {
     pub fn new() -> Self {
-        Self { tasks: Vec::new() }
+        Self {
+            tasks: Vec::new(),
+            health: TaskHealth::default(),
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            stop_grace: DEFAULT_STOP_GRACE,
+        }
+    }
+
+    // Reports into an existing health map, so several runners show up on one
+    // /healthz.
+    pub fn with_health(mut self, health: TaskHealth) -> Self {
+        self.health = health;
+        self
+    }
+
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    // How long a cooperative task gets to return after a stop before it is
+    // aborted.
+    pub fn with_stop_grace(mut self, grace: Duration) -> Self {
+        self.stop_grace = grace;
+        self
+    }
+
+    pub fn health(&self) -> TaskHealth {
+        self.health.clone()
+    }
+
+    // `task` is called again for every restart, so it builds the loop from
+    // clones of what it captured. It is aborted at its next await on stop.
+    pub fn add_task<F, Fut>(&mut self, name: &str, task: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.push(RegisteredTask {
+            name: name.to_string(),
+            factory: Box::new(move |_| Box::pin(task()) as TaskFuture),
+            cooperative: false,
+        });
+    }
+
+    // Like add_task, but the task watches `TaskContext::shutdown` and returns
+    // on its own, so work in flight is not cut off.
+    pub fn add_cooperative_task<F, Fut>(&mut self, name: &str, task: F)
+    where
+        F: Fn(TaskContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.push(RegisteredTask {
+            name: name.to_string(),
+            factory: Box::new(move |context| Box::pin(task(context)) as TaskFuture),
+            cooperative: true,
+        });
     }
 
-    pub fn add_task<F>(&mut self, task: F)
+    // Runs `job` at every time `schedule` gives, under the same supervision
+    // as a loop. `schedule` is asked again before every run and at least
+    // once a minute, so it can follow the config; None pauses the job. A job
+    // already running when the runner stops is let finish.
+    pub fn add_cron_task<S, F, Fut>(&mut self, name: &str, schedule: S, job: F)
     where
-        F: FnOnce() + Send + 'static,
+        S: Fn() -> Option<CronSchedule> + Send + Sync + 'static,
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
     {
-        self.tasks.push(Box::new(task));
+        let schedule = Arc::new(schedule);
+        let job = Arc::new(job);
+        self.add_cooperative_task(name, move |context| {
+            run_on_schedule(schedule.clone(), job.clone(), context)
+        });
     }
 
-    pub fn start_all(self) {
-        for task in self.tasks {
-            task();
+    // One supervisor per task; they run until their task finishes or the
+    // returned handle is stopped.
+    pub fn start_all(self) -> RunningTasks {
+        let (stop, stopping) = watch::channel(false);
+        let shutdown = ShutdownToken(stopping);
+        let handles = self
+            .tasks
+            .into_iter()
+            .map(|task| {
+                tokio::spawn(supervise(
+                    task,
+                    self.health.clone(),
+                    shutdown.clone(),
+                    Backoff {
+                        initial: self.initial_backoff,
+                        max: self.max_backoff,
+                    },
+                    self.stop_grace,
+                ))
+            })
+            .collect();
+        RunningTasks { stop, handles }
+    }
+}
+
+// The started tasks. Dropping this leaves them running.
+pub struct RunningTasks {
+    stop: watch::Sender<bool>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl RunningTasks {
+    // Fires the shutdown token and waits until every task has ended: plain
+    // tasks are aborted, cooperative ones get the stop grace to return.
+    pub async fn stop(self) {
+        let _ = self.stop.send(true);
+        for handle in self.handles {
+            if let Err(err) = handle.await {
+                tracing::error!(error = ?err, "task supervisor failed during shutdown");
+            }
         }
     }
 }
+
+impl Default for TaskRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Backoff {
+    initial: Duration,
+    max: Duration,
+}
+
+async fn supervise(
+    task: RegisteredTask,
+    health: TaskHealth,
+    shutdown: ShutdownToken,
+    backoff_limits: Backoff,
+    stop_grace: Duration,
+) {
+    let name = task.name;
+    let stopped = |health: &TaskHealth| {
+        tracing::info!(task = %name, "background task stopped");
+        health.update(&name, |status| {
+            status.state = TaskState::Stopped;
+            status.next_run = None;
+        });
+    };
+    let mut backoff = backoff_limits.initial;
+    loop {
+        if shutdown.is_stopping() {
+            stopped(&health);
+            return;
+        }
+        let started = tokio::time::Instant::now();
+        health.update(&name, |status| {
+            status.state = TaskState::Running;
+            status.started_at = Utc::now();
+        });
+        let context = TaskContext {
+            name: name.clone(),
+            health: health.clone(),
+            shutdown: shutdown.clone(),
+        };
+        let mut handle = tokio::spawn((task.factory)(context));
+        let outcome = tokio::select! {
+            outcome = &mut handle => outcome,
+            _ = shutdown.stopped() => {
+                if task.cooperative {
+                    if tokio::time::timeout(stop_grace, &mut handle).await.is_ok() {
+                        stopped(&health);
+                        return;
+                    }
+                    tracing::warn!(task = %name, "background task ignored the stop, aborting");
+                    health.update(&name, |status| {
+                        status.last_error = Some("did not stop within the grace period".to_string());
+                    });
+                }
+                handle.abort();
+                let _ = handle.await;
+                stopped(&health);
+                return;
+            }
+        };
+        match outcome {
+            Ok(()) if shutdown.is_stopping() => {
+                stopped(&health);
+                return;
+            }
+            Ok(()) => {
+                tracing::info!(task = %name, "background task finished");
+                health.update(&name, |status| status.state = TaskState::Finished);
+                return;
+            }
+            Err(err) => {
+                let message = if err.is_panic() {
+                    panic_message(err.into_panic())
+                } else {
+                    "cancelled".to_string()
+                };
+                if started.elapsed() >= STABLE_AFTER {
+                    backoff = backoff_limits.initial;
+                }
+                tracing::error!(task = %name, error = %message, retry_in_ms = backoff.as_millis() as u64, "background task crashed, restarting");
+                health.update(&name, |status| {
+                    status.state = TaskState::Restarting;
+                    status.restarts += 1;
+                    status.last_error = Some(message);
+                });
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = shutdown.stopped() => {}
+                }
+                backoff = (backoff * 2).min(backoff_limits.max);
+            }
+        }
+    }
+}
+
+async fn run_on_schedule<S, F, Fut>(schedule: Arc<S>, job: Arc<F>, context: TaskContext)
+where
+    S: Fn() -> Option<CronSchedule> + Send + Sync + 'static,
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    // The wall clock may still read a hair before `next` after the sleep, so
+    // a run is never repeated for the same time.
+    let mut last_run: Option<DateTime<Utc>> = None;
+    loop {
+        let now = Utc::now();
+        let from = last_run.map_or(now, |last| last.max(now));
+        let next = schedule().and_then(|schedule| schedule.next_after(from));
+        context.set_next_run(next);
+        let wait = next.map_or(CRON_RECHECK, |next| (next - now).to_std().unwrap_or_default());
+        tokio::select! {
+            _ = tokio::time::sleep(wait.min(CRON_RECHECK)) => {}
+            _ = context.shutdown().stopped() => return,
+        }
+        let Some(next) = next.filter(|_| wait <= CRON_RECHECK) else {
+            continue;
+        };
+        tracing::info!(task = %context.name(), "running scheduled job");
+        last_run = Some(next);
+        let started = Utc::now();
+        let result = job().await;
+        if let Err(err) = &result {
+            tracing::warn!(task = %context.name(), error = %err, "scheduled job failed");
+        }
+        context.record_run(started, &result);
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| "panicked".to_string()),
+    }
+}
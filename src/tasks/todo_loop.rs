@@ -1,15 +1,22 @@
-use chrono::{DateTime, Duration, TimeZone, Utc};
-use chrono_tz::America::New_York;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
 use memory_db::{DB, save_db};
 use serenity::async_trait;
 use serenity::http::Http;
 use serenity::model::id::UserId;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::sleep;
 
+use crate::config::SharedConfig;
+use crate::i18n::{self, Locale, MessageKey};
+use crate::models::notification::{AckState, Notification};
+use crate::models::settings::{UserSettings, get_user_db_location, hold_for_focus};
 use crate::models::todo::{get_db_location, TodoItem};
+use crate::service::delivery_log::{DeliveryKind, DeliveryLog, DeliveryRecord, get_delivery_log_location};
+use crate::service::upcoming::{SharedUpcoming, UpcomingWindow};
+use crate::tasks::calendar_loop::{self, CalendarClient, CalendarEvent};
 
 #[async_trait]
 pub trait DmSender: Send + Sync {
@@ -46,38 +53,223 @@ impl DmSender for DiscordDmSender {
     }
 }
 
-pub async fn run_todo_loop(db: Arc<Mutex<DB<TodoItem>>>, discord_token: Arc<String>) {
+pub async fn run_todo_loop(
+    db: Arc<Mutex<DB<TodoItem>>>,
+    upcoming: SharedUpcoming,
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+    config: SharedConfig,
+    discord_token: Arc<String>,
+) {
     let sender = DiscordDmSender::new(discord_token.to_string());
+    // Only the configured calendar user sees calendar events in their
+    // briefing.
+    let calendar = config
+        .load()
+        .calendar_provider()
+        .map(|provider| (provider.user_id().to_string(), calendar_loop::connect(&provider)));
     loop {
-        let next_run = next_daily_run(Utc::now());
-        let sleep_for = (next_run - Utc::now())
-            .to_std()
-            .unwrap_or_else(|_| std::time::Duration::from_secs(60));
-        sleep(sleep_for).await;
-        let mut db = db.lock().await;
-        let _ = daily_summary_tick(&mut db, &sender).await;
+        sleep(config.load().briefing_check_interval()).await;
+        let now = Utc::now();
+        let any_due = briefings_due(&*user_settings.lock().await, now).next().is_some();
+        if any_due {
+            let todos = db.lock().await.clone();
+            let upcoming = upcoming.lock().await.clone();
+            let mut settings = user_settings.lock().await;
+            let calendar = calendar
+                .as_ref()
+                .map(|(owner, client)| (client.as_ref(), owner.as_str()));
+            let sent = morning_briefing_tick(&todos, &upcoming, &mut settings, calendar, &sender, now).await;
+            if sent > 0 {
+                if let Err(err) = save_db(&get_user_db_location(), &*settings) {
+                    tracing::warn!(error = %err, "failed to save briefing state");
+                }
+            }
+        }
+
+        let any_summary_due = summaries_due(&*user_settings.lock().await, now).next().is_some();
+        if any_summary_due {
+            let todos = db.lock().await.clone();
+            let mut settings = user_settings.lock().await;
+            if personal_summaries_tick(&todos, &mut settings, &sender, now).await > 0 {
+                if let Err(err) = save_db(&get_user_db_location(), &*settings) {
+                    tracing::warn!(error = %err, "failed to save summary state");
+                }
+            }
+        }
     }
 }
 
-fn next_daily_run(now: DateTime<Utc>) -> DateTime<Utc> {
-    let now_local = now.with_timezone(&New_York);
-    let today = now_local.date_naive();
-    let target_local = New_York
-        .from_local_datetime(&today.and_hms_opt(7, 0, 0).unwrap())
-        .single()
-        .unwrap_or_else(|| New_York.from_utc_datetime(&today.and_hms_opt(7, 0, 0).unwrap()));
+// The "todo_summary" cron task: DMs everyone without a morning briefing
+// their open todos.
+pub async fn run_daily_summary(
+    db: Arc<Mutex<DB<TodoItem>>>,
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+    discord_token: Arc<String>,
+) -> Result<(), String> {
+    let sender = DiscordDmSender::new(discord_token.to_string());
+    let mut db = db.lock().await;
+    let mut settings = user_settings.lock().await;
+    daily_summary_tick(&mut db, &mut settings, &sender, Utc::now()).await
+}
 
-    if now_local < target_local {
-        target_local.with_timezone(&Utc)
-    } else {
-        (target_local + Duration::days(1)).with_timezone(&Utc)
+// The "weekly_digest" cron task.
+pub async fn run_weekly_digest(
+    todo_db: Arc<Mutex<DB<TodoItem>>>,
+    notifications: Arc<Mutex<DB<Notification>>>,
+    user_settings: Arc<Mutex<DB<UserSettings>>>,
+    discord_token: Arc<String>,
+) -> Result<(), String> {
+    let sender = DiscordDmSender::new(discord_token.to_string());
+    let deliveries = match DeliveryLog::new(get_delivery_log_location()).read_from(0) {
+        Ok((records, _)) => records,
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to read deliveries for the weekly digest");
+            Vec::new()
+        }
+    };
+    let todos = todo_db.lock().await.clone();
+    let reminders = notifications.lock().await.clone();
+    let mut settings = user_settings.lock().await;
+    let sent = weekly_digest_tick(&todos, &reminders, &deliveries, &mut settings, &sender, Utc::now()).await;
+    if sent > 0 {
+        save_db(&get_user_db_location(), &*settings)
+            .map_err(|err| format!("failed to save weekly digest state: {}", err))?;
     }
+    Ok(())
 }
 
-async fn daily_summary_tick<S: DmSender + ?Sized>(
-    db: &mut DB<TodoItem>,
+struct WeeklyDigest {
+    delivered: Vec<String>,
+    completed: Vec<String>,
+    overdue: Vec<String>,
+    upcoming: Vec<(DateTime<Utc>, String)>,
+}
+
+impl WeeklyDigest {
+    fn is_empty(&self) -> bool {
+        self.delivered.is_empty() && self.completed.is_empty() && self.overdue.is_empty() && self.upcoming.is_empty()
+    }
+}
+
+// What happened for `user_id` over the last seven days and what the next
+// seven hold. Overdue means a todo open for over a week or a must-ack
+// reminder nobody has acknowledged.
+fn weekly_digest_for(
+    todos: &DB<TodoItem>,
+    notifications: &DB<Notification>,
+    deliveries: &[DeliveryRecord],
+    user_id: &str,
+    now: DateTime<Utc>,
+) -> WeeklyDigest {
+    let week_ago = now - Duration::days(7);
+    let mut seen = BTreeSet::new();
+    let delivered = deliveries
+        .iter()
+        .filter(|record| {
+            record.kind == DeliveryKind::Delivered
+                && record.user_id.as_deref() == Some(user_id)
+                && record.at >= week_ago
+                && record.at < now
+        })
+        .filter(|record| seen.insert(record.notification_id.clone()))
+        .map(|record| record.content.clone())
+        .collect();
+
+    let mut mine: Vec<&TodoItem> = todos.values().filter(|item| item.user_id == user_id).collect();
+    mine.sort_by_key(|item| item.created_at);
+    let completed = mine
+        .iter()
+        .filter(|item| item.completed_at.is_some_and(|done| done >= week_ago && done < now))
+        .map(|item| item.content.clone())
+        .collect();
+    let mut overdue: Vec<String> = notifications
+        .values()
+        .filter(|n| n.notify.iter().any(|u| u == user_id))
+        .filter(|n| {
+            n.ack
+                .as_ref()
+                .is_some_and(|ack| matches!(ack.state, AckState::Awaiting { .. } | AckState::Escalated { .. }))
+        })
+        .map(|n| n.content.clone())
+        .collect();
+    overdue.sort();
+    overdue.extend(
+        mine.iter()
+            .filter(|item| item.completed_at.is_none() && item.created_at < week_ago)
+            .map(|item| item.content.clone()),
+    );
+
+    WeeklyDigest {
+        delivered,
+        completed,
+        overdue,
+        upcoming: reminders_between(notifications, user_id, now, now + Duration::days(7)),
+    }
+}
+
+fn render_weekly_digest(locale: Locale, zone: Tz, digest: &WeeklyDigest) -> String {
+    let mut body = i18n::text(locale, MessageKey::WeeklyDigestHeader).to_string();
+    let mut section = |key: MessageKey, lines: Vec<String>| {
+        if !lines.is_empty() {
+            body.push_str(&format!("\n\n**{}**", i18n::text(locale, key)));
+            for line in lines {
+                body.push_str(&format!("\n• {}", line));
+            }
+        }
+    };
+    section(MessageKey::WeeklyDigestDelivered, digest.delivered.clone());
+    section(MessageKey::WeeklyDigestCompleted, digest.completed.clone());
+    section(MessageKey::WeeklyDigestOverdue, digest.overdue.clone());
+    section(
+        MessageKey::WeeklyDigestUpcoming,
+        digest
+            .upcoming
+            .iter()
+            .map(|(at, content)| format!("{} {}", at.with_timezone(&zone).format("%a %-I:%M%P"), content))
+            .collect(),
+    );
+    body
+}
+
+// DMs everyone with something to report a digest of their week; users with
+// nothing delivered, done, overdue or coming up are skipped. Digests are held
+// for focus like any other nudge. Returns how many went out or were held; the
+// caller saves `settings`.
+pub async fn weekly_digest_tick<S: DmSender + ?Sized>(
+    todos: &DB<TodoItem>,
+    notifications: &DB<Notification>,
+    deliveries: &[DeliveryRecord],
+    settings: &mut DB<UserSettings>,
     sender: &S,
-) -> Result<(), String> {
+    now: DateTime<Utc>,
+) -> usize {
+    let users: BTreeSet<String> = todos
+        .values()
+        .map(|item| item.user_id.clone())
+        .chain(notifications.values().flat_map(|n| n.notify.iter().cloned()))
+        .chain(deliveries.iter().filter_map(|record| record.user_id.clone()))
+        .collect();
+    let mut sent = 0;
+    for user_id in users {
+        let digest = weekly_digest_for(todos, notifications, deliveries, &user_id, now);
+        if digest.is_empty() {
+            continue;
+        }
+        let locale = Locale::resolve(settings.get(&user_id), None);
+        let zone = settings.get(&user_id).map(UserSettings::zone).unwrap_or(chrono_tz::America::New_York);
+        let body = render_weekly_digest(locale, zone, &digest);
+        sent += 1;
+        if hold_for_focus(settings, &user_id, &body, now) {
+            continue;
+        }
+        if let Err(err) = sender.send_dm(user_id.trim_start_matches('@'), &body).await {
+            tracing::warn!(%user_id, error = %err, "failed to send weekly digest");
+        }
+    }
+    sent
+}
+
+fn open_todos_by_user(db: &DB<TodoItem>) -> HashMap<String, Vec<TodoItem>> {
     let mut by_user: HashMap<String, Vec<TodoItem>> = HashMap::new();
     for item in db.values() {
         if item.completed_at.is_none() {
@@ -87,16 +279,276 @@ async fn daily_summary_tick<S: DmSender + ?Sized>(
                 .push(item.clone());
         }
     }
-
-    for (user_id, mut items) in by_user {
+    for items in by_user.values_mut() {
         items.sort_by_key(|item| item.created_at);
-        let mut body = String::from("Good morning! Here is your current todo list:\n");
-        for (idx, item) in items.iter().enumerate() {
-            body.push_str(&format!("{}) {}\n", idx + 1, item.content));
+    }
+    by_user
+}
+
+// Sends (or holds for focus) one user's summary; returns whether it was held.
+async fn send_summary<S: DmSender + ?Sized>(
+    settings: &mut DB<UserSettings>,
+    sender: &S,
+    user_id: &str,
+    items: &[TodoItem],
+    now: DateTime<Utc>,
+) -> Result<bool, String> {
+    let locale = Locale::resolve(settings.get(user_id), None);
+    let mut body = format!("{}\n", i18n::text(locale, MessageKey::TodoSummaryHeader));
+    for (idx, item) in items.iter().enumerate() {
+        body.push_str(&format!("{}) {}\n", idx + 1, item.content));
+    }
+    if hold_for_focus(settings, user_id, body.trim_end(), now) {
+        return Ok(true);
+    }
+    sender.send_dm(user_id.trim_start_matches('@'), body.trim_end()).await?;
+    Ok(false)
+}
+
+// The bot-wide summary, for everyone who hasn't picked their own time, opted
+// out or switched to the morning briefing.
+async fn daily_summary_tick<S: DmSender + ?Sized>(
+    db: &mut DB<TodoItem>,
+    settings: &mut DB<UserSettings>,
+    sender: &S,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let mut held_any = false;
+    for (user_id, items) in open_todos_by_user(db) {
+        if settings
+            .get(&user_id)
+            .is_some_and(|s| s.agenda.is_some() || s.summary.is_some())
+        {
+            continue;
         }
-        sender.send_dm(&user_id, body.trim_end()).await?;
+        held_any |= send_summary(settings, sender, &user_id, &items, now).await?;
     }
 
     save_db(&get_db_location(), db).map_err(|e| e.to_string())?;
+    if held_any {
+        save_db(&get_user_db_location(), settings).map_err(|e| e.to_string())?;
+    }
     Ok(())
 }
+
+// Users with their own summary time that has come in their timezone and
+// who haven't had one yet today, with that local date.
+fn summaries_due(
+    settings: &DB<UserSettings>,
+    now: DateTime<Utc>,
+) -> impl Iterator<Item = (String, NaiveDate)> + '_ {
+    settings.values().filter_map(move |user| {
+        let summary = user.summary.as_ref()?;
+        let local = now.with_timezone(&user.zone());
+        let due = user.agenda.is_none()
+            && summary.time.is_some_and(|at| local.time() >= at)
+            && summary.last_sent != Some(local.date_naive());
+        due.then(|| (user.user_id.clone(), local.date_naive()))
+    })
+}
+
+// Sends the summary to each user whose own summary time has come, skipping
+// those with no open todos, and marks the day done for all of them. Returns
+// how many were marked; the caller saves `settings`.
+pub async fn personal_summaries_tick<S: DmSender + ?Sized>(
+    todos: &DB<TodoItem>,
+    settings: &mut DB<UserSettings>,
+    sender: &S,
+    now: DateTime<Utc>,
+) -> usize {
+    let due: Vec<(String, NaiveDate)> = summaries_due(settings, now).collect();
+    let mut open = open_todos_by_user(todos);
+    for (user_id, today) in &due {
+        if let Some(items) = open.remove(user_id) {
+            if let Err(err) = send_summary(settings, sender, user_id, &items, now).await {
+                tracing::warn!(%user_id, error = %err, "failed to send todo summary");
+            }
+        }
+        if let Some(summary) = settings.get_mut(user_id).and_then(|s| s.summary.as_mut()) {
+            summary.last_sent = Some(*today);
+        }
+    }
+    due.len()
+}
+
+// Users whose briefing hour has come in their own timezone and who haven't
+// had one yet today, with that zone.
+fn briefings_due(
+    settings: &DB<UserSettings>,
+    now: DateTime<Utc>,
+) -> impl Iterator<Item = (String, Tz)> + '_ {
+    settings.values().filter_map(move |user| {
+        let agenda = user.agenda.as_ref()?;
+        let zone = user.zone();
+        let local = now.with_timezone(&zone);
+        (local.hour() >= agenda.hour && agenda.last_sent != Some(local.date_naive()))
+            .then(|| (user.user_id.clone(), zone))
+    })
+}
+
+fn local_day_bounds(day: NaiveDate, zone: Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+    let midnight = |date: NaiveDate| {
+        let naive = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+        zone.from_local_datetime(&naive)
+            .earliest()
+            .unwrap_or_else(|| zone.from_utc_datetime(&naive))
+            .with_timezone(&Utc)
+    };
+    (midnight(day), midnight(day + Duration::days(1)))
+}
+
+// The client is asked per UTC day, so a local day can span two requests.
+async fn calendar_events_between(
+    client: &dyn CalendarClient,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<CalendarEvent>, String> {
+    let mut events = Vec::new();
+    let mut day = start.date_naive();
+    while day <= (end - Duration::seconds(1)).date_naive() {
+        events.extend(client.get_events_for_day(day).await?);
+        day += Duration::days(1);
+    }
+    events.retain(|event| event.start_time >= start && event.start_time < end);
+    events.sort_by_key(|event| event.start_time);
+    Ok(events)
+}
+
+// Each of the user's live reminders with something happening in the window:
+// the event itself if it falls there, otherwise its first ping.
+fn reminders_between(
+    notifications: &DB<Notification>,
+    user_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, String)> {
+    let within = |at: &DateTime<Utc>| *at >= start && *at < end;
+    let mut reminders: Vec<(DateTime<Utc>, String)> = notifications
+        .values()
+        .filter(|n| !n.paused && n.notify.iter().any(|u| u == user_id))
+        .filter_map(|n| {
+            let at = n
+                .event_time
+                .filter(within)
+                .or_else(|| n.notification_times.iter().copied().find(within))?;
+            Some((at, n.content.clone()))
+        })
+        .collect();
+    reminders.sort();
+    reminders
+}
+
+// reminders_between for the next day, read off the upcoming window instead
+// of the whole store.
+fn upcoming_between(
+    upcoming: &UpcomingWindow,
+    user_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, String)> {
+    let within = |at: &DateTime<Utc>| *at >= start && *at < end;
+    let mut seen = BTreeSet::new();
+    let mut reminders: Vec<(DateTime<Utc>, String)> = upcoming
+        .for_user(user_id)
+        .iter()
+        .filter_map(|ping| {
+            let at = ping.event_time.filter(within).or(Some(ping.at).filter(within))?;
+            seen.insert(ping.notification_id.as_str()).then(|| (at, ping.content.clone()))
+        })
+        .collect();
+    reminders.sort();
+    reminders
+}
+
+fn render_briefing(
+    locale: Locale,
+    zone: Tz,
+    events: &[CalendarEvent],
+    reminders: &[(DateTime<Utc>, String)],
+    todos: &[&TodoItem],
+) -> String {
+    let time = |at: DateTime<Utc>| at.with_timezone(&zone).format("%-I:%M%P").to_string();
+    let mut body = i18n::text(locale, MessageKey::AgendaHeader).to_string();
+    if !events.is_empty() {
+        body.push_str(&format!("\n\n**{}**", i18n::text(locale, MessageKey::AgendaCalendar)));
+        for event in events {
+            body.push_str(&format!(
+                "\n• {}–{} {}",
+                time(event.start_time),
+                time(event.end_time),
+                event.title
+            ));
+        }
+    }
+    if !reminders.is_empty() {
+        body.push_str(&format!("\n\n**{}**", i18n::text(locale, MessageKey::AgendaReminders)));
+        for (at, content) in reminders {
+            body.push_str(&format!("\n• {} {}", time(*at), content));
+        }
+    }
+    if !todos.is_empty() {
+        body.push_str(&format!("\n\n**{}**", i18n::text(locale, MessageKey::AgendaTodos)));
+        for (idx, item) in todos.iter().enumerate() {
+            body.push_str(&format!("\n{}) {}", idx + 1, item.content));
+        }
+    }
+    if events.is_empty() && reminders.is_empty() && todos.is_empty() {
+        body.push_str(&format!("\n\n{}", i18n::text(locale, MessageKey::AgendaEmpty)));
+    }
+    body
+}
+
+// Sends each opted-in user whose hour has come one DM merging today's
+// calendar events, the reminders due today and their open todos. Calendar
+// events already mirrored into reminders are listed once, under Calendar.
+// A failed calendar read or DM is logged and the day still counts as done,
+// so nobody gets retried every minute. Returns how many briefings went out
+// (or were held for focus); the caller saves `settings`.
+pub async fn morning_briefing_tick<S: DmSender + ?Sized>(
+    todos: &DB<TodoItem>,
+    upcoming: &UpcomingWindow,
+    settings: &mut DB<UserSettings>,
+    calendar: Option<(&dyn CalendarClient, &str)>,
+    sender: &S,
+    now: DateTime<Utc>,
+) -> usize {
+    let due: Vec<(String, Tz)> = briefings_due(settings, now).collect();
+    for (user_id, zone) in &due {
+        let today = now.with_timezone(zone).date_naive();
+        let (start, end) = local_day_bounds(today, *zone);
+        let events = match calendar {
+            Some((client, owner)) if owner == user_id => {
+                calendar_events_between(client, start, end)
+                    .await
+                    .unwrap_or_else(|err| {
+                        tracing::warn!(%user_id, error = %err, "failed to read calendar for briefing");
+                        Vec::new()
+                    })
+            }
+            _ => Vec::new(),
+        };
+        let mut reminders = upcoming_between(upcoming, user_id, start, end);
+        reminders.retain(|(at, content)| {
+            !events
+                .iter()
+                .any(|event| event.start_time == *at && event.title == *content)
+        });
+        let mut open: Vec<&TodoItem> = todos
+            .values()
+            .filter(|item| item.user_id == *user_id && item.completed_at.is_none())
+            .collect();
+        open.sort_by_key(|item| item.created_at);
+
+        let locale = Locale::resolve(settings.get(user_id), None);
+        let body = render_briefing(locale, *zone, &events, &reminders, &open);
+        if !hold_for_focus(settings, user_id, &body, now) {
+            if let Err(err) = sender.send_dm(user_id.trim_start_matches('@'), &body).await {
+                tracing::warn!(%user_id, error = %err, "failed to send morning briefing");
+            }
+        }
+        if let Some(agenda) = settings.get_mut(user_id).and_then(|s| s.agenda.as_mut()) {
+            agenda.last_sent = Some(today);
+        }
+    }
+    due.len()
+}
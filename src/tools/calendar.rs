@@ -0,0 +1,210 @@
+use std::env;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use memory_db::{DB, load_db, save_db};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::tasks::calendar_loop::{CalendarClient, CalendarEvent};
+use crate::tools::Tool;
+
+pub const CREATE_CALENDAR_EVENT: &str = "create_calendar_event";
+
+// Calendars need an end; events asked for without one get this long.
+const DEFAULT_EVENT_MINUTES: i64 = 30;
+
+// Returns the directory where calendar events created by tools live.
+// Defaults to a relative "./data/calendar" directory.
+pub fn get_calendar_location() -> String {
+    let base = env::var("DB_LOCATION").unwrap_or("./data".to_string());
+    format!("{}/calendar", base)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarEntry {
+    pub id: String,
+    pub user_id: String,
+    pub title: String,
+    pub start: DateTime<Utc>,
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateEventArgs {
+    title: String,
+    start: DateTime<Utc>,
+    #[serde(default)]
+    end: Option<DateTime<Utc>>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+fn parse_args(arguments: &Value) -> Result<CreateEventArgs, String> {
+    let args: CreateEventArgs = serde_json::from_value(arguments.clone())
+        .map_err(|e| format!("Invalid calendar event arguments: {}", e))?;
+    if args.title.trim().is_empty() {
+        return Err("A calendar event needs a title".to_string());
+    }
+    if args.end.is_some_and(|end| end < args.start) {
+        return Err("A calendar event can't end before it starts".to_string());
+    }
+    Ok(args)
+}
+
+// Creates the event on the connected calendar for its owner; everyone else's
+// events go to the bot's own calendar store.
+pub struct CreateCalendarEventTool {
+    location: String,
+    client: Option<(Arc<dyn CalendarClient>, String)>,
+}
+
+impl CreateCalendarEventTool {
+    pub fn new() -> Self {
+        Self::with_location(get_calendar_location())
+    }
+
+    pub fn with_location(location: impl Into<String>) -> Self {
+        Self {
+            location: location.into(),
+            client: None,
+        }
+    }
+
+    // `user_id` is whose calendar `client` writes to.
+    pub fn with_client(mut self, client: Arc<dyn CalendarClient>, user_id: impl Into<String>) -> Self {
+        self.client = Some((client, user_id.into()));
+        self
+    }
+}
+
+impl Default for CreateCalendarEventTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[serenity::async_trait]
+impl Tool for CreateCalendarEventTool {
+    fn name(&self) -> &str {
+        CREATE_CALENDAR_EVENT
+    }
+
+    fn description(&self) -> &str {
+        "Add an event to the user's calendar"
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "title": {"type": "string"},
+                "start": {"type": "string", "format": "date-time"},
+                "end": {"type": "string", "format": "date-time"},
+                "description": {"type": "string"},
+            },
+            "required": ["title", "start"],
+        })
+    }
+
+    fn describe(&self, arguments: &Value) -> Result<String, String> {
+        let args = parse_args(arguments)?;
+        let mut summary = format!(
+            "Add \"{}\" to your calendar at <t:{}:f>",
+            args.title.trim(),
+            args.start.timestamp()
+        );
+        if let Some(end) = args.end {
+            summary.push_str(&format!(" until <t:{}:t>", end.timestamp()));
+        }
+        Ok(summary)
+    }
+
+    async fn execute(&self, user_id: &str, arguments: &Value) -> Result<String, String> {
+        let args = parse_args(arguments)?;
+        if let Some((client, owner)) = &self.client {
+            if owner == user_id {
+                client
+                    .create_event(CalendarEvent {
+                        id: None,
+                        title: args.title.trim().to_string(),
+                        start_time: args.start,
+                        end_time: args
+                            .end
+                            .unwrap_or(args.start + Duration::minutes(DEFAULT_EVENT_MINUTES)),
+                        description: args.description.filter(|d| !d.trim().is_empty()),
+                    })
+                    .await?;
+                return Ok(format!(
+                    "Added \"{}\" to your calendar for <t:{}:f>.",
+                    args.title.trim(),
+                    args.start.timestamp()
+                ));
+            }
+        }
+        let mut db: DB<CalendarEntry> = load_db(&self.location).unwrap_or_else(|_| HashMap::new());
+        let id = Uuid::new_v4().to_string();
+        db.insert(
+            id.clone(),
+            CalendarEntry {
+                id,
+                user_id: user_id.to_string(),
+                title: args.title.trim().to_string(),
+                start: args.start,
+                end: args.end,
+                description: args.description.filter(|d| !d.trim().is_empty()),
+                created_at: Utc::now(),
+            },
+        );
+        save_db(&self.location, &db).map_err(|e| e.to_string())?;
+        // Kept with the bot: only the owner's calendar is connected.
+        Ok(format!(
+            "Saved \"{}\" for <t:{}:f>. Your calendar isn't connected to the bot, so it won't show up there.",
+            args.title.trim(),
+            args.start.timestamp()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_validates_the_arguments() {
+        let tool = CreateCalendarEventTool::with_location("unused");
+        let summary = tool
+            .describe(&json!({"title": "Dentist", "start": "2026-03-02T15:00:00Z"}))
+            .unwrap();
+        assert!(summary.starts_with("Add \"Dentist\" to your calendar"));
+        assert!(tool.describe(&json!({"title": "Dentist"})).is_err());
+        assert!(
+            tool.describe(&json!({
+                "title": "Dentist",
+                "start": "2026-03-02T15:00:00Z",
+                "end": "2026-03-02T14:00:00Z",
+            }))
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn events_without_a_connected_calendar_say_so() {
+        let location = env::temp_dir().join(format!("reminderbot_calendar_{}", Uuid::new_v4()));
+        let tool = CreateCalendarEventTool::with_location(location.to_str().unwrap());
+        let reply = tool
+            .execute("@u", &json!({"title": "Dentist", "start": "2026-03-02T15:00:00Z"}))
+            .await
+            .unwrap();
+        assert!(!reply.contains("to your calendar"));
+        assert!(reply.contains("isn't connected"));
+        let db: DB<CalendarEntry> = load_db(location.to_str().unwrap()).unwrap();
+        assert_eq!(db.len(), 1);
+    }
+}
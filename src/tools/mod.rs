@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+pub mod calendar;
+
+// Something the bot can do on a user's behalf once they approve it. The LLM
+// picks a tool from the registry's catalog and fills in arguments matching
+// its schema; nothing runs until the user confirms the description.
+#[serenity::async_trait]
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+    // JSON schema for the arguments object.
+    fn schema(&self) -> Value;
+    // What running with these arguments will do, for the approval prompt.
+    // Errors mean the arguments are unusable.
+    fn describe(&self, arguments: &Value) -> Result<String, String>;
+    // The message reported back to the user on success.
+    async fn execute(&self, user_id: &str, arguments: &Value) -> Result<String, String>;
+}
+
+// What the tool_selection prompt returns. A null tool means none fits.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolChoice {
+    #[serde(default)]
+    pub tool: Option<String>,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+#[derive(Default, Clone)]
+pub struct ToolRegistry {
+    tools: BTreeMap<String, Arc<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The tools available out of the box.
+    pub fn builtin() -> Self {
+        Self::new().with_tool(Arc::new(calendar::CreateCalendarEventTool::new()))
+    }
+
+    // A later tool with the same name replaces the earlier one.
+    pub fn with_tool(mut self, tool: Arc<dyn Tool>) -> Self {
+        self.tools.insert(tool.name().to_string(), tool);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.tools.get(name).cloned()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    // Name, description and schema of every tool, for the selection prompt.
+    pub fn catalog(&self) -> Value {
+        Value::Array(
+            self.tools
+                .values()
+                .map(|tool| {
+                    json!({
+                        "name": tool.name(),
+                        "description": tool.description(),
+                        "parameters": tool.schema(),
+                    })
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_lists_tools_by_name() {
+        let registry = ToolRegistry::builtin();
+        let catalog = registry.catalog();
+        assert_eq!(catalog[0]["name"], "create_calendar_event");
+        assert_eq!(catalog[0]["parameters"]["required"][0], "title");
+        assert!(registry.get("create_calendar_event").is_some());
+        assert!(registry.get("send_email").is_none());
+    }
+}
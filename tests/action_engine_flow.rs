@@ -2,12 +2,27 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use chrono::TimeZone;
-use reminderBot::handlers::action::{Action, ActionEngine, ActionEvent, ActionPayload, ActionStatus, ActionStore, ActionType, NotificationDraft};
+use reminderBot::handlers::action::{Action, ActionEngine, ActionEvent, ActionPayload, ActionStatus, ActionStore, ActionType, AdminCommand, NotificationDraft, render_action_history};
+use reminderBot::events::retry::EventError;
+use reminderBot::i18n::Locale;
+use reminderBot::handlers::middleware::{ActionMiddleware, Flow};
 use reminderBot::service::approval_prompt::ApprovalPromptService;
 use reminderBot::service::openai_service::OpenAIClient;
 use reminderBot::models::notification::Notification;
+use reminderBot::models::todo::TodoItem;
+use reminderBot::tasks::approval_sweep_loop::approval_sweep_tick;
+use reminderBot::tools::{Tool, ToolRegistry};
 use tokio::sync::Mutex;
 
+// Dead letters and drafts are saved under DB_LOCATION; keep them out of
+// the working tree.
+fn use_temp_db_location() {
+    let temp_dir = std::env::temp_dir().join(format!("reminderbot_action_test_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        std::env::set_var("DB_LOCATION", &temp_dir);
+    }
+}
+
 struct FakeOpenAI {
     response: Result<String, String>,
 }
@@ -64,6 +79,7 @@ async fn approval_confirmed_creates_notification() {
             text: "call mom tomorrow".to_string(),
             user_id: "@u".to_string(),
             channel_id: "123".to_string(),
+            received_at: None,
         })
         .await;
 
@@ -86,6 +102,54 @@ async fn approval_confirmed_creates_notification() {
     assert_eq!(notification.channel, "123");
 }
 
+#[tokio::test]
+async fn confirmed_drafts_ping_at_the_offsets_the_user_asked_for() {
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(
+            "{\"content\":\"dentist\",\"time\":\"2026-02-03T12:00:00Z\",\"offsets\":[30,1440],\
+             \"original_phrase\":\"tuesday at noon\"}"
+                .to_string(),
+        ),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, approval, db.clone());
+
+    engine
+        .handle_event(ActionEvent::NotifyRequested {
+            text: "dentist tuesday at noon, remind me a day and half an hour before".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await;
+
+    let action_id = {
+        let guard = store.lock().await;
+        let action_id = guard.ids().into_iter().next().expect("action exists");
+        let draft = guard.get(&action_id).and_then(|action| action.notification_draft()).unwrap();
+        assert_eq!(draft.offsets, vec![1440, 30]);
+        assert_eq!(draft.original_phrase.as_deref(), Some("tuesday at noon"));
+        action_id
+    };
+
+    engine
+        .handle_event(ActionEvent::ApprovalConfirmed {
+            action_id,
+            user_id: "@u".to_string(),
+        })
+        .await;
+
+    let db_guard = db.lock().await;
+    let notification = db_guard.values().next().expect("reminder saved");
+    let event = chrono::Utc.with_ymd_and_hms(2026, 2, 3, 12, 0, 0).unwrap();
+    assert_eq!(
+        notification.notification_times,
+        vec![event - chrono::Duration::days(1), event - chrono::Duration::minutes(30)]
+    );
+}
+
 #[tokio::test]
 async fn approval_canceled_marks_rejected() {
     let store = Arc::new(Mutex::new(ActionStore::new()));
@@ -105,6 +169,15 @@ async fn approval_canceled_marks_rejected() {
         extra_context: None,
         expires_at: chrono::Utc.with_ymd_and_hms(2026, 2, 3, 12, 5, 0).unwrap(),
         message_id: None,
+        recurrence: None,
+        day_load: 0,
+        alternatives: Vec::new(),
+        checklist: Vec::new(),
+        conflicts: Vec::new(),
+        duplicate: None,
+        time_issue: None,
+        offsets: Vec::new(),
+        original_phrase: None,
     };
 
     let action_id = "a1".to_string();
@@ -117,6 +190,7 @@ async fn approval_canceled_marks_rejected() {
         payload: Some(ActionPayload::NotificationDraft(draft)),
         created_at: chrono::Utc.with_ymd_and_hms(2026, 2, 3, 12, 0, 0).unwrap(),
         updated_at: chrono::Utc.with_ymd_and_hms(2026, 2, 3, 12, 0, 0).unwrap(),
+        history: Vec::new(),
     };
 
     {
@@ -135,3 +209,1198 @@ async fn approval_canceled_marks_rejected() {
     let updated = guard.get(&action_id).expect("action exists");
     assert_eq!(updated.status, ActionStatus::Rejected);
 }
+
+#[tokio::test]
+async fn notification_completed_clears_remaining_times() {
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(String::new()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    db.lock().await.insert(
+        "n1".to_string(),
+        Notification {
+            id: "n1".to_string(),
+            content: "pay rent".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![chrono::Utc.with_ymd_and_hms(2026, 2, 3, 11, 0, 0).unwrap()],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+    let engine = ActionEngine::new(store, openai, approval, db.clone());
+
+    engine
+        .handle_event(ActionEvent::NotificationCompleted {
+            notification_id: "n1".to_string(),
+            user_id: "@someone_else".to_string(),
+        })
+        .await;
+    assert_eq!(db.lock().await.get("n1").unwrap().notification_times.len(), 1);
+
+    engine
+        .handle_event(ActionEvent::NotificationCompleted {
+            notification_id: "n1".to_string(),
+            user_id: "@u".to_string(),
+        })
+        .await;
+
+    let guard = db.lock().await;
+    let notification = guard.get("n1").unwrap();
+    assert!(notification.notification_times.is_empty());
+    assert!(notification.completed_at.is_some());
+}
+
+#[tokio::test]
+async fn dismissing_a_shared_reminder_only_stops_it_for_the_clicker() {
+    use_temp_db_location();
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(String::new()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    db.lock().await.insert(
+        "n1".to_string(),
+        Notification {
+            id: "n1".to_string(),
+            content: "standup".to_string(),
+            notify: vec!["@u".to_string(), "@v".to_string()],
+            notification_times: vec![chrono::Utc::now() + chrono::Duration::hours(1)],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+    let engine = ActionEngine::new(store, openai, approval, db.clone());
+
+    engine
+        .handle_event(ActionEvent::NotificationDismissed {
+            notification_id: "n1".to_string(),
+            user_id: "@v".to_string(),
+        })
+        .await;
+    {
+        let guard = db.lock().await;
+        let notification = guard.get("n1").unwrap();
+        assert_eq!(notification.notify, vec!["@u".to_string()]);
+        assert_eq!(notification.notification_times.len(), 1);
+        assert!(notification.completed_at.is_none());
+    }
+
+    engine
+        .handle_event(ActionEvent::NotificationDismissed {
+            notification_id: "n1".to_string(),
+            user_id: "@u".to_string(),
+        })
+        .await;
+    let guard = db.lock().await;
+    let notification = guard.get("n1").unwrap();
+    assert!(notification.notification_times.is_empty());
+    assert!(notification.completed_at.is_some());
+}
+
+#[tokio::test]
+async fn skip_next_occurrence_moves_recurring_reminder_forward() {
+    use reminderBot::models::recurrence::{Frequency, Recurrence};
+
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(String::new()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let event_time = chrono::Utc::now() + chrono::Duration::days(3);
+    db.lock().await.insert(
+        "n1".to_string(),
+        Notification {
+            id: "n1".to_string(),
+            content: "team standup".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![event_time - chrono::Duration::hours(1)],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: Some(event_time),
+            recurrence: Some(Recurrence::new(Frequency::Weekly)),
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+    let engine = ActionEngine::new(store, openai, approval, db.clone());
+
+    engine
+        .handle_event(ActionEvent::SkipNextOccurrence {
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            query: "standup".to_string(),
+        })
+        .await;
+
+    let guard = db.lock().await;
+    let notification = guard.get("n1").unwrap();
+    let next = notification.event_time.unwrap();
+    assert!((167..=169).contains(&(next - event_time).num_hours()));
+    assert_eq!(notification.recurrence.as_ref().unwrap().skip_dates.len(), 1);
+    assert!(notification.notification_times.iter().all(|t| *t > event_time));
+}
+
+// Keeps the status messages the engine posts.
+#[derive(Default)]
+struct RecordingStatus {
+    messages: std::sync::Mutex<Vec<String>>,
+}
+
+#[serenity::async_trait]
+impl ApprovalPromptService for RecordingStatus {
+    async fn prompt(&self, _action: &mut Action) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status(&self, _action: &Action, _message: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status_message(
+        &self,
+        _channel_id: &str,
+        _user_id: &str,
+        message: &str,
+    ) -> Result<(), String> {
+        self.messages.lock().unwrap().push(message.to_string());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn skip_replies_come_in_the_users_language() {
+    use reminderBot::models::settings::UserSettings;
+
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(String::new()),
+    });
+    let approval = Arc::new(RecordingStatus::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let user_settings = Arc::new(Mutex::new(HashMap::from([(
+        "@u".to_string(),
+        UserSettings {
+            user_id: "@u".to_string(),
+            locale: Some("es".to_string()),
+            ..Default::default()
+        },
+    )])));
+    let engine = ActionEngine::new(store, openai, approval.clone(), db)
+        .with_user_settings(user_settings);
+
+    engine
+        .handle_event(ActionEvent::SkipNextOccurrence {
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            query: "standup".to_string(),
+        })
+        .await;
+    assert_eq!(
+        *approval.messages.lock().unwrap(),
+        vec!["No encontré ningún recordatorio recurrente para omitir.".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn canceling_a_shared_reminder_only_stops_it_for_the_caller() {
+    use reminderBot::models::settings::UserSettings;
+
+    use_temp_db_location();
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(String::new()),
+    });
+    let approval = Arc::new(RecordingStatus::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    db.lock().await.insert(
+        "n1".to_string(),
+        Notification {
+            id: "n1".to_string(),
+            content: "standup".to_string(),
+            notify: vec!["@u".to_string(), "@v".to_string()],
+            notification_times: vec![chrono::Utc::now() + chrono::Duration::hours(1)],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+    let user_settings = Arc::new(Mutex::new(HashMap::from([(
+        "@v".to_string(),
+        UserSettings {
+            user_id: "@v".to_string(),
+            locale: Some("es".to_string()),
+            ..Default::default()
+        },
+    )])));
+    let engine = ActionEngine::new(store, openai, approval.clone(), db.clone())
+        .with_user_settings(user_settings);
+
+    engine
+        .handle_event(ActionEvent::CancelReminder {
+            user_id: "@v".to_string(),
+            channel_id: "123".to_string(),
+            query: "standup".to_string(),
+            reminder_id: None,
+        })
+        .await;
+    let guard = db.lock().await;
+    let notification = guard.get("n1").expect("still there for @u");
+    assert_eq!(notification.notify, vec!["@u".to_string()]);
+    assert_eq!(notification.notification_times.len(), 1);
+    assert_eq!(
+        *approval.messages.lock().unwrap(),
+        vec!["Ya no recibirás \"standup\"; los demás sí.".to_string()]
+    );
+}
+
+fn draft_action(id: &str, status: ActionStatus) -> Action {
+    let created = chrono::Utc.with_ymd_and_hms(2026, 2, 3, 12, 0, 0).unwrap();
+    Action {
+        id: id.to_string(),
+        action_type: ActionType::CreateNotification,
+        status,
+        user_id: "@u".to_string(),
+        channel_id: "123".to_string(),
+        payload: Some(ActionPayload::NotificationDraft(NotificationDraft {
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            content: "call mom".to_string(),
+            time: created,
+            original_text: "call mom".to_string(),
+            extra_context: None,
+            expires_at: created + chrono::Duration::minutes(5),
+            message_id: None,
+            recurrence: None,
+            day_load: 0,
+            alternatives: Vec::new(),
+            checklist: Vec::new(),
+            conflicts: Vec::new(),
+            duplicate: None,
+            time_issue: None,
+            offsets: Vec::new(),
+            original_phrase: None,
+        })),
+        created_at: created,
+        updated_at: created,
+        history: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn admin_purge_and_requeue_clean_up_actions() {
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    {
+        let mut guard = store.lock().await;
+        guard.insert(draft_action("done", ActionStatus::Completed));
+        guard.insert(draft_action("stale", ActionStatus::AwaitingApproval));
+        guard.insert(draft_action("broken", ActionStatus::Failed));
+    }
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(String::new()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    db.lock().await.insert(
+        "spent".to_string(),
+        Notification {
+            id: "spent".to_string(),
+            content: "pay rent".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: Vec::new(),
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+    let dm = Arc::new(RecordingDm::default());
+    let engine = ActionEngine::new(store.clone(), openai, approval, db.clone()).with_dm_sender(dm.clone());
+
+    engine
+        .handle_event(ActionEvent::AdminRequested {
+            user_id: "@admin".to_string(),
+            channel_id: "123".to_string(),
+            command: AdminCommand::PurgeExpired,
+        })
+        .await;
+    {
+        let guard = store.lock().await;
+        let mut ids = guard.ids();
+        ids.sort();
+        assert_eq!(ids, vec!["broken".to_string()]);
+    }
+    assert!(db.lock().await.is_empty());
+    assert_eq!(
+        *dm.sent.lock().unwrap(),
+        vec![(
+            "admin".to_string(),
+            "Purged 2 expired actions and 1 finished notifications.".to_string()
+        )]
+    );
+
+    engine
+        .handle_event(ActionEvent::AdminRequested {
+            user_id: "@admin".to_string(),
+            channel_id: "123".to_string(),
+            command: AdminCommand::RequeueFailed,
+        })
+        .await;
+    let guard = store.lock().await;
+    let requeued = guard.get("broken").expect("action kept");
+    assert_eq!(requeued.status, ActionStatus::AwaitingApproval);
+    assert!(requeued.notification_draft().unwrap().expires_at > chrono::Utc::now());
+}
+
+// Refuses to post prompts while `down` is set, as Discord does during an
+// outage.
+#[derive(Default)]
+struct FlakyApprovalPrompt {
+    down: std::sync::atomic::AtomicBool,
+}
+
+#[serenity::async_trait]
+impl ApprovalPromptService for FlakyApprovalPrompt {
+    async fn prompt(&self, _action: &mut Action) -> Result<(), String> {
+        if self.down.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err("discord unavailable".to_string());
+        }
+        Ok(())
+    }
+
+    async fn update_status(&self, _action: &Action, _message: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status_message(
+        &self,
+        _channel_id: &str,
+        _user_id: &str,
+        _message: &str,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn a_draft_whose_prompt_fails_is_kept_for_requeue() {
+    use_temp_db_location();
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok("{\"content\":\"call mom\",\"time\":\"2030-02-03T12:00:00Z\"}".to_string()),
+    });
+    let approval = Arc::new(FlakyApprovalPrompt::default());
+    approval.down.store(true, std::sync::atomic::Ordering::SeqCst);
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, approval.clone(), db);
+
+    engine
+        .handle_event(ActionEvent::NotifyRequested {
+            text: "call mom in 2030".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await;
+    let id = {
+        let guard = store.lock().await;
+        let ids = guard.ids();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(guard.get(&ids[0]).unwrap().status, ActionStatus::Failed);
+        ids[0].clone()
+    };
+
+    approval.down.store(false, std::sync::atomic::Ordering::SeqCst);
+    engine
+        .handle_event(ActionEvent::AdminRequested {
+            user_id: "@admin".to_string(),
+            channel_id: "123".to_string(),
+            command: AdminCommand::RequeueFailed,
+        })
+        .await;
+    let guard = store.lock().await;
+    assert_eq!(guard.ids().len(), 1);
+    assert_eq!(guard.get(&id).unwrap().status, ActionStatus::AwaitingApproval);
+}
+
+// Fails the first `failures` calls, then returns a valid draft.
+struct FlakyOpenAI {
+    failures: usize,
+    calls: std::sync::atomic::AtomicUsize,
+}
+
+#[serenity::async_trait]
+impl OpenAIClient for FlakyOpenAI {
+    async fn generate_prompt(
+        &self,
+        _prompt: &str,
+        _prompt_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if call < self.failures {
+            return Err("rate limited".into());
+        }
+        Ok("{\"content\":\"call mom\",\"time\":\"2026-02-03T12:00:00Z\"}".to_string())
+    }
+}
+
+#[tokio::test]
+async fn failing_notify_is_dead_lettered_and_retried() {
+    use reminderBot::events::dead_letter::DeadLetterStore;
+    use reminderBot::events::retry::RetryPolicy;
+
+    use_temp_db_location();
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let dead_letters = Arc::new(Mutex::new(DeadLetterStore::new()));
+    let openai = Arc::new(FlakyOpenAI {
+        failures: 3,
+        calls: std::sync::atomic::AtomicUsize::new(0),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai.clone(), approval, db)
+        .with_dead_letters(dead_letters.clone())
+        .with_retry_policy(RetryPolicy {
+            max_attempts: 3,
+            base_delay: std::time::Duration::ZERO,
+            max_delay: std::time::Duration::ZERO,
+        });
+
+    engine
+        .handle_event(ActionEvent::NotifyRequested {
+            text: "call mom tomorrow at noon".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await;
+    assert!(store.lock().await.ids().is_empty());
+    {
+        let guard = dead_letters.lock().await;
+        let letters = guard.list();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].attempts, 3);
+        assert!(letters[0].error.contains("rate limited"));
+    }
+
+    engine
+        .handle_event(ActionEvent::AdminRequested {
+            user_id: "@admin".to_string(),
+            channel_id: "123".to_string(),
+            command: AdminCommand::DeadLetterRetry { id: None },
+        })
+        .await;
+    assert!(dead_letters.lock().await.is_empty());
+    let guard = store.lock().await;
+    assert_eq!(guard.ids().len(), 1);
+    assert_eq!(openai.calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn busy_day_offers_lighter_days_and_reschedules() {
+    let day = (chrono::Utc::now() + chrono::Duration::days(30))
+        .date_naive()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+        .and_utc();
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    {
+        let mut guard = db.lock().await;
+        for i in 0..6 {
+            let id = format!("busy{}", i);
+            let at = day + chrono::Duration::minutes(i);
+            guard.insert(
+                id.clone(),
+                Notification {
+                    id,
+                    content: "meeting".to_string(),
+                    notify: vec!["@u".to_string()],
+                    notification_times: vec![at - chrono::Duration::hours(1)],
+                    channel: "123".to_string(),
+                    completed_at: None,
+                    event_time: Some(at),
+                    recurrence: None,
+                    paused: false,
+                    checklist: Vec::new(),
+                    ack: None,
+                    quiet_delayed: false,
+                    targets: Vec::new(),
+                    delivery_retry: None,
+                },
+            );
+        }
+    }
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(format!(
+            "{{\"content\":\"call mom\",\"time\":\"{}\"}}",
+            day.to_rfc3339()
+        )),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let engine = ActionEngine::new(store.clone(), openai, approval, db);
+
+    engine
+        .handle_event(ActionEvent::NotifyRequested {
+            text: "call mom".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await;
+    let (action_id, alternative) = {
+        let guard = store.lock().await;
+        let action_id = guard.ids().pop().expect("draft stored");
+        let draft = guard.get(&action_id).unwrap().notification_draft().unwrap().clone();
+        assert_eq!(draft.day_load, 6);
+        assert_eq!(draft.alternatives.len(), 3);
+        (action_id, draft.alternatives[0])
+    };
+
+    engine
+        .handle_event(ActionEvent::DraftRescheduled {
+            action_id: action_id.clone(),
+            user_id: "@u".to_string(),
+            time: alternative,
+        })
+        .await;
+    let guard = store.lock().await;
+    let draft = guard.get(&action_id).unwrap().notification_draft().unwrap();
+    assert_eq!(draft.time, alternative);
+    assert_eq!(draft.day_load, 0);
+    assert!(draft.alternatives.is_empty());
+}
+
+#[tokio::test]
+async fn unparseable_draft_is_dead_lettered_without_retrying() {
+    use reminderBot::events::dead_letter::DeadLetterStore;
+
+    use_temp_db_location();
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let dead_letters = Arc::new(Mutex::new(DeadLetterStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok("not json".to_string()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, approval, db)
+        .with_dead_letters(dead_letters.clone());
+
+    engine
+        .handle_event(ActionEvent::NotifyRequested {
+            text: "call mom".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await;
+    let guard = dead_letters.lock().await;
+    let letters = guard.list();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].attempts, 1);
+    assert!(letters[0].error.starts_with("Failed to parse notification JSON"));
+}
+
+#[tokio::test]
+async fn todo_request_is_drafted_then_saved_on_confirm() {
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok("{\"items\":[\"buy milk\",\"call the plumber\"]}".to_string()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, approval, db.clone())
+        .with_todos(todo_db.clone());
+
+    engine
+        .handle_event(ActionEvent::TodoRequested {
+            text: "buy milk and call the plumber".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+        })
+        .await;
+
+    let action_id = {
+        let guard = store.lock().await;
+        let action = guard.values().next().expect("draft stored");
+        assert_eq!(action.action_type, ActionType::CreateTodo);
+        assert_eq!(action.status, ActionStatus::AwaitingApproval);
+        assert_eq!(action.todo_draft().unwrap().items, vec!["buy milk", "call the plumber"]);
+        action.id.clone()
+    };
+    assert!(todo_db.lock().await.is_empty(), "nothing saved before confirming");
+
+    engine
+        .handle_event(ActionEvent::ApprovalConfirmed {
+            action_id: action_id.clone(),
+            user_id: "@u".to_string(),
+        })
+        .await;
+
+    let mut contents: Vec<String> = todo_db
+        .lock()
+        .await
+        .values()
+        .map(|item| item.content.clone())
+        .collect();
+    contents.sort();
+    assert_eq!(contents, vec!["buy milk", "call the plumber"]);
+    assert_eq!(store.lock().await.get(&action_id).unwrap().status, ActionStatus::Completed);
+    assert!(db.lock().await.is_empty());
+}
+
+#[derive(Default)]
+struct RecordingTool {
+    calls: std::sync::Mutex<Vec<(String, serde_json::Value)>>,
+}
+
+#[serenity::async_trait]
+impl Tool for RecordingTool {
+    fn name(&self) -> &str {
+        "echo"
+    }
+
+    fn description(&self) -> &str {
+        "Repeat some text back"
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        serde_json::json!({"type": "object", "properties": {"text": {"type": "string"}}})
+    }
+
+    fn describe(&self, arguments: &serde_json::Value) -> Result<String, String> {
+        arguments["text"]
+            .as_str()
+            .map(|text| format!("Echo \"{}\"", text))
+            .ok_or_else(|| "text is required".to_string())
+    }
+
+    async fn execute(&self, user_id: &str, arguments: &serde_json::Value) -> Result<String, String> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((user_id.to_string(), arguments.clone()));
+        Ok("echoed".to_string())
+    }
+}
+
+#[tokio::test]
+async fn tool_request_runs_only_after_approval() {
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok("{\"tool\":\"echo\",\"arguments\":{\"text\":\"hi\"}}".to_string()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let tool = Arc::new(RecordingTool::default());
+    let engine = ActionEngine::new(store.clone(), openai, approval, db)
+        .with_tools(ToolRegistry::new().with_tool(tool.clone()));
+
+    engine
+        .handle_event(ActionEvent::ToolRequested {
+            text: "say hi".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+        })
+        .await;
+
+    let action_id = {
+        let guard = store.lock().await;
+        let action = guard.values().next().expect("draft stored");
+        assert_eq!(action.action_type, ActionType::ToolUse);
+        assert_eq!(action.tool_draft().unwrap().summary, "Echo \"hi\"");
+        action.id.clone()
+    };
+    assert!(tool.calls.lock().unwrap().is_empty());
+
+    engine
+        .handle_event(ActionEvent::ApprovalConfirmed {
+            action_id: action_id.clone(),
+            user_id: "@u".to_string(),
+        })
+        .await;
+
+    let calls = tool.calls.lock().unwrap().clone();
+    assert_eq!(calls, vec![("@u".to_string(), serde_json::json!({"text": "hi"}))]);
+    assert_eq!(store.lock().await.get(&action_id).unwrap().status, ActionStatus::Completed);
+}
+
+#[derive(Default)]
+struct RecordingExpiry {
+    expired: std::sync::Mutex<Vec<String>>,
+}
+
+#[serenity::async_trait]
+impl ApprovalPromptService for RecordingExpiry {
+    async fn prompt(&self, _action: &mut Action) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status(&self, _action: &Action, _message: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status_message(
+        &self,
+        _channel_id: &str,
+        _user_id: &str,
+        _message: &str,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn expire(&self, action: &Action) -> Result<(), String> {
+        self.expired.lock().unwrap().push(action.id.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn sweeper_expires_lapsed_approval_prompts() {
+    let store = Mutex::new(ActionStore::new());
+    {
+        let mut guard = store.lock().await;
+        guard.insert(draft_action("lapsed", ActionStatus::AwaitingApproval));
+        guard.insert(draft_action("answered", ActionStatus::Completed));
+    }
+    let approval = RecordingExpiry::default();
+    // draft_action's prompt lapses at 12:05.
+    let before = chrono::Utc.with_ymd_and_hms(2026, 2, 3, 12, 4, 0).unwrap();
+    let after = chrono::Utc.with_ymd_and_hms(2026, 2, 3, 12, 6, 0).unwrap();
+
+    assert_eq!(approval_sweep_tick(&store, &approval, before).await, 0);
+    assert_eq!(approval_sweep_tick(&store, &approval, after).await, 1);
+    assert_eq!(approval_sweep_tick(&store, &approval, after).await, 0);
+
+    let guard = store.lock().await;
+    assert_eq!(guard.get("lapsed").unwrap().status, ActionStatus::Expired);
+    assert_eq!(guard.get("answered").unwrap().status, ActionStatus::Completed);
+    assert_eq!(*approval.expired.lock().unwrap(), vec!["lapsed".to_string()]);
+}
+
+#[tokio::test]
+async fn posted_prompts_schedule_their_own_expiry() {
+    let (bus, mut rx) = reminderBot::events::queue::EventBus::new(8);
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok("{\"content\":\"call mom\",\"time\":\"2026-02-03T12:00:00Z\"}".to_string()),
+    });
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, Arc::new(FakeApprovalPrompt), db)
+        .with_event_bus(bus);
+
+    engine
+        .handle_event(ActionEvent::NotifyRequested {
+            text: "call mom tomorrow".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await;
+    let action = store.lock().await.values().next().cloned().expect("draft exists");
+    match rx.try_recv().expect("expiry should be scheduled") {
+        ActionEvent::Scheduled { at, event } => {
+            assert_eq!(Some(at), action.draft_expires_at());
+            assert!(matches!(*event, ActionEvent::ApprovalExpired { ref action_id, .. } if *action_id == action.id));
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    // The timer fires for a draft that was since given more time.
+    let mut extended = draft_action("extended", ActionStatus::AwaitingApproval);
+    extended.set_draft_expires_at(chrono::Utc::now() + chrono::Duration::minutes(5));
+    store.lock().await.insert(draft_action("lapsed", ActionStatus::AwaitingApproval));
+    store.lock().await.insert(extended);
+    for id in ["lapsed", "extended"] {
+        engine
+            .handle_event(ActionEvent::ApprovalExpired {
+                action_id: id.to_string(),
+                user_id: "@u".to_string(),
+            })
+            .await;
+    }
+    let guard = store.lock().await;
+    assert_eq!(guard.get("lapsed").unwrap().status, ActionStatus::Expired);
+    assert_eq!(guard.get("extended").unwrap().status, ActionStatus::AwaitingApproval);
+}
+
+#[tokio::test]
+async fn a_full_queue_does_not_stall_a_prompt() {
+    use reminderBot::events::queue::{EventBus, OverflowPolicy};
+
+    let policy = OverflowPolicy::Block {
+        timeout: std::time::Duration::from_secs(30),
+    };
+    let (bus, _rx) = EventBus::with_overflow(1, policy);
+    bus.emit(ActionEvent::ListRequested {
+        user_id: "@other".to_string(),
+        channel_id: "123".to_string(),
+    })
+    .await
+    .expect("first event should queue");
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok("{\"content\":\"call mom\",\"time\":\"2026-02-03T12:00:00Z\"}".to_string()),
+    });
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, Arc::new(FakeApprovalPrompt), db)
+        .with_event_bus(bus.clone());
+
+    tokio::time::timeout(
+        std::time::Duration::from_secs(2),
+        engine.handle_event(ActionEvent::NotifyRequested {
+            text: "call mom tomorrow".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        }),
+    )
+    .await
+    .expect("prompting should not wait for room in the queue");
+
+    let action = store.lock().await.values().next().cloned().expect("draft exists");
+    assert_eq!(action.status, ActionStatus::AwaitingApproval);
+    assert_eq!(bus.metrics().rejected, 1);
+}
+
+#[tokio::test]
+async fn history_lists_a_users_actions_newest_first() {
+    let mut store = ActionStore::new();
+    let mut older = draft_action("older", ActionStatus::Completed);
+    older.created_at = older.created_at - chrono::Duration::days(3);
+    store.insert(older);
+    store.insert(draft_action("newer", ActionStatus::Rejected));
+    let mut other = draft_action("other", ActionStatus::Completed);
+    other.user_id = "@someone".to_string();
+    store.insert(other);
+
+    let ids = |actions: Vec<&Action>| actions.iter().map(|a| a.id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(store.query("@u", None, None)), vec!["newer", "older"]);
+    assert_eq!(ids(store.query("@u", Some(ActionStatus::Completed), None)), vec!["older"]);
+    let since = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 0, 0, 0).unwrap();
+    assert_eq!(ids(store.query("@u", None, Some(since))), vec!["newer"]);
+
+    let zone = chrono_tz::America::New_York;
+    let rendered = render_action_history(&store.query("@u", None, None), zone, Locale::En);
+    assert!(rendered.contains("Reminder: call mom [rejected]"));
+    assert_eq!(render_action_history(&[], zone, Locale::En), "No matching actions.");
+    assert_eq!(render_action_history(&[], zone, Locale::De), "Keine passenden Aktionen.");
+    assert_eq!(ActionStatus::parse("Awaiting_Approval"), Some(ActionStatus::AwaitingApproval));
+}
+
+#[derive(Default)]
+struct RecordingDm {
+    sent: std::sync::Mutex<Vec<(String, String)>>,
+}
+
+#[serenity::async_trait]
+impl reminderBot::tasks::todo_loop::DmSender for RecordingDm {
+    async fn send_dm(&self, user_id: &str, content: &str) -> Result<(), String> {
+        self.sent.lock().unwrap().push((user_id.to_string(), content.to_string()));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn history_is_sent_by_dm() {
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    store.lock().await.insert(draft_action("a1", ActionStatus::Completed));
+    let dm = Arc::new(RecordingDm::default());
+    let engine = ActionEngine::new(
+        store,
+        Arc::new(FakeOpenAI { response: Err("unused".to_string()) }),
+        Arc::new(FakeApprovalPrompt),
+        Arc::new(Mutex::new(HashMap::new())),
+    )
+    .with_dm_sender(dm.clone());
+
+    engine
+        .handle_event(ActionEvent::HistoryRequested {
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            status: None,
+            since: None,
+        })
+        .await;
+
+    let sent = dm.sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0, "u");
+    assert!(sent[0].1.contains("call mom [completed]"));
+}
+
+struct RecordingMiddleware {
+    seen: Arc<std::sync::Mutex<Vec<String>>>,
+    label: &'static str,
+    block_user: Option<&'static str>,
+}
+
+#[serenity::async_trait]
+impl ActionMiddleware for RecordingMiddleware {
+    fn name(&self) -> &str {
+        self.label
+    }
+
+    async fn before_handle(&self, event: &mut ActionEvent) -> Flow {
+        self.seen
+            .lock()
+            .unwrap()
+            .push(format!("{} before {}", self.label, event.ids().name));
+        match self.block_user {
+            Some(user) if event.user_id() == user => Flow::Stop("blocked".to_string()),
+            _ => Flow::Continue,
+        }
+    }
+
+    async fn after_handle(&self, event: &ActionEvent, result: &Result<(), EventError>) {
+        self.seen.lock().unwrap().push(format!(
+            "{} after {} ok={}",
+            self.label,
+            event.ids().name,
+            result.is_ok()
+        ));
+    }
+}
+
+#[tokio::test]
+async fn middleware_wraps_events_and_can_stop_them() {
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(String::new()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store, openai, approval, db)
+        .with_middleware(Arc::new(RecordingMiddleware {
+            seen: seen.clone(),
+            label: "outer",
+            block_user: None,
+        }))
+        .with_middleware(Arc::new(RecordingMiddleware {
+            seen: seen.clone(),
+            label: "gate",
+            block_user: Some("@blocked"),
+        }));
+    let list = |user: &str| ActionEvent::ListRequested {
+        user_id: user.to_string(),
+        channel_id: "123".to_string(),
+    };
+
+    assert!(engine.try_handle_event(list("@u")).await.is_ok());
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            "outer before list_requested",
+            "gate before list_requested",
+            "gate after list_requested ok=true",
+            "outer after list_requested ok=true",
+        ]
+    );
+
+    seen.lock().unwrap().clear();
+    assert!(engine.try_handle_event(list("@blocked")).await.is_ok());
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec!["outer before list_requested", "gate before list_requested"]
+    );
+}
+
+#[tokio::test]
+async fn answered_prompts_reject_further_transitions() {
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    store
+        .lock()
+        .await
+        .insert(draft_action("a1", ActionStatus::AwaitingApproval));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(String::new()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, approval, db.clone());
+
+    engine
+        .handle_event(ActionEvent::ApprovalCanceled {
+            action_id: "a1".to_string(),
+            user_id: "@u".to_string(),
+        })
+        .await;
+    engine
+        .handle_event(ActionEvent::ApprovalConfirmed {
+            action_id: "a1".to_string(),
+            user_id: "@u".to_string(),
+        })
+        .await;
+
+    assert!(db.lock().await.is_empty(), "a canceled draft is never saved");
+    let mut action = store.lock().await.get("a1").cloned().expect("action exists");
+    assert_eq!(action.status, ActionStatus::Rejected);
+    let moves: Vec<_> = action.history.iter().map(|change| (change.from, change.to)).collect();
+    assert_eq!(moves, vec![(ActionStatus::AwaitingApproval, ActionStatus::Rejected)]);
+
+    assert!(action.transition(ActionStatus::AwaitingApproval, chrono::Utc::now()).is_err());
+    assert_eq!(action.status, ActionStatus::Rejected);
+    assert_eq!(action.history.len(), 1);
+}
+
+#[tokio::test]
+async fn repeated_notify_requests_draft_one_reminder() {
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok("{\"content\":\"call mom\",\"time\":\"2030-02-03T12:00:00Z\"}".to_string()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, approval, db);
+    let now = chrono::Utc::now();
+    let request = |text: &str, seconds: i64| ActionEvent::NotifyRequested {
+        text: text.to_string(),
+        user_id: "@u".to_string(),
+        channel_id: "123".to_string(),
+        received_at: Some(now + chrono::Duration::seconds(seconds)),
+    };
+
+    // A retried interaction, then a double click.
+    engine.handle_event(request("call mom tomorrow", 0)).await;
+    engine.handle_event(request("call mom tomorrow", 0)).await;
+    engine.handle_event(request("Call mom  tomorrow", 5)).await;
+    assert_eq!(store.lock().await.ids().len(), 1);
+
+    engine.handle_event(request("call dad tomorrow", 5)).await;
+    engine.handle_event(request("call mom tomorrow", 45)).await;
+    assert_eq!(store.lock().await.ids().len(), 3);
+}
+
+#[tokio::test]
+async fn a_likely_repeat_is_flagged_and_can_be_merged() {
+    use reminderBot::service::notification_service::{pending_components, render_pending_message};
+
+    let existing_at = chrono::Utc.with_ymd_and_hms(2030, 2, 1, 9, 0, 0).unwrap();
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok("{\"content\":\"pay the rent\",\"time\":\"2030-02-02T12:00:00Z\"}".to_string()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    db.lock().await.insert(
+        "n1".to_string(),
+        Notification {
+            id: "n1".to_string(),
+            content: "Pay rent".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![existing_at - chrono::Duration::hours(1)],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: Some(existing_at),
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+    let engine = ActionEngine::new(store.clone(), openai, approval, db.clone());
+
+    engine
+        .handle_event(ActionEvent::NotifyRequested {
+            text: "remind me to pay the rent feb 2 at noon".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await;
+    let action_id = store.lock().await.ids().pop().expect("draft stored");
+    {
+        let guard = store.lock().await;
+        let draft = guard.get(&action_id).unwrap().notification_draft().unwrap();
+        let duplicate = draft.duplicate.as_ref().expect("flagged as a repeat");
+        assert_eq!((duplicate.notification_id.as_str(), duplicate.time), ("n1", existing_at));
+        assert!(render_pending_message(draft, Locale::En, chrono_tz::UTC).contains("You already have 'Pay rent' on Feb 1 — create anyway?"));
+        let rows = format!("{:?}", pending_components(&action_id, draft, Locale::En, chrono_tz::UTC));
+        assert!(rows.contains(&format!("action_merge:{}", action_id)));
+        assert!(rows.contains(&format!("action_skip:{}", action_id)));
+    }
+
+    engine
+        .handle_event(ActionEvent::DuplicateMerged {
+            action_id: action_id.clone(),
+            user_id: "@u".to_string(),
+        })
+        .await;
+    assert_eq!(store.lock().await.get(&action_id).unwrap().status, ActionStatus::Completed);
+    let guard = db.lock().await;
+    assert_eq!(guard.len(), 1);
+    let merged = guard.get("n1").unwrap();
+    assert_eq!(merged.content, "Pay rent");
+    assert_eq!(merged.event_time, Some(chrono::Utc.with_ymd_and_hms(2030, 2, 2, 12, 0, 0).unwrap()));
+}
+
+#[tokio::test]
+async fn a_time_already_gone_is_flagged_on_the_draft() {
+    use reminderBot::service::notification_service::render_pending_message;
+    use reminderBot::service::time_sanity::TimeIssue;
+
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok("{\"content\":\"call mom\",\"time\":\"2020-02-03T12:00:00Z\"}".to_string()),
+    });
+    let approval = Arc::new(FakeApprovalPrompt::default());
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, approval, db);
+
+    engine
+        .handle_event(ActionEvent::NotifyRequested {
+            text: "call mom on feb 3rd".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await;
+    let action_id = store.lock().await.ids().pop().expect("draft stored");
+    let guard = store.lock().await;
+    let action = guard.get(&action_id).unwrap();
+    assert_eq!(action.status, ActionStatus::AwaitingApproval);
+    let draft = action.notification_draft().unwrap();
+    assert_eq!(draft.time_issue, Some(TimeIssue::InPast));
+    assert!(render_pending_message(draft, Locale::En, chrono_tz::UTC).contains("That time has already passed. Confirm it if that's right"));
+}
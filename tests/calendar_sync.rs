@@ -0,0 +1,603 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::America::New_York;
+use reminderBot::clients::caldav::parse_multistatus;
+use reminderBot::clients::google_calendar::parse_events;
+use reminderBot::clients::outlook_calendar::parse_graph_events;
+use reminderBot::handlers::action::{Action, ActionEngine, ActionEvent, ActionStatus, ActionStore, ActionType};
+use reminderBot::models::notification::Notification;
+use reminderBot::service::ics::ImportedEvent;
+use reminderBot::service::approval_prompt::ApprovalPromptService;
+use reminderBot::service::notification_service::render_pending_message;
+use reminderBot::service::openai_service::OpenAIClient;
+use reminderBot::tasks::calendar_loop::{
+    CalendarClient, CalendarEvent, SyncMode, SyncOptions, SyncOutcome, SyncedEvent, calendar_sync_tick,
+    calendar_sync_tick_with,
+};
+use reminderBot::tools::ToolRegistry;
+use reminderBot::tools::calendar::CreateCalendarEventTool;
+use serde_json::json;
+use tokio::sync::Mutex;
+
+#[derive(Default)]
+struct FakeCalendar {
+    events: StdMutex<Vec<CalendarEvent>>,
+}
+
+impl FakeCalendar {
+    fn set(&self, events: Vec<CalendarEvent>) {
+        *self.events.lock().unwrap() = events;
+    }
+}
+
+#[serenity::async_trait]
+impl CalendarClient for FakeCalendar {
+    async fn get_events_for_day(&self, day: NaiveDate) -> Result<Vec<CalendarEvent>, String> {
+        Ok(self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.start_time.date_naive() == day)
+            .cloned()
+            .collect())
+    }
+
+    async fn create_event(&self, event: CalendarEvent) -> Result<(), String> {
+        self.events.lock().unwrap().push(event);
+        Ok(())
+    }
+}
+
+fn event(id: &str, start: DateTime<Utc>) -> CalendarEvent {
+    CalendarEvent {
+        id: Some(id.to_string()),
+        title: id.to_string(),
+        start_time: start,
+        end_time: start + Duration::hours(1),
+        description: None,
+    }
+}
+
+#[tokio::test]
+async fn calendar_events_are_mirrored_into_reminders() {
+    let now = Utc.with_ymd_and_hms(2026, 2, 10, 9, 0, 0).unwrap();
+    let calendar = FakeCalendar::default();
+    let db = Mutex::new(HashMap::<String, Notification>::new());
+    let mut synced: HashMap<String, SyncedEvent> = HashMap::new();
+
+    calendar.set(vec![
+        event("dentist", now + Duration::hours(5)),
+        event("standup", now + Duration::days(1)),
+        event("breakfast", now - Duration::hours(1)),
+    ]);
+    let outcome = calendar_sync_tick(&calendar, &db, &mut synced, "@u", "123", now)
+        .await
+        .unwrap();
+    assert_eq!(
+        outcome,
+        SyncOutcome {
+            created: 2,
+            moved: 0,
+            removed: 0
+        }
+    );
+    {
+        let db = db.lock().await;
+        assert_eq!(db.len(), 2);
+        let dentist = &db[&synced["dentist"].notification_id];
+        assert_eq!(dentist.notify, vec!["@u".to_string()]);
+        assert_eq!(dentist.event_time, Some(now + Duration::hours(5)));
+    }
+
+    let unchanged = calendar_sync_tick(&calendar, &db, &mut synced, "@u", "123", now)
+        .await
+        .unwrap();
+    assert_eq!(unchanged, SyncOutcome::default());
+
+    calendar.set(vec![event("dentist", now + Duration::hours(7))]);
+    let outcome = calendar_sync_tick(&calendar, &db, &mut synced, "@u", "123", now)
+        .await
+        .unwrap();
+    assert_eq!(
+        outcome,
+        SyncOutcome {
+            created: 0,
+            moved: 1,
+            removed: 1
+        }
+    );
+    let db = db.lock().await;
+    assert_eq!(db.len(), 1);
+    assert_eq!(
+        db[&synced["dentist"].notification_id].event_time,
+        Some(now + Duration::hours(7))
+    );
+    assert!(!synced.contains_key("standup"));
+}
+
+#[tokio::test]
+async fn lead_time_sync_pings_before_matching_meetings_once() {
+    let now = Utc.with_ymd_and_hms(2026, 2, 10, 9, 0, 0).unwrap();
+    let calendar = FakeCalendar::default();
+    let db = Mutex::new(HashMap::<String, Notification>::new());
+    let mut synced: HashMap<String, SyncedEvent> = HashMap::new();
+    let options = SyncOptions {
+        mode: SyncMode::LeadTime { minutes: 60 },
+        include: vec!["meeting".to_string(), "sync".to_string()],
+        exclude: vec!["optional".to_string()],
+    };
+
+    calendar.set(vec![
+        CalendarEvent {
+            title: "Design meeting".to_string(),
+            ..event("design", now + Duration::hours(3))
+        },
+        CalendarEvent {
+            title: "Team sync".to_string(),
+            ..event("team", now + Duration::minutes(30))
+        },
+        CalendarEvent {
+            title: "Optional meeting".to_string(),
+            ..event("optional", now + Duration::hours(4))
+        },
+        event("lunch", now + Duration::hours(2)),
+    ]);
+    let outcome = calendar_sync_tick_with(&calendar, &db, &mut synced, "@u", "123", &options, now)
+        .await
+        .unwrap();
+    assert_eq!(outcome.created, 2);
+    {
+        let db = db.lock().await;
+        assert_eq!(db.len(), 2);
+        let design = &db[&synced["design"].notification_id];
+        assert_eq!(design.notification_times, vec![now + Duration::hours(2)]);
+        assert_eq!(design.event_time, Some(now + Duration::hours(3)));
+        // Already inside the lead time: reminded straight away.
+        let team = &db[&synced["team"].notification_id];
+        assert_eq!(team.notification_times, vec![now]);
+    }
+
+    let again = calendar_sync_tick_with(&calendar, &db, &mut synced, "@u", "123", &options, now)
+        .await
+        .unwrap();
+    assert_eq!(again, SyncOutcome::default());
+    assert_eq!(db.lock().await.len(), 2);
+
+    // A reminder the user set up themselves isn't doubled.
+    let mut fresh: HashMap<String, SyncedEvent> = HashMap::new();
+    let outcome = calendar_sync_tick_with(&calendar, &db, &mut fresh, "@u", "123", &options, now)
+        .await
+        .unwrap();
+    assert_eq!(outcome.created, 0);
+    assert_eq!(db.lock().await.len(), 2);
+}
+
+#[test]
+fn google_events_parse_timed_entries_only() {
+    let body = json!({
+        "items": [
+            {
+                "id": "a",
+                "summary": "Dentist",
+                "start": { "dateTime": "2026-02-10T15:00:00-05:00" },
+                "end": { "dateTime": "2026-02-10T16:00:00-05:00" }
+            },
+            { "id": "b", "summary": "Holiday", "start": { "date": "2026-02-10" } },
+            {
+                "id": "c",
+                "status": "cancelled",
+                "start": { "dateTime": "2026-02-10T18:00:00Z" }
+            }
+        ]
+    });
+    let events = parse_events(&body);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].id.as_deref(), Some("a"));
+    assert_eq!(events[0].title, "Dentist");
+    assert_eq!(events[0].start_time, Utc.with_ymd_and_hms(2026, 2, 10, 20, 0, 0).unwrap());
+}
+
+#[test]
+fn caldav_responses_parse_timed_occurrences() {
+    let body = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+<d:multistatus xmlns:d=\"DAV:\" xmlns:cal=\"urn:ietf:params:xml:ns:caldav\">\n\
+<d:response><d:href>/cal/standup.ics</d:href><d:propstat><d:prop><cal:calendar-data>\
+BEGIN:VCALENDAR&#13;\nVERSION:2.0&#13;\n\
+BEGIN:VEVENT&#13;\nUID:standup&#13;\nRECURRENCE-ID:20260210T150000Z&#13;\nSUMMARY:Standup &amp; planning&#13;\n\
+DTSTART:20260210T150000Z&#13;\nDTEND:20260210T151500Z&#13;\nEND:VEVENT&#13;\n\
+END:VCALENDAR&#13;\n</cal:calendar-data></d:prop></d:propstat></d:response>\n\
+<d:response><d:href>/cal/offsite.ics</d:href><d:propstat><d:prop><cal:calendar-data>\
+BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:offsite\nSUMMARY:Offsite\nDTSTART;VALUE=DATE:20260210\nEND:VEVENT\nEND:VCALENDAR\n\
+</cal:calendar-data></d:prop></d:propstat></d:response>\n\
+</d:multistatus>";
+    let start = Utc.with_ymd_and_hms(2026, 2, 10, 15, 0, 0).unwrap();
+    assert_eq!(
+        parse_multistatus(body),
+        vec![CalendarEvent {
+            id: Some("standup/20260210T150000Z".to_string()),
+            title: "Standup & planning".to_string(),
+            start_time: start,
+            end_time: start + Duration::minutes(15),
+            description: None,
+        }]
+    );
+}
+
+#[test]
+fn outlook_events_parse_timed_entries_only() {
+    let body = json!({
+        "value": [
+            {
+                "id": "AAMk1",
+                "subject": "1:1 with Priya",
+                "bodyPreview": "Quarterly goals",
+                "start": { "dateTime": "2026-02-10T15:00:00.0000000", "timeZone": "UTC" },
+                "end": { "dateTime": "2026-02-10T15:30:00.0000000", "timeZone": "UTC" }
+            },
+            {
+                "id": "AAMk2",
+                "subject": "Company holiday",
+                "isAllDay": true,
+                "start": { "dateTime": "2026-02-10T00:00:00.0000000", "timeZone": "UTC" },
+                "end": { "dateTime": "2026-02-11T00:00:00.0000000", "timeZone": "UTC" }
+            },
+            {
+                "id": "AAMk3",
+                "subject": "Moved",
+                "isCancelled": true,
+                "start": { "dateTime": "2026-02-10T18:00:00.0000000", "timeZone": "UTC" }
+            }
+        ]
+    });
+    let start = Utc.with_ymd_and_hms(2026, 2, 10, 15, 0, 0).unwrap();
+    assert_eq!(
+        parse_graph_events(&body),
+        vec![CalendarEvent {
+            id: Some("AAMk1".to_string()),
+            title: "1:1 with Priya".to_string(),
+            start_time: start,
+            end_time: start + Duration::minutes(30),
+            description: Some("Quarterly goals".to_string()),
+        }]
+    );
+}
+
+struct FakeOpenAI {
+    prompt_type: &'static str,
+    response: String,
+}
+
+#[serenity::async_trait]
+impl OpenAIClient for FakeOpenAI {
+    async fn generate_prompt(
+        &self,
+        _prompt: &str,
+        prompt_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!(prompt_type, self.prompt_type);
+        Ok(self.response.clone())
+    }
+}
+
+struct FakeApprovalPrompt;
+
+#[serenity::async_trait]
+impl ApprovalPromptService for FakeApprovalPrompt {
+    async fn prompt(&self, _action: &mut Action) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status(&self, _action: &Action, _message: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status_message(
+        &self,
+        _channel_id: &str,
+        _user_id: &str,
+        _message: &str,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn calendar_event_requests_reach_the_calendar_after_approval() {
+    let calendar = Arc::new(FakeCalendar::default());
+    let tool = CreateCalendarEventTool::with_location("unused").with_client(calendar.clone(), "@u");
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        prompt_type: "calendar_event_extraction",
+        response: r#"{"title":"Sync with Alex","start":"2026-02-12T19:00:00Z","end":null,"description":null}"#
+            .to_string(),
+    });
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, Arc::new(FakeApprovalPrompt), db)
+        .with_tools(ToolRegistry::new().with_tool(Arc::new(tool)));
+
+    engine
+        .handle_event(ActionEvent::CalendarEventRequested {
+            text: "Schedule a 30-minute sync with Alex on Thursday at 2".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+        })
+        .await;
+
+    let action_id = {
+        let guard = store.lock().await;
+        let action = guard.values().next().expect("draft stored");
+        assert_eq!(action.action_type, ActionType::ToolUse);
+        assert!(action.tool_draft().unwrap().summary.starts_with("Add \"Sync with Alex\" to your calendar"));
+        action.id.clone()
+    };
+    assert!(calendar.events.lock().unwrap().is_empty(), "nothing is created before approval");
+
+    engine
+        .handle_event(ActionEvent::ApprovalConfirmed {
+            action_id: action_id.clone(),
+            user_id: "@u".to_string(),
+        })
+        .await;
+
+    let start = Utc.with_ymd_and_hms(2026, 2, 12, 19, 0, 0).unwrap();
+    let events = calendar.events.lock().unwrap().clone();
+    assert_eq!(
+        events,
+        vec![CalendarEvent {
+            id: None,
+            title: "Sync with Alex".to_string(),
+            start_time: start,
+            end_time: start + Duration::minutes(30),
+            description: None,
+        }]
+    );
+    assert_eq!(store.lock().await.get(&action_id).unwrap().status, ActionStatus::Completed);
+}
+
+#[tokio::test]
+async fn reminder_drafts_warn_about_calendar_conflicts() {
+    let standup = Utc.with_ymd_and_hms(2026, 2, 12, 19, 0, 0).unwrap();
+    let calendar = Arc::new(FakeCalendar::default());
+    calendar.set(vec![
+        CalendarEvent {
+            id: Some("standup".to_string()),
+            title: "Team standup".to_string(),
+            start_time: standup,
+            end_time: standup + Duration::minutes(30),
+            description: None,
+        },
+        event("lunch", standup + Duration::hours(2)),
+    ]);
+    let openai = Arc::new(FakeOpenAI {
+        prompt_type: "notification",
+        response: r#"{"content":"call the bank","time":"2026-02-12T19:15:00Z"}"#.to_string(),
+    });
+
+    for (user_id, expected) in [("@u", 1), ("@other", 0)] {
+        let store = Arc::new(Mutex::new(ActionStore::new()));
+        let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+        let engine = ActionEngine::new(store.clone(), openai.clone(), Arc::new(FakeApprovalPrompt), db)
+            .with_calendar(calendar.clone(), "@u");
+        engine
+            .handle_event(ActionEvent::NotifyRequested {
+                text: "call the bank thursday 2:15pm".to_string(),
+                user_id: user_id.to_string(),
+                channel_id: "123".to_string(),
+                received_at: None,
+            })
+            .await;
+
+        let guard = store.lock().await;
+        let draft = guard
+            .values()
+            .next()
+            .and_then(|action| action.notification_draft())
+            .expect("draft stored");
+        assert_eq!(draft.conflicts.len(), expected, "only the calendar's owner is checked");
+        if expected > 0 {
+            assert!(
+                render_pending_message(draft, reminderBot::i18n::Locale::En, chrono_tz::America::New_York).ends_with("\n⚠️ conflicts with 'Team standup' 14:00–14:30")
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn conflicts_include_events_running_past_midnight() {
+    let start = Utc.with_ymd_and_hms(2026, 2, 12, 23, 0, 0).unwrap();
+    let calendar = Arc::new(FakeCalendar::default());
+    calendar.set(vec![CalendarEvent {
+        id: Some("launch".to_string()),
+        title: "Launch night".to_string(),
+        start_time: start,
+        end_time: start + Duration::hours(3),
+        description: None,
+    }]);
+    let openai = Arc::new(FakeOpenAI {
+        prompt_type: "notification",
+        response: r#"{"content":"check the dashboards","time":"2026-02-13T00:30:00Z"}"#.to_string(),
+    });
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, Arc::new(FakeApprovalPrompt), db)
+        .with_calendar(calendar, "@u");
+    engine
+        .handle_event(ActionEvent::NotifyRequested {
+            text: "check the dashboards at 7:30pm".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await;
+
+    let guard = store.lock().await;
+    let draft = guard
+        .values()
+        .next()
+        .and_then(|action| action.notification_draft())
+        .expect("draft stored");
+    assert_eq!(draft.conflicts.len(), 1);
+    assert_eq!(draft.conflicts[0].title, "Launch night");
+}
+
+#[tokio::test]
+async fn ics_imports_create_reminders_after_approval() {
+    let temp_dir = std::env::temp_dir().join(format!("reminderbot_ics_import_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        std::env::set_var("DB_LOCATION", &temp_dir);
+    }
+    let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+               BEGIN:VEVENT\r\nSUMMARY:Flight to Denver\r\nDTSTART:20300310T150000Z\r\nEND:VEVENT\r\n\
+               BEGIN:VEVENT\r\nSUMMARY:Book club\r\nDTSTART;TZID=America/New_York:20300302T190000\r\nEND:VEVENT\r\n\
+               BEGIN:VEVENT\r\nSUMMARY:Already happened\r\nDTSTART:20200302T190000Z\r\nEND:VEVENT\r\n\
+               END:VCALENDAR\r\n";
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        prompt_type: "unused",
+        response: String::new(),
+    });
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, Arc::new(FakeApprovalPrompt), db.clone());
+
+    engine
+        .handle_event(ActionEvent::ImportRequested {
+            ics: ics.to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+        })
+        .await;
+
+    let book_club = Utc.with_ymd_and_hms(2030, 3, 3, 0, 0, 0).unwrap();
+    let flight = Utc.with_ymd_and_hms(2030, 3, 10, 15, 0, 0).unwrap();
+    let action_id = {
+        let guard = store.lock().await;
+        let action = guard.values().next().expect("draft stored");
+        assert_eq!(action.action_type, ActionType::ImportCalendar);
+        assert_eq!(
+            action.import_draft().unwrap().events,
+            vec![
+                ImportedEvent {
+                    summary: "Book club".to_string(),
+                    start: book_club,
+                },
+                ImportedEvent {
+                    summary: "Flight to Denver".to_string(),
+                    start: flight,
+                },
+            ]
+        );
+        action.id.clone()
+    };
+    assert!(db.lock().await.is_empty(), "nothing is created before approval");
+
+    engine
+        .handle_event(ActionEvent::ApprovalConfirmed {
+            action_id: action_id.clone(),
+            user_id: "@u".to_string(),
+        })
+        .await;
+
+    let mut created: Vec<(String, Option<DateTime<Utc>>)> = db
+        .lock()
+        .await
+        .values()
+        .map(|n| (n.content.clone(), n.event_time))
+        .collect();
+    created.sort_by_key(|(_, at)| *at);
+    assert_eq!(
+        created,
+        vec![
+            ("Book club".to_string(), Some(book_club)),
+            ("Flight to Denver".to_string(), Some(flight)),
+        ]
+    );
+    assert_eq!(store.lock().await.get(&action_id).unwrap().status, ActionStatus::Completed);
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[tokio::test]
+async fn find_time_offers_free_slots_and_books_the_chosen_one() {
+    let temp_dir = std::env::temp_dir().join(format!("reminderbot_find_time_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        std::env::set_var("DB_LOCATION", &temp_dir);
+    }
+    let tomorrow = Utc::now().with_timezone(&New_York).date_naive() + Duration::days(1);
+    let local = |hour: u32, minute: u32| {
+        tomorrow
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+            .and_local_timezone(New_York)
+            .unwrap()
+            .with_timezone(&Utc)
+    };
+    let calendar = Arc::new(FakeCalendar::default());
+    calendar.set(vec![CalendarEvent {
+        id: Some("standup".to_string()),
+        title: "Standup".to_string(),
+        start_time: local(9, 0),
+        end_time: local(10, 0),
+        description: None,
+    }]);
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        prompt_type: "unused",
+        response: String::new(),
+    });
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), openai, Arc::new(FakeApprovalPrompt), db.clone())
+        .with_calendar(calendar.clone(), "@u");
+
+    engine
+        .handle_event(ActionEvent::FindTimeRequested {
+            text: "find 30 minutes tomorrow for a dentist call and add it to my calendar".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+        })
+        .await;
+
+    let action_id = {
+        let guard = store.lock().await;
+        let action = guard.values().next().expect("slots offered");
+        assert_eq!(action.action_type, ActionType::FindTime);
+        let draft = action.slot_draft().unwrap();
+        assert_eq!(draft.title, "dentist call");
+        assert_eq!(draft.slots, vec![local(10, 0), local(10, 30), local(11, 0)]);
+        action.id.clone()
+    };
+
+    // A time that wasn't offered is ignored.
+    engine
+        .handle_event(ActionEvent::SlotChosen {
+            action_id: action_id.clone(),
+            user_id: "@u".to_string(),
+            time: local(9, 0),
+        })
+        .await;
+    assert!(db.lock().await.is_empty());
+
+    engine
+        .handle_event(ActionEvent::SlotChosen {
+            action_id: action_id.clone(),
+            user_id: "@u".to_string(),
+            time: local(10, 30),
+        })
+        .await;
+
+    let reminders: Vec<(String, Option<DateTime<Utc>>)> = db
+        .lock()
+        .await
+        .values()
+        .map(|n| (n.content.clone(), n.event_time))
+        .collect();
+    assert_eq!(reminders, vec![("dentist call".to_string(), Some(local(10, 30)))]);
+    let events = calendar.events.lock().unwrap().clone();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].title, "dentist call");
+    assert_eq!(events[1].end_time, local(11, 0));
+    assert_eq!(store.lock().await.get(&action_id).unwrap().status, ActionStatus::Completed);
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
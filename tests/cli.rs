@@ -0,0 +1,278 @@
+use std::env;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::{TimeZone, Utc};
+use reminderBot::cli::{self, Repl};
+use reminderBot::service::openai_service::OpenAIClient;
+use reminderBot::service::routing::{Intent, IntentResult, IntentRouter};
+
+static ENV_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(str::to_string).collect()
+}
+
+async fn run(name: &str, line: &str) -> Result<String, String> {
+    cli::command(&args(&format!("{} {}", name, line))).await.expect("handled command")
+}
+
+#[tokio::test]
+async fn cli_manages_reminders_and_todos() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_cli_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    assert_eq!(run("list", "").await.unwrap(), "No reminders.");
+    let created = run("create", "--user @1 --channel 123 --at 2031-03-01T09:30 pay rent")
+        .await
+        .unwrap();
+    assert!(created.contains("pay rent"), "{}", created);
+    run("create", "--user @2 --channel 123 --at 2031-03-02T09:30 water plants")
+        .await
+        .unwrap();
+
+    let mine = run("list", "--user @1").await.unwrap();
+    assert_eq!(mine.lines().count(), 1);
+    let id = mine.split_whitespace().next().unwrap().to_string();
+    assert!(mine.contains("Sat Mar 1 2031, 9:30 AM"), "{}", mine);
+
+    let json: serde_json::Value = serde_json::from_str(&run("list", "--user @1 --output json").await.unwrap()).unwrap();
+    assert_eq!(json[0]["content"], "pay rent");
+    assert_eq!(json[0]["notify"][0], "@1");
+    let table = run("list", "--output table").await.unwrap();
+    assert!(table.starts_with("ID"), "{}", table);
+    assert_eq!(table.lines().count(), 3);
+    let shown = cli::command(&args(&format!("--output json show {}", id))).await.unwrap().unwrap();
+    let shown: serde_json::Value = serde_json::from_str(&shown).unwrap();
+    assert_eq!(shown["channel"], "123");
+    assert!(run("list", "--output yaml").await.is_err());
+
+    run("edit", &format!("{} --text rent --at 2031-03-03T10:00", id))
+        .await
+        .unwrap();
+    let shown = run("show", &id).await.unwrap();
+    assert!(shown.contains("text: rent"), "{}", shown);
+    assert!(shown.contains("time: Mon Mar 3 2031, 10:00 AM"), "{}", shown);
+    assert_eq!(shown.matches("ping:").count(), 2);
+
+    run("delete", &id).await.unwrap();
+    assert!(run("show", &id).await.is_err());
+    assert_eq!(run("list", "").await.unwrap().lines().count(), 1);
+
+    run("todo", "add --user @1 buy milk").await.unwrap();
+    let todos = run("todo", "--user @1").await.unwrap();
+    let todo_id = todos.split_whitespace().next().unwrap().to_string();
+    assert!(todos.ends_with("buy milk"));
+    run("todo", &format!("done {}", todo_id)).await.unwrap();
+    assert_eq!(run("todo", "list --user @1").await.unwrap(), "No open todos.");
+
+    assert!(cli::command(&args("serve")).await.is_none());
+}
+
+struct FakeOpenAI;
+
+#[serenity::async_trait]
+impl OpenAIClient for FakeOpenAI {
+    async fn generate_prompt(
+        &self,
+        prompt: &str,
+        _prompt_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let hour = if prompt.contains("4pm") { 21 } else { 20 };
+        Ok(format!(r#"{{"content":"dentist","time":"2031-03-07T{}:00:00Z"}}"#, hour))
+    }
+}
+
+// Needs a time before it calls anything a reminder.
+struct TimeRouter;
+
+#[serenity::async_trait]
+impl IntentRouter for TimeRouter {
+    async fn route(&self, text: &str) -> IntentResult {
+        IntentResult {
+            intent: if text.contains("pm") { Intent::Notification } else { Intent::Unknown },
+            normalized_text: text.to_string(),
+            confidence: 1.0,
+        }
+    }
+}
+
+#[tokio::test]
+async fn repl_finishes_unclear_requests_over_several_lines() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_repl_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+    let now = Utc.with_ymd_and_hms(2031, 3, 1, 12, 0, 0).unwrap();
+    let mut repl = Repl::new(Arc::new(FakeOpenAI), Arc::new(TimeRouter), "@1", "123");
+
+    let reply = repl.handle_line("dentist", now).await;
+    assert_eq!(reply, "What should I remind you about, and when?");
+    let reply = repl.handle_line("friday at 3pm", now).await;
+    assert!(reply.starts_with("Remind you \"dentist\" at Fri Mar 7 2031, 3:00 PM?"), "{}", reply);
+    let reply = repl.handle_line("make it 4pm", now).await;
+    assert!(reply.contains("4:00 PM"), "{}", reply);
+    assert!(repl.handle_line("y", now).await.starts_with("Saved"));
+
+    let listed = run("list", "--user @1").await.unwrap();
+    assert!(listed.contains("Fri Mar 7 2031, 4:00 PM  dentist"), "{}", listed);
+}
+
+#[tokio::test]
+async fn prompt_dry_run_shows_the_extraction_without_saving() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_dry_run_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+    let now = Utc.with_ymd_and_hms(2031, 3, 1, 12, 0, 0).unwrap();
+
+    let output = cli::prompt_command(&args("--dry-run dentist friday at 3pm"), &FakeOpenAI, now)
+        .await
+        .unwrap();
+    assert!(output.contains("\"content\": \"dentist\""), "{}", output);
+    assert!(output.contains("2031-03-07T19:00:00+00:00 (Fri Mar 7 2031, 2:00 PM)"), "{}", output);
+    assert!(output.contains("2031-03-06T20:00:00+00:00"), "{}", output);
+    assert!(output.contains("Please confirm your notification:\nContent: dentist"), "{}", output);
+    assert_eq!(run("list", "").await.unwrap(), "No reminders.");
+
+    assert!(cli::prompt_command(&args("dentist friday at 3pm"), &FakeOpenAI, now).await.is_err());
+    cli::prompt_command(&args("--user @1 --channel 123 dentist friday at 3pm"), &FakeOpenAI, now)
+        .await
+        .unwrap();
+    assert_eq!(run("list", "").await.unwrap().lines().count(), 1);
+}
+
+struct FallbackOpenAI;
+
+#[serenity::async_trait]
+impl OpenAIClient for FallbackOpenAI {
+    async fn generate_prompt(
+        &self,
+        _prompt: &str,
+        _prompt_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        // What the extraction prompt answers when it finds no time.
+        Ok(r#"{"content":"dentist","time":"2031-03-02T12:00:00Z"}"#.to_string())
+    }
+}
+
+#[tokio::test]
+async fn prompt_refuses_a_time_that_looks_made_up() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_sanity_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+    let now = Utc.with_ymd_and_hms(2031, 3, 1, 12, 0, 0).unwrap();
+
+    let err = cli::prompt_command(&args("--user @1 --channel 123 dentist soon"), &FallbackOpenAI, now)
+        .await
+        .unwrap_err();
+    assert!(err.contains("I couldn't find a time"), "{}", err);
+    assert_eq!(run("list", "").await.unwrap(), "No reminders.");
+
+    cli::prompt_command(&args("--user @1 --channel 123 --force dentist soon"), &FallbackOpenAI, now)
+        .await
+        .unwrap();
+    assert_eq!(run("list", "").await.unwrap().lines().count(), 1);
+
+    let mut repl = Repl::new(Arc::new(FallbackOpenAI), Arc::new(TimeRouter), "@1", "123");
+    let reply = repl.handle_line("dentist friday at 3pm", now).await;
+    assert!(reply.contains("? I couldn't find a time, so this is just a day from now. y to save"), "{}", reply);
+}
+
+#[tokio::test]
+async fn import_file_reports_each_line() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_import_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+    std::fs::create_dir_all(&temp_dir).unwrap();
+    let path = temp_dir.join("reminders.jsonl");
+    std::fs::write(
+        &path,
+        "# reminders to bring over\n\
+         {\"content\":\"pay rent\",\"time\":\"2031-03-01 09:30\",\"recurrence\":\"every month\"}\n\
+         \"dentist friday at 3pm\"\n\
+         \n\
+         {\"content\":\"no time\"}\n\
+         {\"content\":\"standup\",\"time\":\"2031-03-02T14:00:00Z\",\"notify\":\"@2\",\"channel\":\"456\"}\n",
+    )
+    .unwrap();
+    let now = Utc.with_ymd_and_hms(2031, 2, 1, 12, 0, 0).unwrap();
+
+    let report = cli::import_file_command(
+        &args(&format!("--file {} --user @1 --channel 123", path.display())),
+        Some(&FakeOpenAI),
+        now,
+    )
+    .await
+    .unwrap();
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines.len(), 5, "{}", report);
+    assert!(lines[0].starts_with("line 2: created"), "{}", lines[0]);
+    assert!(lines[1].starts_with("line 3: created") && lines[1].contains("dentist"), "{}", lines[1]);
+    assert!(lines[2].starts_with("line 5: failed"), "{}", lines[2]);
+    assert!(lines[3].ends_with("standup → @2"), "{}", lines[3]);
+    assert_eq!(lines[4], "Imported 3 of 4 reminders.");
+    assert_eq!(run("list", "--user @1").await.unwrap().lines().count(), 2);
+
+    let without_openai = cli::import_file_command(
+        &args(&format!("--file {} --user @1 --channel 123", path.display())),
+        None,
+        now,
+    )
+    .await
+    .unwrap();
+    assert!(without_openai.contains("line 3: failed: free text needs OPENAI_API_KEY"));
+}
+
+#[test]
+fn init_writes_a_config_that_loads() {
+    let dir = env::temp_dir().join(format!("notificationbot_init_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.properties");
+    let data = dir.join("data");
+    // The blank token and the channel with letters are asked again.
+    let answers = format!("\ntoken-123\nsk-test\nabc\n42\n{}\nworker,http\n", data.display());
+    let mut output = Vec::new();
+
+    let saved = cli::run_init(&mut answers.as_bytes(), &mut output, path.to_str().unwrap()).unwrap();
+    assert!(saved.starts_with("Saved"));
+    let asked = String::from_utf8(output).unwrap();
+    assert_eq!(asked.matches("Discord bot token").count(), 2);
+    assert!(asked.contains("Channel ids are numbers"));
+
+    let config = reminderBot::config::AppConfig::from_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(config.get("DISCORD_CLIENT_SECRET").as_deref(), Some("token-123"));
+    assert_eq!(config.get("OPERATOR_CHANNEL_ID").as_deref(), Some("42"));
+    assert_eq!(config.get("RUN_MODE").as_deref(), Some("worker,http"));
+    assert!(data.is_dir());
+
+    let mut output = Vec::new();
+    let canceled = cli::run_init(&mut "only-a-token\n".as_bytes(), &mut output, path.to_str().unwrap());
+    assert_eq!(canceled.unwrap_err(), "Setup canceled.");
+}
+
+#[tokio::test]
+async fn completions_and_man_page_cover_every_command() {
+    let bash = run("completions", "bash").await.unwrap();
+    assert!(bash.contains("complete -o default -F _reminderBot reminderBot"));
+    assert!(bash.contains("list show create edit delete todo"));
+    assert!(bash.contains("prompt) COMPREPLY=($(compgen -W \"--user --channel --dry-run\""));
+
+    let zsh = run("completions", "zsh").await.unwrap();
+    assert!(zsh.starts_with("#compdef reminderBot"));
+    let fish = run("completions", "fish").await.unwrap();
+    assert!(fish.contains("complete -c reminderBot -n '__fish_seen_subcommand_from import' -l ics"));
+    assert_eq!(run("completions", "powershell").await.unwrap_err(), "Usage: completions <bash|zsh|fish>");
+
+    let man = cli::command(&args("man")).await.unwrap().unwrap();
+    assert!(man.starts_with(".TH REMINDERBOT 1"));
+    assert!(man.contains(".B edit <id> [\\-\\-text <text>] [\\-\\-at <time>]"));
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use chrono::{NaiveTime, TimeZone, Utc};
+use reminderBot::config::{self, AppConfig, CalendarProvider, DEFAULT_OPENAI_MODEL};
+
+fn write_config(path: &std::path::Path, body: &str) {
+    std::fs::write(path, body).unwrap();
+}
+
+#[test]
+fn defaults_apply_without_overrides() {
+    let config = AppConfig::default();
+    assert_eq!(config.notification_interval(), Duration::from_secs(60));
+    assert_eq!(config.openai_model(), DEFAULT_OPENAI_MODEL);
+    assert!(config.prompt_template("notification_message").is_none());
+}
+
+#[test]
+fn calendar_provider_follows_the_config_key() {
+    let dir = "./data/test_calendar_provider";
+    std::fs::create_dir_all(dir).unwrap();
+    let path = std::path::Path::new(dir).join("config.properties");
+    let caldav = "CALDAV_URL=https://dav.example.com/cal/\nCALDAV_USERNAME=alice\nCALDAV_PASSWORD=secret\n\
+                  CALDAV_USER_ID=@1\nCALDAV_CHANNEL_ID=2\n";
+    let google = "GOOGLE_CLIENT_ID=id\nGOOGLE_CLIENT_SECRET=s\nGOOGLE_CALENDAR_USER_ID=@3\nGOOGLE_CALENDAR_CHANNEL_ID=4\n";
+
+    write_config(&path, &format!("{}{}", caldav, google));
+    let config = AppConfig::from_file(path.to_str().unwrap()).unwrap();
+    assert!(matches!(config.calendar_provider(), Some(CalendarProvider::Google(_))));
+
+    write_config(&path, &format!("CALENDAR_PROVIDER=caldav\n{}{}", caldav, google));
+    let provider = AppConfig::from_file(path.to_str().unwrap())
+        .unwrap()
+        .calendar_provider()
+        .expect("caldav configured");
+    assert!(matches!(provider, CalendarProvider::CalDav(_)));
+    assert_eq!((provider.user_id(), provider.channel_id()), ("@1", "2"));
+
+    write_config(&path, &format!("CALENDAR_PROVIDER=caldav\n{}", google));
+    let config = AppConfig::from_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(config.calendar_provider(), None, "incomplete CalDAV settings");
+}
+
+#[test]
+fn loop_schedules_fall_back_to_defaults_when_invalid() {
+    let dir = "./data/test_loop_schedules";
+    std::fs::create_dir_all(dir).unwrap();
+    let path = std::path::Path::new(dir).join("config.properties");
+
+    let defaults = AppConfig::default();
+    assert_eq!(defaults.calendar_sync_interval(), Duration::from_secs(15 * 60));
+    assert_eq!(defaults.approval_sweep_interval(), Duration::from_secs(30));
+    assert_eq!(
+        defaults.todo_summary_schedule(),
+        (NaiveTime::from_hms_opt(7, 0, 0).unwrap(), chrono_tz::America::New_York)
+    );
+    assert!(defaults.problems().is_empty());
+
+    write_config(
+        &path,
+        "FOCUS_DIGEST_INTERVAL_SECS=10
+SESSION_SWEEP_INTERVAL_SECS=0
+CALENDAR_SYNC_INTERVAL_SECS=soon
+         TODO_SUMMARY_TIME=18:30
+TODO_SUMMARY_TIMEZONE=Mars/Olympus
+",
+    );
+    let config = AppConfig::from_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(config.focus_digest_interval(), Duration::from_secs(10));
+    assert_eq!(config.session_sweep_interval(), Duration::from_secs(60));
+    assert_eq!(config.calendar_sync_interval(), Duration::from_secs(15 * 60));
+    assert_eq!(
+        config.todo_summary_schedule(),
+        (NaiveTime::from_hms_opt(18, 30, 0).unwrap(), chrono_tz::America::New_York)
+    );
+    assert_eq!(
+        config.problems(),
+        vec![
+            "SESSION_SWEEP_INTERVAL_SECS=0 is not a number of seconds between 1 and 86400; using 60",
+            "CALENDAR_SYNC_INTERVAL_SECS=soon is not a number of seconds between 1 and 86400; using 900",
+            "TODO_SUMMARY_TIMEZONE=Mars/Olympus is not an IANA time zone; using America/New_York",
+        ]
+    );
+}
+
+#[test]
+fn scheduled_jobs_read_cron_expressions() {
+    let dir = "./data/test_cron_schedules";
+    std::fs::create_dir_all(dir).unwrap();
+    let path = std::path::Path::new(dir).join("config.properties");
+    let at = |hour: u32, minute: u32| Utc.with_ymd_and_hms(2026, 3, 2, hour, minute, 0).unwrap();
+
+    // Without TODO_SUMMARY_CRON the summary keeps following TODO_SUMMARY_TIME.
+    let defaults = AppConfig::default();
+    assert_eq!(defaults.todo_summary_cron().next_after(at(11, 0)), Some(at(12, 0)));
+    assert_eq!(defaults.cleanup_cron().unwrap().expression(), "0 30 3 * * *");
+    assert_eq!(defaults.todo_retention(), chrono::Duration::days(30));
+
+    write_config(
+        &path,
+        "TODO_SUMMARY_CRON=0 18 * * MON-FRI
+CRON_TIMEZONE=UTC
+CLEANUP_CRON=off
+TODO_RETENTION_DAYS=7
+",
+    );
+    let config = AppConfig::from_file(path.to_str().unwrap()).unwrap();
+    // 2026-03-02 is a Monday.
+    assert_eq!(config.todo_summary_cron().next_after(at(11, 0)), Some(at(18, 0)));
+    assert_eq!(config.cleanup_cron(), None);
+    assert_eq!(config.todo_retention(), chrono::Duration::days(7));
+    assert!(config.problems().is_empty());
+
+    write_config(&path, "TODO_SUMMARY_CRON=every morning\nCLEANUP_CRON=61 * * * *\n");
+    let config = AppConfig::from_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(config.todo_summary_cron().expression(), "0 0 7 * * *");
+    assert_eq!(config.cleanup_cron().unwrap().expression(), "0 30 3 * * *");
+    let problems = config.problems();
+    assert_eq!(problems.len(), 2, "{:?}", problems);
+    assert!(problems[0].starts_with("TODO_SUMMARY_CRON: 'every morning' is not a cron expression"));
+    assert!(problems[1].starts_with("CLEANUP_CRON: '61 * * * *' is not a cron expression"));
+}
+
+#[tokio::test]
+async fn watcher_swaps_in_edited_config() {
+    let dir = "./data/test_config_reload";
+    std::fs::create_dir_all(dir).unwrap();
+    let path = std::path::Path::new(dir).join("config.properties");
+    write_config(&path, "NOTIFICATION_INTERVAL_SECS=5\nOPENAI_MODEL=gpt-4o-mini\n");
+
+    let shared = AppConfig::from_file(path.to_str().unwrap()).unwrap().shared();
+    let _watcher = config::watch(&path, shared.clone()).unwrap();
+
+    write_config(
+        &path,
+        "NOTIFICATION_INTERVAL_SECS=30\nOPENAI_MODEL=gpt-4o\nPROMPT_NOTIFICATION_MESSAGE=Remind: {prompt}\\nNow: {now}\n",
+    );
+
+    let mut reloaded = false;
+    for _ in 0..50 {
+        if shared.load().openai_model() == "gpt-4o" {
+            reloaded = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(reloaded, "config was not reloaded");
+    let current = shared.load();
+    assert_eq!(current.notification_interval(), Duration::from_secs(30));
+    assert_eq!(
+        current.prompt_template("notification_message").as_deref(),
+        Some("Remind: {prompt}\nNow: {now}")
+    );
+}
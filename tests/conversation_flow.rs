@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use reminderBot::events::queue::EventBus;
+use reminderBot::handlers::action::ActionEvent;
+use reminderBot::handlers::discord::BotHandler;
+use reminderBot::i18n::Locale;
+use reminderBot::models::notification::Notification;
+use reminderBot::models::todo::TodoItem;
+use reminderBot::service::notify_flow::{
+    ConversationManager, NotifyDecision, PendingSession, ReminderAction, SessionKey,
+};
+use reminderBot::service::routing::HeuristicRouter;
+use tokio::sync::Mutex;
+
+fn reminder(id: &str, content: &str, at: chrono::DateTime<Utc>) -> Notification {
+    Notification {
+        id: id.to_string(),
+        content: content.to_string(),
+        notify: vec!["@u".to_string()],
+        notification_times: vec![at],
+        channel: "123".to_string(),
+        completed_at: None,
+        event_time: Some(at),
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: None,
+        quiet_delayed: false,
+        targets: Vec::new(),
+        delivery_retry: None,
+    }
+}
+
+async fn say(
+    manager: &ConversationManager,
+    sessions: &mut HashMap<SessionKey, PendingSession>,
+    text: &str,
+) -> NotifyDecision {
+    let key = ("@u".to_string(), "123".to_string());
+    manager
+        .handle(&HeuristicRouter, sessions, key, text.to_string(), Locale::En, Utc::now())
+        .await
+}
+
+#[tokio::test]
+async fn never_mind_and_the_turn_limit_end_a_conversation() {
+    let manager = ConversationManager::default().with_max_turns(2);
+    let mut sessions = HashMap::new();
+
+    let decision = say(&manager, &mut sessions, "something").await;
+    assert!(matches!(decision, NotifyDecision::NeedClarification), "{:?}", decision);
+    let decision = say(&manager, &mut sessions, "Never mind.").await;
+    assert!(matches!(decision, NotifyDecision::ConversationCanceled), "{:?}", decision);
+    assert!(sessions.is_empty());
+
+    for text in ["something", "else"] {
+        let decision = say(&manager, &mut sessions, text).await;
+        assert!(matches!(decision, NotifyDecision::NeedClarification), "{:?}", decision);
+    }
+    let decision = say(&manager, &mut sessions, "again").await;
+    assert!(matches!(decision, NotifyDecision::GaveUp), "{:?}", decision);
+    assert!(sessions.is_empty());
+}
+
+#[tokio::test]
+async fn ambiguous_cancel_asks_which_reminder_and_takes_the_pick() {
+    let at = Utc::now() + Duration::days(2);
+    let db = Arc::new(Mutex::new(HashMap::from([
+        ("n1".to_string(), reminder("n1", "Dentist checkup", at)),
+        ("n2".to_string(), reminder("n2", "Dentist bill", at + Duration::days(1))),
+    ])));
+    let (bus, mut rx) = EventBus::new(8);
+    let handler = BotHandler::new(
+        Arc::new(Mutex::new(HashMap::<String, TodoItem>::new())),
+        bus,
+        Arc::new(Mutex::new(HashMap::new())),
+        Arc::new(HeuristicRouter),
+    )
+    .with_notifications(db);
+
+    let decision = handler.handle_notify_internal("cancel the dentist one", "@u", "123", None).await;
+    match &decision {
+        NotifyDecision::ChooseReminder { options } => assert_eq!(options, &["Dentist checkup", "Dentist bill"]),
+        other => panic!("unexpected decision: {:?}", other),
+    }
+    assert!(
+        BotHandler::notify_response(Locale::En, &decision).ends_with("\n1. Dentist checkup\n2. Dentist bill")
+    );
+    assert!(rx.try_recv().is_err());
+
+    let decision = handler.handle_notify_internal("2", "@u", "123", None).await;
+    match &decision {
+        NotifyDecision::ActOnReminder { action, reminder_id } => {
+            assert_eq!((action, reminder_id.as_str()), (&ReminderAction::Cancel, "n2"))
+        }
+        other => panic!("unexpected decision: {:?}", other),
+    }
+    match rx.try_recv().expect("cancel event emitted") {
+        ActionEvent::CancelReminder { reminder_id, .. } => assert_eq!(reminder_id.as_deref(), Some("n2")),
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    // Only one reminder fits, so nothing is asked.
+    let decision = handler.handle_notify_internal("cancel the dentist bill", "@u", "123", None).await;
+    assert!(matches!(decision, NotifyDecision::ActOnReminder { .. }), "{:?}", decision);
+}
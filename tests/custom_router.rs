@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use reminderBot::bot::ReminderBot;
+use reminderBot::handlers::action::ActionEvent;
+use reminderBot::handlers::discord::BotHandler;
+use reminderBot::models::todo::TodoItem;
+use reminderBot::service::notify_flow::{NotifyDecision, route_notify};
+use reminderBot::service::routing::{Intent, IntentResult, IntentRouter};
+use tokio::sync::Mutex;
+
+#[path = "../examples/custom_router.rs"]
+#[allow(dead_code)]
+mod custom_router;
+
+use custom_router::KeywordRouter;
+
+#[tokio::test]
+async fn builder_uses_registered_router() {
+    let bot = ReminderBot::new("discord-token", "unused")
+        .with_intent_router(Arc::new(KeywordRouter::default()));
+
+    let router = bot.intent_router();
+    assert_eq!(router.route("remind me to stretch").await.intent, Intent::Notification);
+    assert_eq!(router.route("buy milk").await.intent, Intent::Todolist);
+}
+
+#[test]
+fn default_openai_client_is_built_once() {
+    let bot = ReminderBot::new("discord-token", "unused");
+    assert!(Arc::ptr_eq(&bot.openai_client(), &bot.openai_client()));
+}
+
+#[tokio::test]
+async fn custom_router_drives_notify_flow() {
+    let bot = ReminderBot::new("discord-token", "unused")
+        .with_intent_router(Arc::new(KeywordRouter::default()));
+    let (bus, mut rx) = reminderBot::events::queue::EventBus::new(8);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, bot.intent_router());
+
+    // No time tokens or task verbs, so the heuristic router would have asked.
+    let decision = handler
+        .handle_notify_internal("remind me to stretch", "@u", "123", None)
+        .await;
+    assert!(matches!(decision, NotifyDecision::EmitNotify { .. }));
+    match rx.try_recv().expect("notify event emitted") {
+        ActionEvent::NotifyRequested { text, .. } => assert_eq!(text, "remind me to stretch"),
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+// Leans towards a todo but isn't sure unless a time comes along.
+struct HesitantRouter;
+
+#[serenity::async_trait]
+impl IntentRouter for HesitantRouter {
+    async fn route(&self, text: &str) -> IntentResult {
+        let timed = text.contains("friday");
+        IntentResult {
+            intent: if timed { Intent::Notification } else { Intent::Todolist },
+            normalized_text: text.to_string(),
+            confidence: if timed { 0.9 } else { 0.3 },
+        }
+    }
+}
+
+#[tokio::test]
+async fn unsure_routing_asks_before_acting() {
+    let now = chrono::Utc::now();
+    let key = ("@u".to_string(), "123".to_string());
+    let mut sessions = HashMap::new();
+
+    let decision = route_notify(
+        &HesitantRouter,
+        &mut sessions,
+        key.clone(),
+        "rent".to_string(),
+        reminderBot::i18n::Locale::En,
+        now,
+    )
+    .await;
+    let (intent, normalized_text) = match decision {
+        NotifyDecision::ClarifyIntent { intent, normalized_text } => (intent, normalized_text),
+        other => panic!("expected a clarifying question, got {:?}", other),
+    };
+    assert_eq!(intent, Intent::Todolist);
+    assert_eq!(
+        reminderBot::service::notify_flow::clarify_question(reminderBot::i18n::Locale::En, &intent, &normalized_text),
+        "Should 'rent' go on your todo list? Re-run /notify with what needs doing, or with a time for a reminder instead."
+    );
+
+    // The answer is read together with the unclear request.
+    let decision = route_notify(
+        &HesitantRouter,
+        &mut sessions,
+        key,
+        "friday 9am".to_string(),
+        reminderBot::i18n::Locale::En,
+        now,
+    )
+    .await;
+    assert!(
+        matches!(&decision, NotifyDecision::EmitNotify { normalized_text } if normalized_text == "rent friday 9am"),
+        "{:?}",
+        decision
+    );
+
+    // A handler configured to trust any guess acts straight away.
+    let (bus, _rx) = reminderBot::events::queue::EventBus::new(8);
+    let handler = BotHandler::new(
+        Arc::new(Mutex::new(HashMap::<String, TodoItem>::new())),
+        bus,
+        Arc::new(Mutex::new(HashMap::new())),
+        Arc::new(HesitantRouter),
+    )
+    .with_confidence_threshold(0.0);
+    let decision = handler.handle_notify_internal("rent", "@u", "123", None).await;
+    assert!(matches!(decision, NotifyDecision::EmitTodo { .. }));
+}
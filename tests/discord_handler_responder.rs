@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use reminderBot::handlers::discord::BotHandler;
-use reminderBot::handlers::discord_responder::InteractionResponder;
+use reminderBot::handlers::action::ActionEvent;
+use reminderBot::handlers::discord::{AliasCommand, BotHandler, FocusCommand};
+use reminderBot::handlers::discord_responder::{Delivery, InitialResponse, InteractionResponder, Reply};
+use reminderBot::i18n::Locale;
 use reminderBot::models::todo::TodoItem;
 use reminderBot::service::routing::HeuristicRouter;
 use std::sync::Mutex as StdMutex;
@@ -25,6 +27,9 @@ struct MockResponder {
     replies: Mutex<Vec<String>>,
     updates: Mutex<Vec<String>>,
     modals: Mutex<Vec<(String, String)>>,
+    followups: Mutex<Vec<(String, bool)>>,
+    edits: Mutex<Vec<String>>,
+    defers: Mutex<usize>,
 }
 
 #[serenity::async_trait]
@@ -44,6 +49,21 @@ impl InteractionResponder for MockResponder {
         let mut modals = self.modals.lock().await;
         modals.push((debug, "".to_string()));
     }
+
+    async fn defer_ephemeral(&self) {
+        let mut defers = self.defers.lock().await;
+        *defers += 1;
+    }
+
+    async fn follow_up(&self, content: &str, ephemeral: bool) {
+        let mut followups = self.followups.lock().await;
+        followups.push((content.to_string(), ephemeral));
+    }
+
+    async fn edit_original(&self, content: &str) {
+        let mut edits = self.edits.lock().await;
+        edits.push(content.to_string());
+    }
 }
 
 #[tokio::test]
@@ -56,15 +76,55 @@ async fn notify_with_responder_emits_response() {
 
     let responder = MockResponder::default();
     let decision = handler
-        .handle_notify_with(&responder, "call mom tomorrow at 5", "@u", "123")
+        .handle_notify_with(&responder, "call mom tomorrow at 5", "@u", "123", None)
         .await;
 
     assert!(matches!(
         decision,
         reminderBot::service::notify_flow::NotifyDecision::EmitNotify { .. }
     ));
-    let replies = responder.replies.lock().await;
-    assert_eq!(replies.last().map(String::as_str), Some("Got it — processing your notification."));
+    assert_eq!(*responder.defers.lock().await, 1);
+    let edits = responder.edits.lock().await;
+    assert_eq!(
+        edits.as_slice(),
+        [
+            "🧠 interpreting your request…".to_string(),
+            "Got it — processing your notification.".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn notify_status_turns_ready_once_the_prompt_is_posted() {
+    use reminderBot::events::prompt_watch::PromptWatch;
+
+    let (bus, _rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let watch = PromptWatch::new();
+    let handler = BotHandler::new(todo_db, bus, sessions, router).with_prompt_watch(watch.clone());
+
+    // Stands in for the worker posting the card a little later.
+    let worker = tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        watch.posted("@someone-else", "123");
+        watch.posted("@u", "123");
+    });
+    let responder = MockResponder::default();
+    handler
+        .handle_notify_with(&responder, "call mom tomorrow at 5", "@u", "123", None)
+        .await;
+    worker.await.unwrap();
+
+    assert_eq!(
+        responder.edits.lock().await.as_slice(),
+        [
+            "🧠 interpreting your request…".to_string(),
+            "Got it — processing your notification.".to_string(),
+            "✅ ready for confirmation".to_string(),
+        ]
+    );
 }
 
 #[tokio::test]
@@ -78,17 +138,29 @@ async fn notify_with_responder_unknown_message() {
 
     let responder = MockResponder::default();
     let decision = handler
-        .handle_notify_with(&responder, "just a thought", "@u", "123")
+        .handle_notify_with(&responder, "just a thought", "@u", "123", None)
         .await;
 
+    assert!(matches!(
+        decision,
+        reminderBot::service::notify_flow::NotifyDecision::NeedClarification
+    ));
+    assert_eq!(
+        responder.edits.lock().await.last().map(String::as_str),
+        Some("I can set notifications. What should I notify you about, and when? Re-run /notify with a time.")
+    );
+
+    let decision = handler
+        .handle_notify_with(&responder, "buy milk", "@v", "123", None)
+        .await;
     assert!(matches!(
         decision,
         reminderBot::service::notify_flow::NotifyDecision::EmitTodo { .. }
     ));
-    let replies = responder.replies.lock().await;
+    let edits = responder.edits.lock().await;
     assert_eq!(
-        replies.last().map(String::as_str),
-        Some("Added to your todo list.")
+        edits.last().map(String::as_str),
+        Some("Got it — drafting your todo.")
     );
 }
 
@@ -102,9 +174,622 @@ async fn pending_context_opens_modal() {
 
     let responder = MockResponder::default();
     handler
-        .handle_pending_context_with(&responder, "action123")
+        .handle_pending_context_with(&responder, Locale::En, "action123")
         .await;
 
     let modals = responder.modals.lock().await;
     assert!(modals.last().unwrap().0.contains("action_context_modal:action123"));
 }
+
+#[tokio::test]
+async fn notify_replies_in_user_locale() {
+    let _guard = prepare_db_location("notify_replies_in_user_locale");
+    let (bus, _rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router);
+
+    let responder = MockResponder::default();
+    handler
+        .handle_locale_with(&responder, "@u", None, "es-ES", false, false)
+        .await;
+    handler
+        .handle_notify_with(&responder, "call mom tomorrow at 5", "@u", "123", None)
+        .await;
+
+    let replies = responder.replies.lock().await;
+    assert_eq!(replies.first().map(String::as_str), Some("Idioma establecido: es."));
+    let edits = responder.edits.lock().await;
+    assert_eq!(
+        edits.last().map(String::as_str),
+        Some("Entendido — procesando tu notificación.")
+    );
+}
+
+#[tokio::test]
+async fn server_locale_requires_manage_guild() {
+    let _guard = prepare_db_location("server_locale_requires_manage_guild");
+    let (bus, _rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router);
+
+    let responder = MockResponder::default();
+    handler
+        .handle_locale_with(&responder, "@u", Some("42"), "de", true, false)
+        .await;
+    assert_eq!(handler.locale_for("@other", Some("42")).await, Locale::En);
+
+    handler
+        .handle_locale_with(&responder, "@u", Some("42"), "de", true, true)
+        .await;
+    assert_eq!(handler.locale_for("@other", Some("42")).await, Locale::De);
+
+    let replies = responder.replies.lock().await;
+    assert_eq!(
+        replies.first().map(String::as_str),
+        Some("You need the Manage Server permission to change server settings.")
+    );
+}
+
+#[tokio::test]
+async fn mention_routes_through_notify_flow() {
+    let (bus, _rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router);
+
+    let responder = MockResponder::default();
+    let ignored = handler
+        .handle_mention_with(&responder, "remind me tomorrow at 5", 999, "@u", "123", None)
+        .await;
+    assert!(ignored.is_none());
+
+    let decision = handler
+        .handle_mention_with(
+            &responder,
+            "<@999> remind me to submit the report Friday",
+            999,
+            "@u",
+            "123",
+            None,
+        )
+        .await;
+
+    assert!(matches!(
+        decision,
+        Some(reminderBot::service::notify_flow::NotifyDecision::EmitNotify { .. })
+    ));
+    let edits = responder.edits.lock().await;
+    assert_eq!(
+        edits.last().map(String::as_str),
+        Some("Got it — processing your notification.")
+    );
+}
+
+#[tokio::test]
+async fn guild_alias_expands_before_routing() {
+    let _guard = prepare_db_location("guild_alias_expands_before_routing");
+    let (bus, mut rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router);
+
+    let responder = MockResponder::default();
+    handler
+        .handle_alias_with(
+            &responder,
+            "@admin",
+            Some("42"),
+            true,
+            AliasCommand::Set {
+                name: "Standup".to_string(),
+                expansion: "team standup in #eng at 9:30am".to_string(),
+            },
+        )
+        .await;
+
+    let decision = handler
+        .handle_notify_internal("standup!", "@u", "123", Some("42"))
+        .await;
+    assert!(matches!(
+        decision,
+        reminderBot::service::notify_flow::NotifyDecision::EmitNotify { .. }
+    ));
+
+    match rx.try_recv().expect("notify event emitted") {
+        ActionEvent::NotifyRequested { text, .. } => {
+            assert_eq!(text, "team standup in #eng at 9:30am!");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    let replies = responder.replies.lock().await;
+    assert_eq!(
+        replies.first().map(String::as_str),
+        Some("Alias 'standup' now expands to: team standup in #eng at 9:30am")
+    );
+}
+
+#[tokio::test]
+async fn timezone_setting_validates_zone_name() {
+    let _guard = prepare_db_location("timezone_setting_validates_zone_name");
+    let (bus, _rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let user_settings = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router)
+        .with_settings(user_settings.clone(), Arc::new(Mutex::new(HashMap::new())));
+
+    let responder = MockResponder::default();
+    handler
+        .handle_timezone_with(&responder, "@u", None, "Mars/Olympus")
+        .await;
+    assert!(user_settings.lock().await.get("@u").is_none());
+
+    handler
+        .handle_timezone_with(&responder, "@u", None, "America/Los_Angeles")
+        .await;
+    let stored = user_settings.lock().await;
+    assert_eq!(
+        stored.get("@u").and_then(|s| s.timezone.as_deref()),
+        Some("America/Los_Angeles")
+    );
+    let replies = responder.replies.lock().await;
+    assert!(replies[0].contains("Unknown timezone 'Mars/Olympus'"));
+    assert_eq!(replies[1], "Your timezone is now America/Los_Angeles.");
+}
+
+#[tokio::test]
+async fn pushover_keys_need_the_app_token_and_thirty_characters() {
+    let _guard = prepare_db_location("pushover_keys_need_the_app_token_and_thirty_characters");
+    let build = |pushover: bool| {
+        let (bus, _rx) = reminderBot::events::queue::EventBus::new(8);
+        let user_settings = Arc::new(Mutex::new(HashMap::new()));
+        let handler = BotHandler::new(
+            Arc::new(Mutex::new(HashMap::<String, TodoItem>::new())),
+            bus,
+            Arc::new(Mutex::new(HashMap::new())),
+            Arc::new(HeuristicRouter),
+        )
+        .with_settings(user_settings.clone(), Arc::new(Mutex::new(HashMap::new())))
+        .with_pushover(pushover);
+        (handler, user_settings)
+    };
+    let key = "u".repeat(30);
+
+    let (handler, user_settings) = build(false);
+    let responder = MockResponder::default();
+    handler
+        .handle_push_with(&responder, "@u", None, "pushover", Some(&key))
+        .await;
+    assert!(user_settings.lock().await.get("@u").is_none());
+    assert!(responder.replies.lock().await[0].contains("Pushover isn't set up"));
+
+    let (handler, user_settings) = build(true);
+    let responder = MockResponder::default();
+    handler
+        .handle_push_with(&responder, "@u", None, "pushover", Some(&key[..29]))
+        .await;
+    assert!(user_settings.lock().await.get("@u").is_none());
+    handler
+        .handle_push_with(&responder, "@u", None, "pushover", Some(&key))
+        .await;
+    assert!(user_settings.lock().await.get("@u").and_then(|s| s.push.as_ref()).is_some());
+}
+
+#[tokio::test]
+async fn notify_uses_locale_time_tokens() {
+    let _guard = prepare_db_location("notify_uses_locale_time_tokens");
+    let (bus, mut rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router);
+
+    let responder = MockResponder::default();
+    handler
+        .handle_locale_with(&responder, "@u", None, "es", false, false)
+        .await;
+
+    let decision = handler
+        .handle_notify_internal("llamar a mamá mañana", "@u", "123", None)
+        .await;
+    assert!(matches!(
+        decision,
+        reminderBot::service::notify_flow::NotifyDecision::EmitNotify { .. }
+    ));
+    match rx.try_recv().expect("notify event emitted") {
+        ActionEvent::NotifyRequested { text, .. } => {
+            assert_eq!(text, "llamar a mamá mañana");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn notify_is_rate_limited_per_user() {
+    let _guard = prepare_db_location("notify_is_rate_limited_per_user");
+    let (bus, mut rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router).with_rate_limiter(
+        reminderBot::service::rate_limit::RateLimiter::new(1, chrono::Duration::minutes(1)),
+    );
+
+    let responder = MockResponder::default();
+    handler
+        .handle_notify_with(&responder, "call mom tomorrow at 5", "@u", "123", None)
+        .await;
+    let decision = handler
+        .handle_notify_with(&responder, "call dad tomorrow at 6", "@u", "123", None)
+        .await;
+    assert!(matches!(
+        decision,
+        reminderBot::service::notify_flow::NotifyDecision::RateLimited { .. }
+    ));
+
+    let replies = responder.replies.lock().await;
+    assert_eq!(replies.len(), 1);
+    assert!(replies[0].starts_with("Slow down! You can send another request <t:"));
+    assert!(matches!(
+        rx.try_recv(),
+        Ok(ActionEvent::NotifyRequested { .. })
+    ));
+    assert!(rx.try_recv().is_err());
+
+    // Another user still has their own budget.
+    let decision = handler
+        .handle_notify_with(&responder, "call mom tomorrow at 5", "@v", "123", None)
+        .await;
+    assert!(matches!(
+        decision,
+        reminderBot::service::notify_flow::NotifyDecision::EmitNotify { .. }
+    ));
+}
+
+#[tokio::test]
+async fn admin_commands_require_configured_role() {
+    let _guard = prepare_db_location("admin_commands_require_configured_role");
+    let (bus, mut rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router)
+        .with_admin_role(Some("900".to_string()));
+
+    let responder = MockResponder::default();
+    handler
+        .handle_admin_with(&responder, "@u", "123", Some("42"), &["100".to_string()], "stats")
+        .await;
+    assert!(rx.try_recv().is_err());
+
+    handler
+        .handle_admin_with(&responder, "@u", "123", Some("42"), &["900".to_string()], "stats")
+        .await;
+    match rx.try_recv().expect("admin event emitted") {
+        ActionEvent::AdminRequested { command, .. } => assert_eq!(
+            command,
+            reminderBot::handlers::action::AdminCommand::Stats {
+                queue_depth: 0,
+                todo_count: 0
+            }
+        ),
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    let replies = responder.replies.lock().await;
+    assert_eq!(replies[0], "This command is limited to the bot's admin role.");
+    assert_eq!(replies[1], "Processing your request.");
+}
+
+#[tokio::test]
+async fn focus_on_and_off_update_user_settings() {
+    let _guard = prepare_db_location("focus_on_and_off_update_user_settings");
+    let (bus, _rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let user_settings = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router)
+        .with_settings(user_settings.clone(), Arc::new(Mutex::new(HashMap::new())));
+    let now = chrono::Utc::now();
+
+    let responder = MockResponder::default();
+    handler
+        .handle_focus_with(
+            &responder,
+            "@u",
+            None,
+            FocusCommand::On {
+                duration: "forever".to_string(),
+            },
+            now,
+        )
+        .await;
+    assert!(user_settings.lock().await.get("@u").is_none());
+
+    handler
+        .handle_focus_with(
+            &responder,
+            "@u",
+            None,
+            FocusCommand::On {
+                duration: "2h".to_string(),
+            },
+            now,
+        )
+        .await;
+    assert_eq!(
+        user_settings
+            .lock()
+            .await
+            .get("@u")
+            .and_then(|s| s.focus.as_ref())
+            .map(|f| f.until),
+        Some(now + chrono::Duration::hours(2))
+    );
+
+    handler
+        .handle_focus_with(&responder, "@u", None, FocusCommand::Off, now)
+        .await;
+    assert_eq!(
+        user_settings
+            .lock()
+            .await
+            .get("@u")
+            .and_then(|s| s.focus.as_ref())
+            .map(|f| f.until),
+        Some(now)
+    );
+
+    let replies = responder.replies.lock().await;
+    assert!(replies[0].contains("Couldn't read 'forever'"));
+    assert!(replies[1].starts_with("Focus mode on until <t:"));
+    assert!(replies[2].starts_with("Focus mode off."));
+}
+
+#[tokio::test]
+async fn server_voice_requires_manage_guild() {
+    use reminderBot::models::settings::Voice;
+
+    let _guard = prepare_db_location("server_voice_requires_manage_guild");
+    let (bus, _rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let guild_settings = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router)
+        .with_settings(Arc::new(Mutex::new(HashMap::new())), guild_settings.clone());
+
+    let responder = MockResponder::default();
+    handler
+        .handle_voice_with(&responder, "@u", Some("42"), "terse", false)
+        .await;
+    assert!(guild_settings.lock().await.get("42").is_none());
+
+    handler
+        .handle_voice_with(&responder, "@u", Some("42"), "terse", true)
+        .await;
+    assert_eq!(
+        guild_settings.lock().await.get("42").map(|guild| guild.voice),
+        Some(Voice::Terse)
+    );
+
+    let replies = responder.replies.lock().await;
+    assert_eq!(
+        replies.last().map(String::as_str),
+        Some("Reminders in this server will now sound terse.")
+    );
+}
+
+#[tokio::test]
+async fn must_ack_command_emits_require_ack() {
+    let _guard = prepare_db_location("must_ack_command_emits_require_ack");
+    let (bus, mut rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router);
+
+    let decision = handler
+        .handle_notify_internal("must-ack 2 @carer 45m", "@u", "123", Some("42"))
+        .await;
+    assert!(matches!(
+        decision,
+        reminderBot::service::notify_flow::NotifyDecision::MustAckRequested
+    ));
+    match rx.try_recv().expect("require ack event emitted") {
+        ActionEvent::RequireAck {
+            index,
+            backup,
+            window_minutes,
+            ..
+        } => {
+            assert_eq!(index, 2);
+            assert_eq!(backup.as_deref(), Some("@carer"));
+            assert_eq!(window_minutes, Some(45));
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn checklist_toggles_update_the_message_and_wrap_up() {
+    use reminderBot::models::notification::{self, Notification};
+
+    let _guard = prepare_db_location("checklist_toggles_update_the_message_and_wrap_up");
+    let (bus, _rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let id = {
+        let mut db = notification_db.lock().await;
+        let id = notification::create_notification(
+            &mut db,
+            &"trip".to_string(),
+            &"@u".to_string(),
+            &(chrono::Utc::now() + chrono::Duration::days(1)),
+            &"123".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+        notification::set_checklist(&mut db, &id, &["passport".to_string(), "charger".to_string()])
+            .unwrap();
+        id
+    };
+    let handler = BotHandler::new(todo_db, bus, sessions, router)
+        .with_notifications(notification_db.clone());
+
+    let responder = MockResponder::default();
+    let message = "Your trip is tomorrow.\n☐ passport\n☐ charger";
+    handler
+        .handle_checklist_toggle_with(&responder, "@someone-else", None, message, &id, 0)
+        .await;
+    assert_eq!(
+        responder.replies.lock().await.last().map(String::as_str),
+        Some("That checklist is no longer available.")
+    );
+
+    handler
+        .handle_checklist_toggle_with(&responder, "@u", None, message, &id, 0)
+        .await;
+    assert_eq!(
+        responder.updates.lock().await.last().map(String::as_str),
+        Some("Your trip is tomorrow.\n☑ passport\n☐ charger")
+    );
+    assert!(responder.followups.lock().await.is_empty());
+
+    handler
+        .handle_checklist_toggle_with(&responder, "@u", None, message, &id, 1)
+        .await;
+    assert!(notification::checklist_complete(&notification_db.lock().await[&id]));
+    assert_eq!(
+        *responder.followups.lock().await,
+        vec![("All 2 items checked off for 'trip'.".to_string(), false)]
+    );
+}
+
+#[tokio::test]
+async fn done_and_dismiss_are_only_for_the_reminders_recipients() {
+    use reminderBot::models::notification::{self, Notification};
+
+    let _guard = prepare_db_location("done_and_dismiss_are_only_for_the_reminders_recipients");
+    let (bus, mut rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let id = notification::create_notification(
+        &mut *notification_db.lock().await,
+        &"standup".to_string(),
+        &"@u,@v".to_string(),
+        &(chrono::Utc::now() + chrono::Duration::days(1)),
+        &"123".to_string(),
+        None,
+    )
+    .await
+    .unwrap();
+    let handler = BotHandler::new(todo_db, bus, sessions, router)
+        .with_notifications(notification_db.clone());
+
+    let responder = MockResponder::default();
+    handler
+        .handle_reminder_resolved_with(&responder, "@stranger", None, &id, false)
+        .await;
+    assert_eq!(
+        responder.replies.lock().await.last().map(String::as_str),
+        Some("That reminder isn't yours to close.")
+    );
+    assert!(rx.try_recv().is_err());
+
+    handler
+        .handle_reminder_resolved_with(&responder, "@v", None, &id, true)
+        .await;
+    assert_eq!(
+        responder.replies.lock().await.last().map(String::as_str),
+        Some("Dismissed — no more reminders for this one.")
+    );
+    // The shared message is left as it is for the other recipient.
+    assert!(responder.updates.lock().await.is_empty());
+    assert!(matches!(
+        rx.try_recv().expect("dismiss emitted"),
+        ActionEvent::NotificationDismissed { user_id, .. } if user_id == "@v"
+    ));
+
+    handler
+        .handle_reminder_resolved_with(&responder, "@u", None, &id, false)
+        .await;
+    assert_eq!(
+        *responder.updates.lock().await,
+        vec!["Marked as done — you won't be reminded about this again.".to_string()]
+    );
+    assert!(matches!(
+        rx.try_recv().expect("done emitted"),
+        ActionEvent::NotificationCompleted { user_id, .. } if user_id == "@u"
+    ));
+}
+
+#[tokio::test]
+async fn reactions_answer_prompts_only_where_enabled() {
+    let _guard = prepare_db_location("reactions_answer_prompts_only_where_enabled");
+    let (bus, mut rx) = reminderBot::events::queue::EventBus::new(8);
+    let router = Arc::new(HeuristicRouter);
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    let guild_settings = Arc::new(Mutex::new(HashMap::new()));
+    let handler = BotHandler::new(todo_db, bus, sessions, router)
+        .with_settings(Arc::new(Mutex::new(HashMap::new())), guild_settings.clone());
+    let buttons = vec![
+        "action_confirm:a1".to_string(),
+        "action_cancel:a1".to_string(),
+    ];
+
+    assert!(!handler.handle_reaction_with("@u", Some("42"), "✅", &buttons).await);
+
+    let responder = MockResponder::default();
+    handler
+        .handle_approval_reactions_with(&responder, "@u", Some("42"), true, true)
+        .await;
+    assert!(!handler.handle_reaction_with("@u", Some("42"), "👍", &buttons).await);
+    assert!(!handler.handle_reaction_with("@u", Some("42"), "✅", &[]).await);
+    assert!(handler.handle_reaction_with("@u", Some("42"), "❌", &buttons).await);
+
+    match rx.try_recv().expect("cancel event emitted") {
+        ActionEvent::ApprovalCanceled { action_id, user_id } => {
+            assert_eq!(action_id, "a1");
+            assert_eq!(user_id, "@u");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn a_second_reply_becomes_a_follow_up() {
+    let initial = InitialResponse::new();
+    assert_eq!(initial.route(Reply::Update, true), Delivery::UpdateMessage);
+    assert_eq!(initial.route(Reply::Update, true), Delivery::FollowUp);
+    assert_eq!(initial.route(Reply::Message, true), Delivery::FollowUp);
+    assert_eq!(initial.route(Reply::Modal, true), Delivery::Skip);
+
+    let initial = InitialResponse::new();
+    assert_eq!(initial.route(Reply::Defer, false), Delivery::Defer);
+    assert_eq!(initial.route(Reply::Message, false), Delivery::FollowUp);
+    assert_eq!(initial.route(Reply::Defer, false), Delivery::Skip);
+
+    // Off a component there's no message to update or modal to open, and
+    // trying the modal doesn't use up the initial response.
+    let initial = InitialResponse::new();
+    assert_eq!(initial.route(Reply::Modal, false), Delivery::Skip);
+    assert_eq!(initial.route(Reply::UpdateWithButtons, false), Delivery::Message);
+    assert_eq!(initial.route(Reply::Update, false), Delivery::FollowUp);
+}
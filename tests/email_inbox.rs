@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use reminderBot::events::queue::EventBus;
+use reminderBot::handlers::action::ActionEvent;
+use reminderBot::tasks::email_loop::{Mailbox, email_tick, parse_email, request_text};
+
+// Messages by uid, and whether each has been marked seen.
+struct FakeMailbox(Mutex<Vec<(u32, Vec<u8>, bool)>>);
+
+impl FakeMailbox {
+    fn new(messages: Vec<Vec<u8>>) -> Self {
+        Self(Mutex::new(
+            messages
+                .into_iter()
+                .zip(1..)
+                .map(|(raw, uid)| (uid, raw, false))
+                .collect(),
+        ))
+    }
+}
+
+#[serenity::async_trait]
+impl Mailbox for FakeMailbox {
+    async fn fetch_unseen(&self) -> Result<Vec<(u32, Vec<u8>)>, String> {
+        let messages = self.0.lock().unwrap();
+        Ok(messages
+            .iter()
+            .filter(|(_, _, seen)| !seen)
+            .map(|(uid, raw, _)| (*uid, raw.clone()))
+            .collect())
+    }
+
+    async fn mark_seen(&self, uids: &[u32]) -> Result<(), String> {
+        for (uid, _, seen) in self.0.lock().unwrap().iter_mut() {
+            *seen |= uids.contains(uid);
+        }
+        Ok(())
+    }
+}
+
+fn email(from: &str, subject: &str, body: &str) -> Vec<u8> {
+    format!(
+        "From: {}\r\nTo: remind@example.com\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}",
+        from, subject, body
+    )
+    .into_bytes()
+}
+
+#[test]
+fn quoted_replies_and_signatures_are_left_out() {
+    let parsed = parse_email(&email(
+        "Alice <Alice@Example.com>",
+        "Dentist",
+        "tomorrow at 9\r\n\r\n> earlier thread\r\n--\r\nAlice\r\n",
+    ))
+    .unwrap();
+    assert_eq!(parsed.from, "alice@example.com");
+    assert_eq!(request_text(&parsed), "Dentist\ntomorrow at 9");
+}
+
+#[tokio::test]
+async fn emails_from_known_senders_become_reminder_requests() {
+    let mailbox = FakeMailbox::new(vec![
+        email("Alice <alice@example.com>", "Call mom", "friday at 5pm"),
+        email("spam@example.net", "Win a prize", "click here"),
+        b"not an email at all".to_vec(),
+    ]);
+    let senders = HashMap::from([("alice@example.com".to_string(), "@u".to_string())]);
+    let (bus, mut rx) = EventBus::new(8);
+
+    let raised = email_tick(&mailbox, &senders, "123", &bus, Utc::now()).await.unwrap();
+    assert_eq!(raised, 1);
+    match rx.try_recv().expect("notify event emitted") {
+        ActionEvent::NotifyRequested { text, user_id, channel_id, .. } => {
+            assert_eq!(text, "Call mom\nfriday at 5pm");
+            assert_eq!((user_id.as_str(), channel_id.as_str()), ("@u", "123"));
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+    assert!(rx.try_recv().is_err());
+
+    // Each email is only read once.
+    assert_eq!(email_tick(&mailbox, &senders, "123", &bus, Utc::now()).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn emails_stay_unread_until_their_request_is_queued() {
+    use reminderBot::events::queue::OverflowPolicy;
+
+    let mailbox = FakeMailbox::new(vec![
+        email("alice@example.com", "Call mom", "friday at 5pm"),
+        email("alice@example.com", "Dentist", "monday at 9"),
+    ]);
+    let senders = HashMap::from([("alice@example.com".to_string(), "@u".to_string())]);
+    let (bus, mut rx) = EventBus::with_overflow(1, OverflowPolicy::Reject);
+
+    assert_eq!(email_tick(&mailbox, &senders, "123", &bus, Utc::now()).await.unwrap(), 1);
+    assert!(rx.try_recv().is_ok());
+
+    // The one that didn't fit is picked up once there's room.
+    assert_eq!(email_tick(&mailbox, &senders, "123", &bus, Utc::now()).await.unwrap(), 1);
+    assert!(rx.try_recv().is_ok());
+    assert_eq!(email_tick(&mailbox, &senders, "123", &bus, Utc::now()).await.unwrap(), 0);
+}
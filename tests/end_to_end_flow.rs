@@ -2,9 +2,10 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use reminderBot::handlers::action::{Action, ActionEngine, ActionEvent, ActionStore};
-use reminderBot::events::queue::EventBus;
+use reminderBot::events::queue::{EventBus, QueuedEvent};
 use reminderBot::events::worker::run_event_worker;
 use reminderBot::handlers::discord::BotHandler;
+use reminderBot::i18n::Locale;
 use reminderBot::models::notification::Notification;
 use reminderBot::models::todo::TodoItem;
 use reminderBot::service::approval_prompt::ApprovalPromptService;
@@ -110,7 +111,7 @@ async fn end_to_end_notify_confirm_flow() {
     let handler = BotHandler::new(todo_db, bus.clone(), sessions, router);
 
     let decision = handler
-        .handle_notify_internal("call mom tomorrow at 5", "@u", "123")
+        .handle_notify_internal("call mom tomorrow at 5", "@u", "123", None)
         .await;
     assert!(matches!(decision, reminderBot::service::notify_flow::NotifyDecision::EmitNotify { .. }));
 
@@ -129,7 +130,8 @@ async fn end_to_end_notify_confirm_flow() {
         action_id,
         user_id: "@u".to_string(),
     })
-    .await;
+    .await
+    .expect("event should queue");
 
     drop(handler);
     drop(bus);
@@ -169,7 +171,7 @@ async fn end_to_end_notify_rejection_flow() {
     let handler = BotHandler::new(todo_db, bus.clone(), sessions, router);
 
     let decision = handler
-        .handle_notify_internal("call mom tomorrow at 5", "@u", "123")
+        .handle_notify_internal("call mom tomorrow at 5", "@u", "123", None)
         .await;
     assert!(matches!(decision, reminderBot::service::notify_flow::NotifyDecision::EmitNotify { .. }));
 
@@ -188,7 +190,8 @@ async fn end_to_end_notify_rejection_flow() {
         action_id,
         user_id: "@u".to_string(),
     })
-    .await;
+    .await
+    .expect("event should queue");
 
     drop(handler);
     drop(bus);
@@ -225,7 +228,7 @@ async fn end_to_end_notify_context_correction_flow() {
     let handler = BotHandler::new(todo_db, bus.clone(), sessions, router);
 
     let decision = handler
-        .handle_notify_internal("call mom tomorrow at 5", "@u", "123")
+        .handle_notify_internal("call mom tomorrow at 5", "@u", "123", None)
         .await;
     assert!(matches!(decision, reminderBot::service::notify_flow::NotifyDecision::EmitNotify { .. }));
 
@@ -245,7 +248,8 @@ async fn end_to_end_notify_context_correction_flow() {
         user_id: "@u".to_string(),
         context: "actually next day".to_string(),
     })
-    .await;
+    .await
+    .expect("event should queue");
 
     let _ = timeout(Duration::from_secs(2), async {
         loop {
@@ -268,7 +272,8 @@ async fn end_to_end_notify_context_correction_flow() {
         action_id,
         user_id: "@u".to_string(),
     })
-    .await;
+    .await
+    .expect("event should queue");
 
     drop(handler);
     drop(bus);
@@ -291,13 +296,480 @@ async fn end_to_end_unknown_message_flow() {
     let handler = BotHandler::new(todo_db, bus, sessions, router);
 
     let decision = handler
-        .handle_notify_internal("just a phrase", "@u", "123")
+        .handle_notify_internal("just a phrase", "@u", "123", None)
         .await;
-    let response = BotHandler::notify_response(&decision);
+    let response = BotHandler::notify_response(Locale::En, &decision);
+
+    assert!(matches!(
+        decision,
+        reminderBot::service::notify_flow::NotifyDecision::NeedClarification
+    ));
+    assert!(response.starts_with("I can set notifications."));
 
+    let decision = handler
+        .handle_notify_internal("order more printer paper", "@v", "123", None)
+        .await;
     assert!(matches!(
         decision,
         reminderBot::service::notify_flow::NotifyDecision::EmitTodo { .. }
     ));
-    assert_eq!(response, "Added to your todo list.");
+    assert_eq!(BotHandler::notify_response(Locale::En, &decision), "Got it — drafting your todo.");
+}
+
+#[tokio::test]
+async fn worker_drains_queued_events_on_shutdown() {
+    use reminderBot::events::worker::run_event_worker_until;
+
+    let (bus, rx) = EventBus::new(16);
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(
+            "{\"content\":\"call mom\",\"time\":\"2026-02-03T12:00:00Z\"}".to_string(),
+        ),
+    });
+    let approval = Arc::new(CapturingApprovalPrompt::new());
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store, openai, approval.clone(), notification_db);
+
+    for text in ["call mom tomorrow at 5", "call dad tomorrow at 6"] {
+        bus.emit(ActionEvent::NotifyRequested {
+            text: text.to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await
+        .expect("event should queue");
+    }
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    shutdown_tx.send(true).unwrap();
+    timeout(
+        Duration::from_secs(2),
+        run_event_worker_until(rx, engine, shutdown_rx, None),
+    )
+    .await
+    .expect("worker should stop after draining");
+
+    assert_eq!(approval.prompts.lock().await.len(), 2);
+
+    // The queue is closed, so later events are dropped instead of blocking.
+    let result = bus
+        .emit(ActionEvent::NotifyRequested {
+            text: "too late".to_string(),
+            user_id: "@u".to_string(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await;
+    assert_eq!(result, Err(reminderBot::events::queue::EmitError::Closed));
+    assert_eq!(approval.prompts.lock().await.len(), 2);
+}
+
+#[tokio::test]
+async fn journaled_events_are_replayed_after_a_crash() {
+    use reminderBot::events::journal::EventJournal;
+    use reminderBot::events::worker::run_event_worker_until;
+
+    let path = std::env::temp_dir().join(format!("events_{}.journal", uuid::Uuid::new_v4()));
+
+    // First run: events are emitted but the process dies before the worker
+    // gets to them.
+    {
+        let journal = Arc::new(EventJournal::open(&path).expect("journal should open"));
+        let (bus, _rx) = EventBus::new(16);
+        let bus = bus.with_journal(journal);
+        for text in ["call mom tomorrow at 5", "call dad tomorrow at 6"] {
+            bus.emit(ActionEvent::NotifyRequested {
+                text: text.to_string(),
+                user_id: "@u".to_string(),
+                channel_id: "123".to_string(),
+                received_at: None,
+            })
+            .await
+            .expect("event should queue");
+        }
+    }
+
+    let journal = Arc::new(EventJournal::open(&path).expect("journal should reopen"));
+    assert_eq!(journal.pending_len(), 2);
+
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let openai = Arc::new(FakeOpenAI {
+        response: Ok(
+            "{\"content\":\"call mom\",\"time\":\"2026-02-03T12:00:00Z\"}".to_string(),
+        ),
+    });
+    let approval = Arc::new(CapturingApprovalPrompt::new());
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store, openai, approval.clone(), notification_db);
+    let (_bus, rx) = EventBus::new(16);
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    shutdown_tx.send(true).unwrap();
+    timeout(
+        Duration::from_secs(2),
+        run_event_worker_until(rx, engine, shutdown_rx, Some(journal.clone())),
+    )
+    .await
+    .expect("worker should stop after replaying");
+
+    assert_eq!(approval.prompts.lock().await.len(), 2);
+    assert_eq!(journal.pending_len(), 0);
+    drop(journal);
+    let reopened = EventJournal::open(&path).expect("journal should reopen");
+    assert_eq!(reopened.pending_len(), 0);
+    let _ = std::fs::remove_file(&path);
+}
+
+// Echoes the request back as the reminder, taking its time over anything
+// that mentions "slow".
+struct SlowOpenAI;
+
+#[serenity::async_trait]
+impl OpenAIClient for SlowOpenAI {
+    async fn generate_prompt(
+        &self,
+        prompt: &str,
+        _prompt_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        if prompt.contains("slow") {
+            sleep(Duration::from_millis(300)).await;
+        }
+        Ok(format!(
+            "{{\"content\":\"{}\",\"time\":\"2026-02-03T12:00:00Z\"}}",
+            prompt
+        ))
+    }
+}
+
+struct OrderedApprovalPrompt {
+    drafted: Mutex<Vec<String>>,
+}
+
+#[serenity::async_trait]
+impl ApprovalPromptService for OrderedApprovalPrompt {
+    async fn prompt(&self, action: &mut Action) -> Result<(), String> {
+        let draft = action.notification_draft().expect("draft");
+        self.drafted.lock().await.push(draft.content.clone());
+        Ok(())
+    }
+
+    async fn update_status(&self, _action: &Action, _message: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status_message(
+        &self,
+        _channel_id: &str,
+        _user_id: &str,
+        _message: &str,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn worker_pool_keeps_per_user_order_without_blocking_others() {
+    use reminderBot::events::worker::{lane_for, run_event_worker_pool_until};
+
+    let lanes = 8;
+    let slow_user = "@slow".to_string();
+    let fast_user = (0..)
+        .map(|i| format!("@fast{}", i))
+        .find(|user| lane_for(user, lanes) != lane_for(&slow_user, lanes))
+        .unwrap();
+
+    let (bus, rx) = EventBus::new(16);
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let approval = Arc::new(OrderedApprovalPrompt {
+        drafted: Mutex::new(Vec::new()),
+    });
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store, Arc::new(SlowOpenAI), approval.clone(), notification_db);
+
+    for (user_id, text) in [
+        (&slow_user, "slow errand"),
+        (&slow_user, "second errand"),
+        (&fast_user, "quick errand"),
+    ] {
+        bus.emit(ActionEvent::NotifyRequested {
+            text: text.to_string(),
+            user_id: user_id.clone(),
+            channel_id: "123".to_string(),
+            received_at: None,
+        })
+        .await
+        .expect("event should queue");
+    }
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    shutdown_tx.send(true).unwrap();
+    timeout(
+        Duration::from_secs(2),
+        run_event_worker_pool_until(rx, engine, lanes, shutdown_rx, None),
+    )
+    .await
+    .expect("pool should stop after draining");
+
+    // The fast user isn't stuck behind the slow one, but the slow user's
+    // second request still waits for their first.
+    assert_eq!(
+        *approval.drafted.lock().await,
+        vec!["quick errand", "slow errand", "second errand"]
+    );
+}
+
+fn notify(text: &str) -> ActionEvent {
+    ActionEvent::NotifyRequested {
+        text: text.to_string(),
+        user_id: "@u".to_string(),
+        channel_id: "123".to_string(),
+        received_at: None,
+    }
+}
+
+#[tokio::test]
+async fn scheduled_events_wait_for_their_time() {
+    let (bus, rx) = EventBus::new(16);
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let approval = Arc::new(OrderedApprovalPrompt {
+        drafted: Mutex::new(Vec::new()),
+    });
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store, Arc::new(SlowOpenAI), approval.clone(), notification_db);
+    let worker = tokio::spawn(run_event_worker(rx, engine));
+
+    let due = chrono::Utc::now() + chrono::Duration::milliseconds(1500);
+    bus.emit_at(notify("later errand"), due).await.expect("event should queue");
+    bus.emit(notify("quick errand")).await.expect("event should queue");
+
+    sleep(Duration::from_millis(500)).await;
+    assert_eq!(*approval.drafted.lock().await, vec!["quick errand"]);
+
+    timeout(Duration::from_secs(5), async {
+        while approval.drafted.lock().await.len() < 2 {
+            sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("scheduled event should fire");
+    assert!(chrono::Utc::now() >= due);
+    assert_eq!(
+        *approval.drafted.lock().await,
+        vec!["quick errand", "later errand"]
+    );
+
+    drop(bus);
+    worker.await.unwrap();
+}
+
+#[tokio::test]
+async fn pending_scheduled_events_stay_journaled_across_restarts() {
+    use reminderBot::events::journal::EventJournal;
+    use reminderBot::events::worker::run_event_worker_until;
+
+    let path = std::env::temp_dir().join(format!("events_{}.journal", uuid::Uuid::new_v4()));
+    let journal = Arc::new(EventJournal::open(&path).expect("journal should open"));
+    let (bus, rx) = EventBus::new(16);
+    let bus = bus.with_journal(journal.clone());
+    bus.emit_at(notify("next week"), chrono::Utc::now() + chrono::Duration::days(7))
+        .await
+        .expect("event should queue");
+
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let approval = Arc::new(OrderedApprovalPrompt {
+        drafted: Mutex::new(Vec::new()),
+    });
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store, Arc::new(SlowOpenAI), approval.clone(), notification_db);
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    shutdown_tx.send(true).unwrap();
+    timeout(
+        Duration::from_secs(2),
+        run_event_worker_until(rx, engine, shutdown_rx, Some(journal.clone())),
+    )
+    .await
+    .expect("worker should not wait for the timer to shut down");
+
+    assert!(approval.drafted.lock().await.is_empty());
+    assert_eq!(journal.pending_len(), 1);
+    drop(bus);
+    drop(journal);
+    let reopened = EventJournal::open(&path).expect("journal should reopen");
+    assert!(matches!(
+        reopened.take_replay().as_slice(),
+        [QueuedEvent {
+            event: ActionEvent::Scheduled { .. },
+            ..
+        }]
+    ));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn journal_is_compacted_behind_a_far_off_scheduled_event() {
+    use reminderBot::events::journal::EventJournal;
+
+    let path = std::env::temp_dir().join(format!("events_{}.journal", uuid::Uuid::new_v4()));
+    let journal = EventJournal::open(&path).expect("journal should open");
+    let next_week = QueuedEvent::new(ActionEvent::Scheduled {
+        at: chrono::Utc::now() + chrono::Duration::days(7),
+        event: Box::new(notify("next week")),
+    });
+    journal.record(&next_week).unwrap();
+    journal.claim_next().expect("scheduled event should be claimed");
+    for n in 0..1000 {
+        journal
+            .record(&QueuedEvent::new(notify(&format!("errand {}", n))))
+            .unwrap();
+        let seq = journal.claim_next().unwrap();
+        journal.ack(seq).unwrap();
+    }
+
+    let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+    assert!(lines < 600, "journal kept {} lines", lines);
+    drop(journal);
+    let reopened = EventJournal::open(&path).expect("journal should reopen");
+    let replay = reopened.take_replay();
+    assert!(matches!(
+        replay.as_slice(),
+        [QueuedEvent {
+            event: ActionEvent::Scheduled { .. },
+            ..
+        }]
+    ));
+    assert_eq!(replay[0].correlation_id, next_week.correlation_id);
+    let _ = std::fs::remove_file(&path);
+}
+
+struct PerPromptOpenAI;
+
+#[serenity::async_trait]
+impl OpenAIClient for PerPromptOpenAI {
+    async fn generate_prompt(
+        &self,
+        _prompt: &str,
+        prompt_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        match prompt_type {
+            "todo_extraction" => Ok("{\"items\":[\"buy milk\"]}".to_string()),
+            _ => Ok("{\"content\":\"call mom\",\"time\":\"2026-02-03T23:00:00Z\"}".to_string()),
+        }
+    }
+}
+
+#[tokio::test]
+async fn end_to_end_message_with_two_requests_gets_two_approvals() {
+    let _guard = prepare_db_location("end_to_end_message_with_two_requests");
+    let (bus, rx) = EventBus::new(16);
+    let approval = Arc::new(CapturingApprovalPrompt::new());
+    let engine = ActionEngine::new(
+        Arc::new(Mutex::new(ActionStore::new())),
+        Arc::new(PerPromptOpenAI),
+        approval.clone(),
+        Arc::new(Mutex::new(HashMap::<String, Notification>::new())),
+    );
+    let worker = tokio::spawn(run_event_worker(rx, engine));
+
+    let handler = BotHandler::new(
+        Arc::new(Mutex::new(HashMap::<String, TodoItem>::new())),
+        bus,
+        Arc::new(Mutex::new(HashMap::new())),
+        Arc::new(HeuristicRouter),
+    );
+    let decision = handler
+        .handle_notify_internal("Add buy milk to my list and remind me to call mom at 6pm", "@u", "123", None)
+        .await;
+    match &decision {
+        reminderBot::service::notify_flow::NotifyDecision::EmitMany { parts } => {
+            let texts: Vec<&str> = parts.iter().map(|part| part.normalized_text.as_str()).collect();
+            assert_eq!(texts, vec!["Add buy milk to my list", "remind me to call mom at 6pm"]);
+        }
+        other => panic!("expected two requests, got {:?}", other),
+    }
+    assert_eq!(
+        BotHandler::notify_response(Locale::En, &decision),
+        "Got it — drafting 2 requests, each with its own approval."
+    );
+
+    let prompts = timeout(Duration::from_secs(2), async {
+        loop {
+            let prompts = approval.prompts.lock().await.clone();
+            if prompts.len() == 2 {
+                break prompts;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("an approval prompt for each request");
+    assert_ne!(prompts[0], prompts[1]);
+
+    drop(handler);
+    let _ = worker.await;
+}
+
+fn reminder(id: &str, content: &str, at: chrono::DateTime<chrono::Utc>) -> Notification {
+    Notification {
+        id: id.to_string(),
+        content: content.to_string(),
+        notify: vec!["@u".to_string()],
+        notification_times: vec![at],
+        channel: "123".to_string(),
+        completed_at: None,
+        event_time: Some(at),
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: None,
+        quiet_delayed: false,
+        targets: Vec::new(),
+        delivery_retry: None,
+    }
+}
+
+#[tokio::test]
+async fn end_to_end_cancel_and_snooze_act_on_existing_reminders() {
+    use reminderBot::service::notify_flow::NotifyDecision;
+
+    let _guard = prepare_db_location("end_to_end_cancel_and_snooze");
+    let at = chrono::Utc::now() + chrono::Duration::days(2);
+    let db = Arc::new(Mutex::new(HashMap::from([
+        ("n1".to_string(), reminder("n1", "Dentist appointment", at)),
+        ("n2".to_string(), reminder("n2", "Pay rent", at)),
+    ])));
+    let (bus, rx) = EventBus::new(16);
+    let engine = ActionEngine::new(
+        Arc::new(Mutex::new(ActionStore::new())),
+        Arc::new(PerPromptOpenAI),
+        Arc::new(CapturingApprovalPrompt::new()),
+        db.clone(),
+    );
+    let worker = tokio::spawn(run_event_worker(rx, engine));
+    let handler = BotHandler::new(
+        Arc::new(Mutex::new(HashMap::<String, TodoItem>::new())),
+        bus,
+        Arc::new(Mutex::new(HashMap::new())),
+        Arc::new(HeuristicRouter),
+    );
+
+    let decision = handler.handle_notify_internal("what do I have this week", "@u", "123", None).await;
+    assert!(matches!(decision, NotifyDecision::EmitQuery { .. }), "{:?}", decision);
+    let decision = handler.handle_notify_internal("cancel the dentist one", "@u", "123", None).await;
+    assert!(matches!(decision, NotifyDecision::EmitCancel { .. }), "{:?}", decision);
+    let decision = handler
+        .handle_notify_internal("snooze my rent reminder a day", "@u", "123", None)
+        .await;
+    assert_eq!(
+        BotHandler::notify_response(Locale::En, &decision),
+        "Looking for the reminder to snooze…"
+    );
+
+    drop(handler);
+    let _ = worker.await;
+    let db = db.lock().await;
+    assert!(!db.contains_key("n1"));
+    let rent = db.get("n2").unwrap();
+    assert_eq!(rent.notification_times, vec![at + chrono::Duration::days(1)]);
+    assert_eq!(rent.event_time, Some(at + chrono::Duration::days(1)));
 }
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+
+use reminderBot::handlers::http;
+use reminderBot::models::notification::Notification;
+use reminderBot::models::settings;
+use reminderBot::models::todo::TodoItem;
+use tokio::sync::Mutex;
+
+fn use_temp_db_location() {
+    let temp_dir = env::temp_dir().join(format!("reminderbot_http_test_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+}
+
+#[tokio::test]
+async fn notifications_can_be_created_listed_and_deleted() {
+    use_temp_db_location();
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let api = http::routes(notification_db.clone(), todo_db);
+
+    let created = warp::test::request()
+        .method("POST")
+        .path("/notifications")
+        .json(&serde_json::json!({
+            "content": "pay rent",
+            "notify": ["@u"],
+            "time": "2030-02-03T12:00:00Z",
+            "channel": "123"
+        }))
+        .reply(&api)
+        .await;
+    assert_eq!(created.status(), 201);
+    let body: Notification = serde_json::from_slice(created.body()).unwrap();
+    assert_eq!(body.content, "pay rent");
+    assert_eq!(body.notification_times.len(), 2);
+
+    let listed = warp::test::request()
+        .method("GET")
+        .path("/notifications")
+        .reply(&api)
+        .await;
+    let all: Vec<Notification> = serde_json::from_slice(listed.body()).unwrap();
+    assert_eq!(all.len(), 1);
+
+    let deleted = warp::test::request()
+        .method("DELETE")
+        .path(&format!("/notifications/{}", body.id))
+        .reply(&api)
+        .await;
+    assert_eq!(deleted.status(), 204);
+    assert!(notification_db.lock().await.is_empty());
+
+    let missing = warp::test::request()
+        .method("DELETE")
+        .path(&format!("/notifications/{}", body.id))
+        .reply(&api)
+        .await;
+    assert_eq!(missing.status(), 404);
+}
+
+#[tokio::test]
+async fn todos_reject_empty_content() {
+    use_temp_db_location();
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let api = http::routes(notification_db, todo_db.clone());
+
+    let rejected = warp::test::request()
+        .method("POST")
+        .path("/todos")
+        .json(&serde_json::json!({ "user_id": "@u", "content": "  " }))
+        .reply(&api)
+        .await;
+    assert_eq!(rejected.status(), 400);
+
+    let created = warp::test::request()
+        .method("POST")
+        .path("/todos")
+        .json(&serde_json::json!({ "user_id": "@u", "content": "buy milk" }))
+        .reply(&api)
+        .await;
+    assert_eq!(created.status(), 201);
+    assert_eq!(todo_db.lock().await.len(), 1);
+}
+
+#[tokio::test]
+async fn webhook_requires_token_and_emits_notify_event() {
+    use reminderBot::events::queue::EventBus;
+    use reminderBot::handlers::action::ActionEvent;
+
+    let (event_bus, mut rx) = EventBus::new(4);
+    let api = http::webhook_routes(event_bus, "s3cret".to_string());
+    let body = serde_json::json!({
+        "text": "deploy finished, check dashboards in 1 hour",
+        "user_id": "42",
+        "channel_id": "99"
+    });
+
+    let unauthorized = warp::test::request()
+        .method("POST")
+        .path("/webhooks/notify")
+        .header("authorization", "Bearer wrong")
+        .json(&body)
+        .reply(&api)
+        .await;
+    assert_eq!(unauthorized.status(), 401);
+    assert!(rx.try_recv().is_err());
+
+    let accepted = warp::test::request()
+        .method("POST")
+        .path("/webhooks/notify")
+        .header("authorization", "Bearer s3cret")
+        .json(&body)
+        .reply(&api)
+        .await;
+    assert_eq!(accepted.status(), 202);
+    match rx.try_recv() {
+        Ok(ActionEvent::NotifyRequested { text, user_id, channel_id, .. }) => {
+            assert_eq!(text, "deploy finished, check dashboards in 1 hour");
+            assert_eq!(user_id, "@42");
+            assert_eq!(channel_id, "99");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+struct FakeOpenAI;
+
+#[serenity::async_trait]
+impl reminderBot::service::openai_service::OpenAIClient for FakeOpenAI {
+    async fn generate_prompt(
+        &self,
+        _prompt: &str,
+        _prompt_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok("{\"content\":\"check dashboards\",\"time\":\"2030-02-03T12:00:00Z\"}".to_string())
+    }
+}
+
+struct FakeApprovalPrompt;
+
+#[serenity::async_trait]
+impl reminderBot::service::approval_prompt::ApprovalPromptService for FakeApprovalPrompt {
+    async fn prompt(&self, _action: &mut reminderBot::handlers::action::Action) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status(&self, _action: &reminderBot::handlers::action::Action, _message: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status_message(
+        &self,
+        _channel_id: &str,
+        _user_id: &str,
+        _message: &str,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn webhook_drafts_can_be_confirmed_by_the_discord_user() {
+    use reminderBot::events::queue::EventBus;
+    use reminderBot::handlers::action::{ActionEngine, ActionEvent, ActionStore};
+
+    use_temp_db_location();
+    let (event_bus, mut rx) = EventBus::new(4);
+    let api = http::webhook_routes(event_bus, "s3cret".to_string());
+    let accepted = warp::test::request()
+        .method("POST")
+        .path("/webhooks/notify")
+        .header("authorization", "Bearer s3cret")
+        .json(&serde_json::json!({
+            "text": "deploy finished, check dashboards in 1 hour",
+            "user_id": "42",
+            "channel_id": "99"
+        }))
+        .reply(&api)
+        .await;
+    assert_eq!(accepted.status(), 202);
+
+    let store = Arc::new(Mutex::new(ActionStore::new()));
+    let db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let engine = ActionEngine::new(store.clone(), Arc::new(FakeOpenAI), Arc::new(FakeApprovalPrompt), db.clone());
+    engine.handle_event(rx.try_recv().expect("webhook event queued")).await;
+    let action_id = store.lock().await.ids().into_iter().next().expect("draft exists");
+
+    // The Discord button reports the clicker the same way.
+    engine
+        .handle_event(ActionEvent::ApprovalConfirmed {
+            action_id,
+            user_id: "@42".to_string(),
+        })
+        .await;
+    let db = db.lock().await;
+    let notification = db.values().next().expect("reminder created");
+    assert_eq!(notification.content, "check dashboards");
+    assert_eq!(notification.notify, vec!["@42".to_string()]);
+}
+
+// Knows which guild each channel is in; never actually sends.
+struct GuildLookup(HashMap<&'static str, &'static str>);
+
+#[serenity::async_trait]
+impl reminderBot::tasks::notification_loop::MessageSender for GuildLookup {
+    async fn send_message(&self, _channel_id: &str, _content: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn send_with_components(
+        &self,
+        _channel_id: &str,
+        _content: &str,
+        _components: Vec<serenity::builder::CreateActionRow>,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn guild_for_channel(&self, channel_id: &str) -> Option<String> {
+        self.0.get(channel_id).map(|guild| guild.to_string())
+    }
+}
+
+#[tokio::test]
+async fn integration_reminders_need_a_known_app_and_user_consent() {
+    use reminderBot::config::AppConfig;
+    use reminderBot::models::settings::{self, UserSettings};
+    use reminderBot::service::audit::AuditLog;
+
+    use_temp_db_location();
+    let dir = env::temp_dir().join(format!("reminderbot_integration_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config_path = dir.join("config.properties");
+    std::fs::write(&config_path, "INTEGRATION_APPS=777:apptoken\n").unwrap();
+    let config = AppConfig::from_file(config_path.to_str().unwrap()).unwrap().shared();
+
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let user_settings = Arc::new(Mutex::new(HashMap::<String, UserSettings>::new()));
+    let audit = Arc::new(AuditLog::new(dir.join("audit.log")));
+    let api = http::integration_routes(
+        notification_db.clone(),
+        user_settings.clone(),
+        audit.clone(),
+        config,
+        Arc::new(GuildLookup(HashMap::from([("99", "g1"), ("500", "g2")]))),
+    );
+    let body_for = |channel: &str| {
+        serde_json::json!({
+            "user_id": "@42",
+            "content": "standup",
+            "time": "2030-02-03T09:00:00Z",
+            "channel": channel
+        })
+    };
+    let send_to = |token: &str, channel: &str| {
+        warp::test::request()
+            .method("POST")
+            .path("/integrations/reminders")
+            .header("authorization", format!("Bearer {}", token))
+            .json(&body_for(channel))
+    };
+    let send = |token: &str| send_to(token, "99");
+
+    assert_eq!(send("wrong").reply(&api).await.status(), 401);
+    assert_eq!(send("apptoken").reply(&api).await.status(), 403);
+    assert!(notification_db.lock().await.is_empty());
+
+    settings::set_user_trusted_app(&mut *user_settings.lock().await, "@42", "777", Some("g1"), true).unwrap();
+    // Consent given in one guild doesn't reach channels of another, or ones
+    // whose guild is unknown.
+    assert_eq!(send_to("apptoken", "500").reply(&api).await.status(), 403);
+    assert_eq!(send_to("apptoken", "12345").reply(&api).await.status(), 403);
+    assert!(notification_db.lock().await.is_empty());
+
+    let created = send("apptoken").reply(&api).await;
+    assert_eq!(created.status(), 201);
+    let body: Notification = serde_json::from_slice(created.body()).unwrap();
+    assert_eq!(body.content, "standup");
+    assert_eq!(body.notify, vec!["@42".to_string()]);
+
+    let entries = audit.entries().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].actor, "app:777");
+    assert_eq!(entries[0].user_id, "@42");
+    assert!(entries[0].detail.starts_with(&body.id));
+
+    // Revoking consent stops further reminders.
+    settings::set_user_trusted_app(&mut *user_settings.lock().await, "@42", "777", None, false).unwrap();
+    assert_eq!(send("apptoken").reply(&api).await.status(), 403);
+    assert_eq!(notification_db.lock().await.len(), 1);
+}
+
+#[tokio::test]
+async fn calendar_feed_lists_a_users_reminders() {
+    use_temp_db_location();
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let api = http::routes(notification_db, todo_db);
+
+    for (content, user) in [("pay rent", "@u"), ("water plants", "@other")] {
+        let created = warp::test::request()
+            .method("POST")
+            .path("/notifications")
+            .json(&serde_json::json!({
+                "content": content,
+                "notify": [user],
+                "time": "2030-02-03T12:00:00Z",
+                "channel": "123"
+            }))
+            .reply(&api)
+            .await;
+        assert_eq!(created.status(), 201);
+    }
+
+    let feed = warp::test::request()
+        .method("GET")
+        .path("/calendar.ics?user=@u")
+        .reply(&api)
+        .await;
+    assert_eq!(feed.status(), 200);
+    assert!(feed.headers()["content-type"].to_str().unwrap().starts_with("text/calendar"));
+    let body = std::str::from_utf8(feed.body()).unwrap();
+    assert!(body.contains("SUMMARY:pay rent\r\n"));
+    assert!(body.contains("DTSTART:20300203T120000Z\r\n"));
+    assert!(!body.contains("water plants"));
+}
+
+#[tokio::test]
+async fn feed_urls_serve_one_users_reminders_until_reset() {
+    use_temp_db_location();
+    let notification_db = Arc::new(Mutex::new(HashMap::<String, Notification>::new()));
+    let todo_db = Arc::new(Mutex::new(HashMap::<String, TodoItem>::new()));
+    let user_settings = Arc::new(Mutex::new(HashMap::new()));
+    let api = http::routes(notification_db.clone(), todo_db);
+    let feeds = http::feed_route(notification_db, user_settings.clone());
+
+    let token = settings::user_feed_token(&mut *user_settings.lock().await, "@u", false).unwrap();
+    assert_eq!(settings::user_feed_token(&mut *user_settings.lock().await, "@u", false).unwrap(), token);
+
+    let feed = |path: String| warp::test::request().method("GET").path(&path).reply(&feeds);
+    // The REST API doesn't serve feeds; they have their own listener.
+    assert_eq!(
+        warp::test::request()
+            .method("GET")
+            .path(&format!("/feeds/{}.ics", token))
+            .reply(&api)
+            .await
+            .status(),
+        404
+    );
+    let created = warp::test::request()
+        .method("POST")
+        .path("/notifications")
+        .json(&serde_json::json!({
+            "content": "pay rent",
+            "notify": ["@u"],
+            "time": "2030-02-03T12:00:00Z",
+            "channel": "123"
+        }))
+        .reply(&api)
+        .await;
+    assert_eq!(created.status(), 201);
+
+    // Read from the shared store, so a reminder added later shows up.
+    let reply = feed(format!("/feeds/{}.ics", token)).await;
+    assert_eq!(reply.status(), 200);
+    let body = std::str::from_utf8(reply.body()).unwrap();
+    assert!(body.contains("SUMMARY:pay rent\r\n"));
+    assert!(body.contains("REFRESH-INTERVAL;VALUE=DURATION:PT15M\r\n"));
+
+    let rotated = settings::user_feed_token(&mut *user_settings.lock().await, "@u", true).unwrap();
+    assert_ne!(rotated, token);
+    assert_eq!(feed(format!("/feeds/{}.ics", token)).await.status(), 404);
+    assert_eq!(feed(format!("/feeds/{}.ics", rotated)).await.status(), 200);
+
+    settings::disable_user_feed(&mut *user_settings.lock().await, "@u").unwrap();
+    assert_eq!(feed(format!("/feeds/{}.ics", rotated)).await.status(), 404);
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use reminderBot::models::notification::Notification;
+use reminderBot::models::settings::{AgendaPreference, SummaryPreference, UserSettings};
+use reminderBot::models::todo::TodoItem;
+use reminderBot::tasks::calendar_loop::{CalendarClient, CalendarEvent};
+use reminderBot::service::delivery_log::{DeliveryKind, DeliveryRecord};
+use reminderBot::service::upcoming::UpcomingWindow;
+use reminderBot::tasks::todo_loop::{DmSender, morning_briefing_tick, personal_summaries_tick, weekly_digest_tick};
+use tokio::sync::Mutex;
+
+struct FakeCalendar {
+    events: Vec<CalendarEvent>,
+}
+
+#[serenity::async_trait]
+impl CalendarClient for FakeCalendar {
+    async fn get_events_for_day(&self, day: NaiveDate) -> Result<Vec<CalendarEvent>, String> {
+        Ok(self
+            .events
+            .iter()
+            .filter(|event| event.start_time.date_naive() == day)
+            .cloned()
+            .collect())
+    }
+
+    async fn create_event(&self, _event: CalendarEvent) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct MockDmSender {
+    sent: Mutex<Vec<(String, String)>>,
+}
+
+#[serenity::async_trait]
+impl DmSender for MockDmSender {
+    async fn send_dm(&self, user_id: &str, content: &str) -> Result<(), String> {
+        self.sent
+            .lock()
+            .await
+            .push((user_id.to_string(), content.to_string()));
+        Ok(())
+    }
+}
+
+fn reminder(id: &str, content: &str, user: &str, at: DateTime<Utc>) -> Notification {
+    Notification {
+        id: id.to_string(),
+        content: content.to_string(),
+        notify: vec![user.to_string()],
+        notification_times: vec![at],
+        channel: "123".to_string(),
+        completed_at: None,
+        event_time: Some(at),
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: None,
+        quiet_delayed: false,
+        targets: Vec::new(),
+        delivery_retry: None,
+    }
+}
+
+fn todo(id: &str, user: &str, content: &str, created_at: DateTime<Utc>) -> TodoItem {
+    TodoItem {
+        id: id.to_string(),
+        user_id: user.to_string(),
+        content: content.to_string(),
+        created_at,
+        completed_at: None,
+    }
+}
+
+#[tokio::test]
+async fn morning_briefing_merges_calendar_reminders_and_todos() {
+    // 6:30am and then 7:05am in Los Angeles.
+    let early = Utc.with_ymd_and_hms(2026, 2, 10, 14, 30, 0).unwrap();
+    let now = Utc.with_ymd_and_hms(2026, 2, 10, 15, 5, 0).unwrap();
+    let standup = Utc.with_ymd_and_hms(2026, 2, 10, 17, 0, 0).unwrap();
+
+    let mut settings: HashMap<String, UserSettings> = HashMap::new();
+    settings.insert(
+        "@42".to_string(),
+        UserSettings {
+            user_id: "@42".to_string(),
+            timezone: Some("America/Los_Angeles".to_string()),
+            agenda: Some(AgendaPreference {
+                hour: 7,
+                last_sent: None,
+            }),
+            ..Default::default()
+        },
+    );
+    settings.insert(
+        "@43".to_string(),
+        UserSettings {
+            user_id: "@43".to_string(),
+            ..Default::default()
+        },
+    );
+
+    let mut notifications: HashMap<String, Notification> = HashMap::new();
+    for n in [
+        reminder("n1", "pay rent", "@42", Utc.with_ymd_and_hms(2026, 2, 10, 20, 30, 0).unwrap()),
+        // Mirrored from the calendar; listed once, under Calendar.
+        reminder("n2", "standup", "@42", standup),
+        // Tomorrow, Los Angeles time.
+        reminder("n3", "dentist", "@42", Utc.with_ymd_and_hms(2026, 2, 11, 9, 0, 0).unwrap()),
+        reminder("n4", "someone else's", "@43", standup),
+    ] {
+        notifications.insert(n.id.clone(), n);
+    }
+
+    let mut todos: HashMap<String, TodoItem> = HashMap::new();
+    let mut done = todo("t3", "@42", "already done", early - Duration::days(3));
+    done.completed_at = Some(early);
+    for item in [
+        todo("t1", "@42", "buy milk", early - Duration::days(2)),
+        todo("t2", "@42", "call mom", early - Duration::days(1)),
+        done,
+        todo("t4", "@43", "not yours", early),
+    ] {
+        todos.insert(item.id.clone(), item);
+    }
+
+    let calendar = FakeCalendar {
+        events: vec![CalendarEvent {
+            id: Some("e1".to_string()),
+            title: "standup".to_string(),
+            start_time: standup,
+            end_time: standup + Duration::minutes(30),
+            description: None,
+        }],
+    };
+    let sender = MockDmSender::default();
+
+    let sent = morning_briefing_tick(
+        &todos,
+        &UpcomingWindow::build(&notifications, early),
+        &mut settings,
+        Some((&calendar as &dyn CalendarClient, "@42")),
+        &sender,
+        early,
+    )
+    .await;
+    assert_eq!(sent, 0, "too early in the user's timezone");
+
+    let sent = morning_briefing_tick(
+        &todos,
+        &UpcomingWindow::build(&notifications, now),
+        &mut settings,
+        Some((&calendar as &dyn CalendarClient, "@42")),
+        &sender,
+        now,
+    )
+    .await;
+    assert_eq!(sent, 1, "only the opted-in user gets a briefing");
+    {
+        let dms = sender.sent.lock().await;
+        assert_eq!(dms.len(), 1);
+        assert_eq!(dms[0].0, "42");
+        assert_eq!(
+            dms[0].1,
+            "Good morning! Here's your day:\n\n\
+             **Calendar**\n• 9:00am–9:30am standup\n\n\
+             **Reminders**\n• 12:30pm pay rent\n\n\
+             **Todos**\n1) buy milk\n2) call mom"
+        );
+    }
+    assert_eq!(
+        settings["@42"].agenda.as_ref().unwrap().last_sent,
+        NaiveDate::from_ymd_opt(2026, 2, 10)
+    );
+
+    let sent = morning_briefing_tick(
+        &todos,
+        &UpcomingWindow::build(&notifications, now + Duration::hours(1)),
+        &mut settings,
+        Some((&calendar as &dyn CalendarClient, "@42")),
+        &sender,
+        now + Duration::hours(1),
+    )
+    .await;
+    assert_eq!(sent, 0, "one briefing a day");
+    assert_eq!(sender.sent.lock().await.len(), 1);
+}
+
+#[tokio::test]
+async fn weekly_digest_sums_up_the_week_for_each_user() {
+    // Sunday 6pm in New York.
+    let now = Utc.with_ymd_and_hms(2026, 2, 15, 23, 0, 0).unwrap();
+    let mut dentist = reminder("r1", "Dentist", "@42", now - Duration::days(2));
+    dentist.notification_times.clear();
+    let standup = reminder("r2", "Team standup", "@42", Utc.with_ymd_and_hms(2026, 2, 16, 14, 30, 0).unwrap());
+    let far_off = reminder("r3", "Renew passport", "@42", now + Duration::days(30));
+    let notifications: HashMap<String, Notification> = HashMap::from([
+        ("r1".to_string(), dentist.clone()),
+        ("r2".to_string(), standup),
+        ("r3".to_string(), far_off),
+    ]);
+    let delivered = |at: DateTime<Utc>| DeliveryRecord::new(DeliveryKind::Delivered, &dentist, at);
+    let deliveries = vec![
+        delivered(now - Duration::days(2) - Duration::hours(1)),
+        delivered(now - Duration::days(2)),
+        DeliveryRecord::new(DeliveryKind::Failed, &dentist, now - Duration::days(1)),
+        delivered(now - Duration::days(9)),
+    ];
+    let mut done = todo("t1", "@42", "File taxes", now - Duration::days(20));
+    done.completed_at = Some(now - Duration::days(3));
+    let mut done_long_ago = todo("t2", "@42", "Buy stamps", now - Duration::days(20));
+    done_long_ago.completed_at = Some(now - Duration::days(10));
+    let todos: HashMap<String, TodoItem> = HashMap::from([
+        ("t1".to_string(), done),
+        ("t2".to_string(), done_long_ago),
+        ("t3".to_string(), todo("t3", "@42", "Fix the fence", now - Duration::days(12))),
+        ("t4".to_string(), todo("t4", "@42", "Call the bank", now - Duration::days(1))),
+        ("t5".to_string(), {
+            let mut old = todo("t5", "@43", "Old news", now - Duration::days(40));
+            old.completed_at = Some(now - Duration::days(35));
+            old
+        }),
+    ]);
+
+    let mut settings: HashMap<String, UserSettings> = HashMap::new();
+    let sender = MockDmSender::default();
+    let sent = weekly_digest_tick(&todos, &notifications, &deliveries, &mut settings, &sender, now).await;
+    assert_eq!(sent, 1, "@43 has nothing to report");
+
+    let dms = sender.sent.lock().await;
+    assert_eq!(dms[0].0, "42");
+    assert_eq!(
+        dms[0].1,
+        "Here's your week:\n\n**Reminded this week**\n• Dentist\n\n**Todos done**\n• File taxes\n\n**Overdue**\n• Fix the fence\n\n**Coming up next week**\n• Mon 9:30am Team standup"
+    );
+}
+
+#[tokio::test]
+async fn personal_summary_goes_out_at_the_users_own_time_once_a_day() {
+    // 7:30am and then 8:05am in Los Angeles.
+    let early = Utc.with_ymd_and_hms(2026, 2, 10, 15, 30, 0).unwrap();
+    let now = Utc.with_ymd_and_hms(2026, 2, 10, 16, 5, 0).unwrap();
+    let mut settings: HashMap<String, UserSettings> = HashMap::new();
+    for (user_id, time) in [("@42", NaiveTime::from_hms_opt(8, 0, 0)), ("@43", None)] {
+        settings.insert(
+            user_id.to_string(),
+            UserSettings {
+                user_id: user_id.to_string(),
+                timezone: Some("America/Los_Angeles".to_string()),
+                summary: Some(SummaryPreference { time, last_sent: None }),
+                ..Default::default()
+            },
+        );
+    }
+    let todos: HashMap<String, TodoItem> = HashMap::from([
+        ("t1".to_string(), todo("t1", "@42", "buy milk", early - Duration::days(1))),
+        ("t2".to_string(), todo("t2", "@43", "opted out", early - Duration::days(1))),
+    ]);
+    let sender = MockDmSender::default();
+
+    assert_eq!(personal_summaries_tick(&todos, &mut settings, &sender, early).await, 0);
+    assert_eq!(personal_summaries_tick(&todos, &mut settings, &sender, now).await, 1);
+    assert_eq!(
+        *sender.sent.lock().await,
+        vec![(
+            "42".to_string(),
+            "Good morning! Here is your current todo list:\n1) buy milk".to_string()
+        )]
+    );
+    assert_eq!(
+        settings["@42"].summary.as_ref().unwrap().last_sent,
+        NaiveDate::from_ymd_opt(2026, 2, 10)
+    );
+
+    assert_eq!(
+        personal_summaries_tick(&todos, &mut settings, &sender, now + Duration::hours(3)).await,
+        0,
+        "one summary a day"
+    );
+    assert_eq!(sender.sent.lock().await.len(), 1);
+}
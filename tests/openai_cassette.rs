@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use reminderBot::clients::cassette::Cassette;
+use reminderBot::events::queue::EventBus;
+use reminderBot::events::worker::run_event_worker;
+use reminderBot::handlers::action::{Action, ActionEngine, ActionStore};
+use reminderBot::handlers::discord::BotHandler;
+use reminderBot::models::notification::Notification;
+use reminderBot::models::todo::TodoItem;
+use reminderBot::service::approval_prompt::ApprovalPromptService;
+use reminderBot::service::openai_service::{CassetteMode, OpenAIClient, OpenAIService};
+use reminderBot::service::routing::OpenAIRouter;
+use tokio::sync::Mutex;
+use tokio::time::{sleep, timeout, Duration};
+
+const FIXTURE: &str = "tests/fixtures/openai_cassette.json";
+
+struct CapturingApprovalPrompt {
+    drafts: Mutex<Vec<String>>,
+}
+
+#[serenity::async_trait]
+impl ApprovalPromptService for CapturingApprovalPrompt {
+    async fn prompt(&self, action: &mut Action) -> Result<(), String> {
+        if let Some(draft) = action.notification_draft() {
+            self.drafts.lock().await.push(format!("{} @ {}", draft.content, draft.time));
+        }
+        Ok(())
+    }
+
+    async fn update_status(&self, _action: &Action, _message: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn update_status_message(
+        &self,
+        _channel_id: &str,
+        _user_id: &str,
+        _message: &str,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn replayed_cassette_drives_notify_pipeline() {
+    let openai: Arc<dyn OpenAIClient> = Arc::new(
+        OpenAIService::with_cassette("unused".to_string(), CassetteMode::Replay, FIXTURE)
+            .expect("fixture loads"),
+    );
+    let approval = Arc::new(CapturingApprovalPrompt {
+        drafts: Mutex::new(Vec::new()),
+    });
+    let engine = ActionEngine::new(
+        Arc::new(Mutex::new(ActionStore::new())),
+        openai.clone(),
+        approval.clone(),
+        Arc::new(Mutex::new(HashMap::<String, Notification>::new())),
+    );
+    let (bus, rx) = EventBus::new(8);
+    let worker = tokio::spawn(run_event_worker(rx, engine));
+
+    let handler = BotHandler::new(
+        Arc::new(Mutex::new(HashMap::<String, TodoItem>::new())),
+        bus,
+        Arc::new(Mutex::new(HashMap::new())),
+        Arc::new(OpenAIRouter::new(openai)),
+    );
+    handler
+        .handle_notify_internal("call mom tomorrow at 5", "@u", "123", None)
+        .await;
+
+    let draft = timeout(Duration::from_secs(2), async {
+        loop {
+            if let Some(draft) = approval.drafts.lock().await.first().cloned() {
+                break draft;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("draft from replayed responses");
+    assert_eq!(draft, "call mom @ 2026-02-03 22:00:00 UTC");
+
+    drop(handler);
+    worker.await.unwrap();
+}
+
+#[tokio::test]
+async fn replay_fails_loudly_on_unrecorded_prompt() {
+    let openai = OpenAIService::with_cassette("unused".to_string(), CassetteMode::Replay, FIXTURE)
+        .expect("fixture loads");
+    let err = openai
+        .generate_prompt("something never recorded", "notification")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("OPENAI_CASSETTE=record"));
+}
+
+#[test]
+fn recorded_interactions_survive_reload() {
+    let path = std::env::temp_dir()
+        .join(format!("reminderbot_cassette_{}", uuid::Uuid::new_v4()))
+        .join("cassette.json");
+    let mut cassette = Cassette::load(&path).expect("missing file is empty");
+    assert!(cassette.lookup("notification", "buy milk").is_none());
+    cassette
+        .record("notification", "buy milk", "{\"content\":\"buy milk\"}")
+        .expect("record writes file");
+
+    let reloaded = Cassette::load(&path).expect("reload");
+    assert_eq!(
+        reloaded.lookup("notification", "buy milk"),
+        Some("{\"content\":\"buy milk\"}")
+    );
+}
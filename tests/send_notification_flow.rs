@@ -1,10 +1,17 @@
 use std::collections::HashMap;
 use std::env;
-use std::sync::{Mutex, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock};
 
 use chrono::TimeZone;
-use reminderBot::models::notification::Notification;
-use reminderBot::tasks::notification_loop::{notification_tick, MessageSender};
+use reminderBot::models::notification::{DeliveryTarget, MAX_DELIVERY_ATTEMPTS, Notification};
+use reminderBot::service::delivery_channel::{NotificationChannel, OutgoingMessage, webhook_body};
+use reminderBot::service::delivery_log::{DeliveryKind, DeliveryLog};
+use reminderBot::service::latency::{LatencyKind, LatencySlo, LatencyTracker};
+use reminderBot::tasks::latency_loop::latency_alert_tick;
+use reminderBot::tasks::notification_loop::{
+    notification_tick, notification_tick_logged, notification_tick_with_catch_up, notification_tick_with_channels,
+    notification_tick_with_latency, CatchUpPolicy, MessageSender, RateLimitedSender,
+};
 use reminderBot::service::openai_service::OpenAIClient;
 use tokio::sync::Mutex as TokioMutex;
 
@@ -28,6 +35,18 @@ impl OpenAIClient for FakeOpenAI {
 
 struct MockSender {
     sent: TokioMutex<Vec<(String, String)>>,
+    components: TokioMutex<Vec<String>>,
+    guild: Option<String>,
+}
+
+impl MockSender {
+    fn new() -> Self {
+        Self {
+            sent: TokioMutex::new(Vec::new()),
+            components: TokioMutex::new(Vec::new()),
+            guild: None,
+        }
+    }
 }
 
 #[serenity::async_trait]
@@ -37,6 +56,20 @@ impl MessageSender for MockSender {
         sent.push((channel_id.to_string(), content.to_string()));
         Ok(())
     }
+
+    async fn send_with_components(
+        &self,
+        channel_id: &str,
+        content: &str,
+        components: Vec<serenity::builder::CreateActionRow>,
+    ) -> Result<(), String> {
+        self.components.lock().await.push(format!("{:?}", components));
+        self.send_message(channel_id, content).await
+    }
+
+    async fn guild_for_channel(&self, _channel_id: &str) -> Option<String> {
+        self.guild.clone()
+    }
 }
 
 static ENV_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
@@ -59,17 +92,24 @@ async fn notification_tick_sends_and_expires_notification() {
             notify: vec!["@u".to_string()],
             notification_times: vec![now - chrono::Duration::minutes(1)],
             channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
         },
     );
 
     let openai = FakeOpenAI {
         response: Ok("Remember to call mom at noon.".to_string()),
     };
-    let sender = MockSender {
-        sent: TokioMutex::new(Vec::new()),
-    };
+    let sender = MockSender::new();
 
-    notification_tick(&mut db, &sender, &openai, now)
+    notification_tick(&mut db, &mut HashMap::new(), &HashMap::new(), &sender, &openai, now)
         .await
         .expect("tick should succeed");
 
@@ -79,3 +119,1261 @@ async fn notification_tick_sends_and_expires_notification() {
     assert_eq!(sent[0].0, "123");
     assert!(sent[0].1.contains("Remember to call mom at noon."));
 }
+
+#[tokio::test]
+async fn notification_tick_offers_done_buttons_when_more_times_remain() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    db.insert(
+        "r1".to_string(),
+        Notification {
+            id: "r1".to_string(),
+            content: "pay rent".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![
+                now - chrono::Duration::minutes(1),
+                now + chrono::Duration::hours(23),
+            ],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+
+    let openai = FakeOpenAI {
+        response: Ok("Rent is due tomorrow.".to_string()),
+    };
+    let sender = MockSender::new();
+
+    notification_tick(&mut db, &mut HashMap::new(), &HashMap::new(), &sender, &openai, now)
+        .await
+        .expect("tick should succeed");
+
+    assert_eq!(db.get("r1").unwrap().notification_times.len(), 1);
+    let components = sender.components.lock().await;
+    assert_eq!(components.len(), 1);
+    assert!(components[0].contains("reminder_done:r1"));
+    assert!(components[0].contains("reminder_dismiss:r1"));
+}
+
+#[tokio::test]
+async fn notification_tick_holds_paused_recurring_reminder() {
+    use reminderBot::models::recurrence::{Frequency, Recurrence};
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let event_time = now + chrono::Duration::minutes(30);
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    db.insert(
+        "r1".to_string(),
+        Notification {
+            id: "r1".to_string(),
+            content: "water plants".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![event_time - chrono::Duration::hours(1)],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: Some(event_time),
+            recurrence: Some(Recurrence::new(Frequency::Daily)),
+            paused: true,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+
+    let openai = FakeOpenAI {
+        response: Ok("Water the plants.".to_string()),
+    };
+    let sender = MockSender::new();
+
+    notification_tick(&mut db, &mut HashMap::new(), &HashMap::new(), &sender, &openai, now)
+        .await
+        .expect("tick should succeed");
+
+    assert!(sender.sent.lock().await.is_empty());
+    let notification = db.get("r1").expect("paused reminder is kept");
+    assert!(notification.paused);
+    assert!(notification.recurrence.is_some());
+    assert_eq!(notification.event_time, Some(event_time + chrono::Duration::days(1)));
+    assert!(notification.notification_times.iter().all(|t| *t > now));
+}
+
+#[tokio::test]
+async fn paused_reminders_keep_their_remaining_occurrences() {
+    use reminderBot::models::recurrence::{Frequency, Recurrence};
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let mut recurrence = Recurrence::new(Frequency::Daily);
+    recurrence.count = Some(2);
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    db.insert(
+        "r1".to_string(),
+        Notification {
+            id: "r1".to_string(),
+            content: "water plants".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![now - chrono::Duration::hours(1)],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: Some(now - chrono::Duration::minutes(30)),
+            recurrence: Some(recurrence),
+            paused: true,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+
+    let openai = FakeOpenAI {
+        response: Ok("Water the plants.".to_string()),
+    };
+    let sender = MockSender::new();
+    // A week asleep would have used up both occurrences.
+    for day in 0..7 {
+        let at = now + chrono::Duration::days(day);
+        notification_tick(&mut db, &mut HashMap::new(), &HashMap::new(), &sender, &openai, at)
+            .await
+            .expect("tick should succeed");
+    }
+
+    assert!(sender.sent.lock().await.is_empty());
+    let notification = db.get("r1").expect("paused reminder is kept");
+    assert_eq!(notification.recurrence.as_ref().unwrap().count, Some(2));
+    assert!(!notification.notification_times.is_empty());
+}
+
+#[tokio::test]
+async fn notification_tick_applies_category_style() {
+    use reminderBot::models::settings::{CategoryStyle, UserSettings};
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    db.insert(
+        "r1".to_string(),
+        Notification {
+            id: "r1".to_string(),
+            content: "pay rent".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![now - chrono::Duration::minutes(1)],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+    let mut settings: HashMap<String, UserSettings> = HashMap::new();
+    let mut user = UserSettings {
+        user_id: "@u".to_string(),
+        ..Default::default()
+    };
+    user.styles.insert(
+        "bill".to_string(),
+        CategoryStyle {
+            emoji: Some("💸".to_string()),
+            color: Some(0xff8800),
+            gif_url: None,
+        },
+    );
+    settings.insert("@u".to_string(), user);
+
+    let openai = FakeOpenAI {
+        response: Ok("Rent is due.".to_string()),
+    };
+    let sender = MockSender::new();
+
+    notification_tick(&mut db, &mut settings, &HashMap::new(), &sender, &openai, now)
+        .await
+        .expect("tick should succeed");
+
+    let sent = sender.sent.lock().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].1, "💸 Rent is due.");
+}
+
+struct MockDmSender {
+    sent: TokioMutex<Vec<(String, String)>>,
+}
+
+#[serenity::async_trait]
+impl reminderBot::tasks::todo_loop::DmSender for MockDmSender {
+    async fn send_dm(&self, user_id: &str, content: &str) -> Result<(), String> {
+        self.sent
+            .lock()
+            .await
+            .push((user_id.to_string(), content.to_string()));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn focus_mode_holds_reminders_until_digest() {
+    use reminderBot::models::settings::{set_user_focus, UserSettings};
+    use reminderBot::tasks::focus_loop::focus_digest_tick;
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let reminder = |id: &str, content: &str| Notification {
+        id: id.to_string(),
+        content: content.to_string(),
+        notify: vec!["@42".to_string()],
+        notification_times: vec![now - chrono::Duration::minutes(1)],
+        channel: "123".to_string(),
+        completed_at: None,
+        event_time: None,
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: None,
+        quiet_delayed: false,
+        targets: Vec::new(),
+        delivery_retry: None,
+    };
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    db.insert("r1".to_string(), reminder("r1", "water the plants"));
+    db.insert("r2".to_string(), reminder("r2", "take my medication"));
+
+    let mut settings: HashMap<String, UserSettings> = HashMap::new();
+    set_user_focus(&mut settings, "@42", now + chrono::Duration::hours(1))
+        .expect("focus should save");
+
+    let openai = FakeOpenAI {
+        response: Err("offline".to_string()),
+    };
+    let sender = MockSender::new();
+    notification_tick(&mut db, &mut settings, &HashMap::new(), &sender, &openai, now)
+        .await
+        .expect("tick should succeed");
+
+    let sent = sender.sent.lock().await;
+    assert_eq!(sent.len(), 1, "only the health reminder gets through");
+    assert!(sent[0].1.contains("medication"));
+    drop(sent);
+
+    let dms = MockDmSender {
+        sent: TokioMutex::new(Vec::new()),
+    };
+    focus_digest_tick(&mut settings, &dms, now)
+        .await
+        .expect("digest tick should succeed");
+    assert!(dms.sent.lock().await.is_empty(), "focus is still running");
+
+    focus_digest_tick(&mut settings, &dms, now + chrono::Duration::hours(2))
+        .await
+        .expect("digest tick should succeed");
+    let digests = dms.sent.lock().await;
+    assert_eq!(digests.len(), 1);
+    assert_eq!(digests[0].0, "42");
+    assert!(digests[0].1.contains("plants"));
+    assert!(settings["@42"].focus.is_none());
+}
+
+struct FailingDmSender;
+
+#[serenity::async_trait]
+impl reminderBot::tasks::todo_loop::DmSender for FailingDmSender {
+    async fn send_dm(&self, _user_id: &str, _content: &str) -> Result<(), String> {
+        Err("DMs closed".to_string())
+    }
+}
+
+#[tokio::test]
+async fn a_focus_digest_that_fails_to_send_is_kept_for_the_next_tick() {
+    use reminderBot::models::settings::{hold_for_focus, set_user_focus, UserSettings};
+    use reminderBot::tasks::focus_loop::focus_digest_tick;
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let mut settings: HashMap<String, UserSettings> = HashMap::new();
+    set_user_focus(&mut settings, "@42", now + chrono::Duration::hours(1))
+        .expect("focus should save");
+    assert!(hold_for_focus(&mut settings, "@42", "water the plants", now));
+
+    let later = now + chrono::Duration::hours(2);
+    focus_digest_tick(&mut settings, &FailingDmSender, later)
+        .await
+        .expect("digest tick should succeed");
+    let focus = settings["@42"].focus.as_ref().expect("digest should be kept");
+    assert_eq!(focus.held, vec!["water the plants".to_string()]);
+
+    let dms = MockDmSender {
+        sent: TokioMutex::new(Vec::new()),
+    };
+    focus_digest_tick(&mut settings, &dms, later + chrono::Duration::minutes(1))
+        .await
+        .expect("digest tick should succeed");
+    assert!(dms.sent.lock().await[0].1.contains("plants"));
+    assert!(settings["@42"].focus.is_none());
+}
+
+#[tokio::test]
+async fn notification_tick_uses_the_guild_voice_for_fallback_messages() {
+    use reminderBot::models::settings::{GuildSettings, Voice};
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let due = now - chrono::Duration::minutes(1);
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    db.insert(
+        "r1".to_string(),
+        Notification {
+            id: "r1".to_string(),
+            content: "call mom".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![due],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: None,
+            recurrence: None,
+            paused: false,
+            checklist: Vec::new(),
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+    let mut guilds: HashMap<String, GuildSettings> = HashMap::new();
+    guilds.insert(
+        "g1".to_string(),
+        GuildSettings {
+            guild_id: "g1".to_string(),
+            voice: Voice::Terse,
+            ..Default::default()
+        },
+    );
+
+    let openai = FakeOpenAI {
+        response: Err("offline".to_string()),
+    };
+    let mut sender = MockSender::new();
+    sender.guild = Some("g1".to_string());
+
+    notification_tick(&mut db, &mut HashMap::new(), &guilds, &sender, &openai, now)
+        .await
+        .expect("tick should succeed");
+
+    let sent = sender.sent.lock().await;
+    assert_eq!(sent[0].1, format!("call mom — {}", due));
+}
+
+#[tokio::test]
+async fn checklist_reminders_stay_until_the_event_then_summarise() {
+    use reminderBot::models::notification::ChecklistItem;
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let item = |text: &str| ChecklistItem {
+        text: text.to_string(),
+        done: false,
+    };
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    db.insert(
+        "r1".to_string(),
+        Notification {
+            id: "r1".to_string(),
+            content: "trip".to_string(),
+            notify: vec!["@u".to_string()],
+            notification_times: vec![now - chrono::Duration::minutes(1)],
+            channel: "123".to_string(),
+            completed_at: None,
+            event_time: Some(now + chrono::Duration::minutes(30)),
+            recurrence: None,
+            paused: false,
+            checklist: vec![item("passport"), item("charger")],
+            ack: None,
+            quiet_delayed: false,
+            targets: Vec::new(),
+            delivery_retry: None,
+        },
+    );
+
+    let openai = FakeOpenAI {
+        response: Ok("Your trip starts soon.".to_string()),
+    };
+    let sender = MockSender::new();
+
+    notification_tick(&mut db, &mut HashMap::new(), &HashMap::new(), &sender, &openai, now)
+        .await
+        .expect("tick should succeed");
+    assert_eq!(
+        sender.sent.lock().await[0].1,
+        "Your trip starts soon.\n☐ passport\n☐ charger"
+    );
+    assert!(sender.components.lock().await[0].contains("checklist:r1:1"));
+    assert!(db.contains_key("r1"), "kept so the checklist can still be ticked");
+
+    db.get_mut("r1").unwrap().checklist[0].done = true;
+    notification_tick(
+        &mut db,
+        &mut HashMap::new(),
+        &HashMap::new(),
+        &sender,
+        &openai,
+        now + chrono::Duration::minutes(10),
+    )
+    .await
+    .expect("tick should succeed");
+    assert_eq!(sender.sent.lock().await.len(), 1);
+
+    notification_tick(
+        &mut db,
+        &mut HashMap::new(),
+        &HashMap::new(),
+        &sender,
+        &openai,
+        now + chrono::Duration::hours(1),
+    )
+    .await
+    .expect("tick should succeed");
+    assert!(db.is_empty());
+    assert_eq!(
+        sender.sent.lock().await[1].1,
+        "Checklist for 'trip': 1/2 done. Still open: charger"
+    );
+}
+
+#[tokio::test]
+async fn must_ack_reminders_escalate_when_left_unacknowledged() {
+    use reminderBot::models::notification::{AckRequirement, AckState, complete_notification};
+    use reminderBot::models::settings::GuildSettings;
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let reminder = |id: &str| Notification {
+        id: id.to_string(),
+        content: format!("take meds {}", id),
+        notify: vec!["@u".to_string()],
+        notification_times: vec![now - chrono::Duration::minutes(1)],
+        channel: "123".to_string(),
+        completed_at: None,
+        event_time: Some(now),
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: Some(AckRequirement {
+            backup: Some("@carer".to_string()),
+            window_minutes: Some(15),
+            state: AckState::Pending,
+        }),
+        quiet_delayed: false,
+        targets: Vec::new(),
+        delivery_retry: None,
+    };
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    db.insert("r1".to_string(), reminder("r1"));
+    db.insert("r2".to_string(), reminder("r2"));
+    let mut guilds: HashMap<String, GuildSettings> = HashMap::new();
+    guilds.insert(
+        "g1".to_string(),
+        GuildSettings {
+            guild_id: "g1".to_string(),
+            escalation_channel: Some("999".to_string()),
+            ..Default::default()
+        },
+    );
+
+    let openai = FakeOpenAI {
+        response: Ok("Time for your meds.".to_string()),
+    };
+    let mut sender = MockSender::new();
+    sender.guild = Some("g1".to_string());
+
+    notification_tick(&mut db, &mut HashMap::new(), &guilds, &sender, &openai, now)
+        .await
+        .expect("tick should succeed");
+    assert_eq!(sender.sent.lock().await.len(), 2);
+    assert!(sender.components.lock().await[0].contains("reminder_done:"));
+    assert!(
+        matches!(db["r1"].ack.as_ref().unwrap().state, AckState::Awaiting { .. }),
+        "kept while waiting for an acknowledgement"
+    );
+
+    complete_notification(&mut db, "r2", "@u", now + chrono::Duration::minutes(5))
+        .expect("ack should save");
+    notification_tick(
+        &mut db,
+        &mut HashMap::new(),
+        &guilds,
+        &sender,
+        &openai,
+        now + chrono::Duration::minutes(10),
+    )
+    .await
+    .expect("tick should succeed");
+    assert!(!db.contains_key("r2"), "acknowledged reminders expire quietly");
+    assert!(db.contains_key("r1"));
+    assert_eq!(sender.sent.lock().await.len(), 2);
+
+    notification_tick(
+        &mut db,
+        &mut HashMap::new(),
+        &guilds,
+        &sender,
+        &openai,
+        now + chrono::Duration::minutes(16),
+    )
+    .await
+    .expect("tick should succeed");
+    let sent = sender.sent.lock().await;
+    assert_eq!(sent.len(), 3);
+    assert_eq!(sent[2].0, "999");
+    assert_eq!(
+        sent[2].1,
+        format!(
+            "@carer — @u hasn't acknowledged 'take meds r1' (last reminded <t:{}:R>).",
+            now.timestamp()
+        )
+    );
+    assert!(db.is_empty());
+}
+
+#[tokio::test]
+async fn late_deliveries_alert_the_operator_channel() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    for i in 0..20 {
+        let id = format!("r{}", i);
+        db.insert(
+            id.clone(),
+            Notification {
+                id,
+                content: "call mom".to_string(),
+                notify: vec!["@u".to_string()],
+                notification_times: vec![now - chrono::Duration::minutes(2)],
+                channel: "123".to_string(),
+                completed_at: None,
+                event_time: None,
+                recurrence: None,
+                paused: false,
+                checklist: Vec::new(),
+                ack: None,
+                quiet_delayed: false,
+                targets: Vec::new(),
+                delivery_retry: None,
+            },
+        );
+    }
+    let openai = FakeOpenAI {
+        response: Ok("Remember to call mom.".to_string()),
+    };
+    let sender = MockSender::new();
+    let latency = LatencyTracker::new();
+
+    notification_tick_with_latency(
+        &mut db,
+        &mut HashMap::new(),
+        &HashMap::new(),
+        &sender,
+        &openai,
+        now,
+        Some(&latency),
+    )
+    .await
+    .expect("tick should succeed");
+    assert!(latency.p95(LatencyKind::Delivery).unwrap() >= chrono::Duration::minutes(2));
+
+    let slo = LatencySlo {
+        prompt_p95: chrono::Duration::seconds(15),
+        delivery_p95: chrono::Duration::seconds(60),
+    };
+    latency_alert_tick(&latency, &sender, &slo, Some("999"), now).await;
+    latency_alert_tick(&latency, &sender, &slo, Some("999"), now).await;
+
+    let sent = sender.sent.lock().await;
+    let alerts: Vec<&(String, String)> = sent.iter().filter(|(channel, _)| channel == "999").collect();
+    assert_eq!(alerts.len(), 1);
+    assert!(alerts[0].1.contains("Reminder delivery p95"));
+}
+
+struct FailingSender;
+
+#[serenity::async_trait]
+impl MessageSender for FailingSender {
+    async fn send_message(&self, _channel_id: &str, _content: &str) -> Result<(), String> {
+        Err("Missing Access".to_string())
+    }
+}
+
+#[tokio::test]
+async fn logged_ticks_report_deliveries_failures_and_expiry() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+    let log = DeliveryLog::new(temp_dir.join("deliveries.log"));
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let reminder = Notification {
+        id: "r1".to_string(),
+        content: "call mom".to_string(),
+        notify: vec!["@u".to_string()],
+        notification_times: vec![now - chrono::Duration::minutes(1)],
+        channel: "123".to_string(),
+        completed_at: None,
+        event_time: None,
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: None,
+        quiet_delayed: false,
+        targets: Vec::new(),
+        delivery_retry: None,
+    };
+    let openai = FakeOpenAI {
+        response: Ok("Remember to call mom.".to_string()),
+    };
+
+    let mut db: HashMap<String, Notification> = HashMap::from([("r1".to_string(), reminder.clone())]);
+    let failed = notification_tick_logged(
+        &mut db,
+        &mut HashMap::new(),
+        &HashMap::new(),
+        &FailingSender,
+        &openai,
+        now,
+        None,
+        Some(&log),
+    )
+    .await;
+    assert_eq!(failed.unwrap_err(), "Missing Access");
+    assert_eq!(db["r1"].notification_times.len(), 1);
+    assert_eq!(db["r1"].delivery_retry.as_ref().map(|retry| retry.attempts), Some(1));
+
+    notification_tick_logged(
+        &mut db,
+        &mut HashMap::new(),
+        &HashMap::new(),
+        &MockSender::new(),
+        &openai,
+        now + chrono::Duration::minutes(1),
+        None,
+        Some(&log),
+    )
+    .await
+    .expect("tick should succeed");
+    assert!(db.is_empty());
+
+    let (records, _) = log.read_from(0).unwrap();
+    let kinds: Vec<DeliveryKind> = records.iter().map(|record| record.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DeliveryKind::Attempt,
+            DeliveryKind::Failed,
+            DeliveryKind::Attempt,
+            DeliveryKind::Delivered,
+            DeliveryKind::Expired,
+        ]
+    );
+    assert_eq!(records[1].detail.as_deref(), Some("Missing Access"));
+    assert!(records.iter().all(|record| record.notification_id == "r1"));
+}
+
+struct PerChannelSender {
+    sent: TokioMutex<Vec<String>>,
+}
+
+#[serenity::async_trait]
+impl MessageSender for PerChannelSender {
+    async fn send_message(&self, channel_id: &str, _content: &str) -> Result<(), String> {
+        match channel_id {
+            "dead" => Err("Unknown Channel".to_string()),
+            "flaky" => Err("Missing Access".to_string()),
+            _ => {
+                self.sent.lock().await.push(channel_id.to_string());
+                Ok(())
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn failed_pings_back_off_without_holding_up_other_reminders() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let reminder = |id: &str, channel: &str| Notification {
+        id: id.to_string(),
+        content: format!("{} reminder", id),
+        notify: vec!["@u".to_string()],
+        notification_times: vec![now - chrono::Duration::minutes(1)],
+        channel: channel.to_string(),
+        completed_at: None,
+        event_time: None,
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: None,
+        quiet_delayed: false,
+        targets: Vec::new(),
+        delivery_retry: None,
+    };
+    let mut db: HashMap<String, Notification> = HashMap::from([
+        ("ok".to_string(), reminder("ok", "ok")),
+        ("dead".to_string(), reminder("dead", "dead")),
+        ("flaky".to_string(), reminder("flaky", "flaky")),
+    ]);
+    let openai = FakeOpenAI {
+        response: Ok("Heads up.".to_string()),
+    };
+    let sender = PerChannelSender {
+        sent: TokioMutex::new(Vec::new()),
+    };
+
+    let failed = notification_tick(&mut db, &mut HashMap::new(), &HashMap::new(), &sender, &openai, now).await;
+    assert_eq!(failed.unwrap_err(), "Missing Access");
+    assert_eq!(*sender.sent.lock().await, vec!["ok".to_string()]);
+    // A channel that no longer exists is given up on straight away.
+    assert!(!db.contains_key("ok") && !db.contains_key("dead"));
+    let retry = db["flaky"].delivery_retry.clone().expect("retry recorded");
+    assert_eq!(retry.attempts, 1);
+    assert_eq!(retry.next_attempt, now + chrono::Duration::minutes(1));
+
+    // Nothing is tried again before the backoff runs out.
+    notification_tick(&mut db, &mut HashMap::new(), &HashMap::new(), &sender, &openai, now + chrono::Duration::seconds(30))
+        .await
+        .expect("nothing due yet");
+    assert_eq!(db["flaky"].delivery_retry.as_ref().map(|retry| retry.attempts), Some(1));
+
+    let mut at = retry.next_attempt;
+    while let Some(retry) = db.get("flaky").and_then(|n| n.delivery_retry.clone()) {
+        assert!(retry.attempts < MAX_DELIVERY_ATTEMPTS);
+        at = retry.next_attempt;
+        let _ = notification_tick(&mut db, &mut HashMap::new(), &HashMap::new(), &sender, &openai, at).await;
+    }
+    assert!(db.is_empty());
+    assert_eq!(at - now, chrono::Duration::minutes(1 + 2 + 4 + 8 + 16));
+}
+
+#[tokio::test]
+async fn quiet_hours_push_pings_to_the_end_of_the_window() {
+    use reminderBot::models::settings::{set_user_quiet_hours, set_user_timezone, QuietHours, UserSettings};
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 3, 0, 0).unwrap();
+    let morning = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 7, 0, 0).unwrap();
+    let reminder = |id: &str, content: &str| Notification {
+        id: id.to_string(),
+        content: content.to_string(),
+        notify: vec!["@42".to_string()],
+        notification_times: vec![
+            now - chrono::Duration::minutes(1),
+            now + chrono::Duration::minutes(30),
+            now + chrono::Duration::hours(10),
+        ],
+        channel: "123".to_string(),
+        completed_at: None,
+        event_time: None,
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: None,
+        quiet_delayed: false,
+        targets: Vec::new(),
+        delivery_retry: None,
+    };
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    db.insert("r1".to_string(), reminder("r1", "water the plants"));
+    db.insert("r2".to_string(), reminder("r2", "take my medication"));
+
+    let mut settings: HashMap<String, UserSettings> = HashMap::new();
+    set_user_timezone(&mut settings, "@42", "UTC").expect("timezone should save");
+    let window = QuietHours {
+        start: chrono::NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+        end: chrono::NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+    };
+    set_user_quiet_hours(&mut settings, "@42", Some(window)).expect("quiet hours should save");
+
+    let openai = FakeOpenAI {
+        response: Err("offline".to_string()),
+    };
+    let sender = MockSender::new();
+    notification_tick(&mut db, &mut settings, &HashMap::new(), &sender, &openai, now)
+        .await
+        .expect("tick should succeed");
+    {
+        let sent = sender.sent.lock().await;
+        assert_eq!(sent.len(), 1, "only the health reminder goes out at night");
+        assert!(sent[0].1.contains("medication"));
+    }
+    assert_eq!(
+        db["r1"].notification_times,
+        vec![morning, now + chrono::Duration::hours(10)],
+        "the 03:30 ping is folded into the 07:00 one"
+    );
+    assert!(db["r1"].quiet_delayed);
+
+    let later = morning + chrono::Duration::seconds(30);
+    notification_tick(&mut db, &mut settings, &HashMap::new(), &sender, &openai, later)
+        .await
+        .expect("tick should succeed");
+    let sent = sender.sent.lock().await;
+    assert_eq!(sent.len(), 3);
+    let plants = sent.iter().find(|(_, body)| body.contains("plants")).expect("plants reminder sent");
+    assert!(plants.1.ends_with("(delayed due to quiet hours)"), "{}", plants.1);
+    assert!(!db["r1"].quiet_delayed);
+}
+
+#[tokio::test]
+async fn missed_pings_after_downtime_go_out_once_and_say_how_late() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let hours = chrono::Duration::hours;
+    let reminder = |id: &str, times: Vec<chrono::DateTime<chrono::Utc>>, event| Notification {
+        id: id.to_string(),
+        content: id.to_string(),
+        notify: vec!["@42".to_string()],
+        notification_times: times,
+        channel: "123".to_string(),
+        completed_at: None,
+        event_time: Some(event),
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: None,
+        quiet_delayed: false,
+        targets: Vec::new(),
+        delivery_retry: None,
+    };
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    db.insert(
+        "standup".to_string(),
+        reminder("standup", vec![now - hours(3), now - hours(2), now + hours(1)], now + hours(2)),
+    );
+    db.insert("breakfast".to_string(), reminder("breakfast", vec![now - hours(4)], now - hours(3)));
+
+    let openai = FakeOpenAI {
+        response: Err("offline".to_string()),
+    };
+    let sender = MockSender::new();
+    let policy = CatchUpPolicy {
+        skip_passed_events: true,
+        ..CatchUpPolicy::default()
+    };
+    notification_tick_with_catch_up(
+        &mut db,
+        &mut HashMap::new(),
+        &HashMap::new(),
+        &sender,
+        &openai,
+        now,
+        None,
+        None,
+        &policy,
+    )
+    .await
+    .expect("tick should succeed");
+
+    let sent = sender.sent.lock().await;
+    assert_eq!(sent.len(), 1, "two missed pings fold into one, the finished event is skipped: {:?}", sent);
+    let due = (now - hours(3)).timestamp();
+    assert!(sent[0].1.ends_with(&format!("(this was due <t:{}:R>)", due)), "{}", sent[0].1);
+    assert_eq!(db["standup"].notification_times, vec![now + hours(1)]);
+    assert!(!db.contains_key("breakfast"));
+}
+
+struct FlakySender {
+    rate_limited_for: usize,
+    error: &'static str,
+    attempts: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+#[serenity::async_trait]
+impl MessageSender for FlakySender {
+    async fn send_message(&self, _channel_id: &str, _content: &str) -> Result<(), String> {
+        let attempt = self.attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        if attempt <= self.rate_limited_for {
+            return Err(self.error.to_string());
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn rate_limited_sender_paces_sends_and_retries_429s() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    let attempts = std::sync::Arc::new(AtomicUsize::new(0));
+    let flaky = |rate_limited_for, error| FlakySender {
+        rate_limited_for,
+        error,
+        attempts: attempts.clone(),
+    };
+    let fast_retries = Duration::from_millis(5);
+
+    let sender = RateLimitedSender::new(flaky(2, "rate limited: 429 Too Many Requests"))
+        .with_rate(1000)
+        .with_retries(3, fast_retries);
+    sender.send_message("123", "hi").await.expect("third attempt goes through");
+    assert_eq!(attempts.swap(0, Ordering::SeqCst), 3);
+
+    let sender = RateLimitedSender::new(flaky(1, "Error sending message: Unknown Channel"))
+        .with_rate(1000)
+        .with_retries(3, fast_retries);
+    assert!(sender.send_message("123", "hi").await.is_err());
+    assert_eq!(attempts.swap(0, Ordering::SeqCst), 1, "only 429s are retried");
+
+    let sender = RateLimitedSender::new(flaky(0, "")).with_rate(50);
+    let started = Instant::now();
+    for _ in 0..5 {
+        sender.send_message("123", "hi").await.unwrap();
+    }
+    assert!(started.elapsed() >= Duration::from_millis(80), "{:?}", started.elapsed());
+}
+
+struct SlowSender {
+    in_flight: std::sync::atomic::AtomicUsize,
+    most_in_flight: std::sync::atomic::AtomicUsize,
+}
+
+#[serenity::async_trait]
+impl MessageSender for SlowSender {
+    async fn send_message(&self, _channel_id: &str, _content: &str) -> Result<(), String> {
+        use std::sync::atomic::Ordering;
+        let now_in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.most_in_flight.fetch_max(now_in_flight, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn due_pings_go_out_side_by_side_up_to_the_sender_concurrency() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let mut db: HashMap<String, Notification> = HashMap::new();
+    for id in ["a", "b", "c", "d"] {
+        db.insert(
+            id.to_string(),
+            Notification {
+                id: id.to_string(),
+                content: format!("task {}", id),
+                notify: vec!["@u".to_string()],
+                notification_times: vec![now - chrono::Duration::minutes(1)],
+                channel: "123".to_string(),
+                completed_at: None,
+                event_time: None,
+                recurrence: None,
+                paused: false,
+                checklist: Vec::new(),
+                ack: None,
+                quiet_delayed: false,
+                targets: Vec::new(),
+                delivery_retry: None,
+            },
+        );
+    }
+    let sender = RateLimitedSender::new(SlowSender {
+        in_flight: AtomicUsize::new(0),
+        most_in_flight: AtomicUsize::new(0),
+    })
+    .with_rate(1000)
+    .with_concurrency(2);
+    let openai = FakeOpenAI {
+        response: Ok("Time for your task.".to_string()),
+    };
+
+    notification_tick(&mut db, &mut HashMap::new(), &HashMap::new(), &sender, &openai, now)
+        .await
+        .expect("tick should succeed");
+
+    assert!(db.is_empty());
+    assert_eq!(sender.inner().most_in_flight.load(Ordering::SeqCst), 2);
+}
+
+struct RecordingChannel {
+    kind: &'static str,
+    delivered: TokioMutex<Vec<(String, String)>>,
+    mirrored: TokioMutex<Vec<serde_json::Value>>,
+}
+
+#[serenity::async_trait]
+impl NotificationChannel for RecordingChannel {
+    fn handles(&self, target: &DeliveryTarget) -> bool {
+        target.kind() == self.kind
+    }
+
+    async fn deliver(&self, target: &DeliveryTarget, message: &OutgoingMessage<'_>) -> Result<(), String> {
+        self.delivered
+            .lock()
+            .await
+            .push((target.address().to_string(), message.content.to_string()));
+        Ok(())
+    }
+
+    async fn delivered(&self, message: &OutgoingMessage<'_>) {
+        self.mirrored.lock().await.push(webhook_body(message));
+    }
+}
+
+#[tokio::test]
+async fn pings_fan_out_to_every_target_of_a_reminder() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+    let log = DeliveryLog::new(temp_dir.join("deliveries.log"));
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let reminder = Notification {
+        id: "r1".to_string(),
+        content: "call mom".to_string(),
+        notify: vec!["@u".to_string()],
+        notification_times: vec![now - chrono::Duration::minutes(1), now + chrono::Duration::hours(1)],
+        channel: "123".to_string(),
+        completed_at: None,
+        event_time: None,
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: None,
+        quiet_delayed: false,
+        targets: vec![
+            DeliveryTarget::Email("me@example.com".to_string()),
+            DeliveryTarget::Webhook("https://example.com/hook".to_string()),
+        ],
+        delivery_retry: None,
+    };
+    let openai = FakeOpenAI {
+        response: Ok("Remember to call mom.".to_string()),
+    };
+    let email = Arc::new(RecordingChannel {
+        kind: "email",
+        delivered: TokioMutex::new(Vec::new()),
+        mirrored: TokioMutex::new(Vec::new()),
+    });
+    let channels: Vec<Arc<dyn NotificationChannel>> = vec![email.clone()];
+
+    let mut db: HashMap<String, Notification> = HashMap::from([("r1".to_string(), reminder.clone())]);
+    notification_tick_with_channels(
+        &mut db,
+        &mut HashMap::new(),
+        &HashMap::new(),
+        &FailingSender,
+        &channels,
+        &openai,
+        now,
+        None,
+        Some(&log),
+        &CatchUpPolicy::default(),
+    )
+    .await
+    .expect("the email got through, so the ping is done");
+    assert_eq!(db["r1"].notification_times.len(), 1);
+    assert_eq!(
+        *email.delivered.lock().await,
+        vec![("me@example.com".to_string(), "Remember to call mom.".to_string())]
+    );
+    {
+        // Every ping that went out is mirrored for sinks.
+        let mirrored = email.mirrored.lock().await;
+        assert_eq!(mirrored.len(), 1);
+        assert_eq!(mirrored[0]["user"], "@u");
+        assert_eq!(mirrored[0]["content"], "call mom");
+        assert_eq!(
+            mirrored[0]["time"],
+            serde_json::json!(now - chrono::Duration::minutes(1))
+        );
+    }
+
+    let (records, _) = log.read_from(0).unwrap();
+    let details: Vec<(DeliveryKind, Option<&str>)> = records
+        .iter()
+        .map(|record| (record.kind, record.detail.as_deref()))
+        .collect();
+    assert_eq!(
+        details[1..],
+        [
+            (DeliveryKind::Failed, Some("Missing Access")),
+            (
+                DeliveryKind::Failed,
+                Some("webhook https://example.com/hook: no webhook delivery configured")
+            ),
+            (DeliveryKind::Delivered, None),
+        ]
+    );
+
+    // With the email gone too, nothing got through and the ping stays due.
+    let mut db: HashMap<String, Notification> = HashMap::from([("r1".to_string(), reminder)]);
+    let failed = notification_tick_with_channels(
+        &mut db,
+        &mut HashMap::new(),
+        &HashMap::new(),
+        &FailingSender,
+        &[],
+        &openai,
+        now,
+        None,
+        None,
+        &CatchUpPolicy::default(),
+    )
+    .await;
+    assert_eq!(failed.unwrap_err(), "Missing Access");
+    assert_eq!(db["r1"].notification_times.len(), 2);
+}
+
+#[tokio::test]
+async fn users_with_push_set_up_get_every_ping_on_their_phone() {
+    use reminderBot::models::settings::UserSettings;
+
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let reminder = Notification {
+        id: "r1".to_string(),
+        content: "take out the trash".to_string(),
+        notify: vec!["@42".to_string(), "@43".to_string()],
+        notification_times: vec![now - chrono::Duration::minutes(1)],
+        channel: "123".to_string(),
+        completed_at: None,
+        event_time: None,
+        recurrence: None,
+        paused: false,
+        checklist: Vec::new(),
+        ack: None,
+        quiet_delayed: false,
+        targets: Vec::new(),
+        delivery_retry: None,
+    };
+    let mut settings: HashMap<String, UserSettings> = HashMap::from([(
+        "@43".to_string(),
+        UserSettings {
+            user_id: "@43".to_string(),
+            push: Some(DeliveryTarget::Ntfy("sam-reminders".to_string())),
+            ..Default::default()
+        },
+    )]);
+    let ntfy = Arc::new(RecordingChannel {
+        kind: "ntfy",
+        delivered: TokioMutex::new(Vec::new()),
+        mirrored: TokioMutex::new(Vec::new()),
+    });
+    let channels: Vec<Arc<dyn NotificationChannel>> = vec![ntfy.clone()];
+    let sender = MockSender::new();
+    let openai = FakeOpenAI {
+        response: Ok("Trash goes out today.".to_string()),
+    };
+
+    let mut db: HashMap<String, Notification> = HashMap::from([("r1".to_string(), reminder)]);
+    notification_tick_with_channels(
+        &mut db,
+        &mut settings,
+        &HashMap::new(),
+        &sender,
+        &channels,
+        &openai,
+        now,
+        None,
+        None,
+        &CatchUpPolicy::default(),
+    )
+    .await
+    .expect("tick should succeed");
+
+    assert_eq!(sender.sent.lock().await.len(), 1);
+    assert_eq!(
+        *ntfy.delivered.lock().await,
+        vec![("sam-reminders".to_string(), "Trash goes out today.".to_string())]
+    );
+}
@@ -0,0 +1,32 @@
+use std::sync::Mutex;
+
+use reminderBot::handlers::simulate::run_script;
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+#[tokio::test]
+async fn scripts_print_replies_and_queued_events() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let base = "./data/test_simulate";
+    std::fs::create_dir_all(base).unwrap();
+    unsafe {
+        std::env::set_var("DB_LOCATION", base);
+    }
+
+    let script = r#"# a reminder, a mention and a bad time zone
+{"kind": "notify", "user": "@1", "channel": "9", "text": "call mom tomorrow at 5"}
+{"kind": "mention", "user": "@2", "text": "<@1> buy milk"}
+{"kind": "timezone", "user": "@1", "zone": "Mars/Olympus"}
+"#;
+    let output = run_script(script).await.unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "line 2: @1 /notify call mom tomorrow at 5");
+    assert!(lines[1].starts_with("  decision: EmitNotify"), "{}", output);
+    assert!(output.contains("  event: notify_requested user=@1 channel=9"), "{}", output);
+    assert!(output.contains("line 3: @2 mentions: <@1> buy milk"), "{}", output);
+    assert!(output.contains("  event: todo_requested user=@2 channel=0"), "{}", output);
+    assert!(output.contains("line 4: @1 /settings timezone Mars/Olympus\n  reply: "), "{}", output);
+
+    let err = run_script("{\"kind\": \"dance\"}").await.unwrap_err();
+    assert!(err.starts_with("line 1:"), "{}", err);
+}
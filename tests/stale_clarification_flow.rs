@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::env;
+
+use chrono::TimeZone;
+use reminderBot::i18n::Locale;
+use reminderBot::models::settings::UserSettings;
+use reminderBot::models::todo::TodoItem;
+use reminderBot::service::notify_flow::{
+    route_notify, take_expired_clarifications, NotifyDecision,
+};
+use reminderBot::service::routing::{Intent, IntentResult, IntentRouter};
+use reminderBot::tasks::session_loop::stale_clarification_tick;
+use reminderBot::tasks::todo_loop::DmSender;
+use tokio::sync::Mutex;
+
+// Stands in for the OpenAI router when it can't tell what the user wants.
+struct UnsureRouter;
+
+#[serenity::async_trait]
+impl IntentRouter for UnsureRouter {
+    async fn route(&self, text: &str) -> IntentResult {
+        IntentResult {
+            intent: Intent::Unknown,
+            normalized_text: text.trim().to_string(),
+            confidence: 0.0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct MockDmSender {
+    sent: Mutex<Vec<(String, String)>>,
+}
+
+#[serenity::async_trait]
+impl DmSender for MockDmSender {
+    async fn send_dm(&self, user_id: &str, content: &str) -> Result<(), String> {
+        self.sent
+            .lock()
+            .await
+            .push((user_id.to_string(), content.to_string()));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn expired_clarification_becomes_todo_for_opted_in_users() {
+    let temp_dir = env::temp_dir().join(format!("notificationbot_it_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+
+    let now = chrono::Utc.with_ymd_and_hms(2026, 2, 2, 12, 0, 0).unwrap();
+    let router = UnsureRouter;
+    let mut sessions = HashMap::new();
+    for user in ["@42", "@43"] {
+        let decision = route_notify(
+            &router,
+            &mut sessions,
+            (user.to_string(), "c1".to_string()),
+            "call mom".to_string(),
+            Locale::En,
+            now,
+        )
+        .await;
+        assert!(matches!(decision, NotifyDecision::NeedClarification));
+    }
+
+    assert!(take_expired_clarifications(&mut sessions, now + chrono::Duration::minutes(1)).is_empty());
+    let expired = take_expired_clarifications(&mut sessions, now + chrono::Duration::minutes(6));
+    assert_eq!(expired.len(), 2);
+    assert!(sessions.is_empty());
+
+    let mut settings: HashMap<String, UserSettings> = HashMap::new();
+    settings.insert(
+        "@42".to_string(),
+        UserSettings {
+            user_id: "@42".to_string(),
+            stale_to_todo: true,
+            ..Default::default()
+        },
+    );
+    let mut todos: HashMap<String, TodoItem> = HashMap::new();
+    let sender = MockDmSender::default();
+    stale_clarification_tick(expired, &mut todos, &settings, &sender).await;
+
+    let items: Vec<&TodoItem> = todos.values().collect();
+    assert_eq!(items.len(), 1, "only the opted-in user gets a todo");
+    assert_eq!(items[0].user_id, "@42");
+    assert_eq!(items[0].content, "call mom");
+
+    let sent = sender.sent.lock().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0, "42");
+    assert!(sent[0].1.contains("'call mom' but never gave a time"));
+}
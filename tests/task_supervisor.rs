@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use reminderBot::handlers::http;
+use reminderBot::tasks::task_runner::{CronSchedule, TaskHealth, TaskRunner, TaskState, TaskStatus};
+
+async fn wait_for(health: &TaskHealth, done: impl Fn(&TaskStatus) -> bool) -> TaskStatus {
+    for _ in 0..200 {
+        if let Some(status) = health.snapshot().into_iter().find(|status| done(status)) {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("task never reached the expected state: {:?}", health.snapshot());
+}
+
+#[tokio::test]
+async fn a_crashed_loop_is_restarted_and_reported_on_healthz() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut runner = TaskRunner::new().with_backoff(Duration::from_millis(200), Duration::from_secs(1));
+    runner.add_task("flaky", {
+        let runs = runs.clone();
+        move || {
+            let run = runs.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if run == 0 {
+                    panic!("store went away");
+                }
+                std::future::pending::<()>().await;
+            }
+        }
+    });
+    runner.add_task("one_shot", || async {});
+    let health = runner.health();
+    let api = http::health_route(health.clone());
+    runner.start_all();
+
+    let crashed = wait_for(&health, |status| status.state == TaskState::Restarting).await;
+    assert_eq!(crashed.name, "flaky");
+    assert_eq!(crashed.last_error.as_deref(), Some("store went away"));
+    let reply = warp::test::request().path("/healthz").reply(&api).await;
+    assert_eq!(reply.status(), 503);
+
+    let restarted = wait_for(&health, |status| status.name == "flaky" && status.state == TaskState::Running).await;
+    assert_eq!(restarted.restarts, 1);
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+    let reply = warp::test::request().path("/healthz").reply(&api).await;
+    assert_eq!(reply.status(), 200);
+    let body: serde_json::Value = serde_json::from_slice(reply.body()).unwrap();
+    assert_eq!(body["status"], "ok");
+    let states: Vec<(&str, &str)> = body["tasks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| (task["name"].as_str().unwrap(), task["state"].as_str().unwrap()))
+        .collect();
+    // A task that returns on its own is finished, not restarted.
+    assert_eq!(states, vec![("flaky", "running"), ("one_shot", "finished")]);
+}
+
+#[tokio::test]
+async fn cron_tasks_run_at_each_scheduled_time() {
+    assert!(CronSchedule::parse("every morning", chrono_tz::UTC).is_err());
+    let every_second = CronSchedule::parse("* * * * * *", chrono_tz::UTC).unwrap();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut runner = TaskRunner::new();
+    runner.add_cron_task("ticker", move || Some(every_second.clone()), {
+        let runs = runs.clone();
+        move || {
+            let runs = runs.clone();
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+    });
+    let health = runner.health();
+    runner.start_all();
+
+    for _ in 0..60 {
+        if runs.load(Ordering::SeqCst) >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(runs.load(Ordering::SeqCst) >= 2);
+    // A job returning is not the task finishing; it waits for the next run.
+    let status = wait_for(&health, |status| status.name == "ticker").await;
+    assert_eq!((status.state, status.restarts), (TaskState::Running, 0));
+    assert!(status.last_run.is_some() && status.next_run.is_some());
+}
+
+#[tokio::test]
+async fn stopping_lets_a_running_job_finish_and_aborts_plain_loops() {
+    let every_second = CronSchedule::parse("* * * * * *", chrono_tz::UTC).unwrap();
+    let started = Arc::new(AtomicUsize::new(0));
+    let finished = Arc::new(AtomicUsize::new(0));
+    let mut runner = TaskRunner::new().with_stop_grace(Duration::from_secs(5));
+    runner.add_task("idle_loop", std::future::pending::<()>);
+    runner.add_cron_task("slow_job", move || Some(every_second.clone()), {
+        let started = started.clone();
+        let finished = finished.clone();
+        move || {
+            let started = started.clone();
+            let finished = finished.clone();
+            async move {
+                started.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                finished.fetch_add(1, Ordering::SeqCst);
+                Err("mailbox full".to_string())
+            }
+        }
+    });
+    let health = runner.health();
+    let tasks = runner.start_all();
+
+    for _ in 0..60 {
+        if started.load(Ordering::SeqCst) > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(started.load(Ordering::SeqCst), 1);
+    tasks.stop().await;
+
+    // The run in flight was not cut off, and no new one started.
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+    let statuses = health.snapshot();
+    assert!(statuses.iter().all(|status| status.state == TaskState::Stopped), "{:?}", statuses);
+    let job = statuses.iter().find(|status| status.name == "slow_job").unwrap();
+    assert!(job.last_run.is_some());
+    assert_eq!(job.next_run, None);
+    assert_eq!(job.last_error.as_deref(), Some("mailbox full"));
+}
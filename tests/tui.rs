@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{TimeZone, Utc};
+use ratatui::crossterm::event::KeyCode;
+use reminderBot::handlers::tui::{KeyOutcome, Mode, Panel, TuiApp};
+use reminderBot::service::openai_service::OpenAIClient;
+
+static ENV_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+
+struct FakeOpenAI {
+    response: String,
+}
+
+#[serenity::async_trait]
+impl OpenAIClient for FakeOpenAI {
+    async fn generate_prompt(
+        &self,
+        _prompt: &str,
+        _prompt_type: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.response.clone())
+    }
+}
+
+async fn type_text(app: &mut TuiApp, text: &str) -> KeyOutcome {
+    let now = Utc::now();
+    for c in text.chars() {
+        app.handle_key(KeyCode::Char(c), now).await;
+    }
+    app.handle_key(KeyCode::Enter, now).await
+}
+
+#[tokio::test]
+async fn tui_adds_completes_and_deletes_entries() {
+    let _guard = ENV_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
+    let temp_dir = env::temp_dir().join(format!("notificationbot_tui_{}", uuid::Uuid::new_v4()));
+    unsafe {
+        env::set_var("DB_LOCATION", &temp_dir);
+    }
+    let now = Utc.with_ymd_and_hms(2031, 3, 1, 12, 0, 0).unwrap();
+    let openai = FakeOpenAI {
+        response: r#"{"content":"dentist","time":"2031-03-07T20:00:00Z"}"#.to_string(),
+    };
+    let mut app = TuiApp::new(HashMap::new(), HashMap::new(), "@1", "123");
+
+    // A reminder goes through the extraction and waits for y.
+    app.handle_key(KeyCode::Char('a'), now).await;
+    let outcome = type_text(&mut app, "dentist friday at 3").await;
+    assert_eq!(outcome, KeyOutcome::Extract("dentist friday at 3".to_string()));
+    app.draft_reminder(&openai, "dentist friday at 3", now).await;
+    assert!(matches!(app.mode, Mode::Confirm(_)));
+    app.handle_key(KeyCode::Char('y'), now).await;
+    assert_eq!(app.reminders().len(), 1);
+    assert_eq!(app.reminders()[0].content, "dentist");
+
+    // Todos are added as typed.
+    app.handle_key(KeyCode::Tab, now).await;
+    assert_eq!(app.panel, Panel::Todos);
+    app.handle_key(KeyCode::Char('a'), now).await;
+    assert_eq!(type_text(&mut app, "buy milk").await, KeyOutcome::Continue);
+    app.handle_key(KeyCode::Char('a'), now).await;
+    type_text(&mut app, "call mom").await;
+    assert_eq!(app.open_todos().len(), 2);
+
+    let first = app.open_todos()[0].content.clone();
+    app.handle_key(KeyCode::Char('c'), now).await;
+    let open: Vec<&str> = app.open_todos().iter().map(|item| item.content.as_str()).collect();
+    assert_eq!(open.len(), 1);
+    assert_ne!(open[0], first);
+
+    app.handle_key(KeyCode::Tab, now).await;
+    app.handle_key(KeyCode::Char('d'), now).await;
+    assert!(app.reminders().is_empty());
+    assert_eq!(app.handle_key(KeyCode::Char('q'), now).await, KeyOutcome::Quit);
+}